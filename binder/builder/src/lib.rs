@@ -53,3 +53,8 @@ pub fn entrypoint() -> Result<()> {
 pub fn artifact_for_pass() -> Result<PathBuf> {
     pass::artifact()
 }
+
+/// Utility function for exposing the installed headers (e.g., `libra_assume.h`) to others
+pub fn artifact_for_include() -> Result<PathBuf> {
+    pass::include_dir()
+}
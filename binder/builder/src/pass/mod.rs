@@ -14,6 +14,7 @@ use crate::deps::llvm::ResolverLLVM;
 
 // path constants
 static SEGMENTS: [&str; 1] = ["oracle"];
+static HEADERS: [&str; 1] = ["libra_assume.h"];
 
 #[derive(StructOpt)]
 pub struct PassArgs {
@@ -60,7 +61,7 @@ impl PassArgs {
                     .ok_or_else(|| anyhow!("non-ascii path"))?
             ))
             .arg("-DCMAKE_BUILD_TYPE=Debug")
-            .arg(path_src);
+            .arg(&path_src);
         cmd.current_dir(&path_build);
         let status = cmd.status()?;
         if !status.success() {
@@ -75,6 +76,16 @@ impl PassArgs {
             return Err(anyhow!("Build failed"));
         }
 
+        // install the headers for C sources
+        let path_include = path_build.join("include");
+        fs::create_dir_all(&path_include)?;
+        for header in HEADERS {
+            fs::copy(
+                path_src.join("include").join(header),
+                path_include.join(header),
+            )?;
+        }
+
         // done
         Ok(())
     }
@@ -102,3 +113,12 @@ pub fn artifact() -> Result<PathBuf> {
     path_build.extend([config_hash.as_str(), "Libra", "libLibra.so"]);
     Ok(path_build)
 }
+
+/// Retrieve the directory of installed headers
+pub fn include_dir() -> Result<PathBuf> {
+    let (config_hash, _) = derive_deps()?;
+    let mut path_build = PATH_STUDIO.to_path_buf();
+    path_build.extend(SEGMENTS);
+    path_build.extend([config_hash.as_str(), "include"]);
+    Ok(path_build)
+}
@@ -0,0 +1,575 @@
+//! The affine-equality domain (Karr's domain), tracking `a1 * x1 + ... + an * xn + c = 0`.
+//!
+//! Equalities are over rational numbers and hence only hold for bitvectors in the absence of
+//! wrap-around, i.e., overflows are assumed to not happen (as signed overflows in C). Casts are
+//! not modeled as zero-extension may change the value of a negative operand.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use rug::{Integer, Rational};
+
+use crate::analysis::dataflow::{solve_forward, Filter};
+use crate::analysis::lattice::Lattice;
+use crate::analysis::variable::{signed_reading, Variable};
+use crate::error::EngineResult;
+use crate::ir::bridge::cfg::Block;
+use crate::ir::bridge::constant::{Constant, NumValue};
+use crate::ir::bridge::function::Function;
+use crate::ir::bridge::instruction::{BinaryOpArith, BinaryOpShift, ComparePredicate, Instruction};
+use crate::ir::bridge::typing::{NumRepr, Type};
+use crate::ir::bridge::value::{BlockLabel, Value};
+
+/// Utility: render a rational number, as an integer if it is one
+fn fmt_rational(value: &Rational) -> String {
+    if value.is_integer() {
+        value.numer().to_string()
+    } else {
+        format!("{}/{}", value.numer(), value.denom())
+    }
+}
+
+/// A linear combination of variables plus a constant, without zero coefficients
+#[derive(Eq, PartialEq, Clone, Default)]
+pub struct Linear {
+    coeffs: BTreeMap<Variable, Rational>,
+    constant: Rational,
+}
+
+impl Linear {
+    pub fn constant(value: Rational) -> Self {
+        Self {
+            coeffs: BTreeMap::new(),
+            constant: value,
+        }
+    }
+
+    pub fn variable(var: Variable) -> Self {
+        Self {
+            coeffs: BTreeMap::from([(var, Rational::from(1))]),
+            constant: Rational::new(),
+        }
+    }
+
+    pub fn coefficient(&self, var: &Variable) -> Option<&Rational> {
+        self.coeffs.get(var)
+    }
+
+    pub fn variables(&self) -> impl Iterator<Item = &Variable> {
+        self.coeffs.keys()
+    }
+
+    pub fn is_constant(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        let mut coeffs = self.coeffs.clone();
+        for (var, k) in &other.coeffs {
+            let sum = coeffs.remove(var).unwrap_or_default() + k;
+            if sum != 0 {
+                coeffs.insert(*var, sum);
+            }
+        }
+        Self {
+            coeffs,
+            constant: self.constant.clone() + &other.constant,
+        }
+    }
+
+    pub fn scale(&self, factor: &Rational) -> Self {
+        if *factor == 0 {
+            return Self::default();
+        }
+        Self {
+            coeffs: self
+                .coeffs
+                .iter()
+                .map(|(var, k)| (*var, k.clone() * factor))
+                .collect(),
+            constant: self.constant.clone() * factor,
+        }
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.scale(&Rational::from(-1)))
+    }
+
+    /// Replace the variable with the expression
+    fn substitute(&self, var: &Variable, expr: &Self) -> Self {
+        match self.coeffs.get(var) {
+            None => self.clone(),
+            Some(k) => {
+                let k = k.clone();
+                let mut rest = self.clone();
+                rest.coeffs.remove(var);
+                rest.add(&expr.scale(&k))
+            }
+        }
+    }
+
+    /// Value of the homogeneous part along a direction
+    fn apply(&self, direction: &BTreeMap<Variable, Rational>) -> Rational {
+        let mut sum = Rational::new();
+        for (var, k) in &self.coeffs {
+            if let Some(d) = direction.get(var) {
+                sum += k.clone() * d;
+            }
+        }
+        sum
+    }
+
+    /// Render `self = 0` as an equation with integer coefficients, variables on the left
+    pub fn render_equation(&self, name: &impl Fn(&Variable) -> String) -> String {
+        // clear the denominators
+        let mut scale = Integer::from(1);
+        for k in self.coeffs.values().chain(std::iter::once(&self.constant)) {
+            scale = scale.lcm(k.denom());
+        }
+        let scale = Rational::from(scale);
+
+        // the leading coefficient is kept positive
+        let sign = match self.coeffs.values().next() {
+            Some(k) if k.cmp0().is_lt() => Rational::from(-1),
+            _ => Rational::from(1),
+        };
+        let factor = scale * sign;
+
+        let mut lhs = String::new();
+        for (var, k) in &self.coeffs {
+            let k = k.clone() * &factor;
+            let (negative, magnitude) = (k.cmp0().is_lt(), k.abs());
+            if lhs.is_empty() {
+                if negative {
+                    lhs.push('-');
+                }
+            } else {
+                lhs.push_str(if negative { " - " } else { " + " });
+            }
+            if magnitude != 1 {
+                lhs.push_str(&format!("{}*", magnitude.numer()));
+            }
+            lhs.push_str(&name(var));
+        }
+        let rhs = -(self.constant.clone() * &factor);
+        format!("{} == {}", lhs, rhs.numer())
+    }
+}
+
+impl Display for Linear {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut terms: Vec<_> = self
+            .coeffs
+            .iter()
+            .map(|(var, k)| format!("{}*{}", fmt_rational(k), var))
+            .collect();
+        if self.constant != 0 || terms.is_empty() {
+            terms.push(fmt_rational(&self.constant));
+        }
+        write!(f, "{}", terms.join(" + "))
+    }
+}
+
+/// A conjunction of affine equalities, kept in reduced row-echelon form: each row defines its
+/// pivot (the smallest variable in the row) in terms of non-pivot variables greater than it
+#[derive(Eq, PartialEq, Clone)]
+pub enum Affine {
+    Bottom,
+    System(BTreeMap<Variable, Linear>),
+}
+
+impl Affine {
+    /// Build the canonical system from equalities of the form `expr = 0`
+    pub fn from_equalities(equalities: impl IntoIterator<Item = Linear>) -> Self {
+        let mut system = Self::top();
+        for eq in equalities {
+            system.add_equality(eq);
+        }
+        system
+    }
+
+    /// The rows in the form of `expr = 0`
+    pub fn equalities(&self) -> Vec<Linear> {
+        match self {
+            Self::Bottom => vec![Linear::constant(Rational::from(1))],
+            Self::System(rows) => rows
+                .iter()
+                .map(|(pivot, expr)| Linear::variable(*pivot).sub(expr))
+                .collect(),
+        }
+    }
+
+    /// Substitute all pivots in the expression
+    fn reduce(&self, expr: &Linear) -> Linear {
+        match self {
+            Self::Bottom => expr.clone(),
+            Self::System(rows) => rows
+                .iter()
+                .fold(expr.clone(), |acc, (pivot, def)| acc.substitute(pivot, def)),
+        }
+    }
+
+    /// Conjoin the equality `expr = 0`
+    pub fn add_equality(&mut self, expr: Linear) {
+        let reduced = self.reduce(&expr);
+        let rows = match self {
+            Self::Bottom => return,
+            Self::System(rows) => rows,
+        };
+        let (pivot, k) = match reduced.coeffs.iter().next() {
+            None => {
+                if reduced.constant != 0 {
+                    *self = Self::Bottom;
+                }
+                return;
+            }
+            Some((var, k)) => (*var, k.clone()),
+        };
+
+        // pivot = -(reduced - k * pivot) / k
+        let mut rest = reduced;
+        rest.coeffs.remove(&pivot);
+        let def = rest.scale(&-k.recip());
+        for row in rows.values_mut() {
+            *row = row.substitute(&pivot, &def);
+        }
+        rows.insert(pivot, def);
+    }
+
+    /// Project out a variable
+    pub fn forget(&mut self, var: &Variable) {
+        let rows = match self {
+            Self::Bottom => return,
+            Self::System(rows) => rows,
+        };
+        if rows.remove(var).is_some() {
+            // a pivot appears in no other row
+            return;
+        }
+
+        // eliminate the variable with one of the rows mentioning it
+        let pick = rows
+            .iter()
+            .find(|(_, def)| def.coefficient(var).is_some())
+            .map(|(pivot, _)| *pivot);
+        let pivot = match pick {
+            None => return,
+            Some(pivot) => pivot,
+        };
+        let def = rows.remove(&pivot).expect("row exists");
+        let eq = Linear::variable(pivot).sub(&def);
+        let k = eq.coefficient(var).expect("variable in row").clone();
+        let mut rest = eq;
+        rest.coeffs.remove(var);
+        let value = rest.scale(&-k.recip());
+
+        let remaining: Vec<_> = rows
+            .iter()
+            .map(|(p, d)| Linear::variable(*p).sub(d).substitute(var, &value))
+            .collect();
+        *self = Self::from_equalities(remaining);
+    }
+
+    /// Assign the variable, with `None` standing for an unknown value
+    pub fn assign(&mut self, var: &Variable, expr: Option<&Linear>) {
+        let expr = expr.map(|e| self.reduce(e));
+        self.forget(var);
+        if let Some(e) = expr {
+            if e.coefficient(var).is_none() {
+                self.add_equality(Linear::variable(*var).sub(&e));
+            }
+        }
+    }
+
+    /// Variables mentioned in the system
+    fn variables(&self) -> BTreeSet<Variable> {
+        match self {
+            Self::Bottom => BTreeSet::new(),
+            Self::System(rows) => rows
+                .iter()
+                .flat_map(|(pivot, def)| std::iter::once(pivot).chain(def.variables()))
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// A point and a basis of directions spanning the solutions over the variables
+    fn generators(
+        rows: &BTreeMap<Variable, Linear>,
+        universe: &BTreeSet<Variable>,
+    ) -> (
+        BTreeMap<Variable, Rational>,
+        Vec<BTreeMap<Variable, Rational>>,
+    ) {
+        let point = rows
+            .iter()
+            .map(|(pivot, def)| (*pivot, def.constant.clone()))
+            .collect();
+        let directions = universe
+            .iter()
+            .filter(|var| !rows.contains_key(var))
+            .map(|free| {
+                let mut dir = BTreeMap::from([(*free, Rational::from(1))]);
+                for (pivot, def) in rows {
+                    if let Some(k) = def.coefficient(free) {
+                        dir.insert(*pivot, k.clone());
+                    }
+                }
+                dir
+            })
+            .collect();
+        (point, directions)
+    }
+}
+
+impl Lattice for Affine {
+    fn bottom() -> Self {
+        Self::Bottom
+    }
+
+    fn top() -> Self {
+        Self::System(BTreeMap::new())
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Bottom, _) => true,
+            (_, Self::Bottom) => false,
+            (Self::System(_), Self::System(_)) => other
+                .equalities()
+                .iter()
+                .all(|eq| self.reduce(eq) == Linear::default()),
+        }
+    }
+
+    /// Affine hull of the union
+    fn join(&self, other: &Self) -> Self {
+        let (rows1, rows2) = match (self, other) {
+            (Self::Bottom, _) => return other.clone(),
+            (_, Self::Bottom) => return self.clone(),
+            (Self::System(r1), Self::System(r2)) => (r1, r2),
+        };
+        let mut universe = self.variables();
+        universe.extend(other.variables());
+        let (p1, _) = Self::generators(rows1, &universe);
+        let (p2, mut directions) = Self::generators(rows2, &universe);
+
+        // the hull is spanned by the point of one side, the directions of both sides, and the
+        // difference between the points
+        let mut delta = p2;
+        for (var, v) in &p1 {
+            let d = delta.remove(var).unwrap_or_default() - v;
+            delta.insert(*var, d);
+        }
+        directions.push(delta);
+
+        // keep the combinations of the equalities of one side that vanish along all directions
+        let mut basis = self.equalities();
+        for dir in directions {
+            let values: Vec<_> = basis.iter().map(|eq| eq.apply(&dir)).collect();
+            let pick = match values.iter().position(|v| *v != 0) {
+                None => continue,
+                Some(pick) => pick,
+            };
+            let base = basis[pick].clone();
+            let base_value = values[pick].clone();
+            basis = basis
+                .iter()
+                .zip(values)
+                .enumerate()
+                .filter(|(i, _)| *i != pick)
+                .map(|(_, (eq, v))| eq.sub(&base.scale(&(v / &base_value))))
+                .collect();
+        }
+        Self::from_equalities(basis)
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for eq in other.equalities() {
+            result.add_equality(eq);
+        }
+        result
+    }
+}
+
+impl Display for Affine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bottom => write!(f, "false"),
+            Self::System(rows) if rows.is_empty() => write!(f, "true"),
+            Self::System(rows) => {
+                let repr: Vec<_> = rows
+                    .iter()
+                    .map(|(pivot, def)| format!("{} = {}", pivot, def))
+                    .collect();
+                write!(f, "{}", repr.join(", "))
+            }
+        }
+    }
+}
+
+/// Utility: the linear form of an integer operand, if it has one
+fn linear_of(value: &Value) -> Option<Linear> {
+    match value {
+        Value::Constant(Constant::NumOne {
+            bits,
+            value: NumValue::Int(v),
+        }) => Some(Linear::constant(Rational::from(signed_reading(*bits, v)))),
+        Value::Constant(_) => None,
+        Value::Argument { index, ty } => matches!(
+            ty,
+            Type::Bitvec {
+                number: NumRepr::Int,
+                length: None,
+                ..
+            }
+        )
+        .then(|| Linear::variable(Variable::Argument(*index))),
+        Value::Register { index, ty } => matches!(
+            ty,
+            Type::Bitvec {
+                number: NumRepr::Int,
+                length: None,
+                ..
+            }
+        )
+        .then(|| Linear::variable(Variable::Register(*index))),
+    }
+}
+
+/// Transfer function of an instruction, yielding the linear form of the result if it has one
+fn transfer(inst: &Instruction) -> Option<Linear> {
+    let expr = match inst {
+        Instruction::BinaryArith {
+            bits: _,
+            number: NumRepr::Int,
+            length: None,
+            opcode,
+            nsw: _,
+            nuw: _,
+            lhs,
+            rhs,
+            result: _,
+        } => {
+            let (l, r) = (linear_of(lhs), linear_of(rhs));
+            match (opcode, l, r) {
+                (BinaryOpArith::Add, Some(l), Some(r)) => Some(l.add(&r)),
+                (BinaryOpArith::Sub, Some(l), Some(r)) => Some(l.sub(&r)),
+                (BinaryOpArith::Mul, Some(l), Some(r)) if r.is_constant() => {
+                    Some(l.scale(&r.constant))
+                }
+                (BinaryOpArith::Mul, Some(l), Some(r)) if l.is_constant() => {
+                    Some(r.scale(&l.constant))
+                }
+                _ => None,
+            }
+        }
+        Instruction::BinaryShift {
+            bits,
+            length: None,
+            opcode: BinaryOpShift::Shl,
+            lhs,
+            rhs,
+            result: _,
+        } => match (linear_of(lhs), linear_of(rhs)) {
+            (Some(l), Some(r)) if r.is_constant() && r.constant.is_integer() => {
+                match r.constant.numer().to_u32() {
+                    Some(n) if (n as usize) < *bits => {
+                        Some(l.scale(&Rational::from(Integer::from(1) << n)))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        },
+        _ => None,
+    };
+    expr
+}
+
+/// State after taking an edge, equalities of the comparisons that hold on it are assumed
+fn assume(filters: &[Filter], state: &Affine) -> Affine {
+    let mut state = state.clone();
+    for filter in filters {
+        if let Filter::Compare {
+            bits: _,
+            predicate: ComparePredicate::EQ,
+            lhs,
+            rhs,
+        } = filter
+        {
+            if let (Some(l), Some(r)) = (linear_of(lhs), linear_of(rhs)) {
+                state.add_equality(l.sub(&r));
+            }
+        }
+    }
+    state
+}
+
+/// Assign the phi nodes of a block on the edge from `src`, all at once, hence the options reading
+/// other phi nodes of the block are dropped
+fn assign_phis(block: &Block, src: &BlockLabel, state: &mut Affine) {
+    let phis: Vec<_> = block
+        .instructions()
+        .iter()
+        .map_while(|inst| match inst {
+            Instruction::Phi { options, result } => Some((options, *result)),
+            _ => None,
+        })
+        .collect();
+    let phi_vars: BTreeSet<_> = phis
+        .iter()
+        .map(|(_, result)| Variable::Register(*result))
+        .collect();
+    for (options, result) in &phis {
+        let expr = options
+            .get(src)
+            .and_then(linear_of)
+            .filter(|e| e.variables().all(|v| !phi_vars.contains(v)));
+        state.assign(&Variable::Register(*result), expr.as_ref());
+    }
+}
+
+/// Execute the non-phi instructions of a block
+fn exit_state(block: &Block, state: &Affine) -> Affine {
+    let mut state = state.clone();
+    for inst in block.instructions() {
+        if matches!(inst, Instruction::Phi { .. }) {
+            continue;
+        }
+        let result = match inst.result() {
+            None => continue,
+            Some(result) => result,
+        };
+        let expr = transfer(inst);
+        state.assign(&Variable::Register(result), expr.as_ref());
+    }
+    state
+}
+
+/// Affine equalities holding on entry to each reachable block of a defined function
+pub fn analyze_function(func: &Function) -> EngineResult<BTreeMap<BlockLabel, Affine>> {
+    let cfg = match &func.body {
+        None => return Ok(BTreeMap::new()),
+        Some(cfg) => cfg,
+    };
+    // the domain has a finite height, so the widening only joins
+    let states = solve_forward(
+        cfg,
+        Affine::top(),
+        |_, block, state| exit_state(block, state),
+        |src, dst, _, filters, state| {
+            let block = cfg
+                .get_block_by_label(dst)
+                .expect("block label points to a valid node");
+            let mut state = assume(filters, state);
+            assign_phis(block, src, &mut state);
+            state
+        },
+    );
+    Ok(states
+        .reachable()
+        .map(|label| (*label, states.at_entry(label)))
+        .collect())
+}
@@ -0,0 +1,420 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use rug::Integer;
+
+use crate::analysis::dataflow::{Exhaustion, Findings};
+use crate::analysis::interval::{eval_value, Bound, Interval};
+use crate::analysis::invariant::{analyze_intervals, Env, IntervalStates};
+use crate::analysis::lattice::Lattice;
+use crate::analysis::memory::AllocSite;
+use crate::analysis::pointer::heap_allocation;
+use crate::analysis::summary::SolverConfig;
+use crate::error::EngineResult;
+use crate::ir::bridge::cfg::ControlFlowGraph;
+use crate::ir::bridge::constant::{Constant, Expression, NumValue};
+use crate::ir::bridge::function::Function;
+use crate::ir::bridge::instruction::Instruction;
+use crate::ir::bridge::layout::{ByteOffset, DataLayout, SymbolicIndex};
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::typing::{NumRepr, Type};
+use crate::ir::bridge::value::{BlockLabel, RegisterSlot, Value};
+use crate::ir::defuse::Site;
+
+/// Certainty of an out-of-bounds access
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub enum OutOfBoundsKind {
+    /// the access is outside of the allocation on every execution reaching it
+    Definite,
+    /// the access may be outside of the allocation
+    Possible,
+}
+
+impl Display for OutOfBoundsKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Definite => write!(f, "definite"),
+            Self::Possible => write!(f, "possible"),
+        }
+    }
+}
+
+/// A load, store, or GEP that may reach past the allocation its pointer is based on
+pub struct OutOfBoundsAccess {
+    pub function: Identifier,
+    pub site: Site,
+    /// the instruction kind, i.e., load, store, or gep
+    pub access: &'static str,
+    /// number of bytes accessed, 0 for a GEP which may point one past the end
+    pub width: usize,
+    /// allocation the pointer is based on
+    pub object: AllocSite,
+    /// size of the allocation in bytes, a range for heap objects sized at run time
+    pub size: Interval,
+    /// byte offset of the pointer from the start of the allocation
+    pub offset: Interval,
+    pub kind: OutOfBoundsKind,
+}
+
+impl Display for OutOfBoundsAccess {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "@{}:{}: {} of {} bytes at offset {} of {} sized {} bytes ({})",
+            self.function,
+            self.site,
+            self.access,
+            self.width,
+            self.offset,
+            self.object,
+            self.size,
+            self.kind
+        )
+    }
+}
+
+/// A pointer into an allocation of bounded size
+#[derive(Clone)]
+pub(crate) struct Bounded {
+    pub(crate) object: AllocSite,
+    /// size of the allocation in bytes
+    pub(crate) size: Interval,
+    /// byte offset from the start of the allocation
+    pub(crate) offset: Interval,
+}
+
+impl Bounded {
+    /// The size of the allocation, if it is the same on every execution
+    pub(crate) fn exact_size(&self) -> Option<usize> {
+        self.size.as_singleton()?.to_usize()
+    }
+}
+
+/// How a pointer register is defined
+enum Definition<'a> {
+    /// the start of a fresh allocation of bounded size
+    Allocation(AllocSite, Interval),
+    /// another pointer moved by a byte offset
+    Derived(&'a Value, Interval),
+}
+
+/// Resolver of pointers in a function into allocations of bounded size
+pub(crate) struct Tracker<'a> {
+    module: &'a Module,
+    /// blocks reached by the interval analysis
+    reachable: BTreeSet<BlockLabel>,
+    /// the budget that ran out in the interval analysis
+    exhausted: Option<Exhaustion>,
+    definitions: BTreeMap<RegisterSlot, Definition<'a>>,
+    cache: BTreeMap<RegisterSlot, Option<Bounded>>,
+}
+
+impl<'a> Tracker<'a> {
+    /// Collect the pointer definitions, with GEP offsets in the interval state before them
+    pub(crate) fn build(
+        module: &'a Module,
+        func: &'a Function,
+        cfg: &'a ControlFlowGraph,
+        states: &IntervalStates,
+    ) -> EngineResult<Self> {
+        let layout = module.layout();
+        let mut tracker = Self {
+            module,
+            reachable: BTreeSet::new(),
+            exhausted: None,
+            definitions: BTreeMap::new(),
+            cache: BTreeMap::new(),
+        };
+        tracker.reachable = states.sites().map(|site| site.block()).collect();
+        tracker.exhausted = states.exhausted();
+        for (label, block) in cfg.blocks() {
+            for (index, inst) in block.instructions().iter().enumerate() {
+                let site = Site::Instruction {
+                    block: *label,
+                    index,
+                };
+                let env = match states.get(&site) {
+                    None => continue,
+                    Some(env) => env,
+                };
+                let definition = match inst {
+                    Instruction::Alloca {
+                        base_type,
+                        size,
+                        align: _,
+                        result,
+                    } => {
+                        let count = match size {
+                            None => Some(1),
+                            Some(value) => constant_count(value),
+                        };
+                        match count {
+                            None => None,
+                            Some(n) => layout.alloc_size(base_type)?.checked_mul(n).map(|bytes| {
+                                let object = AllocSite::Stack {
+                                    function: func.name.clone(),
+                                    site,
+                                };
+                                let size = Interval::singleton(Integer::from(bytes));
+                                (*result, Definition::Allocation(object, size))
+                            }),
+                        }
+                    }
+                    Instruction::CallDirect {
+                        function,
+                        args,
+                        result: Some((_, result)),
+                        ..
+                    } => heap_allocation(&func.name, site, function, args, env)
+                        // an allocation of unbounded size cannot be overflowed
+                        .filter(|object| {
+                            object
+                                .size
+                                .bounds()
+                                .is_some_and(|(_, hi)| matches!(hi, Bound::Finite(_)))
+                        })
+                        .map(|object| (*result, Definition::Allocation(object.site, object.size))),
+                    Instruction::GEP {
+                        pointer, result, ..
+                    } => {
+                        let offset = offset_interval(&layout.gep_offset(inst)?, env);
+                        Some((*result, Definition::Derived(pointer, offset)))
+                    }
+                    Instruction::GEPNop {
+                        pointer, result, ..
+                    }
+                    | Instruction::CastPtr {
+                        operand: pointer,
+                        result,
+                    } => Some((
+                        *result,
+                        Definition::Derived(pointer, Interval::singleton(Integer::new())),
+                    )),
+                    _ => None,
+                };
+                if let Some((slot, definition)) = definition {
+                    tracker.definitions.insert(slot, definition);
+                }
+            }
+        }
+        Ok(tracker)
+    }
+
+    /// Whether the block is reached by the interval analysis
+    pub(crate) fn is_reachable(&self, label: &BlockLabel) -> bool {
+        self.reachable.contains(label)
+    }
+
+    /// The budget that ran out in the interval analysis, if any
+    pub(crate) fn exhausted(&self) -> Option<Exhaustion> {
+        self.exhausted
+    }
+
+    /// The allocation a pointer is based on, with the offset into it, if known
+    pub(crate) fn pointer(&mut self, value: &Value) -> EngineResult<Option<Bounded>> {
+        match value {
+            Value::Register { index, ty: _ } => self.register(*index),
+            Value::Constant(constant) => self.constant(constant),
+            // no assumption on arguments
+            Value::Argument { .. } => Ok(None),
+        }
+    }
+
+    pub(crate) fn register(&mut self, reg: RegisterSlot) -> EngineResult<Option<Bounded>> {
+        if let Some(known) = self.cache.get(&reg) {
+            return Ok(known.clone());
+        }
+        // registers are in SSA form, so the chain of definitions is acyclic without phi nodes
+        let known = match self.definitions.get(&reg) {
+            None => None,
+            Some(Definition::Allocation(object, size)) => Some(Bounded {
+                object: object.clone(),
+                size: size.clone(),
+                offset: Interval::singleton(Integer::new()),
+            }),
+            Some(Definition::Derived(base, offset)) => {
+                let (base, offset) = (*base, offset.clone());
+                self.pointer(base)?.map(|mut known| {
+                    known.offset = known.offset.add(&offset);
+                    known
+                })
+            }
+        };
+        self.cache.insert(reg, known.clone());
+        Ok(known)
+    }
+
+    fn constant(&self, constant: &Constant) -> EngineResult<Option<Bounded>> {
+        let known = match constant {
+            Constant::Variable { name } => self.global(name)?,
+            Constant::Expr(expr) => match expr.as_ref() {
+                Expression::GEP { pointer, .. } | Expression::GEPNop { pointer, .. } => {
+                    match self.constant(pointer)? {
+                        None => None,
+                        Some(mut known) => {
+                            let offset = self.module.layout().gep_const_offset(expr)?;
+                            known.offset = known.offset.add(&offset_interval(&offset, &Env::top()));
+                            Some(known)
+                        }
+                    }
+                }
+                Expression::CastPtr { operand, .. } => self.constant(operand)?,
+                _ => None,
+            },
+            _ => None,
+        };
+        Ok(known)
+    }
+
+    /// Utility: the allocation of a global variable, if its size is settled in this module
+    fn global(&self, name: &Identifier) -> EngineResult<Option<Bounded>> {
+        let gvar = match self.module.get_global(name.as_ref()) {
+            None => return Ok(None),
+            Some(gvar) => gvar,
+        };
+        // a declaration or a weak definition may be resolved to a larger object at link time
+        if gvar.initializer.is_none() || gvar.is_weak {
+            return Ok(None);
+        }
+        Ok(Some(Bounded {
+            object: AllocSite::Global(gvar.name.clone()),
+            size: Interval::singleton(Integer::from(self.module.layout().alloc_size(&gvar.ty)?)),
+            offset: Interval::singleton(Integer::new()),
+        }))
+    }
+}
+
+/// Utility: the constant unsigned integer held in a value, if any
+fn constant_count(value: &Value) -> Option<usize> {
+    match value {
+        Value::Constant(Constant::NumOne {
+            bits: _,
+            value: NumValue::Int(n),
+        }) => n.to_usize(),
+        _ => None,
+    }
+}
+
+/// Utility: the interval of a byte offset, with symbolic indices looked up in the environment
+fn offset_interval(offset: &ByteOffset, env: &Env) -> Interval {
+    let mut total = Interval::singleton(offset.constant.clone());
+    for term in &offset.terms {
+        let index = match term.index {
+            SymbolicIndex::Value(
+                value @ (Value::Register { ty, .. } | Value::Argument { ty, .. }),
+            ) => match ty {
+                Type::Bitvec {
+                    bits,
+                    number: NumRepr::Int,
+                    length: None,
+                } => eval_value(value, *bits, &|v| env.get(v)),
+                _ => Interval::top(),
+            },
+            SymbolicIndex::Value(Value::Constant(_)) | SymbolicIndex::Constant(_) => {
+                Interval::top()
+            }
+        };
+        let scale = Interval::singleton(Integer::from(term.scale));
+        total = total.add(&index.mul(&scale));
+    }
+    total
+}
+
+/// Utility: the pointer accessed by an instruction and the number of bytes accessed
+pub(crate) fn accessed_pointer<'a>(
+    layout: &DataLayout,
+    inst: &'a Instruction,
+) -> EngineResult<Option<(&'static str, &'a Value, usize)>> {
+    let access = match inst {
+        Instruction::Load {
+            pointee_type,
+            pointer,
+            ..
+        } => Some(("load", pointer, layout.store_size(pointee_type)?)),
+        Instruction::Store {
+            pointee_type,
+            pointer,
+            ..
+        } => Some(("store", pointer, layout.store_size(pointee_type)?)),
+        _ => None,
+    };
+    Ok(access)
+}
+
+/// Check a pointer against the allocation it is based on, which is in bounds only if it is in
+/// bounds whatever the size of the allocation, and definitely out of bounds if it is out of
+/// bounds even for the largest size
+fn check(known: &Bounded, width: usize) -> Option<OutOfBoundsKind> {
+    let limit = known.size.sub(&Interval::singleton(Integer::from(width)));
+    let (least, most) = limit.bounds()?;
+    let valid = Interval::new(Bound::Finite(Integer::new()), least.clone());
+    if known.offset.leq(&valid) {
+        return None;
+    }
+    let feasible = Interval::new(Bound::Finite(Integer::new()), most.clone());
+    let kind = if known.offset.meet(&feasible).is_bottom() {
+        OutOfBoundsKind::Definite
+    } else {
+        OutOfBoundsKind::Possible
+    };
+    Some(kind)
+}
+
+/// Find the GEPs, loads, and stores that may index past the allocations of allocas, globals,
+/// and heap objects of bounded size
+pub fn check_out_of_bounds(
+    module: &Module,
+    solver: &SolverConfig,
+) -> EngineResult<Findings<OutOfBoundsAccess>> {
+    let layout = module.layout();
+    let intervals = analyze_intervals(module, solver);
+    let mut findings = Findings::new();
+    for (name, func) in module.functions() {
+        let (cfg, states) = match (&func.body, intervals.get(name)) {
+            (Some(cfg), Some(states)) => (cfg, states),
+            _ => continue,
+        };
+        let mut tracker = Tracker::build(module, func, cfg, states)?;
+        findings.record(name, tracker.exhausted());
+
+        // check each access against the allocation its pointer is based on
+        for (label, block) in cfg.blocks() {
+            if !tracker.is_reachable(label) {
+                continue;
+            }
+            for (index, inst) in block.instructions().iter().enumerate() {
+                let (access, known, width) = match inst {
+                    // the result of a GEP is checked, not its base
+                    Instruction::GEP { result, .. } => ("gep", tracker.register(*result)?, 0),
+                    _ => match accessed_pointer(layout, inst)? {
+                        None => continue,
+                        Some((access, pointer, width)) => {
+                            (access, tracker.pointer(pointer)?, width)
+                        }
+                    },
+                };
+                let known = match known {
+                    None => continue,
+                    Some(known) => known,
+                };
+                if let Some(kind) = check(&known, width) {
+                    findings.push(OutOfBoundsAccess {
+                        function: name.clone(),
+                        site: Site::Instruction {
+                            block: *label,
+                            index,
+                        },
+                        access,
+                        width,
+                        object: known.object,
+                        size: known.size,
+                        offset: known.offset,
+                        kind,
+                    });
+                }
+            }
+        }
+    }
+    Ok(findings)
+}
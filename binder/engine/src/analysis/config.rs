@@ -0,0 +1,332 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::analysis::dataflow::{Budget, Widening};
+use crate::analysis::diagnostics::{run_checkers_with, Checker, Diagnostic, Diagnostics};
+use crate::analysis::paths::PathBudget;
+use crate::analysis::summary::{CallStrings, SolverConfig};
+#[cfg(feature = "smt")]
+use crate::analysis::symbolic::confirm_findings;
+use crate::analysis::taint::TaintSpec;
+use crate::analysis::verify::Domain;
+use crate::error::{EngineError, EngineResult};
+use crate::ir::bridge::function::Function;
+use crate::ir::bridge::module::Module;
+
+/// Extrapolation at the heads of the cycles, see `Widening`
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct WideningConfig {
+    /// number of rounds at a head that join the states before widening them
+    pub delay: usize,
+    /// whether widening stops at the constants of the function before reaching infinity
+    pub thresholds: bool,
+    /// bound on the number of descending passes
+    pub narrowing: usize,
+}
+
+impl Default for WideningConfig {
+    fn default() -> Self {
+        let widening = Widening::default();
+        Self {
+            delay: widening.delay,
+            thresholds: widening.constants,
+            narrowing: widening.narrowing,
+        }
+    }
+}
+
+/// Limits on the analysis of each function, see `Budget`
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct BudgetConfig {
+    /// number of block updates before the function degrades to top
+    pub max_updates: Option<usize>,
+    /// wall time in milliseconds before the function degrades to top
+    pub timeout_ms: Option<u64>,
+}
+
+impl BudgetConfig {
+    pub fn budget(&self) -> Budget {
+        Budget {
+            updates: self.max_updates,
+            time: self.timeout_ms.map(Duration::from_millis),
+        }
+    }
+}
+
+/// Context sensitivity of the interprocedural analyses, see `CallStrings`
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ContextConfig {
+    /// whether the functions are analyzed per call string from the entry points, instead of
+    /// summarized bottom-up independently of their callers
+    pub enabled: bool,
+    /// number of call sites kept in a context
+    pub k: usize,
+    /// number of contexts per function
+    pub max_contexts: usize,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        let config = CallStrings::default();
+        Self {
+            enabled: false,
+            k: config.k,
+            max_contexts: config.max_contexts,
+        }
+    }
+}
+
+impl ContextConfig {
+    pub fn call_strings(&self) -> Option<CallStrings> {
+        self.enabled.then_some(CallStrings {
+            k: self.k,
+            max_contexts: self.max_contexts,
+        })
+    }
+}
+
+/// Bounded symbolic execution confirming the findings before they are reported, see
+/// `PathBudget`
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ConfirmConfig {
+    /// whether the findings are confirmed, which needs the engine built with the `smt` feature
+    pub enabled: bool,
+    /// maximum number of complete paths per function
+    pub max_paths: Option<usize>,
+    /// maximum number of blocks along a single path
+    pub max_depth: Option<usize>,
+    /// maximum number of times a block is revisited along a single path
+    pub max_unroll: Option<usize>,
+}
+
+impl ConfirmConfig {
+    pub fn budget(&self) -> PathBudget {
+        let budget = PathBudget::default();
+        PathBudget {
+            max_paths: self.max_paths.unwrap_or(budget.max_paths),
+            max_depth: self.max_depth.unwrap_or(budget.max_depth),
+            max_unroll: self.max_unroll.unwrap_or(budget.max_unroll),
+            ..budget
+        }
+    }
+}
+
+/// Diagnostics to drop, matching a rule, a function, or both
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Suppression {
+    /// rule id, any rule if absent
+    pub rule: Option<String>,
+    /// function name, any function if absent
+    pub function: Option<String>,
+}
+
+impl Suppression {
+    pub fn matches(&self, diagnostic: &Diagnostic) -> bool {
+        self.rule
+            .as_ref()
+            .is_none_or(|rule| rule == diagnostic.checker.rule())
+            && self
+                .function
+                .as_ref()
+                .is_none_or(|name| name == diagnostic.function.as_ref())
+    }
+}
+
+/// Settings of an analysis run, shared by the library API and the command line
+///
+/// Every field may be omitted in the TOML file, e.g.,
+///
+/// ```toml
+/// checkers = ["out-of-bounds", "heap-misuse"]
+/// domains = ["interval"]
+/// entry-points = ["parse"]
+/// powerset-cap = 4
+///
+/// [widening]
+/// delay = 2
+/// thresholds = true
+///
+/// [budget]
+/// max-updates = 100000
+/// timeout-ms = 5000
+///
+/// [context]
+/// enabled = true
+/// k = 2
+///
+/// [confirm]
+/// enabled = true
+/// max-unroll = 4
+///
+/// [[suppress]]
+/// rule = "integer-overflow"
+/// function = "hash"
+/// ```
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct AnalysisConfig {
+    /// rule ids of the checkers to run, all of them if empty
+    pub checkers: Vec<String>,
+    /// domains discharging the assertions, in the order they are tried
+    pub domains: Vec<Domain>,
+    pub widening: WideningConfig,
+    pub budget: BudgetConfig,
+    pub context: ContextConfig,
+    pub confirm: ConfirmConfig,
+    /// functions where executions start in the call-string mode, the default ones of the
+    /// module if empty
+    pub entry_points: Vec<String>,
+    /// number of contexts of a function whose states are kept apart at its sites, see
+    /// `SolverConfig`
+    pub powerset_cap: usize,
+    /// sources, sinks, and sanitizers of the taint checker, which only runs with a spec
+    pub taint_spec: Option<PathBuf>,
+    pub suppress: Vec<Suppression>,
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        Self {
+            checkers: vec![],
+            domains: Domain::ALL.to_vec(),
+            widening: WideningConfig::default(),
+            budget: BudgetConfig::default(),
+            context: ContextConfig::default(),
+            confirm: ConfirmConfig::default(),
+            entry_points: vec![],
+            powerset_cap: SolverConfig::default().powerset_cap,
+            taint_spec: None,
+            suppress: vec![],
+        }
+    }
+}
+
+impl AnalysisConfig {
+    /// Load the configuration from a TOML file, where a relative taint spec is resolved
+    /// against the directory of the file
+    pub fn load(path: &Path) -> EngineResult<Self> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            EngineError::InvalidAssumption(format!(
+                "unable to read analysis config {}: {}",
+                path.to_string_lossy(),
+                e
+            ))
+        })?;
+        let mut config: Self = toml::from_str(&content).map_err(|e| {
+            EngineError::InvalidAssumption(format!(
+                "malformed analysis config {}: {}",
+                path.to_string_lossy(),
+                e
+            ))
+        })?;
+        if let (Some(spec), Some(base)) = (&config.taint_spec, path.parent()) {
+            config.taint_spec = Some(base.join(spec));
+        }
+        Ok(config)
+    }
+
+    /// The widening of the solvers, bounded by the budget
+    pub fn widening(&self) -> Widening {
+        Widening {
+            delay: self.widening.delay,
+            constants: self.widening.thresholds,
+            narrowing: self.widening.narrowing,
+            budget: self.budget.budget(),
+            ..Widening::default()
+        }
+    }
+
+    /// The configuration of the solvers on the module, with the entry points validated
+    pub fn solver(&self, module: &Module) -> EngineResult<SolverConfig> {
+        let entry_points = if self.entry_points.is_empty() {
+            vec![]
+        } else {
+            self.entry_points(module)?
+                .into_iter()
+                .map(|func| func.name.clone())
+                .collect()
+        };
+        Ok(SolverConfig {
+            widening: self.widening(),
+            call_strings: self.context.call_strings(),
+            entry_points,
+            powerset_cap: self.powerset_cap,
+        })
+    }
+
+    /// The checkers selected, with the rule ids validated
+    pub fn checkers(&self) -> EngineResult<Vec<Checker>> {
+        if self.checkers.is_empty() {
+            return Ok(Checker::ALL.to_vec());
+        }
+        self.checkers
+            .iter()
+            .map(|rule| {
+                Checker::from_rule(rule).ok_or_else(|| {
+                    EngineError::InvalidAssumption(format!("unknown checker rule: {}", rule))
+                })
+            })
+            .collect()
+    }
+
+    /// The functions where executions start
+    pub fn entry_points<'a>(&self, module: &'a Module) -> EngineResult<Vec<&'a Function>> {
+        if self.entry_points.is_empty() {
+            return Ok(module.entry_points());
+        }
+        self.entry_points
+            .iter()
+            .map(|name| {
+                module
+                    .get_function(name)
+                    .filter(|func| func.body.is_some())
+                    .ok_or_else(|| {
+                        EngineError::InvalidAssumption(format!(
+                            "entry point is not a defined function: {}",
+                            name
+                        ))
+                    })
+            })
+            .collect()
+    }
+
+    /// Whether a diagnostic is suppressed
+    pub fn is_suppressed(&self, diagnostic: &Diagnostic) -> bool {
+        self.suppress.iter().any(|item| item.matches(diagnostic))
+    }
+
+    /// Run the selected checkers on the module, with the suppressed diagnostics dropped and the
+    /// others confirmed if asked for
+    pub fn run(&self, module: &Module) -> EngineResult<Diagnostics> {
+        let spec = self
+            .taint_spec
+            .as_deref()
+            .map(TaintSpec::load)
+            .transpose()?;
+        let mut diagnostics = run_checkers_with(
+            module,
+            &self.checkers()?,
+            &self.domains,
+            &self.solver(module)?,
+            spec.as_ref(),
+        )?;
+        if self.confirm.enabled {
+            #[cfg(feature = "smt")]
+            confirm_findings(module, &mut diagnostics, &self.confirm.budget())?;
+            #[cfg(not(feature = "smt"))]
+            return Err(EngineError::InvalidAssumption(
+                "confirming findings needs the engine built with the smt feature".into(),
+            ));
+        }
+        diagnostics.retain(|item| !self.is_suppressed(item));
+        Ok(diagnostics)
+    }
+}
@@ -0,0 +1,491 @@
+//! The congruence domain, tracking `x = r (mod m)` of bitvectors.
+//!
+//! Values are read the same way as in the interval domain. Wrap-around of a `n`-bit operation
+//! changes a value by a multiple of `2^n`, hence results only keep the congruence modulo the
+//! greatest common divisor of `m` and `2^n`, which makes the domain particularly suited for
+//! alignment and strides, i.e., powers of two.
+
+use std::fmt::{Display, Formatter};
+
+use rug::Integer;
+
+use crate::analysis::interval::{self, Bound, Interval};
+use crate::analysis::lattice::Lattice;
+use crate::analysis::product::{ReducedProduct, Reduction};
+use crate::analysis::variable::{signed_reading, Variable};
+use crate::ir::bridge::constant::{Constant, NumValue};
+use crate::ir::bridge::instruction::{
+    BinaryOpArith, BinaryOpBitwise, BinaryOpShift, ComparePredicate, Instruction,
+};
+use crate::ir::bridge::typing::{NumRepr, Type};
+use crate::ir::bridge::value::{RegisterSlot, Value};
+
+/// A set of integers sharing the remainder by a modulus
+#[derive(Eq, PartialEq, Clone)]
+pub enum Congruence {
+    Empty,
+    /// a modulus of zero denotes the constant `residue`, and a modulus of one any integer
+    Class {
+        modulus: Integer,
+        residue: Integer,
+    },
+}
+
+impl Congruence {
+    pub fn new(modulus: Integer, residue: Integer) -> Self {
+        let modulus = modulus.abs();
+        let residue = if modulus == 0 {
+            residue
+        } else {
+            residue.modulo(&modulus)
+        };
+        Self::Class { modulus, residue }
+    }
+
+    pub fn singleton(value: Integer) -> Self {
+        Self::new(Integer::new(), value)
+    }
+
+    /// The congruence of a constant bitvector, given its unsigned value
+    pub fn constant(bits: usize, value: &Integer) -> Self {
+        Self::singleton(signed_reading(bits, value))
+    }
+
+    /// Multiples of the modulus
+    pub fn multiple_of(modulus: Integer) -> Self {
+        Self::new(modulus, Integer::new())
+    }
+
+    pub fn parts(&self) -> Option<(&Integer, &Integer)> {
+        match self {
+            Self::Empty => None,
+            Self::Class { modulus, residue } => Some((modulus, residue)),
+        }
+    }
+
+    /// The only value in the class, if it is a constant
+    pub fn as_singleton(&self) -> Option<&Integer> {
+        match self.parts()? {
+            (m, r) if *m == 0 => Some(r),
+            _ => None,
+        }
+    }
+
+    pub fn contains(&self, value: &Integer) -> bool {
+        match self.parts() {
+            None => false,
+            Some((m, r)) if *m == 0 => value == r,
+            Some((m, r)) => Integer::from(value - r).is_divisible(m),
+        }
+    }
+
+    /// Keep only what survives the wrap-around of a bitvector of the width
+    pub fn fit(self, bits: usize) -> Self {
+        let (m, r) = match self.parts() {
+            None => return Self::Empty,
+            Some(parts) => parts,
+        };
+        let span = Integer::from(1) << bits as u32;
+        if *m == 0 {
+            // a constant wraps into the (signed) range
+            let wrapped = r.clone().modulo(&span);
+            return Self::singleton(signed_reading(bits, &wrapped));
+        }
+        Self::new(m.clone().gcd(&span), r.clone())
+    }
+
+    fn lift2(
+        &self,
+        other: &Self,
+        op: impl Fn(&Integer, &Integer, &Integer, &Integer) -> Self,
+    ) -> Self {
+        match (self.parts(), other.parts()) {
+            (Some((m1, r1)), Some((m2, r2))) => op(m1, r1, m2, r2),
+            _ => Self::Empty,
+        }
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        self.lift2(other, |m1, r1, m2, r2| {
+            Self::new(m1.clone().gcd(m2), Integer::from(r1 + r2))
+        })
+    }
+
+    pub fn neg(&self) -> Self {
+        match self.parts() {
+            None => Self::Empty,
+            Some((m, r)) => Self::new(m.clone(), Integer::from(-r)),
+        }
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        self.lift2(other, |m1, r1, m2, r2| {
+            // (r1 + m1 * i) * (r2 + m2 * j) = r1 * r2 + m1 * r2 * i + m2 * r1 * j + m1 * m2 * i * j
+            let modulus = Integer::from(m1 * m2)
+                .gcd(&Integer::from(m1 * r2))
+                .gcd(&Integer::from(m2 * r1));
+            Self::new(modulus, Integer::from(r1 * r2))
+        })
+    }
+
+    /// Whether the two classes share no value
+    fn is_disjoint(&self, other: &Self) -> bool {
+        self.meet(other).is_bottom()
+    }
+}
+
+impl Lattice for Congruence {
+    fn bottom() -> Self {
+        Self::Empty
+    }
+
+    fn top() -> Self {
+        Self::new(Integer::from(1), Integer::new())
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        match (self.parts(), other.parts()) {
+            (None, _) => true,
+            (_, None) => false,
+            (Some((m1, r1)), Some((m2, _))) => m1.is_divisible(m2) && other.contains(r1),
+        }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (self.parts(), other.parts()) {
+            (None, _) => other.clone(),
+            (_, None) => self.clone(),
+            (Some((m1, r1)), Some((m2, r2))) => {
+                let modulus = m1.clone().gcd(m2).gcd(&Integer::from(r1 - r2).abs());
+                Self::new(modulus, r1.clone())
+            }
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        let ((m1, r1), (m2, r2)) = match (self.parts(), other.parts()) {
+            (None, _) | (_, None) => return Self::Empty,
+            (Some(a), Some(b)) => (a, b),
+        };
+        if *m1 == 0 {
+            return if other.contains(r1) {
+                self.clone()
+            } else {
+                Self::Empty
+            };
+        }
+        if *m2 == 0 {
+            return other.meet(self);
+        }
+
+        // chinese remainder theorem: x = r1 + m1 * k where m1 * k = r2 - r1 (mod m2)
+        let g = m1.clone().gcd(m2);
+        let delta = Integer::from(r2 - r1);
+        if !delta.is_divisible(&g) {
+            return Self::Empty;
+        }
+        let m2_g = Integer::from(m2 / &g);
+        let k = if m2_g == 1 {
+            Integer::new()
+        } else {
+            let inverse = Integer::from(m1 / &g)
+                .invert(&m2_g)
+                .expect("cofactors are coprime");
+            (delta / &g * inverse).modulo(&m2_g)
+        };
+        Self::new(m1.clone().lcm(m2), r1 + Integer::from(m1 * &k))
+    }
+}
+
+impl Display for Congruence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.parts() {
+            None => write!(f, "empty"),
+            Some((m, r)) if *m == 0 => write!(f, "{{{}}}", r),
+            Some((m, _)) if *m == 1 => write!(f, "any"),
+            Some((m, r)) => write!(f, "{} mod {}", r, m),
+        }
+    }
+}
+
+/// Utility: whether the value is `2^k - 1` or `-2^k` (i.e., a low or high bit mask), with `k`
+fn bit_mask(value: &Integer) -> Option<(bool, u32)> {
+    let low = Integer::from(value + 1);
+    if low.cmp0().is_gt() && low.is_power_of_two() {
+        return Some((true, low.significant_bits() - 1));
+    }
+    let high = Integer::from(-value);
+    if high.cmp0().is_gt() && high.is_power_of_two() {
+        return Some((false, high.significant_bits() - 1));
+    }
+    None
+}
+
+/// Tighten an interval and a congruence against each other
+pub fn reduce(interval: &Interval, congruence: &Congruence) -> (Interval, Congruence) {
+    let ((lo, hi), (m, r)) = match (interval.bounds(), congruence.parts()) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return (Interval::Empty, Congruence::Empty),
+    };
+    if *m == 0 {
+        let tightened = interval.meet(&Interval::singleton(r.clone()));
+        return if tightened.is_bottom() {
+            (Interval::Empty, Congruence::Empty)
+        } else {
+            (tightened, congruence.clone())
+        };
+    }
+
+    // move the finite bounds inwards to the closest members of the class
+    let lo = match lo {
+        Bound::Finite(v) => {
+            let gap = Integer::from(r - v).modulo(m);
+            Bound::Finite(v.clone() + gap)
+        }
+        _ => lo.clone(),
+    };
+    let hi = match hi {
+        Bound::Finite(v) => {
+            let gap = Integer::from(v - r).modulo(m);
+            Bound::Finite(v.clone() - gap)
+        }
+        _ => hi.clone(),
+    };
+    let interval = Interval::new(lo, hi);
+    match interval.as_singleton() {
+        _ if interval.is_bottom() => (Interval::Empty, Congruence::Empty),
+        Some(v) => {
+            let congruence = Congruence::singleton(v.clone());
+            (interval, congruence)
+        }
+        None => (interval, congruence.clone()),
+    }
+}
+
+/// Evaluate an operand, with registers looked up in the environment
+pub fn eval_value(
+    value: &Value,
+    bits: usize,
+    env: &impl Fn(&RegisterSlot) -> Congruence,
+) -> Congruence {
+    match value {
+        Value::Constant(Constant::NumOne {
+            bits: _,
+            value: NumValue::Int(v),
+        }) => Congruence::constant(bits, v),
+        Value::Constant(_) | Value::Argument { .. } => Congruence::top(),
+        Value::Register { index, ty: _ } => env(index),
+    }
+}
+
+/// Transfer function of the scalar integer instructions, yielding the congruence of the result
+pub fn transfer(
+    inst: &Instruction,
+    env: &impl Fn(&RegisterSlot) -> Congruence,
+) -> Option<(RegisterSlot, Congruence)> {
+    let item = match inst {
+        Instruction::BinaryArith {
+            bits,
+            number: NumRepr::Int,
+            length: None,
+            opcode,
+            nsw: _,
+            nuw: _,
+            lhs,
+            rhs,
+            result,
+        } => {
+            let l = eval_value(lhs, *bits, env);
+            let r = eval_value(rhs, *bits, env);
+            let v = match opcode {
+                BinaryOpArith::Add => l.add(&r),
+                BinaryOpArith::Sub => l.sub(&r),
+                BinaryOpArith::Mul => l.mul(&r),
+                // only constants are folded, when signed and unsigned readings agree
+                BinaryOpArith::Div | BinaryOpArith::Mod => {
+                    match (l.as_singleton(), r.as_singleton()) {
+                        (Some(a), Some(b)) if a.cmp0().is_ge() && b.cmp0().is_gt() => {
+                            let (a, b) = (a.clone(), b.clone());
+                            Congruence::singleton(if matches!(opcode, BinaryOpArith::Div) {
+                                a / b
+                            } else {
+                                a % b
+                            })
+                        }
+                        _ => Congruence::top(),
+                    }
+                }
+            };
+            (*result, v.fit(*bits))
+        }
+        Instruction::BinaryBitwise {
+            bits,
+            length: None,
+            opcode,
+            lhs,
+            rhs,
+            result,
+        } => {
+            let l = eval_value(lhs, *bits, env);
+            let r = eval_value(rhs, *bits, env);
+            let v = match (opcode, l.as_singleton(), r.as_singleton()) {
+                (_, Some(a), Some(b)) => {
+                    let (a, b) = (a.clone(), b.clone());
+                    Congruence::singleton(match opcode {
+                        BinaryOpBitwise::And => a & b,
+                        BinaryOpBitwise::Or => a | b,
+                        BinaryOpBitwise::Xor => a ^ b,
+                    })
+                }
+                (BinaryOpBitwise::And, None, Some(mask))
+                | (BinaryOpBitwise::And, Some(mask), None) => {
+                    let other = if l.as_singleton().is_some() { &r } else { &l };
+                    match (bit_mask(mask), other.parts()) {
+                        // the low bits are determined by a class modulo a multiple of 2^k
+                        (Some((true, k)), Some((m, res))) if *m != 0 && m.is_divisible_2pow(k) => {
+                            Congruence::singleton(res.clone().keep_bits(k))
+                        }
+                        // clearing the low bits aligns the value
+                        (Some((false, k)), Some(_)) => {
+                            Congruence::multiple_of(Integer::from(1) << k)
+                        }
+                        _ => Congruence::top(),
+                    }
+                }
+                _ => Congruence::top(),
+            };
+            (*result, v.fit(*bits))
+        }
+        Instruction::BinaryShift {
+            bits,
+            length: None,
+            opcode: BinaryOpShift::Shl,
+            lhs,
+            rhs,
+            result,
+        } => {
+            let l = eval_value(lhs, *bits, env);
+            let r = eval_value(rhs, *bits, env);
+            let v = match r.as_singleton().and_then(|v| v.to_u32()) {
+                Some(n) if (n as usize) < *bits => {
+                    l.mul(&Congruence::singleton(Integer::from(1) << n))
+                }
+                _ => Congruence::top(),
+            };
+            (*result, v.fit(*bits))
+        }
+        Instruction::CompareBitvec {
+            bits,
+            number: NumRepr::Int,
+            length: None,
+            predicate,
+            lhs,
+            rhs,
+            result,
+        } => {
+            let l = eval_value(lhs, *bits, env);
+            let r = eval_value(rhs, *bits, env);
+            let outcome = match (predicate, l.as_singleton(), r.as_singleton()) {
+                (ComparePredicate::EQ, Some(a), Some(b)) => Some(a == b),
+                (ComparePredicate::NE, Some(a), Some(b)) => Some(a != b),
+                (ComparePredicate::EQ, _, _) if l.is_disjoint(&r) => Some(false),
+                (ComparePredicate::NE, _, _) if l.is_disjoint(&r) => Some(true),
+                _ => None,
+            };
+            let v = match outcome {
+                None => Congruence::top(),
+                Some(b) => Congruence::singleton(Integer::from(b as u8)),
+            };
+            (*result, v)
+        }
+        Instruction::CastBitvecSize {
+            bits_from,
+            bits_into,
+            number: NumRepr::Int,
+            length: None,
+            operand,
+            result,
+        } => {
+            let v = eval_value(operand, *bits_from, env);
+            let v = if bits_into < bits_from {
+                v.fit(*bits_into)
+            } else if *bits_from == 1 {
+                // zero-extension yields 0 or 1, sign-extension 0 or -1
+                match v.as_singleton() {
+                    Some(b) if *b == 0 => v,
+                    _ => Congruence::top(),
+                }
+            } else {
+                // a zero-extended negative value gains 2^n
+                match v.as_singleton() {
+                    Some(c) if c.cmp0().is_ge() => v,
+                    _ => v.fit(*bits_from),
+                }
+            };
+            (*result, v)
+        }
+        Instruction::ITEOne {
+            cond,
+            then_value,
+            else_value,
+            result,
+        } => {
+            let bits = match then_value {
+                Value::Constant(Constant::NumOne { bits, .. }) => *bits,
+                Value::Register { ty, .. } | Value::Argument { ty, .. } => match ty {
+                    Type::Bitvec {
+                        bits,
+                        number: NumRepr::Int,
+                        length: None,
+                    } => *bits,
+                    _ => return None,
+                },
+                Value::Constant(_) => return None,
+            };
+            let t = eval_value(then_value, bits, env);
+            let e = eval_value(else_value, bits, env);
+            let v = match eval_value(cond, 1, env).as_singleton() {
+                Some(c) if *c == 0 => e,
+                Some(_) => t,
+                None => t.join(&e),
+            };
+            (*result, v)
+        }
+        _ => return None,
+    };
+    Some(item)
+}
+
+/// The reduction between the interval and congruence domains
+pub struct IntervalCongruenceReduction;
+
+impl Reduction<Interval, Congruence> for IntervalCongruenceReduction {
+    fn reduce(interval: &Interval, congruence: &Congruence) -> (Interval, Congruence) {
+        reduce(interval, congruence)
+    }
+}
+
+/// The reduced product of the interval and congruence domains
+pub type IntervalCongruence = ReducedProduct<Interval, Congruence, IntervalCongruenceReduction>;
+
+impl IntervalCongruence {
+    /// Transfer function of the product: both components, then the reduction
+    pub fn transfer(
+        inst: &Instruction,
+        env: &impl Fn(&RegisterSlot) -> Self,
+    ) -> Option<(RegisterSlot, Self)> {
+        let (slot, range) = interval::transfer(inst, &|var| match var {
+            Variable::Register(r) => env(r).first,
+            Variable::Argument(_) | Variable::Return => Interval::top(),
+        })?;
+        let congruence = match transfer(inst, &|r| env(r).second) {
+            None => Congruence::top(),
+            Some((_, v)) => v,
+        };
+        Some((slot, Self::new(range, congruence)))
+    }
+}
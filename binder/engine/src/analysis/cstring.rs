@@ -0,0 +1,799 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use rug::Integer;
+
+use crate::analysis::bounds::{accessed_pointer, Bounded, OutOfBoundsKind, Tracker};
+use crate::analysis::dataflow::{solve_forward_with, Exhaustion, Findings, Store, Widening};
+use crate::analysis::interval::{eval_value, Bound, Interval};
+use crate::analysis::invariant::{analyze_intervals, IntervalStates};
+use crate::analysis::lattice::Lattice;
+use crate::analysis::memory::AllocSite;
+use crate::analysis::pointsto::{analyze_points_to, AliasQuery, PointsTo};
+use crate::analysis::summary::SolverConfig;
+use crate::analysis::variable::int_bits;
+use crate::error::EngineResult;
+use crate::ir::bridge::cfg::{Block, ControlFlowGraph};
+use crate::ir::bridge::constant::{Constant, NumValue};
+use crate::ir::bridge::function::Function;
+use crate::ir::bridge::instruction::{ComparePredicate, Instruction};
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::value::{BlockLabel, Value};
+use crate::ir::defuse::Site;
+
+/// Whether a buffer holds a null terminator
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub enum Termination {
+    Bottom,
+    Terminated,
+    Unterminated,
+    /// terminated on some executions only
+    Unknown,
+}
+
+impl Lattice for Termination {
+    fn bottom() -> Self {
+        Self::Bottom
+    }
+
+    fn top() -> Self {
+        Self::Unknown
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        self == other || matches!(self, Self::Bottom) || matches!(other, Self::Unknown)
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Bottom, v) | (v, Self::Bottom) => *v,
+            (lhs, rhs) if lhs == rhs => *lhs,
+            _ => Self::Unknown,
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Unknown, v) | (v, Self::Unknown) => *v,
+            (lhs, rhs) if lhs == rhs => *lhs,
+            _ => Self::Bottom,
+        }
+    }
+}
+
+impl Display for Termination {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bottom => write!(f, "bottom"),
+            Self::Terminated => write!(f, "terminated"),
+            Self::Unterminated => write!(f, "unterminated"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// The string held in a char buffer, read from its start
+#[derive(Eq, PartialEq, Clone)]
+pub struct CString {
+    pub termination: Termination,
+    /// offset of the first terminator, meaningful only if the buffer is terminated
+    pub length: Interval,
+}
+
+impl CString {
+    fn new(termination: Termination, length: Interval) -> Self {
+        if termination.is_bottom() || length.is_bottom() {
+            return Self::bottom();
+        }
+        Self {
+            termination,
+            length,
+        }
+    }
+
+    fn terminated(length: Interval) -> Self {
+        Self::new(Termination::Terminated, length)
+    }
+
+    fn unterminated() -> Self {
+        Self::new(Termination::Unterminated, Interval::top())
+    }
+
+    /// Length of the string, if a terminator is there for sure
+    pub fn length(&self) -> Option<&Interval> {
+        (self.termination == Termination::Terminated).then_some(&self.length)
+    }
+}
+
+impl Lattice for CString {
+    fn bottom() -> Self {
+        Self {
+            termination: Termination::Bottom,
+            length: Interval::bottom(),
+        }
+    }
+
+    fn top() -> Self {
+        Self {
+            termination: Termination::Unknown,
+            length: Interval::top(),
+        }
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        self.is_bottom()
+            || (self.termination.leq(&other.termination) && self.length.leq(&other.length))
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        Self::new(
+            self.termination.join(&other.termination),
+            self.length.join(&other.length),
+        )
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        Self::new(
+            self.termination.meet(&other.termination),
+            self.length.meet(&other.length),
+        )
+    }
+
+    fn widen(&self, other: &Self) -> Self {
+        Self::new(
+            self.termination.join(&other.termination),
+            self.length.widen(&other.length),
+        )
+    }
+
+    fn narrow(&self, other: &Self) -> Self {
+        Self::new(
+            self.termination.meet(&other.termination),
+            self.length.narrow(&other.length),
+        )
+    }
+}
+
+impl Display for CString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.termination {
+            Termination::Terminated => write!(f, "terminated, length {}", self.length),
+            termination => termination.fmt(f),
+        }
+    }
+}
+
+/// Strings of the tracked buffers, where an absent buffer holds an unknown string
+pub type StringStore = Store<AllocSite, CString>;
+
+/// What a string function does to the buffers passed to it
+enum StringOp {
+    /// copy the string of `src` into `dst`, writing exactly `bound` bytes if given
+    Copy {
+        dst: usize,
+        src: usize,
+        bound: Option<usize>,
+    },
+    /// append the string of `src` to that of `dst`, at most `bound` characters of it if given
+    Concat {
+        dst: usize,
+        src: usize,
+        bound: Option<usize>,
+    },
+    /// write a terminated string into the first `bound` bytes of `dst`
+    Fill { dst: usize, bound: usize },
+    /// write a terminated string of any length into `dst`
+    Format { dst: usize },
+    /// write a terminated string of any length into `dst`, checked for overflow
+    Unbounded { dst: usize },
+}
+
+/// Utility: the string function, if it is a modeled one
+fn string_op(name: &str) -> Option<StringOp> {
+    let op = match name {
+        "strcpy" | "stpcpy" => StringOp::Copy {
+            dst: 0,
+            src: 1,
+            bound: None,
+        },
+        "strncpy" => StringOp::Copy {
+            dst: 0,
+            src: 1,
+            bound: Some(2),
+        },
+        "strcat" => StringOp::Concat {
+            dst: 0,
+            src: 1,
+            bound: None,
+        },
+        "strncat" => StringOp::Concat {
+            dst: 0,
+            src: 1,
+            bound: Some(2),
+        },
+        "fgets" | "snprintf" | "vsnprintf" => StringOp::Fill { dst: 0, bound: 1 },
+        "sprintf" | "vsprintf" => StringOp::Format { dst: 0 },
+        "gets" => StringOp::Unbounded { dst: 0 },
+        _ => return None,
+    };
+    Some(op)
+}
+
+/// Utility: the arguments a library function reads as terminated strings
+fn string_reads(name: &str) -> &'static [usize] {
+    match name {
+        "strlen" | "strchr" | "strrchr" | "strdup" | "puts" | "atoi" | "atol" | "strtol"
+        | "strtoul" | "strncat" => &[0],
+        "strcpy" | "stpcpy" => &[1],
+        "strcmp" | "strstr" | "strcat" => &[0, 1],
+        _ => &[],
+    }
+}
+
+/// Utility: the length of any string
+fn any_length() -> Interval {
+    Interval::new(Bound::Finite(Integer::new()), Bound::PosInf)
+}
+
+/// Utility: the pointwise minimum of two intervals
+fn min(lhs: &Interval, rhs: &Interval) -> Interval {
+    match (lhs.bounds(), rhs.bounds()) {
+        (Some((l1, h1)), Some((l2, h2))) => {
+            Interval::new(l1.clone().min(l2.clone()), h1.clone().min(h2.clone()))
+        }
+        _ => Interval::bottom(),
+    }
+}
+
+/// Utility: the length of the literal string a constant global variable starts with, if any
+fn literal_length(module: &Module, name: &Identifier) -> EngineResult<Option<usize>> {
+    let gvar = match module.get_global(name.as_ref()) {
+        Some(gvar) if gvar.is_constant && !gvar.is_weak => gvar,
+        _ => return Ok(None),
+    };
+    let flat = match gvar.flatten_initializer(module.layout())? {
+        None => return Ok(None),
+        Some(flat) => flat,
+    };
+    for offset in 0..flat.size() {
+        match flat.get(offset) {
+            Some(cell) if cell.size == 1 => match &cell.value {
+                Constant::NumOne {
+                    bits: _,
+                    value: NumValue::Int(v),
+                } if *v == 0 => return Ok(Some(offset)),
+                Constant::NumOne {
+                    bits: _,
+                    value: NumValue::Int(_),
+                } => (),
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        }
+    }
+    Ok(None)
+}
+
+/// What goes wrong with a string function
+pub enum StringIssue {
+    /// the call may write `needed` bytes where only `capacity` bytes are left in the buffer
+    Overflow {
+        needed: Interval,
+        capacity: Interval,
+    },
+    /// the argument is read as a string but holds no terminator
+    Unterminated(usize),
+}
+
+/// A call to a string function that overflows its destination or reads past its source
+pub struct StringMisuse {
+    pub function: Identifier,
+    pub site: Site,
+    pub callee: Identifier,
+    /// the buffer overflowed or read
+    pub object: AllocSite,
+    pub issue: StringIssue,
+    pub kind: OutOfBoundsKind,
+}
+
+impl Display for StringMisuse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.issue {
+            StringIssue::Overflow { needed, capacity } => write!(
+                f,
+                "@{}:{}: {} writes {} bytes into {} with {} bytes left ({})",
+                self.function, self.site, self.callee, needed, self.object, capacity, self.kind
+            ),
+            StringIssue::Unterminated(index) => write!(
+                f,
+                "@{}:{}: {} reads argument {} from {} which is unterminated ({})",
+                self.function, self.site, self.callee, index, self.object, self.kind
+            ),
+        }
+    }
+}
+
+/// String analysis of the buffers of a function
+struct Analysis<'a> {
+    module: &'a Module,
+    function: &'a Identifier,
+    cfg: &'a ControlFlowGraph,
+    widening: &'a Widening,
+    global: &'a PointsTo,
+    /// lengths of the literal strings in the constant globals
+    literals: &'a BTreeMap<AllocSite, usize>,
+    /// stores with the buffers they write into, if known, and their widths
+    stores: BTreeMap<Site, (Option<Bounded>, usize)>,
+    /// buffers passed to calls, by site and argument index
+    pointers: BTreeMap<(Site, usize), Bounded>,
+    /// integers passed to calls, by site and argument index
+    ints: BTreeMap<(Site, usize), Interval>,
+    /// buffers allocated, and whether they are zeroed
+    fresh: BTreeMap<Site, (AllocSite, bool)>,
+    /// the budget that ran out in the interval analysis behind the facts above
+    exhausted: Option<Exhaustion>,
+}
+
+impl<'a> Analysis<'a> {
+    fn new(
+        module: &'a Module,
+        global: &'a PointsTo,
+        literals: &'a BTreeMap<AllocSite, usize>,
+        func: &'a Function,
+        cfg: &'a ControlFlowGraph,
+        states: &IntervalStates,
+        widening: &'a Widening,
+    ) -> EngineResult<Self> {
+        let mut tracker = Tracker::build(module, func, cfg, states)?;
+        let mut stores = BTreeMap::new();
+        let mut pointers = BTreeMap::new();
+        let mut ints = BTreeMap::new();
+        let mut fresh = BTreeMap::new();
+        for (label, block) in cfg.blocks() {
+            for (index, inst) in block.instructions().iter().enumerate() {
+                let site = Site::Instruction {
+                    block: *label,
+                    index,
+                };
+                match inst {
+                    Instruction::Alloca { result, .. } => {
+                        if let Some(known) = tracker.register(*result)? {
+                            fresh.insert(site, (known.object, false));
+                        }
+                    }
+                    Instruction::Store { .. } => {
+                        if let Some((_, pointer, width)) = accessed_pointer(module.layout(), inst)?
+                        {
+                            stores.insert(site, (tracker.pointer(pointer)?, width));
+                        }
+                    }
+                    Instruction::CallDirect {
+                        function,
+                        args,
+                        result,
+                        ..
+                    } => {
+                        for (i, arg) in args.iter().enumerate() {
+                            if let Some(known) = tracker.pointer(arg)? {
+                                pointers.insert((site, i), known);
+                            }
+                            if let (Some(bits), Some(env)) = (int_bits(arg), states.get(&site)) {
+                                ints.insert((site, i), eval_value(arg, bits, &|v| env.get(v)));
+                            }
+                        }
+                        if let Some((_, slot)) = result {
+                            if let Some(known) = tracker.register(*slot)? {
+                                fresh.insert(site, (known.object, function.as_ref() == "calloc"));
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Ok(Self {
+            module,
+            function: &func.name,
+            cfg,
+            widening,
+            global,
+            literals,
+            stores,
+            pointers,
+            ints,
+            fresh,
+            exhausted: tracker.exhausted(),
+        })
+    }
+
+    /// Replace the string of a buffer, joining it for a buffer standing for several objects
+    fn write(&self, state: &mut StringStore, object: &AllocSite, value: CString) {
+        if object.is_summary() {
+            state.update(object.clone(), &value);
+        } else {
+            state.set(object.clone(), value);
+        }
+    }
+
+    /// Set the string starting at the pointer, which is only tracked from the start of a buffer
+    fn write_at(&self, state: &mut StringStore, known: &Bounded, value: CString) {
+        if known.offset.as_singleton().is_some_and(|v| *v == 0) {
+            self.write(state, &known.object, value);
+        } else {
+            state.forget(&known.object);
+        }
+    }
+
+    /// The string starting at the pointer
+    fn read_at(&self, state: &StringStore, known: &Bounded) -> CString {
+        let whole = match self.literals.get(&known.object) {
+            Some(length) => CString::terminated(Interval::singleton(Integer::from(*length))),
+            None => state.get(&known.object),
+        };
+        match whole.length() {
+            // a pointer at or before the terminator sees the rest of the string
+            Some(length)
+                if known.offset.is_non_negative()
+                    && known.offset.compare(&ComparePredicate::LE, length) == Some(true) =>
+            {
+                CString::terminated(length.sub(&known.offset))
+            }
+            _ if known.offset.as_singleton().is_some_and(|v| *v == 0) => whole,
+            _ => CString::top(),
+        }
+    }
+
+    /// Forget the buffers a pointer unknown to the tracker may refer to, along with those other
+    /// functions may see
+    fn forget_targets(&self, state: &mut StringStore, value: &Value) {
+        for object in self.global.targets(self.function, value) {
+            state.forget(&object);
+        }
+        self.forget_exposed(state);
+    }
+
+    /// Forget the buffers other functions may write
+    fn forget_exposed(&self, state: &mut StringStore) {
+        let exposed: Vec<_> = state
+            .iter()
+            .map(|(object, _)| object.clone())
+            .filter(|object| {
+                !matches!(object, AllocSite::Stack { .. }) || self.global.escapes().escapes(object)
+            })
+            .collect();
+        for object in exposed {
+            state.forget(&object);
+        }
+    }
+
+    /// Effect of a store into a known buffer
+    fn store(&self, state: &mut StringStore, known: &Bounded, width: usize, value: &Value) {
+        let current = state.get(&known.object);
+        let byte = match value {
+            Value::Constant(Constant::NumOne {
+                bits: _,
+                value: NumValue::Int(v),
+            }) if width == 1 => Some(v),
+            _ => None,
+        };
+        let updated = match (current.length(), byte) {
+            // bytes past the terminator do not matter
+            (Some(length), _)
+                if known.offset.compare(&ComparePredicate::GT, length) == Some(true) =>
+            {
+                current.clone()
+            }
+            // characters before the terminator leave the length alone
+            (Some(length), Some(v))
+                if *v != 0 && known.offset.compare(&ComparePredicate::LT, length) == Some(true) =>
+            {
+                current.clone()
+            }
+            // a terminator cuts the string short
+            (_, Some(v)) if *v == 0 => match known.offset.as_singleton() {
+                None => CString::top(),
+                Some(offset) => {
+                    let at = Bound::Finite(offset.clone());
+                    let length = match (current.termination, current.length.bounds()) {
+                        (Termination::Terminated, Some((lo, hi))) => {
+                            Interval::new(lo.clone().min(at.clone()), hi.clone().min(at))
+                        }
+                        (Termination::Unterminated, _) => Interval::new(at.clone(), at),
+                        _ => Interval::new(Bound::Finite(Integer::new()), at),
+                    };
+                    CString::terminated(length)
+                }
+            },
+            _ => CString::top(),
+        };
+        self.write(state, &known.object, updated);
+    }
+
+    /// Check that a call writing `needed` bytes at the pointer stays within the buffer
+    fn check_overflow(
+        &self,
+        findings: &mut Option<&mut Vec<StringMisuse>>,
+        site: Site,
+        callee: &Identifier,
+        known: &Bounded,
+        needed: Interval,
+    ) {
+        let findings = match findings {
+            None => return,
+            Some(findings) => findings,
+        };
+        let capacity = known.size.sub(&known.offset);
+        let excess = needed.sub(&capacity);
+        let zero = Interval::singleton(Integer::new());
+        let kind = match excess.compare(&ComparePredicate::GT, &zero) {
+            Some(true) => OutOfBoundsKind::Definite,
+            None => OutOfBoundsKind::Possible,
+            Some(false) => return,
+        };
+        findings.push(StringMisuse {
+            function: self.function.clone(),
+            site,
+            callee: callee.clone(),
+            object: known.object.clone(),
+            issue: StringIssue::Overflow { needed, capacity },
+            kind,
+        });
+    }
+
+    /// Effect of a modeled string function, checking its destination for overflows
+    fn string_op(
+        &self,
+        state: &mut StringStore,
+        site: Site,
+        callee: &Identifier,
+        op: StringOp,
+        findings: &mut Option<&mut Vec<StringMisuse>>,
+    ) {
+        let one = Interval::singleton(Integer::from(1));
+        let source = |src: usize| match self.pointers.get(&(site, src)) {
+            None => CString::top(),
+            Some(known) => self.read_at(state, known),
+        };
+        let int = |index: usize| {
+            self.ints
+                .get(&(site, index))
+                .cloned()
+                .unwrap_or_else(any_length)
+        };
+        let (dst, needed, result) = match op {
+            StringOp::Copy { dst, src, bound } => {
+                let source = source(src);
+                let copied = source.length().cloned().unwrap_or_else(any_length);
+                match bound {
+                    None => (dst, copied.add(&one), CString::terminated(copied)),
+                    Some(index) => {
+                        let count = int(index);
+                        let result = match source.length() {
+                            Some(length)
+                                if length.compare(&ComparePredicate::LT, &count) == Some(true) =>
+                            {
+                                CString::terminated(length.clone())
+                            }
+                            Some(length)
+                                if length.compare(&ComparePredicate::GE, &count) == Some(true) =>
+                            {
+                                CString::unterminated()
+                            }
+                            _ => CString::top(),
+                        };
+                        (dst, count, result)
+                    }
+                }
+            }
+            StringOp::Concat { dst, src, bound } => {
+                let copied = source(src).length().cloned().unwrap_or_else(any_length);
+                let appended = match bound {
+                    None => copied,
+                    Some(index) => min(&copied, &int(index)),
+                };
+                let length = self
+                    .pointers
+                    .get(&(site, dst))
+                    .and_then(|known| self.read_at(state, known).length().cloned())
+                    .unwrap_or_else(any_length)
+                    .add(&appended);
+                (dst, length.add(&one), CString::terminated(length))
+            }
+            StringOp::Fill { dst, bound } => {
+                let count = int(bound);
+                // at most `bound - 1` characters fit before the terminator
+                let length = any_length().meet(&count.sub(&one));
+                let result = if length.is_bottom() {
+                    CString::top()
+                } else {
+                    CString::terminated(length)
+                };
+                (dst, count, result)
+            }
+            StringOp::Format { dst } => {
+                if let Some(known) = self.pointers.get(&(site, dst)) {
+                    self.write_at(state, known, CString::terminated(any_length()));
+                }
+                return;
+            }
+            StringOp::Unbounded { dst } => (
+                dst,
+                any_length().add(&one),
+                CString::terminated(any_length()),
+            ),
+        };
+        if let Some(known) = self.pointers.get(&(site, dst)) {
+            self.check_overflow(findings, site, callee, known, needed);
+            self.write_at(state, known, result);
+        }
+    }
+
+    /// Effect of a direct call
+    fn call(
+        &self,
+        state: &mut StringStore,
+        site: Site,
+        callee: &Identifier,
+        args: &[Value],
+        findings: &mut Option<&mut Vec<StringMisuse>>,
+    ) {
+        if let Some((object, zeroed)) = self.fresh.get(&site) {
+            let value = if *zeroed {
+                CString::terminated(Interval::singleton(Integer::new()))
+            } else {
+                CString::top()
+            };
+            self.write(state, object, value);
+            return;
+        }
+
+        let name: &str = callee.as_ref();
+        let defined = self
+            .module
+            .get_function(name)
+            .is_some_and(|func| func.exact_body().is_some());
+        if !defined {
+            if let Some(findings) = findings.as_mut() {
+                for index in string_reads(name) {
+                    let known = match self.pointers.get(&(site, *index)) {
+                        None => continue,
+                        Some(known) => known,
+                    };
+                    if self.read_at(state, known).termination == Termination::Unterminated {
+                        findings.push(StringMisuse {
+                            function: self.function.clone(),
+                            site,
+                            callee: callee.clone(),
+                            object: known.object.clone(),
+                            issue: StringIssue::Unterminated(*index),
+                            kind: OutOfBoundsKind::Definite,
+                        });
+                    }
+                }
+            }
+            if let Some(op) = string_op(name) {
+                self.string_op(state, site, callee, op, findings);
+                return;
+            }
+            if name == "memset" {
+                let zero = Interval::singleton(Integer::new());
+                let cleared = self.ints.get(&(site, 1)) == Some(&zero)
+                    && self.ints.get(&(site, 2)).is_some_and(|count| {
+                        count.compare(&ComparePredicate::GT, &zero) == Some(true)
+                    });
+                if let (true, Some(known)) = (cleared, self.pointers.get(&(site, 0))) {
+                    self.write_at(state, known, CString::terminated(zero));
+                    return;
+                }
+            }
+        }
+
+        // the callee may change whatever it can reach
+        if !self.module.effect(name).writes_memory() {
+            return;
+        }
+        for (index, arg) in args.iter().enumerate() {
+            match self.pointers.get(&(site, index)) {
+                Some(known) => state.forget(&known.object),
+                None if !matches!(arg, Value::Constant(_)) => self.forget_targets(state, arg),
+                None => (),
+            }
+        }
+        self.forget_exposed(state);
+    }
+
+    /// Strings at the end of a block, with the misuses collected if asked for
+    fn block(
+        &self,
+        label: &BlockLabel,
+        block: &Block,
+        state: &StringStore,
+        mut findings: Option<&mut Vec<StringMisuse>>,
+    ) -> StringStore {
+        let mut state = state.clone();
+        for (index, inst) in block.instructions().iter().enumerate() {
+            let site = Site::Instruction {
+                block: *label,
+                index,
+            };
+            match inst {
+                Instruction::Alloca { .. } => {
+                    if let Some((object, _)) = self.fresh.get(&site) {
+                        state.forget(object);
+                    }
+                }
+                Instruction::Store { pointer, value, .. } => match self.stores.get(&site) {
+                    Some((Some(known), width)) => self.store(&mut state, known, *width, value),
+                    _ => self.forget_targets(&mut state, pointer),
+                },
+                Instruction::CallDirect { function, args, .. } => {
+                    self.call(&mut state, site, function, args, &mut findings);
+                }
+                Instruction::CallIndirect { .. } => self.forget_exposed(&mut state),
+                _ => (),
+            }
+        }
+        state
+    }
+
+    /// Solve the strings of the buffers, then replay them to collect the misuses
+    fn run(&self, findings: &mut Findings<StringMisuse>) {
+        let states = solve_forward_with(
+            self.cfg,
+            StringStore::top(),
+            self.widening,
+            |label, block, state| self.block(label, block, state, None),
+            |_, _, _, _, state| state.clone(),
+        );
+
+        findings.record(self.function, self.exhausted.or(states.exhausted()));
+        for label in states.reachable() {
+            let block = self
+                .cfg
+                .get_block_by_label(label)
+                .expect("block label points to a valid node");
+            self.block(
+                label,
+                block,
+                &states.at_entry(label),
+                Some(&mut findings.items),
+            );
+        }
+    }
+}
+
+/// Find the calls to string functions that may overflow their destination buffers or read
+/// unterminated ones
+pub fn check_string_misuses(
+    module: &Module,
+    solver: &SolverConfig,
+) -> EngineResult<Findings<StringMisuse>> {
+    let global = analyze_points_to(module)?;
+    let mut literals = BTreeMap::new();
+    for name in module.globals().keys() {
+        if let Some(length) = literal_length(module, name)? {
+            literals.insert(AllocSite::Global(name.clone()), length);
+        }
+    }
+
+    let intervals = analyze_intervals(module, solver);
+    let mut findings = Findings::new();
+    for (name, func) in module.functions() {
+        let (cfg, states) = match (&func.body, intervals.get(name)) {
+            (Some(cfg), Some(states)) => (cfg, states),
+            _ => continue,
+        };
+        let analysis = Analysis::new(
+            module,
+            &global,
+            &literals,
+            func,
+            cfg,
+            states,
+            &solver.widening,
+        )?;
+        analysis.run(&mut findings);
+    }
+    Ok(findings)
+}
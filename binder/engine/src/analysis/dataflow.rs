@@ -0,0 +1,1026 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+use rug::Integer;
+
+use crate::analysis::lattice::Lattice;
+use crate::analysis::memory::AllocSite;
+use crate::analysis::variable::{int_bits, signed_reading, Variable};
+use crate::ir::bridge::cfg::{Block, ControlFlowGraph, Edge, WtoComponent};
+use crate::ir::bridge::constant::{Constant, NumValue};
+use crate::ir::bridge::instruction::{ComparePredicate, Instruction, Terminator};
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::typing::{NumRepr, Type};
+use crate::ir::bridge::value::{BlockLabel, RegisterSlot, Value};
+use crate::ir::defuse::Site;
+
+/// A map from keys to abstract values, where an absent key is unconstrained (top)
+#[derive(Eq, PartialEq, Clone)]
+pub struct Store<K: Ord, D> {
+    /// `None` for an unreachable state
+    entries: Option<BTreeMap<K, D>>,
+}
+
+impl<K: Ord + Clone, D: Lattice> Store<K, D> {
+    /// Value of a key, bottom in an unreachable state
+    pub fn get(&self, key: &K) -> D {
+        match &self.entries {
+            None => D::bottom(),
+            Some(entries) => entries.get(key).cloned().unwrap_or_else(D::top),
+        }
+    }
+
+    /// Bind a key to a value, replacing the old one (i.e., a strong update)
+    pub fn set(&mut self, key: K, value: D) {
+        if value.is_bottom() {
+            self.entries = None;
+            return;
+        }
+        if let Some(entries) = self.entries.as_mut() {
+            if value.is_top() {
+                entries.remove(&key);
+            } else {
+                entries.insert(key, value);
+            }
+        }
+    }
+
+    /// Join a value into the binding of a key (i.e., a weak update)
+    pub fn update(&mut self, key: K, value: &D) {
+        let joined = self.get(&key).join(value);
+        if self.entries.is_some() {
+            self.set(key, joined);
+        }
+    }
+
+    /// Forget everything about a key
+    pub fn forget(&mut self, key: &K) {
+        if let Some(entries) = self.entries.as_mut() {
+            entries.remove(key);
+        }
+    }
+
+    /// Keys bound to a value other than top
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &D)> {
+        self.entries.iter().flatten()
+    }
+
+    /// Combine two reachable stores on the keys constrained in both
+    fn combine(&self, other: &Self, f: impl Fn(&D, &D) -> D) -> Self {
+        let (lhs, rhs) = match (&self.entries, &other.entries) {
+            (None, _) => return other.clone(),
+            (_, None) => return self.clone(),
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+        };
+        let entries = lhs
+            .iter()
+            .filter_map(|(k, v)| rhs.get(k).map(|w| (k.clone(), f(v, w))))
+            .filter(|(_, v)| !v.is_top())
+            .collect();
+        Self {
+            entries: Some(entries),
+        }
+    }
+}
+
+impl<K: Ord + Clone, D: Lattice> Lattice for Store<K, D> {
+    fn bottom() -> Self {
+        Self { entries: None }
+    }
+
+    fn top() -> Self {
+        Self {
+            entries: Some(BTreeMap::new()),
+        }
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        match (&self.entries, &other.entries) {
+            (None, _) => true,
+            (_, None) => false,
+            (Some(lhs), Some(rhs)) => rhs
+                .iter()
+                .all(|(k, w)| lhs.get(k).is_some_and(|v| v.leq(w))),
+        }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        self.combine(other, D::join)
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        let (lhs, rhs) = match (&self.entries, &other.entries) {
+            (None, _) | (_, None) => return Self::bottom(),
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+        };
+        let mut result = Self {
+            entries: Some(lhs.clone()),
+        };
+        for (k, w) in rhs {
+            let v = result.get(k).meet(w);
+            result.set(k.clone(), v);
+        }
+        result
+    }
+
+    fn widen(&self, other: &Self) -> Self {
+        self.combine(other, D::widen)
+    }
+
+    fn widen_with_thresholds(&self, other: &Self, thresholds: &BTreeSet<Integer>) -> Self {
+        self.combine(other, |v, w| v.widen_with_thresholds(w, thresholds))
+    }
+
+    fn narrow(&self, other: &Self) -> Self {
+        let (lhs, rhs) = match (&self.entries, &other.entries) {
+            (None, _) | (_, None) => return Self::bottom(),
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+        };
+        let mut result = Self {
+            entries: Some(lhs.clone()),
+        };
+        for (k, w) in rhs {
+            let v = result.get(k).narrow(w);
+            result.set(k.clone(), v);
+        }
+        result
+    }
+}
+
+impl<K: Ord + Display, D: Display> Display for Store<K, D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.entries {
+            None => write!(f, "unreachable"),
+            Some(entries) => {
+                let items: Vec<_> = entries
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", k, v))
+                    .collect();
+                write!(f, "{{{}}}", items.join(", "))
+            }
+        }
+    }
+}
+
+/// Abstract values of the arguments and registers of a function
+pub type VariableStore<D> = Store<Variable, D>;
+
+/// A memory-aware store: values held in variables and in abstract locations (i.e., the objects
+/// allocated at a site), with all fields of an object merged
+#[derive(Eq, PartialEq, Clone)]
+pub struct MemoryStore<D> {
+    /// values held in arguments and registers
+    pub variables: VariableStore<D>,
+    /// values held in the objects
+    pub memory: Store<AllocSite, D>,
+}
+
+impl<D: Lattice> MemoryStore<D> {
+    pub fn new(variables: VariableStore<D>, memory: Store<AllocSite, D>) -> Self {
+        if variables.is_bottom() || memory.is_bottom() {
+            return Self::bottom();
+        }
+        Self { variables, memory }
+    }
+
+    /// Value loaded from any of the locations
+    pub fn load<'s>(&self, locations: impl IntoIterator<Item = &'s AllocSite>) -> D {
+        locations
+            .into_iter()
+            .fold(D::bottom(), |acc, site| acc.join(&self.memory.get(site)))
+    }
+
+    /// Write a value into the locations, replacing the old one if `strong` holds and the write
+    /// certainly hits a single object (a strong update), joining it otherwise (a weak update)
+    pub fn store<'s>(
+        &mut self,
+        locations: impl IntoIterator<Item = &'s AllocSite>,
+        value: &D,
+        strong: bool,
+    ) {
+        let locations: Vec<_> = locations.into_iter().collect();
+        match locations.as_slice() {
+            [site] if strong && !site.is_summary() => {
+                self.memory.set((*site).clone(), value.clone());
+            }
+            _ => {
+                for site in locations {
+                    self.memory.update(site.clone(), value);
+                }
+            }
+        }
+    }
+}
+
+impl<D: Lattice> Lattice for MemoryStore<D> {
+    fn bottom() -> Self {
+        Self {
+            variables: Store::bottom(),
+            memory: Store::bottom(),
+        }
+    }
+
+    fn top() -> Self {
+        Self {
+            variables: Store::top(),
+            memory: Store::top(),
+        }
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        self.is_bottom() || (self.variables.leq(&other.variables) && self.memory.leq(&other.memory))
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        Self::new(
+            self.variables.join(&other.variables),
+            self.memory.join(&other.memory),
+        )
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        Self::new(
+            self.variables.meet(&other.variables),
+            self.memory.meet(&other.memory),
+        )
+    }
+
+    fn widen(&self, other: &Self) -> Self {
+        Self::new(
+            self.variables.widen(&other.variables),
+            self.memory.widen(&other.memory),
+        )
+    }
+
+    fn widen_with_thresholds(&self, other: &Self, thresholds: &BTreeSet<Integer>) -> Self {
+        Self::new(
+            self.variables
+                .widen_with_thresholds(&other.variables, thresholds),
+            self.memory.widen_with_thresholds(&other.memory, thresholds),
+        )
+    }
+
+    fn narrow(&self, other: &Self) -> Self {
+        Self::new(
+            self.variables.narrow(&other.variables),
+            self.memory.narrow(&other.memory),
+        )
+    }
+}
+
+impl<D: Display> Display for MemoryStore<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} / {}", self.variables, self.memory)
+    }
+}
+
+/// A fact that holds along an edge, derived from the terminator of its source
+pub enum Filter<'a> {
+    /// the branch condition has the value
+    Condition { cond: &'a Value, value: bool },
+    /// the comparison defining the branch condition holds, negated on the else edge
+    Compare {
+        bits: usize,
+        predicate: ComparePredicate,
+        lhs: &'a Value,
+        rhs: &'a Value,
+    },
+    /// the switch condition is one of the values
+    OneOf {
+        bits: usize,
+        cond: &'a Value,
+        values: Vec<&'a Integer>,
+    },
+    /// the switch condition is none of the values
+    NoneOf {
+        bits: usize,
+        cond: &'a Value,
+        values: Vec<&'a Integer>,
+    },
+}
+
+/// Integer comparisons of a CFG, keyed by their results
+pub struct Comparisons<'a> {
+    compares: BTreeMap<RegisterSlot, (usize, &'a ComparePredicate, &'a Value, &'a Value)>,
+    /// integer extensions, which keep the truth of their operands, e.g., `!!(c)` in a contract
+    extensions: BTreeMap<RegisterSlot, &'a Value>,
+}
+
+impl<'a> Comparisons<'a> {
+    pub fn collect(cfg: &'a ControlFlowGraph) -> Self {
+        let mut compares = BTreeMap::new();
+        let mut extensions = BTreeMap::new();
+        for (_, block) in cfg.blocks() {
+            for inst in block.instructions() {
+                match inst {
+                    Instruction::CompareBitvec {
+                        bits,
+                        number: NumRepr::Int,
+                        length: None,
+                        predicate,
+                        lhs,
+                        rhs,
+                        result,
+                    } => {
+                        compares.insert(*result, (*bits, predicate, lhs, rhs));
+                    }
+                    Instruction::CastBitvecSize {
+                        bits_from,
+                        bits_into,
+                        number: NumRepr::Int,
+                        length: None,
+                        operand,
+                        result,
+                    } if bits_into > bits_from => {
+                        extensions.insert(*result, operand);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Self {
+            compares,
+            extensions,
+        }
+    }
+
+    /// The filters that hold when a condition has the value, i.e., the condition itself and the
+    /// comparison defining it, negated for a false value
+    pub fn filters(&self, cond: &'a Value, value: bool) -> Vec<Filter<'a>> {
+        let mut filters = vec![Filter::Condition { cond, value }];
+
+        // look through the extensions to the condition they are taken from
+        let mut cond = cond;
+        while let Some(operand) = match cond {
+            Value::Register { index, ty: _ } => self.extensions.get(index),
+            Value::Argument { .. } | Value::Constant(_) => None,
+        } {
+            cond = operand;
+            if int_bits(cond) == Some(1) {
+                filters.push(Filter::Condition { cond, value });
+            }
+        }
+
+        if let Value::Register { index, ty: _ } = cond {
+            if let Some((bits, predicate, lhs, rhs)) = self.compares.get(index) {
+                let predicate = if value {
+                    (*predicate).clone()
+                } else {
+                    predicate.negate()
+                };
+                filters.push(Filter::Compare {
+                    bits: *bits,
+                    predicate,
+                    lhs,
+                    rhs,
+                });
+            }
+        }
+        filters
+    }
+}
+
+/// Utility: the filters of an edge out of a block
+fn edge_filters<'a>(
+    cfg: &'a ControlFlowGraph,
+    comparisons: &Comparisons<'a>,
+    src: &BlockLabel,
+    edge: &Edge,
+) -> Vec<Filter<'a>> {
+    let terminator = cfg
+        .get_block_by_label(src)
+        .expect("block label points to a valid node")
+        .terminator();
+    let mut filters = vec![];
+    match (terminator, edge) {
+        (Terminator::Branch { cond, .. }, Edge::Branch(taken)) => {
+            filters.extend(comparisons.filters(cond, *taken));
+        }
+        (Terminator::Switch { cond, cases, .. }, Edge::Switch(taken)) => {
+            let bits = match int_bits(cond) {
+                None => return filters,
+                Some(bits) => bits,
+            };
+            // on the default edge, the cases leading elsewhere are excluded
+            if taken.contains(&None) {
+                let values = cases
+                    .keys()
+                    .filter(|v| !taken.contains(&Some((*v).clone())))
+                    .collect();
+                filters.push(Filter::NoneOf { bits, cond, values });
+            } else {
+                let values = cases
+                    .keys()
+                    .filter(|v| taken.contains(&Some((*v).clone())))
+                    .collect();
+                filters.push(Filter::OneOf { bits, cond, values });
+            }
+        }
+        _ => (),
+    }
+    filters
+}
+
+/// Stabilized states of a CFG, where unreachable blocks are absent
+pub struct CfgState<S> {
+    states_in: BTreeMap<BlockLabel, S>,
+    states_out: BTreeMap<BlockLabel, S>,
+    /// states right before each site, only when recorded as they cost memory
+    points: Option<BTreeMap<Site, S>>,
+    /// the budget that ran out, in which case every block is top
+    exhausted: Option<Exhaustion>,
+}
+
+impl<S: Lattice> CfgState<S> {
+    /// State on entry to a block
+    pub fn at_entry(&self, label: &BlockLabel) -> S {
+        self.states_in.get(label).cloned().unwrap_or_else(S::bottom)
+    }
+
+    /// State on exit of a block
+    pub fn at_exit(&self, label: &BlockLabel) -> S {
+        self.states_out
+            .get(label)
+            .cloned()
+            .unwrap_or_else(S::bottom)
+    }
+
+    /// Blocks reached by the analysis
+    pub fn reachable(&self) -> impl Iterator<Item = &BlockLabel> {
+        self.states_in.keys()
+    }
+
+    /// The budget that ran out before the states stabilized, `None` if they are precise
+    pub fn exhausted(&self) -> Option<Exhaustion> {
+        self.exhausted
+    }
+
+    /// Record the state right before each site of the reachable blocks, replaying the blocks
+    /// from their entry states with `step` computing the state after an instruction
+    pub fn record_points(
+        &mut self,
+        cfg: &ControlFlowGraph,
+        mut step: impl FnMut(Site, &Instruction, &S) -> S,
+    ) {
+        let mut points = BTreeMap::new();
+        for (label, entry) in &self.states_in {
+            let block = cfg
+                .get_block_by_label(label)
+                .expect("block label points to a valid node");
+            let mut state = entry.clone();
+            for (index, inst) in block.instructions().iter().enumerate() {
+                let site = Site::Instruction {
+                    block: *label,
+                    index,
+                };
+                let next = step(site, inst, &state);
+                points.insert(site, state);
+                state = next;
+            }
+            points.insert(Site::Terminator { block: *label }, state);
+        }
+        self.points = Some(points);
+    }
+
+    /// State right before a site, `None` if the states at the sites are not recorded
+    pub fn at_point(&self, site: &Site) -> Option<S> {
+        self.points
+            .as_ref()
+            .map(|points| points.get(site).cloned().unwrap_or_else(S::bottom))
+    }
+
+    /// State right before the instruction at the index of a block, `None` if not recorded
+    pub fn before_instruction(&self, label: &BlockLabel, index: usize) -> Option<S> {
+        self.at_point(&Site::Instruction {
+            block: *label,
+            index,
+        })
+    }
+}
+
+/// Number of descending passes after stabilization, by default
+const NUM_PASSES_NARROW: usize = 2;
+
+/// Limits on the ascending iteration over a function, unlimited by default
+#[derive(Clone, Copy, Default)]
+pub struct Budget {
+    /// number of block updates
+    pub updates: Option<usize>,
+    /// wall time
+    pub time: Option<Duration>,
+}
+
+/// A budget that ran out
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub enum Exhaustion {
+    Updates,
+    Time,
+}
+
+impl Display for Exhaustion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Updates => write!(f, "update budget exhausted"),
+            Self::Time => write!(f, "time budget exhausted"),
+        }
+    }
+}
+
+/// Findings of a checker over a module, along with the functions whose analyses ran out of the
+/// budget, where the findings are sound but imprecise
+pub struct Findings<T> {
+    pub items: Vec<T>,
+    pub exhausted: BTreeMap<Identifier, Exhaustion>,
+}
+
+impl<T> Default for Findings<T> {
+    fn default() -> Self {
+        Self {
+            items: vec![],
+            exhausted: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> Findings<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.items.push(item);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.items.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Record the budget that ran out in an analysis of the function, the first one kept
+    pub fn record(&mut self, function: &Identifier, exhausted: Option<Exhaustion>) {
+        if let Some(exhaustion) = exhausted {
+            self.exhausted.entry(function.clone()).or_insert(exhaustion);
+        }
+    }
+
+    /// Record the budgets that ran out in the analyses behind other findings
+    pub fn record_all<U>(&mut self, other: &Findings<U>) {
+        for (function, exhaustion) in &other.exhausted {
+            self.record(function, Some(*exhaustion));
+        }
+    }
+}
+
+impl<T> IntoIterator for Findings<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Findings<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+/// Configuration of the extrapolation at the heads of the cycles
+#[derive(Clone)]
+pub struct Widening {
+    /// number of rounds at a head that join the states before widening them
+    pub delay: usize,
+    /// bounds that widening stops at before extrapolating to infinity
+    pub thresholds: BTreeSet<Integer>,
+    /// whether the program constants in the CFG are thresholds as well, i.e., the operands of
+    /// integer comparisons and the lengths of arrays
+    pub constants: bool,
+    /// bound on the number of descending passes that narrow the stabilized states
+    pub narrowing: usize,
+    /// limits on the ascending iteration, beyond which the function degrades to top
+    pub budget: Budget,
+}
+
+impl Default for Widening {
+    fn default() -> Self {
+        Self {
+            delay: 0,
+            thresholds: BTreeSet::new(),
+            constants: true,
+            narrowing: NUM_PASSES_NARROW,
+            budget: Budget::default(),
+        }
+    }
+}
+
+impl Widening {
+    /// The thresholds in a CFG, the program constants included if asked for
+    fn thresholds_in(&self, cfg: &ControlFlowGraph) -> BTreeSet<Integer> {
+        let mut thresholds = self.thresholds.clone();
+        if !self.constants {
+            return thresholds;
+        }
+        for (_, block) in cfg.blocks() {
+            for inst in block.instructions() {
+                match inst {
+                    Instruction::CompareBitvec {
+                        bits,
+                        number: NumRepr::Int,
+                        length: None,
+                        predicate: _,
+                        lhs,
+                        rhs,
+                        result: _,
+                    } => {
+                        for operand in [lhs, rhs] {
+                            if let Value::Constant(Constant::NumOne {
+                                bits: _,
+                                value: NumValue::Int(v),
+                            }) = operand
+                            {
+                                let v = signed_reading(*bits, v);
+                                thresholds.insert(v.clone() - 1);
+                                thresholds.insert(v.clone());
+                                thresholds.insert(v + 1);
+                            }
+                        }
+                    }
+                    Instruction::Alloca { base_type, .. } => {
+                        collect_array_lengths(base_type, &mut thresholds);
+                    }
+                    Instruction::GEP {
+                        src_pointee_type, ..
+                    } => {
+                        collect_array_lengths(src_pointee_type, &mut thresholds);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        thresholds
+    }
+}
+
+/// Utility: collect the lengths of the arrays in a type along with their last indices
+fn collect_array_lengths(ty: &Type, thresholds: &mut BTreeSet<Integer>) {
+    match ty {
+        Type::Array { element, length } => {
+            thresholds.insert(Integer::from(*length) - 1);
+            thresholds.insert(Integer::from(*length));
+            collect_array_lengths(element, thresholds);
+        }
+        Type::Struct { fields, .. } => {
+            for field in fields {
+                collect_array_lengths(field, thresholds);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Utility: an incoming edge of a block, along with its source and filters
+type Incoming<'a> = (BlockLabel, &'a Edge, Vec<Filter<'a>>);
+
+/// Utility: state of the iteration of a forward analysis over a CFG
+struct Solver<'a, S, B, E> {
+    cfg: &'a ControlFlowGraph,
+    initial: S,
+    widening: &'a Widening,
+    /// bounds that widening stops at in the CFG
+    thresholds: BTreeSet<Integer>,
+    /// predecessors of each block, along with the edge and its filters
+    preds: BTreeMap<BlockLabel, Vec<Incoming<'a>>>,
+    /// number of rounds taken at each head so far
+    rounds: BTreeMap<BlockLabel, usize>,
+    /// whether the states are stable and being narrowed
+    descending: bool,
+    /// number of block updates in the ascending iteration so far
+    updates: usize,
+    started: Instant,
+    block: B,
+    edge: E,
+    result: CfgState<S>,
+}
+
+impl<S, B, E> Solver<'_, S, B, E>
+where
+    S: Lattice,
+    B: FnMut(&BlockLabel, &Block, &S) -> S,
+    E: FnMut(&BlockLabel, &BlockLabel, &Edge, &[Filter], &S) -> S,
+{
+    /// Join of the states flowing into a block
+    fn incoming(&mut self, label: &BlockLabel) -> S {
+        let mut new = if *label == self.cfg.entry() {
+            self.initial.clone()
+        } else {
+            S::bottom()
+        };
+        for (src, kind, filters) in self.preds.get(label).into_iter().flatten() {
+            if let Some(out) = self.result.states_out.get(src) {
+                new = new.join(&(self.edge)(src, label, kind, filters, out));
+            }
+        }
+        new
+    }
+
+    /// Recompute the state of a block, extrapolating it at a head (i.e., widening it once the
+    /// delay is over, or narrowing it when descending), and return whether it changed
+    fn update(&mut self, label: &BlockLabel, at_head: bool) -> bool {
+        if !self.descending && !self.within_budget() {
+            return false;
+        }
+        let new = self.incoming(label);
+        if new.is_bottom() {
+            return false;
+        }
+        let new = match self.result.states_in.get(label) {
+            Some(old) if at_head && self.descending => old.narrow(&new),
+            Some(old) if at_head => {
+                let round = self.rounds.entry(*label).or_default();
+                *round += 1;
+                let joined = old.join(&new);
+                if *round <= self.widening.delay {
+                    joined
+                } else {
+                    old.widen_with_thresholds(&joined, &self.thresholds)
+                }
+            }
+            _ => new,
+        };
+        if self.result.states_in.get(label) == Some(&new) {
+            return false;
+        }
+
+        let body = self
+            .cfg
+            .get_block_by_label(label)
+            .expect("block label points to a valid node");
+        let out = (self.block)(label, body, &new);
+        self.result.states_in.insert(*label, new);
+        self.result.states_out.insert(*label, out);
+        true
+    }
+
+    /// Count an update against the budget, returning false once the budget has run out
+    fn within_budget(&mut self) -> bool {
+        if self.result.exhausted.is_some() {
+            return false;
+        }
+        self.updates += 1;
+        let budget = &self.widening.budget;
+        if budget.updates.is_some_and(|limit| self.updates > limit) {
+            self.result.exhausted = Some(Exhaustion::Updates);
+        } else if budget
+            .time
+            .is_some_and(|limit| self.started.elapsed() > limit)
+        {
+            self.result.exhausted = Some(Exhaustion::Time);
+        }
+        self.result.exhausted.is_none()
+    }
+
+    /// Give up on the precision of the states, setting every block to top
+    fn degrade(&mut self) {
+        for (label, _) in self.cfg.blocks() {
+            self.result.states_in.insert(*label, S::top());
+            self.result.states_out.insert(*label, S::top());
+        }
+    }
+
+    /// Stabilize the components in order, each cycle iterated until its head is stable
+    fn iterate(&mut self, components: &[WtoComponent]) {
+        for component in components {
+            match component {
+                WtoComponent::Vertex(label) => {
+                    self.update(label, false);
+                }
+                WtoComponent::Cycle { head, body } => {
+                    self.update(head, false);
+                    self.iterate(body);
+                    while self.update(head, true) {
+                        self.iterate(body);
+                    }
+                }
+            }
+        }
+    }
+
+    /// One descending pass over the components, returning whether any state changed
+    fn descend(&mut self, components: &[WtoComponent]) -> bool {
+        let mut changed = false;
+        for component in components {
+            match component {
+                WtoComponent::Vertex(label) => {
+                    changed |= self.update(label, false);
+                }
+                WtoComponent::Cycle { head, body } => {
+                    changed |= self.update(head, true);
+                    changed |= self.descend(body);
+                }
+            }
+        }
+        changed
+    }
+}
+
+/// Iterate a forward analysis over the CFG to a fixedpoint, following a weak topological order
+/// of the blocks and widening at the heads of its cycles only, then narrow the result in a few
+/// descending passes
+///
+/// If the ascending iteration runs out of the budget, every block is top in the result, which
+/// records the exhaustion.
+///
+/// The `block` closure computes the exit state of a block from its entry state, and the `edge`
+/// closure refines the exit state of `src` for the edge into `dst` (e.g., resolving the phi
+/// nodes of `dst` or assuming the filters derived from the terminator of `src`), returning
+/// bottom for an infeasible edge.
+pub fn solve_forward<S, B, E>(cfg: &ControlFlowGraph, initial: S, block: B, edge: E) -> CfgState<S>
+where
+    S: Lattice,
+    B: FnMut(&BlockLabel, &Block, &S) -> S,
+    E: FnMut(&BlockLabel, &BlockLabel, &Edge, &[Filter], &S) -> S,
+{
+    solve_forward_with(cfg, initial, &Widening::default(), block, edge)
+}
+
+/// Same as `solve_forward`, with the widening configured
+pub fn solve_forward_with<S, B, E>(
+    cfg: &ControlFlowGraph,
+    initial: S,
+    widening: &Widening,
+    block: B,
+    edge: E,
+) -> CfgState<S>
+where
+    S: Lattice,
+    B: FnMut(&BlockLabel, &Block, &S) -> S,
+    E: FnMut(&BlockLabel, &BlockLabel, &Edge, &[Filter], &S) -> S,
+{
+    let comparisons = Comparisons::collect(cfg);
+    let mut preds: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for (label, _) in cfg.blocks() {
+        for (dst, kind) in cfg.successors(label) {
+            let filters = edge_filters(cfg, &comparisons, label, kind);
+            preds.entry(dst).or_default().push((*label, kind, filters));
+        }
+    }
+
+    let mut solver = Solver {
+        cfg,
+        initial,
+        widening,
+        thresholds: widening.thresholds_in(cfg),
+        preds,
+        rounds: BTreeMap::new(),
+        descending: false,
+        updates: 0,
+        started: Instant::now(),
+        block,
+        edge,
+        result: CfgState {
+            states_in: BTreeMap::new(),
+            states_out: BTreeMap::new(),
+            points: None,
+            exhausted: None,
+        },
+    };
+    let wto = cfg.wto();
+    solver.iterate(&wto);
+    if solver.result.exhausted.is_some() {
+        solver.degrade();
+        return solver.result;
+    }
+
+    // recover the precision lost in widening
+    solver.descending = true;
+    for _ in 0..widening.narrowing {
+        if !solver.descend(&wto) {
+            break;
+        }
+    }
+    solver.result
+}
+
+/// Transfer functions of a forward analysis, site by site
+pub trait TransferFunction {
+    type State: Lattice;
+
+    /// Update the state across an instruction other than a call
+    fn instruction(&self, state: &mut Self::State, site: Site, inst: &Instruction);
+
+    /// Update the state across a call or an invoke, where `callee` is `None` if indirect
+    fn call(
+        &self,
+        state: &mut Self::State,
+        site: Site,
+        callee: Option<&Identifier>,
+        args: &[Value],
+        result: Option<RegisterSlot>,
+    );
+
+    /// Update the state across a terminator other than an invoke
+    fn terminator(&self, _state: &mut Self::State, _site: Site, _term: &Terminator) {}
+
+    /// Refine the exit state of `src` on the edge into `dst` under the filters of the edge,
+    /// bottom for an infeasible edge
+    fn edge(
+        &self,
+        _src: &BlockLabel,
+        _dst: &BlockLabel,
+        _edge: &Edge,
+        _filters: &[Filter],
+        state: &Self::State,
+    ) -> Self::State {
+        state.clone()
+    }
+
+    /// Update the state across any instruction, dispatching the calls to `call`
+    fn step(&self, state: &mut Self::State, site: Site, inst: &Instruction) {
+        match inst {
+            Instruction::CallDirect {
+                function,
+                args,
+                result,
+                ..
+            } => self.call(
+                state,
+                site,
+                Some(function),
+                args,
+                result.as_ref().map(|(_, slot)| *slot),
+            ),
+            Instruction::CallIndirect { args, result, .. } => self.call(
+                state,
+                site,
+                None,
+                args,
+                result.as_ref().map(|(_, slot)| *slot),
+            ),
+            _ => self.instruction(state, site, inst),
+        }
+    }
+
+    /// Exit state of a block from its entry state
+    fn block(&self, label: &BlockLabel, block: &Block, state: &Self::State) -> Self::State {
+        let mut state = state.clone();
+        for (index, inst) in block.instructions().iter().enumerate() {
+            let site = Site::Instruction {
+                block: *label,
+                index,
+            };
+            self.step(&mut state, site, inst);
+        }
+        let site = Site::Terminator { block: *label };
+        match block.terminator() {
+            Terminator::InvokeDirect {
+                function,
+                args,
+                result,
+                ..
+            } => self.call(
+                &mut state,
+                site,
+                Some(function),
+                args,
+                result.as_ref().map(|(_, slot)| *slot),
+            ),
+            Terminator::InvokeIndirect { args, result, .. } => self.call(
+                &mut state,
+                site,
+                None,
+                args,
+                result.as_ref().map(|(_, slot)| *slot),
+            ),
+            term => self.terminator(&mut state, site, term),
+        }
+        state
+    }
+}
+
+/// Iterate the transfer functions over the CFG to a fixedpoint, see `solve_forward_with`
+pub fn solve_transfer<T: TransferFunction>(
+    cfg: &ControlFlowGraph,
+    initial: T::State,
+    widening: &Widening,
+    transfer: &T,
+) -> CfgState<T::State> {
+    solve_forward_with(
+        cfg,
+        initial,
+        widening,
+        |label, block, state| transfer.block(label, block, state),
+        |src, dst, edge, filters, state| transfer.edge(src, dst, edge, filters, state),
+    )
+}
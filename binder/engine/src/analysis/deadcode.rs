@@ -0,0 +1,218 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use rug::Integer;
+
+use crate::analysis::dataflow::{Exhaustion, Findings};
+use crate::analysis::interval::{eval_value, Interval};
+use crate::analysis::invariant::{analyze_intervals, IntervalStates};
+use crate::analysis::summary::SolverConfig;
+use crate::analysis::variable::int_bits;
+use crate::error::EngineResult;
+use crate::ir::bridge::cfg::{ControlFlowGraph, Edge};
+use crate::ir::bridge::instruction::Terminator;
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::value::BlockLabel;
+use crate::ir::defuse::Site;
+
+/// Why code is dead
+#[derive(Eq, PartialEq, Clone)]
+pub enum DeadCodeKind {
+    /// no path in the CFG leads from the entry to the block
+    Disconnected,
+    /// every path from the entry to the block is infeasible under the intervals
+    Infeasible,
+    /// the condition of the terminator always has the value (the branch taken for a branch)
+    ConstantCondition(Integer),
+}
+
+/// A block that never executes, or a branch that always goes the same way
+pub struct DeadCode {
+    pub function: Identifier,
+    pub block: BlockLabel,
+    pub kind: DeadCodeKind,
+}
+
+impl Display for DeadCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            DeadCodeKind::Disconnected => write!(
+                f,
+                "@{}:{}: block is not connected to the entry",
+                self.function, self.block
+            ),
+            DeadCodeKind::Infeasible => write!(
+                f,
+                "@{}:{}: block is unreachable on every path",
+                self.function, self.block
+            ),
+            DeadCodeKind::ConstantCondition(value) => write!(
+                f,
+                "@{}:{}: condition of the terminator is always {}",
+                self.function, self.block, value
+            ),
+        }
+    }
+}
+
+/// Blocks and edges of a CFG that may execute
+pub struct LiveCode {
+    pub blocks: BTreeSet<BlockLabel>,
+    pub edges: BTreeSet<(BlockLabel, BlockLabel)>,
+    /// blocks whose terminator has a constant condition, with the value
+    pub constants: Vec<(BlockLabel, Integer)>,
+    /// the budget that ran out in the interval analysis, in which case every block is live
+    pub exhausted: Option<Exhaustion>,
+}
+
+impl LiveCode {
+    /// Render the CFG in DOT format without the dead blocks and edges
+    pub fn to_dot(&self, cfg: &ControlFlowGraph) -> String {
+        cfg.to_dot_filtered(
+            |label| self.blocks.contains(label),
+            |src, dst| self.edges.contains(&(*src, *dst)),
+        )
+    }
+}
+
+/// Utility: whether the edge is taken when the condition of its terminator has the value
+fn takes(edge: &Edge, bits: usize, value: &Integer, term: &Terminator) -> bool {
+    match (edge, term) {
+        (Edge::Branch(taken), _) => *taken == (*value != 0),
+        (Edge::Switch(labels), Terminator::Switch { cases, .. }) => {
+            let matched = cases
+                .keys()
+                .find(|case| Interval::constant(bits, case).as_singleton() == Some(value));
+            match matched {
+                None => labels.contains(&None),
+                Some(case) => labels.contains(&Some(case.clone())),
+            }
+        }
+        _ => true,
+    }
+}
+
+/// Find the blocks and edges of a function that may execute under its interval states
+pub fn analyze_live_code(cfg: &ControlFlowGraph, states: &IntervalStates) -> LiveCode {
+    let mut live = LiveCode {
+        blocks: BTreeSet::new(),
+        edges: BTreeSet::new(),
+        constants: vec![],
+        exhausted: states.exhausted(),
+    };
+    for (label, block) in cfg.blocks() {
+        // the state before the terminator is absent if the block is dead or an assumption fails
+        let env = match states.get(&Site::Terminator { block: *label }) {
+            None => {
+                if states.sites().any(|site| site.block() == *label) {
+                    live.blocks.insert(*label);
+                }
+                continue;
+            }
+            Some(env) => env,
+        };
+        live.blocks.insert(*label);
+
+        let term = block.terminator();
+        let constant = match term {
+            Terminator::Branch { cond, .. } | Terminator::Switch { cond, .. } => int_bits(cond)
+                .and_then(|bits| {
+                    let value = eval_value(cond, bits, &|v| env.get(v));
+                    let truth = match term {
+                        // a branch is decided as soon as the condition is known to be nonzero
+                        Terminator::Branch { .. } if !value.contains(&Integer::new()) => {
+                            Some(Integer::from(1))
+                        }
+                        _ => value.as_singleton().cloned(),
+                    };
+                    truth.map(|v| (bits, v))
+                }),
+            _ => None,
+        };
+        if let Some((_, value)) = &constant {
+            live.constants.push((*label, value.clone()));
+        }
+        for (dst, edge) in cfg.successors(label) {
+            let feasible = constant
+                .as_ref()
+                .is_none_or(|(bits, value)| takes(edge, *bits, value, term));
+            if feasible {
+                live.edges.insert((*label, dst));
+            }
+        }
+    }
+    // a block is only live if a live edge leads to it
+    let entry = cfg.entry();
+    live.blocks
+        .retain(|label| *label == entry || live.edges.iter().any(|(_, dst)| dst == label));
+    live.edges
+        .retain(|(src, dst)| live.blocks.contains(src) && live.blocks.contains(dst));
+    live
+}
+
+/// Report the dead blocks, at the boundary of the dead regions only, and the terminators with a
+/// constant condition in the defined functions
+pub fn check_dead_code(module: &Module, solver: &SolverConfig) -> EngineResult<Findings<DeadCode>> {
+    let intervals = analyze_intervals(module, solver);
+    let mut findings = Findings::new();
+    for (name, func) in module.functions() {
+        let (cfg, states) = match (&func.body, intervals.get(name)) {
+            (Some(cfg), Some(states)) => (cfg, states),
+            _ => continue,
+        };
+        let live = analyze_live_code(cfg, states);
+        findings.record(name, live.exhausted);
+
+        // blocks connected to the entry in the CFG, and the predecessors of each block
+        let mut preds: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for (label, _) in cfg.blocks() {
+            for (dst, _) in cfg.successors(label) {
+                preds.entry(dst).or_default().push(*label);
+            }
+        }
+        let mut connected = BTreeSet::from([cfg.entry()]);
+        let mut stack = vec![cfg.entry()];
+        while let Some(label) = stack.pop() {
+            for (dst, _) in cfg.successors(&label) {
+                if connected.insert(dst) {
+                    stack.push(dst);
+                }
+            }
+        }
+
+        for (label, block) in cfg.blocks() {
+            if live.blocks.contains(label) {
+                continue;
+            }
+            // code marked unreachable by the compiler is dead on purpose
+            if matches!(block.terminator(), Terminator::Unreachable) {
+                continue;
+            }
+            let kind = if connected.contains(label) {
+                DeadCodeKind::Infeasible
+            } else {
+                DeadCodeKind::Disconnected
+            };
+            // inside a dead region, only the blocks entered from live code (or from nowhere)
+            // are reported
+            let entered = preds.get(label).map_or(&[][..], |items| items.as_slice());
+            if !entered.is_empty() && !entered.iter().any(|pred| live.blocks.contains(pred)) {
+                continue;
+            }
+            findings.push(DeadCode {
+                function: name.clone(),
+                block: *label,
+                kind,
+            });
+        }
+        for (label, value) in &live.constants {
+            findings.push(DeadCode {
+                function: name.clone(),
+                block: *label,
+                kind: DeadCodeKind::ConstantCondition(value.clone()),
+            });
+        }
+    }
+    Ok(findings)
+}
@@ -0,0 +1,571 @@
+use std::fmt::{Display, Formatter};
+
+use serde_json::json;
+
+use crate::analysis::bounds::{check_out_of_bounds, OutOfBoundsKind};
+use crate::analysis::cstring::{check_string_misuses, StringIssue};
+use crate::analysis::dataflow::Findings;
+use crate::analysis::deadcode::{check_dead_code, DeadCodeKind};
+use crate::analysis::escape::{check_stack_leaks, EscapeRoute};
+use crate::analysis::lifetime::{check_heap_lifetimes, MisuseKind};
+use crate::analysis::overflow::{check_overflows, OverflowKind};
+use crate::analysis::pointer::{
+    check_null_dereferences, validate_null_checks, NullCheckVerdict, NullDerefKind,
+};
+use crate::analysis::summary::SolverConfig;
+use crate::analysis::taint::{analyze_taint, TaintSpec};
+use crate::analysis::uninit::{check_uninitialized_reads, UninitKind};
+use crate::analysis::verify::{verify_assertions_with, Domain, Verdict};
+use crate::error::EngineResult;
+use crate::ir::alignment::{check_misaligned_accesses, Misalignment};
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::{Identifier, SourceLocation};
+use crate::ir::defuse::Site;
+
+/// How much attention a diagnostic deserves
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Copy)]
+pub enum Severity {
+    /// a fact worth knowing, e.g., a proved assertion or a redundant check
+    Note,
+    /// a defect that may happen on some executions
+    Warning,
+    /// a defect that happens on every execution reaching the site
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Note => write!(f, "note"),
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A checker reporting diagnostics
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Copy)]
+pub enum Checker {
+    Misaligned,
+    Assertions,
+    NullChecks,
+    NullDerefs,
+    Overflows,
+    OutOfBounds,
+    HeapMisuses,
+    UninitReads,
+    Taint,
+    DeadCode,
+    StackLeaks,
+    StringMisuses,
+}
+
+impl Checker {
+    /// All checkers, in the order they run
+    pub const ALL: [Checker; 12] = [
+        Checker::Misaligned,
+        Checker::Assertions,
+        Checker::NullChecks,
+        Checker::NullDerefs,
+        Checker::Overflows,
+        Checker::OutOfBounds,
+        Checker::HeapMisuses,
+        Checker::UninitReads,
+        Checker::Taint,
+        Checker::DeadCode,
+        Checker::StackLeaks,
+        Checker::StringMisuses,
+    ];
+
+    /// Identifier of the rule the diagnostics of the checker are reported under
+    pub fn rule(&self) -> &'static str {
+        match self {
+            Self::Misaligned => "misaligned-access",
+            Self::Assertions => "assertion",
+            Self::NullChecks => "redundant-null-check",
+            Self::NullDerefs => "null-dereference",
+            Self::Overflows => "integer-overflow",
+            Self::OutOfBounds => "out-of-bounds",
+            Self::HeapMisuses => "heap-misuse",
+            Self::UninitReads => "uninitialized-read",
+            Self::Taint => "tainted-flow",
+            Self::DeadCode => "dead-code",
+            Self::StackLeaks => "stack-address-leak",
+            Self::StringMisuses => "string-overflow",
+        }
+    }
+
+    /// The checker reporting under the rule id
+    pub fn from_rule(rule: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|checker| checker.rule() == rule)
+    }
+
+    /// What the rule looks for, in one sentence
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Misaligned => "Loads and stores claiming more alignment than their pointers have",
+            Self::Assertions => "User assertions treated as proof obligations",
+            Self::NullChecks => "Null checks whose outcome is decided by the pointer states",
+            Self::NullDerefs => "Loads, stores, and GEPs through pointers that may be null",
+            Self::Overflows => "Arithmetic promised not to wrap that may overflow",
+            Self::OutOfBounds => "GEPs, loads, and stores that may index past their allocations",
+            Self::HeapMisuses => "Uses and frees of heap objects that may be freed already",
+            Self::UninitReads => "Loads from stack slots that may not be initialized",
+            Self::Taint => "Tainted flows into sinks, as configured by the taint spec",
+            Self::DeadCode => "Blocks that never execute and branches that always go one way",
+            Self::StackLeaks => "Stack addresses that may outlive their functions",
+            Self::StringMisuses => {
+                "String functions that may overflow their buffers or read unterminated strings"
+            }
+        }
+    }
+}
+
+impl Display for Checker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.rule())
+    }
+}
+
+/// A finding reported by a checker
+pub struct Diagnostic {
+    pub checker: Checker,
+    pub severity: Severity,
+    pub message: String,
+    pub function: Identifier,
+    /// the instruction or terminator, `None` for a finding about the whole function
+    pub site: Option<Site>,
+    /// where the site comes from in the source code, if known
+    pub source: Option<SourceLocation>,
+}
+
+impl Diagnostic {
+    pub fn new(
+        checker: Checker,
+        severity: Severity,
+        function: &Identifier,
+        site: Option<Site>,
+        message: String,
+    ) -> Self {
+        Self {
+            checker,
+            severity,
+            message,
+            function: function.clone(),
+            site,
+            source: None,
+        }
+    }
+
+    /// The diagnostic as a SARIF result
+    fn to_sarif(&self) -> serde_json::Value {
+        let index = Checker::ALL
+            .iter()
+            .position(|checker| *checker == self.checker)
+            .expect("every checker is listed");
+        let name = match &self.site {
+            None => self.function.to_string(),
+            Some(site) => format!("{}:{}", self.function, site),
+        };
+        let mut location = json!({
+            "logicalLocations": [{
+                "name": name,
+                "fullyQualifiedName": format!("@{}", name),
+                "kind": "function",
+            }],
+        });
+        if let Some(source) = &self.source {
+            // SARIF counts lines and columns from 1, leaving 0 (i.e., unknown) out
+            let mut physical = json!({
+                "artifactLocation": { "uri": source.file },
+            });
+            if source.line != 0 {
+                physical["region"] = json!({ "startLine": source.line });
+                if source.column != 0 {
+                    physical["region"]["startColumn"] = json!(source.column);
+                }
+            }
+            location["physicalLocation"] = physical;
+        }
+        json!({
+            "ruleId": self.checker.rule(),
+            "ruleIndex": index,
+            "level": self.severity.to_string(),
+            "message": { "text": self.message },
+            "locations": [location],
+        })
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}[{}] @{}", self.severity, self.checker, self.function)?;
+        if let Some(site) = &self.site {
+            write!(f, ":{}", site)?;
+        }
+        if let Some(source) = &self.source {
+            write!(f, " ({})", source)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Diagnostics collected from the checkers
+#[derive(Default)]
+pub struct Diagnostics {
+    items: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.items.push(diagnostic);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.items.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Diagnostic> {
+        self.items.iter_mut()
+    }
+
+    /// Keep only the diagnostics satisfying the predicate
+    pub fn retain(&mut self, keep: impl FnMut(&Diagnostic) -> bool) {
+        self.items.retain(keep);
+    }
+
+    /// Number of diagnostics of the severity
+    pub fn count(&self, severity: Severity) -> usize {
+        self.items
+            .iter()
+            .filter(|item| item.severity == severity)
+            .count()
+    }
+
+    /// Order the diagnostics by location, then by checker
+    pub fn sort(&mut self) {
+        self.items.sort_by(|a, b| {
+            (&a.function, &a.site, a.checker).cmp(&(&b.function, &b.site, b.checker))
+        });
+    }
+
+    /// The diagnostics as a SARIF 2.1.0 log with a single run, listing every checker as a rule
+    pub fn to_sarif(&self) -> serde_json::Value {
+        let rules: Vec<_> = Checker::ALL
+            .iter()
+            .map(|checker| {
+                json!({
+                    "id": checker.rule(),
+                    "shortDescription": { "text": checker.description() },
+                })
+            })
+            .collect();
+        let results: Vec<_> = self.items.iter().map(|item| item.to_sarif()).collect();
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "libra",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    },
+                },
+                "results": results,
+            }],
+        })
+    }
+}
+
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for item in &self.items {
+            writeln!(f, "{}", item)?;
+        }
+        writeln!(
+            f,
+            "{} error(s), {} warning(s), {} note(s)",
+            self.count(Severity::Error),
+            self.count(Severity::Warning),
+            self.count(Severity::Note)
+        )
+    }
+}
+
+/// Utility: the severity of a finding that is either certain or not
+fn certainty(definite: bool) -> Severity {
+    if definite {
+        Severity::Error
+    } else {
+        Severity::Warning
+    }
+}
+
+/// Utility: a note on each function whose analyses ran out of the budget for the checker
+fn note_exhausted<T>(diagnostics: &mut Diagnostics, checker: Checker, findings: &Findings<T>) {
+    for (function, exhaustion) in &findings.exhausted {
+        diagnostics.push(Diagnostic::new(
+            checker,
+            Severity::Note,
+            function,
+            None,
+            format!("analysis gave up ({}), findings are imprecise", exhaustion),
+        ));
+    }
+}
+
+/// Run the checkers on the module and collect their findings, where the taint checker only runs
+/// with a spec
+pub fn run_checkers(
+    module: &Module,
+    checkers: &[Checker],
+    taint: Option<&TaintSpec>,
+) -> EngineResult<Diagnostics> {
+    run_checkers_with(
+        module,
+        checkers,
+        &Domain::ALL,
+        &SolverConfig::default(),
+        taint,
+    )
+}
+
+/// Run the checkers on the module, with the assertions discharged by the given domains and the
+/// analyses as precise as asked for
+pub fn run_checkers_with(
+    module: &Module,
+    checkers: &[Checker],
+    domains: &[Domain],
+    solver: &SolverConfig,
+    taint: Option<&TaintSpec>,
+) -> EngineResult<Diagnostics> {
+    let mut diagnostics = Diagnostics::new();
+    for checker in checkers {
+        let checker = *checker;
+        match checker {
+            Checker::Misaligned => {
+                for item in check_misaligned_accesses(module)? {
+                    diagnostics.push(Diagnostic::new(
+                        checker,
+                        certainty(item.kind == Misalignment::Definite),
+                        &item.function,
+                        Some(item.site),
+                        format!(
+                            "access claims align {} but the pointer is {}-aligned",
+                            item.claimed, item.provable
+                        ),
+                    ));
+                }
+            }
+            Checker::Assertions => {
+                let findings = verify_assertions_with(module, domains, solver)?;
+                note_exhausted(&mut diagnostics, checker, &findings);
+                for item in findings {
+                    let severity = match &item.verdict {
+                        Verdict::Proved => Severity::Note,
+                        Verdict::Unknown => Severity::Warning,
+                        Verdict::Violated(_) => Severity::Error,
+                    };
+                    diagnostics.push(Diagnostic::new(
+                        checker,
+                        severity,
+                        &item.function,
+                        Some(item.site),
+                        format!("{} {}", item.obligation, item.verdict),
+                    ));
+                }
+            }
+            Checker::NullChecks => {
+                let findings = validate_null_checks(module, &solver.widening)?;
+                note_exhausted(&mut diagnostics, checker, &findings);
+                for item in findings {
+                    if item.verdict == NullCheckVerdict::Necessary {
+                        continue;
+                    }
+                    diagnostics.push(Diagnostic::new(
+                        checker,
+                        Severity::Note,
+                        &item.function,
+                        Some(item.site),
+                        format!("null check on {} is {}", item.pointer, item.verdict),
+                    ));
+                }
+            }
+            Checker::NullDerefs => {
+                let findings = check_null_dereferences(module, &solver.widening)?;
+                note_exhausted(&mut diagnostics, checker, &findings);
+                for item in findings {
+                    diagnostics.push(Diagnostic::new(
+                        checker,
+                        certainty(item.kind == NullDerefKind::Definite),
+                        &item.function,
+                        Some(item.site),
+                        format!("{} through {} which may be null", item.access, item.pointer),
+                    ));
+                }
+            }
+            Checker::Overflows => {
+                let findings = check_overflows(module, solver)?;
+                note_exhausted(&mut diagnostics, checker, &findings);
+                for summary in findings {
+                    for item in summary.sites {
+                        diagnostics.push(Diagnostic::new(
+                            checker,
+                            certainty(item.kind == OverflowKind::Definite),
+                            &summary.function,
+                            Some(item.site),
+                            format!(
+                                "{} {} i{} yields {}",
+                                item.opcode, item.wrap, item.bits, item.range
+                            ),
+                        ));
+                    }
+                }
+            }
+            Checker::OutOfBounds => {
+                let findings = check_out_of_bounds(module, solver)?;
+                note_exhausted(&mut diagnostics, checker, &findings);
+                for item in findings {
+                    diagnostics.push(Diagnostic::new(
+                        checker,
+                        certainty(item.kind == OutOfBoundsKind::Definite),
+                        &item.function,
+                        Some(item.site),
+                        format!(
+                            "{} of {} bytes at offset {} of {} sized {} bytes",
+                            item.access, item.width, item.offset, item.object, item.size
+                        ),
+                    ));
+                }
+            }
+            Checker::HeapMisuses => {
+                let findings = check_heap_lifetimes(module, solver)?;
+                note_exhausted(&mut diagnostics, checker, &findings);
+                for item in findings {
+                    diagnostics.push(Diagnostic::new(
+                        checker,
+                        certainty(item.kind == MisuseKind::Definite),
+                        &item.function,
+                        Some(item.site),
+                        format!("{}: {} through {}", item.misuse, item.access, item.pointer),
+                    ));
+                }
+            }
+            Checker::UninitReads => {
+                let findings = check_uninitialized_reads(module, solver)?;
+                note_exhausted(&mut diagnostics, checker, &findings);
+                for item in findings {
+                    diagnostics.push(Diagnostic::new(
+                        checker,
+                        certainty(item.kind == UninitKind::Definite),
+                        &item.function,
+                        Some(item.site),
+                        format!(
+                            "load of {} bytes at offset {} of {} reads uninitialized memory",
+                            item.width, item.offset, item.object
+                        ),
+                    ));
+                }
+            }
+            Checker::Taint => {
+                let spec = match taint {
+                    None => continue,
+                    Some(spec) => spec,
+                };
+                let findings = analyze_taint(module, spec, &solver.widening)?;
+                note_exhausted(&mut diagnostics, checker, &findings);
+                for item in findings {
+                    diagnostics.push(Diagnostic::new(
+                        checker,
+                        Severity::Error,
+                        &item.function,
+                        Some(item.site),
+                        format!(
+                            "argument {} of {} is tainted by {}",
+                            item.arg, item.sink, item.origin
+                        ),
+                    ));
+                }
+            }
+            Checker::DeadCode => {
+                let findings = check_dead_code(module, solver)?;
+                note_exhausted(&mut diagnostics, checker, &findings);
+                for item in findings {
+                    let message = match &item.kind {
+                        DeadCodeKind::Disconnected => {
+                            format!("block {} is not connected to the entry", item.block)
+                        }
+                        DeadCodeKind::Infeasible => {
+                            format!("block {} is unreachable on every path", item.block)
+                        }
+                        DeadCodeKind::ConstantCondition(value) => {
+                            format!("condition of the terminator is always {}", value)
+                        }
+                    };
+                    diagnostics.push(Diagnostic::new(
+                        checker,
+                        Severity::Warning,
+                        &item.function,
+                        Some(Site::Terminator { block: item.block }),
+                        message,
+                    ));
+                }
+            }
+            Checker::StackLeaks => {
+                for item in check_stack_leaks(module)? {
+                    diagnostics.push(Diagnostic::new(
+                        checker,
+                        certainty(item.route == EscapeRoute::Return),
+                        &item.function,
+                        Some(item.site),
+                        format!("stack address {}", item.route),
+                    ));
+                }
+            }
+            Checker::StringMisuses => {
+                let findings = check_string_misuses(module, solver)?;
+                note_exhausted(&mut diagnostics, checker, &findings);
+                for item in findings {
+                    let message = match &item.issue {
+                        StringIssue::Overflow { needed, capacity } => format!(
+                            "{} writes {} bytes into {} with {} bytes left",
+                            item.callee, needed, item.object, capacity
+                        ),
+                        StringIssue::Unterminated(index) => format!(
+                            "{} reads argument {} from {} which is unterminated",
+                            item.callee, index, item.object
+                        ),
+                    };
+                    diagnostics.push(Diagnostic::new(
+                        checker,
+                        certainty(item.kind == OutOfBoundsKind::Definite),
+                        &item.function,
+                        Some(item.site),
+                        message,
+                    ));
+                }
+            }
+        }
+    }
+
+    // map the sites back to the source code where debug info is available
+    for item in &mut diagnostics.items {
+        let cfg = module
+            .get_function(item.function.as_ref())
+            .and_then(|func| func.body.as_ref());
+        if let (Some(cfg), Some(site)) = (cfg, &item.site) {
+            item.source = cfg.location(site).cloned();
+        }
+    }
+    diagnostics.sort();
+    Ok(diagnostics)
+}
@@ -0,0 +1,220 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use crate::analysis::libc::find_model;
+use crate::analysis::memory::AllocSite;
+use crate::analysis::pointsto::{analyze_points_to, Node, PointsTo};
+use crate::error::EngineResult;
+use crate::ir::bridge::instruction::{Instruction, Terminator};
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::value::Value;
+use crate::ir::defuse::Site;
+
+/// How the address of a stack object leaves its function
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone)]
+pub enum EscapeRoute {
+    /// stored in a global variable
+    Global(Identifier),
+    /// stored in a heap object
+    Heap,
+    /// returned to the callers
+    Return,
+    /// passed to a callee that may keep it
+    Call(Identifier),
+}
+
+impl EscapeRoute {
+    /// Whether the address may outlive the object, i.e., survive the return of its function
+    pub fn outlives(&self) -> bool {
+        !matches!(self, Self::Call(_))
+    }
+}
+
+impl Display for EscapeRoute {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Global(name) => write!(f, "stored in @{}", name),
+            Self::Heap => write!(f, "stored in the heap"),
+            Self::Return => write!(f, "returned"),
+            Self::Call(name) => write!(f, "passed to @{}", name),
+        }
+    }
+}
+
+/// Stack objects whose address may be seen outside their function, with the routes
+#[derive(Default)]
+pub struct Escapes {
+    routes: BTreeMap<AllocSite, BTreeSet<EscapeRoute>>,
+}
+
+impl Escapes {
+    /// Whether the stack object may be accessed by other functions, so that a call may update
+    /// it and a store to it is only strong between calls
+    pub fn escapes(&self, site: &AllocSite) -> bool {
+        self.routes.contains_key(site)
+    }
+
+    /// Routes along which the stack object escapes, empty if it does not
+    pub fn routes(&self, site: &AllocSite) -> BTreeSet<EscapeRoute> {
+        self.routes.get(site).cloned().unwrap_or_default()
+    }
+
+    /// Escaping stack objects of a function
+    pub fn escaped_in<'a>(
+        &'a self,
+        function: &'a Identifier,
+    ) -> impl Iterator<Item = &'a AllocSite> + 'a {
+        self.routes.keys().filter(move |site| match site {
+            AllocSite::Stack {
+                function: owner, ..
+            } => owner == function,
+            _ => false,
+        })
+    }
+}
+
+/// Utility: whether a declared callee may keep a pointer passed to it, unless it is an
+/// intrinsic, a modeled library function, or writes no memory
+fn may_retain(module: &Module, callee: &Identifier) -> bool {
+    let name: &str = callee.as_ref();
+    match module.get_function(name) {
+        Some(func) if func.exact_body().is_some() => false,
+        _ => {
+            !name.starts_with("llvm.")
+                && find_model(name).is_none()
+                && module.effect(name).writes_memory()
+        }
+    }
+}
+
+/// Find the stack objects escaping their functions, given the points-to sets of the module
+pub(crate) fn analyze_escapes(module: &Module, global: &PointsTo) -> Escapes {
+    let mut routes: BTreeMap<AllocSite, BTreeSet<EscapeRoute>> = BTreeMap::new();
+    let mut add = |site: &AllocSite, route: EscapeRoute| {
+        if matches!(site, AllocSite::Stack { .. }) {
+            routes.entry(site.clone()).or_default().insert(route);
+        }
+    };
+
+    // addresses held by the nodes that outlive a register
+    for (node, set) in global.nodes() {
+        let route = match node {
+            Node::Register { .. } => continue,
+            Node::Argument { function, slot: _ } => EscapeRoute::Call(function.clone()),
+            // objects of the callers come back through their callees, escaping there already
+            Node::Return(function) => {
+                for site in set {
+                    if matches!(site, AllocSite::Stack { function: owner, .. } if owner == function)
+                    {
+                        add(site, EscapeRoute::Return);
+                    }
+                }
+                continue;
+            }
+            Node::Object(AllocSite::Global(name)) => EscapeRoute::Global(name.clone()),
+            Node::Object(AllocSite::Heap { .. }) => EscapeRoute::Heap,
+            // stack objects pass their routes on below
+            Node::Object(AllocSite::Stack { .. }) => continue,
+        };
+        for site in set {
+            add(site, route.clone());
+        }
+    }
+
+    // declared callees are not linked in the points-to sets
+    for (name, func) in module.functions() {
+        let cfg = match &func.body {
+            None => continue,
+            Some(cfg) => cfg,
+        };
+        for (_, block) in cfg.blocks() {
+            let calls = block
+                .instructions()
+                .iter()
+                .filter_map(|inst| match inst {
+                    Instruction::CallDirect { function, args, .. } => Some((function, args)),
+                    _ => None,
+                })
+                .chain(match block.terminator() {
+                    Terminator::InvokeDirect { function, args, .. } => Some((function, args)),
+                    _ => None,
+                });
+            for (callee, args) in calls {
+                if !may_retain(module, callee) {
+                    continue;
+                }
+                for arg in args.iter().filter(|arg| !matches!(arg, Value::Constant(_))) {
+                    let node = Node::of_value(name, arg).expect("non-constant value");
+                    for site in global.points_to(&node) {
+                        add(&site, EscapeRoute::Call(callee.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    // addresses stored in an escaping stack object escape along with it
+    loop {
+        let mut changed = false;
+        for (node, set) in global.nodes() {
+            let holder = match node {
+                Node::Object(site @ AllocSite::Stack { .. }) => site,
+                _ => continue,
+            };
+            let inherited = match routes.get(holder) {
+                None => continue,
+                Some(items) => items.clone(),
+            };
+            for site in set.iter().filter(|s| matches!(s, AllocSite::Stack { .. })) {
+                let entry = routes.entry(site.clone()).or_default();
+                let count = entry.len();
+                entry.extend(inherited.iter().cloned());
+                changed |= entry.len() != count;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    Escapes { routes }
+}
+
+/// A stack address that may be used after its function returns
+pub struct StackLeak {
+    pub function: Identifier,
+    /// the alloca of the object
+    pub site: Site,
+    pub route: EscapeRoute,
+}
+
+impl Display for StackLeak {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "@{}:{}: stack address {}",
+            self.function, self.site, self.route
+        )
+    }
+}
+
+/// Report the stack objects whose address may outlive them
+pub fn check_stack_leaks(module: &Module) -> EngineResult<Vec<StackLeak>> {
+    let global = analyze_points_to(module)?;
+    let mut findings = vec![];
+    for (object, routes) in &global.escapes().routes {
+        let (function, site) = match object {
+            AllocSite::Stack { function, site } => (function, site),
+            _ => continue,
+        };
+        for route in routes.iter().filter(|route| route.outlives()) {
+            findings.push(StackLeak {
+                function: function.clone(),
+                site: *site,
+                route: route.clone(),
+            });
+        }
+    }
+    Ok(findings)
+}
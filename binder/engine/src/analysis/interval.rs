@@ -0,0 +1,665 @@
+//! The classical interval domain over bitvectors.
+//!
+//! The bridge does not keep the signedness of operations, so intervals hold the signed reading
+//! of a bitvector (except 1-bit vectors, i.e., booleans, which are read as unsigned). Transfer
+//! functions of operations whose semantics depend on signedness (e.g., division) are precise only
+//! when both readings agree, i.e., when the operands are known to be non-negative.
+
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+use rug::Integer;
+
+use crate::analysis::dataflow::{Filter, VariableStore};
+use crate::analysis::lattice::Lattice;
+use crate::analysis::variable::{signed_reading, Variable};
+use crate::ir::bridge::constant::{Constant, NumValue};
+use crate::ir::bridge::instruction::{
+    BinaryOpArith, BinaryOpBitwise, BinaryOpShift, ComparePredicate, Instruction,
+};
+use crate::ir::bridge::typing::{NumRepr, Type};
+use crate::ir::bridge::value::{RegisterSlot, Value};
+
+/// An end of an interval
+#[derive(Eq, PartialEq, Clone)]
+pub enum Bound {
+    NegInf,
+    Finite(Integer),
+    PosInf,
+}
+
+impl Bound {
+    fn rank(&self) -> u8 {
+        match self {
+            Self::NegInf => 0,
+            Self::Finite(_) => 1,
+            Self::PosInf => 2,
+        }
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Finite(a), Self::Finite(b)) => Self::Finite(Integer::from(a + b)),
+            (Self::NegInf, Self::PosInf) | (Self::PosInf, Self::NegInf) => {
+                unreachable!("intervals never have an infinite bound on the wrong side")
+            }
+            (Self::NegInf, _) | (_, Self::NegInf) => Self::NegInf,
+            (Self::PosInf, _) | (_, Self::PosInf) => Self::PosInf,
+        }
+    }
+
+    fn neg(&self) -> Self {
+        match self {
+            Self::NegInf => Self::PosInf,
+            Self::Finite(v) => Self::Finite(Integer::from(-v)),
+            Self::PosInf => Self::NegInf,
+        }
+    }
+
+    fn signum(&self) -> Ordering {
+        match self {
+            Self::NegInf => Ordering::Less,
+            Self::Finite(v) => v.cmp0(),
+            Self::PosInf => Ordering::Greater,
+        }
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Finite(a), Self::Finite(b)) => Self::Finite(Integer::from(a * b)),
+            _ => match (self.signum(), other.signum()) {
+                (Ordering::Equal, _) | (_, Ordering::Equal) => Self::Finite(Integer::new()),
+                (a, b) if a == b => Self::PosInf,
+                _ => Self::NegInf,
+            },
+        }
+    }
+}
+
+impl PartialOrd for Bound {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Bound {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Finite(a), Self::Finite(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl Display for Bound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NegInf => write!(f, "-oo"),
+            Self::Finite(v) => write!(f, "{}", v),
+            Self::PosInf => write!(f, "+oo"),
+        }
+    }
+}
+
+/// A set of integers represented by its bounds
+#[derive(Eq, PartialEq, Clone)]
+pub enum Interval {
+    Empty,
+    Range { lo: Bound, hi: Bound },
+}
+
+impl Interval {
+    pub fn new(lo: Bound, hi: Bound) -> Self {
+        // no integers lie beyond the infinities
+        if lo > hi || lo == Bound::PosInf || hi == Bound::NegInf {
+            Self::Empty
+        } else {
+            Self::Range { lo, hi }
+        }
+    }
+
+    pub fn singleton(value: Integer) -> Self {
+        Self::new(Bound::Finite(value.clone()), Bound::Finite(value))
+    }
+
+    pub fn finite(lo: Integer, hi: Integer) -> Self {
+        Self::new(Bound::Finite(lo), Bound::Finite(hi))
+    }
+
+    /// All values representable in a bitvector of the width
+    pub fn full(bits: usize) -> Self {
+        if bits == 1 {
+            return Self::finite(Integer::new(), Integer::from(1));
+        }
+        let half = Integer::from(1) << (bits as u32 - 1);
+        Self::finite(Integer::from(-&half), half - 1)
+    }
+
+    /// The interval of a constant bitvector, given its unsigned value
+    pub fn constant(bits: usize, value: &Integer) -> Self {
+        Self::singleton(signed_reading(bits, value))
+    }
+
+    pub fn bounds(&self) -> Option<(&Bound, &Bound)> {
+        match self {
+            Self::Empty => None,
+            Self::Range { lo, hi } => Some((lo, hi)),
+        }
+    }
+
+    /// The only value in the interval, if it is a singleton
+    pub fn as_singleton(&self) -> Option<&Integer> {
+        match self.bounds()? {
+            (Bound::Finite(lo), Bound::Finite(hi)) if lo == hi => Some(lo),
+            _ => None,
+        }
+    }
+
+    pub fn contains(&self, value: &Integer) -> bool {
+        match self.bounds() {
+            None => false,
+            Some((lo, hi)) => {
+                let v = Bound::Finite(value.clone());
+                lo <= &v && &v <= hi
+            }
+        }
+    }
+
+    /// Whether all values are non-negative, in which case signed and unsigned readings agree
+    pub fn is_non_negative(&self) -> bool {
+        match self.bounds() {
+            None => true,
+            Some((lo, _)) => lo >= &Bound::Finite(Integer::new()),
+        }
+    }
+
+    /// Fit the interval into a bitvector of the width, giving up on overflows
+    pub fn fit(self, bits: usize) -> Self {
+        let full = Self::full(bits);
+        if self.leq(&full) {
+            self
+        } else {
+            full
+        }
+    }
+
+    fn lift2(&self, other: &Self, op: impl Fn(&Bound, &Bound, &Bound, &Bound) -> Self) -> Self {
+        match (self.bounds(), other.bounds()) {
+            (Some((l1, h1)), Some((l2, h2))) => op(l1, h1, l2, h2),
+            _ => Self::Empty,
+        }
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        self.lift2(other, |l1, h1, l2, h2| Self::new(l1.add(l2), h1.add(h2)))
+    }
+
+    pub fn neg(&self) -> Self {
+        match self.bounds() {
+            None => Self::Empty,
+            Some((lo, hi)) => Self::new(hi.neg(), lo.neg()),
+        }
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        self.lift2(other, |l1, h1, l2, h2| {
+            let products = [l1.mul(l2), l1.mul(h2), h1.mul(l2), h1.mul(h2)];
+            let lo = products.iter().min().unwrap().clone();
+            let hi = products.iter().max().unwrap().clone();
+            Self::new(lo, hi)
+        })
+    }
+
+    /// Division truncating towards zero, for non-negative operands only
+    fn div_non_negative(&self, other: &Self) -> Option<Self> {
+        let (l1, h1) = self.bounds()?;
+        let (l2, h2) = other.bounds()?;
+        let d_lo = match l2 {
+            Bound::Finite(v) if *v > 0 => v.clone(),
+            Bound::Finite(_) => Integer::from(1),
+            _ => return None,
+        };
+        let lo = match (l1, h2) {
+            (Bound::Finite(_), Bound::Finite(d)) if *d == 0 => return None,
+            (Bound::Finite(n), Bound::Finite(d)) => Bound::Finite(Integer::from(n / d)),
+            (Bound::Finite(_), Bound::PosInf) => Bound::Finite(Integer::new()),
+            _ => return None,
+        };
+        let hi = match h1 {
+            Bound::Finite(n) => Bound::Finite(Integer::from(n / &d_lo)),
+            Bound::PosInf => Bound::PosInf,
+            Bound::NegInf => return None,
+        };
+        Some(Self::new(lo, hi))
+    }
+
+    /// Remainder, for non-negative operands only
+    fn rem_non_negative(&self, other: &Self) -> Option<Self> {
+        let (_, h1) = self.bounds()?;
+        let (_, h2) = other.bounds()?;
+        let hi = match h2 {
+            Bound::Finite(d) if *d > 0 => Bound::Finite(Integer::from(d - 1)).min(h1.clone()),
+            Bound::Finite(_) => return None,
+            _ => h1.clone(),
+        };
+        Some(Self::new(Bound::Finite(Integer::new()), hi))
+    }
+
+    /// Largest value with all bits set that covers the upper bound of a non-negative interval
+    fn bit_cover(&self) -> Option<Integer> {
+        match self.bounds()? {
+            (_, Bound::Finite(hi)) => {
+                let bits = hi.significant_bits();
+                Some((Integer::from(1) << bits) - 1)
+            }
+            _ => None,
+        }
+    }
+
+    /// Three-valued evaluation of a comparison
+    pub fn compare(&self, predicate: &ComparePredicate, other: &Self) -> Option<bool> {
+        let (l1, h1) = self.bounds()?;
+        let (l2, h2) = other.bounds()?;
+        let (always, never) = match predicate {
+            ComparePredicate::EQ => (
+                self.as_singleton().is_some() && self == other,
+                h1 < l2 || h2 < l1,
+            ),
+            ComparePredicate::NE => (
+                h1 < l2 || h2 < l1,
+                self.as_singleton().is_some() && self == other,
+            ),
+            ComparePredicate::LT => (h1 < l2, l1 >= h2),
+            ComparePredicate::LE => (h1 <= l2, l1 > h2),
+            ComparePredicate::GT => (l1 > h2, h1 <= l2),
+            ComparePredicate::GE => (l1 >= h2, h1 < l2),
+        };
+        if always {
+            Some(true)
+        } else if never {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Refine the interval under the assumption that `self <predicate> other` holds
+    pub fn refine(&self, predicate: &ComparePredicate, other: &Self) -> Self {
+        let (lo, hi) = match other.bounds() {
+            None => return Self::Empty,
+            Some(bounds) => bounds,
+        };
+        let one = Bound::Finite(Integer::from(1));
+        let constraint = match predicate {
+            ComparePredicate::EQ => other.clone(),
+            ComparePredicate::NE => match other.as_singleton() {
+                None => return self.clone(),
+                Some(v) => {
+                    // only a bound can be excluded
+                    let v = Bound::Finite(v.clone());
+                    return match self.bounds() {
+                        None => Self::Empty,
+                        Some((l, h)) if l == &v => Self::new(l.add(&one), h.clone()),
+                        Some((l, h)) if h == &v => Self::new(l.clone(), h.add(&one.neg())),
+                        Some(_) => self.clone(),
+                    };
+                }
+            },
+            ComparePredicate::LT => Self::new(Bound::NegInf, hi.add(&one.neg())),
+            ComparePredicate::LE => Self::new(Bound::NegInf, hi.clone()),
+            ComparePredicate::GT => Self::new(lo.add(&one), Bound::PosInf),
+            ComparePredicate::GE => Self::new(lo.clone(), Bound::PosInf),
+        };
+        self.meet(&constraint)
+    }
+
+    /// Widening that jumps to the next threshold instead of infinity when one exists
+    pub fn widen_with_thresholds(&self, other: &Self, thresholds: &BTreeSet<Integer>) -> Self {
+        let ((l1, h1), (l2, h2)) = match (self.bounds(), other.bounds()) {
+            (None, _) => return other.clone(),
+            (_, None) => return self.clone(),
+            (Some(a), Some(b)) => (a, b),
+        };
+        let lo = if l2 < l1 {
+            let below = thresholds
+                .iter()
+                .rev()
+                .find(|t| &Bound::Finite((*t).clone()) <= l2);
+            below.map_or(Bound::NegInf, |t| Bound::Finite(t.clone()))
+        } else {
+            l1.clone()
+        };
+        let hi = if h2 > h1 {
+            let above = thresholds
+                .iter()
+                .find(|t| &Bound::Finite((*t).clone()) >= h2);
+            above.map_or(Bound::PosInf, |t| Bound::Finite(t.clone()))
+        } else {
+            h1.clone()
+        };
+        Self::new(lo, hi)
+    }
+}
+
+impl Lattice for Interval {
+    fn bottom() -> Self {
+        Self::Empty
+    }
+
+    fn top() -> Self {
+        Self::Range {
+            lo: Bound::NegInf,
+            hi: Bound::PosInf,
+        }
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        match (self.bounds(), other.bounds()) {
+            (None, _) => true,
+            (_, None) => false,
+            (Some((l1, h1)), Some((l2, h2))) => l2 <= l1 && h1 <= h2,
+        }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (self.bounds(), other.bounds()) {
+            (None, _) => other.clone(),
+            (_, None) => self.clone(),
+            (Some((l1, h1)), Some((l2, h2))) => Self::new(l1.min(l2).clone(), h1.max(h2).clone()),
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        match (self.bounds(), other.bounds()) {
+            (None, _) | (_, None) => Self::Empty,
+            (Some((l1, h1)), Some((l2, h2))) => Self::new(l1.max(l2).clone(), h1.min(h2).clone()),
+        }
+    }
+
+    fn widen(&self, other: &Self) -> Self {
+        self.widen_with_thresholds(other, &BTreeSet::new())
+    }
+
+    fn widen_with_thresholds(&self, other: &Self, thresholds: &BTreeSet<Integer>) -> Self {
+        Interval::widen_with_thresholds(self, other, thresholds)
+    }
+
+    /// Narrowing that only refines the infinite bounds
+    fn narrow(&self, other: &Self) -> Self {
+        match (self.bounds(), other.bounds()) {
+            (None, _) | (_, None) => Self::Empty,
+            (Some((l1, h1)), Some((l2, h2))) => {
+                let lo = if l1 == &Bound::NegInf { l2 } else { l1 };
+                let hi = if h1 == &Bound::PosInf { h2 } else { h1 };
+                Self::new(lo.clone(), hi.clone())
+            }
+        }
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty"),
+            Self::Range { lo, hi } => write!(f, "[{}, {}]", lo, hi),
+        }
+    }
+}
+
+impl VariableStore<Interval> {
+    /// Refine the intervals of the variables under a filter, bottom if the filter cannot hold
+    pub fn assume(&self, filter: &Filter) -> Self {
+        let mut store = self.clone();
+        let eval = |value: &Value, bits: usize| match Variable::of_value(value) {
+            None => eval_value(value, bits, &|_| Interval::top()),
+            Some(var) => self.get(&var).meet(&Interval::full(bits)),
+        };
+        let mut refine = |value: &Value, interval: Interval| {
+            if let Some(var) = Variable::of_value(value) {
+                store.set(var, interval);
+            } else if interval.is_bottom() {
+                store = Self::bottom();
+            }
+        };
+        match filter {
+            Filter::Condition { cond, value } => {
+                let outcome = Interval::singleton(Integer::from(*value as u8));
+                refine(cond, eval(cond, 1).meet(&outcome));
+            }
+            Filter::Compare {
+                bits,
+                predicate,
+                lhs,
+                rhs,
+            } => {
+                let l = eval(lhs, *bits);
+                let r = eval(rhs, *bits);
+
+                // the signedness of ordered comparisons is lost, non-negative readings agree
+                let ordered = !matches!(predicate, ComparePredicate::EQ | ComparePredicate::NE);
+                if ordered && !(l.is_non_negative() && r.is_non_negative()) {
+                    return store;
+                }
+                refine(lhs, l.refine(predicate, &r));
+                refine(rhs, r.refine(&predicate.swap(), &l));
+            }
+            Filter::OneOf { bits, cond, values } => {
+                let allowed = values.iter().fold(Interval::bottom(), |acc, v| {
+                    acc.join(&Interval::constant(*bits, v))
+                });
+                refine(cond, eval(cond, *bits).meet(&allowed));
+            }
+            Filter::NoneOf { bits, cond, values } => {
+                let mut current = eval(cond, *bits);
+                for v in values {
+                    current = current.refine(&ComparePredicate::NE, &Interval::constant(*bits, v));
+                }
+                refine(cond, current);
+            }
+        }
+        store
+    }
+}
+
+/// Evaluate an operand, with arguments and registers looked up in the environment
+pub fn eval_value(value: &Value, bits: usize, env: &impl Fn(&Variable) -> Interval) -> Interval {
+    match value {
+        Value::Constant(Constant::NumOne {
+            bits: _,
+            value: NumValue::Int(v),
+        }) => Interval::constant(bits, v),
+        Value::Constant(_) => Interval::full(bits),
+        Value::Argument { index, ty: _ } => {
+            env(&Variable::Argument(*index)).meet(&Interval::full(bits))
+        }
+        Value::Register { index, ty: _ } => {
+            env(&Variable::Register(*index)).meet(&Interval::full(bits))
+        }
+    }
+}
+
+/// Transfer function of the scalar integer instructions, yielding the interval of the result
+pub fn transfer(
+    inst: &Instruction,
+    env: &impl Fn(&Variable) -> Interval,
+) -> Option<(RegisterSlot, Interval)> {
+    let item = match inst {
+        Instruction::BinaryArith {
+            bits,
+            number: NumRepr::Int,
+            length: None,
+            opcode,
+            nsw: _,
+            nuw: _,
+            lhs,
+            rhs,
+            result,
+        } => {
+            let l = eval_value(lhs, *bits, env);
+            let r = eval_value(rhs, *bits, env);
+            let v = match opcode {
+                BinaryOpArith::Add => l.add(&r),
+                BinaryOpArith::Sub => l.sub(&r),
+                BinaryOpArith::Mul => l.mul(&r),
+                BinaryOpArith::Div if l.is_non_negative() && r.is_non_negative() => l
+                    .div_non_negative(&r)
+                    .unwrap_or_else(|| Interval::full(*bits)),
+                BinaryOpArith::Mod if l.is_non_negative() && r.is_non_negative() => l
+                    .rem_non_negative(&r)
+                    .unwrap_or_else(|| Interval::full(*bits)),
+                BinaryOpArith::Div | BinaryOpArith::Mod => Interval::full(*bits),
+            };
+            (*result, v.fit(*bits))
+        }
+        Instruction::BinaryBitwise {
+            bits,
+            length: None,
+            opcode,
+            lhs,
+            rhs,
+            result,
+        } => {
+            let l = eval_value(lhs, *bits, env);
+            let r = eval_value(rhs, *bits, env);
+            let zero = Integer::new();
+            let v = match (l.as_singleton(), r.as_singleton()) {
+                (Some(a), Some(b)) => {
+                    let (a, b) = (a.clone(), b.clone());
+                    let folded = match opcode {
+                        BinaryOpBitwise::And => a & b,
+                        BinaryOpBitwise::Or => a | b,
+                        BinaryOpBitwise::Xor => a ^ b,
+                    };
+                    Interval::singleton(folded)
+                }
+                _ if l.is_non_negative() && r.is_non_negative() => match opcode {
+                    BinaryOpBitwise::And => {
+                        let hi = match (l.bounds(), r.bounds()) {
+                            (Some((_, h1)), Some((_, h2))) => h1.min(h2).clone(),
+                            _ => Bound::PosInf,
+                        };
+                        Interval::new(Bound::Finite(zero), hi)
+                    }
+                    BinaryOpBitwise::Or | BinaryOpBitwise::Xor => {
+                        match (l.bit_cover(), r.bit_cover()) {
+                            (Some(a), Some(b)) => Interval::finite(zero, a.max(b)),
+                            _ => Interval::full(*bits),
+                        }
+                    }
+                },
+                _ => Interval::full(*bits),
+            };
+            (*result, v.fit(*bits))
+        }
+        Instruction::BinaryShift {
+            bits,
+            length: None,
+            opcode,
+            lhs,
+            rhs,
+            result,
+        } => {
+            let l = eval_value(lhs, *bits, env);
+            let r = eval_value(rhs, *bits, env);
+            let amount = r.as_singleton().and_then(|v| v.to_u32());
+            let v = match (opcode, amount) {
+                (BinaryOpShift::Shl, Some(n)) if (n as usize) < *bits => {
+                    l.mul(&Interval::singleton(Integer::from(1) << n))
+                }
+                (BinaryOpShift::Shr, Some(n)) if (n as usize) < *bits && l.is_non_negative() => l
+                    .div_non_negative(&Interval::singleton(Integer::from(1) << n))
+                    .unwrap_or_else(|| Interval::full(*bits)),
+                _ => Interval::full(*bits),
+            };
+            (*result, v.fit(*bits))
+        }
+        Instruction::CompareBitvec {
+            bits,
+            number: NumRepr::Int,
+            length: None,
+            predicate,
+            lhs,
+            rhs,
+            result,
+        } => {
+            let l = eval_value(lhs, *bits, env);
+            let r = eval_value(rhs, *bits, env);
+            // the predicate may be signed or unsigned, both readings must agree
+            let outcome = match predicate {
+                ComparePredicate::EQ | ComparePredicate::NE => l.compare(predicate, &r),
+                _ if l.is_non_negative() && r.is_non_negative() => l.compare(predicate, &r),
+                _ => None,
+            };
+            let v = match outcome {
+                None => Interval::full(1),
+                Some(b) => Interval::singleton(Integer::from(b as u8)),
+            };
+            (*result, v)
+        }
+        Instruction::CastBitvecSize {
+            bits_from,
+            bits_into,
+            number: NumRepr::Int,
+            length: None,
+            operand,
+            result,
+        } => {
+            let v = eval_value(operand, *bits_from, env);
+            let v = if bits_into < bits_from {
+                // truncation is exact when no bits are lost
+                let fitted = v.clone().fit(*bits_into);
+                if fitted == v {
+                    v
+                } else {
+                    Interval::full(*bits_into)
+                }
+            } else if *bits_from == 1 {
+                // zero-extension yields 0 or 1, sign-extension 0 or -1
+                match v.as_singleton() {
+                    Some(b) if *b == 0 => v,
+                    _ => Interval::finite(Integer::from(-1), Integer::from(1)),
+                }
+            } else if v.is_non_negative() {
+                v
+            } else {
+                // a negative value may be zero-extended into a large positive one
+                Interval::full(*bits_into)
+            };
+            (*result, v)
+        }
+        Instruction::ITEOne {
+            cond,
+            then_value,
+            else_value,
+            result,
+        } => {
+            let bits = match then_value {
+                Value::Constant(Constant::NumOne { bits, .. }) => *bits,
+                Value::Register { ty, .. } | Value::Argument { ty, .. } => match ty {
+                    Type::Bitvec {
+                        bits,
+                        number: NumRepr::Int,
+                        length: None,
+                    } => *bits,
+                    _ => return None,
+                },
+                Value::Constant(_) => return None,
+            };
+            let t = eval_value(then_value, bits, env);
+            let e = eval_value(else_value, bits, env);
+            let v = match eval_value(cond, 1, env).as_singleton() {
+                Some(c) if *c == 0 => e,
+                Some(_) => t,
+                None => t.join(&e),
+            };
+            (*result, v)
+        }
+        _ => return None,
+    };
+    Some(item)
+}
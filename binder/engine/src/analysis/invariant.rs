@@ -0,0 +1,642 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use rug::Integer;
+use serde_json::json;
+
+use crate::analysis::affine::analyze_function;
+use crate::analysis::dataflow::{
+    solve_forward_with, CfgState, Comparisons, Exhaustion, Filter, VariableStore, Widening,
+};
+use crate::analysis::interval::{eval_value, transfer, Bound, Interval};
+use crate::analysis::lattice::Lattice;
+use crate::analysis::libc::Returns;
+use crate::analysis::powerset::{JoinOverflow, Powerset};
+use crate::analysis::summary::{
+    solve_summaries, CallStrings, Calls, SolverConfig, SummaryAnalysis,
+};
+use crate::analysis::variable::{int_bits, int_type_bits, Variable};
+use crate::error::EngineResult;
+use crate::ir::bridge::cfg::{Block, ControlFlowGraph, Edge};
+use crate::ir::bridge::function::{Function, Parameter};
+use crate::ir::bridge::instruction::{ContractKind, Instruction, Terminator};
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::{Identifier, SourceLocation};
+use crate::ir::bridge::value::{BlockLabel, RegisterSlot};
+use crate::ir::defuse::Site;
+
+/// Intervals of the variables, where a variable not in the store can take any value
+pub(crate) type Env = VariableStore<Interval>;
+
+/// A fact on a variable that holds whenever the loop header is entered
+pub struct InvariantFact {
+    /// name of the variable
+    pub variable: String,
+    /// values the variable may take
+    pub interval: Interval,
+}
+
+impl Display for InvariantFact {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} in {}", self.variable, self.interval)
+    }
+}
+
+/// Candidate invariants of a loop
+pub struct LoopInvariant {
+    /// function holding the loop
+    pub function: Identifier,
+    /// header of the loop
+    pub header: BlockLabel,
+    /// where the header comes from in the source code, if known
+    pub source: Option<SourceLocation>,
+    /// facts on the loop-carried variables
+    pub facts: Vec<InvariantFact>,
+    /// affine equalities involving the loop-carried variables
+    pub relations: Vec<String>,
+}
+
+impl LoopInvariant {
+    /// Machine-readable form of the invariant
+    pub fn to_json(&self) -> serde_json::Value {
+        let facts: Vec<_> = self
+            .facts
+            .iter()
+            .map(|fact| {
+                let (lo, hi) = match fact.interval.bounds() {
+                    None => (None, None),
+                    Some((lo, hi)) => (finite(lo), finite(hi)),
+                };
+                json!({
+                    "variable": fact.variable,
+                    "lower": lo,
+                    "upper": hi,
+                })
+            })
+            .collect();
+        let source = self.source.as_ref().map(|loc| {
+            json!({
+                "file": loc.file,
+                "line": loc.line,
+                "column": loc.column,
+            })
+        });
+        json!({
+            "function": self.function.to_string(),
+            "header": self.header.to_string(),
+            "source": source,
+            "facts": facts,
+            "relations": self.relations,
+        })
+    }
+
+    /// Invariant as an ACSL annotation, if there is anything to state
+    pub fn to_acsl(&self) -> Option<String> {
+        let mut clauses: Vec<_> = self
+            .facts
+            .iter()
+            .filter_map(|fact| {
+                let (lo, hi) = match fact.interval.bounds() {
+                    None => return Some("\\false".to_string()),
+                    Some((lo, hi)) => (finite(lo), finite(hi)),
+                };
+                match (lo, hi) {
+                    (None, None) => None,
+                    (Some(l), None) => Some(format!("{} <= {}", l, fact.variable)),
+                    (None, Some(h)) => Some(format!("{} <= {}", fact.variable, h)),
+                    (Some(l), Some(h)) if l == h => Some(format!("{} == {}", fact.variable, l)),
+                    (Some(l), Some(h)) => Some(format!("{} <= {} <= {}", l, fact.variable, h)),
+                }
+            })
+            .collect();
+        clauses.extend(self.relations.iter().cloned());
+        if clauses.is_empty() {
+            return None;
+        }
+        let lines: Vec<_> = clauses
+            .iter()
+            .map(|c| format!("  @ loop invariant {};", c))
+            .collect();
+        let origin = match &self.source {
+            None => format!("{}: {}", self.function, self.header),
+            Some(loc) => format!("{}: {} at {}", self.function, self.header, loc),
+        };
+        Some(format!("/*@ {}\n{}\n  @*/", origin, lines.join("\n")))
+    }
+}
+
+impl Display for LoopInvariant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let facts: Vec<_> = self
+            .facts
+            .iter()
+            .map(|fact| fact.to_string())
+            .chain(self.relations.iter().cloned())
+            .collect();
+        write!(
+            f,
+            "@{} {}: {}",
+            self.function,
+            self.header,
+            facts.join(", ")
+        )?;
+        if let Some(source) = &self.source {
+            write!(f, " ({})", source)?;
+        }
+        Ok(())
+    }
+}
+
+/// Utility: the value of a finite bound
+fn finite(bound: &Bound) -> Option<String> {
+    match bound {
+        Bound::Finite(v) => Some(v.to_string()),
+        Bound::NegInf | Bound::PosInf => None,
+    }
+}
+
+/// Interval analysis of a function, iterated to a fixedpoint by the dataflow engine
+struct Analysis<'a> {
+    cfg: &'a ControlFlowGraph,
+    /// comparisons behind the conditions of the assumptions
+    comparisons: Comparisons<'a>,
+}
+
+impl<'a> Analysis<'a> {
+    fn new(cfg: &'a ControlFlowGraph) -> Self {
+        Self {
+            cfg,
+            comparisons: Comparisons::collect(cfg),
+        }
+    }
+
+    /// State after taking the edge from `src` into `dst`, bottom if the edge is infeasible,
+    /// where the phi nodes of `dst` are evaluated in the state of the edge
+    fn edge(&self, src: &BlockLabel, dst: &BlockLabel, filters: &[Filter], env: &Env) -> Env {
+        let mut env = filters
+            .iter()
+            .fold(env.clone(), |env, filter| env.assume(filter));
+        if env.is_bottom() {
+            return env;
+        }
+        let block = self
+            .cfg
+            .get_block_by_label(dst)
+            .expect("block label points to a valid node");
+        let mut resolved = vec![];
+        for inst in block.instructions() {
+            let (options, result) = match inst {
+                Instruction::Phi { options, result } => (options, result),
+                _ => break,
+            };
+            let value = options.get(src).and_then(|option| {
+                int_bits(option).map(|bits| eval_value(option, bits, &|v| env.get(v)))
+            });
+            resolved.push((Variable::Register(*result), value));
+        }
+        for (var, value) in resolved {
+            match value {
+                None => env.forget(&var),
+                Some(v) => env.set(var, v),
+            }
+        }
+        env
+    }
+
+    /// Execute a non-phi instruction, where an assumption cuts off the path (i.e., the state
+    /// becomes bottom) if it cannot hold
+    fn step(&self, env: &mut Env, inst: &Instruction) {
+        if let Instruction::Contract {
+            kind: ContractKind::Assume,
+            cond,
+        } = inst
+        {
+            for filter in self.comparisons.filters(cond, true) {
+                *env = env.assume(&filter);
+            }
+            return;
+        }
+        execute(env, inst);
+    }
+
+    /// Execute the non-phi instructions of a block
+    fn block(&self, block: &Block, env: &Env) -> Env {
+        let mut env = env.clone();
+        for inst in block.instructions() {
+            if !matches!(inst, Instruction::Phi { .. }) {
+                self.step(&mut env, inst);
+            }
+        }
+        env
+    }
+
+    fn solve(&self, widening: &Widening) -> CfgState<Env> {
+        solve_forward_with(
+            self.cfg,
+            Env::top(),
+            widening,
+            |_, block, env| self.block(block, env),
+            |src, dst, _, filters, env| self.edge(src, dst, filters, env),
+        )
+    }
+}
+
+/// Execute a non-phi instruction on the environment
+fn execute(env: &mut Env, inst: &Instruction) {
+    match transfer(inst, &|v| env.get(v)) {
+        Some((slot, interval)) => env.set(Variable::Register(slot), interval),
+        None => {
+            if let Some(slot) = inst.result() {
+                env.forget(&Variable::Register(slot));
+            }
+        }
+    }
+}
+
+/// States of the contexts of a function at a site, kept apart up to the powerset cap
+type Disjuncts = Powerset<Env, JoinOverflow<{ usize::MAX }>>;
+
+/// Interval states before each non-phi instruction and the terminator of the reachable blocks
+/// of a function, joined over the contexts it is analyzed in (and also kept apart up to the
+/// powerset cap), where the sites after an infeasible assumption are absent
+pub struct IntervalStates {
+    points: BTreeMap<Site, Env>,
+    disjuncts: BTreeMap<Site, Disjuncts>,
+    powerset_cap: usize,
+    exhausted: Option<Exhaustion>,
+}
+
+impl IntervalStates {
+    fn new(powerset_cap: usize) -> Self {
+        Self {
+            points: BTreeMap::new(),
+            disjuncts: BTreeMap::new(),
+            powerset_cap,
+            exhausted: None,
+        }
+    }
+
+    /// State right before a site, `None` if the site is not reached
+    pub(crate) fn get(&self, site: &Site) -> Option<&Env> {
+        self.points.get(site)
+    }
+
+    /// States of the contexts right before a site, as many as the powerset cap permits, none
+    /// if the site is not reached
+    pub(crate) fn disjuncts(&self, site: &Site) -> &[Env] {
+        self.disjuncts
+            .get(site)
+            .map_or(&[], |states| states.disjuncts())
+    }
+
+    /// Sites reached by the analysis
+    pub(crate) fn sites(&self) -> impl Iterator<Item = &Site> {
+        self.points.keys()
+    }
+
+    /// The budget that ran out, in which case every reachable site is top
+    pub(crate) fn exhausted(&self) -> Option<Exhaustion> {
+        self.exhausted
+    }
+
+    /// Join the state into the one recorded at the site
+    fn record(&mut self, site: Site, env: &Env) {
+        let joined = match self.points.get(&site) {
+            None => env.clone(),
+            Some(old) => old.join(env),
+        };
+        self.points.insert(site, joined);
+
+        let mut states = self
+            .disjuncts
+            .remove(&site)
+            .map_or(vec![], |old| old.disjuncts().to_vec());
+        states.push(env.clone());
+        self.disjuncts
+            .insert(site, Powerset::with_cap(states, self.powerset_cap));
+    }
+}
+
+/// Interval states of the defined functions in a module
+pub struct ModuleIntervals {
+    functions: BTreeMap<Identifier, IntervalStates>,
+}
+
+impl ModuleIntervals {
+    /// States of a function, `None` for a declaration
+    pub fn get(&self, name: &Identifier) -> Option<&IntervalStates> {
+        self.functions.get(name)
+    }
+}
+
+/// Utility: the interval of the value returned by a library function, if modeled
+fn model_return(returns: Returns, bits: usize) -> Option<Interval> {
+    let interval = match returns {
+        Returns::Range(lo, hi) => Interval::new(
+            Bound::Finite(Integer::from(lo)),
+            Bound::Finite(Integer::from(hi)),
+        ),
+        Returns::NonNegative => Interval::new(Bound::Finite(Integer::new()), Bound::PosInf),
+        _ => return None,
+    };
+    Some(interval.meet(&Interval::full(bits)))
+}
+
+/// Interval analysis of the functions in a module, where a call yields the value returned in
+/// the summary of the callee, or in the model of a library function
+struct Interprocedural<'a> {
+    solver: &'a SolverConfig,
+    /// intraprocedural part of the analysis of each defined function
+    functions: BTreeMap<&'a Identifier, Analysis<'a>>,
+}
+
+impl Interprocedural<'_> {
+    /// Execute a non-phi instruction, with the calls resolved through `calls`
+    fn step(
+        &self,
+        func: &Function,
+        site: Site,
+        env: &mut Env,
+        inst: &Instruction,
+        calls: &mut Calls<'_, Env>,
+    ) {
+        let (function, args, result) = match inst {
+            Instruction::CallDirect {
+                function,
+                args,
+                result,
+                ..
+            } => (function, args, result),
+            _ => {
+                self.functions[&func.name].step(env, inst);
+                return;
+            }
+        };
+
+        let mut input = Env::top();
+        for (index, arg) in args.iter().enumerate() {
+            if let Some(bits) = int_bits(arg) {
+                let value = eval_value(arg, bits, &|v| env.get(v));
+                input.set(Variable::Argument(index.into()), value);
+            }
+        }
+        let output = calls.apply(function, site, input);
+        if output.as_ref().is_some_and(|state| state.is_bottom()) {
+            // the callee never returns
+            *env = Env::bottom();
+            return;
+        }
+        let (ty, slot) = match result {
+            None => return,
+            Some(item) => item,
+        };
+        let returned = int_type_bits(ty).and_then(|bits| {
+            let value = match &output {
+                Some(state) => Some(state.get(&Variable::Return)),
+                None => calls
+                    .model(function)
+                    .and_then(|model| model_return(model.returns, bits)),
+            };
+            value.map(|v| v.meet(&Interval::full(bits)))
+        });
+        match returned {
+            None => env.forget(&Variable::Register(*slot)),
+            Some(value) => env.set(Variable::Register(*slot), value),
+        }
+    }
+
+    /// Record the state before each site of the reachable blocks of a function analyzed in a
+    /// context, replaying the blocks from their stabilized entry states
+    fn replay(
+        &self,
+        func: &Function,
+        states: &CfgState<Env>,
+        calls: &mut Calls<'_, Env>,
+        result: &mut IntervalStates,
+    ) {
+        let cfg = func
+            .body
+            .as_ref()
+            .expect("only defined functions are analyzed");
+        result.exhausted = result.exhausted.or(states.exhausted());
+        'blocks: for label in states.reachable() {
+            let block = cfg
+                .get_block_by_label(label)
+                .expect("block label points to a valid node");
+            let mut env = states.at_entry(label);
+            for (index, inst) in block.instructions().iter().enumerate() {
+                if matches!(inst, Instruction::Phi { .. }) {
+                    continue;
+                }
+                if env.is_bottom() {
+                    continue 'blocks;
+                }
+                let site = Site::Instruction {
+                    block: *label,
+                    index,
+                };
+                result.record(site, &env);
+                self.step(func, site, &mut env, inst, calls);
+            }
+            if !env.is_bottom() {
+                result.record(Site::Terminator { block: *label }, &env);
+            }
+        }
+    }
+}
+
+impl SummaryAnalysis for Interprocedural<'_> {
+    type State = Env;
+
+    fn entry(&self, _func: &Function) -> Env {
+        Env::top()
+    }
+
+    fn block(
+        &self,
+        func: &Function,
+        label: &BlockLabel,
+        block: &Block,
+        state: &Env,
+        calls: &mut Calls<'_, Env>,
+    ) -> Env {
+        let mut env = state.clone();
+        for (index, inst) in block.instructions().iter().enumerate() {
+            if !matches!(inst, Instruction::Phi { .. }) {
+                let site = Site::Instruction {
+                    block: *label,
+                    index,
+                };
+                self.step(func, site, &mut env, inst, calls);
+            }
+        }
+        if let Terminator::Return { val: Some(value) } = block.terminator() {
+            if let Some(bits) = int_bits(value) {
+                let returned = eval_value(value, bits, &|v| env.get(v));
+                env.set(Variable::Return, returned);
+            }
+        }
+        env
+    }
+
+    fn edge(
+        &self,
+        func: &Function,
+        src: &BlockLabel,
+        dst: &BlockLabel,
+        _edge: &Edge,
+        filters: &[Filter],
+        state: &Env,
+    ) -> Env {
+        self.functions[&func.name].edge(src, dst, filters, state)
+    }
+
+    /// Only the value returned is observed by the callers
+    fn exit(&self, _func: &Function, state: &Env) -> Env {
+        let mut exit = Env::top();
+        exit.set(Variable::Return, state.get(&Variable::Return));
+        exit
+    }
+
+    fn widening(&self, _func: &Function) -> Widening {
+        self.solver.widening.clone()
+    }
+
+    fn entry_points<'m>(&self, module: &'m Module) -> Vec<&'m Function> {
+        if self.solver.entry_points.is_empty() {
+            return module.entry_points();
+        }
+        self.solver
+            .entry_points
+            .iter()
+            .filter_map(|name| module.get_function(name.as_ref()))
+            .filter(|func| func.body.is_some())
+            .collect()
+    }
+
+    fn call_strings(&self) -> Option<CallStrings> {
+        self.solver.call_strings
+    }
+}
+
+/// Run the interval analysis on the defined functions of the module, with the callees
+/// summarized bottom-up over the call graph, or per call string with the arguments passed
+pub fn analyze_intervals(module: &Module, solver: &SolverConfig) -> ModuleIntervals {
+    let analysis = Interprocedural {
+        solver,
+        functions: module
+            .functions()
+            .iter()
+            .filter_map(|(name, func)| func.body.as_ref().map(|cfg| (name, Analysis::new(cfg))))
+            .collect(),
+    };
+    let result = solve_summaries(module, &analysis);
+
+    let mut functions = BTreeMap::new();
+    for (name, func) in module.functions() {
+        if func.body.is_some() {
+            functions.insert(name.clone(), IntervalStates::new(solver.powerset_cap));
+        }
+    }
+    for (key, states) in &result.states {
+        let func = module
+            .get_function(key.0.as_ref())
+            .expect("only defined functions are analyzed");
+        let states_at = functions
+            .get_mut(&key.0)
+            .expect("only defined functions are analyzed");
+        analysis.replay(func, states, &mut result.calls(module, key), states_at);
+    }
+    ModuleIntervals { functions }
+}
+
+/// Utility: name of a register as it would appear in the source
+fn variable_name(cfg: &ControlFlowGraph, slot: &RegisterSlot) -> String {
+    match cfg.register_name(slot) {
+        // SSA construction derives names like `i.0` from the source variable `i`
+        Some(name) => match name.split_once('.') {
+            Some((base, _)) if !base.is_empty() => base.to_string(),
+            _ => name.to_string(),
+        },
+        None => format!("%r{}", slot.index()),
+    }
+}
+
+/// Synthesize candidate invariants at the loop headers, i.e., the heads of the weak topological
+/// order, from the interval facts stabilized and narrowed under the widening
+pub fn synthesize_loop_invariants(
+    module: &Module,
+    widening: &Widening,
+) -> EngineResult<Vec<LoopInvariant>> {
+    let mut invariants = vec![];
+    for (name, func) in module.functions() {
+        let cfg = match &func.body {
+            None => continue,
+            Some(cfg) => cfg,
+        };
+        let states = Analysis::new(cfg).solve(widening);
+        let equalities = analyze_function(func)?;
+        let name_of = |var: &Variable| match var {
+            Variable::Register(slot) => variable_name(cfg, slot),
+            Variable::Argument(slot) => match func.params.get(slot.index()) {
+                Some(Parameter {
+                    name: Some(ident), ..
+                }) => ident.to_string(),
+                _ => var.to_string(),
+            },
+            Variable::Return => var.to_string(),
+        };
+
+        for header in &cfg.wto_heads() {
+            // unreachable loops have no meaningful invariant
+            let env = states.at_entry(header);
+            if env.is_bottom() {
+                continue;
+            }
+            let block = cfg
+                .get_block_by_label(header)
+                .expect("block label points to a valid node");
+
+            // loop-carried variables are the phi nodes at the header
+            let mut facts = vec![];
+            let mut carried = BTreeSet::new();
+            for inst in block.instructions() {
+                let (options, result) = match inst {
+                    Instruction::Phi { options, result } => (options, result),
+                    _ => break,
+                };
+                carried.insert(Variable::Register(*result));
+                let bits = match options.values().find_map(int_bits) {
+                    None => continue,
+                    Some(bits) => bits,
+                };
+                // facts implied by the width alone are not worth stating
+                let interval = env.get(&Variable::Register(*result));
+                if !Interval::full(bits).leq(&interval) {
+                    facts.push(InvariantFact {
+                        variable: variable_name(cfg, result),
+                        interval,
+                    });
+                }
+            }
+            // relations among the loop-carried variables and the others
+            let relations = equalities
+                .get(header)
+                .map(|state| state.equalities())
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|eq| eq.variables().any(|v| carried.contains(v)))
+                .map(|eq| eq.render_equation(&name_of))
+                .collect();
+
+            invariants.push(LoopInvariant {
+                function: name.clone(),
+                header: *header,
+                source: cfg.block_location(header).cloned(),
+                facts,
+                relations,
+            });
+        }
+    }
+    Ok(invariants)
+}
@@ -0,0 +1,45 @@
+use std::collections::BTreeSet;
+
+use rug::Integer;
+
+/// An abstract domain ordered as a lattice
+pub trait Lattice: Clone + Eq {
+    /// The least element, i.e., no concrete state
+    fn bottom() -> Self;
+
+    /// The greatest element, i.e., any concrete state
+    fn top() -> Self;
+
+    fn is_bottom(&self) -> bool {
+        *self == Self::bottom()
+    }
+
+    fn is_top(&self) -> bool {
+        *self == Self::top()
+    }
+
+    /// Partial order of the lattice
+    fn leq(&self, other: &Self) -> bool;
+
+    /// Least upper bound
+    fn join(&self, other: &Self) -> Self;
+
+    /// Greatest lower bound
+    fn meet(&self, other: &Self) -> Self;
+
+    /// Extrapolate an ascending chain, defaults to join for domains of finite height
+    fn widen(&self, other: &Self) -> Self {
+        self.join(other)
+    }
+
+    /// Widening that may stop at one of the thresholds (constants of the program) instead of
+    /// extrapolating further, defaults to the plain widening for non-numeric domains
+    fn widen_with_thresholds(&self, other: &Self, _thresholds: &BTreeSet<Integer>) -> Self {
+        self.widen(other)
+    }
+
+    /// Refine a post-fixpoint reached by widening, defaults to meet for domains of finite height
+    fn narrow(&self, other: &Self) -> Self {
+        self.meet(other)
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt::{Display, Formatter};
+
+use crate::ir::bridge::module::Module;
+
+/// What a library function returns, as far as the analyses care
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub enum Returns {
+    /// nothing, or a value nothing is known about
+    Opaque,
+    /// an integer within the bounds (inclusive)
+    Range(i64, i64),
+    /// a non-negative integer, e.g., a length
+    NonNegative,
+    /// the pointer passed as the argument
+    Argument(usize),
+    /// the pointer passed as the argument, or null on failure
+    ArgumentOrNull(usize),
+    /// a pointer into the object the argument points into, or null if not found
+    IntoOrNull(usize),
+}
+
+impl Display for Returns {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Opaque => write!(f, "opaque"),
+            Self::Range(lo, hi) => write!(f, "[{}, {}]", lo, hi),
+            Self::NonNegative => write!(f, "non-negative"),
+            Self::Argument(index) => write!(f, "arg {}", index),
+            Self::ArgumentOrNull(index) => write!(f, "arg {} or null", index),
+            Self::IntoOrNull(index) => write!(f, "into arg {} or null", index),
+        }
+    }
+}
+
+/// Abstract semantics of a library function, for calls to a declaration
+///
+/// A modeled function touches nothing but the pointees of its pointer arguments as listed, so
+/// the rest of the memory survives the call.
+pub struct LibcModel {
+    pub name: &'static str,
+    /// arguments whose pointees are read
+    pub reads: &'static [usize],
+    /// arguments whose pointees are written
+    pub writes: &'static [usize],
+    /// contents copied from the pointee of an argument into the pointee of another, as
+    /// (destination, source)
+    pub copies: &'static [(usize, usize)],
+    pub returns: Returns,
+}
+
+/// Utility: shorthand for the table below
+const fn model(
+    name: &'static str,
+    reads: &'static [usize],
+    writes: &'static [usize],
+    copies: &'static [(usize, usize)],
+    returns: Returns,
+) -> LibcModel {
+    LibcModel {
+        name,
+        reads,
+        writes,
+        copies,
+        returns,
+    }
+}
+
+/// The curated library functions
+static MODELS: [LibcModel; 26] = [
+    // strings
+    model("strlen", &[0], &[], &[], Returns::NonNegative),
+    model("strnlen", &[0], &[], &[], Returns::NonNegative),
+    model("strcmp", &[0, 1], &[], &[], Returns::Opaque),
+    model("strncmp", &[0, 1], &[], &[], Returns::Opaque),
+    model("strcpy", &[1], &[0], &[(0, 1)], Returns::Argument(0)),
+    model("strncpy", &[1], &[0], &[(0, 1)], Returns::Argument(0)),
+    model("strcat", &[0, 1], &[0], &[(0, 1)], Returns::Argument(0)),
+    model("strncat", &[0, 1], &[0], &[(0, 1)], Returns::Argument(0)),
+    model("strchr", &[0], &[], &[], Returns::IntoOrNull(0)),
+    model("strrchr", &[0], &[], &[], Returns::IntoOrNull(0)),
+    model("strstr", &[0, 1], &[], &[], Returns::IntoOrNull(0)),
+    // memory
+    model("memcmp", &[0, 1], &[], &[], Returns::Opaque),
+    model("memcpy", &[1], &[0], &[(0, 1)], Returns::Argument(0)),
+    model("memmove", &[1], &[0], &[(0, 1)], Returns::Argument(0)),
+    model("memset", &[], &[0], &[], Returns::Argument(0)),
+    model("memchr", &[0], &[], &[], Returns::IntoOrNull(0)),
+    // conversions
+    model("atoi", &[0], &[], &[], Returns::Opaque),
+    model("atol", &[0], &[], &[], Returns::Opaque),
+    model("strtol", &[0], &[1], &[], Returns::Opaque),
+    model("strtoul", &[0], &[1], &[], Returns::Opaque),
+    model("toupper", &[], &[], &[], Returns::Range(-1, 255)),
+    model("tolower", &[], &[], &[], Returns::Range(-1, 255)),
+    // input and output
+    model("fgets", &[2], &[0, 2], &[], Returns::ArgumentOrNull(0)),
+    model("fread", &[3], &[0, 3], &[], Returns::NonNegative),
+    model("puts", &[0], &[], &[], Returns::Opaque),
+    model("fputs", &[0, 1], &[1], &[], Returns::Opaque),
+];
+
+/// Model of a library function, if it is among the curated ones
+pub fn find_model(name: &str) -> Option<&'static LibcModel> {
+    MODELS.iter().find(|model| model.name == name)
+}
+
+/// Model of a function called in the module, unless the module defines the function for good
+pub fn callee_model(module: &Module, name: &str) -> Option<&'static LibcModel> {
+    match module.get_function(name) {
+        Some(func) if func.exact_body().is_some() => None,
+        _ => find_model(name),
+    }
+}
@@ -0,0 +1,547 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use crate::analysis::dataflow::{solve_forward_with, Exhaustion, Findings, Store, Widening};
+use crate::analysis::lattice::Lattice;
+use crate::analysis::memory::{AllocSite, HeapAllocation, Recency};
+use crate::analysis::pointer::{analyze_heap_allocations, Pointees, PointerStates};
+use crate::analysis::pointsto::{analyze_points_to, PointsTo};
+use crate::analysis::summary::SolverConfig;
+use crate::error::EngineResult;
+use crate::ir::bridge::cfg::{Block, ControlFlowGraph};
+use crate::ir::bridge::function::Function;
+use crate::ir::bridge::instruction::{Instruction, Terminator};
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::value::{ArgumentSlot, BlockLabel, RegisterSlot, Value};
+use crate::ir::callgraph::CallGraph;
+use crate::ir::defuse::Site;
+
+/// Allocation state of an abstract heap object
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub enum AllocState {
+    Bottom,
+    /// not freed since its allocation
+    Allocated,
+    Freed,
+    /// freed on some executions only
+    Unknown,
+}
+
+impl Lattice for AllocState {
+    fn bottom() -> Self {
+        Self::Bottom
+    }
+
+    fn top() -> Self {
+        Self::Unknown
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        self == other || matches!(self, Self::Bottom) || matches!(other, Self::Unknown)
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Bottom, v) | (v, Self::Bottom) => *v,
+            (lhs, rhs) if lhs == rhs => *lhs,
+            _ => Self::Unknown,
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Unknown, v) | (v, Self::Unknown) => *v,
+            (lhs, rhs) if lhs == rhs => *lhs,
+            _ => Self::Bottom,
+        }
+    }
+}
+
+impl Display for AllocState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bottom => write!(f, "bottom"),
+            Self::Allocated => write!(f, "allocated"),
+            Self::Freed => write!(f, "freed"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// An object whose allocation state is tracked
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone)]
+pub enum HeapObject {
+    /// objects allocated at a heap site
+    Site(AllocSite),
+    /// the object passed in a parameter of the function under analysis
+    Param(ArgumentSlot),
+}
+
+impl Display for HeapObject {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Site(site) => write!(f, "{}", site),
+            Self::Param(slot) => write!(f, "param {}", slot),
+        }
+    }
+}
+
+/// Allocation states of the tracked objects, where an absent object is unknown
+pub type HeapStore = Store<HeapObject, AllocState>;
+
+/// Effect of a function on the objects passed in its parameters
+#[derive(Eq, PartialEq, Clone, Default)]
+pub struct HeapSummary {
+    /// state on return of the parameters the function may free, i.e., freed or unknown
+    pub frees: BTreeMap<ArgumentSlot, AllocState>,
+    /// parameters the function may load from or store into
+    pub uses: BTreeSet<ArgumentSlot>,
+}
+
+/// Kind of misuse of a freed object
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub enum Misuse {
+    UseAfterFree,
+    DoubleFree,
+}
+
+impl Display for Misuse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UseAfterFree => write!(f, "use after free"),
+            Self::DoubleFree => write!(f, "double free"),
+        }
+    }
+}
+
+/// Certainty of a misuse
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub enum MisuseKind {
+    /// every object the pointer may point to is freed
+    Definite,
+    /// some object the pointer may point to may be freed
+    Possible,
+}
+
+impl Display for MisuseKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Definite => write!(f, "definite"),
+            Self::Possible => write!(f, "possible"),
+        }
+    }
+}
+
+/// A use or a free of a pointer to an object that may be freed already
+pub struct HeapMisuse {
+    pub function: Identifier,
+    pub site: Site,
+    pub misuse: Misuse,
+    /// the operation, e.g., load, store, free, or a call to a function freeing or using it
+    pub access: String,
+    /// the pointer misused, as printed in the IR
+    pub pointer: String,
+    /// heap objects the pointer may refer to and that may be freed already
+    pub objects: Vec<HeapAllocation>,
+    pub kind: MisuseKind,
+}
+
+impl Display for HeapMisuse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "@{}:{}: {}: {} through {} ({})",
+            self.function, self.site, self.misuse, self.access, self.pointer, self.kind
+        )
+    }
+}
+
+/// Lifetime analysis of the heap objects in a function, given the summaries of its callees
+struct Analysis<'a> {
+    function: &'a Identifier,
+    cfg: &'a ControlFlowGraph,
+    widening: &'a Widening,
+    pointers: PointerStates<'a>,
+    /// heap objects of the module
+    heap: &'a BTreeMap<AllocSite, HeapAllocation>,
+    summaries: &'a BTreeMap<Identifier, HeapSummary>,
+    /// registers holding a parameter, and whether they point to its start
+    params: BTreeMap<RegisterSlot, (ArgumentSlot, bool)>,
+}
+
+/// What an instruction does to the objects pointed to by one of its operands
+enum Effect<'a> {
+    /// the objects are accessed
+    Use(&'a str, &'a Value),
+    /// the objects are freed, for sure or on some executions only
+    Free(&'a str, &'a Value, bool),
+}
+
+impl<'a> Analysis<'a> {
+    fn new(
+        module: &'a Module,
+        global: &'a PointsTo,
+        func: &'a Function,
+        cfg: &'a ControlFlowGraph,
+        widening: &'a Widening,
+        heap: &'a BTreeMap<AllocSite, HeapAllocation>,
+        summaries: &'a BTreeMap<Identifier, HeapSummary>,
+    ) -> Self {
+        // definitions dominate their uses, so the reverse post-order sees operands first
+        let mut params = BTreeMap::new();
+        for label in cfg.blocks_rpo() {
+            let block = cfg
+                .get_block_by_label(&label)
+                .expect("block label points to a valid node");
+            for inst in block.instructions() {
+                let (operand, result, exact) = match inst {
+                    Instruction::CastPtr { operand, result }
+                    | Instruction::GEPNop {
+                        pointer: operand,
+                        result,
+                        ..
+                    } => (operand, result, true),
+                    Instruction::GEP {
+                        pointer, result, ..
+                    } => (pointer, result, false),
+                    _ => continue,
+                };
+                let param = match operand {
+                    Value::Argument { index, ty: _ } => Some((*index, true)),
+                    Value::Register { index, ty: _ } => params.get(index).copied(),
+                    Value::Constant(_) => None,
+                };
+                if let Some((slot, base)) = param {
+                    params.insert(*result, (slot, base && exact));
+                }
+            }
+        }
+
+        Self {
+            function: &func.name,
+            cfg,
+            widening,
+            pointers: PointerStates::new(module, global, func, cfg, widening),
+            heap,
+            summaries,
+            params,
+        }
+    }
+
+    /// Utility: the parameter a value holds, and whether it points to the start of it
+    fn param_of(&self, value: &Value) -> Option<(ArgumentSlot, bool)> {
+        match value {
+            Value::Argument { index, ty: _ } => Some((*index, true)),
+            Value::Register { index, ty: _ } => self.params.get(index).copied(),
+            Value::Constant(_) => None,
+        }
+    }
+
+    fn initial(&self, func: &Function) -> HeapStore {
+        let mut state = HeapStore::top();
+        for site in self.heap.keys() {
+            state.set(HeapObject::Site(site.clone()), AllocState::Allocated);
+        }
+        for index in 0..func.params.len() {
+            state.set(HeapObject::Param(index.into()), AllocState::Allocated);
+        }
+        state
+    }
+
+    /// Objects a pointer may refer to, null excluded as freeing it is a no-op
+    fn objects(&self, target: &Pointees, value: &Value) -> Vec<HeapObject> {
+        let mut objects: Vec<_> = target
+            .sites
+            .iter()
+            .filter(|site| self.heap.contains_key(*site))
+            .map(|site| HeapObject::Site(site.clone()))
+            .collect();
+        if let Some((slot, _)) = self.param_of(value) {
+            objects.push(HeapObject::Param(slot));
+        }
+        objects
+    }
+
+    /// Effects of an instruction on the objects of its pointer operands
+    fn effects<'i>(&self, inst: &'i Instruction) -> Vec<Effect<'i>> {
+        let (function, args) = match inst {
+            Instruction::Load { pointer, .. } => return vec![Effect::Use("load", pointer)],
+            Instruction::Store { pointer, .. } => return vec![Effect::Use("store", pointer)],
+            Instruction::CallDirect { function, args, .. } => (function, args),
+            _ => return vec![],
+        };
+        match (function.as_ref(), args.as_slice()) {
+            ("free", [pointer]) => return vec![Effect::Free("free", pointer, true)],
+            ("realloc", [pointer, _]) => return vec![Effect::Free("realloc", pointer, true)],
+            ("_ZdlPv" | "_ZdaPv", [pointer]) | ("_ZdlPvm" | "_ZdaPvm", [pointer, _]) => {
+                return vec![Effect::Free("delete", pointer, true)]
+            }
+            ("__cxa_free_exception", [pointer]) => {
+                return vec![Effect::Free("__cxa_free_exception", pointer, true)]
+            }
+            _ => (),
+        }
+        let summary = match self.summaries.get(function) {
+            None => return vec![],
+            Some(summary) => summary,
+        };
+        let mut effects = vec![];
+        for slot in &summary.uses {
+            if let Some(arg) = args.get(slot.index()) {
+                effects.push(Effect::Use("call", arg));
+            }
+        }
+        for (slot, state) in &summary.frees {
+            if let Some(arg) = args.get(slot.index()) {
+                effects.push(Effect::Free("call", arg, *state == AllocState::Freed));
+            }
+        }
+        effects
+    }
+
+    /// Check the objects about to be used or freed, returning how sure the misuse is
+    fn check(
+        &self,
+        heap: &HeapStore,
+        target: &Pointees,
+        objects: &[HeapObject],
+    ) -> Option<MisuseKind> {
+        let states: Vec<_> = objects.iter().map(|object| heap.get(object)).collect();
+        if !states
+            .iter()
+            .any(|state| matches!(state, AllocState::Freed | AllocState::Unknown))
+        {
+            return None;
+        }
+        let kind = if !target.unknown && states.iter().all(|state| *state == AllocState::Freed) {
+            MisuseKind::Definite
+        } else {
+            MisuseKind::Possible
+        };
+        Some(kind)
+    }
+
+    /// Free the objects a pointer refers to, strongly if the free is certain and the target is
+    /// unique
+    fn free(&self, heap: &mut HeapStore, target: &Pointees, value: &Value, certain: bool) {
+        // a unique heap site stands for the object allocated last at it
+        let objects = self.objects(target, value);
+        let sites = objects
+            .iter()
+            .filter(|object| matches!(object, HeapObject::Site(_)))
+            .count();
+        let mut updates = vec![];
+        for object in objects {
+            let strong = match &object {
+                HeapObject::Site(_) => sites == 1,
+                // only a pointer to the start of a parameter frees it
+                HeapObject::Param(_) => match self.param_of(value) {
+                    Some((_, true)) => true,
+                    _ => continue,
+                },
+            };
+            updates.push((object, certain && strong));
+        }
+        for (object, strong) in updates {
+            if strong {
+                heap.set(object, AllocState::Freed);
+            } else {
+                heap.update(object, &AllocState::Freed);
+            }
+        }
+    }
+
+    /// Apply the instructions of a block, collecting the misuses and the parameter uses if asked
+    fn block(
+        &self,
+        label: &BlockLabel,
+        block: &Block,
+        heap: &HeapStore,
+        mut report: Option<&mut Report>,
+    ) -> HeapStore {
+        let mut heap = heap.clone();
+        let mut pointers = self.pointers.at_entry(label);
+        for (index, inst) in block.instructions().iter().enumerate() {
+            let site = Site::Instruction {
+                block: *label,
+                index,
+            };
+            for effect in self.effects(inst) {
+                let (access, value) = match &effect {
+                    Effect::Use(access, value) | Effect::Free(access, value, _) => {
+                        (*access, *value)
+                    }
+                };
+                let target = self.pointers.eval(&pointers, value);
+
+                if let Some(report) = report.as_deref_mut() {
+                    let misuse = match effect {
+                        Effect::Use(..) => {
+                            if let Some((slot, _)) = self.param_of(value) {
+                                report.summary.uses.insert(slot);
+                            }
+                            Misuse::UseAfterFree
+                        }
+                        Effect::Free(..) => Misuse::DoubleFree,
+                    };
+                    let objects = self.objects(&target, value);
+                    if let Some(kind) = self.check(&heap, &target, &objects) {
+                        let access = match inst {
+                            Instruction::CallDirect { function, .. } if access == "call" => {
+                                format!("call to @{}", function)
+                            }
+                            _ => access.to_string(),
+                        };
+                        let freed = objects
+                            .iter()
+                            .filter_map(|object| match object {
+                                HeapObject::Site(site) => self.heap.get(site),
+                                HeapObject::Param(_) => None,
+                            })
+                            .filter(|object| {
+                                let state = heap.get(&HeapObject::Site(object.site.clone()));
+                                matches!(state, AllocState::Freed | AllocState::Unknown)
+                            })
+                            .cloned()
+                            .collect();
+                        report.findings.push(HeapMisuse {
+                            function: self.function.clone(),
+                            site,
+                            misuse,
+                            access,
+                            pointer: value.to_string(),
+                            objects: freed,
+                            kind,
+                        });
+                    }
+                }
+
+                if let Effect::Free(_, _, certain) = effect {
+                    self.free(&mut heap, &target, value, certain);
+                }
+            }
+
+            // a fresh object is allocated
+            if let Instruction::CallDirect {
+                result: Some(_), ..
+            } = inst
+            {
+                let object = AllocSite::Heap {
+                    function: self.function.clone(),
+                    site,
+                    recency: Recency::Summary,
+                };
+                if self.heap.contains_key(&object) {
+                    heap.set(HeapObject::Site(object), AllocState::Allocated);
+                }
+            }
+            self.pointers.step(&mut pointers, site, inst);
+        }
+        heap
+    }
+
+    /// Solve the allocation states, then replay them to collect the misuses and the summary
+    fn run(&self, func: &Function) -> Report {
+        let states = solve_forward_with(
+            self.cfg,
+            self.initial(func),
+            self.widening,
+            |label, block, state| self.block(label, block, state, None),
+            |_, _, _, _, state| state.clone(),
+        );
+
+        let mut report = Report {
+            exhausted: self.pointers.exhausted().or(states.exhausted()),
+            ..Report::default()
+        };
+        let mut exit = HeapStore::bottom();
+        for label in states.reachable() {
+            let block = self
+                .cfg
+                .get_block_by_label(label)
+                .expect("block label points to a valid node");
+            self.block(label, block, &states.at_entry(label), Some(&mut report));
+            if matches!(block.terminator(), Terminator::Return { .. }) {
+                exit = exit.join(&states.at_exit(label));
+            }
+        }
+        for index in 0..func.params.len() {
+            let slot = ArgumentSlot::from(index);
+            let state = exit.get(&HeapObject::Param(slot));
+            if matches!(state, AllocState::Freed | AllocState::Unknown) {
+                report.summary.frees.insert(slot, state);
+            }
+        }
+        report
+    }
+}
+
+/// Outcome of the analysis of a function
+#[derive(Default)]
+struct Report {
+    summary: HeapSummary,
+    findings: Vec<HeapMisuse>,
+    /// the budget that ran out in the analyses of the function
+    exhausted: Option<Exhaustion>,
+}
+
+/// Find the uses and frees of heap objects that may be freed already, with the callees
+/// summarized bottom-up over the call graph
+pub fn check_heap_lifetimes(
+    module: &Module,
+    solver: &SolverConfig,
+) -> EngineResult<Findings<HeapMisuse>> {
+    let global = analyze_points_to(module)?;
+    let allocations = analyze_heap_allocations(module, solver)?;
+    let heap: BTreeMap<_, _> = allocations
+        .iter()
+        .map(|object| (object.site.clone(), object.clone()))
+        .collect();
+
+    // callees come before their callers, and a recursion is iterated to a fixedpoint
+    let graph = CallGraph::build(module);
+    let mut summaries = BTreeMap::new();
+    let mut reports = BTreeMap::new();
+    for component in graph.components() {
+        loop {
+            let mut changed = false;
+            for name in &component.functions {
+                let func = match module.get_function(name.as_ref()) {
+                    None => continue,
+                    Some(func) => func,
+                };
+                let cfg = match &func.body {
+                    None => continue,
+                    Some(cfg) => cfg,
+                };
+                let analysis = Analysis::new(
+                    module,
+                    &global,
+                    func,
+                    cfg,
+                    &solver.widening,
+                    &heap,
+                    &summaries,
+                );
+                let report = analysis.run(func);
+                if summaries.get(*name) != Some(&report.summary) {
+                    summaries.insert((*name).clone(), report.summary.clone());
+                    changed = true;
+                }
+                reports.insert((*name).clone(), report);
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    let mut findings = Findings::new();
+    findings.record_all(&allocations);
+    for (name, report) in reports {
+        findings.record(&name, report.exhausted);
+        findings.items.extend(report.findings);
+    }
+    Ok(findings)
+}
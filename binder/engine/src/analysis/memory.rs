@@ -0,0 +1,948 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use rug::Integer;
+
+use crate::analysis::interval::Interval;
+use crate::analysis::lattice::Lattice;
+use crate::error::{EngineError, EngineResult};
+use crate::ir::bridge::constant::{Constant, NumValue};
+use crate::ir::bridge::global::GlobalVariable;
+use crate::ir::bridge::instruction::Instruction;
+use crate::ir::bridge::layout::{ByteOffset, DataLayout};
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::typing::Type;
+use crate::ir::bridge::value::Value;
+use crate::ir::defuse::Site;
+
+/// Age of a heap object under the recency abstraction
+#[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone)]
+pub enum Recency {
+    /// the object allocated last at the site, updated strongly
+    MostRecent,
+    /// all older objects allocated at the site, updated weakly
+    Summary,
+}
+
+impl Display for Recency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MostRecent => write!(f, "recent"),
+            Self::Summary => write!(f, "summary"),
+        }
+    }
+}
+
+/// A site where memory is allocated
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone)]
+pub enum AllocSite {
+    /// a global variable
+    Global(Identifier),
+    /// an alloca instruction in a function
+    Stack { function: Identifier, site: Site },
+    /// a call to an allocator in a function
+    Heap {
+        function: Identifier,
+        site: Site,
+        recency: Recency,
+    },
+}
+
+impl AllocSite {
+    /// The same site with the given recency, a no-op for non-heap sites
+    pub fn with_recency(&self, age: Recency) -> Self {
+        match self {
+            Self::Global(_) | Self::Stack { .. } => self.clone(),
+            Self::Heap {
+                function,
+                site,
+                recency: _,
+            } => Self::Heap {
+                function: function.clone(),
+                site: *site,
+                recency: age,
+            },
+        }
+    }
+
+    /// Whether the site may stand for several live objects at the same time
+    pub fn is_summary(&self) -> bool {
+        matches!(
+            self,
+            Self::Heap {
+                recency: Recency::Summary,
+                ..
+            }
+        )
+    }
+}
+
+impl Display for AllocSite {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Global(name) => write!(f, "@{}", name),
+            Self::Stack { function, site } => write!(f, "@{}:{}", function, site),
+            Self::Heap {
+                function,
+                site,
+                recency,
+            } => write!(f, "heap@{}:{} ({})", function, site, recency),
+        }
+    }
+}
+
+/// An object allocated on the heap by a call to an allocator
+#[derive(Clone)]
+pub struct HeapAllocation {
+    /// the allocation site, as a summary of all objects allocated there
+    pub site: AllocSite,
+    /// the allocator called, e.g., malloc
+    pub allocator: Identifier,
+    /// size in bytes, from the interval of the size argument at the call
+    pub size: Interval,
+}
+
+impl Display for HeapAllocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} by @{} sized {} bytes",
+            self.site, self.allocator, self.size
+        )
+    }
+}
+
+/// Lifetime of an allocation
+#[derive(Eq, PartialEq, Copy, Clone)]
+pub enum Lifetime {
+    Live,
+    Freed,
+    /// freed on some executions only
+    MaybeFreed,
+}
+
+impl Lifetime {
+    fn join(self, other: Self) -> Self {
+        if self == other {
+            self
+        } else {
+            Self::MaybeFreed
+        }
+    }
+
+    fn leq(self, other: Self) -> bool {
+        self == other || other == Self::MaybeFreed
+    }
+}
+
+impl Display for Lifetime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Live => write!(f, "live"),
+            Self::Freed => write!(f, "freed"),
+            Self::MaybeFreed => write!(f, "maybe-freed"),
+        }
+    }
+}
+
+/// Precision of the memory model on an allocation
+#[derive(Eq, PartialEq, Copy, Clone)]
+pub enum Precision {
+    /// every byte range of the allocation is tracked on its own
+    FieldSensitive,
+    /// all elements of an array are summarized into one element
+    ArraySmashing,
+}
+
+impl Display for Precision {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FieldSensitive => write!(f, "field-sensitive"),
+            Self::ArraySmashing => write!(f, "array-smashing"),
+        }
+    }
+}
+
+/// Choice of precision per allocation site
+#[derive(Clone)]
+pub struct MemoryConfig {
+    default: Precision,
+    overrides: BTreeMap<AllocSite, Precision>,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            default: Precision::FieldSensitive,
+            overrides: BTreeMap::new(),
+        }
+    }
+}
+
+impl MemoryConfig {
+    /// Set the precision for sites without an explicit choice
+    pub fn set_default(&mut self, precision: Precision) {
+        self.default = precision;
+    }
+
+    /// Set the precision for one site
+    pub fn set_precision(&mut self, site: AllocSite, precision: Precision) {
+        self.overrides.insert(site, precision);
+    }
+
+    /// Precision chosen for the site, shared by all objects allocated there
+    pub fn precision_of(&self, site: &AllocSite) -> Precision {
+        self.overrides
+            .get(&site.with_recency(Recency::MostRecent))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+/// Utility: greatest common divisor, where 0 is the identity
+fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// An abstract address in the form of `site + offset + stride * <unknown>`
+#[derive(Eq, PartialEq, Clone)]
+pub struct Location {
+    /// allocation pointed to
+    pub site: AllocSite,
+    /// constant byte offset from the start of the allocation
+    pub offset: Integer,
+    /// greatest common divisor of the symbolic offsets, 0 if the offset is exact
+    pub stride: usize,
+}
+
+impl Location {
+    /// The start of an allocation
+    pub fn base(site: AllocSite) -> Self {
+        Self {
+            site,
+            offset: Integer::ZERO,
+            stride: 0,
+        }
+    }
+
+    /// Whether the location refers to exactly one address
+    pub fn is_exact(&self) -> bool {
+        self.stride == 0
+    }
+
+    /// Move the location by a byte offset, e.g., one lowered from a GEP
+    pub fn shift(&self, offset: &ByteOffset) -> Self {
+        let mut stride = self.stride;
+        for term in &offset.terms {
+            stride = gcd(stride, term.scale);
+        }
+        Self {
+            site: self.site.clone(),
+            offset: Integer::from(&self.offset + &offset.constant),
+            stride,
+        }
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}+{}", self.site, self.offset)?;
+        if self.stride != 0 {
+            write!(f, "+{}*?", self.stride)?;
+        }
+        Ok(())
+    }
+}
+
+/// A value stored at a byte range
+#[derive(Eq, PartialEq, Clone)]
+struct Cell<V: Lattice> {
+    size: usize,
+    value: V,
+}
+
+/// A location normalized against a region
+enum Target {
+    /// exactly this byte offset
+    Exact(usize),
+    /// offsets congruent to `offset` modulo `stride`
+    Strided { offset: usize, stride: usize },
+    /// possibly outside of the region
+    OutOfBounds,
+}
+
+/// Contents of one allocation, as non-overlapping cells keyed by their byte offsets
+///
+/// Bytes not covered by any cell are uninitialized: reading them yields top, and as defined
+/// executions never read them, they act as bottom when states are merged.
+#[derive(Eq, PartialEq, Clone)]
+struct Region<V: Lattice> {
+    precision: Precision,
+    /// size in bytes, if known
+    size: Option<usize>,
+    /// size of the summarized element under array smashing
+    element: usize,
+    /// whether the region stands for several objects
+    summary: bool,
+    lifetime: Lifetime,
+    cells: BTreeMap<usize, Cell<V>>,
+}
+
+impl<V: Lattice> Region<V> {
+    /// Whether one cell may stand for several concrete ones
+    fn is_summarized(&self) -> bool {
+        self.summary
+            || (self.precision == Precision::ArraySmashing && self.size != Some(self.element))
+    }
+
+    /// Bytes covered by the cells
+    fn extent(&self) -> usize {
+        match self.precision {
+            Precision::FieldSensitive => self.size.unwrap_or(usize::MAX),
+            Precision::ArraySmashing => self.element,
+        }
+    }
+
+    fn normalize(&self, loc: &Location, size: usize) -> Target {
+        if loc.is_exact() {
+            let in_bounds = match (loc.offset.to_usize(), self.size) {
+                (None, _) => false,
+                (Some(_), None) => true,
+                (Some(offset), Some(limit)) => {
+                    offset.checked_add(size).is_some_and(|end| end <= limit)
+                }
+            };
+            if !in_bounds {
+                return Target::OutOfBounds;
+            }
+        }
+
+        let offset = match self.precision {
+            Precision::FieldSensitive => loc.offset.clone(),
+            // fold every element onto the first one
+            Precision::ArraySmashing => loc.offset.clone().modulo(&Integer::from(self.element)),
+        };
+        let stride = match self.precision {
+            Precision::FieldSensitive => loc.stride,
+            Precision::ArraySmashing => {
+                if loc.stride.is_multiple_of(self.element) {
+                    0
+                } else {
+                    gcd(loc.stride, self.element)
+                }
+            }
+        };
+        let offset = match offset.to_usize() {
+            None => return Target::OutOfBounds,
+            Some(offset) => offset,
+        };
+
+        if stride == 0 {
+            match offset.checked_add(size) {
+                Some(end) if end <= self.extent() => Target::Exact(offset),
+                _ => Target::OutOfBounds,
+            }
+        } else {
+            Target::Strided {
+                offset: offset % stride,
+                stride,
+            }
+        }
+    }
+
+    /// Offsets of the cells overlapping with a byte range
+    fn overlapping(&self, offset: usize, size: usize) -> Vec<usize> {
+        self.cells
+            .iter()
+            .filter(|(start, cell)| **start < offset + size && offset < **start + cell.size)
+            .map(|(start, _)| *start)
+            .collect()
+    }
+
+    /// Whether a cell may overlap with an access at `offset + stride * k` for some k
+    fn may_overlap(
+        start: usize,
+        cell_size: usize,
+        offset: usize,
+        stride: usize,
+        size: usize,
+    ) -> bool {
+        if size >= stride || cell_size >= stride {
+            return true;
+        }
+        let delta = (start + stride - offset % stride) % stride;
+        delta < size || delta + cell_size > stride
+    }
+
+    /// Forget all cells overlapping with a byte range, leaving one top cell behind
+    fn clobber(&mut self, offset: usize, size: usize) {
+        let mut lo = offset;
+        let mut hi = offset + size;
+        for start in self.overlapping(offset, size) {
+            let cell = self.cells.remove(&start).unwrap();
+            lo = lo.min(start);
+            hi = hi.max(start + cell.size);
+        }
+        self.cells.insert(
+            lo,
+            Cell {
+                size: hi - lo,
+                value: V::top(),
+            },
+        );
+    }
+
+    /// Forget everything about the region
+    fn havoc_all(&mut self) {
+        self.cells.clear();
+        self.cells.insert(
+            0,
+            Cell {
+                size: self.extent(),
+                value: V::top(),
+            },
+        );
+    }
+
+    /// Fill the uninitialized bytes that an access may hit with top cells
+    fn fill_gaps(&mut self, offset: usize, stride: usize, size: usize) {
+        let extent = self.extent();
+        let mut gaps = vec![];
+        let mut cursor = 0;
+        for (start, cell) in &self.cells {
+            if *start > cursor {
+                gaps.push((cursor, *start - cursor));
+            }
+            cursor = cursor.max(start + cell.size);
+        }
+        if cursor < extent {
+            gaps.push((cursor, extent - cursor));
+        }
+
+        for (start, len) in gaps {
+            if Self::may_overlap(start, len, offset, stride, size) {
+                self.cells.insert(
+                    start,
+                    Cell {
+                        size: len,
+                        value: V::top(),
+                    },
+                );
+            }
+        }
+    }
+
+    fn read(&self, loc: &Location, size: usize) -> V {
+        match self.normalize(loc, size) {
+            Target::OutOfBounds => V::top(),
+            Target::Exact(offset) => match self.cells.get(&offset) {
+                Some(cell) if cell.size == size => cell.value.clone(),
+                _ => V::top(),
+            },
+            Target::Strided { offset, stride } => {
+                let mut result: Option<V> = None;
+                for (start, cell) in &self.cells {
+                    if !Self::may_overlap(*start, cell.size, offset, stride, size) {
+                        continue;
+                    }
+                    if cell.size != size || (*start % stride) != offset {
+                        return V::top();
+                    }
+                    result = Some(match result {
+                        None => cell.value.clone(),
+                        Some(value) => value.join(&cell.value),
+                    });
+                }
+                result.unwrap_or_else(V::top)
+            }
+        }
+    }
+
+    fn write(&mut self, loc: &Location, size: usize, value: V) {
+        match self.normalize(loc, size) {
+            Target::OutOfBounds => self.havoc_all(),
+            Target::Exact(offset) => {
+                let overlaps = self.overlapping(offset, size);
+                let exact = overlaps.len() == 1
+                    && overlaps[0] == offset
+                    && self
+                        .cells
+                        .get(&offset)
+                        .is_some_and(|cell| cell.size == size);
+
+                if !self.is_summarized() {
+                    // strong update, trimming the partially overwritten cells
+                    for start in overlaps {
+                        let cell = self.cells.remove(&start).unwrap();
+                        if start < offset {
+                            self.cells.insert(
+                                start,
+                                Cell {
+                                    size: offset - start,
+                                    value: V::top(),
+                                },
+                            );
+                        }
+                        if start + cell.size > offset + size {
+                            self.cells.insert(
+                                offset + size,
+                                Cell {
+                                    size: start + cell.size - offset - size,
+                                    value: V::top(),
+                                },
+                            );
+                        }
+                    }
+                    self.cells.insert(offset, Cell { size, value });
+                } else if exact {
+                    let cell = self.cells.get_mut(&offset).unwrap();
+                    cell.value = cell.value.join(&value);
+                } else if overlaps.is_empty() {
+                    // the other elements summarized are uninitialized
+                    self.cells.insert(offset, Cell { size, value });
+                } else {
+                    self.clobber(offset, size);
+                }
+            }
+            Target::Strided { offset, stride } => {
+                // weak update on every cell the access may hit
+                let starts: Vec<_> = self.cells.keys().copied().collect();
+                for start in starts {
+                    let cell = self.cells.get_mut(&start).unwrap();
+                    if !Self::may_overlap(start, cell.size, offset, stride, size) {
+                        continue;
+                    }
+                    cell.value = if cell.size == size && start % stride == offset {
+                        cell.value.join(&value)
+                    } else {
+                        V::top()
+                    };
+                }
+                self.fill_gaps(offset, stride, size);
+            }
+        }
+    }
+
+    /// Merge two regions of the same site, with `op` combining cells at the same byte range
+    fn merge<F: Fn(&V, &V) -> V>(&self, other: &Self, op: F) -> Self {
+        // sweep the cells of both sides in the order of their offsets
+        let mut entries: Vec<_> = self
+            .cells
+            .iter()
+            .map(|(start, cell)| (*start, cell, true))
+            .chain(
+                other
+                    .cells
+                    .iter()
+                    .map(|(start, cell)| (*start, cell, false)),
+            )
+            .collect();
+        entries.sort_by_key(|(start, _, _)| *start);
+
+        let mut cells = BTreeMap::new();
+        let mut cluster: Vec<(usize, &Cell<V>, bool)> = vec![];
+        let mut cluster_end = 0;
+        let flush = |cluster: &mut Vec<(usize, &Cell<V>, bool)>,
+                     cells: &mut BTreeMap<usize, Cell<V>>| {
+            match cluster.as_slice() {
+                [] => (),
+                // present on one side only, uninitialized on the other
+                [(start, cell, _)] => {
+                    cells.insert(*start, (*cell).clone());
+                }
+                [(start, lhs, true), (_, rhs, false)] | [(start, rhs, false), (_, lhs, true)]
+                    if lhs.size == rhs.size =>
+                {
+                    cells.insert(
+                        *start,
+                        Cell {
+                            size: lhs.size,
+                            value: op(&lhs.value, &rhs.value),
+                        },
+                    );
+                }
+                _ => {
+                    // conflicting layouts
+                    let lo = cluster[0].0;
+                    let hi = cluster
+                        .iter()
+                        .map(|(start, cell, _)| start + cell.size)
+                        .max()
+                        .unwrap();
+                    cells.insert(
+                        lo,
+                        Cell {
+                            size: hi - lo,
+                            value: V::top(),
+                        },
+                    );
+                }
+            }
+            cluster.clear();
+        };
+        for (start, cell, side) in entries {
+            if !cluster.is_empty() && start >= cluster_end {
+                flush(&mut cluster, &mut cells);
+            }
+            cluster_end = if cluster.is_empty() {
+                start + cell.size
+            } else {
+                cluster_end.max(start + cell.size)
+            };
+            cluster.push((start, cell, side));
+        }
+        flush(&mut cluster, &mut cells);
+
+        Self {
+            precision: self.precision,
+            size: if self.size == other.size {
+                self.size
+            } else {
+                None
+            },
+            element: self.element,
+            summary: self.summary || other.summary,
+            lifetime: self.lifetime.join(other.lifetime),
+            cells,
+        }
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        if !self.lifetime.leq(other.lifetime) {
+            return false;
+        }
+        // every initialized byte range here must be covered by the other side
+        for (start, cell) in &self.cells {
+            let covers = other.overlapping(*start, cell.size);
+            match covers.as_slice() {
+                [single] => {
+                    let rhs = other.cells.get(single).unwrap();
+                    if rhs.value.is_top() {
+                        if *single > *start || single + rhs.size < start + cell.size {
+                            return false;
+                        }
+                    } else if *single != *start
+                        || rhs.size != cell.size
+                        || !cell.value.leq(&rhs.value)
+                    {
+                        return false;
+                    }
+                }
+                _ => return false,
+            }
+        }
+        // every initialized byte range on the other side must be compatible here
+        for (start, cell) in &other.cells {
+            if cell.value.is_top() {
+                continue;
+            }
+            for overlap in self.overlapping(*start, cell.size) {
+                if overlap != *start {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+/// A byte-precise model of the memory, keyed by allocation sites
+#[derive(Eq, PartialEq, Clone)]
+pub struct MemoryDomain<V: Lattice> {
+    regions: BTreeMap<AllocSite, Region<V>>,
+}
+
+impl<V: Lattice> Default for MemoryDomain<V> {
+    fn default() -> Self {
+        Self {
+            regions: BTreeMap::new(),
+        }
+    }
+}
+
+/// Utility: innermost element type of a (possibly nested) array type
+fn innermost_element(ty: &Type) -> &Type {
+    match ty {
+        Type::Array { element, length: _ } => innermost_element(element),
+        _ => ty,
+    }
+}
+
+impl<V: Lattice> MemoryDomain<V> {
+    /// Track a fresh allocation of `count` objects of the type, with an unknown count forcing
+    /// array smashing
+    pub fn allocate(
+        &mut self,
+        config: &MemoryConfig,
+        layout: &DataLayout,
+        site: AllocSite,
+        ty: &Type,
+        count: Option<usize>,
+    ) -> EngineResult<()> {
+        let unit = layout.alloc_size(ty)?;
+        let size = match count {
+            None => None,
+            Some(n) => Some(unit.checked_mul(n).ok_or_else(|| {
+                EngineError::InvalidAssumption(format!("allocation size overflow at {}", site))
+            })?),
+        };
+        let (precision, element) = match (config.precision_of(&site), count) {
+            (Precision::FieldSensitive, Some(_)) => (Precision::FieldSensitive, unit),
+            (Precision::FieldSensitive, None) | (Precision::ArraySmashing, _) => (
+                Precision::ArraySmashing,
+                layout.alloc_size(innermost_element(ty))?,
+            ),
+        };
+        if element == 0 {
+            return Err(EngineError::InvalidAssumption(format!(
+                "zero-sized allocation at {}",
+                site
+            )));
+        }
+
+        self.regions.insert(
+            site,
+            Region {
+                precision,
+                size,
+                element,
+                summary: false,
+                lifetime: Lifetime::Live,
+                cells: BTreeMap::new(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Track the allocation made by a global variable
+    pub fn allocate_global(
+        &mut self,
+        config: &MemoryConfig,
+        layout: &DataLayout,
+        gvar: &GlobalVariable,
+    ) -> EngineResult<()> {
+        self.allocate(
+            config,
+            layout,
+            AllocSite::Global(gvar.name.clone()),
+            &gvar.ty,
+            Some(1),
+        )
+    }
+
+    /// Track the allocation made by an alloca instruction at the site
+    pub fn allocate_stack(
+        &mut self,
+        config: &MemoryConfig,
+        layout: &DataLayout,
+        function: &Identifier,
+        site: Site,
+        inst: &Instruction,
+    ) -> EngineResult<()> {
+        let (base_type, size) = match inst {
+            Instruction::Alloca {
+                base_type,
+                size,
+                align: _,
+                result: _,
+            } => (base_type, size),
+            _ => {
+                return Err(EngineError::InvariantViolation(
+                    "expect an alloca instruction for stack allocation".into(),
+                ));
+            }
+        };
+        let count = match size {
+            None => Some(1),
+            Some(Value::Constant(Constant::NumOne {
+                bits: _,
+                value: NumValue::Int(n),
+            })) => n.to_usize(),
+            Some(_) => None,
+        };
+        let site = AllocSite::Stack {
+            function: function.clone(),
+            site,
+        };
+        self.allocate(config, layout, site, base_type, count)
+    }
+
+    /// Track a fresh heap object of `size` bytes (if known) allocated at the call site
+    ///
+    /// The previous most-recent object of the site, if any, is folded into the summary of the
+    /// site; returns whether this happened, in which case the value domain shall redirect the
+    /// pointers to the most-recent object onto the summary.
+    pub fn allocate_heap(
+        &mut self,
+        config: &MemoryConfig,
+        layout: &DataLayout,
+        function: &Identifier,
+        site: Site,
+        size: Option<usize>,
+        element: Option<&Type>,
+    ) -> EngineResult<bool> {
+        let recent = AllocSite::Heap {
+            function: function.clone(),
+            site,
+            recency: Recency::MostRecent,
+        };
+
+        // age the previous object
+        let aged = match self.regions.remove(&recent) {
+            None => false,
+            Some(mut region) => {
+                region.summary = true;
+                let summary = recent.with_recency(Recency::Summary);
+                let merged = match self.regions.get(&summary) {
+                    None => region,
+                    Some(existing) => existing.merge(&region, V::join),
+                };
+                self.regions.insert(summary, merged);
+                true
+            }
+        };
+
+        // smash only when the element type is known
+        let (precision, element) = match (config.precision_of(&recent), element) {
+            (Precision::ArraySmashing, Some(ty)) => (
+                Precision::ArraySmashing,
+                layout.alloc_size(innermost_element(ty))?,
+            ),
+            (Precision::FieldSensitive, _) | (Precision::ArraySmashing, None) => {
+                (Precision::FieldSensitive, size.unwrap_or(0))
+            }
+        };
+        if precision == Precision::ArraySmashing && element == 0 {
+            return Err(EngineError::InvalidAssumption(format!(
+                "zero-sized heap element at {}",
+                recent
+            )));
+        }
+
+        self.regions.insert(
+            recent,
+            Region {
+                precision,
+                size,
+                element,
+                summary: false,
+                lifetime: Lifetime::Live,
+                cells: BTreeMap::new(),
+            },
+        );
+        Ok(aged)
+    }
+
+    /// Release the object at the site: the most-recent object is freed for sure while the
+    /// summary is freed for one of its objects only
+    pub fn free(&mut self, site: &AllocSite) {
+        if let Some(region) = self.regions.get_mut(site) {
+            region.lifetime = if region.summary {
+                region.lifetime.join(Lifetime::Freed)
+            } else {
+                Lifetime::Freed
+            };
+        }
+    }
+
+    /// Lifetime of a tracked allocation
+    pub fn lifetime_of(&self, site: &AllocSite) -> Option<Lifetime> {
+        self.regions.get(site).map(|region| region.lifetime)
+    }
+
+    /// Heap objects possibly not freed yet, e.g., for leak detection at program exit
+    pub fn live_heap_objects(&self) -> impl Iterator<Item = &AllocSite> {
+        self.regions
+            .iter()
+            .filter(|(site, region)| {
+                matches!(site, AllocSite::Heap { .. }) && region.lifetime != Lifetime::Freed
+            })
+            .map(|(site, _)| site)
+    }
+
+    /// Stop tracking an allocation, e.g., when its function returns
+    pub fn deallocate(&mut self, site: &AllocSite) {
+        self.regions.remove(site);
+    }
+
+    /// Whether the allocation is tracked
+    pub fn contains(&self, site: &AllocSite) -> bool {
+        self.regions.contains_key(site)
+    }
+
+    /// Precision applied to a tracked allocation
+    pub fn precision_of(&self, site: &AllocSite) -> Option<Precision> {
+        self.regions.get(site).map(|region| region.precision)
+    }
+
+    /// Value of `size` bytes at the location
+    pub fn read(&self, loc: &Location, size: usize) -> V {
+        match self.regions.get(&loc.site) {
+            None => V::top(),
+            Some(region) => region.read(loc, size),
+        }
+    }
+
+    /// Store `size` bytes at the location, strongly if it is a single concrete address
+    pub fn write(&mut self, loc: &Location, size: usize, value: V) {
+        if let Some(region) = self.regions.get_mut(&loc.site) {
+            region.write(loc, size, value);
+        }
+    }
+
+    /// Forget the contents at the location, e.g., on volatile accesses
+    pub fn havoc(&mut self, loc: &Location, size: usize) {
+        self.write(loc, size, V::top());
+    }
+
+    fn merge<F: Fn(&Region<V>, &Region<V>) -> Region<V>>(&self, other: &Self, op: F) -> Self {
+        let mut regions = self.regions.clone();
+        for (site, rhs) in &other.regions {
+            let merged = match regions.get(site) {
+                None => rhs.clone(),
+                Some(lhs) => op(lhs, rhs),
+            };
+            regions.insert(site.clone(), merged);
+        }
+        Self { regions }
+    }
+
+    /// Least upper bound of two memory states
+    pub fn join(&self, other: &Self) -> Self {
+        self.merge(other, |lhs, rhs| lhs.merge(rhs, V::join))
+    }
+
+    /// Join with the values in matching cells widened
+    pub fn widen(&self, other: &Self) -> Self {
+        self.merge(other, |lhs, rhs| lhs.merge(rhs, V::widen))
+    }
+
+    /// Partial order of memory states
+    pub fn leq(&self, other: &Self) -> bool {
+        self.regions
+            .iter()
+            .all(|(site, lhs)| match other.regions.get(site) {
+                None => false,
+                Some(rhs) => lhs.leq(rhs),
+            })
+    }
+}
+
+impl<V: Lattice + Display> Display for MemoryDomain<V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (site, region) in &self.regions {
+            match region.size {
+                None => write!(f, "{} [?] ({}", site, region.precision)?,
+                Some(size) => write!(f, "{} [{}] ({}", site, size, region.precision)?,
+            }
+            writeln!(f, ", {}):", region.lifetime)?;
+            for (start, cell) in &region.cells {
+                writeln!(f, "  [{}, {}) = {}", start, start + cell.size, cell.value)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+pub mod affine;
+pub mod bounds;
+pub mod config;
+pub mod congruence;
+pub mod cstring;
+pub mod dataflow;
+pub mod deadcode;
+pub mod diagnostics;
+pub mod escape;
+pub mod interval;
+pub mod invariant;
+pub mod lattice;
+pub mod libc;
+pub mod lifetime;
+pub mod memory;
+pub mod overflow;
+pub mod paths;
+pub mod pointer;
+pub mod pointsto;
+pub mod powerset;
+pub mod product;
+pub mod summary;
+#[cfg(feature = "smt")]
+pub mod symbolic;
+pub mod taint;
+pub mod unification;
+pub mod uninit;
+pub mod variable;
+pub mod verify;
@@ -0,0 +1,214 @@
+use std::fmt::{Display, Formatter};
+
+use rug::Integer;
+
+use crate::analysis::dataflow::Findings;
+use crate::analysis::interval::{eval_value, Bound, Interval};
+use crate::analysis::invariant::{analyze_intervals, Env};
+use crate::analysis::lattice::Lattice;
+use crate::analysis::summary::SolverConfig;
+use crate::error::EngineResult;
+use crate::ir::bridge::instruction::{BinaryOpArith, Instruction};
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::typing::NumRepr;
+use crate::ir::defuse::Site;
+
+/// Reading of the operands under which an operation promises not to wrap
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub enum Wrap {
+    /// `nsw`
+    Signed,
+    /// `nuw`
+    Unsigned,
+}
+
+impl Display for Wrap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Signed => write!(f, "nsw"),
+            Self::Unsigned => write!(f, "nuw"),
+        }
+    }
+}
+
+/// Certainty of an overflow
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub enum OverflowKind {
+    /// the result is out of range on every execution reaching the operation
+    Definite,
+    /// the result may be out of range
+    Possible,
+}
+
+impl Display for OverflowKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Definite => write!(f, "definite"),
+            Self::Possible => write!(f, "possible"),
+        }
+    }
+}
+
+/// An arithmetic operation that may break its no-wrap promise, yielding poison
+pub struct OverflowSite {
+    pub site: Site,
+    pub opcode: BinaryOpArith,
+    /// width of the operation
+    pub bits: usize,
+    pub wrap: Wrap,
+    /// mathematical result of the operation, under the reading of the promise
+    pub range: Interval,
+    pub kind: OverflowKind,
+}
+
+impl Display for OverflowSite {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} {} i{} yields {} ({})",
+            self.site, self.opcode, self.wrap, self.bits, self.range, self.kind
+        )
+    }
+}
+
+/// Potential overflow sites of a function
+pub struct OverflowSummary {
+    pub function: Identifier,
+    /// number of operations carrying a no-wrap flag
+    pub checked: usize,
+    pub sites: Vec<OverflowSite>,
+}
+
+impl Display for OverflowSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "@{}: {} of {} no-wrap operations may overflow",
+            self.function,
+            self.sites.len(),
+            self.checked
+        )?;
+        for site in &self.sites {
+            writeln!(f, "  {}", site)?;
+        }
+        Ok(())
+    }
+}
+
+/// Utility: the unsigned reading of an interval held in the signed reading
+fn unsigned_reading(value: &Interval, bits: usize) -> Interval {
+    let modulus = Integer::from(1) << bits as u32;
+    if value.is_non_negative() {
+        return value.clone();
+    }
+    match value.bounds() {
+        Some((_, Bound::Finite(hi))) if *hi < 0 => value.add(&Interval::singleton(modulus)),
+        _ => Interval::finite(Integer::new(), modulus - 1),
+    }
+}
+
+/// Check an operation against one of its promises
+fn check(
+    opcode: &BinaryOpArith,
+    bits: usize,
+    wrap: Wrap,
+    lhs: &Interval,
+    rhs: &Interval,
+) -> Option<(Interval, OverflowKind)> {
+    let (lhs, rhs, bound) = match wrap {
+        Wrap::Signed => (lhs.clone(), rhs.clone(), Interval::full(bits)),
+        Wrap::Unsigned => (
+            unsigned_reading(lhs, bits),
+            unsigned_reading(rhs, bits),
+            Interval::finite(Integer::new(), (Integer::from(1) << bits as u32) - 1),
+        ),
+    };
+    let range = match opcode {
+        BinaryOpArith::Add => lhs.add(&rhs),
+        BinaryOpArith::Sub => lhs.sub(&rhs),
+        BinaryOpArith::Mul => lhs.mul(&rhs),
+        BinaryOpArith::Div | BinaryOpArith::Mod => return None,
+    };
+    if range.leq(&bound) {
+        return None;
+    }
+    let kind = if range.meet(&bound).is_bottom() {
+        OverflowKind::Definite
+    } else {
+        OverflowKind::Possible
+    };
+    Some((range, kind))
+}
+
+/// Check the no-wrap operations of an instruction in the interval state before it
+fn check_instruction(env: &Env, site: Site, inst: &Instruction, summary: &mut OverflowSummary) {
+    let (bits, opcode, nsw, nuw, lhs, rhs) = match inst {
+        Instruction::BinaryArith {
+            bits,
+            number: NumRepr::Int,
+            length: None,
+            opcode,
+            nsw,
+            nuw,
+            lhs,
+            rhs,
+            result: _,
+        } if (*nsw || *nuw) && *bits > 1 => (*bits, opcode, *nsw, *nuw, lhs, rhs),
+        _ => return,
+    };
+    summary.checked += 1;
+
+    let l = eval_value(lhs, bits, &|v| env.get(v));
+    let r = eval_value(rhs, bits, &|v| env.get(v));
+    if l.is_bottom() || r.is_bottom() {
+        return;
+    }
+    let wraps = [(nsw, Wrap::Signed), (nuw, Wrap::Unsigned)];
+    for wrap in wraps.into_iter().filter(|(flag, _)| *flag).map(|(_, w)| w) {
+        if let Some((range, kind)) = check(opcode, bits, wrap, &l, &r) {
+            summary.sites.push(OverflowSite {
+                site,
+                opcode: opcode.clone(),
+                bits,
+                wrap,
+                range,
+                kind,
+            });
+        }
+    }
+}
+
+/// Find the `nsw`/`nuw` arithmetic whose result interval exceeds the width, per function
+pub fn check_overflows(
+    module: &Module,
+    solver: &SolverConfig,
+) -> EngineResult<Findings<OverflowSummary>> {
+    let intervals = analyze_intervals(module, solver);
+    let mut summaries = Findings::new();
+    for (name, func) in module.functions() {
+        let (cfg, states) = match (&func.body, intervals.get(name)) {
+            (Some(cfg), Some(states)) => (cfg, states),
+            _ => continue,
+        };
+        let mut summary = OverflowSummary {
+            function: name.clone(),
+            checked: 0,
+            sites: vec![],
+        };
+        summaries.record(name, states.exhausted());
+        for (label, block) in cfg.blocks() {
+            for (index, inst) in block.instructions().iter().enumerate() {
+                let site = Site::Instruction {
+                    block: *label,
+                    index,
+                };
+                if let Some(env) = states.get(&site) {
+                    check_instruction(env, site, inst, &mut summary);
+                }
+            }
+        }
+        summaries.push(summary);
+    }
+    Ok(summaries)
+}
@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use crate::analysis::lattice::Lattice;
+use crate::ir::bridge::cfg::{Block, ControlFlowGraph, Edge};
+use crate::ir::bridge::value::BlockLabel;
+
+/// Limits that keep path enumeration tractable
+#[derive(Clone, Copy)]
+pub struct PathBudget {
+    /// maximum number of complete paths (i.e., reaching an exit block)
+    pub max_paths: usize,
+    /// maximum number of blocks along a single path
+    pub max_depth: usize,
+    /// maximum number of times a block is revisited along a single path (loop unrolling)
+    pub max_unroll: usize,
+    /// maximum number of distinct states kept per block before they are merged
+    pub max_states: usize,
+    /// maximum number of block visits in total
+    pub max_steps: usize,
+}
+
+impl Default for PathBudget {
+    fn default() -> Self {
+        Self {
+            max_paths: 1024,
+            max_depth: 256,
+            max_unroll: 2,
+            max_states: 8,
+            max_steps: 65536,
+        }
+    }
+}
+
+/// Sensitivity of a checker to control-flow paths
+#[derive(Clone, Copy, Default)]
+pub enum PathMode {
+    /// states are joined at every merge point (the default)
+    #[default]
+    Insensitive,
+    /// paths are enumerated by a bounded DFS, merging states only when the budget requires
+    Sensitive(PathBudget),
+}
+
+/// Transfer functions driven by the path enumeration
+pub trait PathTransfer {
+    type State: Lattice;
+
+    /// Effect of executing a block on a path
+    fn block(&mut self, label: &BlockLabel, block: &Block, state: &Self::State) -> Self::State;
+
+    /// Refinement of the state when taking an edge (e.g., assuming a branch condition),
+    /// returning bottom for an infeasible edge
+    fn edge(
+        &mut self,
+        _src: &BlockLabel,
+        _dst: &BlockLabel,
+        _edge: &Edge,
+        state: &Self::State,
+    ) -> Self::State {
+        state.clone()
+    }
+}
+
+/// Statistics of a bounded path enumeration
+#[derive(Default)]
+pub struct PathReport {
+    /// number of complete paths explored
+    pub paths: usize,
+    /// number of block visits
+    pub steps: usize,
+    /// number of paths found infeasible
+    pub infeasible: usize,
+    /// number of paths pruned as their states are subsumed by a prior visit
+    pub subsumed: usize,
+    /// number of times the states at a block are merged
+    pub merged: usize,
+    /// number of paths cut by the depth or unrolling bounds
+    pub truncated: usize,
+    /// whether the enumeration stops early due to the path or step budget
+    pub exhausted: bool,
+}
+
+impl Display for PathReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            paths,
+            steps,
+            infeasible,
+            subsumed,
+            merged,
+            truncated,
+            exhausted,
+        } = self;
+        write!(
+            f,
+            "{} paths in {} steps (infeasible: {}, subsumed: {}, merged: {}, truncated: {}){}",
+            paths,
+            steps,
+            infeasible,
+            subsumed,
+            merged,
+            truncated,
+            if *exhausted { ", budget exhausted" } else { "" }
+        )
+    }
+}
+
+/// A pending visit of a block on a path
+struct Frontier<S> {
+    label: BlockLabel,
+    state: S,
+    depth: usize,
+    visits: BTreeMap<BlockLabel, usize>,
+}
+
+/// Enumerate the paths of a CFG from the entry block in DFS order, within the budget
+pub fn explore<T: PathTransfer>(
+    cfg: &ControlFlowGraph,
+    budget: &PathBudget,
+    transfer: &mut T,
+    initial: T::State,
+) -> PathReport {
+    let mut report = PathReport::default();
+    let mut seen: BTreeMap<BlockLabel, Vec<T::State>> = BTreeMap::new();
+
+    let mut stack = vec![Frontier {
+        label: cfg.entry(),
+        state: initial,
+        depth: 0,
+        visits: BTreeMap::new(),
+    }];
+    while let Some(item) = stack.pop() {
+        let Frontier {
+            label,
+            mut state,
+            depth,
+            mut visits,
+        } = item;
+
+        // budget on the overall effort
+        if report.paths >= budget.max_paths || report.steps >= budget.max_steps {
+            report.exhausted = true;
+            break;
+        }
+
+        // infeasible paths
+        if state.is_bottom() {
+            report.infeasible += 1;
+            continue;
+        }
+
+        // subsumption by states already explored from this block
+        let states = seen.entry(label).or_default();
+        if states.iter().any(|prior| state.leq(prior)) {
+            report.subsumed += 1;
+            continue;
+        }
+
+        // merge heuristic: too many distinct states reaching the block
+        states.push(state.clone());
+        if states.len() > budget.max_states {
+            state = states.iter().fold(T::State::bottom(), |acc, s| acc.join(s));
+            *states = vec![state.clone()];
+            report.merged += 1;
+        }
+
+        // bounds on a single path
+        let count = visits.entry(label).or_insert(0);
+        *count += 1;
+        if *count > budget.max_unroll || depth >= budget.max_depth {
+            report.truncated += 1;
+            continue;
+        }
+
+        // execute the block
+        report.steps += 1;
+        let block = cfg
+            .get_block_by_label(&label)
+            .expect("block label points to a valid node");
+        let state = transfer.block(&label, block, &state);
+
+        // extend the path, pushing successors in reverse so that they are popped in order
+        let succs = cfg.successors(&label);
+        if succs.is_empty() {
+            report.paths += 1;
+            continue;
+        }
+        for (dst, edge) in succs.into_iter().rev() {
+            stack.push(Frontier {
+                label: dst,
+                state: transfer.edge(&label, &dst, edge, &state),
+                depth: depth + 1,
+                visits: visits.clone(),
+            });
+        }
+    }
+    report
+}
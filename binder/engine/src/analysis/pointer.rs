@@ -0,0 +1,841 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use rug::Integer;
+
+use crate::analysis::dataflow::{
+    solve_transfer, CfgState, Exhaustion, Filter, Findings, MemoryStore, TransferFunction, Widening,
+};
+use crate::analysis::interval::{eval_value, Bound, Interval};
+use crate::analysis::invariant::{analyze_intervals, Env};
+use crate::analysis::lattice::Lattice;
+use crate::analysis::libc::{callee_model, LibcModel, Returns};
+use crate::analysis::memory::{AllocSite, HeapAllocation, Recency};
+use crate::analysis::pointsto::{
+    analyze_points_to, constant_addresses, is_allocator, Node, PointsTo,
+};
+use crate::analysis::summary::SolverConfig;
+use crate::analysis::variable::{int_bits, Variable};
+use crate::error::EngineResult;
+use crate::ir::bridge::cfg::{ControlFlowGraph, Edge};
+use crate::ir::bridge::constant::Constant;
+use crate::ir::bridge::function::Function;
+use crate::ir::bridge::instruction::{ComparePredicate, Instruction, Terminator};
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::value::{BlockLabel, RegisterSlot, Value};
+use crate::ir::defuse::Site;
+
+/// Where a pointer may point to at a program point
+#[derive(Eq, PartialEq, Clone)]
+pub struct Pointees {
+    /// whether the pointer may be null
+    pub null: bool,
+    /// whether the pointer may hold an address not tracked by the analysis
+    pub unknown: bool,
+    /// allocations the pointer may point into, empty if the address is unknown
+    pub sites: BTreeSet<AllocSite>,
+}
+
+impl Pointees {
+    pub fn new(null: bool, unknown: bool, sites: BTreeSet<AllocSite>) -> Self {
+        let sites = if unknown { BTreeSet::new() } else { sites };
+        Self {
+            null,
+            unknown,
+            sites,
+        }
+    }
+
+    /// The null pointer
+    pub fn null() -> Self {
+        Self::new(true, false, BTreeSet::new())
+    }
+
+    /// A pointer into exactly one allocation
+    pub fn to(site: AllocSite) -> Self {
+        Self::new(false, false, BTreeSet::from([site]))
+    }
+
+    /// Whether the pointer is null on every execution
+    pub fn is_null(&self) -> bool {
+        self.null && !self.unknown && self.sites.is_empty()
+    }
+
+    /// The only allocation pointed to, which allows strong updates
+    pub fn unique_target(&self) -> Option<&AllocSite> {
+        if self.null || self.unknown || self.sites.len() != 1 {
+            return None;
+        }
+        self.sites.first()
+    }
+
+    /// The same pointer assumed to be non-null
+    pub fn non_null(&self) -> Self {
+        Self::new(false, self.unknown, self.sites.clone())
+    }
+}
+
+impl Lattice for Pointees {
+    fn bottom() -> Self {
+        Self::new(false, false, BTreeSet::new())
+    }
+
+    fn top() -> Self {
+        Self::new(true, true, BTreeSet::new())
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        (!self.null || other.null)
+            && (other.unknown || (!self.unknown && self.sites.is_subset(&other.sites)))
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        Self::new(
+            self.null || other.null,
+            self.unknown || other.unknown,
+            self.sites.union(&other.sites).cloned().collect(),
+        )
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        let sites = match (self.unknown, other.unknown) {
+            (true, _) => other.sites.clone(),
+            (_, true) => self.sites.clone(),
+            (false, false) => self.sites.intersection(&other.sites).cloned().collect(),
+        };
+        Self::new(
+            self.null && other.null,
+            self.unknown && other.unknown,
+            sites,
+        )
+    }
+}
+
+impl Display for Pointees {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut items = vec![];
+        if self.null {
+            items.push("null".to_string());
+        }
+        items.extend(self.sites.iter().map(|site| site.to_string()));
+        if self.unknown {
+            items.push("?".to_string());
+        }
+        write!(f, "{{{}}}", items.join(", "))
+    }
+}
+
+/// Pointers held in variables and in the stack objects of the function
+pub type PointerStore = MemoryStore<Pointees>;
+
+/// Flow-sensitive pointer analysis of a function, falling back on the flow-insensitive
+/// points-to sets for whatever is not local to the function
+struct Analysis<'a> {
+    module: &'a Module,
+    function: &'a Identifier,
+    cfg: &'a ControlFlowGraph,
+    widening: &'a Widening,
+    global: &'a PointsTo,
+    /// stack objects of the function
+    locals: BTreeSet<AllocSite>,
+    /// stack objects whose address may be seen by other functions
+    escaped: BTreeSet<AllocSite>,
+    /// comparisons against null: the pointer compared and whether a true result means null
+    null_tests: BTreeMap<RegisterSlot, (Variable, bool)>,
+}
+
+impl<'a> Analysis<'a> {
+    fn new(
+        module: &'a Module,
+        global: &'a PointsTo,
+        func: &'a Function,
+        cfg: &'a ControlFlowGraph,
+        widening: &'a Widening,
+    ) -> Self {
+        let mut locals = BTreeSet::new();
+        let mut null_tests = BTreeMap::new();
+        for (label, block) in cfg.blocks() {
+            for (index, inst) in block.instructions().iter().enumerate() {
+                match inst {
+                    Instruction::Alloca { .. } => {
+                        locals.insert(AllocSite::Stack {
+                            function: func.name.clone(),
+                            site: Site::Instruction {
+                                block: *label,
+                                index,
+                            },
+                        });
+                    }
+                    Instruction::ComparePtr {
+                        predicate,
+                        lhs,
+                        rhs,
+                        result,
+                    } => {
+                        if let Some(var) = null_test_operand(lhs, rhs) {
+                            match predicate {
+                                ComparePredicate::EQ => {
+                                    null_tests.insert(*result, (var, true));
+                                }
+                                ComparePredicate::NE => {
+                                    null_tests.insert(*result, (var, false));
+                                }
+                                _ => (),
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        // an address stored where other functions see it, passed, or returned may be used by
+        // other functions
+        let escaped = global.escapes().escaped_in(&func.name).cloned().collect();
+
+        Self {
+            module,
+            function: &func.name,
+            cfg,
+            widening,
+            global,
+            locals,
+            escaped,
+            null_tests,
+        }
+    }
+
+    /// Points-to set of a node by the flow-insensitive analysis, possibly null
+    fn fallback(&self, node: &Node) -> Pointees {
+        Pointees::new(true, false, self.global.points_to(node))
+    }
+
+    fn eval(&self, state: &PointerStore, value: &Value) -> Pointees {
+        match value {
+            Value::Constant(Constant::Null) => Pointees::null(),
+            Value::Constant(constant) => {
+                let sites = constant_addresses(constant);
+                if sites.is_empty() {
+                    Pointees::top()
+                } else {
+                    Pointees::new(false, false, sites)
+                }
+            }
+            _ => {
+                let var = Variable::of_value(value).expect("non-constant value");
+                state.variables.get(&var)
+            }
+        }
+    }
+
+    /// Pointers held in the objects pointed to
+    fn load(&self, state: &PointerStore, target: &Pointees) -> Pointees {
+        if target.unknown {
+            return Pointees::top();
+        }
+        let (locals, others): (Vec<_>, Vec<_>) = target
+            .sites
+            .iter()
+            .partition(|object| self.locals.contains(*object));
+        others.into_iter().fold(state.load(locals), |acc, object| {
+            acc.join(&self.fallback(&Node::Object(object.clone())))
+        })
+    }
+
+    /// Stack objects of the function a pointer may point to
+    fn local_targets<'t>(&'t self, target: &'t Pointees) -> Vec<&'t AllocSite> {
+        if target.unknown {
+            self.locals.iter().collect()
+        } else {
+            target.sites.intersection(&self.locals).collect()
+        }
+    }
+
+    /// Effect of a call to a modeled library function, which leaves the objects not passed to
+    /// it untouched
+    fn call_model(
+        &self,
+        state: &mut PointerStore,
+        model: &LibcModel,
+        args: &[Value],
+        result: Option<RegisterSlot>,
+    ) {
+        for (index, arg) in args.iter().enumerate() {
+            if !model.writes.contains(&index) {
+                continue;
+            }
+            let target = self.eval(state, arg);
+            let copied = model
+                .copies
+                .iter()
+                .filter(|(dst, _)| *dst == index)
+                .filter_map(|(_, src)| args.get(*src))
+                .fold(Pointees::bottom(), |acc, src| {
+                    acc.join(&self.load(state, &self.eval(state, src)))
+                });
+            for object in self.local_targets(&target) {
+                // bytes other than copied pointers may be written as well
+                let value = if model.copies.iter().any(|(dst, _)| *dst == index) {
+                    copied.clone()
+                } else {
+                    self.fallback(&Node::Object(object.clone()))
+                };
+                state.memory.update(object.clone(), &value);
+            }
+        }
+
+        if let Some(slot) = result {
+            let value = match model.returns {
+                Returns::Argument(index) => args.get(index).map(|arg| self.eval(state, arg)),
+                Returns::ArgumentOrNull(index) | Returns::IntoOrNull(index) => args
+                    .get(index)
+                    .map(|arg| self.eval(state, arg).join(&Pointees::null())),
+                Returns::Opaque | Returns::Range(..) | Returns::NonNegative => None,
+            };
+            state.variables.set(
+                Variable::Register(slot),
+                value.unwrap_or_else(Pointees::top),
+            );
+        }
+    }
+
+    fn initial(&self, func: &Function) -> PointerStore {
+        let mut state = PointerStore::top();
+        for index in 0..func.params.len() {
+            let node = Node::Argument {
+                function: self.function.clone(),
+                slot: index.into(),
+            };
+            state
+                .variables
+                .set(Variable::Argument(index.into()), self.fallback(&node));
+        }
+        state
+    }
+}
+
+impl TransferFunction for Analysis<'_> {
+    type State = PointerStore;
+
+    /// Effect of a call on the result and on the escaped stack objects
+    fn call(
+        &self,
+        state: &mut PointerStore,
+        site: Site,
+        callee: Option<&Identifier>,
+        args: &[Value],
+        result: Option<RegisterSlot>,
+    ) {
+        if let Some(name) = callee {
+            if is_allocator(name.as_ref()) {
+                if let Some(slot) = result {
+                    let object = AllocSite::Heap {
+                        function: self.function.clone(),
+                        site,
+                        recency: Recency::Summary,
+                    };
+                    // allocation may fail
+                    let value = Pointees::to(object).join(&Pointees::null());
+                    state.variables.set(Variable::Register(slot), value);
+                }
+                return;
+            }
+            if let Some(model) = callee_model(self.module, name.as_ref()) {
+                self.call_model(state, model, args, result);
+                return;
+            }
+        }
+
+        if let Some(slot) = result {
+            let defined = callee
+                .and_then(|name| self.module.get_function(name.as_ref()))
+                .is_some_and(|func| func.exact_body().is_some());
+            let value = if defined || callee.is_none() {
+                self.fallback(&Node::Register {
+                    function: self.function.clone(),
+                    slot,
+                })
+            } else {
+                Pointees::top()
+            };
+            state.variables.set(Variable::Register(slot), value);
+        }
+        // escaped objects survive a callee that writes no memory
+        if callee.is_some_and(|name| !self.module.effect(name.as_ref()).writes_memory()) {
+            return;
+        }
+        for object in &self.escaped {
+            let value = self.fallback(&Node::Object(object.clone()));
+            state.memory.update(object.clone(), &value);
+        }
+    }
+
+    fn instruction(&self, state: &mut PointerStore, site: Site, inst: &Instruction) {
+        match inst {
+            Instruction::Alloca { result, .. } => {
+                let object = AllocSite::Stack {
+                    function: self.function.clone(),
+                    site,
+                };
+                state
+                    .variables
+                    .set(Variable::Register(*result), Pointees::to(object));
+            }
+            Instruction::Load {
+                pointer, result, ..
+            } => {
+                let target = self.eval(state, pointer);
+                let value = self.load(state, &target);
+                state.variables.set(Variable::Register(*result), value);
+            }
+            Instruction::Store { pointer, value, .. } => {
+                let target = self.eval(state, pointer);
+                let value = self.eval(state, value);
+                let strong = target.unique_target().is_some();
+                state.store(self.local_targets(&target), &value, strong);
+            }
+            Instruction::CastPtr { operand, result }
+            | Instruction::GEP {
+                pointer: operand,
+                result,
+                ..
+            }
+            | Instruction::GEPNop {
+                pointer: operand,
+                result,
+                ..
+            } => {
+                let value = self.eval(state, operand);
+                state.variables.set(Variable::Register(*result), value);
+            }
+            Instruction::ITEOne {
+                cond: _,
+                then_value,
+                else_value,
+                result,
+            } => {
+                let value = self
+                    .eval(state, then_value)
+                    .join(&self.eval(state, else_value));
+                state.variables.set(Variable::Register(*result), value);
+            }
+            // phi nodes are resolved on the incoming edges
+            Instruction::Phi { .. } => (),
+            _ => {
+                if let Some(slot) = inst.result() {
+                    state.variables.forget(&Variable::Register(slot));
+                }
+            }
+        }
+    }
+
+    fn edge(
+        &self,
+        src: &BlockLabel,
+        dst: &BlockLabel,
+        edge: &Edge,
+        _filters: &[Filter],
+        state: &PointerStore,
+    ) -> PointerStore {
+        let mut state = state.clone();
+
+        // assume the outcome of a null check
+        if let (Edge::Branch(taken), Some(block)) = (edge, self.cfg.get_block_by_label(src)) {
+            if let Terminator::Branch {
+                cond: Value::Register { index, ty: _ },
+                ..
+            } = block.terminator()
+            {
+                if let Some((var, means_null)) = self.null_tests.get(index) {
+                    let current = state.variables.get(var);
+                    let refined = if *taken == *means_null {
+                        current.meet(&Pointees::null())
+                    } else {
+                        current.non_null()
+                    };
+                    state.variables.set(*var, refined);
+                    if state.variables.is_bottom() {
+                        return PointerStore::bottom();
+                    }
+                }
+            }
+        }
+
+        // resolve the phi nodes, all at once
+        let block = self
+            .cfg
+            .get_block_by_label(dst)
+            .expect("block label points to a valid node");
+        let mut resolved = vec![];
+        for inst in block.instructions() {
+            let (options, result) = match inst {
+                Instruction::Phi { options, result } => (options, result),
+                _ => break,
+            };
+            let value = match options.get(src) {
+                None => Pointees::top(),
+                Some(option) => self.eval(&state, option),
+            };
+            resolved.push((*result, value));
+        }
+        for (slot, value) in resolved {
+            state.variables.set(Variable::Register(slot), value);
+        }
+        state
+    }
+}
+
+impl Analysis<'_> {
+    fn solve(&self, func: &Function) -> CfgState<PointerStore> {
+        solve_transfer(self.cfg, self.initial(func), self.widening, self)
+    }
+}
+
+/// Utility: the variable compared against null, if the comparison is a null check
+fn null_test_operand(lhs: &Value, rhs: &Value) -> Option<Variable> {
+    match (lhs, rhs) {
+        (Value::Constant(Constant::Null), other) | (other, Value::Constant(Constant::Null)) => {
+            Variable::of_value(other)
+        }
+        _ => None,
+    }
+}
+
+/// Run the flow-sensitive pointer analysis on each defined function
+pub fn analyze_pointers(
+    module: &Module,
+    widening: &Widening,
+) -> EngineResult<BTreeMap<Identifier, CfgState<PointerStore>>> {
+    let global = analyze_points_to(module)?;
+    let mut results = BTreeMap::new();
+    for (name, func) in module.functions() {
+        let cfg = match &func.body {
+            None => continue,
+            Some(cfg) => cfg,
+        };
+        let analysis = Analysis::new(module, &global, func, cfg, widening);
+        results.insert(name.clone(), analysis.solve(func));
+    }
+    Ok(results)
+}
+
+/// Utility: the size in bytes requested by a call to an allocator, in the interval state
+/// before the call
+fn requested_size(callee: &str, args: &[Value], env: &Env) -> Interval {
+    let any = Interval::new(Bound::Finite(Integer::new()), Bound::PosInf);
+    let size = |value: &Value| match int_bits(value) {
+        None => any.clone(),
+        Some(bits) => {
+            let n = eval_value(value, bits, &|v| env.get(v));
+            // sizes are unsigned, so a possibly negative reading may be any size
+            if n.is_non_negative() {
+                n
+            } else {
+                any.clone()
+            }
+        }
+    };
+    match (callee, args) {
+        ("malloc" | "_Znwm" | "_Znam", [n]) => size(n),
+        ("calloc", [count, n]) => size(count).mul(&size(n)),
+        ("realloc" | "aligned_alloc", [_, n]) => size(n),
+        // the copy holds the terminator and at most n characters
+        ("strndup", [_, n]) => {
+            let one = Interval::singleton(Integer::from(1));
+            one.join(&size(n).add(&one))
+        }
+        ("strdup", [_]) => Interval::new(Bound::Finite(Integer::from(1)), Bound::PosInf),
+        _ => any,
+    }
+}
+
+/// The heap object allocated by a call, if the callee is an allocator
+pub(crate) fn heap_allocation(
+    function: &Identifier,
+    site: Site,
+    callee: &Identifier,
+    args: &[Value],
+    env: &Env,
+) -> Option<HeapAllocation> {
+    if !is_allocator(callee.as_ref()) {
+        return None;
+    }
+    Some(HeapAllocation {
+        site: AllocSite::Heap {
+            function: function.clone(),
+            site,
+            recency: Recency::Summary,
+        },
+        allocator: callee.clone(),
+        size: requested_size(callee.as_ref(), args, env),
+    })
+}
+
+/// Collect the heap objects allocated in each defined function, at the allocation sites the
+/// pointer analysis creates, sized by the interval states at the calls
+pub fn analyze_heap_allocations(
+    module: &Module,
+    solver: &SolverConfig,
+) -> EngineResult<Findings<HeapAllocation>> {
+    let intervals = analyze_intervals(module, solver);
+    let mut objects = Findings::new();
+    for (name, func) in module.functions() {
+        let (cfg, states) = match (&func.body, intervals.get(name)) {
+            (Some(cfg), Some(states)) => (cfg, states),
+            _ => continue,
+        };
+        objects.record(name, states.exhausted());
+        for (label, block) in cfg.blocks() {
+            for (index, inst) in block.instructions().iter().enumerate() {
+                let site = Site::Instruction {
+                    block: *label,
+                    index,
+                };
+                if let (
+                    Instruction::CallDirect {
+                        function,
+                        args,
+                        result: Some(_),
+                        ..
+                    },
+                    Some(env),
+                ) = (inst, states.get(&site))
+                {
+                    if let Some(object) = heap_allocation(name, site, function, args, env) {
+                        objects.push(object);
+                    }
+                }
+            }
+        }
+    }
+    Ok(objects)
+}
+
+/// Flow-sensitive pointer states of a function, replayable instruction by instruction
+pub(crate) struct PointerStates<'a> {
+    analysis: Analysis<'a>,
+    states: CfgState<PointerStore>,
+}
+
+impl<'a> PointerStates<'a> {
+    pub(crate) fn new(
+        module: &'a Module,
+        global: &'a PointsTo,
+        func: &'a Function,
+        cfg: &'a ControlFlowGraph,
+        widening: &'a Widening,
+    ) -> Self {
+        let analysis = Analysis::new(module, global, func, cfg, widening);
+        let states = analysis.solve(func);
+        Self { analysis, states }
+    }
+
+    /// The budget that ran out in the pointer analysis, if any
+    pub(crate) fn exhausted(&self) -> Option<Exhaustion> {
+        self.states.exhausted()
+    }
+
+    /// State on entry to a block, bottom if the block is unreachable
+    pub(crate) fn at_entry(&self, label: &BlockLabel) -> PointerStore {
+        self.states.at_entry(label)
+    }
+
+    /// Where a value may point to in the state
+    pub(crate) fn eval(&self, state: &PointerStore, value: &Value) -> Pointees {
+        self.analysis.eval(state, value)
+    }
+
+    /// Apply the instruction at the site to the state
+    pub(crate) fn step(&self, state: &mut PointerStore, site: Site, inst: &Instruction) {
+        self.analysis.step(state, site, inst)
+    }
+}
+
+/// What the pointer states say about a null check
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub enum NullCheckVerdict {
+    /// the pointer is never null, so the check is redundant
+    NeverNull,
+    /// the pointer is always null
+    AlwaysNull,
+    /// the pointer may or may not be null
+    Necessary,
+}
+
+impl Display for NullCheckVerdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NeverNull => write!(f, "never null"),
+            Self::AlwaysNull => write!(f, "always null"),
+            Self::Necessary => write!(f, "necessary"),
+        }
+    }
+}
+
+/// A comparison of a pointer against null
+pub struct NullCheck {
+    pub function: Identifier,
+    pub site: Site,
+    /// the pointer being checked
+    pub pointer: Variable,
+    pub verdict: NullCheckVerdict,
+}
+
+impl Display for NullCheck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "@{}:{}: null check on {} is {}",
+            self.function, self.site, self.pointer, self.verdict
+        )
+    }
+}
+
+/// Decide each null check in the module with the flow-sensitive pointer states
+pub fn validate_null_checks(
+    module: &Module,
+    widening: &Widening,
+) -> EngineResult<Findings<NullCheck>> {
+    let global = analyze_points_to(module)?;
+    let mut checks = Findings::new();
+    for (name, func) in module.functions() {
+        let cfg = match &func.body {
+            None => continue,
+            Some(cfg) => cfg,
+        };
+        let analysis = Analysis::new(module, &global, func, cfg, widening);
+        let states = analysis.solve(func);
+        checks.record(name, states.exhausted());
+
+        for label in states.reachable() {
+            let block = cfg
+                .get_block_by_label(label)
+                .expect("block label points to a valid node");
+            let mut state = states.at_entry(label);
+            for (index, inst) in block.instructions().iter().enumerate() {
+                let site = Site::Instruction {
+                    block: *label,
+                    index,
+                };
+                if let Some((var, _)) = inst.result().and_then(|r| analysis.null_tests.get(&r)) {
+                    let value = state.variables.get(var);
+                    let verdict = if !value.null {
+                        NullCheckVerdict::NeverNull
+                    } else if value.is_null() {
+                        NullCheckVerdict::AlwaysNull
+                    } else {
+                        NullCheckVerdict::Necessary
+                    };
+                    checks.push(NullCheck {
+                        function: name.clone(),
+                        site,
+                        pointer: *var,
+                        verdict,
+                    });
+                }
+                analysis.step(&mut state, site, inst);
+            }
+        }
+    }
+    Ok(checks)
+}
+
+/// Certainty of a null-pointer dereference
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub enum NullDerefKind {
+    /// the pointer is null on every execution reaching the access
+    Definite,
+    /// the pointer may be null
+    Possible,
+}
+
+impl Display for NullDerefKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Definite => write!(f, "definite"),
+            Self::Possible => write!(f, "possible"),
+        }
+    }
+}
+
+/// A load, store, or address computation through a pointer that may be null
+pub struct NullDereference {
+    pub function: Identifier,
+    pub site: Site,
+    /// the instruction kind, i.e., load, store, or gep
+    pub access: &'static str,
+    /// the pointer dereferenced, as printed in the IR
+    pub pointer: String,
+    pub kind: NullDerefKind,
+}
+
+impl Display for NullDereference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "@{}:{}: {} through {} which may be null ({})",
+            self.function, self.site, self.access, self.pointer, self.kind
+        )
+    }
+}
+
+/// Utility: the pointer dereferenced by an instruction, if any
+fn dereferenced_pointer(inst: &Instruction) -> Option<(&'static str, &Value)> {
+    match inst {
+        Instruction::Load { pointer, .. } => Some(("load", pointer)),
+        Instruction::Store { pointer, .. } => Some(("store", pointer)),
+        Instruction::GEP { pointer, .. } => Some(("gep", pointer)),
+        _ => None,
+    }
+}
+
+/// Find the accesses whose pointer may be null under the flow-sensitive pointer states
+pub fn check_null_dereferences(
+    module: &Module,
+    widening: &Widening,
+) -> EngineResult<Findings<NullDereference>> {
+    let global = analyze_points_to(module)?;
+    let mut findings = Findings::new();
+    for (name, func) in module.functions() {
+        let cfg = match &func.body {
+            None => continue,
+            Some(cfg) => cfg,
+        };
+        let analysis = Analysis::new(module, &global, func, cfg, widening);
+        let states = analysis.solve(func);
+        findings.record(name, states.exhausted());
+
+        for label in states.reachable() {
+            let block = cfg
+                .get_block_by_label(label)
+                .expect("block label points to a valid node");
+            let mut state = states.at_entry(label);
+            for (index, inst) in block.instructions().iter().enumerate() {
+                let site = Site::Instruction {
+                    block: *label,
+                    index,
+                };
+                if let Some((access, pointer)) = dereferenced_pointer(inst) {
+                    let value = analysis.eval(&state, pointer);
+                    if value.null {
+                        let kind = if value.is_null() {
+                            NullDerefKind::Definite
+                        } else {
+                            NullDerefKind::Possible
+                        };
+                        findings.push(NullDereference {
+                            function: name.clone(),
+                            site,
+                            access,
+                            pointer: pointer.to_string(),
+                            kind,
+                        });
+                    }
+                }
+                analysis.step(&mut state, site, inst);
+            }
+        }
+    }
+    Ok(findings)
+}
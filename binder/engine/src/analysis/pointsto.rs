@@ -0,0 +1,647 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt::{Display, Formatter};
+
+use crate::analysis::escape::{analyze_escapes, Escapes};
+use crate::analysis::libc::{find_model, Returns};
+use crate::analysis::memory::{AllocSite, Recency};
+use crate::analysis::unification::analyze_unification;
+use crate::error::{EngineError, EngineResult};
+use crate::ir::bridge::constant::Constant;
+use crate::ir::bridge::instruction::{Instruction, Terminator};
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::value::{ArgumentSlot, RegisterSlot, Value};
+use crate::ir::bridge::visit::{walk_constant, Visitor};
+use crate::ir::defuse::Site;
+
+/// Functions returning a fresh heap object on each call
+static ALLOCATORS: [&str; 9] = [
+    "malloc",
+    "calloc",
+    "realloc",
+    "aligned_alloc",
+    "strdup",
+    "strndup",
+    "_Znwm",
+    "_Znam",
+    "__cxa_allocate_exception",
+];
+
+/// Whether a function returns a fresh heap object on each call
+pub(crate) fn is_allocator(name: &str) -> bool {
+    ALLOCATORS.contains(&name)
+}
+
+/// An entity holding pointers
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone)]
+pub enum Node {
+    /// a register in a function
+    Register {
+        function: Identifier,
+        slot: RegisterSlot,
+    },
+    /// a parameter of a function
+    Argument {
+        function: Identifier,
+        slot: ArgumentSlot,
+    },
+    /// the value returned by a function
+    Return(Identifier),
+    /// the contents of an allocation, with all fields merged
+    Object(AllocSite),
+}
+
+impl Node {
+    /// The node of a non-constant value in a function
+    pub fn of_value(function: &Identifier, value: &Value) -> Option<Self> {
+        match value {
+            Value::Constant(_) => None,
+            Value::Argument { index, ty: _ } => Some(Self::Argument {
+                function: function.clone(),
+                slot: *index,
+            }),
+            Value::Register { index, ty: _ } => Some(Self::Register {
+                function: function.clone(),
+                slot: *index,
+            }),
+        }
+    }
+}
+
+impl Display for Node {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Register { function, slot } => write!(f, "{}:{}", function, slot),
+            Self::Argument { function, slot } => write!(f, "{}:{}", function, slot),
+            Self::Return(function) => write!(f, "{}:ret", function),
+            Self::Object(site) => write!(f, "*{}", site),
+        }
+    }
+}
+
+/// Collect the addresses of globals and functions mentioned in a constant
+#[derive(Default)]
+struct AddressCollector {
+    found: BTreeSet<AllocSite>,
+}
+
+impl<'a> Visitor<'a> for AddressCollector {
+    fn visit_constant(&mut self, constant: &'a Constant) {
+        match constant {
+            // functions are addressed like global variables
+            Constant::Variable { name } | Constant::Function { name } => {
+                self.found.insert(AllocSite::Global(name.clone()));
+            }
+            _ => walk_constant(self, constant),
+        }
+    }
+}
+
+/// Utility: addresses a constant may evaluate to
+pub(crate) fn constant_addresses(constant: &Constant) -> BTreeSet<AllocSite> {
+    let mut collector = AddressCollector::default();
+    collector.visit_constant(constant);
+    collector.found
+}
+
+/// An operand of a constraint
+#[derive(Clone)]
+pub(crate) enum Operand {
+    Node(Node),
+    Constant(BTreeSet<AllocSite>),
+}
+
+impl Operand {
+    pub(crate) fn of_value(function: &Identifier, value: &Value) -> Self {
+        match value {
+            Value::Constant(constant) => Self::Constant(constant_addresses(constant)),
+            _ => Self::Node(Node::of_value(function, value).expect("non-constant value")),
+        }
+    }
+}
+
+/// A call whose callee is only known from the points-to set of a node
+pub(crate) struct IndirectCall {
+    pub(crate) function: Identifier,
+    pub(crate) site: Site,
+    pub(crate) args: Vec<Operand>,
+    pub(crate) result: Option<Node>,
+}
+
+/// Receiver of the pointer constraints of a module, shared by the pointer analyses
+pub(crate) trait ConstraintSink {
+    /// `dst ⊇ addresses`
+    fn add_addresses(&mut self, dst: &Node, addresses: &BTreeSet<AllocSite>);
+
+    /// `dst ⊇ src`
+    fn add_copy(&mut self, dst: &Node, src: &Operand);
+
+    /// `dst ⊇ *src`
+    fn add_load(&mut self, dst: &Node, src: &Operand);
+
+    /// `*dst ⊇ src`
+    fn add_store(&mut self, dst: &Operand, src: &Operand);
+
+    /// A call to be linked with each function the callee may point to
+    fn add_indirect_call(&mut self, callee: Node, call: IndirectCall);
+
+    /// Bind the arguments and the result of a call to a known callee
+    fn link(
+        &mut self,
+        module: &Module,
+        function: &Identifier,
+        site: Site,
+        callee: &Identifier,
+        args: &[Operand],
+        result: Option<&Node>,
+    ) {
+        if is_allocator(callee.as_ref()) {
+            if let Some(node) = result {
+                let object = AllocSite::Heap {
+                    function: function.clone(),
+                    site,
+                    recency: Recency::Summary,
+                };
+                self.add_addresses(node, &BTreeSet::from([object]));
+            }
+            return;
+        }
+
+        // calls to declared functions are modeled by the pointers they return only
+        let target = match module.get_function(callee.as_ref()) {
+            Some(func) if func.exact_body().is_some() => func,
+            _ => {
+                let returned = find_model(callee.as_ref()).and_then(|model| match model.returns {
+                    Returns::Argument(index)
+                    | Returns::ArgumentOrNull(index)
+                    | Returns::IntoOrNull(index) => args.get(index),
+                    Returns::Opaque | Returns::Range(..) | Returns::NonNegative => None,
+                });
+                if let (Some(node), Some(arg)) = (result, returned) {
+                    self.add_copy(node, arg);
+                }
+                return;
+            }
+        };
+        for (index, arg) in args.iter().enumerate().take(target.params.len()) {
+            let param = Node::Argument {
+                function: callee.clone(),
+                slot: index.into(),
+            };
+            self.add_copy(&param, arg);
+        }
+        if let Some(node) = result {
+            self.add_copy(node, &Operand::Node(Node::Return(callee.clone())));
+        }
+    }
+
+    /// Generate the constraints of a call
+    fn add_call(
+        &mut self,
+        module: &Module,
+        function: &Identifier,
+        site: Site,
+        callee: Operand,
+        args: &[Value],
+        result: Option<RegisterSlot>,
+    ) {
+        let args: Vec<_> = args
+            .iter()
+            .map(|arg| Operand::of_value(function, arg))
+            .collect();
+        let result = result.map(|slot| Node::Register {
+            function: function.clone(),
+            slot,
+        });
+        match callee {
+            Operand::Constant(addresses) => {
+                for target in addresses {
+                    if let AllocSite::Global(name) = target {
+                        self.link(module, function, site, &name, &args, result.as_ref());
+                    }
+                }
+            }
+            Operand::Node(node) => {
+                self.add_indirect_call(
+                    node,
+                    IndirectCall {
+                        function: function.clone(),
+                        site,
+                        args,
+                        result,
+                    },
+                );
+            }
+        }
+    }
+
+    fn instruction(
+        &mut self,
+        module: &Module,
+        function: &Identifier,
+        site: Site,
+        inst: &Instruction,
+    ) {
+        let register = |slot: RegisterSlot| Node::Register {
+            function: function.clone(),
+            slot,
+        };
+        match inst {
+            Instruction::Alloca { result, .. } => {
+                let object = AllocSite::Stack {
+                    function: function.clone(),
+                    site,
+                };
+                self.add_addresses(&register(*result), &BTreeSet::from([object]));
+            }
+            Instruction::Load {
+                pointer, result, ..
+            } => {
+                self.add_load(&register(*result), &Operand::of_value(function, pointer));
+            }
+            Instruction::Store { pointer, value, .. } => {
+                self.add_store(
+                    &Operand::of_value(function, pointer),
+                    &Operand::of_value(function, value),
+                );
+            }
+            Instruction::CallDirect {
+                function: callee,
+                args,
+                result,
+                ..
+            } => {
+                let callee = Operand::Constant(BTreeSet::from([AllocSite::Global(callee.clone())]));
+                self.add_call(
+                    module,
+                    function,
+                    site,
+                    callee,
+                    args,
+                    result.as_ref().map(|(_, slot)| *slot),
+                );
+            }
+            Instruction::CallIndirect {
+                callee,
+                args,
+                result,
+                ..
+            } => {
+                let callee = Operand::of_value(function, callee);
+                self.add_call(
+                    module,
+                    function,
+                    site,
+                    callee,
+                    args,
+                    result.as_ref().map(|(_, slot)| *slot),
+                );
+            }
+            // results that never carry a pointer
+            Instruction::CompareBitvec { .. }
+            | Instruction::CompareOrder { .. }
+            | Instruction::ComparePtr { .. }
+            | Instruction::LandingPad { .. } => (),
+            // everything else propagates the pointers of its operands, including those
+            // laundered through integers
+            _ => {
+                if let Some(result) = inst.result() {
+                    let dst = register(result);
+                    for value in inst.operands() {
+                        self.add_copy(&dst, &Operand::of_value(function, value));
+                    }
+                }
+            }
+        }
+    }
+
+    fn terminator(
+        &mut self,
+        module: &Module,
+        function: &Identifier,
+        site: Site,
+        term: &Terminator,
+    ) {
+        match term {
+            Terminator::Return { val: Some(val) } => {
+                self.add_copy(
+                    &Node::Return(function.clone()),
+                    &Operand::of_value(function, val),
+                );
+            }
+            Terminator::InvokeDirect {
+                function: callee,
+                args,
+                result,
+                ..
+            } => {
+                let callee = Operand::Constant(BTreeSet::from([AllocSite::Global(callee.clone())]));
+                self.add_call(
+                    module,
+                    function,
+                    site,
+                    callee,
+                    args,
+                    result.as_ref().map(|(_, slot)| *slot),
+                );
+            }
+            Terminator::InvokeIndirect {
+                callee,
+                args,
+                result,
+                ..
+            } => {
+                let callee = Operand::of_value(function, callee);
+                self.add_call(
+                    module,
+                    function,
+                    site,
+                    callee,
+                    args,
+                    result.as_ref().map(|(_, slot)| *slot),
+                );
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Feed the constraints of the module into the sink
+pub(crate) fn generate_constraints<S: ConstraintSink>(module: &Module, sink: &mut S) {
+    // step 1: constraints from the initializers of globals
+    for (name, gvar) in module.globals() {
+        if let Some(init) = &gvar.initializer {
+            let object = Node::Object(AllocSite::Global(name.clone()));
+            sink.add_addresses(&object, &constant_addresses(init));
+        }
+    }
+
+    // step 2: constraints from the function bodies
+    for (name, func) in module.functions() {
+        let cfg = match &func.body {
+            None => continue,
+            Some(cfg) => cfg,
+        };
+        for (label, block) in cfg.blocks() {
+            for (index, inst) in block.instructions().iter().enumerate() {
+                let site = Site::Instruction {
+                    block: *label,
+                    index,
+                };
+                sink.instruction(module, name, site, inst);
+            }
+            let site = Site::Terminator { block: *label };
+            sink.terminator(module, name, site, block.terminator());
+        }
+    }
+}
+
+/// Inclusion constraints and their solution in progress
+#[derive(Default)]
+struct Solver {
+    sets: BTreeMap<Node, BTreeSet<AllocSite>>,
+    /// `dst ⊇ src`, keyed by src
+    copies: BTreeMap<Node, BTreeSet<Node>>,
+    /// `dst ⊇ *src`, keyed by src
+    loads: BTreeMap<Node, BTreeSet<Node>>,
+    /// `*dst ⊇ src`, keyed by dst
+    stores: BTreeMap<Node, Vec<Operand>>,
+    /// indirect calls, keyed by the callee
+    calls: BTreeMap<Node, Vec<IndirectCall>>,
+    worklist: VecDeque<Node>,
+}
+
+impl ConstraintSink for Solver {
+    fn add_addresses(&mut self, dst: &Node, addresses: &BTreeSet<AllocSite>) {
+        let set = self.sets.entry(dst.clone()).or_default();
+        let before = set.len();
+        set.extend(addresses.iter().cloned());
+        if set.len() != before {
+            self.worklist.push_back(dst.clone());
+        }
+    }
+
+    fn add_copy(&mut self, dst: &Node, src: &Operand) {
+        match src {
+            Operand::Constant(addresses) => self.add_addresses(dst, addresses),
+            Operand::Node(node) => {
+                if node == dst {
+                    return;
+                }
+                if self
+                    .copies
+                    .entry(node.clone())
+                    .or_default()
+                    .insert(dst.clone())
+                {
+                    self.worklist.push_back(node.clone());
+                }
+            }
+        }
+    }
+
+    fn add_load(&mut self, dst: &Node, src: &Operand) {
+        match src {
+            Operand::Constant(addresses) => {
+                for site in addresses {
+                    self.add_copy(dst, &Operand::Node(Node::Object(site.clone())));
+                }
+            }
+            Operand::Node(node) => {
+                self.loads
+                    .entry(node.clone())
+                    .or_default()
+                    .insert(dst.clone());
+                self.worklist.push_back(node.clone());
+            }
+        }
+    }
+
+    fn add_store(&mut self, dst: &Operand, src: &Operand) {
+        match dst {
+            Operand::Constant(addresses) => {
+                for site in addresses {
+                    self.add_copy(&Node::Object(site.clone()), src);
+                }
+            }
+            Operand::Node(node) => {
+                self.stores
+                    .entry(node.clone())
+                    .or_default()
+                    .push(src.clone());
+                self.worklist.push_back(node.clone());
+            }
+        }
+    }
+
+    fn add_indirect_call(&mut self, callee: Node, call: IndirectCall) {
+        self.calls.entry(callee.clone()).or_default().push(call);
+        self.worklist.push_back(callee);
+    }
+}
+
+impl Solver {
+    /// Propagate until no points-to set changes
+    fn solve(&mut self, module: &Module) {
+        while let Some(node) = self.worklist.pop_front() {
+            let set = match self.sets.get(&node) {
+                None => continue,
+                Some(set) => set.clone(),
+            };
+
+            // complex constraints turn into copies once the pointees are known
+            let loads: Vec<_> = self
+                .loads
+                .get(&node)
+                .into_iter()
+                .flatten()
+                .cloned()
+                .collect();
+            let stores = self.stores.get(&node).cloned().unwrap_or_default();
+            for site in &set {
+                let object = Node::Object(site.clone());
+                for dst in &loads {
+                    self.add_copy(dst, &Operand::Node(object.clone()));
+                }
+                for src in &stores {
+                    self.add_copy(&object, src);
+                }
+            }
+
+            // resolve indirect calls
+            if let Some(calls) = self.calls.remove(&node) {
+                for call in &calls {
+                    for site in &set {
+                        if let AllocSite::Global(name) = site {
+                            self.link(
+                                module,
+                                &call.function,
+                                call.site,
+                                name,
+                                &call.args,
+                                call.result.as_ref(),
+                            );
+                        }
+                    }
+                }
+                self.calls.insert(node.clone(), calls);
+            }
+
+            // copies
+            let targets: Vec<_> = self
+                .copies
+                .get(&node)
+                .into_iter()
+                .flatten()
+                .cloned()
+                .collect();
+            for dst in targets {
+                self.add_addresses(&dst, &set);
+            }
+        }
+    }
+}
+
+/// Queries answered by a pointer analysis, printed as the points-to sets of all nodes
+pub trait AliasQuery: Display {
+    /// Allocations a value in a function may point to
+    fn targets(&self, function: &Identifier, value: &Value) -> BTreeSet<AllocSite>;
+
+    /// Whether two values in a function may point to the same allocation
+    fn may_alias(&self, function: &Identifier, a: &Value, b: &Value) -> bool {
+        let lhs = self.targets(function, a);
+        let rhs = self.targets(function, b);
+        !lhs.is_disjoint(&rhs)
+    }
+}
+
+/// Flow- and context-insensitive points-to sets of a module, solved by inclusion constraints
+pub struct PointsTo {
+    sets: BTreeMap<Node, BTreeSet<AllocSite>>,
+    /// stack objects reachable from outside their functions, derived from the sets
+    escapes: Escapes,
+}
+
+impl PointsTo {
+    /// Points-to set of a node
+    pub fn points_to(&self, node: &Node) -> BTreeSet<AllocSite> {
+        self.sets.get(node).cloned().unwrap_or_default()
+    }
+
+    /// Nodes with a non-empty points-to set
+    pub fn nodes(&self) -> impl Iterator<Item = (&Node, &BTreeSet<AllocSite>)> {
+        self.sets.iter().filter(|(_, set)| !set.is_empty())
+    }
+
+    /// Stack objects escaping their functions
+    pub fn escapes(&self) -> &Escapes {
+        &self.escapes
+    }
+}
+
+impl AliasQuery for PointsTo {
+    fn targets(&self, function: &Identifier, value: &Value) -> BTreeSet<AllocSite> {
+        match value {
+            Value::Constant(constant) => constant_addresses(constant),
+            _ => self.points_to(&Node::of_value(function, value).expect("non-constant value")),
+        }
+    }
+}
+
+impl Display for PointsTo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (node, set) in self.nodes() {
+            let items: Vec<_> = set.iter().map(|site| site.to_string()).collect();
+            writeln!(f, "{} -> {{{}}}", node, items.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Run an inclusion-based (Andersen-style) pointer analysis over the module
+pub fn analyze_points_to(module: &Module) -> EngineResult<PointsTo> {
+    let mut solver = Solver::default();
+    generate_constraints(module, &mut solver);
+    solver.solve(module);
+    let mut result = PointsTo {
+        sets: solver.sets,
+        escapes: Escapes::default(),
+    };
+    result.escapes = analyze_escapes(module, &result);
+    Ok(result)
+}
+
+/// Choice of the pointer analysis
+#[derive(Clone, Copy, Default)]
+pub enum AliasMode {
+    /// inclusion-based, the precise one (the default)
+    #[default]
+    Inclusion,
+    /// unification-based, near-linear for very large modules
+    Unification,
+}
+
+impl AliasMode {
+    pub fn parse(name: &str) -> EngineResult<Self> {
+        let parsed = match name {
+            "inclusion" => Self::Inclusion,
+            "unification" => Self::Unification,
+            _ => {
+                return Err(EngineError::InvalidAssumption(format!(
+                    "unknown alias analysis: {}",
+                    name
+                )));
+            }
+        };
+        Ok(parsed)
+    }
+}
+
+/// Run the pointer analysis of the chosen mode over the module
+pub fn analyze_aliases(module: &Module, mode: AliasMode) -> EngineResult<Box<dyn AliasQuery>> {
+    let analysis: Box<dyn AliasQuery> = match mode {
+        AliasMode::Inclusion => Box::new(analyze_points_to(module)?),
+        AliasMode::Unification => Box::new(analyze_unification(module)?),
+    };
+    Ok(analysis)
+}
@@ -0,0 +1,191 @@
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+
+use rug::Integer;
+
+use crate::analysis::lattice::Lattice;
+
+/// How a powerset keeps its disjuncts bounded
+pub trait Collapse<D> {
+    /// Maximum number of disjuncts to retain, at least one
+    const CAP: usize;
+
+    /// Merge the disjuncts, which exceed the cap, into at most `cap` of them
+    fn collapse(disjuncts: Vec<D>, cap: usize) -> Vec<D>;
+}
+
+/// Collapse into a single disjunct on overflow
+pub struct JoinAll<const CAP: usize>;
+
+impl<D: Lattice, const CAP: usize> Collapse<D> for JoinAll<CAP> {
+    const CAP: usize = CAP;
+
+    fn collapse(disjuncts: Vec<D>, _cap: usize) -> Vec<D> {
+        vec![join_all(&disjuncts)]
+    }
+}
+
+/// Keep the oldest disjuncts intact and merge the overflow into the last slot
+pub struct JoinOverflow<const CAP: usize>;
+
+impl<D: Lattice, const CAP: usize> Collapse<D> for JoinOverflow<CAP> {
+    const CAP: usize = CAP;
+
+    fn collapse(mut disjuncts: Vec<D>, cap: usize) -> Vec<D> {
+        let keep = cap.max(1) - 1;
+        let overflow = disjuncts.split_off(keep.min(disjuncts.len()));
+        disjuncts.push(join_all(&overflow));
+        disjuncts
+    }
+}
+
+/// Utility: join a sequence of elements
+fn join_all<D: Lattice>(elements: &[D]) -> D {
+    elements
+        .iter()
+        .fold(D::bottom(), |acc, item| acc.join(item))
+}
+
+/// Utility: drop the empty and subsumed elements, keeping the order of the rest
+fn antichain<D: Lattice>(elements: Vec<D>) -> Vec<D> {
+    let mut kept: Vec<D> = vec![];
+    for item in elements {
+        if item.is_bottom() || kept.iter().any(|k| item.leq(k)) {
+            continue;
+        }
+        kept.retain(|k| !k.leq(&item));
+        kept.push(item);
+    }
+    kept
+}
+
+/// A bounded disjunctive completion of a domain, collapsed by `C` when it overflows
+pub struct Powerset<D, C> {
+    /// non-bottom disjuncts, none subsumed by another, in the order they were added
+    disjuncts: Vec<D>,
+    collapse: PhantomData<fn() -> C>,
+}
+
+impl<D: Lattice, C: Collapse<D>> Powerset<D, C> {
+    pub fn new(disjuncts: Vec<D>) -> Self {
+        Self::with_cap(disjuncts, C::CAP)
+    }
+
+    /// A powerset bounded by a cap chosen at runtime, which never exceeds `C::CAP`
+    pub fn with_cap(disjuncts: Vec<D>, cap: usize) -> Self {
+        let cap = cap.min(C::CAP).max(1);
+        let mut kept = antichain(disjuncts);
+        if kept.len() > cap {
+            // merging may introduce subsumption again
+            kept = antichain(C::collapse(kept, cap));
+        }
+        Self {
+            disjuncts: kept,
+            collapse: PhantomData,
+        }
+    }
+
+    pub fn singleton(item: D) -> Self {
+        Self::new(vec![item])
+    }
+
+    pub fn disjuncts(&self) -> &[D] {
+        &self.disjuncts
+    }
+
+    /// The join of all disjuncts, i.e., the element of the base domain
+    pub fn merge(&self) -> D {
+        join_all(&self.disjuncts)
+    }
+
+    /// Apply a function to each disjunct
+    pub fn map(&self, f: impl Fn(&D) -> D) -> Self {
+        Self::new(self.disjuncts.iter().map(f).collect())
+    }
+}
+
+impl<D: Clone, C> Clone for Powerset<D, C> {
+    fn clone(&self) -> Self {
+        Self {
+            disjuncts: self.disjuncts.clone(),
+            collapse: PhantomData,
+        }
+    }
+}
+
+impl<D: PartialEq, C> PartialEq for Powerset<D, C> {
+    fn eq(&self, other: &Self) -> bool {
+        // disjuncts form an antichain, so equal sets match one-to-one
+        self.disjuncts.len() == other.disjuncts.len()
+            && self
+                .disjuncts
+                .iter()
+                .all(|item| other.disjuncts.contains(item))
+    }
+}
+
+impl<D: Eq, C> Eq for Powerset<D, C> {}
+
+impl<D: Lattice, C: Collapse<D>> Lattice for Powerset<D, C> {
+    fn bottom() -> Self {
+        Self::new(vec![])
+    }
+
+    fn top() -> Self {
+        Self::new(vec![D::top()])
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        self.disjuncts
+            .iter()
+            .all(|item| other.disjuncts.iter().any(|k| item.leq(k)))
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        Self::new(
+            self.disjuncts
+                .iter()
+                .chain(other.disjuncts.iter())
+                .cloned()
+                .collect(),
+        )
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        let mut disjuncts = vec![];
+        for lhs in &self.disjuncts {
+            for rhs in &other.disjuncts {
+                disjuncts.push(lhs.meet(rhs));
+            }
+        }
+        Self::new(disjuncts)
+    }
+
+    fn widen(&self, other: &Self) -> Self {
+        // an ascending chain of disjunctions may grow forever, hence widen the merged ones
+        if other.leq(self) {
+            return self.clone();
+        }
+        let merged = self.merge();
+        Self::singleton(merged.widen(&merged.join(&other.merge())))
+    }
+
+    fn widen_with_thresholds(&self, other: &Self, thresholds: &BTreeSet<Integer>) -> Self {
+        if other.leq(self) {
+            return self.clone();
+        }
+        let merged = self.merge();
+        Self::singleton(merged.widen_with_thresholds(&merged.join(&other.merge()), thresholds))
+    }
+}
+
+impl<D: Display, C> Display for Powerset<D, C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.disjuncts.is_empty() {
+            return write!(f, "empty");
+        }
+        let items: Vec<_> = self.disjuncts.iter().map(|d| d.to_string()).collect();
+        write!(f, "{}", items.join(" \\/ "))
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+
+use rug::Integer;
+
+use crate::analysis::lattice::Lattice;
+
+/// A reduction operator that propagates information between two components
+pub trait Reduction<A, B> {
+    /// Tighten both components against each other without losing concrete states
+    fn reduce(first: &A, second: &B) -> (A, B);
+}
+
+/// The reduction that keeps the components apart, i.e., the plain cartesian product
+pub struct NoReduction;
+
+impl<A: Clone, B: Clone> Reduction<A, B> for NoReduction {
+    fn reduce(first: &A, second: &B) -> (A, B) {
+        (first.clone(), second.clone())
+    }
+}
+
+/// The product of two domains, reduced by `R` after every operation except widening
+pub struct ReducedProduct<A, B, R> {
+    pub first: A,
+    pub second: B,
+    reduction: PhantomData<fn() -> R>,
+}
+
+impl<A: Lattice, B: Lattice, R: Reduction<A, B>> ReducedProduct<A, B, R> {
+    pub fn new(first: A, second: B) -> Self {
+        let (first, second) = R::reduce(&first, &second);
+        Self::unreduced(first, second)
+    }
+
+    /// Pair the components as they are
+    fn unreduced(first: A, second: B) -> Self {
+        Self {
+            first,
+            second,
+            reduction: PhantomData,
+        }
+    }
+}
+
+impl<A: Clone, B: Clone, R> Clone for ReducedProduct<A, B, R> {
+    fn clone(&self) -> Self {
+        Self {
+            first: self.first.clone(),
+            second: self.second.clone(),
+            reduction: PhantomData,
+        }
+    }
+}
+
+impl<A: PartialEq, B: PartialEq, R> PartialEq for ReducedProduct<A, B, R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.first == other.first && self.second == other.second
+    }
+}
+
+impl<A: Eq, B: Eq, R> Eq for ReducedProduct<A, B, R> {}
+
+impl<A: Lattice, B: Lattice, R: Reduction<A, B>> Lattice for ReducedProduct<A, B, R> {
+    fn bottom() -> Self {
+        Self::unreduced(A::bottom(), B::bottom())
+    }
+
+    fn top() -> Self {
+        Self::unreduced(A::top(), B::top())
+    }
+
+    fn is_bottom(&self) -> bool {
+        // the product is empty as soon as one component is, even when unreduced
+        self.first.is_bottom() || self.second.is_bottom()
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        self.is_bottom() || (self.first.leq(&other.first) && self.second.leq(&other.second))
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        if self.is_bottom() {
+            return other.clone();
+        }
+        if other.is_bottom() {
+            return self.clone();
+        }
+        Self::new(
+            self.first.join(&other.first),
+            self.second.join(&other.second),
+        )
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        Self::new(
+            self.first.meet(&other.first),
+            self.second.meet(&other.second),
+        )
+    }
+
+    fn widen(&self, other: &Self) -> Self {
+        // reducing after widening may break termination, hence no reduction here
+        Self::unreduced(
+            self.first.widen(&other.first),
+            self.second.widen(&other.second),
+        )
+    }
+
+    fn widen_with_thresholds(&self, other: &Self, thresholds: &BTreeSet<Integer>) -> Self {
+        Self::unreduced(
+            self.first.widen_with_thresholds(&other.first, thresholds),
+            self.second.widen_with_thresholds(&other.second, thresholds),
+        )
+    }
+
+    fn narrow(&self, other: &Self) -> Self {
+        Self::unreduced(
+            self.first.narrow(&other.first),
+            self.second.narrow(&other.second),
+        )
+    }
+}
+
+impl<A: Display, B: Display, R> Display for ReducedProduct<A, B, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} /\\ {}", self.first, self.second)
+    }
+}
@@ -0,0 +1,519 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use crate::analysis::dataflow::{solve_forward_with, CfgState, Exhaustion, Filter, Widening};
+use crate::analysis::lattice::Lattice;
+use crate::analysis::libc::{callee_model, LibcModel};
+use crate::ir::bridge::cfg::{Block, Edge};
+use crate::ir::bridge::function::Function;
+use crate::ir::bridge::instruction::Terminator;
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::value::BlockLabel;
+use crate::ir::callgraph::CallGraph;
+use crate::ir::defuse::Site;
+
+/// The most recent call sites leading to a function, oldest first
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Default)]
+pub struct CallString(Vec<(Identifier, Site)>);
+
+impl CallString {
+    /// The context of a callee called at the site of a function running in this context,
+    /// keeping the last `k` call sites only
+    fn push(&self, caller: &Identifier, site: Site, k: usize) -> Self {
+        let mut sites = self.0.clone();
+        sites.push((caller.clone(), site));
+        let excess = sites.len().saturating_sub(k);
+        sites.drain(..excess);
+        Self(sites)
+    }
+}
+
+impl Display for CallString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let items: Vec<_> = self
+            .0
+            .iter()
+            .map(|(caller, site)| format!("@{}:{}", caller, site))
+            .collect();
+        write!(f, "[{}]", items.join(" > "))
+    }
+}
+
+/// Configuration of the call-string (k-CFA style) mode
+#[derive(Clone, Copy)]
+pub struct CallStrings {
+    /// number of call sites kept in a context, 0 for a context-insensitive analysis
+    pub k: usize,
+    /// number of contexts per function, beyond which new contexts merge into the empty one
+    pub max_contexts: usize,
+}
+
+impl Default for CallStrings {
+    fn default() -> Self {
+        Self {
+            k: 1,
+            max_contexts: 16,
+        }
+    }
+}
+
+/// Configuration of the solvers behind the checkers, within functions and across calls
+#[derive(Clone)]
+pub struct SolverConfig {
+    pub widening: Widening,
+    /// configuration of the call-string mode, `None` to summarize the functions bottom-up
+    pub call_strings: Option<CallStrings>,
+    /// functions where executions start in the call-string mode, the default ones if empty
+    pub entry_points: Vec<Identifier>,
+    /// number of states kept apart at a site, e.g., those of the contexts of a function,
+    /// before they are joined
+    pub powerset_cap: usize,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            widening: Widening::default(),
+            call_strings: None,
+            entry_points: vec![],
+            powerset_cap: 1,
+        }
+    }
+}
+
+/// Effect of a function, as the state it assumes on entry and the state it yields on return
+#[derive(Eq, PartialEq, Clone)]
+pub struct Summary<S> {
+    pub input: S,
+    /// bottom if the function never returns
+    pub output: S,
+}
+
+/// A function analyzed in a context, which is always empty in the bottom-up mode
+type Key = (Identifier, CallString);
+
+/// Summaries of the functions analyzed so far, one per context
+pub struct Summaries<S> {
+    summaries: BTreeMap<Key, Summary<S>>,
+}
+
+impl<S> Summaries<S> {
+    fn new() -> Self {
+        Self {
+            summaries: BTreeMap::new(),
+        }
+    }
+
+    /// Summary of a function in a context, absent for a declaration or a function not
+    /// analyzed (yet) in the context
+    pub fn get(&self, name: &Identifier, context: &CallString) -> Option<&Summary<S>> {
+        self.summaries.get(&(name.clone(), context.clone()))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Identifier, &CallString, &Summary<S>)> {
+        self.summaries
+            .iter()
+            .map(|((name, context), summary)| (name, context, summary))
+    }
+}
+
+/// Memo table of the call-string mode
+struct Table<S> {
+    summaries: Summaries<S>,
+    /// contexts whose input grew since they were last analyzed
+    dirty: BTreeSet<Key>,
+    /// contexts that consumed the output of a context
+    dependents: BTreeMap<Key, BTreeSet<Key>>,
+}
+
+/// How the call sites in a function are resolved
+enum Resolution<'r, S> {
+    /// by the summaries of the callees, in the context of the caller in the call-string mode
+    Summaries {
+        summaries: &'r Summaries<S>,
+        context: Option<(CallStrings, &'r Key)>,
+    },
+    /// by the memo table, in the context of the caller
+    CallStrings {
+        config: CallStrings,
+        table: &'r mut Table<S>,
+        caller: &'r Key,
+    },
+}
+
+/// Resolver of the call sites in the function being analyzed
+pub struct Calls<'r, S> {
+    module: &'r Module,
+    resolution: Resolution<'r, S>,
+}
+
+impl<S: Lattice> Calls<'_, S> {
+    /// State on return from a call to a defined function, given the entry state of the callee
+    /// at the call site; `None` if the callee is not analyzed, e.g., a declaration
+    ///
+    /// Within a recursion, the state returned may not be stable yet, in which case the caller
+    /// is analyzed again once it is.
+    pub fn apply(&mut self, callee: &Identifier, site: Site, input: S) -> Option<S> {
+        let module = self.module;
+        // a body the linker may replace is not trusted, as for a declaration
+        if module
+            .get_function(callee.as_ref())
+            .is_none_or(|func| func.exact_body().is_none())
+        {
+            return None;
+        }
+        let (config, table, caller) = match &mut self.resolution {
+            Resolution::Summaries { summaries, context } => {
+                // a context past the cap is merged into the empty one
+                let summary = context
+                    .and_then(|(config, caller)| {
+                        summaries.get(callee, &caller.1.push(&caller.0, site, config.k))
+                    })
+                    .or_else(|| summaries.get(callee, &CallString::default()));
+                return summary.map(|summary| summary.output.clone());
+            }
+            Resolution::CallStrings {
+                config,
+                table,
+                caller,
+            } => (*config, &mut **table, *caller),
+        };
+
+        // past the cap, new contexts share the empty one
+        let mut key = (callee.clone(), caller.1.push(&caller.0, site, config.k));
+        if !table.summaries.summaries.contains_key(&key) {
+            let count = table
+                .summaries
+                .summaries
+                .keys()
+                .filter(|(name, _)| name == callee)
+                .count();
+            if count >= config.max_contexts {
+                key.1 = CallString::default();
+            }
+        }
+
+        let summary = table
+            .summaries
+            .summaries
+            .entry(key.clone())
+            .or_insert_with(|| Summary {
+                input: S::bottom(),
+                output: S::bottom(),
+            });
+        if !input.leq(&summary.input) {
+            summary.input = summary.input.widen(&summary.input.join(&input));
+            table.dirty.insert(key.clone());
+        }
+        let output = summary.output.clone();
+        table
+            .dependents
+            .entry(key)
+            .or_default()
+            .insert(caller.clone());
+        Some(output)
+    }
+
+    /// Model of a library function called, consulted when the callee has no body
+    pub fn model(&self, callee: &Identifier) -> Option<&'static LibcModel> {
+        callee_model(self.module, callee.as_ref())
+    }
+}
+
+/// A forward analysis made interprocedural by summarizing functions
+///
+/// Call sites are resolved in `block` through `calls`, which also provides the models of the
+/// library functions without a body. By default, each function is summarized once, bottom-up,
+/// from an entry state independent of its callers. In the call-string mode, the functions are
+/// analyzed top-down from the entry points of the module instead, once per calling context,
+/// with the entry states coming from the call sites.
+pub trait SummaryAnalysis {
+    type State: Lattice;
+
+    /// State on entry to a function, independent of its callers
+    fn entry(&self, func: &Function) -> Self::State;
+
+    /// Exit state of a block from its entry state
+    fn block(
+        &self,
+        func: &Function,
+        label: &BlockLabel,
+        block: &Block,
+        state: &Self::State,
+        calls: &mut Calls<'_, Self::State>,
+    ) -> Self::State;
+
+    /// Refine the exit state of `src` on the edge into `dst` under the filters of the edge,
+    /// bottom for an infeasible edge
+    fn edge(
+        &self,
+        _func: &Function,
+        _src: &BlockLabel,
+        _dst: &BlockLabel,
+        _edge: &Edge,
+        _filters: &[Filter],
+        state: &Self::State,
+    ) -> Self::State {
+        state.clone()
+    }
+
+    /// Project the state on return to what callers may observe, e.g., dropping the locals
+    fn exit(&self, _func: &Function, state: &Self::State) -> Self::State {
+        state.clone()
+    }
+
+    /// Configuration of the widening within a function
+    fn widening(&self, _func: &Function) -> Widening {
+        Widening::default()
+    }
+
+    /// Functions analyzed from their entry states in the call-string mode
+    fn entry_points<'m>(&self, module: &'m Module) -> Vec<&'m Function> {
+        module.entry_points()
+    }
+
+    /// Configuration of the call-string mode, `None` for the bottom-up mode
+    fn call_strings(&self) -> Option<CallStrings> {
+        None
+    }
+}
+
+/// Outcome of a summary-based analysis of a module
+pub struct SummaryResult<S> {
+    pub summaries: Summaries<S>,
+    /// stabilized states of the functions analyzed, per context
+    pub states: BTreeMap<(Identifier, CallString), CfgState<S>>,
+    /// configuration of the call-string mode the functions are analyzed in, if any
+    call_strings: Option<CallStrings>,
+}
+
+impl<S: Lattice> SummaryResult<S> {
+    /// Functions whose analysis ran out of budget in a context, with their states degraded
+    pub fn exhausted(&self) -> impl Iterator<Item = (&Identifier, &CallString, Exhaustion)> {
+        self.states
+            .iter()
+            .filter_map(|((name, ctx), states)| states.exhausted().map(|e| (name, ctx, e)))
+    }
+
+    /// Resolver of the call sites of a function analyzed in a context by the stable summaries,
+    /// e.g., to replay the blocks of the function from their stabilized states
+    pub fn calls<'r>(
+        &'r self,
+        module: &'r Module,
+        caller: &'r (Identifier, CallString),
+    ) -> Calls<'r, S> {
+        Calls {
+            module,
+            resolution: Resolution::Summaries {
+                summaries: &self.summaries,
+                context: self.call_strings.map(|config| (config, caller)),
+            },
+        }
+    }
+}
+
+/// Utility: analyze a function from an entry state, yielding the state on return
+fn analyze_function<A: SummaryAnalysis>(
+    analysis: &A,
+    func: &Function,
+    input: A::State,
+    calls: &mut Calls<'_, A::State>,
+) -> (A::State, CfgState<A::State>) {
+    let cfg = func
+        .body
+        .as_ref()
+        .expect("only defined functions are analyzed");
+    let widening = analysis.widening(func);
+    let states = solve_forward_with(
+        cfg,
+        input,
+        &widening,
+        |label, block, state| analysis.block(func, label, block, state, calls),
+        |src, dst, edge, filters, state| analysis.edge(func, src, dst, edge, filters, state),
+    );
+
+    let mut returned = A::State::bottom();
+    for label in states.reachable() {
+        let block = cfg
+            .get_block_by_label(label)
+            .expect("block label points to a valid node");
+        if matches!(block.terminator(), Terminator::Return { .. }) {
+            returned = returned.join(&states.at_exit(label));
+        }
+    }
+    let output = if returned.is_bottom() {
+        returned
+    } else {
+        analysis.exit(func, &returned)
+    };
+    (output, states)
+}
+
+/// Summarize the functions bottom-up over the call-graph SCCs, iterating each recursion to a
+/// fixedpoint with the outputs widened
+fn solve_bottom_up<A: SummaryAnalysis>(module: &Module, analysis: &A) -> SummaryResult<A::State> {
+    let graph = CallGraph::build(module);
+    let mut summaries = Summaries::new();
+    let mut states = BTreeMap::new();
+
+    // callees come before their callers, also within a recursion as far as possible
+    for component in graph.components() {
+        let funcs: Vec<_> = component
+            .functions
+            .iter()
+            .filter_map(|name| module.get_function(name.as_ref()))
+            .filter(|func| func.body.is_some())
+            .collect();
+        let recursive = component.recursive;
+
+        // within a recursion, the calls to the members not summarized yet return nothing
+        if recursive {
+            for func in &funcs {
+                summaries.summaries.insert(
+                    (func.name.clone(), CallString::default()),
+                    Summary {
+                        input: analysis.entry(func),
+                        output: A::State::bottom(),
+                    },
+                );
+            }
+        }
+
+        loop {
+            let mut changed = false;
+            for func in &funcs {
+                let input = analysis.entry(func);
+                let mut calls = Calls {
+                    module,
+                    resolution: Resolution::Summaries {
+                        summaries: &summaries,
+                        context: None,
+                    },
+                };
+                let (output, cfg_state) =
+                    analyze_function(analysis, func, input.clone(), &mut calls);
+                let key = (func.name.clone(), CallString::default());
+                states.insert(key.clone(), cfg_state);
+
+                let output = match summaries.summaries.get(&key) {
+                    Some(old) if recursive => old.output.widen(&old.output.join(&output)),
+                    _ => output,
+                };
+                let summary = Summary { input, output };
+                if summaries.summaries.get(&key) != Some(&summary) {
+                    summaries.summaries.insert(key, summary);
+                    changed = true;
+                }
+            }
+            if !recursive || !changed {
+                break;
+            }
+        }
+    }
+    SummaryResult {
+        summaries,
+        states,
+        call_strings: None,
+    }
+}
+
+/// Analyze the functions top-down from the entry points of the module, once per call string,
+/// with the summaries memoized per context, and then the functions not reached from their own
+/// entry states
+fn solve_call_strings<A: SummaryAnalysis>(
+    module: &Module,
+    analysis: &A,
+    config: CallStrings,
+) -> SummaryResult<A::State> {
+    let mut table = Table {
+        summaries: Summaries::new(),
+        dirty: BTreeSet::new(),
+        dependents: BTreeMap::new(),
+    };
+    for func in analysis.entry_points(module) {
+        let key = (func.name.clone(), CallString::default());
+        table.summaries.summaries.insert(
+            key.clone(),
+            Summary {
+                input: analysis.entry(func),
+                output: A::State::bottom(),
+            },
+        );
+        table.dirty.insert(key);
+    }
+
+    let mut states = BTreeMap::new();
+    loop {
+        let key = match table.dirty.pop_first() {
+            Some(key) => key,
+            None => {
+                let unreached = module.functions().values().find(|func| {
+                    func.body.is_some()
+                        && !table
+                            .summaries
+                            .summaries
+                            .keys()
+                            .any(|(name, _)| name == &func.name)
+                });
+                let func = match unreached {
+                    None => break,
+                    Some(func) => func,
+                };
+                let key = (func.name.clone(), CallString::default());
+                table.summaries.summaries.insert(
+                    key.clone(),
+                    Summary {
+                        input: analysis.entry(func),
+                        output: A::State::bottom(),
+                    },
+                );
+                key
+            }
+        };
+        let func = module
+            .get_function(key.0.as_ref())
+            .expect("only defined functions are scheduled");
+        let input = table.summaries.summaries[&key].input.clone();
+        let mut calls = Calls {
+            module,
+            resolution: Resolution::CallStrings {
+                config,
+                table: &mut table,
+                caller: &key,
+            },
+        };
+        let (output, cfg_state) = analyze_function(analysis, func, input, &mut calls);
+        states.insert(key.clone(), cfg_state);
+
+        // the callers relying on a grown output are analyzed again
+        let summary = table
+            .summaries
+            .summaries
+            .get_mut(&key)
+            .expect("scheduled contexts have a summary");
+        let output = summary.output.widen(&summary.output.join(&output));
+        if output != summary.output {
+            summary.output = output;
+            if let Some(callers) = table.dependents.get(&key) {
+                table.dirty.extend(callers.iter().cloned());
+            }
+        }
+    }
+    SummaryResult {
+        summaries: table.summaries,
+        states,
+        call_strings: Some(config),
+    }
+}
+
+/// Run a summary-based analysis over the module, in the mode the analysis asks for
+pub fn solve_summaries<A: SummaryAnalysis>(
+    module: &Module,
+    analysis: &A,
+) -> SummaryResult<A::State> {
+    match analysis.call_strings() {
+        None => solve_bottom_up(module, analysis),
+        Some(config) => solve_call_strings(module, analysis, config),
+    }
+}
@@ -0,0 +1,688 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use rug::Integer;
+use z3::ast::{Ast, Bool, BV};
+use z3::{Config, Context, SatResult, Solver};
+
+use crate::analysis::diagnostics::{Checker, Diagnostic, Diagnostics, Severity};
+use crate::analysis::overflow::Wrap;
+use crate::analysis::paths::PathBudget;
+use crate::analysis::variable::{int_bits, signed_reading};
+use crate::error::EngineResult;
+use crate::ir::bridge::cfg::{Block, ControlFlowGraph, Edge};
+use crate::ir::bridge::constant::{Constant, NumValue};
+use crate::ir::bridge::function::Function;
+use crate::ir::bridge::instruction::{
+    BinaryOpArith, BinaryOpBitwise, BinaryOpShift, ComparePredicate, ContractKind, Instruction,
+    Terminator,
+};
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::typing::{NumRepr, Type};
+use crate::ir::bridge::value::{BlockLabel, RegisterSlot, Value};
+use crate::ir::defuse::Site;
+
+/// Inputs and the path leading to a violation
+pub struct Counterexample {
+    /// values of the integer arguments, in the signed reading
+    pub arguments: Vec<(String, Integer)>,
+    /// blocks along the path, from the entry to the site
+    pub path: Vec<BlockLabel>,
+}
+
+impl Display for Counterexample {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let path: Vec<_> = self.path.iter().map(|label| label.to_string()).collect();
+        write!(f, "path {}", path.join(" -> "))?;
+        if !self.arguments.is_empty() {
+            let args: Vec<_> = self
+                .arguments
+                .iter()
+                .map(|(name, value)| format!("{} = {}", name, value))
+                .collect();
+            write!(f, " with {}", args.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of checking a finding by bounded symbolic execution
+pub enum Confirmation {
+    /// a path within the bounds violates the property
+    Confirmed(Counterexample),
+    /// every path is explored and none violates the property
+    Refuted,
+    /// no violation is found, but the bounds cut some paths
+    Unknown,
+}
+
+/// Utility: a bitvector constant, assembled from 64-bit chunks for wide integers
+fn constant<'c>(ctx: &'c Context, bits: usize, value: &Integer) -> BV<'c> {
+    let mut result: Option<BV<'c>> = None;
+    let mut done = 0;
+    while done < bits {
+        let width = (bits - done).min(64);
+        let chunk = (value.clone() >> done as u32).to_u64_wrapping();
+        let part = BV::from_u64(ctx, chunk, width as u32);
+        result = Some(match result {
+            None => part,
+            Some(low) => part.concat(&low),
+        });
+        done += width;
+    }
+    result.expect("bitvectors have at least one bit")
+}
+
+/// Utility: the unsigned value of a bitvector in a model
+fn numeral(value: &BV, bits: usize) -> Option<Integer> {
+    let mut result = Integer::new();
+    let mut done = 0;
+    while done < bits {
+        let width = (bits - done).min(64);
+        let chunk = value
+            .extract((done + width - 1) as u32, done as u32)
+            .simplify()
+            .as_u64()?;
+        result |= Integer::from(chunk) << done as u32;
+        done += width;
+    }
+    Some(result)
+}
+
+/// Utility: whether the sign bit of a bitvector is clear
+fn non_negative<'c>(ctx: &'c Context, value: &BV<'c>, bits: usize) -> Bool<'c> {
+    let msb = value.extract(bits as u32 - 1, bits as u32 - 1);
+    msb._eq(&BV::from_u64(ctx, 0, 1))
+}
+
+/// Utility: an `i1` bitvector from a boolean
+fn from_bool<'c>(ctx: &'c Context, cond: &Bool<'c>) -> BV<'c> {
+    cond.ite(&BV::from_u64(ctx, 1, 1), &BV::from_u64(ctx, 0, 1))
+}
+
+/// Utility: the condition that an operation breaks its no-wrap promise, computed exactly in
+/// twice the width
+fn wraps<'c>(
+    opcode: &BinaryOpArith,
+    bits: usize,
+    wrap: Wrap,
+    lhs: &BV<'c>,
+    rhs: &BV<'c>,
+) -> Option<Bool<'c>> {
+    let extend = |v: &BV<'c>| match wrap {
+        Wrap::Signed => v.sign_ext(bits as u32),
+        Wrap::Unsigned => v.zero_ext(bits as u32),
+    };
+    let (l, r) = (extend(lhs), extend(rhs));
+    let exact = match opcode {
+        BinaryOpArith::Add => l.bvadd(&r),
+        BinaryOpArith::Sub => l.bvsub(&r),
+        BinaryOpArith::Mul => l.bvmul(&r),
+        BinaryOpArith::Div | BinaryOpArith::Mod => return None,
+    };
+    let truncated = exact.extract(bits as u32 - 1, 0);
+    Some(exact._eq(&extend(&truncated)).not())
+}
+
+/// Integer registers of a path
+type Registers<'c> = BTreeMap<RegisterSlot, BV<'c>>;
+
+/// Bounded symbolic execution of a function, checking the no-wrap promises at the given sites
+///
+/// Integers are bitvectors; memory and pointers are not modeled, so loads, call results, and
+/// non-integer arguments are unconstrained. The IR does not keep the signedness of
+/// comparisons, divisions, shifts, and extensions, which are exact only where both readings
+/// agree and unconstrained otherwise, the same way the interval domain reads them.
+struct Executor<'c, 'a> {
+    ctx: &'c Context,
+    solver: Solver<'c>,
+    cfg: &'a ControlFlowGraph,
+    budget: &'a PathBudget,
+    /// promises to check, by site
+    queries: BTreeMap<Site, Vec<Wrap>>,
+    /// integer arguments with their names and widths
+    arguments: BTreeMap<usize, (String, usize, BV<'c>)>,
+    confirmed: BTreeMap<Site, Counterexample>,
+    /// number of unconstrained values created
+    fresh: usize,
+    /// number of complete paths explored
+    paths: usize,
+    /// whether no path is cut by the bounds
+    complete: bool,
+}
+
+impl<'c, 'a> Executor<'c, 'a> {
+    fn new(
+        ctx: &'c Context,
+        func: &'a Function,
+        cfg: &'a ControlFlowGraph,
+        budget: &'a PathBudget,
+        queries: BTreeMap<Site, Vec<Wrap>>,
+    ) -> Self {
+        let mut arguments = BTreeMap::new();
+        for (index, param) in func.params.iter().enumerate() {
+            if let Type::Bitvec {
+                bits,
+                number: NumRepr::Int,
+                length: None,
+            } = &param.ty
+            {
+                let name = match &param.name {
+                    None => format!("%arg{}", index),
+                    Some(ident) => ident.to_string(),
+                };
+                let symbol = BV::new_const(ctx, format!("arg!{}", index), *bits as u32);
+                arguments.insert(index, (name, *bits, symbol));
+            }
+        }
+        Self {
+            ctx,
+            solver: Solver::new(ctx),
+            cfg,
+            budget,
+            queries,
+            arguments,
+            confirmed: BTreeMap::new(),
+            fresh: 0,
+            paths: 0,
+            complete: true,
+        }
+    }
+
+    /// An unconstrained bitvector
+    fn fresh(&mut self, bits: usize) -> BV<'c> {
+        self.fresh += 1;
+        BV::new_const(self.ctx, format!("fresh!{}", self.fresh), bits as u32)
+    }
+
+    /// An unconstrained boolean
+    fn fresh_bool(&mut self) -> Bool<'c> {
+        self.fresh += 1;
+        Bool::new_const(self.ctx, format!("fresh!{}", self.fresh))
+    }
+
+    /// The bitvector of an integer operand
+    fn value(&mut self, regs: &Registers<'c>, value: &Value, bits: usize) -> BV<'c> {
+        match value {
+            Value::Constant(Constant::NumOne {
+                bits: _,
+                value: NumValue::Int(v),
+            }) => constant(self.ctx, bits, v),
+            Value::Register { index, ty: _ } => match regs.get(index) {
+                Some(v) if v.get_size() as usize == bits => v.clone(),
+                _ => self.fresh(bits),
+            },
+            Value::Argument { index, ty: _ } => match self.arguments.get(&index.index()) {
+                Some((_, width, v)) if *width == bits => v.clone(),
+                _ => self.fresh(bits),
+            },
+            Value::Constant(_) => self.fresh(bits),
+        }
+    }
+
+    /// A comparison, exact for the predicates whose signed and unsigned readings agree
+    fn compare(&mut self, predicate: &ComparePredicate, lhs: &BV<'c>, rhs: &BV<'c>) -> Bool<'c> {
+        let (signed, unsigned) = match predicate {
+            ComparePredicate::EQ => return lhs._eq(rhs),
+            ComparePredicate::NE => return lhs._eq(rhs).not(),
+            ComparePredicate::GT => (lhs.bvsgt(rhs), lhs.bvugt(rhs)),
+            ComparePredicate::GE => (lhs.bvsge(rhs), lhs.bvuge(rhs)),
+            ComparePredicate::LT => (lhs.bvslt(rhs), lhs.bvult(rhs)),
+            ComparePredicate::LE => (lhs.bvsle(rhs), lhs.bvule(rhs)),
+        };
+        let either = self.fresh_bool();
+        signed._eq(&unsigned).ite(&signed, &either)
+    }
+
+    /// Execute an instruction, returning `false` if the path is cut by an assumption
+    fn execute(&mut self, regs: &mut Registers<'c>, inst: &Instruction) -> bool {
+        let (result, value) = match inst {
+            Instruction::BinaryArith {
+                bits,
+                number: NumRepr::Int,
+                length: None,
+                opcode,
+                lhs,
+                rhs,
+                result,
+                ..
+            } => {
+                let l = self.value(regs, lhs, *bits);
+                let r = self.value(regs, rhs, *bits);
+                let value = match opcode {
+                    BinaryOpArith::Add => l.bvadd(&r),
+                    BinaryOpArith::Sub => l.bvsub(&r),
+                    BinaryOpArith::Mul => l.bvmul(&r),
+                    BinaryOpArith::Div | BinaryOpArith::Mod => {
+                        // both readings agree on non-negative operands
+                        let exact = match opcode {
+                            BinaryOpArith::Div => l.bvudiv(&r),
+                            _ => l.bvurem(&r),
+                        };
+                        let agree = Bool::and(
+                            self.ctx,
+                            &[
+                                &non_negative(self.ctx, &l, *bits),
+                                &non_negative(self.ctx, &r, *bits),
+                            ],
+                        );
+                        agree.ite(&exact, &self.fresh(*bits))
+                    }
+                };
+                (*result, value)
+            }
+            Instruction::BinaryBitwise {
+                bits,
+                length: None,
+                opcode,
+                lhs,
+                rhs,
+                result,
+            } => {
+                let l = self.value(regs, lhs, *bits);
+                let r = self.value(regs, rhs, *bits);
+                let value = match opcode {
+                    BinaryOpBitwise::And => l.bvand(&r),
+                    BinaryOpBitwise::Or => l.bvor(&r),
+                    BinaryOpBitwise::Xor => l.bvxor(&r),
+                };
+                (*result, value)
+            }
+            Instruction::BinaryShift {
+                bits,
+                length: None,
+                opcode,
+                lhs,
+                rhs,
+                result,
+            } => {
+                let l = self.value(regs, lhs, *bits);
+                let r = self.value(regs, rhs, *bits);
+                let value = match opcode {
+                    BinaryOpShift::Shl => l.bvshl(&r),
+                    // logical and arithmetic shifts agree on non-negative operands
+                    BinaryOpShift::Shr => {
+                        non_negative(self.ctx, &l, *bits).ite(&l.bvlshr(&r), &self.fresh(*bits))
+                    }
+                };
+                (*result, value)
+            }
+            Instruction::CompareBitvec {
+                bits,
+                number: NumRepr::Int,
+                length: None,
+                predicate,
+                lhs,
+                rhs,
+                result,
+            } => {
+                let l = self.value(regs, lhs, *bits);
+                let r = self.value(regs, rhs, *bits);
+                let cond = self.compare(predicate, &l, &r);
+                (*result, from_bool(self.ctx, &cond))
+            }
+            Instruction::CastBitvecSize {
+                bits_from,
+                bits_into,
+                number: NumRepr::Int,
+                length: None,
+                operand,
+                result,
+            } => {
+                let v = self.value(regs, operand, *bits_from);
+                let value = if bits_into < bits_from {
+                    v.extract(*bits_into as u32 - 1, 0)
+                } else {
+                    // either a zero or a sign extension
+                    let extra = (bits_into - bits_from) as u32;
+                    let signed = self.fresh_bool();
+                    signed.ite(&v.sign_ext(extra), &v.zero_ext(extra))
+                };
+                (*result, value)
+            }
+            Instruction::ITEOne {
+                cond,
+                then_value,
+                else_value,
+                result,
+            } => {
+                let bits = match int_bits(then_value).or_else(|| int_bits(else_value)) {
+                    None => return true,
+                    Some(bits) => bits,
+                };
+                let c = self.value(regs, cond, 1);
+                let t = self.value(regs, then_value, bits);
+                let e = self.value(regs, else_value, bits);
+                (*result, c._eq(&BV::from_u64(self.ctx, 1, 1)).ite(&t, &e))
+            }
+            Instruction::Contract {
+                kind: ContractKind::Assume,
+                cond,
+            } => {
+                let c = self.value(regs, cond, 1);
+                self.solver.assert(&c._eq(&BV::from_u64(self.ctx, 1, 1)));
+                return self.solver.check() != SatResult::Unsat;
+            }
+            // loads and calls yield an unconstrained integer, kept for the later uses
+            Instruction::Load {
+                pointee_type:
+                    Type::Bitvec {
+                        bits,
+                        number: NumRepr::Int,
+                        length: None,
+                    },
+                result,
+                ..
+            }
+            | Instruction::CallDirect {
+                result:
+                    Some((
+                        Type::Bitvec {
+                            bits,
+                            number: NumRepr::Int,
+                            length: None,
+                        },
+                        result,
+                    )),
+                ..
+            }
+            | Instruction::CallIndirect {
+                result:
+                    Some((
+                        Type::Bitvec {
+                            bits,
+                            number: NumRepr::Int,
+                            length: None,
+                        },
+                        result,
+                    )),
+                ..
+            } => (*result, self.fresh(*bits)),
+            // everything else is not an integer or not modeled
+            _ => {
+                if let Some(slot) = inst.result() {
+                    regs.remove(&slot);
+                }
+                return true;
+            }
+        };
+        regs.insert(result, value);
+        true
+    }
+
+    /// Check the promises of the operation at a site, on the path so far
+    fn query(&mut self, regs: &Registers<'c>, site: Site, inst: &Instruction, path: &[BlockLabel]) {
+        if self.confirmed.contains_key(&site) {
+            return;
+        }
+        let wraps_at = match self.queries.get(&site) {
+            None => return,
+            Some(items) => items.clone(),
+        };
+        let (bits, opcode, lhs, rhs) = match inst {
+            Instruction::BinaryArith {
+                bits,
+                number: NumRepr::Int,
+                length: None,
+                opcode,
+                lhs,
+                rhs,
+                ..
+            } => (*bits, opcode, lhs, rhs),
+            _ => return,
+        };
+        let l = self.value(regs, lhs, bits);
+        let r = self.value(regs, rhs, bits);
+        let violations: Vec<_> = wraps_at
+            .into_iter()
+            .filter_map(|wrap| wraps(opcode, bits, wrap, &l, &r))
+            .collect();
+        if violations.is_empty() {
+            return;
+        }
+        let any = Bool::or(self.ctx, &violations.iter().collect::<Vec<_>>());
+
+        self.solver.push();
+        self.solver.assert(&any);
+        if self.solver.check() == SatResult::Sat {
+            if let Some(model) = self.solver.get_model() {
+                let arguments = self
+                    .arguments
+                    .values()
+                    .filter_map(|(name, bits, symbol)| {
+                        let value = model.eval(symbol, true)?;
+                        let number = numeral(&value, *bits)?;
+                        Some((name.clone(), signed_reading(*bits, &number)))
+                    })
+                    .collect();
+                self.confirmed.insert(
+                    site,
+                    Counterexample {
+                        arguments,
+                        path: path.to_vec(),
+                    },
+                );
+            }
+        }
+        self.solver.pop(1);
+    }
+
+    /// Condition of taking an edge out of a block
+    fn edge_condition(
+        &mut self,
+        regs: &Registers<'c>,
+        terminator: &Terminator,
+        edge: &Edge,
+    ) -> Option<Bool<'c>> {
+        match (terminator, edge) {
+            (Terminator::Branch { cond, .. }, Edge::Branch(taken)) => {
+                let c = self.value(regs, cond, 1);
+                Some(c._eq(&BV::from_u64(self.ctx, *taken as u64, 1)))
+            }
+            (Terminator::Switch { cond, cases, .. }, Edge::Switch(values)) => {
+                let bits = int_bits(cond)?;
+                let c = self.value(regs, cond, bits);
+                let matches = |v: &Integer| c._eq(&constant(self.ctx, bits, v));
+                let options: Vec<_> = values
+                    .iter()
+                    .map(|value| match value {
+                        Some(v) => matches(v),
+                        // the default edge is taken when no case matches
+                        None => {
+                            let others: Vec<_> = cases.keys().map(|v| matches(v).not()).collect();
+                            Bool::and(self.ctx, &others.iter().collect::<Vec<_>>())
+                        }
+                    })
+                    .collect();
+                Some(Bool::or(self.ctx, &options.iter().collect::<Vec<_>>()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Explore the paths through a block, entered from a predecessor (if any)
+    fn visit(
+        &mut self,
+        label: BlockLabel,
+        from: Option<BlockLabel>,
+        mut regs: Registers<'c>,
+        mut visits: BTreeMap<BlockLabel, usize>,
+        mut path: Vec<BlockLabel>,
+    ) {
+        if self.paths >= self.budget.max_paths {
+            self.complete = false;
+            return;
+        }
+        let count = visits.entry(label).or_insert(0);
+        *count += 1;
+        if *count > self.budget.max_unroll || path.len() >= self.budget.max_depth {
+            self.complete = false;
+            return;
+        }
+        path.push(label);
+
+        let cfg = self.cfg;
+        let block: &Block = cfg
+            .get_block_by_label(&label)
+            .expect("block label points to a valid node");
+
+        // phi nodes read the registers before any of them is assigned
+        let mut phis = vec![];
+        for inst in block.instructions() {
+            let (options, result) = match inst {
+                Instruction::Phi { options, result } => (options, result),
+                _ => break,
+            };
+            let incoming = from.and_then(|pred| options.get(&pred));
+            let value = match (incoming, options.values().find_map(int_bits)) {
+                (Some(value), Some(bits)) => Some(self.value(&regs, value, bits)),
+                _ => None,
+            };
+            phis.push((*result, value));
+        }
+        for (slot, value) in phis {
+            match value {
+                None => regs.remove(&slot),
+                Some(v) => regs.insert(slot, v),
+            };
+        }
+
+        self.solver.push();
+        let mut feasible = true;
+        for (index, inst) in block.instructions().iter().enumerate() {
+            if matches!(inst, Instruction::Phi { .. }) {
+                continue;
+            }
+            let site = Site::Instruction {
+                block: label,
+                index,
+            };
+            self.query(&regs, site, inst, &path);
+            if !self.execute(&mut regs, inst) {
+                feasible = false;
+                break;
+            }
+        }
+
+        if feasible {
+            let succs = cfg.successors(&label);
+            if succs.is_empty() {
+                self.paths += 1;
+            }
+            for (dst, edge) in succs {
+                let cond = self.edge_condition(&regs, block.terminator(), edge);
+                self.solver.push();
+                if let Some(cond) = &cond {
+                    self.solver.assert(cond);
+                }
+                // infeasible edges are pruned, undecided ones explored
+                if self.solver.check() != SatResult::Unsat {
+                    self.visit(dst, Some(label), regs.clone(), visits.clone(), path.clone());
+                }
+                self.solver.pop(1);
+            }
+        }
+        self.solver.pop(1);
+    }
+
+    /// Explore the function from its entry, deciding every query
+    fn run(mut self) -> BTreeMap<Site, Confirmation> {
+        let entry = self.cfg.entry();
+        self.visit(entry, None, Registers::new(), BTreeMap::new(), vec![]);
+
+        let sites: BTreeSet<_> = self.queries.keys().copied().collect();
+        let mut outcomes = BTreeMap::new();
+        for site in sites {
+            let outcome = match self.confirmed.remove(&site) {
+                Some(counterexample) => Confirmation::Confirmed(counterexample),
+                None if self.complete => Confirmation::Refuted,
+                None => Confirmation::Unknown,
+            };
+            outcomes.insert(site, outcome);
+        }
+        outcomes
+    }
+}
+
+/// Decide by bounded symbolic execution whether the no-wrap operations at the given sites of a
+/// function may overflow
+pub fn confirm_overflows(
+    func: &Function,
+    sites: &BTreeSet<Site>,
+    budget: &PathBudget,
+) -> BTreeMap<Site, Confirmation> {
+    let cfg = match &func.body {
+        None => return BTreeMap::new(),
+        Some(cfg) => cfg,
+    };
+    let mut queries = BTreeMap::new();
+    for site in sites {
+        let (block, index) = match site {
+            Site::Instruction { block, index } => (block, *index),
+            Site::Terminator { .. } => continue,
+        };
+        let inst = cfg
+            .get_block_by_label(block)
+            .and_then(|b| b.instructions().get(index));
+        if let Some(Instruction::BinaryArith { nsw, nuw, .. }) = inst {
+            let promises = [(*nsw, Wrap::Signed), (*nuw, Wrap::Unsigned)];
+            let wraps: Vec<_> = promises
+                .into_iter()
+                .filter(|(flag, _)| *flag)
+                .map(|(_, wrap)| wrap)
+                .collect();
+            queries.insert(*site, wraps);
+        }
+    }
+    if queries.is_empty() {
+        return BTreeMap::new();
+    }
+
+    let config = Config::new();
+    let ctx = Context::new(&config);
+    Executor::new(&ctx, func, cfg, budget, queries).run()
+}
+
+/// Confirm or refute the findings of the abstract domains before they are reported: refuted
+/// ones are dropped, and confirmed ones are raised to errors with a counterexample
+pub fn confirm_findings(
+    module: &Module,
+    diagnostics: &mut Diagnostics,
+    budget: &PathBudget,
+) -> EngineResult<()> {
+    let mut sites: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+    for item in diagnostics.iter() {
+        if let (Checker::Overflows, Some(site)) = (item.checker, item.site) {
+            sites.entry(item.function.clone()).or_default().insert(site);
+        }
+    }
+
+    let mut outcomes = BTreeMap::new();
+    for (name, items) in sites {
+        let func = match module.get_function(name.as_ref()) {
+            None => continue,
+            Some(func) => func,
+        };
+        for (site, outcome) in confirm_overflows(func, &items, budget) {
+            outcomes.insert((name.clone(), site), outcome);
+        }
+    }
+
+    let lookup = |item: &Diagnostic| {
+        let site = item.site?;
+        outcomes.get(&(item.function.clone(), site))
+    };
+    diagnostics.retain(|item| {
+        item.checker != Checker::Overflows || !matches!(lookup(item), Some(Confirmation::Refuted))
+    });
+    for item in diagnostics.iter_mut() {
+        if item.checker != Checker::Overflows {
+            continue;
+        }
+        if let Some(Confirmation::Confirmed(counterexample)) = lookup(item) {
+            item.severity = Severity::Error;
+            item.message = format!("{}, e.g., on {}", item.message, counterexample);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,565 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::dataflow::{
+    solve_transfer, Filter, Findings, MemoryStore, TransferFunction, Widening,
+};
+use crate::analysis::lattice::Lattice;
+use crate::analysis::libc::find_model;
+use crate::analysis::memory::AllocSite;
+use crate::analysis::pointsto::{analyze_points_to, AliasQuery, PointsTo};
+use crate::analysis::variable::Variable;
+use crate::error::{EngineError, EngineResult};
+use crate::ir::bridge::cfg::{ControlFlowGraph, Edge};
+use crate::ir::bridge::function::Function;
+use crate::ir::bridge::instruction::{Instruction, Terminator};
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::value::{BlockLabel, RegisterSlot, Value};
+use crate::ir::defuse::Site;
+
+/// A function of interest to the taint analysis
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FunctionSpec {
+    /// name of the function
+    pub function: String,
+    /// positions of the pointer arguments whose pointees are affected or checked
+    #[serde(default)]
+    pub args: Vec<usize>,
+    /// whether the return value is affected
+    #[serde(default, rename = "return")]
+    pub ret: bool,
+}
+
+/// Sources, sinks, and sanitizers of the taint analysis
+///
+/// A source taints its return value and the pointees of its listed arguments, a sink reports
+/// tainted values (or pointees) in its listed arguments, and a sanitizer cleans its return
+/// value and the pointees of its listed arguments.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct TaintSpec {
+    #[serde(default)]
+    pub sources: Vec<FunctionSpec>,
+    #[serde(default)]
+    pub sinks: Vec<FunctionSpec>,
+    #[serde(default)]
+    pub sanitizers: Vec<FunctionSpec>,
+}
+
+impl TaintSpec {
+    /// Load the spec from a JSON file
+    pub fn load(path: &Path) -> EngineResult<Self> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            EngineError::InvalidAssumption(format!(
+                "unable to read taint spec {}: {}",
+                path.to_string_lossy(),
+                e
+            ))
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            EngineError::InvalidAssumption(format!(
+                "malformed taint spec {}: {}",
+                path.to_string_lossy(),
+                e
+            ))
+        })
+    }
+
+    fn find<'a>(items: &'a [FunctionSpec], name: &str) -> Option<&'a FunctionSpec> {
+        items.iter().find(|item| item.function == name)
+    }
+}
+
+/// A call to a source function
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone)]
+pub struct Origin {
+    pub source: String,
+    pub function: Identifier,
+    pub site: Site,
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at @{}:{}", self.source, self.function, self.site)
+    }
+}
+
+/// Utility: render a trace of sites
+fn fmt_trace(trace: &[Site]) -> String {
+    let items: Vec<_> = trace.iter().map(|site| site.to_string()).collect();
+    items.join(" -> ")
+}
+
+/// Taint of a value: the sources it may derive from, each with a witness trace
+#[derive(Eq, PartialEq, Clone)]
+pub enum Taint {
+    /// no value at all
+    Never,
+    /// derived from the sources (clean if none), or possibly from anything if `unknown`
+    Flows {
+        sources: BTreeMap<Origin, Vec<Site>>,
+        unknown: bool,
+    },
+}
+
+impl Taint {
+    pub fn clean() -> Self {
+        Self::Flows {
+            sources: BTreeMap::new(),
+            unknown: false,
+        }
+    }
+
+    /// The value produced by a source
+    pub fn from_source(origin: Origin) -> Self {
+        let trace = vec![origin.site];
+        Self::Flows {
+            sources: BTreeMap::from([(origin, trace)]),
+            unknown: false,
+        }
+    }
+
+    /// Sources the value may derive from, with the trace of each flow
+    pub fn sources(&self) -> impl Iterator<Item = (&Origin, &Vec<Site>)> {
+        let sources = match self {
+            Self::Never => None,
+            Self::Flows { sources, .. } => Some(sources),
+        };
+        sources.into_iter().flatten()
+    }
+
+    /// The same taint propagated through a site
+    fn step(&self, site: Site) -> Self {
+        match self {
+            Self::Never => Self::Never,
+            Self::Flows { sources, unknown } => Self::Flows {
+                sources: sources
+                    .iter()
+                    .map(|(origin, trace)| {
+                        let mut trace = trace.clone();
+                        if trace.last() != Some(&site) {
+                            trace.push(site);
+                        }
+                        (origin.clone(), trace)
+                    })
+                    .collect(),
+                unknown: *unknown,
+            },
+        }
+    }
+}
+
+impl Lattice for Taint {
+    fn bottom() -> Self {
+        Self::Never
+    }
+
+    fn top() -> Self {
+        Self::Flows {
+            sources: BTreeMap::new(),
+            unknown: true,
+        }
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Never, _) => true,
+            (_, Self::Never) => false,
+            (
+                Self::Flows {
+                    sources: s1,
+                    unknown: u1,
+                },
+                Self::Flows {
+                    sources: s2,
+                    unknown: u2,
+                },
+            ) => *u2 || (!*u1 && s1.keys().all(|k| s2.contains_key(k))),
+        }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Never, _) => other.clone(),
+            (_, Self::Never) => self.clone(),
+            (
+                Self::Flows {
+                    sources: s1,
+                    unknown: u1,
+                },
+                Self::Flows {
+                    sources: s2,
+                    unknown: u2,
+                },
+            ) => {
+                if *u1 || *u2 {
+                    return Self::top();
+                }
+                // keep the shorter trace as the witness, which also makes the join stabilize
+                let mut sources = s1.clone();
+                for (origin, trace) in s2 {
+                    match sources.get(origin) {
+                        Some(known) if (known.len(), known) <= (trace.len(), trace) => (),
+                        _ => {
+                            sources.insert(origin.clone(), trace.clone());
+                        }
+                    }
+                }
+                Self::Flows {
+                    sources,
+                    unknown: false,
+                }
+            }
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Never, _) | (_, Self::Never) => Self::Never,
+            (Self::Flows { unknown: true, .. }, _) => other.clone(),
+            (_, Self::Flows { unknown: true, .. }) => self.clone(),
+            (Self::Flows { sources: s1, .. }, Self::Flows { sources: s2, .. }) => Self::Flows {
+                sources: s1
+                    .iter()
+                    .filter(|(k, _)| s2.contains_key(*k))
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+                unknown: false,
+            },
+        }
+    }
+}
+
+impl Display for Taint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Never => write!(f, "never"),
+            Self::Flows { unknown: true, .. } => write!(f, "unknown"),
+            Self::Flows { sources, .. } if sources.is_empty() => write!(f, "clean"),
+            Self::Flows { sources, .. } => {
+                let items: Vec<_> = sources.keys().map(|origin| origin.to_string()).collect();
+                write!(f, "tainted by {}", items.join(", "))
+            }
+        }
+    }
+}
+
+/// Taint of the variables and of the memory
+pub type TaintStore = MemoryStore<Taint>;
+
+/// A tainted value reaching an argument of a sink
+pub struct TaintFlow {
+    pub function: Identifier,
+    pub site: Site,
+    pub sink: String,
+    /// position of the argument
+    pub arg: usize,
+    pub origin: Origin,
+    /// sites the tainted value flows through, from the source to the sink
+    pub trace: Vec<Site>,
+}
+
+impl Display for TaintFlow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "@{}:{}: argument {} of {} is tainted by {} (trace: {})",
+            self.function,
+            self.site,
+            self.arg,
+            self.sink,
+            self.origin,
+            fmt_trace(&self.trace)
+        )
+    }
+}
+
+/// Intraprocedural taint propagation in a function, with memory resolved by the points-to sets
+struct Analysis<'a> {
+    spec: &'a TaintSpec,
+    function: &'a Identifier,
+    cfg: &'a ControlFlowGraph,
+    global: &'a PointsTo,
+    /// allocations tracked in the memory, all clean on entry
+    sites: &'a BTreeSet<AllocSite>,
+}
+
+impl Analysis<'_> {
+    fn eval(&self, state: &TaintStore, value: &Value) -> Taint {
+        match Variable::of_value(value) {
+            None => Taint::clean(),
+            Some(var) => state.variables.get(&var),
+        }
+    }
+
+    /// Taint of whatever the value points to
+    fn pointees(&self, state: &TaintStore, value: &Value) -> Taint {
+        let targets = self.global.targets(self.function, value);
+        Taint::clean().join(&state.load(&targets))
+    }
+
+    /// Write a taint into the pointees, replacing the old one if `strong` and the pointee is
+    /// a unique object
+    fn write(&self, state: &mut TaintStore, pointer: &Value, taint: Taint, strong: bool) {
+        let targets = self.global.targets(self.function, pointer);
+        state.store(&targets, &taint, strong);
+    }
+
+    /// Taint of the arguments of a call and of their pointees
+    fn propagate(&self, state: &TaintStore, args: &[Value]) -> Taint {
+        args.iter().fold(Taint::clean(), |acc, arg| {
+            acc.join(&self.eval(state, arg))
+                .join(&self.pointees(state, arg))
+        })
+    }
+
+    fn initial(&self, func: &Function) -> TaintStore {
+        let mut state = TaintStore::top();
+        for index in 0..func.params.len() {
+            state
+                .variables
+                .set(Variable::Argument(index.into()), Taint::clean());
+        }
+        for site in self.sites {
+            state.memory.set(site.clone(), Taint::clean());
+        }
+        state
+    }
+
+    /// Tainted values reaching the sink arguments of a call
+    fn check_call(
+        &self,
+        state: &TaintStore,
+        site: Site,
+        callee: &Identifier,
+        args: &[Value],
+        flows: &mut Vec<TaintFlow>,
+    ) {
+        let spec = match TaintSpec::find(&self.spec.sinks, callee.as_ref()) {
+            None => return,
+            Some(spec) => spec,
+        };
+        for (index, arg) in args.iter().enumerate() {
+            if !spec.args.contains(&index) {
+                continue;
+            }
+            let taint = self.eval(state, arg).join(&self.pointees(state, arg));
+            for (origin, trace) in taint.sources() {
+                let mut trace = trace.clone();
+                trace.push(site);
+                flows.push(TaintFlow {
+                    function: self.function.clone(),
+                    site,
+                    sink: spec.function.clone(),
+                    arg: index,
+                    origin: origin.clone(),
+                    trace,
+                });
+            }
+        }
+    }
+}
+
+impl TransferFunction for Analysis<'_> {
+    type State = TaintStore;
+
+    fn call(
+        &self,
+        state: &mut TaintStore,
+        site: Site,
+        callee: Option<&Identifier>,
+        args: &[Value],
+        result: Option<RegisterSlot>,
+    ) {
+        let name = callee.map(|name| name.as_ref());
+        if let Some(spec) = name.and_then(|n| TaintSpec::find(&self.spec.sources, n)) {
+            let taint = Taint::from_source(Origin {
+                source: spec.function.clone(),
+                function: self.function.clone(),
+                site,
+            });
+            if let (true, Some(slot)) = (spec.ret, result) {
+                state.variables.set(Variable::Register(slot), taint.clone());
+            }
+            for arg in spec.args.iter().filter_map(|i| args.get(*i)) {
+                self.write(state, arg, taint.clone(), false);
+            }
+            return;
+        }
+        if let Some(spec) = name.and_then(|n| TaintSpec::find(&self.spec.sanitizers, n)) {
+            if let Some(slot) = result {
+                let taint = if spec.ret {
+                    Taint::clean()
+                } else {
+                    self.propagate(state, args).step(site)
+                };
+                state.variables.set(Variable::Register(slot), taint);
+            }
+            // cleaning is only sound when the pointee is known exactly
+            for arg in spec.args.iter().filter_map(|i| args.get(*i)) {
+                self.write(state, arg, Taint::clean(), true);
+            }
+            return;
+        }
+
+        // modeled library functions move the taint between the pointees as they copy data
+        if let Some(model) = name.and_then(find_model) {
+            for (dst, src) in model.copies {
+                if let (Some(dst), Some(src)) = (args.get(*dst), args.get(*src)) {
+                    let taint = self.pointees(state, src).step(site);
+                    self.write(state, dst, taint, false);
+                }
+            }
+            if let Some(slot) = result {
+                let taint = model
+                    .reads
+                    .iter()
+                    .filter_map(|i| args.get(*i))
+                    .fold(Taint::clean(), |acc, arg| {
+                        acc.join(&self.pointees(state, arg))
+                    });
+                let taint = args
+                    .iter()
+                    .fold(taint, |acc, arg| acc.join(&self.eval(state, arg)));
+                state
+                    .variables
+                    .set(Variable::Register(slot), taint.step(site));
+            }
+            return;
+        }
+
+        // other functions pass the taint of their arguments on to the result
+        if let Some(slot) = result {
+            let taint = self.propagate(state, args).step(site);
+            state.variables.set(Variable::Register(slot), taint);
+        }
+    }
+
+    fn instruction(&self, state: &mut TaintStore, site: Site, inst: &Instruction) {
+        match inst {
+            Instruction::Alloca { result, .. } => {
+                state
+                    .variables
+                    .set(Variable::Register(*result), Taint::clean());
+            }
+            Instruction::Load {
+                pointer, result, ..
+            } => {
+                let taint = self.pointees(state, pointer).step(site);
+                state.variables.set(Variable::Register(*result), taint);
+            }
+            Instruction::Store { pointer, value, .. } => {
+                let taint = self.eval(state, value).step(site);
+                self.write(state, pointer, taint, true);
+            }
+            // phi nodes are resolved on the incoming edges
+            Instruction::Phi { .. } => (),
+            _ => {
+                if let Some(slot) = inst.result() {
+                    let taint = inst
+                        .operands()
+                        .into_iter()
+                        .fold(Taint::clean(), |acc, v| acc.join(&self.eval(state, v)));
+                    state
+                        .variables
+                        .set(Variable::Register(slot), taint.step(site));
+                }
+            }
+        }
+    }
+
+    fn edge(
+        &self,
+        src: &BlockLabel,
+        dst: &BlockLabel,
+        _edge: &Edge,
+        _filters: &[Filter],
+        state: &TaintStore,
+    ) -> TaintStore {
+        let mut state = state.clone();
+        let block = self
+            .cfg
+            .get_block_by_label(dst)
+            .expect("block label points to a valid node");
+        let mut resolved = vec![];
+        for (index, inst) in block.instructions().iter().enumerate() {
+            let (options, result) = match inst {
+                Instruction::Phi { options, result } => (options, result),
+                _ => break,
+            };
+            let site = Site::Instruction { block: *dst, index };
+            let taint = match options.get(src) {
+                None => Taint::top(),
+                Some(option) => self.eval(&state, option).step(site),
+            };
+            resolved.push((*result, taint));
+        }
+        for (slot, taint) in resolved {
+            state.variables.set(Variable::Register(slot), taint);
+        }
+        state
+    }
+}
+
+/// Find the flows from the sources to the sinks in each function of the module
+pub fn analyze_taint(
+    module: &Module,
+    spec: &TaintSpec,
+    widening: &Widening,
+) -> EngineResult<Findings<TaintFlow>> {
+    let global = analyze_points_to(module)?;
+    let mut sites: BTreeSet<_> = module
+        .globals()
+        .keys()
+        .map(|name| AllocSite::Global(name.clone()))
+        .collect();
+    for (_, set) in global.nodes() {
+        sites.extend(set.iter().cloned());
+    }
+
+    let mut flows = Findings::new();
+    for (name, func) in module.functions() {
+        let cfg = match &func.body {
+            None => continue,
+            Some(cfg) => cfg,
+        };
+        let analysis = Analysis {
+            spec,
+            function: name,
+            cfg,
+            global: &global,
+            sites: &sites,
+        };
+        let states = solve_transfer(cfg, analysis.initial(func), widening, &analysis);
+        flows.record(name, states.exhausted());
+
+        // replay the reachable blocks to inspect the state at each sink
+        for label in states.reachable() {
+            let block = cfg
+                .get_block_by_label(label)
+                .expect("block label points to a valid node");
+            let mut state = states.at_entry(label);
+            for (index, inst) in block.instructions().iter().enumerate() {
+                let site = Site::Instruction {
+                    block: *label,
+                    index,
+                };
+                if let Instruction::CallDirect { function, args, .. } = inst {
+                    analysis.check_call(&state, site, function, args, &mut flows.items);
+                }
+                analysis.step(&mut state, site, inst);
+            }
+            if let Terminator::InvokeDirect { function, args, .. } = block.terminator() {
+                let site = Site::Terminator { block: *label };
+                analysis.check_call(&state, site, function, args, &mut flows.items);
+            }
+        }
+    }
+    Ok(flows)
+}
@@ -0,0 +1,273 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use crate::analysis::memory::AllocSite;
+use crate::analysis::pointsto::{
+    constant_addresses, generate_constraints, AliasQuery, ConstraintSink, IndirectCall, Node,
+    Operand,
+};
+use crate::error::EngineResult;
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::value::Value;
+
+/// Equivalence classes of nodes, each pointing to at most one class
+#[derive(Default)]
+struct Unifier {
+    /// class allocated for each node
+    ids: BTreeMap<Node, usize>,
+    /// union-find forest over the classes
+    parent: Vec<usize>,
+    /// the class pointed to, allocated lazily
+    pointee: Vec<Option<usize>>,
+    /// indirect calls, resolved after the other constraints
+    calls: Vec<(Node, IndirectCall)>,
+}
+
+impl Unifier {
+    fn fresh(&mut self) -> usize {
+        let id = self.parent.len();
+        self.parent.push(id);
+        self.pointee.push(None);
+        id
+    }
+
+    fn find(&mut self, mut id: usize) -> usize {
+        while self.parent[id] != id {
+            self.parent[id] = self.parent[self.parent[id]];
+            id = self.parent[id];
+        }
+        id
+    }
+
+    fn class(&mut self, node: &Node) -> usize {
+        let id = match self.ids.get(node) {
+            Some(id) => *id,
+            None => {
+                let id = self.fresh();
+                self.ids.insert(node.clone(), id);
+                id
+            }
+        };
+        self.find(id)
+    }
+
+    /// The class pointed to by a class, allocated if none yet
+    fn deref(&mut self, id: usize) -> usize {
+        let root = self.find(id);
+        match self.pointee[root] {
+            Some(target) => self.find(target),
+            None => {
+                let target = self.fresh();
+                self.pointee[root] = Some(target);
+                target
+            }
+        }
+    }
+
+    /// Merge two classes, and recursively the classes they point to
+    fn union(&mut self, lhs: usize, rhs: usize) {
+        let mut pending = vec![(lhs, rhs)];
+        while let Some((lhs, rhs)) = pending.pop() {
+            let lhs = self.find(lhs);
+            let rhs = self.find(rhs);
+            if lhs == rhs {
+                continue;
+            }
+            self.parent[rhs] = lhs;
+            match (self.pointee[lhs], self.pointee[rhs]) {
+                (Some(a), Some(b)) => pending.push((a, b)),
+                (None, Some(b)) => self.pointee[lhs] = Some(b),
+                (_, None) => (),
+            }
+        }
+    }
+
+    /// The class pointed to by an operand, if it may point to anything
+    fn pointee_of(&mut self, operand: &Operand) -> Option<usize> {
+        match operand {
+            Operand::Node(node) => {
+                let id = self.class(node);
+                Some(self.deref(id))
+            }
+            Operand::Constant(addresses) => {
+                let mut merged = None;
+                for site in addresses {
+                    let id = self.class(&Node::Object(site.clone()));
+                    match merged {
+                        None => merged = Some(id),
+                        Some(prev) => self.union(prev, id),
+                    }
+                }
+                merged
+            }
+        }
+    }
+
+    /// Link the indirect calls until no new callee shows up
+    fn resolve_calls(&mut self, module: &Module) {
+        let mut linked = BTreeSet::new();
+        loop {
+            let mut changed = false;
+            let calls = std::mem::take(&mut self.calls);
+            let functions = self.sites_by_class();
+            for (index, (callee, call)) in calls.iter().enumerate() {
+                let class = self.class(callee);
+                let target = self.deref(class);
+                let names: Vec<_> = functions
+                    .get(&self.find(target))
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|site| match site {
+                        AllocSite::Global(name) => Some(name.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                for name in names {
+                    if linked.insert((index, name.clone())) {
+                        changed = true;
+                        self.link(
+                            module,
+                            &call.function,
+                            call.site,
+                            &name,
+                            &call.args,
+                            call.result.as_ref(),
+                        );
+                    }
+                }
+            }
+            self.calls = calls;
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    /// Allocation sites whose contents fall into each class
+    fn sites_by_class(&mut self) -> BTreeMap<usize, BTreeSet<AllocSite>> {
+        let objects: Vec<_> = self
+            .ids
+            .iter()
+            .filter_map(|(node, id)| match node {
+                Node::Object(site) => Some((site.clone(), *id)),
+                _ => None,
+            })
+            .collect();
+        let mut sites: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+        for (site, id) in objects {
+            let root = self.find(id);
+            sites.entry(root).or_default().insert(site);
+        }
+        sites
+    }
+}
+
+impl ConstraintSink for Unifier {
+    fn add_addresses(&mut self, dst: &Node, addresses: &BTreeSet<AllocSite>) {
+        if let Some(src) = self.pointee_of(&Operand::Constant(addresses.clone())) {
+            let class = self.class(dst);
+            let target = self.deref(class);
+            self.union(target, src);
+        }
+    }
+
+    fn add_copy(&mut self, dst: &Node, src: &Operand) {
+        if let Some(src) = self.pointee_of(src) {
+            let class = self.class(dst);
+            let target = self.deref(class);
+            self.union(target, src);
+        }
+    }
+
+    fn add_load(&mut self, dst: &Node, src: &Operand) {
+        if let Some(src) = self.pointee_of(src) {
+            let src = self.deref(src);
+            let class = self.class(dst);
+            let target = self.deref(class);
+            self.union(target, src);
+        }
+    }
+
+    fn add_store(&mut self, dst: &Operand, src: &Operand) {
+        if let (Some(dst), Some(src)) = (self.pointee_of(dst), self.pointee_of(src)) {
+            let target = self.deref(dst);
+            self.union(target, src);
+        }
+    }
+
+    fn add_indirect_call(&mut self, callee: Node, call: IndirectCall) {
+        self.calls.push((callee, call));
+    }
+}
+
+/// Flow- and context-insensitive points-to sets of a module, solved by unification
+pub struct UnifiedPointsTo {
+    /// class of each node
+    classes: BTreeMap<Node, usize>,
+    /// class pointed to by each class
+    pointees: BTreeMap<usize, usize>,
+    /// allocation sites whose contents fall into each class
+    sites: BTreeMap<usize, BTreeSet<AllocSite>>,
+}
+
+impl UnifiedPointsTo {
+    /// Points-to set of a node
+    pub fn points_to(&self, node: &Node) -> BTreeSet<AllocSite> {
+        self.classes
+            .get(node)
+            .and_then(|class| self.pointees.get(class))
+            .and_then(|target| self.sites.get(target))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+impl AliasQuery for UnifiedPointsTo {
+    fn targets(&self, function: &Identifier, value: &Value) -> BTreeSet<AllocSite> {
+        match value {
+            Value::Constant(constant) => constant_addresses(constant),
+            _ => self.points_to(&Node::of_value(function, value).expect("non-constant value")),
+        }
+    }
+}
+
+impl Display for UnifiedPointsTo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for node in self.classes.keys() {
+            let set = self.points_to(node);
+            if set.is_empty() {
+                continue;
+            }
+            let items: Vec<_> = set.iter().map(|site| site.to_string()).collect();
+            writeln!(f, "{} -> {{{}}}", node, items.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Run a unification-based (Steensgaard-style) pointer analysis over the module
+pub fn analyze_unification(module: &Module) -> EngineResult<UnifiedPointsTo> {
+    let mut unifier = Unifier::default();
+    generate_constraints(module, &mut unifier);
+    unifier.resolve_calls(module);
+
+    // freeze the classes
+    let sites = unifier.sites_by_class();
+    let nodes: Vec<_> = unifier.ids.keys().cloned().collect();
+    let mut classes = BTreeMap::new();
+    let mut pointees = BTreeMap::new();
+    for node in nodes {
+        let class = unifier.class(&node);
+        if let Some(target) = unifier.pointee[class] {
+            let target = unifier.find(target);
+            pointees.insert(class, target);
+        }
+        classes.insert(node, class);
+    }
+    Ok(UnifiedPointsTo {
+        classes,
+        pointees,
+        sites,
+    })
+}
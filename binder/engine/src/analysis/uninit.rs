@@ -0,0 +1,484 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use crate::analysis::bounds::{accessed_pointer, Bounded, Tracker};
+use crate::analysis::dataflow::{solve_forward_with, Exhaustion, Findings, Widening};
+use crate::analysis::interval::{Bound, Interval};
+use crate::analysis::invariant::{analyze_intervals, IntervalStates};
+use crate::analysis::lattice::Lattice;
+use crate::analysis::libc::callee_model;
+use crate::analysis::memory::AllocSite;
+use crate::analysis::pointer::{Pointees, PointerStates};
+use crate::analysis::pointsto::{analyze_points_to, PointsTo};
+use crate::analysis::summary::SolverConfig;
+use crate::error::EngineResult;
+use crate::ir::bridge::cfg::{Block, ControlFlowGraph};
+use crate::ir::bridge::function::Function;
+use crate::ir::bridge::instruction::Instruction;
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::value::BlockLabel;
+use crate::ir::defuse::Site;
+
+/// Bound on the size of a stack object tracked byte by byte
+const MAX_TRACKED_BYTES: usize = 4096;
+
+/// Initialization state of a byte
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub enum InitState {
+    Bottom,
+    Initialized,
+    Uninitialized,
+    /// initialized on some executions only
+    Unknown,
+}
+
+impl Lattice for InitState {
+    fn bottom() -> Self {
+        Self::Bottom
+    }
+
+    fn top() -> Self {
+        Self::Unknown
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        self == other || matches!(self, Self::Bottom) || matches!(other, Self::Unknown)
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Bottom, v) | (v, Self::Bottom) => *v,
+            (lhs, rhs) if lhs == rhs => *lhs,
+            _ => Self::Unknown,
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Unknown, v) | (v, Self::Unknown) => *v,
+            (lhs, rhs) if lhs == rhs => *lhs,
+            _ => Self::Bottom,
+        }
+    }
+}
+
+impl Display for InitState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bottom => write!(f, "bottom"),
+            Self::Initialized => write!(f, "initialized"),
+            Self::Uninitialized => write!(f, "uninitialized"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Initialization states of the bytes of the tracked stack objects
+#[derive(Eq, PartialEq, Clone)]
+pub struct InitStore {
+    /// `None` for an unreachable state, where an absent object is not tracked
+    objects: Option<BTreeMap<AllocSite, Vec<InitState>>>,
+}
+
+impl InitStore {
+    /// Bytes of an object, if tracked
+    pub fn get(&self, object: &AllocSite) -> Option<&[InitState]> {
+        self.objects
+            .as_ref()?
+            .get(object)
+            .map(|bytes| bytes.as_slice())
+    }
+
+    /// Start tracking a fresh object with all its bytes uninitialized
+    fn allocate(&mut self, object: AllocSite, size: usize) {
+        if let Some(objects) = self.objects.as_mut() {
+            objects.insert(object, vec![InitState::Uninitialized; size]);
+        }
+    }
+
+    /// Mark a byte range of an object as initialized, for sure or on some executions only
+    fn initialize(&mut self, object: &AllocSite, lo: usize, hi: usize, strong: bool) {
+        let bytes = match self
+            .objects
+            .as_mut()
+            .and_then(|objects| objects.get_mut(object))
+        {
+            None => return,
+            Some(bytes) => bytes,
+        };
+        let hi = hi.min(bytes.len());
+        for byte in bytes.iter_mut().take(hi).skip(lo) {
+            *byte = if strong {
+                InitState::Initialized
+            } else {
+                byte.join(&InitState::Initialized)
+            };
+        }
+    }
+
+    /// Combine two reachable stores bytewise on the objects tracked in both
+    fn combine(&self, other: &Self, f: impl Fn(&InitState, &InitState) -> InitState) -> Self {
+        let (lhs, rhs) = match (&self.objects, &other.objects) {
+            (None, _) => return other.clone(),
+            (_, None) => return self.clone(),
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+        };
+        let objects = lhs
+            .iter()
+            .filter_map(|(k, v)| {
+                rhs.get(k).filter(|w| w.len() == v.len()).map(|w| {
+                    let bytes = v.iter().zip(w).map(|(a, b)| f(a, b)).collect();
+                    (k.clone(), bytes)
+                })
+            })
+            .collect();
+        Self {
+            objects: Some(objects),
+        }
+    }
+}
+
+impl Lattice for InitStore {
+    fn bottom() -> Self {
+        Self { objects: None }
+    }
+
+    fn top() -> Self {
+        Self {
+            objects: Some(BTreeMap::new()),
+        }
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        match (&self.objects, &other.objects) {
+            (None, _) => true,
+            (_, None) => false,
+            (Some(lhs), Some(rhs)) => rhs.iter().all(|(k, w)| {
+                lhs.get(k)
+                    .is_some_and(|v| v.len() == w.len() && v.iter().zip(w).all(|(a, b)| a.leq(b)))
+            }),
+        }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        self.combine(other, InitState::join)
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        let (lhs, rhs) = match (&self.objects, &other.objects) {
+            (None, _) | (_, None) => return Self::bottom(),
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+        };
+        let mut objects = lhs.clone();
+        for (k, w) in rhs {
+            let bytes = match objects.get(k) {
+                None => w.clone(),
+                Some(v) if v.len() == w.len() => v.iter().zip(w).map(|(a, b)| a.meet(b)).collect(),
+                Some(v) => v.clone(),
+            };
+            if bytes.iter().any(|byte| byte.is_bottom()) {
+                return Self::bottom();
+            }
+            objects.insert(k.clone(), bytes);
+        }
+        Self {
+            objects: Some(objects),
+        }
+    }
+}
+
+/// Certainty of an uninitialized read
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub enum UninitKind {
+    /// every byte read is uninitialized
+    Definite,
+    /// some byte read may be uninitialized
+    Possible,
+}
+
+impl Display for UninitKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Definite => write!(f, "definite"),
+            Self::Possible => write!(f, "possible"),
+        }
+    }
+}
+
+/// A load from a stack slot that may not be initialized
+pub struct UninitializedRead {
+    pub function: Identifier,
+    pub site: Site,
+    /// stack object read
+    pub object: AllocSite,
+    /// byte offset of the load from the start of the object
+    pub offset: Interval,
+    /// number of bytes read
+    pub width: usize,
+    pub kind: UninitKind,
+}
+
+impl Display for UninitializedRead {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "@{}:{}: load of {} bytes at offset {} of {} reads uninitialized memory ({})",
+            self.function, self.site, self.width, self.offset, self.object, self.kind
+        )
+    }
+}
+
+/// Utility: the byte range an access may touch in its object, and whether it is exact
+fn span(known: &Bounded, width: usize) -> Option<(usize, usize, bool)> {
+    let size = known.exact_size()?;
+    let (lo, hi) = match known.offset.bounds() {
+        None => return None,
+        Some((Bound::Finite(lo), Bound::Finite(hi))) => (
+            lo.to_isize().unwrap_or(isize::MIN),
+            hi.to_isize().unwrap_or(isize::MAX),
+        ),
+        // an unbounded offset may touch any byte
+        Some(_) => return Some((0, size, false)),
+    };
+    let start = lo.max(0) as usize;
+    let end = hi.saturating_add(width as isize).clamp(0, size as isize) as usize;
+    if start >= end {
+        return None;
+    }
+    Some((start, end, lo == hi))
+}
+
+/// Initialization analysis of the stack objects of a function
+struct Analysis<'a> {
+    module: &'a Module,
+    function: &'a Identifier,
+    cfg: &'a ControlFlowGraph,
+    widening: &'a Widening,
+    pointers: PointerStates<'a>,
+    /// sizes of the tracked stack objects
+    sizes: BTreeMap<AllocSite, usize>,
+    /// loads and stores, with their widths and the tracked objects their pointers are based on
+    accesses: BTreeMap<Site, (usize, Option<Bounded>)>,
+    /// the budget that ran out in the interval analysis behind the accesses
+    exhausted: Option<Exhaustion>,
+}
+
+impl<'a> Analysis<'a> {
+    fn new(
+        module: &'a Module,
+        global: &'a PointsTo,
+        func: &'a Function,
+        cfg: &'a ControlFlowGraph,
+        states: &IntervalStates,
+        widening: &'a Widening,
+    ) -> EngineResult<Self> {
+        let mut tracker = Tracker::build(module, func, cfg, states)?;
+        let mut sizes = BTreeMap::new();
+        let mut accesses = BTreeMap::new();
+        for (label, block) in cfg.blocks() {
+            for (index, inst) in block.instructions().iter().enumerate() {
+                let site = Site::Instruction {
+                    block: *label,
+                    index,
+                };
+                if let Instruction::Alloca { result, .. } = inst {
+                    if let Some(known) = tracker.register(*result)? {
+                        if let Some(size) = known.exact_size().filter(|n| *n <= MAX_TRACKED_BYTES) {
+                            sizes.insert(known.object, size);
+                        }
+                    }
+                    continue;
+                }
+                if let Some((_, pointer, width)) = accessed_pointer(module.layout(), inst)? {
+                    let known = tracker.pointer(pointer)?;
+                    accesses.insert(site, (width, known));
+                }
+            }
+        }
+        // only the stack objects are tracked
+        for (_, known) in accesses.values_mut() {
+            if known
+                .as_ref()
+                .is_some_and(|k| !sizes.contains_key(&k.object))
+            {
+                *known = None;
+            }
+        }
+
+        Ok(Self {
+            module,
+            function: &func.name,
+            cfg,
+            widening,
+            pointers: PointerStates::new(module, global, func, cfg, widening),
+            sizes,
+            accesses,
+            exhausted: tracker.exhausted(),
+        })
+    }
+
+    /// Tracked objects a pointer may point to, all of them if the pointer is unknown
+    fn objects<'p>(&'p self, target: &'p Pointees) -> Vec<&'p AllocSite> {
+        if target.unknown {
+            self.sizes.keys().collect()
+        } else {
+            target
+                .sites
+                .iter()
+                .filter(|site| self.sizes.contains_key(*site))
+                .collect()
+        }
+    }
+
+    /// Check a load against the bytes it reads
+    fn check(&self, state: &InitStore, known: &Bounded, width: usize) -> Option<UninitKind> {
+        let bytes = state.get(&known.object)?;
+        let (lo, hi, _) = span(known, width)?;
+        let read = &bytes[lo..hi.min(bytes.len())];
+        if read.iter().all(|byte| *byte == InitState::Initialized) {
+            return None;
+        }
+        let kind = if read.iter().all(|byte| *byte == InitState::Uninitialized) {
+            UninitKind::Definite
+        } else {
+            UninitKind::Possible
+        };
+        Some(kind)
+    }
+
+    /// Apply the instructions of a block, collecting the uninitialized reads if asked
+    fn block(
+        &self,
+        label: &BlockLabel,
+        block: &Block,
+        state: &InitStore,
+        mut report: Option<&mut Vec<UninitializedRead>>,
+    ) -> InitStore {
+        let mut state = state.clone();
+        let mut pointers = self.pointers.at_entry(label);
+        for (index, inst) in block.instructions().iter().enumerate() {
+            let site = Site::Instruction {
+                block: *label,
+                index,
+            };
+            match inst {
+                Instruction::Alloca { .. } => {
+                    let object = AllocSite::Stack {
+                        function: self.function.clone(),
+                        site,
+                    };
+                    if let Some(size) = self.sizes.get(&object) {
+                        state.allocate(object, *size);
+                    }
+                }
+                Instruction::Load { .. } => {
+                    if let (Some((width, Some(known))), Some(findings)) =
+                        (self.accesses.get(&site), report.as_deref_mut())
+                    {
+                        if let Some(kind) = self.check(&state, known, *width) {
+                            findings.push(UninitializedRead {
+                                function: self.function.clone(),
+                                site,
+                                object: known.object.clone(),
+                                offset: known.offset.clone(),
+                                width: *width,
+                                kind,
+                            });
+                        }
+                    }
+                }
+                Instruction::Store { pointer, .. } => match self.accesses.get(&site) {
+                    Some((width, Some(known))) => {
+                        if let Some((lo, hi, exact)) = span(known, *width) {
+                            state.initialize(&known.object, lo, hi, exact);
+                        }
+                    }
+                    Some((width, None)) => {
+                        // a store covering a unique object initializes all of it
+                        let target = self.pointers.eval(&pointers, pointer);
+                        let objects = self.objects(&target);
+                        let unique = !target.unknown && objects.len() == 1;
+                        for object in objects {
+                            let size = self.sizes[object];
+                            state.initialize(object, 0, size, unique && *width >= size);
+                        }
+                    }
+                    None => (),
+                },
+                // callees writing memory are assumed to initialize the memory passed to them,
+                // library functions only the pointees of the arguments they write
+                Instruction::CallDirect { function, .. }
+                    if !self.module.effect(function.as_ref()).writes_memory() => {}
+                Instruction::CallDirect { args, .. } | Instruction::CallIndirect { args, .. } => {
+                    let model = match inst {
+                        Instruction::CallDirect { function, .. } => {
+                            callee_model(self.module, function.as_ref())
+                        }
+                        _ => None,
+                    };
+                    for (index, arg) in args.iter().enumerate() {
+                        if model.is_some_and(|model| !model.writes.contains(&index)) {
+                            continue;
+                        }
+                        let target = self.pointers.eval(&pointers, arg);
+                        if target.unknown {
+                            continue;
+                        }
+                        for object in self.objects(&target) {
+                            state.initialize(object, 0, self.sizes[object], true);
+                        }
+                    }
+                }
+                _ => (),
+            }
+            self.pointers.step(&mut pointers, site, inst);
+        }
+        state
+    }
+
+    /// Solve the initialization states, then replay them to collect the uninitialized reads
+    fn run(&self, findings: &mut Findings<UninitializedRead>) {
+        let states = solve_forward_with(
+            self.cfg,
+            InitStore::top(),
+            self.widening,
+            |label, block, state| self.block(label, block, state, None),
+            |_, _, _, _, state| state.clone(),
+        );
+
+        let exhausted = self.exhausted.or(self.pointers.exhausted());
+        findings.record(self.function, exhausted.or(states.exhausted()));
+        for label in states.reachable() {
+            let block = self
+                .cfg
+                .get_block_by_label(label)
+                .expect("block label points to a valid node");
+            self.block(
+                label,
+                block,
+                &states.at_entry(label),
+                Some(&mut findings.items),
+            );
+        }
+    }
+}
+
+/// Find the loads from stack objects that may not be initialized, tracked byte by byte
+pub fn check_uninitialized_reads(
+    module: &Module,
+    solver: &SolverConfig,
+) -> EngineResult<Findings<UninitializedRead>> {
+    let global = analyze_points_to(module)?;
+    let intervals = analyze_intervals(module, solver);
+    let mut findings = Findings::new();
+    for (name, func) in module.functions() {
+        let (cfg, states) = match (&func.body, intervals.get(name)) {
+            (Some(cfg), Some(states)) => (cfg, states),
+            _ => continue,
+        };
+        let analysis = Analysis::new(module, &global, func, cfg, states, &solver.widening)?;
+        analysis.run(&mut findings);
+    }
+    Ok(findings)
+}
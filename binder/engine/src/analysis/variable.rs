@@ -0,0 +1,68 @@
+//! Variables and scalar values as seen by the analyses, independent of any domain.
+
+use std::fmt::{Display, Formatter};
+
+use rug::Integer;
+
+use crate::ir::bridge::constant::Constant;
+use crate::ir::bridge::typing::{NumRepr, Type};
+use crate::ir::bridge::value::{ArgumentSlot, RegisterSlot, Value};
+
+/// An argument or a register of a function, or the value it returns in a summary
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Copy)]
+pub enum Variable {
+    Argument(ArgumentSlot),
+    Register(RegisterSlot),
+    Return,
+}
+
+impl Variable {
+    /// The variable of a non-constant value
+    pub fn of_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Constant(_) => None,
+            Value::Argument { index, ty: _ } => Some(Self::Argument(*index)),
+            Value::Register { index, ty: _ } => Some(Self::Register(*index)),
+        }
+    }
+}
+
+impl Display for Variable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Argument(slot) => write!(f, "%a{}", slot.index()),
+            Self::Register(slot) => write!(f, "%r{}", slot.index()),
+            Self::Return => write!(f, "%ret"),
+        }
+    }
+}
+
+/// Width of a scalar integer type
+pub fn int_type_bits(ty: &Type) -> Option<usize> {
+    match ty {
+        Type::Bitvec {
+            bits,
+            number: NumRepr::Int,
+            length: None,
+        } => Some(*bits),
+        _ => None,
+    }
+}
+
+/// Width of a scalar integer value
+pub fn int_bits(value: &Value) -> Option<usize> {
+    match value {
+        Value::Constant(Constant::NumOne { bits, value: _ }) => Some(*bits),
+        Value::Constant(_) => None,
+        Value::Argument { index: _, ty } | Value::Register { index: _, ty } => int_type_bits(ty),
+    }
+}
+
+/// The value of a constant bitvector as held by the domains, given its unsigned value
+pub fn signed_reading(bits: usize, value: &Integer) -> Integer {
+    if bits > 1 && value.get_bit(bits as u32 - 1) {
+        value.clone() - (Integer::from(1) << bits as u32)
+    } else {
+        value.clone()
+    }
+}
@@ -0,0 +1,444 @@
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{Display, Formatter};
+
+use rug::Integer;
+use serde::Deserialize;
+
+use crate::analysis::dataflow::Findings;
+use crate::analysis::interval::eval_value;
+use crate::analysis::invariant::{analyze_intervals, Env};
+use crate::analysis::lattice::Lattice;
+use crate::analysis::summary::SolverConfig;
+use crate::analysis::variable::int_bits;
+use crate::error::EngineResult;
+use crate::ir::bridge::cfg::{ControlFlowGraph, Edge};
+use crate::ir::bridge::constant::{Constant, NumValue};
+use crate::ir::bridge::instruction::{
+    BinaryOpBitwise, ComparePredicate, ContractKind, Instruction, Terminator,
+};
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::typing::NumRepr;
+use crate::ir::bridge::value::{BlockLabel, Value};
+use crate::ir::defuse::{DefUse, Site, Statement};
+
+/// Functions that `assert()` calls on failure in common C libraries
+static ASSERT_FAIL_FUNCTIONS: [&str; 4] = ["__assert_fail", "__assert_rtn", "_assert", "__assert"];
+
+/// Origin of a proof obligation
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub enum Obligation {
+    /// `LIBRA_ASSERT(c)`: the condition holds
+    Contract,
+    /// `assert(c)`: the failure handler is never called
+    LibcAssert,
+}
+
+impl Display for Obligation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Contract => write!(f, "LIBRA_ASSERT"),
+            Self::LibcAssert => write!(f, "assert"),
+        }
+    }
+}
+
+/// Abstract domain used to discharge the proof obligations
+#[derive(Deserialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Domain {
+    /// constants propagated through the definitions of the condition
+    Constant,
+    /// intervals of the integer registers, refined by branches and `LIBRA_ASSUME(c)`
+    Interval,
+}
+
+impl Domain {
+    /// All domains, tried in order until one settles the condition
+    pub const ALL: [Domain; 2] = [Domain::Constant, Domain::Interval];
+}
+
+impl Display for Domain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Constant => write!(f, "constant"),
+            Self::Interval => write!(f, "interval"),
+        }
+    }
+}
+
+/// Outcome of discharging a proof obligation
+pub enum Verdict {
+    /// the assertion holds on all executions
+    Proved,
+    /// the assertion fails on the path (a sequence of blocks from the entry)
+    Violated(Vec<BlockLabel>),
+    /// neither can be established
+    Unknown,
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Proved => write!(f, "proved"),
+            Self::Violated(trace) => {
+                let repr: Vec<_> = trace.iter().map(|l| l.to_string()).collect();
+                write!(f, "violated via {}", repr.join(" -> "))
+            }
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// A user assertion along with its verdict
+pub struct AssertionReport {
+    /// function holding the assertion
+    pub function: Identifier,
+    /// location of the assertion
+    pub site: Site,
+    /// kind of the assertion
+    pub obligation: Obligation,
+    /// outcome of the verification
+    pub verdict: Verdict,
+}
+
+impl Display for AssertionReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "@{} {}: {} {}",
+            self.function, self.site, self.obligation, self.verdict
+        )
+    }
+}
+
+/// Evaluator of values that are constant within a function
+struct Evaluator<'a> {
+    cfg: &'a ControlFlowGraph,
+    defuse: DefUse,
+}
+
+impl Evaluator<'_> {
+    /// Bit width and (unsigned) value of an integer, if constant
+    fn integer(&self, value: &Value, depth: usize) -> Option<(usize, Integer)> {
+        if depth == 0 {
+            return None;
+        }
+        let inst = match value {
+            Value::Constant(Constant::NumOne {
+                bits,
+                value: NumValue::Int(v),
+            }) => return Some((*bits, v.clone())),
+            Value::Constant(_) | Value::Argument { .. } => return None,
+            Value::Register { index, ty: _ } => {
+                let site = self.defuse.definition(index)?;
+                match DefUse::resolve(self.cfg, &site)? {
+                    Statement::Instruction(inst) => inst,
+                    Statement::Terminator(_) => return None,
+                }
+            }
+        };
+        match inst {
+            Instruction::CompareBitvec {
+                bits: _,
+                number: NumRepr::Int,
+                length: None,
+                predicate,
+                lhs,
+                rhs,
+                result: _,
+            } => {
+                let (bits, l) = self.integer(lhs, depth - 1)?;
+                let (_, r) = self.integer(rhs, depth - 1)?;
+                // signedness is not kept, so the result is only known if both readings agree
+                let unsigned = compare(predicate, &l, &r);
+                let signed = compare(predicate, &to_signed(&l, bits), &to_signed(&r, bits));
+                (unsigned == signed).then(|| (1, Integer::from(unsigned as u8)))
+            }
+            Instruction::CastBitvecSize {
+                bits_from,
+                bits_into,
+                number: NumRepr::Int,
+                length: None,
+                operand,
+                result: _,
+            } => {
+                let (_, v) = self.integer(operand, depth - 1)?;
+                if bits_into < bits_from {
+                    Some((*bits_into, v.keep_bits(*bits_into as u32)))
+                } else if !v.get_bit((*bits_from - 1) as u32) {
+                    // zero- and sign-extension agree
+                    Some((*bits_into, v))
+                } else {
+                    None
+                }
+            }
+            Instruction::BinaryBitwise {
+                bits,
+                length: None,
+                opcode,
+                lhs,
+                rhs,
+                result: _,
+            } => {
+                let (_, l) = self.integer(lhs, depth - 1)?;
+                let (_, r) = self.integer(rhs, depth - 1)?;
+                let v = match opcode {
+                    BinaryOpBitwise::And => l & r,
+                    BinaryOpBitwise::Or => l | r,
+                    BinaryOpBitwise::Xor => l ^ r,
+                };
+                Some((*bits, v))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether an integer is nonzero, if known
+    fn truth(&self, value: &Value, depth: usize) -> Option<bool> {
+        if let Some((_, v)) = self.integer(value, depth) {
+            return Some(v != 0);
+        }
+        if depth == 0 {
+            return None;
+        }
+
+        // extensions preserve the truth of the operand regardless of signedness
+        let index = match value {
+            Value::Register { index, ty: _ } => index,
+            Value::Constant(_) | Value::Argument { .. } => return None,
+        };
+        let site = self.defuse.definition(index)?;
+        match DefUse::resolve(self.cfg, &site)? {
+            Statement::Instruction(Instruction::CastBitvecSize {
+                bits_from,
+                bits_into,
+                number: NumRepr::Int,
+                length: None,
+                operand,
+                result: _,
+            }) if bits_into > bits_from => self.truth(operand, depth - 1),
+            _ => None,
+        }
+    }
+
+    /// The operand under the extensions a value results from, which has the same truth
+    fn unextended<'v>(&'v self, value: &'v Value, depth: usize) -> &'v Value {
+        let index = match value {
+            Value::Register { index, ty: _ } if depth != 0 => index,
+            _ => return value,
+        };
+        let site = match self.defuse.definition(index) {
+            None => return value,
+            Some(site) => site,
+        };
+        match DefUse::resolve(self.cfg, &site) {
+            Some(Statement::Instruction(Instruction::CastBitvecSize {
+                bits_from,
+                bits_into,
+                number: NumRepr::Int,
+                length: None,
+                operand,
+                result: _,
+            })) if bits_into > bits_from => self.unextended(operand, depth - 1),
+            _ => value,
+        }
+    }
+
+    /// Successors that may be taken, and whether the one taken is determined
+    fn successors(&self, label: &BlockLabel) -> (Vec<BlockLabel>, bool) {
+        let succs = self.cfg.successors(label);
+        let block = match self.cfg.get_block_by_label(label) {
+            None => return (vec![], false),
+            Some(block) => block,
+        };
+        match block.terminator() {
+            Terminator::Goto { target } => (vec![*target], true),
+            Terminator::Branch { cond, .. } => match self.truth(cond, EVAL_DEPTH) {
+                None => (succs.into_iter().map(|(l, _)| l).collect(), false),
+                Some(taken) => (
+                    succs
+                        .into_iter()
+                        .filter(|(_, edge)| matches!(edge, Edge::Branch(b) if *b == taken))
+                        .map(|(l, _)| l)
+                        .collect(),
+                    true,
+                ),
+            },
+            _ => (succs.into_iter().map(|(l, _)| l).collect(), false),
+        }
+    }
+
+    /// Blocks reachable from the entry, either along any feasible edges (may) or along
+    /// determined edges only (must), with the BFS predecessors for trace reconstruction
+    fn reachability(&self, must: bool) -> BTreeMap<BlockLabel, Option<BlockLabel>> {
+        let entry = self.cfg.entry();
+        let mut parents = BTreeMap::from([(entry, None)]);
+        let mut queue = VecDeque::from([entry]);
+        while let Some(label) = queue.pop_front() {
+            let (succs, determined) = self.successors(&label);
+            if must && !determined {
+                continue;
+            }
+            for next in succs {
+                if let Entry::Vacant(e) = parents.entry(next) {
+                    e.insert(Some(label));
+                    queue.push_back(next);
+                }
+            }
+        }
+        parents
+    }
+}
+
+/// Bound on the depth of constant evaluation through definitions
+const EVAL_DEPTH: usize = 16;
+
+/// Utility: compare two integers under the predicate
+fn compare(predicate: &ComparePredicate, lhs: &Integer, rhs: &Integer) -> bool {
+    match predicate {
+        ComparePredicate::EQ => lhs == rhs,
+        ComparePredicate::NE => lhs != rhs,
+        ComparePredicate::GT => lhs > rhs,
+        ComparePredicate::GE => lhs >= rhs,
+        ComparePredicate::LT => lhs < rhs,
+        ComparePredicate::LE => lhs <= rhs,
+    }
+}
+
+/// Utility: the signed reading of an unsigned integer of the bit width
+fn to_signed(value: &Integer, bits: usize) -> Integer {
+    if bits > 0 && value.get_bit((bits - 1) as u32) {
+        value.clone() - (Integer::from(1) << (bits as u32))
+    } else {
+        value.clone()
+    }
+}
+
+/// Utility: whether a condition is nonzero in the interval state, if known
+fn interval_truth(env: &Env, cond: &Value) -> Option<bool> {
+    let bits = int_bits(cond)?;
+    let value = eval_value(cond, bits, &|v| env.get(v));
+    let zero = Integer::new();
+    if value.is_bottom() {
+        None
+    } else if !value.contains(&zero) {
+        Some(true)
+    } else if value.as_singleton() == Some(&zero) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Utility: whether a condition is nonzero in every state, if they all agree
+fn disjunct_truth(states: &[Env], cond: &Value) -> Option<bool> {
+    let mut truths = states.iter().map(|env| interval_truth(env, cond));
+    let first = truths.next()??;
+    truths.all(|truth| truth == Some(first)).then_some(first)
+}
+
+/// Utility: the path from the entry to the block along the BFS predecessors
+fn trace(
+    parents: &BTreeMap<BlockLabel, Option<BlockLabel>>,
+    target: BlockLabel,
+) -> Vec<BlockLabel> {
+    let mut path = vec![target];
+    let mut cursor = target;
+    while let Some(Some(prev)) = parents.get(&cursor) {
+        path.push(*prev);
+        cursor = *prev;
+    }
+    path.reverse();
+    path
+}
+
+/// Treat the user assertions in the module as proof obligations and discharge them
+pub fn verify_assertions(module: &Module) -> EngineResult<Findings<AssertionReport>> {
+    verify_assertions_with(module, &Domain::ALL, &SolverConfig::default())
+}
+
+/// Discharge the user assertions in the module with the given domains only
+pub fn verify_assertions_with(
+    module: &Module,
+    domains: &[Domain],
+    solver: &SolverConfig,
+) -> EngineResult<Findings<AssertionReport>> {
+    let intervals = domains
+        .contains(&Domain::Interval)
+        .then(|| analyze_intervals(module, solver));
+    let mut reports = Findings::new();
+    for (name, func) in module.functions() {
+        let cfg = match &func.body {
+            None => continue,
+            Some(cfg) => cfg,
+        };
+        let eval = Evaluator {
+            cfg,
+            defuse: DefUse::build(func)?,
+        };
+        let may = eval.reachability(false);
+        let must = eval.reachability(true);
+        let intervals = intervals.as_ref().and_then(|states| states.get(name));
+        if let Some(states) = intervals {
+            reports.record(name, states.exhausted());
+        }
+
+        for (label, block) in cfg.blocks() {
+            for (index, inst) in block.instructions().iter().enumerate() {
+                let (obligation, cond) = match inst {
+                    Instruction::Contract {
+                        kind: ContractKind::Assert,
+                        cond,
+                    } => (Obligation::Contract, Some(cond)),
+                    Instruction::CallDirect { function, .. }
+                        if ASSERT_FAIL_FUNCTIONS.contains(&function.as_ref()) =>
+                    {
+                        (Obligation::LibcAssert, None)
+                    }
+                    _ => continue,
+                };
+
+                let site = Site::Instruction {
+                    block: *label,
+                    index,
+                };
+                let state = intervals.map(|states| states.get(&site));
+
+                // unreachable assertions hold vacuously, including those cut off by assumptions
+                let verdict = if !may.contains_key(label) || matches!(state, Some(None)) {
+                    Verdict::Proved
+                } else {
+                    let truth = |c: &Value| {
+                        domains.iter().find_map(|domain| match domain {
+                            Domain::Constant => eval.truth(c, EVAL_DEPTH),
+                            Domain::Interval => disjunct_truth(
+                                intervals?.disjuncts(&site),
+                                eval.unextended(c, EVAL_DEPTH),
+                            ),
+                        })
+                    };
+                    match cond.map(truth) {
+                        // a failure handler that is definitely reached
+                        None if must.contains_key(label) => Verdict::Violated(trace(&must, *label)),
+                        None => Verdict::Unknown,
+                        Some(Some(true)) => Verdict::Proved,
+                        // a false condition is only a violation where the site is surely reached
+                        Some(Some(false)) if must.contains_key(label) => {
+                            Verdict::Violated(trace(&must, *label))
+                        }
+                        Some(Some(false)) | Some(None) => Verdict::Unknown,
+                    }
+                };
+                reports.push(AssertionReport {
+                    function: name.clone(),
+                    site,
+                    obligation,
+                    verdict,
+                });
+            }
+        }
+    }
+    Ok(reports)
+}
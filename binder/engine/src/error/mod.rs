@@ -1,8 +1,10 @@
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 
+use serde::{Deserialize, Serialize};
+
 /// A list of operations not supported
-#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Unsupported {
     ModuleLevelAssembly,
     InlineAssembly,
@@ -34,6 +36,7 @@ pub enum Unsupported {
     AtomicInstruction,
     WindowsEH,
     MetadataSystem,
+    LossyLowering,
 }
 
 impl Display for Unsupported {
@@ -129,6 +132,9 @@ impl Display for Unsupported {
             Self::MetadataSystem => {
                 write!(f, "metadata system")
             }
+            Self::LossyLowering => {
+                write!(f, "lowering of information dropped in conversion")
+            }
         }
     }
 }
@@ -140,12 +146,16 @@ pub enum EngineError {
     CompilationError(String),
     /// Error during the loading of a compiled LLVM module
     LLVMLoadingError(String),
+    /// Crash of the oracle (i.e., the libra pass) on a compiled LLVM module
+    OracleCrash(String),
     /// Invalid assumption made about the program
     InvalidAssumption(String),
     /// Operation not supported yet
     NotSupportedYet(Unsupported),
     /// Invariant violation
     InvariantViolation(String),
+    /// An external tool (e.g., clang or opt) killed for exceeding its limits
+    ToolTimeout(String),
 }
 
 pub type EngineResult<T> = Result<T, EngineError>;
@@ -159,6 +169,9 @@ impl Display for EngineError {
             Self::LLVMLoadingError(msg) => {
                 write!(f, "[libra::loading] {}", msg)
             }
+            Self::OracleCrash(msg) => {
+                write!(f, "[libra::crash] {}", msg)
+            }
             Self::InvalidAssumption(msg) => {
                 write!(f, "[libra::assumption] {}", msg)
             }
@@ -168,8 +181,23 @@ impl Display for EngineError {
             Self::InvariantViolation(msg) => {
                 write!(f, "[libra::invariant] {}", msg)
             }
+            Self::ToolTimeout(msg) => {
+                write!(f, "[libra::timeout] {}", msg)
+            }
         }
     }
 }
 
 impl Error for EngineError {}
+
+impl EngineError {
+    /// Report a failure in running an external tool as a compilation error, unless the tool is
+    /// killed for exceeding its limits
+    pub fn from_tool(e: anyhow::Error, context: &str) -> Self {
+        match e.downcast::<Self>() {
+            Ok(err @ Self::ToolTimeout(_)) => err,
+            Ok(err) => Self::CompilationError(format!("{}: {}", context, err)),
+            Err(err) => Self::CompilationError(format!("{}: {}", context, err)),
+        }
+    }
+}
@@ -1,34 +1,171 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use libra_shared::compile_db::CompileDB;
 
 use crate::error::{EngineError, EngineResult};
+use crate::flow::manifest::Manifest;
 use crate::flow::shared::Context;
 
 /// Default flags to be sent to clang
-static PRESET_CLANG_FLAGS: [&str; 8] = [
+static PRESET_CLANG_FLAGS: [&str; 4] = [
     // attach debug symbol
     "-g",
+    // disable unsupported features
+    "-fno-vectorize",
+    // allow subsequent optimizations
+    "-Xclang",
+    "-disable-O0-optnone",
+];
+
+/// Flags for C sources
+static PRESET_C_FLAGS: [&str; 4] = [
     // targeting the C language
     "--language",
     "c",
     // feature selection
     "-std=gnu17",
     "-Wno-c2x-extensions",
-    // disable unsupported features
-    "-fno-vectorize",
-    // allow subsequent optimizations
-    "-Xclang",
-    "-disable-O0-optnone",
 ];
 
+/// Flags for C++ sources
+static PRESET_CXX_FLAGS: [&str; 3] = [
+    // targeting the C++ language
+    "--language",
+    "c++",
+    // feature selection
+    "-std=gnu++17",
+];
+
+/// Language of a source file
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum Language {
+    C,
+    Cxx,
+}
+
+impl Language {
+    fn flags(&self) -> &'static [&'static str] {
+        match self {
+            Self::C => &PRESET_C_FLAGS,
+            Self::Cxx => &PRESET_CXX_FLAGS,
+        }
+    }
+}
+
+/// A named preset of clang flags on top of the language flags
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub enum Profile {
+    /// the language flags only
+    #[default]
+    Default,
+    /// well-defined semantics for some undefined behaviors, as in hardened builds
+    Hardened,
+    /// optimized for code size in the frontend
+    Size,
+    /// strict ISO C11 for C sources
+    C11,
+    /// GNU C89 for legacy C sources
+    Gnu89,
+}
+
+/// Flags for the hardened profile
+static PROFILE_HARDENED_FLAGS: [&str; 3] = [
+    // no reads of uninitialized stack variables
+    "-ftrivial-auto-var-init=zero",
+    // signed integer overflow wraps around
+    "-fwrapv",
+    // null pointer checks survive dereferences
+    "-fno-delete-null-pointer-checks",
+];
+
+/// Flags for the size profile
+static PROFILE_SIZE_FLAGS: [&str; 2] = [
+    "-Os",
+    // keep vectorization disabled as in the default flags
+    "-fno-slp-vectorize",
+];
+
+impl Profile {
+    pub fn parse(name: &str) -> EngineResult<Self> {
+        let parsed = match name {
+            "default" => Self::Default,
+            "hardened" => Self::Hardened,
+            "size" => Self::Size,
+            "c11" => Self::C11,
+            "gnu89" => Self::Gnu89,
+            _ => {
+                return Err(EngineError::InvalidAssumption(format!(
+                    "unknown profile: {}",
+                    name
+                )));
+            }
+        };
+        Ok(parsed)
+    }
+
+    /// Flags of the profile for sources in the language, placed after the language flags
+    fn flags(&self, lang: Language) -> &'static [&'static str] {
+        match (self, lang) {
+            (Self::Default, _) => &[],
+            (Self::Hardened, _) => &PROFILE_HARDENED_FLAGS,
+            (Self::Size, _) => &PROFILE_SIZE_FLAGS,
+            (Self::C11, Language::C) => &["-std=c11"],
+            (Self::Gnu89, Language::C) => &["-std=gnu89"],
+            // the language standards only apply to C sources
+            (Self::C11 | Self::Gnu89, Language::Cxx) => &[],
+        }
+    }
+}
+
+/// Kind of an input file
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum InputKind {
+    /// a source file to be compiled
+    Source(Language),
+    /// LLVM bitcode or textual IR, linked as is
+    Bitcode,
+}
+
+impl InputKind {
+    /// Classify an input by its file extension
+    pub fn detect(path: &Path) -> EngineResult<Self> {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let kind = match ext {
+            "c" => Self::Source(Language::C),
+            "cc" | "cpp" | "cxx" | "c++" | "C" => Self::Source(Language::Cxx),
+            "bc" | "ll" => Self::Bitcode,
+            _ => {
+                return Err(EngineError::CompilationError(format!(
+                    "Unknown input kind: {}",
+                    path.to_string_lossy()
+                )));
+            }
+        };
+        Ok(kind)
+    }
+}
+
+/// Utility: report an error in maintaining the manifest
+fn manifest_err(e: anyhow::Error) -> EngineError {
+    EngineError::CompilationError(format!("Error in manifest: {}", e))
+}
+
 pub struct FlowBuildSimple<'a> {
     /// Context manager
     ctxt: &'a Context,
-    /// Source file
+    /// Source files, bitcode files, or textual IR files
     inputs: Vec<PathBuf>,
     /// Workspace for the analysis
     output: PathBuf,
     /// Flags (to be sent to Clang)
     flags: Vec<String>,
+    /// Symbols to keep externally visible after linking, `None` to internalize all
+    public_api: Option<Vec<String>>,
+    /// Preset of flags on top of the language flags
+    profile: Profile,
+    /// Where to write the compilation database of the sources, if requested
+    compile_db: Option<PathBuf>,
 }
 
 impl<'a> FlowBuildSimple<'a> {
@@ -48,35 +185,146 @@ impl<'a> FlowBuildSimple<'a> {
             inputs,
             output,
             flags: all_flags,
+            public_api: None,
+            profile: Profile::default(),
+            compile_db: None,
         }
     }
 
-    pub fn execute(self) -> EngineResult<PathBuf> {
+    /// Keep the listed symbols (e.g., the exported API of a library) externally visible
+    /// after linking, instead of assuming a standalone program
+    pub fn set_public_api(&mut self, symbols: Vec<String>) {
+        self.public_api = Some(symbols);
+    }
+
+    /// Compile the source files with a preset of flags, which the extra flags may still override
+    pub fn set_profile(&mut self, profile: Profile) {
+        self.profile = profile;
+    }
+
+    /// Write a compilation database describing how each source file is compiled to bitcode
+    pub fn set_compile_db(&mut self, path: PathBuf) {
+        self.compile_db = Some(path);
+    }
+
+    /// Compile each source file into a bitcode file in the workspace, keeping the bitcode and IR
+    /// inputs in place
+    fn compile(&self) -> EngineResult<(Manifest, Vec<PathBuf>)> {
         let Self {
             ctxt,
             inputs,
             output,
             flags,
+            public_api: _,
+            profile,
+            compile_db,
         } = self;
 
+        // provenance
+        let mut manifest = Manifest::open(ctxt, output).map_err(manifest_err)?;
+        for src in inputs {
+            manifest.record_input(src).map_err(manifest_err)?;
+        }
+        manifest.record_flags(flags);
+
         // compilation
         let mut init_bc_files = vec![];
+        let mut entries = vec![];
         for (i, src) in inputs.iter().enumerate() {
+            let lang = match InputKind::detect(src)? {
+                // pre-built inputs go to the linker directly
+                InputKind::Bitcode => {
+                    init_bc_files.push(src.clone());
+                    continue;
+                }
+                InputKind::Source(lang) => lang,
+            };
+            let timer = Instant::now();
             let bc_path = output.join(format!("init-{}.bc", i));
-            ctxt.compile_to_bitcode(src, &bc_path, flags.iter().map(|i| i.as_str()))
-                .map_err(|e| EngineError::CompilationError(format!("Error during clang: {}", e)))?;
+            // the language and profile flags come first so that the user flags may override them
+            let args: Vec<_> = lang
+                .flags()
+                .iter()
+                .chain(profile.flags(lang))
+                .copied()
+                .chain(flags.iter().map(|i| i.as_str()))
+                .collect();
+            if compile_db.is_some() {
+                let entry = ctxt
+                    .compile_entry(lang == Language::Cxx, src, &bc_path, &args)
+                    .map_err(|e| {
+                        EngineError::CompilationError(format!(
+                            "Error in compilation database: {}",
+                            e
+                        ))
+                    })?;
+                entries.push(entry);
+            }
+            match lang {
+                Language::C => ctxt.compile_to_bitcode(src, &bc_path, &args),
+                Language::Cxx => ctxt.compile_cxx_to_bitcode(src, &bc_path, &args),
+            }
+            .map_err(|e| EngineError::from_tool(e, "Error during clang"))?;
             ctxt.disassemble_in_place(&bc_path)
-                .map_err(|e| EngineError::CompilationError(format!("Error during disas: {}", e)))?;
+                .map_err(|e| EngineError::from_tool(e, "Error during disas"))?;
+            manifest
+                .record_step(&format!("compile-{}", i), &bc_path, timer.elapsed())
+                .map_err(manifest_err)?;
             init_bc_files.push(bc_path);
         }
 
+        // compilation database
+        if let Some(path) = compile_db {
+            CompileDB { entries }.save(path).map_err(|e| {
+                EngineError::CompilationError(format!("Error in compilation database: {}", e))
+            })?;
+        }
+        Ok((manifest, init_bc_files))
+    }
+
+    pub fn execute(self) -> EngineResult<PathBuf> {
+        let (mut manifest, init_bc_files) = self.compile()?;
+
         // linking
+        let timer = Instant::now();
         let path_refs: Vec<_> = init_bc_files.iter().map(|p| p.as_path()).collect();
-        let merged_bc_path = output.join("merged.bc");
-        ctxt.link_bitcode(&path_refs, &merged_bc_path)
-            .map_err(|e| EngineError::CompilationError(format!("Error during llvm-link: {}", e)))?;
+        let merged_bc_path = self.output.join("merged.bc");
+        match &self.public_api {
+            None => {
+                self.ctxt
+                    .link_bitcode(&path_refs, &merged_bc_path, true)
+                    .map_err(|e| EngineError::from_tool(e, "Error during llvm-link"))?;
+                manifest
+                    .record_step("link", &merged_bc_path, timer.elapsed())
+                    .map_err(manifest_err)?;
+            }
+            Some(symbols) => {
+                let linked_bc_path = self.output.join("linked.bc");
+                self.ctxt
+                    .link_bitcode(&path_refs, &linked_bc_path, false)
+                    .map_err(|e| EngineError::from_tool(e, "Error during llvm-link"))?;
+                manifest
+                    .record_step("link", &linked_bc_path, timer.elapsed())
+                    .map_err(manifest_err)?;
+
+                let timer = Instant::now();
+                self.ctxt
+                    .internalize(&linked_bc_path, &merged_bc_path, symbols)
+                    .map_err(|e| EngineError::from_tool(e, "Error during internalize"))?;
+                manifest
+                    .record_step("internalize", &merged_bc_path, timer.elapsed())
+                    .map_err(manifest_err)?;
+            }
+        }
 
         // return the merged bitcode file
         Ok(merged_bc_path)
     }
+
+    /// Compile each source file without linking, returning the bitcode file per translation unit
+    /// (the input itself for a bitcode or IR input)
+    pub fn execute_separate(self) -> EngineResult<Vec<(PathBuf, PathBuf)>> {
+        let (_, init_bc_files) = self.compile()?;
+        Ok(self.inputs.into_iter().zip(init_bc_files).collect())
+    }
 }
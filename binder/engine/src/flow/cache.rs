@@ -0,0 +1,110 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use tempfile::tempdir_in;
+
+use libra_shared::config::PATH_STUDIO;
+
+use crate::flow::manifest::{digest, fingerprint};
+use crate::flow::shared::{Context, SerialFormat};
+
+/// Name of the bitcode file in a cache entry
+static ENTRY_BITCODE: &str = "module.bc";
+
+/// Stem of the serialized module in a cache entry, with the extension of the format
+static ENTRY_SERIALIZED: &str = "module";
+
+/// Name of the file in a cache entry holding the full key of the entry
+static ENTRY_KEY: &str = "key";
+
+/// A content-addressed cache of the fixedpoint steps in the studio, where each entry holds the
+/// bitcode file and its serialized module derived from an input bitcode under a list of flags
+#[derive(Clone)]
+pub struct FixedpointCache {
+    /// directory of the entries, specific to the toolchain and the libra pass
+    root: PathBuf,
+    /// format of the serialized modules
+    format: SerialFormat,
+}
+
+impl FixedpointCache {
+    /// Open the cache for the toolchain and the libra pass of the context
+    pub fn open(ctxt: &Context) -> Result<Self> {
+        let toolchain = format!("{}\0{}", ctxt.llvm_version()?, ctxt.oracle_fingerprint()?);
+        let root = PATH_STUDIO
+            .join("cache")
+            .join("fixedpoint")
+            .join(digest(toolchain.as_bytes()));
+        fs::create_dir_all(&root)?;
+        Ok(Self {
+            root,
+            format: ctxt.serial_format(),
+        })
+    }
+
+    /// Utility: location of the serialized module next to a bitcode file
+    fn serialized(&self, path: &Path) -> PathBuf {
+        path.with_extension(self.format.extension())
+    }
+
+    /// Key of the entry derived from the input under the flags
+    fn key(&self, input: &Path, flags: &[&str]) -> Result<String> {
+        let mut key = fingerprint(input)?;
+        key.push('\0');
+        key.push_str(self.format.extension());
+        for flag in flags {
+            key.push('\0');
+            key.push_str(flag);
+        }
+        Ok(key)
+    }
+
+    /// Location of the entry under its key
+    fn entry(&self, key: &str) -> PathBuf {
+        self.root.join(digest(key.as_bytes()))
+    }
+
+    /// Restore the entry derived from the input under the flags into the output bitcode file and
+    /// its serialization next to it, yielding whether the entry is cached
+    pub fn restore(&self, input: &Path, flags: &[&str], output: &Path) -> Result<bool> {
+        let key = self.key(input, flags)?;
+        let entry = self.entry(&key);
+        if !entry.exists() {
+            return Ok(false);
+        }
+        // an entry under the same digest but a different key is a collision, not a hit
+        if fs::read_to_string(entry.join(ENTRY_KEY)).ok().as_ref() != Some(&key) {
+            return Ok(false);
+        }
+        // the serialization is copied last to be seen as up to date with the bitcode
+        fs::copy(entry.join(ENTRY_BITCODE), output)?;
+        fs::copy(
+            self.serialized(&entry.join(ENTRY_SERIALIZED)),
+            self.serialized(output),
+        )?;
+        Ok(true)
+    }
+
+    /// Store the output bitcode file and its serialization as the entry derived from the input
+    /// under the flags
+    pub fn store(&self, input: &Path, flags: &[&str], output: &Path) -> Result<()> {
+        let key = self.key(input, flags)?;
+        let entry = self.entry(&key);
+        if entry.exists() {
+            return Ok(());
+        }
+
+        // populate the entry aside and move it in place, as concurrent runs may share the cache,
+        // in which case losing the race is fine as the entries are identical
+        let scratch = tempdir_in(&self.root)?;
+        fs::write(scratch.path().join(ENTRY_KEY), &key)?;
+        fs::copy(output, scratch.path().join(ENTRY_BITCODE))?;
+        fs::copy(
+            self.serialized(output),
+            self.serialized(&scratch.path().join(ENTRY_SERIALIZED)),
+        )?;
+        let _ = fs::rename(scratch.path(), &entry);
+        Ok(())
+    }
+}
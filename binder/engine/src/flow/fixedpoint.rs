@@ -1,11 +1,183 @@
-use std::path::PathBuf;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
-use log::debug;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
 
 use crate::error::EngineError;
 use crate::error::EngineResult;
+use crate::flow::cache::FixedpointCache;
+use crate::flow::manifest::Manifest;
 use crate::flow::shared::Context;
 use crate::ir::bridge;
+use crate::ir::diff::ModuleDiff;
+
+/// Optimization levels of the default opt pipelines
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum OptLevel {
+    O1,
+    O2,
+    O3,
+    Os,
+    Oz,
+}
+
+impl OptLevel {
+    pub fn parse(name: &str) -> EngineResult<Self> {
+        let parsed = match name {
+            "O1" => Self::O1,
+            "O2" => Self::O2,
+            "O3" => Self::O3,
+            "Os" => Self::Os,
+            "Oz" => Self::Oz,
+            _ => {
+                return Err(EngineError::InvalidAssumption(format!(
+                    "unknown optimization level: {}",
+                    name
+                )));
+            }
+        };
+        Ok(parsed)
+    }
+
+    /// The default opt pipeline at this level
+    pub fn pipeline(&self) -> String {
+        format!("default<{}>", self)
+    }
+}
+
+impl Display for OptLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::O1 => write!(f, "O1"),
+            Self::O2 => write!(f, "O2"),
+            Self::O3 => write!(f, "O3"),
+            Self::Os => write!(f, "Os"),
+            Self::Oz => write!(f, "Oz"),
+        }
+    }
+}
+
+/// Maximum number of changed functions named in a round summary
+static MAX_FUNCTIONS_SHOWN: usize = 8;
+
+/// Changes made by a fixedpoint round
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+pub struct RoundSummary {
+    /// step produced by the round
+    pub step: usize,
+    /// opt pipeline applied
+    pub pipeline: String,
+    /// number of global variables changed
+    pub globals: usize,
+    /// functions added, removed, or changed
+    pub functions: Vec<String>,
+    /// number of instructions added
+    pub added: usize,
+    /// number of instructions removed
+    pub removed: usize,
+}
+
+impl RoundSummary {
+    fn new(step: usize, pipeline: &str, diff: Option<&ModuleDiff>) -> Self {
+        let (functions, (added, removed)) = match diff {
+            None => (vec![], (0, 0)),
+            Some(diff) => (
+                diff.function_names()
+                    .into_iter()
+                    .map(|name| name.to_string())
+                    .collect(),
+                diff.instruction_delta(),
+            ),
+        };
+        Self {
+            step,
+            pipeline: pipeline.to_string(),
+            globals: diff.map_or(0, |diff| diff.globals.len()),
+            functions,
+            added,
+            removed,
+        }
+    }
+}
+
+impl Display for RoundSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] {}: {} globals changed, {} functions changed",
+            self.step,
+            self.pipeline,
+            self.globals,
+            self.functions.len()
+        )?;
+        if !self.functions.is_empty() {
+            let shown: Vec<_> = self
+                .functions
+                .iter()
+                .take(MAX_FUNCTIONS_SHOWN)
+                .map(|name| format!("@{}", name))
+                .collect();
+            write!(f, " ({}", shown.join(", "))?;
+            if self.functions.len() > MAX_FUNCTIONS_SHOWN {
+                write!(f, ", ...")?;
+            }
+            write!(f, ")")?;
+        }
+        write!(
+            f,
+            ", {} instructions added, {} removed",
+            self.added, self.removed
+        )
+    }
+}
+
+/// Opt pipelines of the fixedpoint rounds: the prelude ones for the first rounds in order, and
+/// the cycle ones round-robin afterwards
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+pub struct Schedule {
+    /// pipelines of the first rounds
+    prelude: Vec<String>,
+    /// pipelines of the remaining rounds, applied round-robin
+    cycle: Vec<String>,
+}
+
+impl Schedule {
+    /// Apply the pipelines round-robin in all rounds
+    pub fn round_robin(pipelines: Vec<String>) -> Self {
+        Self {
+            prelude: vec![],
+            cycle: pipelines,
+        }
+    }
+
+    /// Apply the levels to the first rounds in order, staying at the last level afterwards
+    /// (e.g., `[O1, O3]` for O1 in the first round and O3 in the others)
+    pub fn levels(levels: &[OptLevel]) -> Self {
+        let mut pipelines: Vec<_> = levels.iter().map(|level| level.pipeline()).collect();
+        let cycle = pipelines.pop().into_iter().collect();
+        Self {
+            prelude: pipelines,
+            cycle,
+        }
+    }
+
+    /// Pipeline of a round, counting from 0
+    fn pipeline(&self, round: usize) -> &str {
+        match self.prelude.get(round) {
+            Some(pipeline) => pipeline,
+            None => &self.cycle[(round - self.prelude.len()) % self.cycle.len()],
+        }
+    }
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self::levels(&[OptLevel::O3])
+    }
+}
 
 pub struct FlowFixedpoint<'a> {
     /// Context manager
@@ -16,6 +188,10 @@ pub struct FlowFixedpoint<'a> {
     output: PathBuf,
     /// Depth of loop (if set)
     depth: Option<usize>,
+    /// Opt pipelines of the rounds
+    schedule: Schedule,
+    /// Cache of the steps in the studio (if set)
+    cache: Option<FixedpointCache>,
 }
 
 /// Entrypoints
@@ -26,63 +202,282 @@ impl<'a> FlowFixedpoint<'a> {
             input,
             output,
             depth,
+            schedule: Schedule::default(),
+            cache: None,
         }
     }
 
+    /// Set the opt pipelines of the rounds
+    pub fn set_schedule(&mut self, schedule: Schedule) {
+        self.schedule = schedule;
+    }
+
+    /// Reuse the steps kept in the cache and populate it with the others
+    pub fn set_cache(&mut self, cache: FixedpointCache) {
+        self.cache = Some(cache);
+    }
+
     pub fn execute(self) -> EngineResult<Vec<bridge::module::Module>> {
+        self.execute_with_rounds().map(|(trace, _)| trace)
+    }
+
+    /// Run the optimization, also yielding the summary of each round
+    pub fn execute_with_rounds(
+        self,
+    ) -> EngineResult<(Vec<bridge::module::Module>, Vec<RoundSummary>)> {
         let Self {
             ctxt,
             input,
             output,
             depth,
+            schedule,
+            cache,
         } = self;
+        if schedule.cycle.is_empty() {
+            return Err(EngineError::InvalidAssumption(
+                "no opt pipelines for the fixedpoint rounds".to_string(),
+            ));
+        }
 
         // sanity checking
-        ctxt.opt_verify(&input).map_err(|e| {
-            EngineError::CompilationError(format!("Error during opt -passes=verify: {}", e))
-        })?;
+        ctxt.opt_verify(&input)
+            .map_err(|e| EngineError::from_tool(e, "Error during opt -passes=verify"))?;
         ctxt.disassemble_in_place(&input)
-            .map_err(|e| EngineError::CompilationError(format!("Error during disas: {}", e)))?;
+            .map_err(|e| EngineError::from_tool(e, "Error during disas"))?;
         debug!("[0] sanity checked");
 
-        // baseline loading
+        // provenance
+        let mut manifest = Manifest::open(ctxt, &output).map_err(manifest_err)?;
+        if !input.starts_with(&output) {
+            manifest.record_input(&input).map_err(manifest_err)?;
+        }
+        manifest.record_schedule(&schedule);
+
+        // baseline loading, preserved as the first step so that the workspace can be resumed
+        let timer = Instant::now();
+        let base = output.join(step_file(0));
+        if base != input {
+            fs::copy(&input, &base).map_err(|e| {
+                EngineError::CompilationError(format!("Error during copying baseline: {}", e))
+            })?;
+        }
         let mut history = vec![];
-        let baseline = ctxt.load(&input)?;
-        history.push((input, baseline));
+        let baseline = load_step(ctxt, cache.as_ref(), &base, &[], &base, || Ok(()))?;
+        manifest
+            .record_step("fixedpoint-0", &base, timer.elapsed())
+            .map_err(manifest_err)?;
+        history.push((base, baseline));
         debug!("[0] baseline recorded");
 
+        Self::iterate(
+            ctxt,
+            &output,
+            depth,
+            &schedule,
+            cache.as_ref(),
+            history,
+            &mut manifest,
+        )
+    }
+
+    /// Resume the optimization from the steps kept in the output directory of a prior run, with
+    /// the opt pipelines recorded by that run
+    pub fn resume(
+        ctxt: &Context,
+        output: &Path,
+        depth: Option<usize>,
+    ) -> EngineResult<Vec<bridge::module::Module>> {
+        // make sure the kept artifacts are still valid
+        match Manifest::load(output).map_err(manifest_err)? {
+            None => warn!(
+                "no manifest in {}, resuming without validation",
+                output.to_string_lossy()
+            ),
+            Some(manifest) => manifest
+                .validate(ctxt)
+                .map_err(|e| EngineError::InvalidAssumption(format!("Stale workspace: {}", e)))?,
+        }
+        let mut manifest = Manifest::open(ctxt, output).map_err(manifest_err)?;
+        let schedule = manifest.schedule().cloned().unwrap_or_default();
+
+        // reconstruct the history from existing steps
+        let mut history: Vec<(PathBuf, bridge::module::Module)> = vec![];
+        loop {
+            let step = history.len();
+            let path = output.join(step_file(step));
+            if !path.exists() {
+                break;
+            }
+            let module = ctxt.load_or_reuse(&path)?;
+            debug!("[{}] module restored", step);
+
+            // the prior run has already reached a fixedpoint
+            if history
+                .last()
+                .is_some_and(|(_, last_ir)| is_same_module(last_ir, &module))
+            {
+                debug!("[{}] fixedpoint already reached", step);
+                return Ok(history.into_iter().map(|(_, m)| m).collect());
+            }
+            history.push((path, module));
+        }
+        if history.is_empty() {
+            return Err(EngineError::InvalidAssumption(format!(
+                "no fixedpoint steps found in {}",
+                output.to_string_lossy()
+            )));
+        }
+
+        Self::iterate(ctxt, output, depth, &schedule, None, history, &mut manifest)
+            .map(|(trace, _)| trace)
+    }
+
+    /// Optimize until a fixedpoint, continuing from the history, where a fixedpoint means that a
+    /// full cycle of the pipelines after the prelude leaves the module unchanged
+    fn iterate(
+        ctxt: &Context,
+        output: &Path,
+        depth: Option<usize>,
+        schedule: &Schedule,
+        cache: Option<&FixedpointCache>,
+        mut history: Vec<(PathBuf, bridge::module::Module)>,
+        manifest: &mut Manifest,
+    ) -> EngineResult<(Vec<bridge::module::Module>, Vec<RoundSummary>)> {
+        // every round recorded in the history changes the module
+        let mut round = history.len() - 1;
+        let mut unchanged = 0;
+        let mut rounds = vec![];
+
         // optimization until a fixedpoint
         loop {
-            // limit the number of iterations if requested
-            if depth.map_or(false, |limit| history.len() > limit) {
+            // limit the number of iterations if requested, reporting why no fixedpoint is reached
+            if let Some(limit) = depth.filter(|limit| history.len() > *limit) {
+                if !rounds.is_empty() {
+                    warn!(
+                        "no fixedpoint within the depth limit of {}, with the rounds:",
+                        limit
+                    );
+                    for summary in &rounds {
+                        warn!("{}", summary);
+                    }
+                }
                 break;
             }
 
             let (last_path, last_ir) = history.last().unwrap();
             let step = history.len();
+            let timer = Instant::now();
 
             // optimization
-            let this_path = output.join(format!("step-{}.bc", step));
-            ctxt.opt_pipeline(last_path, &this_path, "default<O3>")
-                .map_err(|e| EngineError::CompilationError(format!("Error during opt: {}", e)))?;
+            let pipeline = schedule.pipeline(round);
+            round += 1;
+            let this_path = output.join(step_file(step));
+            let optimized = load_step(ctxt, cache, last_path, &[pipeline], &this_path, || {
+                ctxt.opt_pipeline(last_path, &this_path, pipeline)
+                    .map_err(|e| EngineError::from_tool(e, "Error during opt"))
+            })?;
             ctxt.disassemble_in_place(&this_path)
-                .map_err(|e| EngineError::CompilationError(format!("Error during disas: {}", e)))?;
-            debug!("[{}] optimization done", step);
+                .map_err(|e| EngineError::from_tool(e, "Error during disas"))?;
+            debug!("[{}] optimization done with {}", step, pipeline);
 
-            // loading
-            let optimized = ctxt.load(&this_path)?;
+            // summarize the changes
+            let same = is_same_module(last_ir, &optimized);
+            let summary = if same {
+                RoundSummary::new(step, pipeline, None)
+            } else {
+                RoundSummary::new(
+                    step,
+                    pipeline,
+                    Some(&ModuleDiff::compute(last_ir, &optimized)),
+                )
+            };
+            debug!("{}", summary);
+            manifest
+                .record_round(
+                    &format!("fixedpoint-{}", step),
+                    &this_path,
+                    timer.elapsed(),
+                    &summary,
+                )
+                .map_err(manifest_err)?;
+            rounds.push(summary);
             debug!("[{}] module recorded", step);
 
             // check for fixedpoint
-            if last_ir == &optimized {
-                break;
+            if same {
+                if round > schedule.prelude.len() {
+                    unchanged += 1;
+                    if unchanged >= schedule.cycle.len() {
+                        break;
+                    }
+                }
+                continue;
             }
+            unchanged = 0;
             history.push((this_path, optimized));
         }
         debug!("[{}] fixedpoint optimization done", history.len());
 
         // return the full optimization trace
         let trace = history.into_iter().map(|(_, m)| m).collect();
-        Ok(trace)
+        Ok((trace, rounds))
     }
 }
+
+/// Utility: report a failure in maintaining the manifest
+fn manifest_err(e: anyhow::Error) -> EngineError {
+    EngineError::CompilationError(format!("Error in manifest: {}", e))
+}
+
+/// Utility: report a failure in maintaining the cache
+fn cache_err(e: anyhow::Error) -> EngineError {
+    EngineError::CompilationError(format!("Error in cache: {}", e))
+}
+
+/// Utility: run the step producing the output bitcode from the input and load the output, unless
+/// the cache holds the step under the flags
+fn load_step<F>(
+    ctxt: &Context,
+    cache: Option<&FixedpointCache>,
+    input: &Path,
+    flags: &[&str],
+    output: &Path,
+    step: F,
+) -> EngineResult<bridge::module::Module>
+where
+    F: FnOnce() -> EngineResult<()>,
+{
+    let cache = match cache {
+        None => {
+            step()?;
+            return ctxt.load(output);
+        }
+        Some(cache) => cache,
+    };
+    if cache.restore(input, flags, output).map_err(cache_err)? {
+        debug!("cache hit on {}", output.to_string_lossy());
+        return ctxt.load_or_reuse(output);
+    }
+    step()?;
+    let module = ctxt.load(output)?;
+    cache.store(input, flags, output).map_err(cache_err)?;
+    Ok(module)
+}
+
+/// Utility: check whether two modules are equal, with canonical hashes as a fast path and deep
+/// equality (which involves CFG isomorphism) only when the hashes agree
+fn is_same_module(lhs: &bridge::module::Module, rhs: &bridge::module::Module) -> bool {
+    if lhs.canonical_hash() != rhs.canonical_hash() {
+        return false;
+    }
+    let same = lhs == rhs;
+    if !same {
+        debug!("canonical hash collision between distinct modules");
+    }
+    same
+}
+
+/// Name of the bitcode file of an optimization step
+fn step_file(step: usize) -> String {
+    format!("step-{}.bc", step)
+}
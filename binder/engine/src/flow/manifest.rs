@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::flow::fixedpoint::{RoundSummary, Schedule};
+use crate::flow::shared::Context;
+
+/// Name of the manifest file in a workspace
+static MANIFEST_FILE: &str = "manifest.json";
+
+/// A step performed in the workspace
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct StepRecord {
+    /// name of the step
+    pub name: String,
+    /// artifact produced, relative to the workspace
+    pub output: String,
+    /// fingerprint of the artifact
+    pub fingerprint: String,
+    /// wall-clock time spent, in milliseconds
+    pub millis: u128,
+    /// opt pipeline applied and changes made, for a fixedpoint round
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub round: Option<RoundSummary>,
+}
+
+/// Provenance of the artifacts in a workspace
+#[derive(Serialize, Deserialize)]
+pub struct Manifest {
+    /// location of the workspace (not serialized)
+    #[serde(skip)]
+    workspace: PathBuf,
+    /// seconds since the UNIX epoch when the workspace is created
+    created: u64,
+    /// version of the LLVM toolchain
+    llvm_version: String,
+    /// fingerprint of the libra pass plugin, or of the loader in its place
+    pass_fingerprint: String,
+    /// input files and their fingerprints
+    inputs: BTreeMap<String, String>,
+    /// flags passed to the compiler
+    flags: Vec<String>,
+    /// opt pipelines of the fixedpoint rounds
+    #[serde(default)]
+    schedule: Option<Schedule>,
+    /// steps performed, in order
+    steps: Vec<StepRecord>,
+}
+
+/// Utility: a SHA-256 digest of the content, in hex
+pub fn digest(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Utility: a stable fingerprint of a file, as the digest of its content
+pub fn fingerprint(path: &Path) -> Result<String> {
+    Ok(digest(&fs::read(path)?))
+}
+
+impl Manifest {
+    fn path(workspace: &Path) -> PathBuf {
+        workspace.join(MANIFEST_FILE)
+    }
+
+    /// Load the manifest of the workspace, if one exists
+    pub fn load(workspace: &Path) -> Result<Option<Self>> {
+        let path = Self::path(workspace);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        let mut manifest: Self = serde_json::from_str(&content)?;
+        manifest.workspace = workspace.to_path_buf();
+        Ok(Some(manifest))
+    }
+
+    /// Load the manifest of the workspace, or start a new one
+    pub fn open(ctxt: &Context, workspace: &Path) -> Result<Self> {
+        if let Some(manifest) = Self::load(workspace)? {
+            return Ok(manifest);
+        }
+        Ok(Self {
+            workspace: workspace.to_path_buf(),
+            created: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            llvm_version: ctxt
+                .llvm_version()
+                .unwrap_or_else(|_| "unknown".to_string()),
+            pass_fingerprint: ctxt.oracle_fingerprint()?,
+            inputs: BTreeMap::new(),
+            flags: vec![],
+            schedule: None,
+            steps: vec![],
+        })
+    }
+
+    /// Record an input file
+    pub fn record_input(&mut self, path: &Path) -> Result<()> {
+        self.inputs
+            .insert(path.to_string_lossy().to_string(), fingerprint(path)?);
+        Ok(())
+    }
+
+    /// Record the compiler flags
+    pub fn record_flags(&mut self, flags: &[String]) {
+        self.flags = flags.to_vec();
+    }
+
+    /// Record the opt pipelines of the fixedpoint rounds
+    pub fn record_schedule(&mut self, schedule: &Schedule) {
+        self.schedule = Some(schedule.clone());
+    }
+
+    /// Opt pipelines of the fixedpoint rounds, if recorded
+    pub fn schedule(&self) -> Option<&Schedule> {
+        self.schedule.as_ref()
+    }
+
+    /// Record a step (replacing prior records on the same artifact) and save the manifest
+    pub fn record_step(&mut self, name: &str, output: &Path, elapsed: Duration) -> Result<()> {
+        self.record(name, output, elapsed, None)
+    }
+
+    /// Record a fixedpoint round along with its summary
+    pub fn record_round(
+        &mut self,
+        name: &str,
+        output: &Path,
+        elapsed: Duration,
+        round: &RoundSummary,
+    ) -> Result<()> {
+        self.record(name, output, elapsed, Some(round.clone()))
+    }
+
+    fn record(
+        &mut self,
+        name: &str,
+        output: &Path,
+        elapsed: Duration,
+        round: Option<RoundSummary>,
+    ) -> Result<()> {
+        let relative = output
+            .strip_prefix(&self.workspace)
+            .unwrap_or(output)
+            .to_string_lossy()
+            .to_string();
+        self.steps.retain(|step| step.output != relative);
+        self.steps.push(StepRecord {
+            name: name.to_string(),
+            fingerprint: fingerprint(output)?,
+            output: relative,
+            millis: elapsed.as_millis(),
+            round,
+        });
+        self.save()
+    }
+
+    /// Save the manifest into the workspace
+    pub fn save(&self) -> Result<()> {
+        fs::write(
+            Self::path(&self.workspace),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+
+    /// Steps recorded in the manifest
+    pub fn steps(&self) -> &[StepRecord] {
+        &self.steps
+    }
+
+    /// Check that the toolchain and the recorded artifacts are unchanged
+    pub fn validate(&self, ctxt: &Context) -> Result<()> {
+        let llvm_version = ctxt
+            .llvm_version()
+            .unwrap_or_else(|_| "unknown".to_string());
+        if llvm_version != self.llvm_version {
+            bail!(
+                "LLVM version changed: {} (manifest: {})",
+                llvm_version,
+                self.llvm_version
+            );
+        }
+        if ctxt.oracle_fingerprint()? != self.pass_fingerprint {
+            bail!("libra pass or loader changed since the workspace is created");
+        }
+        for step in &self.steps {
+            let path = self.workspace.join(&step.output);
+            if !path.exists() {
+                continue;
+            }
+            if fingerprint(&path)? != step.fingerprint {
+                bail!(
+                    "artifact changed since the step {}: {}",
+                    step.name,
+                    step.output
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,7 @@
 pub mod build_simple;
+pub mod cache;
 pub mod fixedpoint;
+pub mod manifest;
+pub mod multi;
 pub mod shared;
+pub mod triage;
@@ -0,0 +1,203 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::panic::resume_unwind;
+use std::path::PathBuf;
+use std::thread;
+
+use log::{debug, info};
+
+use crate::error::{EngineError, EngineResult};
+use crate::flow::build_simple::{FlowBuildSimple, Profile};
+use crate::flow::cache::FixedpointCache;
+use crate::flow::fixedpoint::{FlowFixedpoint, Schedule};
+use crate::flow::shared::Context;
+use crate::ir::bridge::module::Module;
+
+/// An independent analysis target, built into a module of its own
+pub struct Target {
+    /// name of the target, also naming its workspace
+    pub name: String,
+    /// source files, or bitcode and IR files to link along
+    pub inputs: Vec<PathBuf>,
+}
+
+impl Target {
+    /// Parse a target in the form of `name=input[,input...]`
+    pub fn parse(text: &str) -> EngineResult<Self> {
+        let (name, inputs) = text.split_once('=').ok_or_else(|| {
+            EngineError::InvalidAssumption(format!("expect a target as name=inputs: {}", text))
+        })?;
+        // the name is a directory under the output, which must not escape it
+        if name.is_empty() || name == "." || name == ".." || name.contains(std::path::is_separator)
+        {
+            return Err(EngineError::InvalidAssumption(format!(
+                "invalid target name: {}",
+                name
+            )));
+        }
+        let inputs: Vec<_> = inputs
+            .split(',')
+            .filter(|item| !item.is_empty())
+            .map(PathBuf::from)
+            .collect();
+        if inputs.is_empty() {
+            return Err(EngineError::InvalidAssumption(format!(
+                "no inputs for target {}",
+                name
+            )));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            inputs,
+        })
+    }
+}
+
+pub struct FlowMultiTarget<'a> {
+    /// Context manager
+    ctxt: &'a Context,
+    /// Targets to analyze
+    targets: Vec<Target>,
+    /// Output directory of the process, holding a workspace per target
+    output: PathBuf,
+    /// Extra flags passed to clang
+    flags: Vec<String>,
+    /// Depth of fixedpoint optimization (if set)
+    depth: Option<usize>,
+    /// Preset of clang flags
+    profile: Profile,
+    /// Symbols kept as the public API of every target
+    public_api: Vec<String>,
+    /// Whether to run the fixedpoint optimization after the build
+    fixedpoint: bool,
+    /// Opt pipelines of the fixedpoint rounds
+    schedule: Schedule,
+    /// Cache of the fixedpoint steps in the studio (if set)
+    cache: Option<FixedpointCache>,
+}
+
+impl<'a> FlowMultiTarget<'a> {
+    pub fn new(
+        ctxt: &'a Context,
+        targets: Vec<Target>,
+        output: PathBuf,
+        flags: Vec<String>,
+        depth: Option<usize>,
+    ) -> Self {
+        Self {
+            ctxt,
+            targets,
+            output,
+            flags,
+            depth,
+            profile: Profile::default(),
+            public_api: vec![],
+            fixedpoint: true,
+            schedule: Schedule::default(),
+            cache: None,
+        }
+    }
+
+    /// Compile the sources of every target with a preset of flags
+    pub fn set_profile(&mut self, profile: Profile) {
+        self.profile = profile;
+    }
+
+    /// Keep the symbols as the public API of every target
+    pub fn set_public_api(&mut self, symbols: Vec<String>) {
+        self.public_api = symbols;
+    }
+
+    /// Set whether to run the fixedpoint optimization after the build
+    pub fn set_fixedpoint(&mut self, enabled: bool) {
+        self.fixedpoint = enabled;
+    }
+
+    /// Set the opt pipelines of the fixedpoint rounds
+    pub fn set_schedule(&mut self, schedule: Schedule) {
+        self.schedule = schedule;
+    }
+
+    /// Share the cache of the fixedpoint steps among the targets
+    pub fn set_cache(&mut self, cache: FixedpointCache) {
+        self.cache = Some(cache);
+    }
+
+    /// Build and optimize each target in parallel, yielding the fixedpoint trace of each, or the
+    /// built module alone if the optimization is skipped
+    pub fn execute(self) -> EngineResult<BTreeMap<String, EngineResult<Vec<Module>>>> {
+        let Self {
+            ctxt,
+            targets,
+            output,
+            flags,
+            depth,
+            profile,
+            public_api,
+            fixedpoint,
+            schedule,
+            cache,
+        } = self;
+
+        // each target gets a distinct workspace
+        let mut workspaces = BTreeMap::new();
+        for target in &targets {
+            let path = output.join(&target.name);
+            if workspaces
+                .insert(target.name.clone(), path.clone())
+                .is_some()
+            {
+                return Err(EngineError::InvalidAssumption(format!(
+                    "duplicated target: {}",
+                    target.name
+                )));
+            }
+            fs::create_dir_all(&path).map_err(|e| {
+                EngineError::CompilationError(format!("Error creating workspace: {}", e))
+            })?;
+        }
+        info!("Analyzing {} targets in parallel", targets.len());
+
+        // a failing target does not stop the others
+        let results = thread::scope(|scope| {
+            let handles: Vec<_> = targets
+                .into_iter()
+                .map(|target| {
+                    let workspace = workspaces[&target.name].clone();
+                    let flags = flags.clone();
+                    let public_api = public_api.clone();
+                    let schedule = schedule.clone();
+                    let cache = cache.clone();
+                    let handle = scope.spawn(move || {
+                        let mut build =
+                            FlowBuildSimple::new(ctxt, target.inputs, workspace.clone(), flags);
+                        build.set_profile(profile);
+                        if !public_api.is_empty() {
+                            build.set_public_api(public_api);
+                        }
+                        let bitcode = build.execute()?;
+                        if !fixedpoint {
+                            return Ok(vec![ctxt.load(&bitcode)?]);
+                        }
+                        let mut flow = FlowFixedpoint::new(ctxt, bitcode, workspace, depth);
+                        flow.set_schedule(schedule);
+                        if let Some(cache) = cache {
+                            flow.set_cache(cache);
+                        }
+                        flow.execute()
+                    });
+                    (target.name, handle)
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|(name, handle)| {
+                    let result = handle.join().unwrap_or_else(|e| resume_unwind(e));
+                    debug!("target {} analyzed", name);
+                    (name, result)
+                })
+                .collect()
+        });
+        Ok(results)
+    }
+}
@@ -1,52 +1,211 @@
+use std::collections::BTreeMap;
+use std::env;
 use std::ffi::{OsStr, OsString};
 use std::fs;
+use std::io::{self, Read};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Output, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, bail, Result};
+use log::debug;
 use serde::Deserialize;
 
-use libra_builder::{artifact_for_pass, ResolverLLVM};
+use libra_builder::{artifact_for_include, artifact_for_pass, ResolverLLVM};
+use libra_shared::compile_db::{quote_token, CompileEntry};
 use libra_shared::dep::Resolver;
 
 use crate::error::{EngineError, EngineResult};
+use crate::flow::manifest::fingerprint;
+use crate::flow::triage::CrashReport;
+use crate::ir::bridge::constant::UndefSemantics;
+use crate::ir::bridge::program::Program;
+use crate::ir::census::Census;
 use crate::ir::{adapter, bridge};
 
+/// Interval between checks on a running tool for its time limit
+static POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Maximum length of the diagnostics of a tool kept in an error
+static MAX_DIAGNOSTICS_LENGTH: usize = 4096;
+
+/// Format of the modules serialized by the libra pass
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Default)]
+pub enum SerialFormat {
+    /// JSON text
+    #[default]
+    Json,
+    /// CBOR binary, more compact and faster to parse for big modules
+    Cbor,
+}
+
+impl SerialFormat {
+    pub fn parse(name: &str) -> EngineResult<Self> {
+        let parsed = match name {
+            "json" => Self::Json,
+            "cbor" => Self::Cbor,
+            _ => {
+                return Err(EngineError::InvalidAssumption(format!(
+                    "unknown serialization format: {}",
+                    name
+                )));
+            }
+        };
+        Ok(parsed)
+    }
+
+    /// Name of the format, also the extension of the serialized files
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Cbor => "cbor",
+        }
+    }
+
+    /// Format of a serialized file by its extension
+    fn of_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext == "cbor" => Self::Cbor,
+            _ => Self::Json,
+        }
+    }
+}
+
+/// Producer of the serialized modules
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Default)]
+pub enum Loader {
+    /// the libra pass, run as a plugin of opt
+    #[default]
+    Pass,
+    /// a bitcode reader within the engine, usable without the libra pass at a reduced fidelity
+    Native,
+}
+
+impl Loader {
+    pub fn parse(name: &str) -> EngineResult<Self> {
+        let parsed = match name {
+            "pass" => Self::Pass,
+            "native" => Self::Native,
+            _ => {
+                return Err(EngineError::InvalidAssumption(format!(
+                    "unknown loader: {}",
+                    name
+                )));
+            }
+        };
+        Ok(parsed)
+    }
+}
+
+/// Limits on the external tools spawned (e.g., clang and opt)
+#[derive(Default, Clone, Copy)]
+pub struct ToolLimits {
+    /// wall-clock time before a tool is killed
+    pub timeout: Option<Duration>,
+    /// bytes of address space a tool may map
+    pub memory: Option<u64>,
+}
+
 /// Context for all workflow
 pub struct Context {
     /// Path to the llvm installation base
     pkg_llvm: PathBuf,
     /// Path to the clang compiler
     bin_clang: PathBuf,
+    /// Path to the clang++ compiler
+    bin_clangxx: PathBuf,
     /// Path to the llvm-link tool
     bin_llvm_link: PathBuf,
     /// Path to the llvm-as tool
     bin_llvm_as: PathBuf,
     /// Path to the llvm-dis tool
     bin_llvm_dis: PathBuf,
+    /// Path to the llvm-extract tool
+    bin_llvm_extract: PathBuf,
+    /// Path to the llvm-nm tool
+    bin_llvm_nm: PathBuf,
     /// Path to the opt tool
     bin_opt: PathBuf,
     /// Path to the libra pass
     lib_pass: PathBuf,
+    /// Path to the libra headers for C sources
+    inc_libra: PathBuf,
+    /// Interpretation of undef values
+    undef: UndefSemantics,
+    /// Limits on the external tools
+    limits: ToolLimits,
+    /// Format of the serialized modules
+    format: SerialFormat,
+    /// Producer of the serialized modules
+    loader: Loader,
+    /// Whether to keep the artifacts for debugging only, e.g., disassembled bitcode
+    debug_artifacts: bool,
 }
 
 impl Context {
     pub fn new() -> Result<Self> {
         let (_, resolver_llvm) = ResolverLLVM::seek()?;
         let lib_pass = artifact_for_pass()?;
+        let inc_libra = artifact_for_include()?;
         let pkg_llvm = resolver_llvm.path_install().to_path_buf();
 
         Ok(Self {
             bin_clang: pkg_llvm.join("bin").join("clang"),
+            bin_clangxx: pkg_llvm.join("bin").join("clang++"),
             bin_llvm_link: pkg_llvm.join("bin").join("llvm-link"),
             bin_llvm_as: pkg_llvm.join("bin").join("llvm-as"),
             bin_llvm_dis: pkg_llvm.join("bin").join("llvm-dis"),
+            bin_llvm_extract: pkg_llvm.join("bin").join("llvm-extract"),
+            bin_llvm_nm: pkg_llvm.join("bin").join("llvm-nm"),
             bin_opt: pkg_llvm.join("bin").join("opt"),
             pkg_llvm,
             lib_pass: lib_pass.to_path_buf(),
+            inc_libra,
+            undef: UndefSemantics::default(),
+            limits: ToolLimits::default(),
+            format: SerialFormat::default(),
+            loader: Loader::default(),
+            debug_artifacts: true,
         })
     }
 
+    /// Set the interpretation of undef values for modules loaded afterwards
+    pub fn set_undef_semantics(&mut self, semantics: UndefSemantics) {
+        self.undef = semantics;
+    }
+
+    /// Set the format of the modules serialized afterwards
+    pub fn set_serial_format(&mut self, format: SerialFormat) {
+        self.format = format;
+    }
+
+    /// Format of the serialized modules
+    pub fn serial_format(&self) -> SerialFormat {
+        self.format
+    }
+
+    /// Location of the serialized module of a bitcode file
+    pub fn serialized_path(&self, input: &Path) -> PathBuf {
+        input.with_extension(self.format.extension())
+    }
+
+    /// Set the producer of the modules serialized afterwards
+    pub fn set_loader(&mut self, loader: Loader) {
+        self.loader = loader;
+    }
+
+    /// Set whether to produce the artifacts for debugging only, e.g., disassembled bitcode
+    pub fn set_debug_artifacts(&mut self, enabled: bool) {
+        self.debug_artifacts = enabled;
+    }
+
+    /// Set the limits on the external tools spawned afterwards
+    pub fn set_tool_limits(&mut self, limits: ToolLimits) {
+        self.limits = limits;
+    }
+
     pub fn path_llvm<I, S>(&self, segments: I) -> Result<String>
     where
         I: IntoIterator<Item = S>,
@@ -59,46 +218,218 @@ impl Context {
             .map_err(|_| anyhow!("non-ascii llvm path"))
     }
 
-    fn run(mut cmd: Command) -> Result<()> {
-        let status = cmd.status()?;
-        if !status.success() {
+    /// Utility: the command line of a command
+    fn command_line(cmd: &Command) -> String {
+        std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|arg| arg.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Utility: read a pipe of a tool to the end aside, so that the tool never blocks on it
+    fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> Option<JoinHandle<io::Result<Vec<u8>>>> {
+        pipe.map(|mut pipe| {
+            thread::spawn(move || {
+                let mut buffer = vec![];
+                pipe.read_to_end(&mut buffer).map(|_| buffer)
+            })
+        })
+    }
+
+    /// Utility: content of a pipe drained aside, empty if the pipe is not captured
+    fn drained(handle: Option<JoinHandle<io::Result<Vec<u8>>>>) -> Result<Vec<u8>> {
+        match handle {
+            None => Ok(vec![]),
+            Some(handle) => handle
+                .join()
+                .map_err(|_| anyhow!("unable to collect the output of a tool"))?
+                .map_err(|e| e.into()),
+        }
+    }
+
+    /// Run the command to completion within the limits on the tools, collecting the outputs that
+    /// are piped, where a tool running out of time is killed with an `EngineError::ToolTimeout`
+    fn execute(&self, cmd: &mut Command) -> Result<Output> {
+        if let Some(bytes) = self.limits.memory {
+            let limit = libc::rlimit {
+                rlim_cur: bytes as libc::rlim_t,
+                rlim_max: bytes as libc::rlim_t,
+            };
+            // SAFETY: setrlimit is async-signal-safe and touches no state of the parent
+            unsafe {
+                cmd.pre_exec(move || {
+                    if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+        let mut child = cmd.spawn()?;
+        let stdout = Self::drain(child.stdout.take());
+        let stderr = Self::drain(child.stderr.take());
+
+        let status = match self.limits.timeout {
+            None => child.wait()?,
+            Some(timeout) => {
+                let deadline = Instant::now() + timeout;
+                loop {
+                    if let Some(status) = child.try_wait()? {
+                        break status;
+                    }
+                    if Instant::now() >= deadline {
+                        child.kill()?;
+                        child.wait()?;
+                        return Err(EngineError::ToolTimeout(format!(
+                            "killed after {}s: {}",
+                            timeout.as_secs(),
+                            Self::command_line(cmd)
+                        ))
+                        .into());
+                    }
+                    thread::sleep(POLL_INTERVAL);
+                }
+            }
+        };
+        Ok(Output {
+            status,
+            stdout: Self::drained(stdout)?,
+            stderr: Self::drained(stderr)?,
+        })
+    }
+
+    /// Utility: the diagnostics printed by a tool, truncated to a bounded length
+    fn diagnostics(output: &Output) -> String {
+        let text = String::from_utf8_lossy(&output.stderr);
+        let text = text.trim();
+        if text.len() <= MAX_DIAGNOSTICS_LENGTH {
+            return text.to_string();
+        }
+        let mut end = MAX_DIAGNOSTICS_LENGTH;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!("{}\n... ({} more bytes)", &text[..end], text.len() - end)
+    }
+
+    /// Run the command, embedding the diagnostics of the tool into the error if it fails
+    fn run(&self, mut cmd: Command) -> Result<()> {
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        let output = self.execute(&mut cmd)?;
+        let diagnostics = Self::diagnostics(&output);
+        if !output.status.success() {
             bail!(
-                "Command failed with status {}: {} {}",
-                status,
-                cmd.get_program().to_str().unwrap(),
-                cmd.get_args()
-                    .map(|arg| arg.to_str().unwrap())
-                    .collect::<Vec<_>>()
-                    .join(" ")
+                "Command failed with status {}: {}\n{}",
+                output.status,
+                Self::command_line(&cmd),
+                diagnostics
             );
         }
+        if !diagnostics.is_empty() {
+            debug!("{}\n{}", Self::command_line(&cmd), diagnostics);
+        }
         Ok(())
     }
 
-    fn run_clang<I, S>(&self, input: &Path, output: &Path, args: I) -> Result<()>
+    fn run_clang<I, S>(&self, compiler: &Path, input: &Path, output: &Path, args: I) -> Result<()>
     where
         I: IntoIterator<Item = S>,
         S: AsRef<OsStr>,
     {
-        let mut cmd = Command::new(&self.bin_clang);
+        let mut cmd = Command::new(compiler);
         cmd.args(args).arg("-o").arg(output).arg(input);
-        Self::run(cmd)
+        self.run(cmd)
     }
 
-    pub fn compile_to_bitcode<I, S>(&self, input: &Path, output: &Path, args: I) -> Result<()>
+    fn compile_with<I, S>(
+        &self,
+        compiler: &Path,
+        input: &Path,
+        output: &Path,
+        args: I,
+    ) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.run_clang(compiler, input, output, self.compile_flags(args))
+    }
+
+    /// Flags to compile a source file into a bitcode file, with the extra flags at the end
+    fn compile_flags<I, S>(&self, args: I) -> Vec<OsString>
     where
         I: IntoIterator<Item = S>,
         S: AsRef<OsStr>,
     {
         let mut flags = vec![OsString::from("-c"), OsString::from("-emit-llvm")];
+        // make the specification macros available and effective
+        flags.push(OsString::from("-D__LIBRA__"));
+        flags.push(OsString::from("-isystem"));
+        flags.push(self.inc_libra.clone().into_os_string());
         flags.extend(args.into_iter().map(|i| i.as_ref().to_os_string()));
-        self.run_clang(input, output, flags)
+        flags
+    }
+
+    /// Describe the compilation of `compile_to_bitcode`, or `compile_cxx_to_bitcode` if `cxx` is
+    /// set, as an entry of a compilation database
+    pub fn compile_entry<I, S>(
+        &self,
+        cxx: bool,
+        input: &Path,
+        output: &Path,
+        args: I,
+    ) -> Result<CompileEntry>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let compiler = if cxx {
+            &self.bin_clangxx
+        } else {
+            &self.bin_clang
+        };
+        let tokens: Vec<_> = std::iter::once(compiler.as_os_str().to_os_string())
+            .chain(self.compile_flags(args))
+            .chain([
+                OsString::from("-o"),
+                output.as_os_str().to_os_string(),
+                input.as_os_str().to_os_string(),
+            ])
+            .map(|token| quote_token(&token.to_string_lossy()))
+            .collect();
+        Ok(CompileEntry {
+            file: input.to_string_lossy().to_string(),
+            directory: env::current_dir()?.to_string_lossy().to_string(),
+            command: tokens.join(" "),
+        })
+    }
+
+    pub fn compile_to_bitcode<I, S>(&self, input: &Path, output: &Path, args: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.compile_with(&self.bin_clang, input, output, args)
+    }
+
+    /// Same as `compile_to_bitcode`, with the C++ driver that sets up the C++ standard headers
+    pub fn compile_cxx_to_bitcode<I, S>(&self, input: &Path, output: &Path, args: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.compile_with(&self.bin_clangxx, input, output, args)
     }
 
-    pub fn link_bitcode(&self, input: &[&Path], output: &Path) -> Result<()> {
+    /// Link bitcode files, optionally internalizing the linked symbols
+    pub fn link_bitcode(&self, input: &[&Path], output: &Path, internalize: bool) -> Result<()> {
         let mut cmd = Command::new(&self.bin_llvm_link);
-        cmd.arg("--internalize").arg("-o").arg(output).args(input);
-        Self::run(cmd)
+        if internalize {
+            cmd.arg("--internalize");
+        }
+        cmd.arg("-o").arg(output).args(input);
+        self.run(cmd)
     }
 
     fn run_opt<I, S>(&self, input: &Path, output: Option<&Path>, args: I) -> Result<()>
@@ -111,25 +442,53 @@ impl Context {
             .arg("-o")
             .arg(output.unwrap_or_else(|| Path::new("/dev/null")));
         cmd.arg(input);
-        Self::run(cmd)
+        self.run(cmd)
+    }
+
+    /// Path to the libra pass plugin
+    pub fn path_pass(&self) -> &Path {
+        &self.lib_pass
+    }
+
+    /// Fingerprint of the producer of the serialized modules, which changes with the libra pass
+    pub fn oracle_fingerprint(&self) -> Result<String> {
+        match self.loader {
+            Loader::Pass => fingerprint(&self.lib_pass),
+            Loader::Native => Ok("native".to_string()),
+        }
+    }
+
+    /// Retrieve the version of the LLVM toolchain
+    pub fn llvm_version(&self) -> Result<String> {
+        let output = Command::new(self.path_llvm(["bin", "llvm-config"])?)
+            .arg("--version")
+            .output()?;
+        if !output.status.success() {
+            bail!("unable to query the LLVM version");
+        }
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
     }
 
     /// Assemble the readable format into raw bitcode file
     pub fn assemble(&self, input: &Path, output: &Path) -> Result<()> {
         let mut cmd = Command::new(&self.bin_llvm_as);
         cmd.arg("-o").arg(output).arg(input);
-        Self::run(cmd)
+        self.run(cmd)
     }
 
     /// Disassemble the bitcode file into readable format
     pub fn disassemble(&self, input: &Path, output: &Path) -> Result<()> {
         let mut cmd = Command::new(&self.bin_llvm_dis);
         cmd.arg("-o").arg(output).arg(input);
-        Self::run(cmd)
+        self.run(cmd)
     }
 
-    /// Disassemble the bitcode file into readable format in the same directory
+    /// Disassemble the bitcode file into readable format in the same directory, unless the
+    /// artifacts for debugging are disabled
     pub fn disassemble_in_place(&self, input: &Path) -> Result<()> {
+        if !self.debug_artifacts {
+            return Ok(());
+        }
         let output = input.with_extension("ll");
         self.disassemble(input, &output)
     }
@@ -139,51 +498,261 @@ impl Context {
         self.run_opt(input, None, ["-passes=verify"])
     }
 
+    /// Internalize all symbols in the bitcode file except the public API
+    pub fn internalize(&self, input: &Path, output: &Path, public_api: &[String]) -> Result<()> {
+        self.run_opt(
+            input,
+            Some(output),
+            [
+                "-passes=internalize".to_string(),
+                format!("-internalize-public-api-list={}", public_api.join(",")),
+            ],
+        )
+    }
+
     /// Run a specified opt pipeline
     pub fn opt_pipeline(&self, input: &Path, output: &Path, pipeline: &str) -> Result<()> {
         self.run_opt(input, Some(output), [format!("--passes={}", pipeline)])
     }
 
-    /// Serialize a bitcode file to JSON
-    fn serialize(&self, input: &Path, output: &Path) -> Result<()> {
+    /// Names of the functions defined in the bitcode file
+    pub fn defined_functions(&self, input: &Path) -> Result<Vec<String>> {
+        let output = Command::new(&self.bin_llvm_nm)
+            .arg("--defined-only")
+            .arg("--format=posix")
+            .arg(input)
+            .output()?;
+        if !output.status.success() {
+            bail!("unable to list symbols in {}", input.to_string_lossy());
+        }
+        let mut names = vec![];
+        for line in String::from_utf8(output.stdout)?.lines() {
+            let mut tokens = line.split_whitespace();
+            match (tokens.next(), tokens.next()) {
+                (Some(name), Some("T" | "t" | "W" | "w")) => names.push(name.to_string()),
+                _ => continue,
+            }
+        }
+        Ok(names)
+    }
+
+    /// Extract a single function (with its dependencies declared) from the bitcode file
+    pub fn extract_function(&self, input: &Path, name: &str, output: &Path) -> Result<()> {
+        let mut cmd = Command::new(&self.bin_llvm_extract);
+        cmd.arg(format!("--func={}", name))
+            .arg("-o")
+            .arg(output)
+            .arg(input);
+        self.run(cmd)
+    }
+
+    /// Run the libra pass on a bitcode file without interpreting the exit status
+    fn run_pass(&self, input: &Path, output: &Path) -> Result<Output> {
         let lib_pass = self
             .lib_pass
             .to_str()
             .ok_or_else(|| anyhow!("non-ascii path"))?;
-        self.run_opt(
-            input,
-            None,
-            [
-                &format!("-load-pass-plugin={}", lib_pass),
-                "-passes=Libra",
-                &format!("--libra-output={}", output.to_str().unwrap()),
-            ],
-        )
+        let mut cmd = Command::new(&self.bin_opt);
+        cmd.arg(format!("-load-pass-plugin={}", lib_pass))
+            .arg("-passes=Libra")
+            .arg(format!("--libra-output={}", output.to_str().unwrap()))
+            .arg(format!(
+                "--libra-output-format={}",
+                SerialFormat::of_path(output).extension()
+            ))
+            .arg("-o")
+            .arg("/dev/null")
+            .arg(input)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        self.execute(&mut cmd)
+    }
+
+    /// Run the libra pass and report the signal if the pass crashes
+    pub(crate) fn serialize_status(&self, input: &Path, output: &Path) -> Result<Option<i32>> {
+        let result = self.run_pass(input, output)?;
+        Ok(result.status.signal())
+    }
+
+    /// Serialize a bitcode file to JSON, triaging the input if the libra pass crashes
+    fn serialize(&self, input: &Path, output: &Path) -> EngineResult<()> {
+        if self.loader == Loader::Native {
+            return Self::serialize_native(input, output);
+        }
+
+        let result =
+            self.run_pass(input, output)
+                .map_err(|e| match e.downcast::<EngineError>() {
+                    Ok(err) => err,
+                    Err(e) => EngineError::LLVMLoadingError(format!(
+                        "unable to serialize the bitcode file: {}",
+                        e
+                    )),
+                })?;
+        if result.status.success() {
+            return Ok(());
+        }
+
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        match result.status.signal() {
+            None => Err(EngineError::LLVMLoadingError(format!(
+                "unable to serialize the bitcode file: libra pass failed with status {}: {}",
+                result.status,
+                Self::diagnostics(&result)
+            ))),
+            Some(signal) => {
+                let report = CrashReport::triage(self, input, signal, &stderr).map_err(|e| {
+                    EngineError::OracleCrash(format!(
+                        "libra pass terminated by signal {}, triage failed: {}",
+                        signal, e
+                    ))
+                })?;
+                Err(EngineError::OracleCrash(report.describe()))
+            }
+        }
+    }
+
+    /// Serialize a bitcode file, in the format by the extension of the output, with the bitcode
+    /// reader within the engine
+    #[cfg(feature = "native")]
+    fn serialize_native(input: &Path, output: &Path) -> EngineResult<()> {
+        let module_adapted = adapter::native::parse_bitcode(input)?;
+        let content = match SerialFormat::of_path(output) {
+            SerialFormat::Json => serde_json::to_vec(&module_adapted).map_err(|e| {
+                EngineError::LLVMLoadingError(format!("unable to serialize the module: {}", e))
+            })?,
+            SerialFormat::Cbor => {
+                let mut content = vec![];
+                ciborium::ser::into_writer(&module_adapted, &mut content).map_err(|e| {
+                    EngineError::LLVMLoadingError(format!("unable to serialize the module: {}", e))
+                })?;
+                content
+            }
+        };
+        fs::write(output, content)
+            .map_err(|e| EngineError::LLVMLoadingError(format!("unable to save the module: {}", e)))
     }
 
-    /// Deserialize the JSON file to a module
-    fn deserialize(input: &Path) -> EngineResult<bridge::module::Module> {
+    #[cfg(not(feature = "native"))]
+    fn serialize_native(_input: &Path, _output: &Path) -> EngineResult<()> {
+        Err(EngineError::InvalidAssumption(
+            "loading bitcode natively needs the engine built with the native feature".into(),
+        ))
+    }
+
+    /// Deserialize the serialized file, in the format by its extension, to an adapted module
+    fn deserialize_adapted(input: &Path) -> EngineResult<adapter::module::Module> {
+        let module_adapted = match SerialFormat::of_path(input) {
+            SerialFormat::Json => {
+                let content = fs::read_to_string(input).map_err(|e| {
+                    EngineError::LLVMLoadingError(format!("Corrupted JSON file: {}", e))
+                })?;
+
+                // manually construct the deserializer in order to disable the recursion limit
+                let mut deserializer = serde_json::Deserializer::from_str(&content);
+                deserializer.disable_recursion_limit();
+                adapter::module::Module::deserialize(&mut deserializer).map_err(|e| {
+                    EngineError::LLVMLoadingError(format!("Error during deserialization: {}", e))
+                })?
+            }
+            SerialFormat::Cbor => {
+                let content = fs::read(input).map_err(|e| {
+                    EngineError::LLVMLoadingError(format!("Corrupted CBOR file: {}", e))
+                })?;
+                ciborium::de::from_reader_with_recursion_limit(content.as_slice(), usize::MAX)
+                    .map_err(|e| {
+                        EngineError::LLVMLoadingError(format!(
+                            "Error during deserialization: {}",
+                            e
+                        ))
+                    })?
+            }
+        };
+        Ok(module_adapted)
+    }
+
+    /// Deserialize the serialized file to a module
+    fn deserialize(
+        input: &Path,
+        semantics: UndefSemantics,
+    ) -> EngineResult<bridge::module::Module> {
+        let module_adapted = Self::deserialize_adapted(input)?;
+        let module_bridge = bridge::module::Module::convert(&module_adapted, semantics)?;
+        Ok(module_bridge)
+    }
+
+    /// Serialize a bitcode file and then load it as a module
+    pub fn load(&self, input: &Path) -> EngineResult<bridge::module::Module> {
+        let output = self.serialized_path(input);
+        self.serialize(input, &output)?;
+        Self::deserialize(&output, self.undef)
+    }
+
+    /// Load a bitcode file as a module, reusing its serialization if it is up to date
+    pub fn load_or_reuse(&self, input: &Path) -> EngineResult<bridge::module::Module> {
+        let output = self.serialized_path(input);
+        let is_fresh = match (fs::metadata(input), fs::metadata(&output)) {
+            (Ok(meta_input), Ok(meta_output)) => {
+                match (meta_input.modified(), meta_output.modified()) {
+                    (Ok(time_input), Ok(time_output)) => time_output >= time_input,
+                    _ => false,
+                }
+            }
+            _ => false,
+        };
+        if is_fresh {
+            return Self::deserialize(&output, self.undef);
+        }
+        self.load(input)
+    }
+
+    /// Load the bitcode file of each translation unit as a separate module and link them
+    /// logically, reusing the conversion of units that are unchanged
+    pub fn load_program(&self, units: &[(PathBuf, PathBuf)]) -> EngineResult<Program> {
+        let mut modules = BTreeMap::new();
+        for (source, bitcode) in units {
+            let name = source.to_string_lossy().to_string();
+            let module = self.load_or_reuse(bitcode)?;
+            if modules.insert(name, module).is_some() {
+                return Err(EngineError::InvalidAssumption(format!(
+                    "duplicated translation unit: {}",
+                    source.to_string_lossy()
+                )));
+            }
+        }
+        Program::link(modules)
+    }
+
+    /// Save a converted module as a JSON file, e.g., for caching
+    pub fn save(module: &bridge::module::Module, output: &Path) -> EngineResult<()> {
+        let content = serde_json::to_string(module).map_err(|e| {
+            EngineError::InvariantViolation(format!("unable to serialize the module: {}", e))
+        })?;
+        fs::write(output, content).map_err(|e| {
+            EngineError::InvariantViolation(format!("unable to save the module: {}", e))
+        })?;
+        Ok(())
+    }
+
+    /// Restore a converted module from a JSON file produced by `save`
+    pub fn restore(input: &Path) -> EngineResult<bridge::module::Module> {
         let content = fs::read_to_string(input)
             .map_err(|e| EngineError::LLVMLoadingError(format!("Corrupted JSON file: {}", e)))?;
 
         // manually construct the deserializer in order to disable the recursion limit
         let mut deserializer = serde_json::Deserializer::from_str(&content);
         deserializer.disable_recursion_limit();
-        let module_adapted =
-            adapter::module::Module::deserialize(&mut deserializer).map_err(|e| {
-                EngineError::LLVMLoadingError(format!("Error during deserialization: {}", e))
-            })?;
-
-        let module_bridge = bridge::module::Module::convert(&module_adapted)?;
-        Ok(module_bridge)
+        let module = bridge::module::Module::deserialize(&mut deserializer).map_err(|e| {
+            EngineError::LLVMLoadingError(format!("Error during deserialization: {}", e))
+        })?;
+        Ok(module)
     }
 
-    /// Serialize a bitcode file to JSON and then load it as a module
-    pub fn load(&self, input: &Path) -> EngineResult<bridge::module::Module> {
-        let output = input.with_extension("json");
-        self.serialize(input, &output).map_err(|e| {
-            EngineError::LLVMLoadingError(format!("unable to serialize the bitcode file: {}", e))
-        })?;
-        Self::deserialize(&output)
+    /// Serialize a bitcode file and collect a census over it
+    pub fn census(&self, input: &Path) -> EngineResult<Census> {
+        let output = self.serialized_path(input);
+        self.serialize(input, &output)?;
+        let module_adapted = Self::deserialize_adapted(&output)?;
+        Ok(Census::collect(&module_adapted))
     }
 }
@@ -0,0 +1,82 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use log::{info, warn};
+
+use libra_shared::config::PATH_STUDIO;
+
+use crate::flow::manifest::fingerprint;
+use crate::flow::shared::Context;
+
+/// Maximum number of functions to try when locating the crashing one
+static MAX_FUNCTIONS_TO_BISECT: usize = 256;
+
+/// Findings of a triage on an input that crashes the libra pass
+pub struct CrashReport {
+    /// signal that terminates the pass
+    pub signal: i32,
+    /// directory holding the preserved artifacts
+    pub location: PathBuf,
+    /// function on which the pass crashes, if located
+    pub function: Option<String>,
+}
+
+impl CrashReport {
+    /// Triage the crash: preserve the input and try to locate the crashing function
+    pub fn triage(ctxt: &Context, input: &Path, signal: i32, stderr: &str) -> Result<Self> {
+        // preserve the input bitcode and the diagnostics
+        let location = PATH_STUDIO.join("crashes").join(fingerprint(input)?);
+        fs::create_dir_all(&location)?;
+        fs::copy(input, location.join("input.bc"))?;
+        fs::write(location.join("stderr.txt"), stderr)?;
+
+        // try each defined function in isolation
+        let scratch = location.join("scratch");
+        fs::create_dir_all(&scratch)?;
+        let mut function = None;
+        let candidates = ctxt.defined_functions(input)?;
+        if candidates.len() > MAX_FUNCTIONS_TO_BISECT {
+            warn!(
+                "too many functions ({}) to locate the crash, trying the first {} only",
+                candidates.len(),
+                MAX_FUNCTIONS_TO_BISECT
+            );
+        }
+        for name in candidates.into_iter().take(MAX_FUNCTIONS_TO_BISECT) {
+            let extracted = scratch.join("function.bc");
+            if ctxt.extract_function(input, &name, &extracted).is_err() {
+                continue;
+            }
+            let signal = ctxt.serialize_status(&extracted, &scratch.join("function.json"))?;
+            if signal.is_some() {
+                fs::rename(&extracted, location.join("function.bc"))?;
+                function = Some(name);
+                break;
+            }
+        }
+        fs::remove_dir_all(&scratch)?;
+
+        info!(
+            "crash of the libra pass preserved at: {}",
+            location.to_string_lossy()
+        );
+        Ok(Self {
+            signal,
+            location,
+            function,
+        })
+    }
+
+    /// Summarize the report as an error message
+    pub fn describe(&self) -> String {
+        format!(
+            "libra pass terminated by signal {} on function {}, artifacts preserved at {}",
+            self.signal,
+            self.function
+                .as_ref()
+                .map_or_else(|| "<unknown>".to_string(), |name| format!("@{}", name)),
+            self.location.to_string_lossy()
+        )
+    }
+}
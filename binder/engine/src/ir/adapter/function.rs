@@ -16,6 +16,14 @@ pub struct Function {
     pub is_exact: bool,
     /// whether the function is intrinsic
     pub is_intrinsic: bool,
+    /// calling convention
+    pub calling_conv: usize,
+    /// linkage type
+    pub linkage: String,
+    /// function attributes
+    pub attrs: Vec<String>,
+    /// return value attributes
+    pub ret_attrs: ValueAttrs,
     /// parameters
     pub params: Vec<Parameter>,
     /// body of the function
@@ -41,4 +49,21 @@ pub struct Parameter {
     pub in_alloca: Option<Type>,
     /// opaque pointer
     pub element_type: Option<Type>,
+    /// pointer-related attributes
+    pub attrs: ValueAttrs,
+}
+
+/// A representation of LLVM attributes on pointer values
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ValueAttrs {
+    /// attribute: noalias
+    pub no_alias: bool,
+    /// attribute: nonnull
+    pub non_null: bool,
+    /// attribute: align
+    pub align: Option<usize>,
+    /// attribute: by-val
+    pub by_val: Option<Type>,
+    /// attribute: struct-ret
+    pub struct_ret: Option<Type>,
 }
@@ -20,6 +20,12 @@ pub struct GlobalVariable {
     pub is_thread_local: bool,
     /// address space of the global variable
     pub address_space: usize,
+    /// linkage type
+    pub linkage: String,
+    /// visibility
+    pub visibility: String,
+    /// section, if explicitly specified
+    pub section: Option<String>,
     /// initializer
     pub initializer: Option<Constant>,
 }
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::ir::adapter::constant::Constant;
+use crate::ir::adapter::function::ValueAttrs;
 use crate::ir::adapter::typing::Type;
 use crate::ir::adapter::value::{InlineAsm, Value};
 
@@ -11,11 +12,14 @@ pub enum Inst {
     Alloca {
         allocated_type: Type,
         size: Option<Value>,
+        align: usize,
         address_space: usize,
     },
     Load {
         pointee_type: Type,
         pointer: Value,
+        align: usize,
+        is_volatile: bool,
         ordering: String,
         address_space: usize,
     },
@@ -23,6 +27,8 @@ pub enum Inst {
         pointee_type: Type,
         pointer: Value,
         value: Value,
+        align: usize,
+        is_volatile: bool,
         ordering: String,
         address_space: usize,
     },
@@ -33,18 +39,24 @@ pub enum Inst {
     Intrinsic {
         callee: Value,
         target_type: Type,
+        calling_conv: usize,
         args: Vec<Value>,
+        arg_attrs: Vec<ValueAttrs>,
     },
     // call
     CallDirect {
         callee: Value,
         target_type: Type,
+        calling_conv: usize,
         args: Vec<Value>,
+        arg_attrs: Vec<ValueAttrs>,
     },
     CallIndirect {
         callee: Value,
         target_type: Type,
+        calling_conv: usize,
         args: Vec<Value>,
+        arg_attrs: Vec<ValueAttrs>,
     },
     CallAsm {
         asm: InlineAsm,
@@ -58,6 +70,8 @@ pub enum Inst {
     // binary
     Binary {
         opcode: String,
+        no_signed_wrap: bool,
+        no_unsigned_wrap: bool,
         lhs: Value,
         rhs: Value,
     },
@@ -176,14 +190,18 @@ pub enum Inst {
     InvokeDirect {
         callee: Value,
         target_type: Type,
+        calling_conv: usize,
         args: Vec<Value>,
+        arg_attrs: Vec<ValueAttrs>,
         normal: usize,
         unwind: usize,
     },
     InvokeIndirect {
         callee: Value,
         target_type: Type,
+        calling_conv: usize,
         args: Vec<Value>,
+        arg_attrs: Vec<ValueAttrs>,
         normal: usize,
         unwind: usize,
     },
@@ -213,6 +231,18 @@ pub struct Instruction {
     pub index: usize,
     /// the actual representation of an instruction
     pub repr: Inst,
+    /// where the instruction comes from, if debug info is available
+    pub debug: Option<DebugLocation>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DebugLocation {
+    /// source file
+    pub file: String,
+    /// line number, starting from 1
+    pub line: usize,
+    /// column number, 0 if unknown
+    pub column: usize,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
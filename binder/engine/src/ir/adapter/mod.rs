@@ -4,5 +4,7 @@ pub mod function;
 pub mod global;
 pub mod instruction;
 pub mod module;
+#[cfg(feature = "native")]
+pub mod native;
 pub mod typing;
 pub mod value;
@@ -11,6 +11,8 @@ pub struct Module {
     pub name: String,
     /// module-level assembly
     pub asm: String,
+    /// data layout specification
+    pub data_layout: String,
     /// user-defined structs
     pub structs: Vec<UserDefinedStruct>,
     /// global variables
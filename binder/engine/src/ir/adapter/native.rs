@@ -0,0 +1,1458 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Display;
+use std::path::Path;
+
+use either::Either;
+use llvm_ir::function::{CallingConvention, FunctionAttribute, ParameterAttribute};
+use llvm_ir::instruction::{self as ll_inst, Atomicity, MemoryOrdering, RMWBinOp};
+use llvm_ir::module::{Linkage, ThreadLocalMode, Visibility};
+use llvm_ir::types::{FPType, NamedStructDef, Types};
+use llvm_ir::{constant as ll_const, terminator as ll_term};
+use llvm_ir::{ConstantRef, FPPredicate, HasDebugLoc, IntPredicate, Name, Operand, TypeRef, Typed};
+
+use crate::error::{EngineError, EngineResult};
+use crate::ir::adapter::cfg::Block;
+use crate::ir::adapter::constant::{Const, Constant};
+use crate::ir::adapter::function::{Function, Parameter, ValueAttrs};
+use crate::ir::adapter::global::GlobalVariable;
+use crate::ir::adapter::instruction::{DebugLocation, Inst, Instruction, PhiOption, SwitchCase};
+use crate::ir::adapter::module::Module;
+use crate::ir::adapter::typing::{Type, UserDefinedStruct};
+use crate::ir::adapter::value::{InlineAsm, Value};
+
+/// Parse a bitcode file into an adapted module without the libra pass
+///
+/// This mirrors the serialization of the libra pass at a reduced fidelity, as the bitcode reader
+/// does not expose everything the pass sees:
+/// - the value types of global variables without initializers are unknown,
+/// - the wrapping flags on arithmetics and the memory attributes on functions are dropped,
+/// - the text of inline assembly is left empty (which the bridge rejects anyway),
+/// - block addresses, landing pads, and constant GEPs are rejected.
+pub fn parse_bitcode(path: &Path) -> EngineResult<Module> {
+    let module = llvm_ir::Module::from_bc_path(path).map_err(|e| {
+        EngineError::LLVMLoadingError(format!("unable to parse the bitcode file: {}", e))
+    })?;
+    ModuleContext::new(&module).convert(&module)
+}
+
+/// Utility: report a feature the native loading cannot represent
+fn unsupported<T: Display>(item: T) -> EngineError {
+    EngineError::LLVMLoadingError(format!("not supported in native loading: {}", item))
+}
+
+/// Utility: the name of a value, if it is not numbered
+fn name_of(name: &Name) -> Option<String> {
+    match name {
+        Name::Name(text) => Some(text.to_string()),
+        Name::Number(_) => None,
+    }
+}
+
+/// Utility: whether the function is a debug intrinsic, which the libra pass leaves out
+fn is_debug_function(name: &str) -> bool {
+    name.starts_with("llvm.dbg.")
+}
+
+/// Utility: address space of a pointer type
+fn address_space(ty: &Type) -> Option<usize> {
+    match ty {
+        Type::Pointer { address_space } | Type::TypedPointer { address_space, .. } => {
+            Some(*address_space)
+        }
+        _ => None,
+    }
+}
+
+/// Utility: whether the definition of a global value with the linkage is the one at runtime
+fn is_exact(linkage: &Linkage) -> bool {
+    !matches!(
+        linkage,
+        Linkage::WeakODR
+            | Linkage::LinkOnceODR
+            | Linkage::AvailableExternally
+            | Linkage::WeakAny
+            | Linkage::LinkOnceAny
+            | Linkage::Common
+            | Linkage::ExternalWeak
+    )
+}
+
+fn linkage_name(linkage: &Linkage) -> EngineResult<String> {
+    let name = match linkage {
+        Linkage::External => "external",
+        Linkage::AvailableExternally => "available_externally",
+        Linkage::LinkOnceAny => "link_once_any",
+        Linkage::LinkOnceODR => "link_once_odr",
+        Linkage::WeakAny => "weak_any",
+        Linkage::WeakODR => "weak_odr",
+        Linkage::Appending => "appending",
+        Linkage::Internal => "internal",
+        Linkage::Private => "private",
+        Linkage::ExternalWeak => "external_weak",
+        Linkage::Common => "common",
+        _ => return Err(unsupported(format!("linkage {:?}", linkage))),
+    };
+    Ok(name.to_string())
+}
+
+fn visibility_name(visibility: &Visibility) -> String {
+    match visibility {
+        Visibility::Default => "default",
+        Visibility::Hidden => "hidden",
+        Visibility::Protected => "protected",
+    }
+    .to_string()
+}
+
+fn calling_conv(conv: &CallingConvention) -> EngineResult<usize> {
+    let id = match conv {
+        CallingConvention::C => 0,
+        CallingConvention::Fast => 8,
+        CallingConvention::Cold => 9,
+        CallingConvention::Numeric(id) => *id as usize,
+        _ => return Err(unsupported(format!("calling convention {:?}", conv))),
+    };
+    Ok(id)
+}
+
+fn ordering_name(ordering: &MemoryOrdering) -> String {
+    match ordering {
+        MemoryOrdering::NotAtomic => "notatomic",
+        MemoryOrdering::Unordered => "unordered",
+        MemoryOrdering::Monotonic => "monotonic",
+        MemoryOrdering::Acquire => "acquire",
+        MemoryOrdering::Release => "release",
+        MemoryOrdering::AcquireRelease => "acq_rel",
+        MemoryOrdering::SequentiallyConsistent => "seq_cst",
+    }
+    .to_string()
+}
+
+fn atomic_ordering(atomicity: Option<&Atomicity>) -> String {
+    atomicity.map_or_else(
+        || ordering_name(&MemoryOrdering::NotAtomic),
+        |item| ordering_name(&item.mem_ordering),
+    )
+}
+
+fn scope_name(atomicity: &Atomicity) -> String {
+    match atomicity.synch_scope {
+        ll_inst::SynchronizationScope::SingleThread => "thread",
+        ll_inst::SynchronizationScope::System => "system",
+    }
+    .to_string()
+}
+
+fn int_predicate(predicate: &IntPredicate) -> &'static str {
+    match predicate {
+        IntPredicate::EQ => "i_eq",
+        IntPredicate::NE => "i_ne",
+        IntPredicate::UGT => "i_ugt",
+        IntPredicate::UGE => "i_uge",
+        IntPredicate::ULT => "i_ult",
+        IntPredicate::ULE => "i_ule",
+        IntPredicate::SGT => "i_sgt",
+        IntPredicate::SGE => "i_sge",
+        IntPredicate::SLT => "i_slt",
+        IntPredicate::SLE => "i_sle",
+    }
+}
+
+fn float_predicate(predicate: &FPPredicate) -> &'static str {
+    match predicate {
+        FPPredicate::False => "f_false",
+        FPPredicate::OEQ => "f_oeq",
+        FPPredicate::OGT => "f_ogt",
+        FPPredicate::OGE => "f_oge",
+        FPPredicate::OLT => "f_olt",
+        FPPredicate::OLE => "f_ole",
+        FPPredicate::ONE => "f_one",
+        FPPredicate::ORD => "f_ord",
+        FPPredicate::UNO => "f_uno",
+        FPPredicate::UEQ => "f_ueq",
+        FPPredicate::UGT => "f_ugt",
+        FPPredicate::UGE => "f_uge",
+        FPPredicate::ULT => "f_ult",
+        FPPredicate::ULE => "f_ule",
+        FPPredicate::UNE => "f_une",
+        FPPredicate::True => "f_true",
+    }
+}
+
+fn rmw_opcode(operation: &RMWBinOp) -> EngineResult<String> {
+    let opcode = match operation {
+        RMWBinOp::Xchg => "xchg",
+        RMWBinOp::Add => "add",
+        RMWBinOp::Sub => "sub",
+        RMWBinOp::And => "and",
+        RMWBinOp::Nand => "nand",
+        RMWBinOp::Or => "or",
+        RMWBinOp::Xor => "xor",
+        RMWBinOp::Max => "max",
+        RMWBinOp::Min => "min",
+        RMWBinOp::UMax => "umax",
+        RMWBinOp::UMin => "umin",
+        RMWBinOp::FAdd => "fadd",
+        RMWBinOp::FSub => "fsub",
+        _ => return Err(unsupported(format!("atomic-rmw operator {:?}", operation))),
+    };
+    Ok(opcode.to_string())
+}
+
+fn binary_inst(opcode: &str, lhs: Value, rhs: Value) -> Inst {
+    Inst::Binary {
+        opcode: opcode.to_string(),
+        // the bitcode reader does not expose the wrapping flags
+        no_signed_wrap: false,
+        no_unsigned_wrap: false,
+        lhs,
+        rhs,
+    }
+}
+
+fn compare_inst(predicate: &str, lhs: Value, rhs: Value) -> Inst {
+    Inst::Compare {
+        predicate: predicate.to_string(),
+        operand_type: lhs.get_type().clone(),
+        lhs,
+        rhs,
+    }
+}
+
+fn cast_inst(opcode: &str, dst_ty: Type, operand: Value) -> Inst {
+    let src_ty = operand.get_type().clone();
+    let (src_address_space, dst_address_space) = match opcode {
+        "ptr_to_int" => (address_space(&src_ty), None),
+        "int_to_ptr" => (None, address_space(&dst_ty)),
+        "address_space_cast" => (address_space(&src_ty), address_space(&dst_ty)),
+        _ => (None, None),
+    };
+    Inst::Cast {
+        opcode: opcode.to_string(),
+        src_ty,
+        dst_ty,
+        src_address_space,
+        dst_address_space,
+        operand,
+    }
+}
+
+/// Context of the conversion over a module
+struct ModuleContext<'a> {
+    /// type registry of the module
+    types: &'a Types,
+    /// names of the functions, including declarations
+    functions: BTreeSet<&'a str>,
+    /// names of the global aliases
+    aliases: BTreeSet<&'a str>,
+}
+
+impl<'a> ModuleContext<'a> {
+    fn new(module: &'a llvm_ir::Module) -> Self {
+        let functions = module
+            .functions
+            .iter()
+            .map(|func| func.name.as_str())
+            .chain(
+                module
+                    .func_declarations
+                    .iter()
+                    .map(|decl| decl.name.as_str()),
+            )
+            .collect();
+        let aliases = module
+            .global_aliases
+            .iter()
+            .filter_map(|alias| match &alias.name {
+                Name::Name(text) => Some(text.as_str()),
+                Name::Number(_) => None,
+            })
+            .collect();
+        Self {
+            types: &module.types,
+            functions,
+            aliases,
+        }
+    }
+
+    fn convert(&self, module: &llvm_ir::Module) -> EngineResult<Module> {
+        let mut structs = vec![];
+        for name in self.types.all_struct_names() {
+            let (fields, packed) = self.struct_def(name)?;
+            structs.push(UserDefinedStruct {
+                name: Some(name.to_string()),
+                fields,
+                packed,
+            });
+        }
+
+        let global_variables = module
+            .global_vars
+            .iter()
+            .map(|gvar| self.global_variable(gvar))
+            .collect::<EngineResult<_>>()?;
+
+        let mut functions = vec![];
+        for func in &module.functions {
+            functions.push(FunctionContext::new(self, func).convert(func)?);
+        }
+        for decl in &module.func_declarations {
+            if is_debug_function(&decl.name) {
+                continue;
+            }
+            functions.push(self.function_declaration(decl)?);
+        }
+
+        Ok(Module {
+            name: module.name.clone(),
+            asm: module.inline_assembly.clone(),
+            data_layout: module.data_layout.layout_str.clone(),
+            structs,
+            global_variables,
+            functions,
+        })
+    }
+
+    /// Fields and packing of a named struct, with no fields if it is opaque
+    fn struct_def(&self, name: &str) -> EngineResult<(Option<Vec<Type>>, bool)> {
+        match self.types.named_struct_def(name) {
+            None => Err(EngineError::LLVMLoadingError(format!(
+                "unknown struct type: {}",
+                name
+            ))),
+            Some(NamedStructDef::Opaque) => Ok((None, false)),
+            Some(NamedStructDef::Defined(ty)) => match ty.as_ref() {
+                llvm_ir::Type::StructType {
+                    element_types,
+                    is_packed,
+                } => {
+                    let fields = element_types
+                        .iter()
+                        .map(|field| self.ty(field))
+                        .collect::<EngineResult<_>>()?;
+                    Ok((Some(fields), *is_packed))
+                }
+                _ => Err(EngineError::LLVMLoadingError(format!(
+                    "struct {} defined as a non-struct type",
+                    name
+                ))),
+            },
+        }
+    }
+
+    fn ty(&self, ty: &TypeRef) -> EngineResult<Type> {
+        let converted = match ty.as_ref() {
+            llvm_ir::Type::VoidType => Type::Void,
+            llvm_ir::Type::IntegerType { bits } => Type::Int {
+                width: *bits as usize,
+            },
+            llvm_ir::Type::X86_MMXType => Type::Int { width: 64 },
+            llvm_ir::Type::X86_AMXType => Type::Int { width: 8192 },
+            llvm_ir::Type::FPType(fp) => {
+                let (width, name) = match fp {
+                    FPType::Half => (16, "half"),
+                    FPType::BFloat => (16, "bfloat"),
+                    FPType::Single => (32, "float"),
+                    FPType::Double => (64, "double"),
+                    FPType::X86_FP80 => (80, "x86_fp80"),
+                    FPType::FP128 => (128, "fp128"),
+                    FPType::PPC_FP128 => (128, "ppc_fp128"),
+                };
+                Type::Float {
+                    width,
+                    name: name.to_string(),
+                }
+            }
+            llvm_ir::Type::ArrayType {
+                element_type,
+                num_elements,
+            } => Type::Array {
+                element: Box::new(self.ty(element_type)?),
+                length: *num_elements,
+            },
+            llvm_ir::Type::StructType {
+                element_types,
+                is_packed,
+            } => Type::Struct {
+                name: None,
+                fields: Some(
+                    element_types
+                        .iter()
+                        .map(|field| self.ty(field))
+                        .collect::<EngineResult<_>>()?,
+                ),
+                packed: *is_packed,
+            },
+            llvm_ir::Type::NamedStructType { name } => {
+                let (fields, packed) = self.struct_def(name)?;
+                Type::Struct {
+                    name: Some(name.clone()),
+                    fields,
+                    packed,
+                }
+            }
+            llvm_ir::Type::FuncType {
+                result_type,
+                param_types,
+                is_var_arg,
+            } => Type::Function {
+                params: param_types
+                    .iter()
+                    .map(|param| self.ty(param))
+                    .collect::<EngineResult<_>>()?,
+                variadic: *is_var_arg,
+                ret: Box::new(self.ty(result_type)?),
+            },
+            llvm_ir::Type::PointerType { addr_space, .. } => Type::Pointer {
+                address_space: *addr_space as usize,
+            },
+            llvm_ir::Type::VectorType {
+                element_type,
+                num_elements,
+                scalable,
+            } => Type::Vector {
+                element: Box::new(self.ty(element_type)?),
+                fixed: !*scalable,
+                length: *num_elements,
+            },
+            llvm_ir::Type::LabelType => Type::Label,
+            llvm_ir::Type::TokenType => Type::Token,
+            llvm_ir::Type::MetadataType => Type::Metadata,
+            _ => return Err(unsupported(format!("type {}", ty))),
+        };
+        Ok(converted)
+    }
+
+    fn type_of<T: Typed>(&self, item: &T) -> EngineResult<Type> {
+        self.ty(&item.get_type(self.types))
+    }
+
+    /// Element type reached by the indices of a GEP, where the first index steps over the source
+    fn indexed_type(&self, source: &TypeRef, indices: &[&Operand]) -> EngineResult<TypeRef> {
+        let mut current = source.clone();
+        for index in indices.iter().skip(1) {
+            let next = match current.as_ref() {
+                llvm_ir::Type::ArrayType { element_type, .. }
+                | llvm_ir::Type::VectorType { element_type, .. } => element_type.clone(),
+                llvm_ir::Type::StructType { element_types, .. } => {
+                    Self::field_of(element_types, index)?
+                }
+                llvm_ir::Type::NamedStructType { name } => {
+                    match self.types.named_struct_def(name) {
+                        Some(NamedStructDef::Defined(ty)) => match ty.as_ref() {
+                            llvm_ir::Type::StructType { element_types, .. } => {
+                                Self::field_of(element_types, index)?
+                            }
+                            _ => return Err(unsupported(format!("GEP into {}", ty))),
+                        },
+                        _ => return Err(unsupported(format!("GEP into opaque struct {}", name))),
+                    }
+                }
+                _ => return Err(unsupported(format!("GEP into {}", current))),
+            };
+            current = next;
+        }
+        Ok(current)
+    }
+
+    fn field_of(fields: &[TypeRef], index: &Operand) -> EngineResult<TypeRef> {
+        let slot = match index {
+            Operand::ConstantOperand(constant) => match constant.as_ref() {
+                llvm_ir::Constant::Int { value, .. } => *value as usize,
+                _ => return Err(unsupported("non-integer struct index in GEP")),
+            },
+            _ => return Err(unsupported("non-constant struct index in GEP")),
+        };
+        fields.get(slot).cloned().ok_or_else(|| {
+            EngineError::LLVMLoadingError(format!("struct index out of bound in GEP: {}", slot))
+        })
+    }
+
+    fn global_variable(
+        &self,
+        gvar: &llvm_ir::module::GlobalVariable,
+    ) -> EngineResult<GlobalVariable> {
+        let initializer = gvar
+            .initializer
+            .as_ref()
+            .map(|init| self.constant(init))
+            .transpose()?;
+        // the reader only exposes the pointer type of a global variable, hence the value type is
+        // recovered from the initializer and left as an empty array for declarations
+        let ty = match &initializer {
+            Some(init) => init.ty.clone(),
+            None => Type::Array {
+                element: Box::new(Type::Int { width: 8 }),
+                length: 0,
+            },
+        };
+        Ok(GlobalVariable {
+            name: name_of(&gvar.name),
+            ty,
+            is_defined: initializer.is_some(),
+            is_exact: is_exact(&gvar.linkage),
+            is_const: gvar.is_constant,
+            is_thread_local: !matches!(gvar.thread_local_mode, ThreadLocalMode::NotThreadLocal),
+            address_space: gvar.addr_space as usize,
+            linkage: linkage_name(&gvar.linkage)?,
+            visibility: visibility_name(&gvar.visibility),
+            section: gvar.section.clone(),
+            initializer,
+        })
+    }
+
+    fn function_declaration(
+        &self,
+        decl: &llvm_ir::function::FunctionDeclaration,
+    ) -> EngineResult<Function> {
+        let params = decl
+            .parameters
+            .iter()
+            .map(|param| self.parameter(param))
+            .collect::<EngineResult<Vec<_>>>()?;
+        Ok(Function {
+            name: Some(decl.name.clone()),
+            ty: Type::Function {
+                params: params.iter().map(|param| param.ty.clone()).collect(),
+                variadic: decl.is_var_arg,
+                ret: Box::new(self.ty(&decl.return_type)?),
+            },
+            is_defined: false,
+            is_exact: is_exact(&decl.linkage),
+            is_intrinsic: decl.name.starts_with("llvm."),
+            calling_conv: calling_conv(&decl.calling_convention)?,
+            linkage: linkage_name(&decl.linkage)?,
+            attrs: vec![],
+            ret_attrs: self.value_attrs(&decl.return_attributes)?,
+            params,
+            blocks: vec![],
+        })
+    }
+
+    fn parameter(&self, param: &llvm_ir::function::Parameter) -> EngineResult<Parameter> {
+        let mut converted = Parameter {
+            name: name_of(&param.name),
+            ty: self.ty(&param.ty)?,
+            by_val: None,
+            by_ref: None,
+            pre_allocated: None,
+            struct_ret: None,
+            in_alloca: None,
+            element_type: None,
+            attrs: self.value_attrs(&param.attributes)?,
+        };
+        for attr in &param.attributes {
+            match attr {
+                ParameterAttribute::ByVal(ty) => converted.by_val = Some(self.ty(ty)?),
+                ParameterAttribute::ByRef(ty) => converted.by_ref = Some(self.ty(ty)?),
+                ParameterAttribute::Preallocated(ty) => {
+                    converted.pre_allocated = Some(self.ty(ty)?)
+                }
+                ParameterAttribute::SRet(ty) => converted.struct_ret = Some(self.ty(ty)?),
+                ParameterAttribute::InAlloca(ty) => converted.in_alloca = Some(self.ty(ty)?),
+                _ => (),
+            }
+        }
+        Ok(converted)
+    }
+
+    fn value_attrs(&self, attrs: &[ParameterAttribute]) -> EngineResult<ValueAttrs> {
+        let mut converted = ValueAttrs {
+            no_alias: false,
+            non_null: false,
+            align: None,
+            by_val: None,
+            struct_ret: None,
+        };
+        for attr in attrs {
+            match attr {
+                ParameterAttribute::NoAlias => converted.no_alias = true,
+                ParameterAttribute::NonNull => converted.non_null = true,
+                ParameterAttribute::Alignment(align) => converted.align = Some(*align as usize),
+                ParameterAttribute::ByVal(ty) => converted.by_val = Some(self.ty(ty)?),
+                ParameterAttribute::SRet(ty) => converted.struct_ret = Some(self.ty(ty)?),
+                _ => (),
+            }
+        }
+        Ok(converted)
+    }
+
+    /// Name of the function a constant refers to directly, if any
+    fn function_ref<'c>(&self, constant: &'c ConstantRef) -> Option<&'c str> {
+        match constant.as_ref() {
+            llvm_ir::Constant::GlobalReference {
+                name: Name::Name(text),
+                ..
+            } if self.functions.contains(text.as_str()) => Some(text.as_str()),
+            _ => None,
+        }
+    }
+
+    fn constant(&self, constant: &ConstantRef) -> EngineResult<Constant> {
+        let ty = self.type_of(constant.as_ref())?;
+        let repr = match constant.as_ref() {
+            llvm_ir::Constant::Int { bits, value } => {
+                if *bits > 64 {
+                    return Err(unsupported(format!("integer constant of {} bits", bits)));
+                }
+                Const::Int {
+                    value: value.to_string(),
+                }
+            }
+            llvm_ir::Constant::Float(float) => {
+                let value = match float {
+                    ll_const::Float::Single(val) => val.to_string(),
+                    ll_const::Float::Double(val) => val.to_string(),
+                    _ => return Err(unsupported(format!("float constant {}", constant))),
+                };
+                Const::Float { value }
+            }
+            llvm_ir::Constant::Null(_) => Const::Null,
+            llvm_ir::Constant::AggregateZero(_) => Const::Default,
+            llvm_ir::Constant::Undef(_) | llvm_ir::Constant::Poison(_) => Const::Undef,
+            llvm_ir::Constant::TokenNone => Const::None,
+            llvm_ir::Constant::Struct { values, .. } => Const::Struct {
+                elements: self.constants(values)?,
+            },
+            llvm_ir::Constant::Array { elements, .. } => Const::Array {
+                elements: self.constants(elements)?,
+            },
+            llvm_ir::Constant::Vector(elements) => Const::Vector {
+                elements: self.constants(elements)?,
+            },
+            llvm_ir::Constant::GlobalReference { name, .. } => {
+                let text = name_of(name);
+                let is_in = |names: &BTreeSet<&str>| {
+                    text.as_deref().is_some_and(|item| names.contains(item))
+                };
+                if is_in(&self.functions) {
+                    Const::Function { name: text }
+                } else if is_in(&self.aliases) {
+                    Const::Alias { name: text }
+                } else {
+                    Const::Variable { name: text }
+                }
+            }
+            llvm_ir::Constant::Add(ll_const::Add {
+                operand0, operand1, ..
+            }) => self.const_binary("add", operand0, operand1)?,
+            llvm_ir::Constant::Sub(ll_const::Sub {
+                operand0, operand1, ..
+            }) => self.const_binary("sub", operand0, operand1)?,
+            llvm_ir::Constant::Mul(ll_const::Mul {
+                operand0, operand1, ..
+            }) => self.const_binary("mul", operand0, operand1)?,
+            llvm_ir::Constant::Xor(ll_const::Xor {
+                operand0, operand1, ..
+            }) => self.const_binary("xor", operand0, operand1)?,
+            llvm_ir::Constant::Shl(ll_const::Shl {
+                operand0, operand1, ..
+            }) => self.const_binary("shl", operand0, operand1)?,
+            llvm_ir::Constant::ICmp(ll_const::ICmp {
+                predicate,
+                operand0,
+                operand1,
+                ..
+            }) => Const::Expr {
+                inst: Box::new(compare_inst(
+                    int_predicate(predicate),
+                    Value::Constant(self.constant(operand0)?),
+                    Value::Constant(self.constant(operand1)?),
+                )),
+            },
+            llvm_ir::Constant::Trunc(ll_const::Trunc { operand, .. }) => {
+                self.const_cast("trunc", &ty, operand)?
+            }
+            llvm_ir::Constant::PtrToInt(ll_const::PtrToInt { operand, .. }) => {
+                self.const_cast("ptr_to_int", &ty, operand)?
+            }
+            llvm_ir::Constant::IntToPtr(ll_const::IntToPtr { operand, .. }) => {
+                self.const_cast("int_to_ptr", &ty, operand)?
+            }
+            llvm_ir::Constant::BitCast(ll_const::BitCast { operand, .. }) => {
+                self.const_cast("bitcast", &ty, operand)?
+            }
+            llvm_ir::Constant::AddrSpaceCast(ll_const::AddrSpaceCast { operand, .. }) => {
+                self.const_cast("address_space_cast", &ty, operand)?
+            }
+            // block addresses are opaque and constant GEPs lack their source element type in the
+            // bitcode reader
+            _ => return Err(unsupported(format!("constant {}", constant))),
+        };
+        Ok(Constant { ty, repr })
+    }
+
+    fn constants(&self, constants: &[ConstantRef]) -> EngineResult<Vec<Constant>> {
+        constants.iter().map(|item| self.constant(item)).collect()
+    }
+
+    fn const_binary(
+        &self,
+        opcode: &str,
+        lhs: &ConstantRef,
+        rhs: &ConstantRef,
+    ) -> EngineResult<Const> {
+        Ok(Const::Expr {
+            inst: Box::new(binary_inst(
+                opcode,
+                Value::Constant(self.constant(lhs)?),
+                Value::Constant(self.constant(rhs)?),
+            )),
+        })
+    }
+
+    fn const_cast(
+        &self,
+        opcode: &str,
+        dst_ty: &Type,
+        operand: &ConstantRef,
+    ) -> EngineResult<Const> {
+        Ok(Const::Expr {
+            inst: Box::new(cast_inst(
+                opcode,
+                dst_ty.clone(),
+                Value::Constant(self.constant(operand)?),
+            )),
+        })
+    }
+}
+
+/// Context of the conversion within a function, numbering its blocks, instructions, and
+/// arguments in the same way as the libra pass
+struct FunctionContext<'a> {
+    /// context of the module
+    module: &'a ModuleContext<'a>,
+    /// labels of the blocks
+    blocks: HashMap<&'a Name, usize>,
+    /// indices of the instructions with a result
+    insts: HashMap<&'a Name, usize>,
+    /// indices of the arguments
+    args: HashMap<&'a Name, usize>,
+}
+
+impl<'a> FunctionContext<'a> {
+    fn new(module: &'a ModuleContext<'a>, func: &'a llvm_ir::Function) -> Self {
+        let args = func
+            .parameters
+            .iter()
+            .enumerate()
+            .map(|(index, param)| (&param.name, index))
+            .collect();
+
+        // every instruction counts towards the indices, including the debug ones left out
+        let mut blocks = HashMap::new();
+        let mut insts = HashMap::new();
+        let mut counter = 0;
+        for (label, block) in func.basic_blocks.iter().enumerate() {
+            blocks.insert(&block.name, label);
+            for inst in &block.instrs {
+                if let Some(name) = inst.try_get_result() {
+                    insts.insert(name, counter);
+                }
+                counter += 1;
+            }
+            if let llvm_ir::Terminator::Invoke(invoke) = &block.term {
+                insts.insert(&invoke.result, counter);
+            }
+            counter += 1;
+        }
+
+        Self {
+            module,
+            blocks,
+            insts,
+            args,
+        }
+    }
+
+    fn convert(&self, func: &llvm_ir::Function) -> EngineResult<Function> {
+        let module = self.module;
+        let params = func
+            .parameters
+            .iter()
+            .map(|param| module.parameter(param))
+            .collect::<EngineResult<Vec<_>>>()?;
+
+        let mut attrs = vec![];
+        if func
+            .function_attributes
+            .contains(&FunctionAttribute::NoReturn)
+        {
+            attrs.push("noreturn".to_string());
+        }
+        if func
+            .function_attributes
+            .contains(&FunctionAttribute::NoUnwind)
+        {
+            attrs.push("nounwind".to_string());
+        }
+
+        let mut blocks = vec![];
+        let mut counter = 0;
+        for block in &func.basic_blocks {
+            let mut body = vec![];
+            for inst in &block.instrs {
+                let index = counter;
+                counter += 1;
+                if self.is_debug_instruction(inst) {
+                    continue;
+                }
+                body.push(Instruction {
+                    name: inst.try_get_result().and_then(name_of),
+                    ty: module.type_of(inst)?,
+                    index,
+                    repr: self.inst(inst)?,
+                    debug: Self::debug_location(inst),
+                });
+            }
+            let terminator = Instruction {
+                name: match &block.term {
+                    llvm_ir::Terminator::Invoke(invoke) => name_of(&invoke.result),
+                    _ => None,
+                },
+                ty: module.type_of(&block.term)?,
+                index: counter,
+                repr: self.terminator(&block.term)?,
+                debug: Self::debug_location(&block.term),
+            };
+            counter += 1;
+            blocks.push(Block {
+                label: self.block(&block.name)?,
+                name: name_of(&block.name),
+                body,
+                terminator,
+            });
+        }
+
+        Ok(Function {
+            name: Some(func.name.clone()),
+            ty: Type::Function {
+                params: params.iter().map(|param| param.ty.clone()).collect(),
+                variadic: func.is_var_arg,
+                ret: Box::new(module.ty(&func.return_type)?),
+            },
+            is_defined: true,
+            is_exact: is_exact(&func.linkage),
+            is_intrinsic: func.name.starts_with("llvm."),
+            calling_conv: calling_conv(&func.calling_convention)?,
+            linkage: linkage_name(&func.linkage)?,
+            attrs,
+            ret_attrs: module.value_attrs(&func.return_attributes)?,
+            params,
+            blocks,
+        })
+    }
+
+    fn debug_location<T: HasDebugLoc>(item: &T) -> Option<DebugLocation> {
+        item.get_debug_loc().as_ref().map(|loc| DebugLocation {
+            file: loc.filename.clone(),
+            line: loc.line as usize,
+            column: loc.col.unwrap_or(0) as usize,
+        })
+    }
+
+    fn is_debug_instruction(&self, inst: &llvm_ir::Instruction) -> bool {
+        match inst {
+            llvm_ir::Instruction::Call(call) => match &call.function {
+                Either::Right(Operand::ConstantOperand(callee)) => self
+                    .module
+                    .function_ref(callee)
+                    .is_some_and(is_debug_function),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn block(&self, name: &Name) -> EngineResult<usize> {
+        self.blocks
+            .get(name)
+            .copied()
+            .ok_or_else(|| EngineError::LLVMLoadingError(format!("unknown block: {}", name)))
+    }
+
+    fn value(&self, operand: &Operand) -> EngineResult<Value> {
+        let converted = match operand {
+            Operand::LocalOperand { name, ty } => {
+                let ty = self.module.ty(ty)?;
+                if let Some(index) = self.args.get(name) {
+                    Value::Argument { ty, index: *index }
+                } else if let Some(index) = self.insts.get(name) {
+                    Value::Instruction { ty, index: *index }
+                } else {
+                    return Err(EngineError::LLVMLoadingError(format!(
+                        "unknown local value: {}",
+                        name
+                    )));
+                }
+            }
+            Operand::ConstantOperand(constant) => Value::Constant(self.module.constant(constant)?),
+            Operand::MetadataOperand => Value::Metadata,
+        };
+        Ok(converted)
+    }
+
+    fn values(&self, operands: &[&Operand]) -> EngineResult<Vec<Value>> {
+        operands.iter().map(|item| self.value(item)).collect()
+    }
+
+    fn binary(&self, opcode: &str, lhs: &Operand, rhs: &Operand) -> EngineResult<Inst> {
+        Ok(binary_inst(opcode, self.value(lhs)?, self.value(rhs)?))
+    }
+
+    fn cast(&self, opcode: &str, operand: &Operand, to_type: &TypeRef) -> EngineResult<Inst> {
+        Ok(cast_inst(
+            opcode,
+            self.module.ty(to_type)?,
+            self.value(operand)?,
+        ))
+    }
+
+    fn pointer_address_space(&self, pointer: &Operand) -> EngineResult<usize> {
+        Ok(address_space(&self.module.type_of(pointer)?).unwrap_or(0))
+    }
+
+    /// Convert a call-like instruction, with everything but the successors on an invoke
+    fn call(
+        &self,
+        function: &Either<ll_inst::InlineAssembly, Operand>,
+        function_ty: &TypeRef,
+        arguments: &[(Operand, Vec<ParameterAttribute>)],
+        conv: &CallingConvention,
+    ) -> EngineResult<Inst> {
+        let args = arguments
+            .iter()
+            .map(|(arg, _)| self.value(arg))
+            .collect::<EngineResult<Vec<_>>>()?;
+        let callee = match function {
+            // the reader does not expose the text of inline assembly
+            Either::Left(_) => {
+                return Ok(Inst::CallAsm {
+                    asm: InlineAsm {
+                        asm: String::new(),
+                        constraint: String::new(),
+                    },
+                    args,
+                });
+            }
+            Either::Right(callee) => callee,
+        };
+        let arg_attrs = arguments
+            .iter()
+            .map(|(_, attrs)| self.module.value_attrs(attrs))
+            .collect::<EngineResult<_>>()?;
+        let target_name = match callee {
+            Operand::ConstantOperand(constant) => self.module.function_ref(constant),
+            _ => None,
+        };
+
+        let callee = self.value(callee)?;
+        let target_type = self.module.ty(function_ty)?;
+        let calling_conv = calling_conv(conv)?;
+        let converted = match target_name {
+            Some(name) if name.starts_with("llvm.") => Inst::Intrinsic {
+                callee,
+                target_type,
+                calling_conv,
+                args,
+                arg_attrs,
+            },
+            Some(_) => Inst::CallDirect {
+                callee,
+                target_type,
+                calling_conv,
+                args,
+                arg_attrs,
+            },
+            None => Inst::CallIndirect {
+                callee,
+                target_type,
+                calling_conv,
+                args,
+                arg_attrs,
+            },
+        };
+        Ok(converted)
+    }
+
+    fn inst(&self, inst: &llvm_ir::Instruction) -> EngineResult<Inst> {
+        use llvm_ir::Instruction as I;
+
+        let module = self.module;
+        let converted = match inst {
+            // memory
+            I::Alloca(ll_inst::Alloca {
+                allocated_type,
+                num_elements,
+                alignment,
+                ..
+            }) => {
+                let is_single = matches!(
+                    num_elements,
+                    Operand::ConstantOperand(constant)
+                        if matches!(constant.as_ref(), llvm_ir::Constant::Int { value: 1, .. })
+                );
+                Inst::Alloca {
+                    allocated_type: module.ty(allocated_type)?,
+                    size: if is_single {
+                        None
+                    } else {
+                        Some(self.value(num_elements)?)
+                    },
+                    align: *alignment as usize,
+                    address_space: address_space(&module.type_of(inst)?).unwrap_or(0),
+                }
+            }
+            I::Load(ll_inst::Load {
+                address,
+                volatile,
+                atomicity,
+                alignment,
+                ..
+            }) => Inst::Load {
+                pointee_type: module.type_of(inst)?,
+                pointer: self.value(address)?,
+                align: *alignment as usize,
+                is_volatile: *volatile,
+                ordering: atomic_ordering(atomicity.as_ref()),
+                address_space: self.pointer_address_space(address)?,
+            },
+            I::Store(ll_inst::Store {
+                address,
+                value,
+                volatile,
+                atomicity,
+                alignment,
+                ..
+            }) => Inst::Store {
+                pointee_type: module.type_of(value)?,
+                pointer: self.value(address)?,
+                value: self.value(value)?,
+                align: *alignment as usize,
+                is_volatile: *volatile,
+                ordering: atomic_ordering(atomicity.as_ref()),
+                address_space: self.pointer_address_space(address)?,
+            },
+            I::VAArg(ll_inst::VAArg { arg_list, .. }) => Inst::VAArg {
+                pointer: self.value(arg_list)?,
+            },
+
+            // call
+            I::Call(ll_inst::Call {
+                function,
+                function_ty,
+                arguments,
+                calling_convention,
+                ..
+            }) => self.call(function, function_ty, arguments, calling_convention)?,
+
+            // unary and binary
+            I::FNeg(ll_inst::FNeg { operand, .. }) => Inst::Unary {
+                opcode: "fneg".to_string(),
+                operand: self.value(operand)?,
+            },
+            I::Add(ll_inst::Add {
+                operand0, operand1, ..
+            }) => self.binary("add", operand0, operand1)?,
+            I::Sub(ll_inst::Sub {
+                operand0, operand1, ..
+            }) => self.binary("sub", operand0, operand1)?,
+            I::Mul(ll_inst::Mul {
+                operand0, operand1, ..
+            }) => self.binary("mul", operand0, operand1)?,
+            I::UDiv(ll_inst::UDiv {
+                operand0, operand1, ..
+            }) => self.binary("udiv", operand0, operand1)?,
+            I::SDiv(ll_inst::SDiv {
+                operand0, operand1, ..
+            }) => self.binary("sdiv", operand0, operand1)?,
+            I::URem(ll_inst::URem {
+                operand0, operand1, ..
+            }) => self.binary("urem", operand0, operand1)?,
+            I::SRem(ll_inst::SRem {
+                operand0, operand1, ..
+            }) => self.binary("srem", operand0, operand1)?,
+            I::And(ll_inst::And {
+                operand0, operand1, ..
+            }) => self.binary("and", operand0, operand1)?,
+            I::Or(ll_inst::Or {
+                operand0, operand1, ..
+            }) => self.binary("or", operand0, operand1)?,
+            I::Xor(ll_inst::Xor {
+                operand0, operand1, ..
+            }) => self.binary("xor", operand0, operand1)?,
+            I::Shl(ll_inst::Shl {
+                operand0, operand1, ..
+            }) => self.binary("shl", operand0, operand1)?,
+            I::LShr(ll_inst::LShr {
+                operand0, operand1, ..
+            }) => self.binary("lshr", operand0, operand1)?,
+            I::AShr(ll_inst::AShr {
+                operand0, operand1, ..
+            }) => self.binary("ashr", operand0, operand1)?,
+            I::FAdd(ll_inst::FAdd {
+                operand0, operand1, ..
+            }) => self.binary("fadd", operand0, operand1)?,
+            I::FSub(ll_inst::FSub {
+                operand0, operand1, ..
+            }) => self.binary("fsub", operand0, operand1)?,
+            I::FMul(ll_inst::FMul {
+                operand0, operand1, ..
+            }) => self.binary("fmul", operand0, operand1)?,
+            I::FDiv(ll_inst::FDiv {
+                operand0, operand1, ..
+            }) => self.binary("fdiv", operand0, operand1)?,
+            I::FRem(ll_inst::FRem {
+                operand0, operand1, ..
+            }) => self.binary("frem", operand0, operand1)?,
+
+            // comparison
+            I::ICmp(ll_inst::ICmp {
+                predicate,
+                operand0,
+                operand1,
+                ..
+            }) => compare_inst(
+                int_predicate(predicate),
+                self.value(operand0)?,
+                self.value(operand1)?,
+            ),
+            I::FCmp(ll_inst::FCmp {
+                predicate,
+                operand0,
+                operand1,
+                ..
+            }) => compare_inst(
+                float_predicate(predicate),
+                self.value(operand0)?,
+                self.value(operand1)?,
+            ),
+
+            // cast
+            I::Trunc(ll_inst::Trunc {
+                operand, to_type, ..
+            }) => self.cast("trunc", operand, to_type)?,
+            I::ZExt(ll_inst::ZExt {
+                operand, to_type, ..
+            }) => self.cast("zext", operand, to_type)?,
+            I::SExt(ll_inst::SExt {
+                operand, to_type, ..
+            }) => self.cast("sext", operand, to_type)?,
+            I::FPToUI(ll_inst::FPToUI {
+                operand, to_type, ..
+            }) => self.cast("fp_to_ui", operand, to_type)?,
+            I::FPToSI(ll_inst::FPToSI {
+                operand, to_type, ..
+            }) => self.cast("fp_to_si", operand, to_type)?,
+            I::UIToFP(ll_inst::UIToFP {
+                operand, to_type, ..
+            }) => self.cast("ui_to_fp", operand, to_type)?,
+            I::SIToFP(ll_inst::SIToFP {
+                operand, to_type, ..
+            }) => self.cast("si_to_fp", operand, to_type)?,
+            I::FPTrunc(ll_inst::FPTrunc {
+                operand, to_type, ..
+            }) => self.cast("fp_trunc", operand, to_type)?,
+            I::FPExt(ll_inst::FPExt {
+                operand, to_type, ..
+            }) => self.cast("fp_ext", operand, to_type)?,
+            I::PtrToInt(ll_inst::PtrToInt {
+                operand, to_type, ..
+            }) => self.cast("ptr_to_int", operand, to_type)?,
+            I::IntToPtr(ll_inst::IntToPtr {
+                operand, to_type, ..
+            }) => self.cast("int_to_ptr", operand, to_type)?,
+            I::BitCast(ll_inst::BitCast {
+                operand, to_type, ..
+            }) => self.cast("bitcast", operand, to_type)?,
+            I::AddrSpaceCast(ll_inst::AddrSpaceCast {
+                operand, to_type, ..
+            }) => self.cast("address_space_cast", operand, to_type)?,
+
+            // freeze
+            I::Freeze(ll_inst::Freeze { operand, .. }) => Inst::Freeze {
+                operand: self.value(operand)?,
+            },
+
+            // GEP
+            I::GetElementPtr(ll_inst::GetElementPtr {
+                address,
+                indices,
+                source_element_type,
+                ..
+            }) => {
+                let indices: Vec<_> = indices.iter().collect();
+                Inst::GEP {
+                    src_pointee_ty: module.ty(source_element_type)?,
+                    dst_pointee_ty: module
+                        .ty(&module.indexed_type(source_element_type, &indices)?)?,
+                    pointer: self.value(address)?,
+                    indices: self.values(&indices)?,
+                    address_space: self.pointer_address_space(address)?,
+                }
+            }
+
+            // choice
+            I::Select(ll_inst::Select {
+                condition,
+                true_value,
+                false_value,
+                ..
+            }) => Inst::ITE {
+                cond: self.value(condition)?,
+                then_value: self.value(true_value)?,
+                else_value: self.value(false_value)?,
+            },
+            I::Phi(ll_inst::Phi {
+                incoming_values, ..
+            }) => Inst::Phi {
+                options: incoming_values
+                    .iter()
+                    .map(|(value, block)| {
+                        Ok(PhiOption {
+                            block: self.block(block)?,
+                            value: self.value(value)?,
+                        })
+                    })
+                    .collect::<EngineResult<_>>()?,
+            },
+
+            // aggregates
+            I::ExtractValue(ll_inst::ExtractValue {
+                aggregate, indices, ..
+            }) => Inst::GetValue {
+                from_ty: module.type_of(aggregate)?,
+                aggregate: self.value(aggregate)?,
+                indices: indices.iter().map(|idx| *idx as usize).collect(),
+            },
+            I::InsertValue(ll_inst::InsertValue {
+                aggregate,
+                element,
+                indices,
+                ..
+            }) => Inst::SetValue {
+                aggregate: self.value(aggregate)?,
+                value: self.value(element)?,
+                indices: indices.iter().map(|idx| *idx as usize).collect(),
+            },
+            I::ExtractElement(ll_inst::ExtractElement { vector, index, .. }) => Inst::GetElement {
+                vec_ty: module.type_of(vector)?,
+                vector: self.value(vector)?,
+                slot: self.value(index)?,
+            },
+            I::InsertElement(ll_inst::InsertElement {
+                vector,
+                element,
+                index,
+                ..
+            }) => Inst::SetElement {
+                vector: self.value(vector)?,
+                value: self.value(element)?,
+                slot: self.value(index)?,
+            },
+            I::ShuffleVector(ll_inst::ShuffleVector {
+                operand0,
+                operand1,
+                mask,
+                ..
+            }) => Inst::ShuffleVector {
+                lhs: self.value(operand0)?,
+                rhs: self.value(operand1)?,
+                mask: self.shuffle_mask(mask)?,
+            },
+
+            // concurrency
+            I::Fence(ll_inst::Fence { atomicity, .. }) => Inst::Fence {
+                ordering: ordering_name(&atomicity.mem_ordering),
+                scope: scope_name(atomicity),
+            },
+            I::CmpXchg(ll_inst::CmpXchg {
+                address,
+                expected,
+                replacement,
+                atomicity,
+                failure_memory_ordering,
+                ..
+            }) => Inst::AtomicCmpXchg {
+                pointee_type: module.type_of(inst)?,
+                pointer: self.value(address)?,
+                value_cmp: self.value(expected)?,
+                value_xchg: self.value(replacement)?,
+                ordering_success: ordering_name(&atomicity.mem_ordering),
+                ordering_failure: ordering_name(failure_memory_ordering),
+                scope: scope_name(atomicity),
+                address_space: self.pointer_address_space(address)?,
+            },
+            I::AtomicRMW(ll_inst::AtomicRMW {
+                operation,
+                address,
+                value,
+                atomicity,
+                ..
+            }) => Inst::AtomicRMW {
+                pointee_type: module.type_of(inst)?,
+                pointer: self.value(address)?,
+                value: self.value(value)?,
+                opcode: rmw_opcode(operation)?,
+                ordering: ordering_name(&atomicity.mem_ordering),
+                scope: scope_name(atomicity),
+                address_space: self.pointer_address_space(address)?,
+            },
+
+            // exception handling
+            I::CatchPad(_) => Inst::CatchPad,
+            I::CleanupPad(_) => Inst::CleanupPad,
+            _ => return Err(unsupported(format!("instruction {}", inst))),
+        };
+        Ok(converted)
+    }
+
+    /// Mask of a shuffle, where undefined slots are -1
+    fn shuffle_mask(&self, mask: &ConstantRef) -> EngineResult<Vec<i128>> {
+        let length = match self.module.type_of(mask.as_ref())? {
+            Type::Vector { length, .. } => length,
+            _ => return Err(unsupported(format!("shuffle mask {}", mask))),
+        };
+        let slots = match mask.as_ref() {
+            llvm_ir::Constant::AggregateZero(_) => vec![0; length],
+            llvm_ir::Constant::Undef(_) | llvm_ir::Constant::Poison(_) => vec![-1; length],
+            llvm_ir::Constant::Vector(elements) => elements
+                .iter()
+                .map(|item| match item.as_ref() {
+                    llvm_ir::Constant::Int { value, .. } => Ok(*value as i128),
+                    llvm_ir::Constant::Undef(_) | llvm_ir::Constant::Poison(_) => Ok(-1),
+                    _ => Err(unsupported(format!("shuffle mask {}", mask))),
+                })
+                .collect::<EngineResult<_>>()?,
+            _ => return Err(unsupported(format!("shuffle mask {}", mask))),
+        };
+        Ok(slots)
+    }
+
+    fn terminator(&self, term: &llvm_ir::Terminator) -> EngineResult<Inst> {
+        use llvm_ir::Terminator as T;
+
+        let converted = match term {
+            T::Ret(ll_term::Ret { return_operand, .. }) => Inst::Return {
+                value: return_operand
+                    .as_ref()
+                    .map(|value| self.value(value))
+                    .transpose()?,
+            },
+            T::Br(ll_term::Br { dest, .. }) => Inst::Branch {
+                cond: None,
+                targets: vec![self.block(dest)?],
+            },
+            T::CondBr(ll_term::CondBr {
+                condition,
+                true_dest,
+                false_dest,
+                ..
+            }) => Inst::Branch {
+                cond: Some(self.value(condition)?),
+                targets: vec![self.block(true_dest)?, self.block(false_dest)?],
+            },
+            T::Switch(ll_term::Switch {
+                operand,
+                dests,
+                default_dest,
+                ..
+            }) => Inst::Switch {
+                cond: self.value(operand)?,
+                cond_ty: self.module.type_of(operand)?,
+                cases: dests
+                    .iter()
+                    .map(|(value, block)| {
+                        Ok(SwitchCase {
+                            block: self.block(block)?,
+                            value: self.module.constant(value)?,
+                        })
+                    })
+                    .collect::<EngineResult<_>>()?,
+                default: Some(self.block(default_dest)?),
+            },
+            T::IndirectBr(ll_term::IndirectBr {
+                operand,
+                possible_dests,
+                ..
+            }) => Inst::IndirectJump {
+                address: self.value(operand)?,
+                targets: possible_dests
+                    .iter()
+                    .map(|block| self.block(block))
+                    .collect::<EngineResult<_>>()?,
+            },
+            T::Invoke(ll_term::Invoke {
+                function,
+                function_ty,
+                arguments,
+                return_label,
+                exception_label,
+                calling_convention,
+                ..
+            }) => {
+                let normal = self.block(return_label)?;
+                let unwind = self.block(exception_label)?;
+                match self.call(function, function_ty, arguments, calling_convention)? {
+                    Inst::CallAsm { asm, args } => Inst::InvokeAsm {
+                        asm,
+                        args,
+                        normal,
+                        unwind,
+                    },
+                    Inst::CallIndirect {
+                        callee,
+                        target_type,
+                        calling_conv,
+                        args,
+                        arg_attrs,
+                    } => Inst::InvokeIndirect {
+                        callee,
+                        target_type,
+                        calling_conv,
+                        args,
+                        arg_attrs,
+                        normal,
+                        unwind,
+                    },
+                    Inst::CallDirect {
+                        callee,
+                        target_type,
+                        calling_conv,
+                        args,
+                        arg_attrs,
+                    }
+                    | Inst::Intrinsic {
+                        callee,
+                        target_type,
+                        calling_conv,
+                        args,
+                        arg_attrs,
+                    } => Inst::InvokeDirect {
+                        callee,
+                        target_type,
+                        calling_conv,
+                        args,
+                        arg_attrs,
+                        normal,
+                        unwind,
+                    },
+                    _ => {
+                        return Err(EngineError::InvariantViolation(
+                            "invoke converted into a non-call".into(),
+                        ));
+                    }
+                }
+            }
+            T::Resume(ll_term::Resume { operand, .. }) => Inst::Resume {
+                value: self.value(operand)?,
+            },
+            T::Unreachable(_) => Inst::Unreachable,
+            T::CleanupRet(_) => Inst::CleanupReturn,
+            T::CatchRet(_) => Inst::CatchReturn,
+            T::CatchSwitch(_) => Inst::CatchSwitch,
+            T::CallBr(_) => Inst::CallBranch,
+        };
+        Ok(converted)
+    }
+}
@@ -15,6 +15,7 @@ pub enum Type {
     Struct {
         name: Option<String>,
         fields: Option<Vec<Type>>,
+        packed: bool,
     },
     /// Function type, which can include variadic arguments
     Function {
@@ -50,4 +51,5 @@ pub enum Type {
 pub struct UserDefinedStruct {
     pub name: Option<String>,
     pub fields: Option<Vec<Type>>,
+    pub packed: bool,
 }
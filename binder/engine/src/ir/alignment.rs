@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use rug::Integer;
+
+use crate::error::EngineResult;
+use crate::ir::bridge::function::Function;
+use crate::ir::bridge::instruction::Instruction;
+use crate::ir::bridge::layout::DataLayout;
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::value::{RegisterSlot, Value};
+use crate::ir::defuse::{DefUse, Site, Statement};
+
+/// Certainty of a misaligned access
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub enum Misalignment {
+    /// the pointer is never aligned as claimed
+    Definite,
+    /// the pointer is not provably aligned as claimed
+    Possible,
+}
+
+impl Display for Misalignment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Definite => write!(f, "definite"),
+            Self::Possible => write!(f, "possible"),
+        }
+    }
+}
+
+/// A load or store whose claimed alignment is not backed by its pointer
+pub struct MisalignedAccess {
+    /// function holding the access
+    pub function: Identifier,
+    /// location of the access
+    pub site: Site,
+    /// alignment claimed by the access
+    pub claimed: usize,
+    /// alignment provable on the pointer
+    pub provable: usize,
+    /// certainty of the finding
+    pub kind: Misalignment,
+}
+
+impl Display for MisalignedAccess {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "@{} {}: access claims align {} but the pointer is {}-aligned ({})",
+            self.function, self.site, self.claimed, self.provable, self.kind
+        )
+    }
+}
+
+/// Alignment knowledge on a pointer, in the form of `base + constant + stride * <unknown>`
+#[derive(Clone)]
+struct PointerAlignment {
+    /// alignment of the base allocation
+    base: usize,
+    /// constant byte offset from the base
+    constant: Integer,
+    /// largest power of two dividing all symbolic offsets, 0 if there are none
+    stride: usize,
+}
+
+impl PointerAlignment {
+    /// Largest power of two known to divide the address
+    fn provable(&self) -> usize {
+        let mut align = self.base;
+        if let Some(bit) = self.constant.find_one(0) {
+            align = align.min(1 << bit);
+        }
+        if self.stride != 0 {
+            align = align.min(self.stride);
+        }
+        align
+    }
+}
+
+/// Utility: largest power of two dividing the number, 0 for zero
+fn low_bit(n: usize) -> usize {
+    n & n.wrapping_neg()
+}
+
+/// Tracker of pointer alignments in a function
+struct Tracker<'a> {
+    layout: &'a DataLayout,
+    func: &'a Function,
+    defuse: DefUse,
+    cache: BTreeMap<RegisterSlot, Option<PointerAlignment>>,
+}
+
+impl<'a> Tracker<'a> {
+    fn pointer(&mut self, value: &Value) -> EngineResult<Option<PointerAlignment>> {
+        match value {
+            Value::Register { index, ty: _ } => self.register(*index),
+            // an argument is as aligned as its parameter attribute promises
+            Value::Argument { index, ty: _ } => Ok(self
+                .func
+                .params
+                .get(index.index())
+                .and_then(|param| param.attrs.align)
+                .map(|align| PointerAlignment {
+                    base: align,
+                    constant: Integer::ZERO,
+                    stride: 0,
+                })),
+            // no assumption on constants
+            Value::Constant(_) => Ok(None),
+        }
+    }
+
+    fn register(&mut self, reg: RegisterSlot) -> EngineResult<Option<PointerAlignment>> {
+        if let Some(known) = self.cache.get(&reg) {
+            return Ok(known.clone());
+        }
+        let cfg = match &self.func.body {
+            None => return Ok(None),
+            Some(cfg) => cfg,
+        };
+        let inst = match self
+            .defuse
+            .definition(&reg)
+            .and_then(|site| DefUse::resolve(cfg, &site))
+        {
+            Some(Statement::Instruction(inst)) => inst,
+            Some(Statement::Terminator(_)) | None => return Ok(None),
+        };
+
+        let known = match inst {
+            Instruction::Alloca { align, .. } => Some(PointerAlignment {
+                base: *align,
+                constant: Integer::ZERO,
+                stride: 0,
+            }),
+            Instruction::CastPtr { operand, result: _ } => self.pointer(operand)?,
+            Instruction::GEP { pointer, .. } | Instruction::GEPNop { pointer, .. } => {
+                match self.pointer(pointer)? {
+                    None => None,
+                    Some(mut known) => {
+                        let offset = self.layout.gep_offset(inst)?;
+                        known.constant += &offset.constant;
+                        for term in &offset.terms {
+                            let scale = low_bit(term.scale);
+                            if scale != 0 {
+                                known.stride = if known.stride == 0 {
+                                    scale
+                                } else {
+                                    known.stride.min(scale)
+                                };
+                            }
+                        }
+                        Some(known)
+                    }
+                }
+            }
+            _ => None,
+        };
+        self.cache.insert(reg, known.clone());
+        Ok(known)
+    }
+}
+
+/// Find loads and stores that claim more alignment than their pointers provably have
+pub fn check_misaligned_accesses(module: &Module) -> EngineResult<Vec<MisalignedAccess>> {
+    let mut findings = vec![];
+    for (name, func) in module.functions() {
+        let cfg = match &func.body {
+            None => continue,
+            Some(cfg) => cfg,
+        };
+        let mut tracker = Tracker {
+            layout: module.layout(),
+            func,
+            defuse: DefUse::build(func)?,
+            cache: BTreeMap::new(),
+        };
+
+        for (label, block) in cfg.blocks() {
+            for (index, inst) in block.instructions().iter().enumerate() {
+                let (pointer, claimed) = match inst {
+                    Instruction::Load { pointer, align, .. }
+                    | Instruction::Store { pointer, align, .. } => (pointer, *align),
+                    _ => continue,
+                };
+                let known = match tracker.pointer(pointer)? {
+                    None => continue,
+                    Some(known) => known,
+                };
+                let provable = known.provable();
+                if provable >= claimed {
+                    continue;
+                }
+
+                // exact offsets from a sufficiently aligned base are definitely misaligned
+                let kind = if known.stride == 0 && known.base >= claimed {
+                    Misalignment::Definite
+                } else {
+                    Misalignment::Possible
+                };
+                findings.push(MisalignedAccess {
+                    function: name.clone(),
+                    site: Site::Instruction {
+                        block: *label,
+                        index,
+                    },
+                    claimed,
+                    provable,
+                    kind,
+                });
+            }
+        }
+    }
+    Ok(findings)
+}
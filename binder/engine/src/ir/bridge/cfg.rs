@@ -1,19 +1,27 @@
+use std::collections::hash_map::DefaultHasher;
 use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
 
 use petgraph::algo::is_isomorphic_matching;
 use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
 use rug::Integer;
+use serde::{Deserialize, Serialize};
 
 use crate::error::{EngineError, EngineResult};
 use crate::ir::adapter;
+use crate::ir::bridge::constant::UndefSemantics;
+use crate::ir::bridge::dominance::DominatorTree;
 use crate::ir::bridge::function::Parameter;
 use crate::ir::bridge::instruction::{Context, Instruction, Terminator};
-use crate::ir::bridge::shared::SymbolRegistry;
+use crate::ir::bridge::shared::{SourceLocation, SymbolRegistry};
 use crate::ir::bridge::typing::{Type, TypeRegistry};
-use crate::ir::bridge::value::BlockLabel;
+use crate::ir::bridge::value::{BlockLabel, RegisterSlot};
+use crate::ir::defuse::Site;
 
 /// An adapted representation of an LLVM basic block
-#[derive(Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub struct Block {
     /// sequence of instructions
     sequence: Vec<Instruction>,
@@ -21,8 +29,29 @@ pub struct Block {
     terminator: Terminator,
 }
 
+impl Block {
+    /// Instructions in the block, excluding the terminator
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.sequence
+    }
+
+    /// Terminator of the block
+    pub fn terminator(&self) -> &Terminator {
+        &self.terminator
+    }
+}
+
+impl Display for Block {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for inst in &self.sequence {
+            writeln!(f, "  {}", inst)?;
+        }
+        writeln!(f, "  {}", self.terminator)
+    }
+}
+
 /// A representation of CFG edges
-#[derive(Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub enum Edge {
     Goto,
     Branch(bool),
@@ -31,12 +60,183 @@ pub enum Edge {
     Invoke(bool),
 }
 
+impl Display for Edge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Goto => write!(f, "goto"),
+            Self::Branch(true) => write!(f, "then"),
+            Self::Branch(false) => write!(f, "else"),
+            Self::Switch(cases) => {
+                let repr: Vec<_> = cases
+                    .iter()
+                    .map(|case| match case {
+                        None => "default".to_string(),
+                        Some(v) => v.to_string(),
+                    })
+                    .collect();
+                write!(f, "case {}", repr.join(", "))
+            }
+            Self::Indirect => write!(f, "indirect"),
+            Self::Invoke(true) => write!(f, "normal"),
+            Self::Invoke(false) => write!(f, "unwind"),
+        }
+    }
+}
+
+/// Utility: escape a string for a quoted DOT label
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// A natural loop in the CFG
+pub struct Loop {
+    /// the only entry into the loop
+    pub header: BlockLabel,
+    /// blocks in the loop, including the header and nested loops
+    pub body: BTreeSet<BlockLabel>,
+    /// sources of back edges to the header
+    pub latches: BTreeSet<BlockLabel>,
+    /// edges leaving the loop, as (inside, outside)
+    pub exits: BTreeSet<(BlockLabel, BlockLabel)>,
+    /// header of the immediately enclosing loop
+    pub parent: Option<BlockLabel>,
+    /// headers of the immediately nested loops
+    pub children: BTreeSet<BlockLabel>,
+}
+
+/// Loops in a CFG organized as a nesting forest
+pub struct LoopForest {
+    /// loops keyed by their headers
+    loops: BTreeMap<BlockLabel, Loop>,
+}
+
+impl LoopForest {
+    /// All loops, keyed by their headers
+    pub fn loops(&self) -> &BTreeMap<BlockLabel, Loop> {
+        &self.loops
+    }
+
+    /// The loop headed by the block
+    pub fn get(&self, header: &BlockLabel) -> Option<&Loop> {
+        self.loops.get(header)
+    }
+
+    /// Whether the block is a loop header (e.g., a widening point)
+    pub fn is_header(&self, label: &BlockLabel) -> bool {
+        self.loops.contains_key(label)
+    }
+
+    /// Loops that are not nested in others
+    pub fn top_level(&self) -> impl Iterator<Item = &Loop> {
+        self.loops.values().filter(|l| l.parent.is_none())
+    }
+
+    /// The innermost loop containing the block
+    pub fn innermost(&self, label: &BlockLabel) -> Option<&Loop> {
+        self.loops
+            .values()
+            .filter(|l| l.body.contains(label))
+            .min_by_key(|l| l.body.len())
+    }
+
+    /// Number of loops containing the block
+    pub fn depth(&self, label: &BlockLabel) -> usize {
+        self.loops
+            .values()
+            .filter(|l| l.body.contains(label))
+            .count()
+    }
+}
+
+/// A component of a weak topological order (Bourdoncle) of the CFG
+pub enum WtoComponent {
+    /// a block not in any cycle at this nesting level
+    Vertex(BlockLabel),
+    /// a strongly connected part entered through its head, i.e., a widening point
+    Cycle {
+        head: BlockLabel,
+        body: Vec<WtoComponent>,
+    },
+}
+
+/// Utility: state of the recursive construction of a weak topological order
+struct WtoBuilder {
+    succs: BTreeMap<BlockLabel, BTreeSet<BlockLabel>>,
+    /// depth-first number, 0 for unvisited and `usize::MAX` for placed
+    dfn: BTreeMap<BlockLabel, usize>,
+    count: usize,
+    stack: Vec<BlockLabel>,
+}
+
+impl WtoBuilder {
+    fn dfn(&self, label: &BlockLabel) -> usize {
+        self.dfn.get(label).copied().unwrap_or(0)
+    }
+
+    fn successors(&self, label: &BlockLabel) -> Vec<BlockLabel> {
+        self.succs
+            .get(label)
+            .map(|s| s.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Visit a block, prepending the components completed to the partition (kept reversed),
+    /// and return the smallest depth-first number reachable from it
+    fn visit(&mut self, label: BlockLabel, partition: &mut Vec<WtoComponent>) -> usize {
+        self.stack.push(label);
+        self.count += 1;
+        self.dfn.insert(label, self.count);
+        let mut head = self.count;
+        let mut is_cycle = false;
+        for succ in self.successors(&label) {
+            let min = match self.dfn(&succ) {
+                0 => self.visit(succ, partition),
+                n => n,
+            };
+            if min <= head {
+                head = min;
+                is_cycle = true;
+            }
+        }
+
+        if head == self.dfn(&label) {
+            self.dfn.insert(label, usize::MAX);
+            let mut element = self.stack.pop().expect("the visited block is on the stack");
+            if is_cycle {
+                // the cycle is ordered again from its head, with the other blocks unvisited
+                while element != label {
+                    self.dfn.insert(element, 0);
+                    element = self.stack.pop().expect("the head is on the stack");
+                }
+                let mut body = vec![];
+                for succ in self.successors(&label) {
+                    if self.dfn(&succ) == 0 {
+                        self.visit(succ, &mut body);
+                    }
+                }
+                body.reverse();
+                partition.push(WtoComponent::Cycle { head: label, body });
+            } else {
+                partition.push(WtoComponent::Vertex(label));
+            }
+        }
+        head
+    }
+}
+
 /// An adapted representation of an LLVM control-flow graph
+#[derive(Serialize, Deserialize)]
 pub struct ControlFlowGraph {
     /// the control-flow graph
     graph: DiGraph<Block, Edge>,
     /// block label to index in the graph
     block_label_to_index: BTreeMap<BlockLabel, NodeIndex>,
+    /// names of registers as given in the source (not part of the identity of the CFG)
+    #[serde(default)]
+    register_names: BTreeMap<RegisterSlot, String>,
+    /// source locations of the instructions and terminators (not part of the identity of the CFG)
+    #[serde(default, with = "crate::ir::bridge::shared::map_as_pairs")]
+    locations: BTreeMap<Site, SourceLocation>,
 }
 
 impl PartialEq for ControlFlowGraph {
@@ -51,10 +251,42 @@ impl PartialEq for ControlFlowGraph {
 }
 impl Eq for ControlFlowGraph {}
 
+impl Hash for ControlFlowGraph {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // equality is up to isomorphism on the blocks, hence hash the blocks as a multiset,
+        // independent of the label-to-node mapping and the order of insertion
+        let mut digests: Vec<_> = self
+            .graph
+            .node_weights()
+            .map(|block| {
+                let mut hasher = DefaultHasher::new();
+                block.hash(&mut hasher);
+                hasher.finish()
+            })
+            .collect();
+        digests.sort_unstable();
+        digests.hash(state);
+    }
+}
+
+impl Display for ControlFlowGraph {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (label, index) in &self.block_label_to_index {
+            writeln!(f, "{}:", label)?;
+            self.graph
+                .node_weight(*index)
+                .expect("block label points to a valid node")
+                .fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
 impl ControlFlowGraph {
     pub fn build(
         typing: &TypeRegistry,
         symbols: &SymbolRegistry,
+        semantics: UndefSemantics,
         params: &[Parameter],
         ret_ty: Option<&Type>,
         blocks: &[adapter::cfg::Block],
@@ -95,6 +327,7 @@ impl ControlFlowGraph {
         let mut ctxt = Context {
             typing,
             symbols,
+            semantics,
             blocks: block_labels,
             insts: inst_labels,
             args: arg_labels,
@@ -105,6 +338,9 @@ impl ControlFlowGraph {
         let mut graph = DiGraph::new();
         let mut block_label_to_index = BTreeMap::new();
         let mut edges: BTreeMap<(BlockLabel, BlockLabel), _> = BTreeMap::new();
+        let mut register_names = BTreeMap::new();
+        let mut locations = BTreeMap::new();
+        let mut truncated = BTreeSet::new();
         for block in blocks {
             let AdaptedBlock {
                 label,
@@ -113,11 +349,58 @@ impl ControlFlowGraph {
                 terminator,
             } = block;
 
-            let body_new = body
+            // keep the source-level names of the registers
+            for inst in body.iter() {
+                if let Some(name) = inst.name.as_ref().filter(|n| !n.is_empty()) {
+                    register_names.insert(inst.index.into(), name.clone());
+                }
+            }
+
+            let mut body_new: Vec<_> = body
                 .iter()
                 .map(|inst| ctxt.parse_instruction(inst))
                 .collect::<EngineResult<_>>()?;
-            let terminator_new = ctxt.parse_terminator(terminator)?;
+            let mut terminator_new = ctxt.parse_terminator(terminator)?;
+
+            // calls to noreturn functions terminate the block
+            let no_return_call = body_new.iter().position(|inst| match inst {
+                Instruction::CallDirect { function, .. } => symbols.is_no_return(function),
+                _ => false,
+            });
+            if let Some(pos) = no_return_call {
+                body_new.truncate(pos + 1);
+                terminator_new = Terminator::Unreachable;
+                truncated.insert(BlockLabel::from(label));
+            }
+
+            // keep the debug locations of what remains in the block
+            let sites = body
+                .iter()
+                .take(body_new.len())
+                .enumerate()
+                .map(|(index, inst)| {
+                    let site = Site::Instruction {
+                        block: label.into(),
+                        index,
+                    };
+                    (site, inst)
+                })
+                .chain(no_return_call.is_none().then_some((
+                    Site::Terminator {
+                        block: label.into(),
+                    },
+                    terminator,
+                )));
+            for (site, inst) in sites {
+                if let Some(debug) = &inst.debug {
+                    let location = SourceLocation {
+                        file: debug.file.clone(),
+                        line: debug.line,
+                        column: debug.column,
+                    };
+                    locations.insert(site, location);
+                }
+            }
 
             // collect the edges
             match &terminator_new {
@@ -259,6 +542,17 @@ impl ControlFlowGraph {
             block_label_to_index.insert(label.into(), node_index);
         }
 
+        // the truncated blocks no longer flow into their former successors
+        if !truncated.is_empty() {
+            for block in graph.node_weights_mut() {
+                for inst in block.sequence.iter_mut() {
+                    if let Instruction::Phi { options, .. } = inst {
+                        options.retain(|src, _| !truncated.contains(src));
+                    }
+                }
+            }
+        }
+
         // add the edges
         for ((src, dst), edge) in edges {
             let src_index = block_label_to_index.get(&src).unwrap();
@@ -305,10 +599,329 @@ impl ControlFlowGraph {
         Ok(Self {
             graph,
             block_label_to_index,
+            register_names,
+            locations,
+        })
+    }
+
+    /// Source-level name of a register, if it has one
+    pub fn register_name(&self, slot: &RegisterSlot) -> Option<&str> {
+        self.register_names.get(slot).map(|n| n.as_str())
+    }
+
+    /// Source location of an instruction or a terminator, if the module has debug info
+    pub fn location(&self, site: &Site) -> Option<&SourceLocation> {
+        self.locations.get(site)
+    }
+
+    /// Source location of the first instruction or terminator of a block that has one
+    pub fn block_location(&self, label: &BlockLabel) -> Option<&SourceLocation> {
+        let first = Site::Instruction {
+            block: *label,
+            index: 0,
+        };
+        let last = Site::Instruction {
+            block: *label,
+            index: usize::MAX,
+        };
+        self.locations
+            .range(first..=last)
+            .map(|(_, location)| location)
+            .next()
+            .or_else(|| self.location(&Site::Terminator { block: *label }))
+    }
+
+    /// Heads of the cycles in the weak topological order, nested ones included
+    pub fn wto_heads(&self) -> BTreeSet<BlockLabel> {
+        fn collect(components: &[WtoComponent], heads: &mut BTreeSet<BlockLabel>) {
+            for component in components {
+                if let WtoComponent::Cycle { head, body } = component {
+                    heads.insert(*head);
+                    collect(body, heads);
+                }
+            }
+        }
+        let mut heads = BTreeSet::new();
+        collect(&self.wto(), &mut heads);
+        heads
+    }
+
+    /// Render the CFG in the Graphviz DOT format
+    pub fn to_dot(&self) -> String {
+        self.to_dot_filtered(|_| true, |_, _| true)
+    }
+
+    /// Same as `to_dot`, restricted to the blocks and the edges (by source and target) kept,
+    /// e.g., to render the CFG pruned of the code found dead
+    pub fn to_dot_filtered(
+        &self,
+        keep_block: impl Fn(&BlockLabel) -> bool,
+        keep_edge: impl Fn(&BlockLabel, &BlockLabel) -> bool,
+    ) -> String {
+        let index_to_label: BTreeMap<_, _> = self
+            .block_label_to_index
+            .iter()
+            .map(|(label, index)| (*index, *label))
+            .collect();
+
+        let mut dot = String::from("digraph cfg {\n");
+        dot.push_str("  node [shape=box, fontname=monospace];\n");
+
+        // blocks with their instructions
+        for (label, index) in &self.block_label_to_index {
+            if !keep_block(label) {
+                continue;
+            }
+            let block = self
+                .graph
+                .node_weight(*index)
+                .expect("block label points to a valid node");
+            let mut text = format!("{}:\\l", label);
+            for inst in &block.sequence {
+                text.push_str(&format!("  {}\\l", escape_dot(&inst.to_string())));
+            }
+            text.push_str(&format!(
+                "  {}\\l",
+                escape_dot(&block.terminator.to_string())
+            ));
+            dot.push_str(&format!("  \"{}\" [label=\"{}\"];\n", label, text));
+        }
+
+        // edges labeled by kind
+        for (label, index) in &self.block_label_to_index {
+            if !keep_block(label) {
+                continue;
+            }
+            let mut targets: Vec<_> = self
+                .graph
+                .edges(*index)
+                .map(|edge| (index_to_label[&edge.target()], edge.weight()))
+                .filter(|(target, _)| keep_block(target) && keep_edge(label, target))
+                .collect();
+            targets.sort_by_key(|(target, _)| *target);
+            for (target, edge) in targets {
+                dot.push_str(&format!(
+                    "  \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                    label,
+                    target,
+                    escape_dot(&edge.to_string())
+                ));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Label of the entry block
+    pub fn entry(&self) -> BlockLabel {
+        // the oracle labels blocks in their order within the function
+        *self
+            .block_label_to_index
+            .keys()
+            .next()
+            .expect("a CFG has at least one block")
+    }
+
+    /// Blocks in reverse post-order from the entry (successors visited in label order),
+    /// followed by the unreachable blocks in label order
+    pub fn blocks_rpo(&self) -> Vec<BlockLabel> {
+        let mut succs: BTreeMap<BlockLabel, BTreeSet<BlockLabel>> = BTreeMap::new();
+        for (src, dst) in self.label_edges() {
+            succs.entry(src).or_default().insert(dst);
+        }
+
+        // iterative post-order traversal
+        let mut visited = BTreeSet::new();
+        let mut post_order = vec![];
+        let entry = self.entry();
+        visited.insert(entry);
+        let mut stack = vec![(entry, succs.remove(&entry).unwrap_or_default().into_iter())];
+        while let Some((label, iter)) = stack.last_mut() {
+            match iter.next() {
+                None => {
+                    post_order.push(*label);
+                    stack.pop();
+                }
+                Some(next) => {
+                    if visited.insert(next) {
+                        let next_succs = succs.remove(&next).unwrap_or_default();
+                        stack.push((next, next_succs.into_iter()));
+                    }
+                }
+            }
+        }
+
+        post_order.reverse();
+        post_order.extend(
+            self.block_label_to_index
+                .keys()
+                .filter(|label| !visited.contains(*label)),
+        );
+        post_order
+    }
+
+    /// Successors of a block along with the edges leading to them, in label order
+    pub fn successors(&self, label: &BlockLabel) -> Vec<(BlockLabel, &Edge)> {
+        let index = match self.block_label_to_index.get(label) {
+            None => return vec![],
+            Some(index) => *index,
+        };
+        let index_to_label: BTreeMap<_, _> = self
+            .block_label_to_index
+            .iter()
+            .map(|(label, index)| (*index, *label))
+            .collect();
+        let mut succs: Vec<_> = self
+            .graph
+            .edges(index)
+            .map(|edge| (index_to_label[&edge.target()], edge.weight()))
+            .collect();
+        succs.sort_by_key(|(target, _)| *target);
+        succs
+    }
+
+    /// Edges between blocks, in terms of block labels
+    fn label_edges(&self) -> Vec<(BlockLabel, BlockLabel)> {
+        let index_to_label: BTreeMap<_, _> = self
+            .block_label_to_index
+            .iter()
+            .map(|(label, index)| (*index, *label))
+            .collect();
+        self.graph
+            .edge_references()
+            .map(|edge| {
+                (
+                    index_to_label[&edge.source()],
+                    index_to_label[&edge.target()],
+                )
+            })
+            .collect()
+    }
+
+    /// Dominator tree rooted at the entry block
+    pub fn dominators(&self) -> DominatorTree {
+        let nodes = self.block_label_to_index.keys().copied().collect();
+        let roots = BTreeSet::from([self.entry()]);
+        DominatorTree::compute(&nodes, &self.label_edges(), &roots)
+    }
+
+    /// Post-dominator tree rooted at a virtual exit that follows every exit block
+    pub fn post_dominators(&self) -> DominatorTree {
+        let nodes = self.block_label_to_index.keys().copied().collect();
+        let edges: Vec<_> = self
+            .label_edges()
+            .into_iter()
+            .map(|(src, dst)| (dst, src))
+            .collect();
+        let roots = self
+            .block_label_to_index
+            .iter()
+            .filter(|(_, index)| self.graph.neighbors(**index).next().is_none())
+            .map(|(label, _)| *label)
+            .collect();
+        DominatorTree::compute(&nodes, &edges, &roots)
+    }
+
+    /// Natural loops identified by back edges to dominating headers
+    /// (loops in irreducible control flow are not identified)
+    pub fn loops(&self) -> LoopForest {
+        let doms = self.dominators();
+        let edges = self.label_edges();
+
+        let mut preds: BTreeMap<BlockLabel, BTreeSet<BlockLabel>> = BTreeMap::new();
+        for (src, dst) in &edges {
+            preds.entry(*dst).or_default().insert(*src);
+        }
+
+        // collect the bodies, merging loops that share a header
+        let mut loops: BTreeMap<BlockLabel, Loop> = BTreeMap::new();
+        for (src, dst) in &edges {
+            if !doms.dominates(dst, src) {
+                continue;
+            }
+            let item = loops.entry(*dst).or_insert_with(|| Loop {
+                header: *dst,
+                body: BTreeSet::from([*dst]),
+                latches: BTreeSet::new(),
+                exits: BTreeSet::new(),
+                parent: None,
+                children: BTreeSet::new(),
+            });
+            item.latches.insert(*src);
+
+            // walk backwards from the latch until the header
+            let mut worklist = vec![*src];
+            while let Some(label) = worklist.pop() {
+                if !item.body.insert(label) {
+                    continue;
+                }
+                if let Some(incoming) = preds.get(&label) {
+                    worklist.extend(incoming.iter().filter(|p| doms.is_reachable(p)));
+                }
+            }
+        }
+
+        // exit edges
+        for item in loops.values_mut() {
+            item.exits = edges
+                .iter()
+                .filter(|(src, dst)| item.body.contains(src) && !item.body.contains(dst))
+                .copied()
+                .collect();
+        }
+
+        // nesting, where the parent is the smallest enclosing loop
+        let headers: Vec<_> = loops.keys().copied().collect();
+        for header in &headers {
+            let parent = headers
+                .iter()
+                .filter(|other| *other != header && loops[*other].body.contains(header))
+                .min_by_key(|other| loops[*other].body.len())
+                .copied();
+            if let Some(parent_header) = parent {
+                loops.get_mut(header).unwrap().parent = Some(parent_header);
+                loops
+                    .get_mut(&parent_header)
+                    .unwrap()
+                    .children
+                    .insert(*header);
+            }
+        }
+
+        LoopForest { loops }
+    }
+
+    /// Weak topological order of the blocks reachable from the entry, where the heads of the
+    /// cycles (nested ones included) are the widening points
+    pub fn wto(&self) -> Vec<WtoComponent> {
+        let mut succs: BTreeMap<BlockLabel, BTreeSet<BlockLabel>> = BTreeMap::new();
+        for (src, dst) in self.label_edges() {
+            succs.entry(src).or_default().insert(dst);
+        }
+        let mut builder = WtoBuilder {
+            succs,
+            dfn: BTreeMap::new(),
+            count: 0,
+            stack: vec![],
+        };
+        let mut partition = vec![];
+        builder.visit(self.entry(), &mut partition);
+        partition.reverse();
+        partition
+    }
+
+    /// Blocks in the order of their labels
+    pub fn blocks(&self) -> impl Iterator<Item = (&BlockLabel, &Block)> {
+        self.block_label_to_index.iter().map(|(label, index)| {
+            (
+                label,
+                self.graph
+                    .node_weight(*index)
+                    .expect("block label points to a valid node"),
+            )
         })
     }
 
-    #[allow(dead_code)] // TODO: this will be used in next stage construction
     pub fn get_block_by_label(&self, label: &BlockLabel) -> Option<&Block> {
         self.block_label_to_index
             .get(label)
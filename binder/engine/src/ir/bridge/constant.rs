@@ -1,7 +1,9 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
 
 use rug::ops::CompleteRound;
 use rug::{Complete, Float, Integer, Rational};
+use serde::{Deserialize, Serialize};
 
 use crate::error::{EngineError, EngineResult, Unsupported};
 use crate::ir::adapter;
@@ -16,7 +18,7 @@ use crate::ir::bridge::typing::{NumRepr, Type, TypeRegistry};
 static CONSTANT_AGGREGATE_LENGTH_MAX: usize = 1 << 20;
 
 /// The underlying representation of the bitvec
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Clone)]
 pub enum NumValue {
     Int(Integer),
     IntUndef,
@@ -24,8 +26,57 @@ pub enum NumValue {
     FloatUndef,
 }
 
+/// Interpretation of undef values, shared by conversion and analysis
+#[derive(Serialize, Deserialize, Eq, PartialEq, Copy, Clone, Debug, Default)]
+pub enum UndefSemantics {
+    /// undef is kept symbolic and any use of it yields poison
+    #[default]
+    Poison,
+    /// undef may take any value of its type, independently at each use
+    Any,
+    /// undef is the zero value of its type
+    Zero,
+}
+
+impl UndefSemantics {
+    pub fn parse(name: &str) -> EngineResult<Self> {
+        let parsed = match name {
+            "poison" => Self::Poison,
+            "any" => Self::Any,
+            "zero" => Self::Zero,
+            _ => {
+                return Err(EngineError::InvalidAssumption(format!(
+                    "unknown undef semantics: {}",
+                    name
+                )));
+            }
+        };
+        Ok(parsed)
+    }
+
+    /// Decide how a transfer function should treat an undef operand of the given type
+    pub fn resolve(&self, ty: &Type) -> EngineResult<UndefResolution> {
+        let resolved = match self {
+            Self::Poison => UndefResolution::Poison,
+            Self::Any => UndefResolution::Unconstrained,
+            Self::Zero => UndefResolution::Concrete(Constant::default_from_type(ty)?),
+        };
+        Ok(resolved)
+    }
+}
+
+/// Treatment of an undef operand in a transfer function
+pub enum UndefResolution {
+    /// the result is poison
+    Poison,
+    /// the operand can be any value of its type
+    Unconstrained,
+    /// the operand is this concrete value
+    Concrete(Constant),
+}
+
 /// A naive translation from an LLVM constant
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Clone)]
 pub enum Constant {
     /// A single bitvec for a number
     NumOne { bits: usize, value: NumValue },
@@ -57,6 +108,40 @@ pub enum Constant {
 }
 
 impl Constant {
+    /// Constants nested in this constant (excluding those in expressions)
+    pub fn children(&self) -> Vec<&Constant> {
+        match self {
+            Self::NumVec { elements, .. } | Self::Array { elements, .. } => {
+                elements.iter().collect()
+            }
+            Self::Struct { name: _, fields } => fields.iter().collect(),
+            Self::NumOne { .. }
+            | Self::Null
+            | Self::UndefPointer
+            | Self::Variable { .. }
+            | Self::Function { .. }
+            | Self::Block { .. }
+            | Self::Expr(_) => vec![],
+        }
+    }
+
+    /// Mutable references to the constants nested in this constant (excluding those in expressions)
+    pub fn children_mut(&mut self) -> Vec<&mut Constant> {
+        match self {
+            Self::NumVec { elements, .. } | Self::Array { elements, .. } => {
+                elements.iter_mut().collect()
+            }
+            Self::Struct { name: _, fields } => fields.iter_mut().collect(),
+            Self::NumOne { .. }
+            | Self::Null
+            | Self::UndefPointer
+            | Self::Variable { .. }
+            | Self::Function { .. }
+            | Self::Block { .. }
+            | Self::Expr(_) => vec![],
+        }
+    }
+
     fn default_from_type(ty: &Type) -> EngineResult<Self> {
         let value = match ty {
             Type::Bitvec {
@@ -120,7 +205,11 @@ impl Constant {
                         .collect::<EngineResult<_>>()?,
                 }
             }
-            Type::Struct { name, fields } => {
+            Type::Struct {
+                name,
+                fields,
+                packed: _,
+            } => {
                 let defaults = fields
                     .iter()
                     .map(Self::default_from_type)
@@ -204,7 +293,11 @@ impl Constant {
                         .collect::<EngineResult<_>>()?,
                 }
             }
-            Type::Struct { name, fields } => Self::Struct {
+            Type::Struct {
+                name,
+                fields,
+                packed: _,
+            } => Self::Struct {
                 name: name.clone(),
                 fields: fields
                     .iter()
@@ -227,6 +320,7 @@ impl Constant {
         expected_type: &Type,
         typing: &TypeRegistry,
         symbols: &SymbolRegistry,
+        semantics: UndefSemantics,
     ) -> EngineResult<Self> {
         use adapter::constant::Const as AdaptedConst;
 
@@ -329,7 +423,12 @@ impl Constant {
             }
             AdaptedConst::Undef => {
                 check_type(ty)?;
-                Self::undef_from_type(expected_type)?
+                match semantics {
+                    UndefSemantics::Poison | UndefSemantics::Any => {
+                        Self::undef_from_type(expected_type)?
+                    }
+                    UndefSemantics::Zero => Self::default_from_type(expected_type)?,
+                }
             }
             AdaptedConst::Default => {
                 check_type(ty)?;
@@ -357,7 +456,7 @@ impl Constant {
                         };
                         let elements_new = elements
                             .iter()
-                            .map(|e| Self::convert(e, &element_ty, typing, symbols))
+                            .map(|e| Self::convert(e, &element_ty, typing, symbols, semantics))
                             .collect::<EngineResult<_>>()?;
                         Self::NumVec {
                             bits: *bits,
@@ -386,7 +485,7 @@ impl Constant {
                         }
                         let elements_new = elements
                             .iter()
-                            .map(|e| Self::convert(e, element, typing, symbols))
+                            .map(|e| Self::convert(e, element, typing, symbols, semantics))
                             .collect::<EngineResult<_>>()?;
                         Self::Array {
                             sub: element.as_ref().clone(),
@@ -404,7 +503,11 @@ impl Constant {
             AdaptedConst::Struct { elements } => {
                 check_type(ty)?;
                 match expected_type {
-                    Type::Struct { name, fields } => {
+                    Type::Struct {
+                        name,
+                        fields,
+                        packed: _,
+                    } => {
                         if elements.len() != fields.len() {
                             return Err(EngineError::InvalidAssumption(format!(
                                 "type mismatch: expect {} elements, found {}",
@@ -415,7 +518,7 @@ impl Constant {
                         let elements_new = elements
                             .iter()
                             .zip(fields.iter())
-                            .map(|(e, t)| Self::convert(e, t, typing, symbols))
+                            .map(|(e, t)| Self::convert(e, t, typing, symbols, semantics))
                             .collect::<EngineResult<_>>()?;
                         Self::Struct {
                             name: name.clone(),
@@ -507,6 +610,7 @@ impl Constant {
                 let mut ctxt = Context {
                     typing,
                     symbols,
+                    semantics,
                     // simulate an environment where there is no function body
                     blocks: BTreeSet::new(),
                     insts: BTreeMap::new(),
@@ -520,17 +624,23 @@ impl Constant {
                     ty: ty.clone(),
                     index: usize::MAX,
                     repr: inst.as_ref().clone(),
+                    debug: None,
                 };
                 let inst_parsed = ctxt.parse_instruction(&fake_inst)?;
                 let expr_parsed = Expression::from_instruction(inst_parsed)?;
-                Self::Expr(Box::new(expr_parsed))
+
+                // fold the expression into a concrete value if possible
+                match expr_parsed.evaluate() {
+                    None => Self::Expr(Box::new(expr_parsed)),
+                    Some(folded) => folded,
+                }
             }
         };
         Ok(result)
     }
 }
 
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Clone)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum Expression {
     // unary
@@ -689,7 +799,7 @@ pub enum Expression {
     },
 }
 
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Clone)]
 pub enum GEPConstIndex {
     Array(Constant),
     Struct(usize),
@@ -697,6 +807,134 @@ pub enum GEPConstIndex {
 }
 
 impl Expression {
+    /// Constants read by this expression, in order of appearance
+    pub fn operands(&self) -> Vec<&Constant> {
+        match self {
+            Self::UnaryArith { operand, .. }
+            | Self::CastBitvecSize { operand, .. }
+            | Self::CastBitvecRepr { operand, .. }
+            | Self::CastBitvecFree { operand, .. }
+            | Self::CastPtr { operand }
+            | Self::CastPtrToInt { operand, .. }
+            | Self::CastIntToPtr { operand, .. } => vec![operand],
+            Self::BinaryArith { lhs, rhs, .. }
+            | Self::BinaryBitwise { lhs, rhs, .. }
+            | Self::BinaryShift { lhs, rhs, .. }
+            | Self::CompareBitvec { lhs, rhs, .. }
+            | Self::CompareOrder { lhs, rhs, .. }
+            | Self::ComparePtr { lhs, rhs, .. }
+            | Self::ShuffleVec { lhs, rhs, .. } => vec![lhs, rhs],
+            Self::GEP {
+                src_pointee_type: _,
+                dst_pointee_type: _,
+                pointer,
+                offset,
+                indices,
+            } => {
+                let mut constants = vec![pointer, offset];
+                for index in indices {
+                    match index {
+                        GEPConstIndex::Array(c) | GEPConstIndex::Vector(c) => constants.push(c),
+                        GEPConstIndex::Struct(_) => (),
+                    }
+                }
+                constants
+            }
+            Self::GEPNop {
+                pointee_type: _,
+                pointer,
+            } => vec![pointer],
+            Self::ITEOne {
+                cond,
+                then_value,
+                else_value,
+            }
+            | Self::ITEVec {
+                cond,
+                then_value,
+                else_value,
+                ..
+            } => vec![cond, then_value, else_value],
+            Self::GetValue { aggregate, .. } => vec![aggregate],
+            Self::SetValue {
+                aggregate,
+                value,
+                indices: _,
+            } => vec![aggregate, value],
+            Self::GetElement { vector, slot, .. } => vec![vector, slot],
+            Self::SetElement {
+                vector,
+                value,
+                slot,
+                ..
+            } => vec![vector, value, slot],
+        }
+    }
+
+    /// Mutable references to the constants read by this expression
+    pub fn operands_mut(&mut self) -> Vec<&mut Constant> {
+        match self {
+            Self::UnaryArith { operand, .. }
+            | Self::CastBitvecSize { operand, .. }
+            | Self::CastBitvecRepr { operand, .. }
+            | Self::CastBitvecFree { operand, .. }
+            | Self::CastPtr { operand }
+            | Self::CastPtrToInt { operand, .. }
+            | Self::CastIntToPtr { operand, .. } => vec![operand],
+            Self::BinaryArith { lhs, rhs, .. }
+            | Self::BinaryBitwise { lhs, rhs, .. }
+            | Self::BinaryShift { lhs, rhs, .. }
+            | Self::CompareBitvec { lhs, rhs, .. }
+            | Self::CompareOrder { lhs, rhs, .. }
+            | Self::ComparePtr { lhs, rhs, .. }
+            | Self::ShuffleVec { lhs, rhs, .. } => vec![lhs, rhs],
+            Self::GEP {
+                src_pointee_type: _,
+                dst_pointee_type: _,
+                pointer,
+                offset,
+                indices,
+            } => {
+                let mut constants = vec![pointer, offset];
+                for index in indices {
+                    match index {
+                        GEPConstIndex::Array(c) | GEPConstIndex::Vector(c) => constants.push(c),
+                        GEPConstIndex::Struct(_) => (),
+                    }
+                }
+                constants
+            }
+            Self::GEPNop {
+                pointee_type: _,
+                pointer,
+            } => vec![pointer],
+            Self::ITEOne {
+                cond,
+                then_value,
+                else_value,
+            }
+            | Self::ITEVec {
+                cond,
+                then_value,
+                else_value,
+                ..
+            } => vec![cond, then_value, else_value],
+            Self::GetValue { aggregate, .. } => vec![aggregate],
+            Self::SetValue {
+                aggregate,
+                value,
+                indices: _,
+            } => vec![aggregate, value],
+            Self::GetElement { vector, slot, .. } => vec![vector, slot],
+            Self::SetElement {
+                vector,
+                value,
+                slot,
+                ..
+            } => vec![vector, value, slot],
+        }
+    }
+
     pub fn from_instruction(inst: Instruction) -> EngineResult<Self> {
         let expr = match inst {
             Instruction::UnaryArith {
@@ -721,6 +959,8 @@ impl Expression {
                 number,
                 length,
                 opcode,
+                nsw: _,
+                nuw: _,
                 lhs,
                 rhs,
                 result,
@@ -1061,6 +1301,7 @@ impl Expression {
             | Instruction::Store { .. }
             | Instruction::VariadicArg { .. }
             | Instruction::CallDirect { .. }
+            | Instruction::Contract { .. }
             | Instruction::CallIndirect { .. }
             | Instruction::FreezeBitvec { .. }
             | Instruction::FreezePtr
@@ -1075,3 +1316,838 @@ impl Expression {
         Ok(expr)
     }
 }
+
+/// Utility: join the items with a separator for display
+pub(crate) fn join_display<T: Display>(items: &[T], sep: &str) -> String {
+    items
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+impl Display for NumValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Int(v) => write!(f, "{}", v),
+            Self::IntUndef | Self::FloatUndef => write!(f, "undef"),
+            Self::Float(None) => write!(f, "non-finite"),
+            Self::Float(Some(v)) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl Display for Constant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NumOne { bits, value } => {
+                let number = match value {
+                    NumValue::Int(_) | NumValue::IntUndef => NumRepr::Int,
+                    NumValue::Float(_) | NumValue::FloatUndef => NumRepr::Float,
+                };
+                write!(f, "{}{} {}", number, bits, value)
+            }
+            Self::NumVec {
+                bits,
+                number,
+                elements,
+            } => {
+                let repr: Vec<_> = elements
+                    .iter()
+                    .map(|e| match e {
+                        Self::NumOne { bits: _, value } => value.to_string(),
+                        _ => e.to_string(),
+                    })
+                    .collect();
+                write!(
+                    f,
+                    "{}{}<{}> <{}>",
+                    number,
+                    bits,
+                    elements.len(),
+                    repr.join(", ")
+                )
+            }
+            Self::Null => write!(f, "null"),
+            Self::UndefPointer => write!(f, "undef"),
+            Self::Array { sub, elements } => {
+                write!(
+                    f,
+                    "{}[{}] [{}]",
+                    sub,
+                    elements.len(),
+                    join_display(elements, ", ")
+                )
+            }
+            Self::Struct { name, fields } => write!(
+                f,
+                "{}{{{}}}",
+                name.as_ref()
+                    .map_or_else(|| "<anonymous>".to_string(), |n| n.to_string()),
+                join_display(fields, ", ")
+            ),
+            Self::Variable { name } => write!(f, "@{}", name),
+            Self::Function { name } => write!(f, "@{}", name),
+            Self::Block { func, block } => write!(f, "blockaddress(@{}, #{})", func, block),
+            Self::Expr(expr) => write!(f, "({})", expr),
+        }
+    }
+}
+
+impl Display for GEPConstIndex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Array(v) => write!(f, "[{}]", v),
+            Self::Struct(v) => write!(f, ".{}", v),
+            Self::Vector(v) => write!(f, "<{}>", v),
+        }
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let bitvec = |bits: usize, number: NumRepr, length: Option<usize>| Type::Bitvec {
+            bits,
+            number,
+            length,
+        };
+        match self {
+            Self::UnaryArith {
+                bits,
+                number,
+                length,
+                opcode,
+                operand,
+            } => write!(
+                f,
+                "{} {} {}",
+                opcode,
+                bitvec(*bits, *number, *length),
+                operand
+            ),
+            Self::BinaryArith {
+                bits,
+                number,
+                length,
+                opcode,
+                lhs,
+                rhs,
+            } => write!(
+                f,
+                "{} {} {}, {}",
+                opcode,
+                bitvec(*bits, *number, *length),
+                lhs,
+                rhs
+            ),
+            Self::BinaryBitwise {
+                bits,
+                length,
+                opcode,
+                lhs,
+                rhs,
+            } => write!(
+                f,
+                "{} {} {}, {}",
+                opcode,
+                bitvec(*bits, NumRepr::Int, *length),
+                lhs,
+                rhs
+            ),
+            Self::BinaryShift {
+                bits,
+                length,
+                opcode,
+                lhs,
+                rhs,
+            } => write!(
+                f,
+                "{} {} {}, {}",
+                opcode,
+                bitvec(*bits, NumRepr::Int, *length),
+                lhs,
+                rhs
+            ),
+            Self::CompareBitvec {
+                bits,
+                number,
+                length,
+                predicate,
+                lhs,
+                rhs,
+            } => write!(
+                f,
+                "cmp {} {} {}, {}",
+                predicate,
+                bitvec(*bits, *number, *length),
+                lhs,
+                rhs
+            ),
+            Self::CompareOrder {
+                bits,
+                length,
+                ordered,
+                lhs,
+                rhs,
+            } => write!(
+                f,
+                "cmp {} {} {}, {}",
+                if *ordered { "ord" } else { "uno" },
+                bitvec(*bits, NumRepr::Float, *length),
+                lhs,
+                rhs
+            ),
+            Self::ComparePtr {
+                predicate,
+                lhs,
+                rhs,
+            } => write!(f, "cmp {} ptr {}, {}", predicate, lhs, rhs),
+            Self::CastBitvecSize {
+                bits_from,
+                bits_into,
+                number,
+                length,
+                operand,
+            } => write!(
+                f,
+                "resize {} {} to {}",
+                bitvec(*bits_from, *number, *length),
+                operand,
+                bitvec(*bits_into, *number, *length)
+            ),
+            Self::CastBitvecRepr {
+                bits_from,
+                bits_into,
+                number_from,
+                number_into,
+                length,
+                operand,
+            } => write!(
+                f,
+                "convert {} {} to {}",
+                bitvec(*bits_from, *number_from, *length),
+                operand,
+                bitvec(*bits_into, *number_into, *length)
+            ),
+            Self::CastBitvecFree {
+                bits_from,
+                bits_into,
+                number_from,
+                number_into,
+                length_from,
+                length_into,
+                operand,
+            } => write!(
+                f,
+                "bitcast {} {} to {}",
+                bitvec(*bits_from, *number_from, *length_from),
+                operand,
+                bitvec(*bits_into, *number_into, *length_into)
+            ),
+            Self::CastPtr { operand } => write!(f, "cast ptr {}", operand),
+            Self::CastPtrToInt { bits_into, operand } => {
+                write!(f, "ptr_to_int ptr {} to int{}", operand, bits_into)
+            }
+            Self::CastIntToPtr { bits_from, operand } => {
+                write!(f, "int_to_ptr int{} {} to ptr", bits_from, operand)
+            }
+            Self::GEP {
+                src_pointee_type,
+                dst_pointee_type,
+                pointer,
+                offset,
+                indices,
+            } => write!(
+                f,
+                "gep {}, {}, [{}]{} -> {}",
+                src_pointee_type,
+                pointer,
+                offset,
+                join_display(indices, ""),
+                dst_pointee_type
+            ),
+            Self::GEPNop {
+                pointee_type,
+                pointer,
+            } => write!(f, "gep {}, {}", pointee_type, pointer),
+            Self::ITEOne {
+                cond,
+                then_value,
+                else_value,
+            } => write!(f, "ite {}, {}, {}", cond, then_value, else_value),
+            Self::ITEVec {
+                bits,
+                number,
+                length,
+                cond,
+                then_value,
+                else_value,
+            } => write!(
+                f,
+                "ite {} {}, {}, {}",
+                bitvec(*bits, *number, Some(*length)),
+                cond,
+                then_value,
+                else_value
+            ),
+            Self::GetValue {
+                src_ty,
+                dst_ty,
+                aggregate,
+                indices,
+            } => write!(
+                f,
+                "get_value {} {}, [{}] -> {}",
+                src_ty,
+                aggregate,
+                join_display(indices, ", "),
+                dst_ty
+            ),
+            Self::SetValue {
+                aggregate,
+                value,
+                indices,
+            } => write!(
+                f,
+                "set_value {}, {}, [{}]",
+                aggregate,
+                value,
+                join_display(indices, ", ")
+            ),
+            Self::GetElement {
+                bits,
+                number,
+                length,
+                vector,
+                slot,
+            } => write!(
+                f,
+                "get_element {} {}, {}",
+                bitvec(*bits, *number, Some(*length)),
+                vector,
+                slot
+            ),
+            Self::SetElement {
+                bits,
+                number,
+                length,
+                vector,
+                value,
+                slot,
+            } => write!(
+                f,
+                "set_element {} {}, {}, {}",
+                bitvec(*bits, *number, Some(*length)),
+                vector,
+                value,
+                slot
+            ),
+            Self::ShuffleVec {
+                bits,
+                number,
+                length,
+                lhs,
+                rhs,
+                mask,
+            } => write!(
+                f,
+                "shuffle {} {}, {}, [{}]",
+                bitvec(*bits, *number, Some(*length)),
+                lhs,
+                rhs,
+                join_display(mask, ", ")
+            ),
+        }
+    }
+}
+
+/// Mantissa precision (including the implicit bit) of a floating-point type
+fn float_precision(bits: usize) -> Option<u32> {
+    let prec = match bits {
+        16 => 11,
+        32 => 24,
+        64 => 53,
+        80 => 64,
+        128 => 113,
+        _ => return None,
+    };
+    Some(prec)
+}
+
+/// Check whether the most significant bit of an unsigned bitvec value is set
+fn int_msb(bits: usize, value: &Integer) -> bool {
+    value.get_bit((bits - 1) as u32)
+}
+
+fn int_one(bits: usize, value: Integer) -> Constant {
+    Constant::NumOne {
+        bits,
+        value: NumValue::Int(value.keep_bits(bits as u32)),
+    }
+}
+
+fn float_one(bits: usize, value: Rational) -> Option<Constant> {
+    // round the exact result to the precision of the target type
+    let rounded = Float::with_val(float_precision(bits)?, &value).to_rational()?;
+    Some(Constant::NumOne {
+        bits,
+        value: NumValue::Float(Some(rounded)),
+    })
+}
+
+fn bool_one(value: bool) -> Constant {
+    int_one(
+        1,
+        if value {
+            Integer::ONE.clone()
+        } else {
+            Integer::ZERO
+        },
+    )
+}
+
+fn expect_int(constant: &Constant) -> Option<&Integer> {
+    match constant {
+        Constant::NumOne {
+            bits: _,
+            value: NumValue::Int(v),
+        } => Some(v),
+        _ => None,
+    }
+}
+
+fn expect_float(constant: &Constant) -> Option<&Rational> {
+    match constant {
+        Constant::NumOne {
+            bits: _,
+            value: NumValue::Float(Some(v)),
+        } => Some(v),
+        _ => None,
+    }
+}
+
+/// Apply a scalar folding function to a scalar or element-wise to a vector
+fn fold_unary<F>(
+    length: Option<usize>,
+    bits: usize,
+    number: NumRepr,
+    operand: &Constant,
+    func: F,
+) -> Option<Constant>
+where
+    F: Fn(&Constant) -> Option<Constant>,
+{
+    match length {
+        None => func(operand),
+        Some(_) => match operand {
+            Constant::NumVec { elements, .. } => Some(Constant::NumVec {
+                bits,
+                number,
+                elements: elements.iter().map(func).collect::<Option<_>>()?,
+            }),
+            _ => None,
+        },
+    }
+}
+
+/// Apply a scalar folding function to scalars or element-wise to vectors
+fn fold_binary<F>(
+    length: Option<usize>,
+    bits: usize,
+    number: NumRepr,
+    lhs: &Constant,
+    rhs: &Constant,
+    func: F,
+) -> Option<Constant>
+where
+    F: Fn(&Constant, &Constant) -> Option<Constant>,
+{
+    match length {
+        None => func(lhs, rhs),
+        Some(_) => match (lhs, rhs) {
+            (
+                Constant::NumVec {
+                    elements: lhs_elems,
+                    ..
+                },
+                Constant::NumVec {
+                    elements: rhs_elems,
+                    ..
+                },
+            ) => Some(Constant::NumVec {
+                bits,
+                number,
+                elements: lhs_elems
+                    .iter()
+                    .zip(rhs_elems.iter())
+                    .map(|(l, r)| func(l, r))
+                    .collect::<Option<_>>()?,
+            }),
+            _ => None,
+        },
+    }
+}
+
+fn compare_ordering(predicate: &ComparePredicate, ordering: std::cmp::Ordering) -> bool {
+    use std::cmp::Ordering;
+    match predicate {
+        ComparePredicate::EQ => ordering == Ordering::Equal,
+        ComparePredicate::NE => ordering != Ordering::Equal,
+        ComparePredicate::GT => ordering == Ordering::Greater,
+        ComparePredicate::GE => ordering != Ordering::Less,
+        ComparePredicate::LT => ordering == Ordering::Less,
+        ComparePredicate::LE => ordering != Ordering::Greater,
+    }
+}
+
+impl Expression {
+    /// Fold the expression into a concrete constant, if possible.
+    ///
+    /// Signedness is not preserved in the bridge (e.g., `udiv` and `sdiv` are
+    /// both `Div`), hence an operation is only folded when the signed and the
+    /// unsigned interpretations agree. Expressions involving undef values,
+    /// symbolic addresses, or non-finite floats are left untouched.
+    pub fn evaluate(&self) -> Option<Constant> {
+        match self {
+            Self::UnaryArith {
+                bits,
+                number,
+                length,
+                opcode,
+                operand,
+            } => fold_unary(*length, *bits, *number, operand, |v| {
+                match (number, opcode) {
+                    (NumRepr::Int, UnaryOpArith::Neg) => {
+                        Some(int_one(*bits, Integer::from(-expect_int(v)?)))
+                    }
+                    (NumRepr::Float, UnaryOpArith::Neg) => {
+                        float_one(*bits, Rational::from(-expect_float(v)?))
+                    }
+                }
+            }),
+            Self::BinaryArith {
+                bits,
+                number,
+                length,
+                opcode,
+                lhs,
+                rhs,
+            } => fold_binary(*length, *bits, *number, lhs, rhs, |l, r| match number {
+                NumRepr::Int => {
+                    let l = expect_int(l)?;
+                    let r = expect_int(r)?;
+                    let v = match opcode {
+                        BinaryOpArith::Add => Integer::from(l + r),
+                        BinaryOpArith::Sub => Integer::from(l - r),
+                        BinaryOpArith::Mul => Integer::from(l * r),
+                        BinaryOpArith::Div | BinaryOpArith::Mod => {
+                            if *r == 0 || int_msb(*bits, l) || int_msb(*bits, r) {
+                                return None;
+                            }
+                            if matches!(opcode, BinaryOpArith::Div) {
+                                Integer::from(l / r)
+                            } else {
+                                Integer::from(l % r)
+                            }
+                        }
+                    };
+                    Some(int_one(*bits, v))
+                }
+                NumRepr::Float => {
+                    let l = expect_float(l)?;
+                    let r = expect_float(r)?;
+                    let v = match opcode {
+                        BinaryOpArith::Add => Rational::from(l + r),
+                        BinaryOpArith::Sub => Rational::from(l - r),
+                        BinaryOpArith::Mul => Rational::from(l * r),
+                        BinaryOpArith::Div => {
+                            if *r == 0 {
+                                return None;
+                            }
+                            Rational::from(l / r)
+                        }
+                        BinaryOpArith::Mod => {
+                            if *r == 0 {
+                                return None;
+                            }
+                            // frem takes the sign of the dividend
+                            let quotient = Rational::from(l / r);
+                            let truncated = Integer::from(quotient.numer() / quotient.denom());
+                            l - Rational::from(r * truncated)
+                        }
+                    };
+                    float_one(*bits, v)
+                }
+            }),
+            Self::BinaryBitwise {
+                bits,
+                length,
+                opcode,
+                lhs,
+                rhs,
+            } => fold_binary(*length, *bits, NumRepr::Int, lhs, rhs, |l, r| {
+                let l = expect_int(l)?;
+                let r = expect_int(r)?;
+                let v = match opcode {
+                    BinaryOpBitwise::And => Integer::from(l & r),
+                    BinaryOpBitwise::Or => Integer::from(l | r),
+                    BinaryOpBitwise::Xor => Integer::from(l ^ r),
+                };
+                Some(int_one(*bits, v))
+            }),
+            Self::BinaryShift {
+                bits,
+                length,
+                opcode,
+                lhs,
+                rhs,
+            } => fold_binary(*length, *bits, NumRepr::Int, lhs, rhs, |l, r| {
+                let l = expect_int(l)?;
+                let r = expect_int(r)?;
+                // shifting by the bit width or more yields poison
+                let amount = r.to_u32().filter(|n| (*n as usize) < *bits)?;
+                let v = match opcode {
+                    BinaryOpShift::Shl => Integer::from(l << amount),
+                    BinaryOpShift::Shr => {
+                        if int_msb(*bits, l) {
+                            return None;
+                        }
+                        Integer::from(l >> amount)
+                    }
+                };
+                Some(int_one(*bits, v))
+            }),
+            Self::CompareBitvec {
+                bits,
+                number,
+                length,
+                predicate,
+                lhs,
+                rhs,
+            } => fold_binary(*length, 1, NumRepr::Int, lhs, rhs, |l, r| {
+                let ordering = match number {
+                    NumRepr::Int => {
+                        let l = expect_int(l)?;
+                        let r = expect_int(r)?;
+                        let agnostic =
+                            matches!(predicate, ComparePredicate::EQ | ComparePredicate::NE);
+                        if !agnostic && int_msb(*bits, l) != int_msb(*bits, r) {
+                            return None;
+                        }
+                        l.cmp(r)
+                    }
+                    NumRepr::Float => expect_float(l)?.cmp(expect_float(r)?),
+                };
+                Some(bool_one(compare_ordering(predicate, ordering)))
+            }),
+            Self::CompareOrder {
+                bits: _,
+                length,
+                ordered,
+                lhs,
+                rhs,
+            } => fold_binary(*length, 1, NumRepr::Int, lhs, rhs, |l, r| {
+                expect_float(l)?;
+                expect_float(r)?;
+                Some(bool_one(*ordered))
+            }),
+            Self::ComparePtr {
+                predicate,
+                lhs,
+                rhs,
+            } => {
+                // only identical addresses can be decided without a memory model
+                let same = match (lhs, rhs) {
+                    (Constant::Null, Constant::Null) => true,
+                    (Constant::Variable { name: l }, Constant::Variable { name: r })
+                    | (Constant::Function { name: l }, Constant::Function { name: r }) => l == r,
+                    _ => false,
+                };
+                if !same {
+                    return None;
+                }
+                Some(bool_one(compare_ordering(
+                    predicate,
+                    std::cmp::Ordering::Equal,
+                )))
+            }
+            Self::CastBitvecSize {
+                bits_from,
+                bits_into,
+                number,
+                length,
+                operand,
+            } => fold_unary(*length, *bits_into, *number, operand, |v| match number {
+                NumRepr::Int => {
+                    let v = expect_int(v)?;
+                    if bits_into > bits_from && int_msb(*bits_from, v) {
+                        return None;
+                    }
+                    Some(int_one(*bits_into, v.clone()))
+                }
+                NumRepr::Float => float_one(*bits_into, expect_float(v)?.clone()),
+            }),
+            Self::CastBitvecRepr {
+                bits_from,
+                bits_into,
+                number_from,
+                number_into,
+                length,
+                operand,
+            } => fold_unary(*length, *bits_into, *number_into, operand, |v| {
+                match (number_from, number_into) {
+                    (NumRepr::Int, NumRepr::Float) => {
+                        let v = expect_int(v)?;
+                        if int_msb(*bits_from, v) {
+                            return None;
+                        }
+                        float_one(*bits_into, Rational::from(v))
+                    }
+                    (NumRepr::Float, NumRepr::Int) => {
+                        let v = expect_float(v)?;
+                        let truncated = Integer::from(v.numer() / v.denom());
+                        // out-of-range conversions yield poison
+                        if truncated < 0 || truncated.significant_bits() as usize >= *bits_into {
+                            return None;
+                        }
+                        Some(int_one(*bits_into, truncated))
+                    }
+                    _ => None,
+                }
+            }),
+            Self::CastBitvecFree {
+                bits_from,
+                bits_into,
+                number_from,
+                number_into,
+                length_from,
+                length_into,
+                operand,
+            } => {
+                // only no-op casts are folded
+                if bits_from == bits_into
+                    && number_from == number_into
+                    && length_from == length_into
+                {
+                    Some(operand.clone())
+                } else {
+                    None
+                }
+            }
+            Self::CastPtr { operand } => Some(operand.clone()),
+            Self::CastPtrToInt { bits_into, operand } => match operand {
+                Constant::Null => Some(int_one(*bits_into, Integer::ZERO)),
+                _ => None,
+            },
+            Self::CastIntToPtr {
+                bits_from: _,
+                operand,
+            } => match expect_int(operand) {
+                Some(v) if *v == 0 => Some(Constant::Null),
+                _ => None,
+            },
+            Self::GEP {
+                src_pointee_type: _,
+                dst_pointee_type: _,
+                pointer,
+                offset,
+                indices,
+            } => {
+                // a GEP with all-zero indices does not move the pointer
+                let is_zero = |c: &Constant| expect_int(c).is_some_and(|v| *v == 0);
+                if !is_zero(offset) {
+                    return None;
+                }
+                for idx in indices {
+                    let zero = match idx {
+                        GEPConstIndex::Array(v) | GEPConstIndex::Vector(v) => is_zero(v),
+                        GEPConstIndex::Struct(v) => *v == 0,
+                    };
+                    if !zero {
+                        return None;
+                    }
+                }
+                Some(pointer.clone())
+            }
+            Self::GEPNop {
+                pointee_type: _,
+                pointer,
+            } => Some(pointer.clone()),
+            Self::ITEOne {
+                cond,
+                then_value,
+                else_value,
+            } => match expect_int(cond) {
+                None => None,
+                Some(v) if *v == 0 => Some(else_value.clone()),
+                Some(_) => Some(then_value.clone()),
+            },
+            Self::ITEVec {
+                bits,
+                number,
+                length: _,
+                cond,
+                then_value,
+                else_value,
+            } => match (cond, then_value, else_value) {
+                (
+                    Constant::NumVec {
+                        elements: conds, ..
+                    },
+                    Constant::NumVec {
+                        elements: then_elems,
+                        ..
+                    },
+                    Constant::NumVec {
+                        elements: else_elems,
+                        ..
+                    },
+                ) => {
+                    let mut elements = vec![];
+                    for ((c, t), e) in conds.iter().zip(then_elems).zip(else_elems) {
+                        let selected = if *expect_int(c)? == 0 { e } else { t };
+                        elements.push(selected.clone());
+                    }
+                    Some(Constant::NumVec {
+                        bits: *bits,
+                        number: *number,
+                        elements,
+                    })
+                }
+                _ => None,
+            },
+            Self::GetValue {
+                src_ty: _,
+                dst_ty: _,
+                aggregate,
+                indices,
+            } => {
+                let mut cursor = aggregate;
+                for idx in indices {
+                    cursor = match cursor {
+                        Constant::Array { sub: _, elements } => elements.get(*idx)?,
+                        Constant::Struct { name: _, fields } => fields.get(*idx)?,
+                        _ => return None,
+                    };
+                }
+                Some(cursor.clone())
+            }
+            Self::SetValue { .. } => None,
+            Self::GetElement {
+                bits: _,
+                number: _,
+                length: _,
+                vector,
+                slot,
+            } => match vector {
+                Constant::NumVec { elements, .. } => {
+                    let slot = expect_int(slot)?.to_usize()?;
+                    elements.get(slot).cloned()
+                }
+                _ => None,
+            },
+            Self::SetElement { .. } => None,
+            Self::ShuffleVec { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use petgraph::algo::dominators::simple_fast;
+use petgraph::graph::DiGraph;
+
+use crate::ir::bridge::value::BlockLabel;
+
+/// A dominator tree over the blocks of a CFG, rooted at a virtual node that
+/// leads to the entry block (dominators) or to every exit block (post-dominators)
+pub struct DominatorTree {
+    /// immediate dominator of each reachable block, `None` if it is the virtual root
+    idom: BTreeMap<BlockLabel, Option<BlockLabel>>,
+    /// dominance frontier of each reachable block
+    frontiers: BTreeMap<BlockLabel, BTreeSet<BlockLabel>>,
+}
+
+impl DominatorTree {
+    /// Compute the tree over the edges (in the direction of dominance) and the roots
+    pub(crate) fn compute(
+        nodes: &BTreeSet<BlockLabel>,
+        edges: &[(BlockLabel, BlockLabel)],
+        roots: &BTreeSet<BlockLabel>,
+    ) -> Self {
+        // build the graph with a virtual root
+        let mut graph = DiGraph::new();
+        let root = graph.add_node(None);
+        let mut label_to_index = BTreeMap::new();
+        for label in nodes {
+            label_to_index.insert(*label, graph.add_node(Some(*label)));
+        }
+        let mut preds: BTreeMap<BlockLabel, BTreeSet<Option<BlockLabel>>> = BTreeMap::new();
+        for label in roots {
+            graph.add_edge(root, label_to_index[label], ());
+            preds.entry(*label).or_default().insert(None);
+        }
+        for (src, dst) in edges {
+            graph.add_edge(label_to_index[src], label_to_index[dst], ());
+            preds.entry(*dst).or_default().insert(Some(*src));
+        }
+
+        // immediate dominators
+        let doms = simple_fast(&graph, root);
+        let mut idom = BTreeMap::new();
+        for (label, index) in &label_to_index {
+            if let Some(parent) = doms.immediate_dominator(*index) {
+                idom.insert(*label, graph[parent]);
+            }
+        }
+
+        // dominance frontiers, following Cooper, Harvey, and Kennedy
+        let mut frontiers: BTreeMap<BlockLabel, BTreeSet<BlockLabel>> =
+            idom.keys().map(|label| (*label, BTreeSet::new())).collect();
+        for (label, parent) in &idom {
+            let incoming = match preds.get(label) {
+                None => continue,
+                Some(incoming) => incoming,
+            };
+            if incoming.len() < 2 {
+                continue;
+            }
+            for pred in incoming {
+                let mut runner = *pred;
+                while runner != *parent {
+                    let cursor = match runner {
+                        None => break,
+                        Some(cursor) => cursor,
+                    };
+                    let next = match idom.get(&cursor) {
+                        // unreachable predecessors do not contribute
+                        None => break,
+                        Some(next) => *next,
+                    };
+                    frontiers.entry(cursor).or_default().insert(*label);
+                    runner = next;
+                }
+            }
+        }
+
+        Self { idom, frontiers }
+    }
+
+    /// Whether the block is reachable from the root
+    pub fn is_reachable(&self, label: &BlockLabel) -> bool {
+        self.idom.contains_key(label)
+    }
+
+    /// Immediate dominator of the block, if there is one
+    pub fn immediate_dominator(&self, label: &BlockLabel) -> Option<BlockLabel> {
+        self.idom.get(label).copied().flatten()
+    }
+
+    /// Blocks immediately dominated by the block
+    pub fn children(&self, label: &BlockLabel) -> BTreeSet<BlockLabel> {
+        self.idom
+            .iter()
+            .filter(|(_, parent)| parent.as_ref() == Some(label))
+            .map(|(child, _)| *child)
+            .collect()
+    }
+
+    /// Whether block `a` dominates block `b` (a block dominates itself)
+    pub fn dominates(&self, a: &BlockLabel, b: &BlockLabel) -> bool {
+        if !self.is_reachable(a) || !self.is_reachable(b) {
+            return false;
+        }
+        let mut cursor = Some(*b);
+        while let Some(label) = cursor {
+            if &label == a {
+                return true;
+            }
+            cursor = self.immediate_dominator(&label);
+        }
+        false
+    }
+
+    /// Dominance frontier of the block
+    pub fn frontier(&self, label: &BlockLabel) -> BTreeSet<BlockLabel> {
+        self.frontiers.get(label).cloned().unwrap_or_default()
+    }
+
+    /// Iterated dominance frontier of a set of blocks (e.g., for phi placement)
+    pub fn iterated_frontier(&self, blocks: &BTreeSet<BlockLabel>) -> BTreeSet<BlockLabel> {
+        let mut result = BTreeSet::new();
+        let mut worklist: Vec<_> = blocks.iter().copied().collect();
+        let mut visited: BTreeSet<_> = blocks.clone();
+        while let Some(label) = worklist.pop() {
+            for item in self.frontier(&label) {
+                result.insert(item);
+                if visited.insert(item) {
+                    worklist.push(item);
+                }
+            }
+        }
+        result
+    }
+}
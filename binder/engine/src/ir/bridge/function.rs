@@ -1,12 +1,84 @@
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
 use crate::error::{EngineError, EngineResult, Unsupported};
 use crate::ir::adapter;
 use crate::ir::bridge::cfg::ControlFlowGraph;
+use crate::ir::bridge::constant::UndefSemantics;
+use crate::ir::bridge::global::Linkage;
 use crate::ir::bridge::intrinsics::filter_intrinsics;
 use crate::ir::bridge::shared::{Identifier, SymbolRegistry};
 use crate::ir::bridge::typing::{Type, TypeRegistry};
 
+/// An adapted representation of an LLVM calling convention
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Ord, PartialOrd, Clone, Copy, Debug)]
+pub enum CallingConvention {
+    C,
+    Fast,
+    Cold,
+    GHC,
+    PreserveMost,
+    PreserveAll,
+    Swift,
+    Tail,
+    SwiftTail,
+    /// target-specific or rarely used conventions, by LLVM id
+    Other(usize),
+}
+
+impl CallingConvention {
+    pub fn parse(id: usize) -> Self {
+        match id {
+            0 => Self::C,
+            8 => Self::Fast,
+            9 => Self::Cold,
+            10 => Self::GHC,
+            14 => Self::PreserveMost,
+            15 => Self::PreserveAll,
+            16 => Self::Swift,
+            18 => Self::Tail,
+            20 => Self::SwiftTail,
+            _ => Self::Other(id),
+        }
+    }
+}
+
+impl Display for CallingConvention {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::C => write!(f, "ccc"),
+            Self::Fast => write!(f, "fastcc"),
+            Self::Cold => write!(f, "coldcc"),
+            Self::GHC => write!(f, "ghccc"),
+            Self::PreserveMost => write!(f, "preserve_mostcc"),
+            Self::PreserveAll => write!(f, "preserve_allcc"),
+            Self::Swift => write!(f, "swiftcc"),
+            Self::Tail => write!(f, "tailcc"),
+            Self::SwiftTail => write!(f, "swifttailcc"),
+            Self::Other(id) => write!(f, "cc{}", id),
+        }
+    }
+}
+
+/// An adapted representation of LLVM attributes on pointer values
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Clone, Default)]
+pub struct ValueAttributes {
+    /// the pointer does not alias with others
+    pub no_alias: bool,
+    /// the pointer is not null
+    pub non_null: bool,
+    /// alignment of the pointer
+    pub align: Option<usize>,
+    /// passed by value, with the pointee type
+    pub by_val: Option<Type>,
+    /// struct return, with the pointee type
+    pub struct_ret: Option<Type>,
+}
+
 /// An adapted representation of an LLVM function parameter
-#[derive(Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub struct Parameter {
     /// name
     pub name: Option<Identifier>,
@@ -14,10 +86,101 @@ pub struct Parameter {
     pub ty: Type,
     /// element annotation
     pub annotated_pointee_type: Option<Type>,
+    /// pointer-related attributes
+    pub attrs: ValueAttributes,
+}
+
+/// An adapted representation of an LLVM function attribute
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Ord, PartialOrd, Clone, Debug)]
+pub enum FunctionAttribute {
+    NoReturn,
+    ReadNone,
+    ReadOnly,
+    NoUnwind,
+    /// an attribute not consumed by the analyses, kept by name only
+    Ignored(String),
+}
+
+impl FunctionAttribute {
+    pub fn parse(name: &str) -> Self {
+        match name {
+            "noreturn" => Self::NoReturn,
+            "readnone" => Self::ReadNone,
+            "readonly" => Self::ReadOnly,
+            "nounwind" => Self::NoUnwind,
+            _ => Self::Ignored(name.to_string()),
+        }
+    }
+}
+
+impl Display for ValueAttributes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            no_alias,
+            non_null,
+            align,
+            by_val,
+            struct_ret,
+        } = self;
+
+        let mut items = vec![];
+        if *no_alias {
+            items.push("noalias".to_string());
+        }
+        if *non_null {
+            items.push("nonnull".to_string());
+        }
+        if let Some(n) = align {
+            items.push(format!("align({})", n));
+        }
+        if let Some(ty) = by_val {
+            items.push(format!("byval({})", ty));
+        }
+        if let Some(ty) = struct_ret {
+            items.push(format!("sret({})", ty));
+        }
+        write!(f, "{}", items.join(" "))
+    }
+}
+
+impl Display for Parameter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            name,
+            ty,
+            annotated_pointee_type,
+            attrs,
+        } = self;
+
+        write!(f, "{}", ty)?;
+        if let Some(pointee) = annotated_pointee_type {
+            write!(f, "<{}>", pointee)?;
+        }
+        let attrs = attrs.to_string();
+        if !attrs.is_empty() {
+            write!(f, " {}", attrs)?;
+        }
+        if let Some(n) = name {
+            write!(f, " {}", n)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for FunctionAttribute {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoReturn => write!(f, "noreturn"),
+            Self::ReadNone => write!(f, "readnone"),
+            Self::ReadOnly => write!(f, "readonly"),
+            Self::NoUnwind => write!(f, "nounwind"),
+            Self::Ignored(name) => write!(f, "{}", name),
+        }
+    }
 }
 
 /// An adapted representation of an LLVM function
-#[derive(Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub struct Function {
     /// function name
     pub name: Identifier,
@@ -25,14 +188,54 @@ pub struct Function {
     pub params: Vec<Parameter>,
     /// has variadic args
     pub variadic: bool,
+    /// calling convention
+    pub conv: CallingConvention,
+    /// linkage type
+    pub linkage: Linkage,
     /// return type
     pub ret: Option<Type>,
+    /// return value attributes
+    pub ret_attrs: ValueAttributes,
     /// one-definition rule (ODR)
     pub is_weak: bool,
+    /// function attributes
+    pub attrs: BTreeSet<FunctionAttribute>,
     /// body of the function (in terms of a CFG)
     pub body: Option<ControlFlowGraph>,
 }
 
+impl ValueAttributes {
+    pub fn convert(
+        attrs: &adapter::function::ValueAttrs,
+        expected_ty: &Type,
+        typing: &TypeRegistry,
+    ) -> EngineResult<Self> {
+        let adapter::function::ValueAttrs {
+            no_alias,
+            non_null,
+            align,
+            by_val,
+            struct_ret,
+        } = attrs;
+
+        // all these attributes only make sense on pointers
+        let converted = Self {
+            no_alias: *no_alias,
+            non_null: *non_null,
+            align: *align,
+            by_val: by_val.as_ref().map(|t| typing.convert(t)).transpose()?,
+            struct_ret: struct_ret.as_ref().map(|t| typing.convert(t)).transpose()?,
+        };
+        if converted != Self::default() && !matches!(expected_ty, Type::Pointer) {
+            return Err(EngineError::InvalidAssumption(format!(
+                "only pointer values can have pointer attributes, found on {}",
+                expected_ty
+            )));
+        }
+        Ok(converted)
+    }
+}
+
 impl Parameter {
     fn set_or_check_annotated_type(
         current: &mut Option<Type>,
@@ -83,6 +286,7 @@ impl Parameter {
             struct_ret,
             in_alloca,
             element_type,
+            attrs,
         } = param;
 
         // extract basic type
@@ -139,10 +343,14 @@ impl Parameter {
             "element-type",
         )?;
 
+        // extract pointer-related attributes
+        let attrs_new = ValueAttributes::convert(attrs, expected_ty, typing)?;
+
         Ok(Parameter {
             name: param_name.as_ref().map(|e| e.into()),
             ty: param_ty_new,
             annotated_pointee_type,
+            attrs: attrs_new,
         })
     }
 }
@@ -152,6 +360,7 @@ impl Function {
         func: &adapter::function::Function,
         typing: &TypeRegistry,
         symbols: &SymbolRegistry,
+        semantics: UndefSemantics,
     ) -> EngineResult<Self> {
         let adapter::function::Function {
             name,
@@ -159,6 +368,10 @@ impl Function {
             is_defined,
             is_exact,
             is_intrinsic,
+            calling_conv,
+            linkage,
+            attrs,
+            ret_attrs,
             params,
             blocks,
         } = func;
@@ -188,6 +401,9 @@ impl Function {
             }
         };
 
+        // convert attributes
+        let attrs_new = attrs.iter().map(|a| FunctionAttribute::parse(a)).collect();
+
         // convert parameters
         if params.len() != param_tys.len() {
             return Err(EngineError::InvalidAssumption(format!(
@@ -201,6 +417,11 @@ impl Function {
             .map(|(p, t)| Parameter::convert(p, &t, typing))
             .collect::<EngineResult<_>>()?;
 
+        let ret_attrs_new = match &ret_ty {
+            None => ValueAttributes::default(),
+            Some(t) => ValueAttributes::convert(ret_attrs, t, typing)?,
+        };
+
         let body = if *is_defined {
             if blocks.is_empty() {
                 return Err(EngineError::InvalidAssumption(format!(
@@ -219,6 +440,7 @@ impl Function {
             Some(ControlFlowGraph::build(
                 typing,
                 symbols,
+                semantics,
                 &params_new,
                 ret_ty.as_ref(),
                 blocks,
@@ -232,8 +454,12 @@ impl Function {
             name: ident,
             params: params_new,
             variadic,
+            conv: CallingConvention::parse(*calling_conv),
+            linkage: Linkage::parse(linkage)?,
             ret: ret_ty,
+            ret_attrs: ret_attrs_new,
             is_weak: !*is_exact,
+            attrs: attrs_new,
             body,
         })
     }
@@ -276,3 +502,65 @@ impl Function {
         Ok(val)
     }
 }
+
+impl Function {
+    /// Body of the function the callers can rely on, i.e., none for a definition the linker may
+    /// replace with another one
+    pub fn exact_body(&self) -> Option<&ControlFlowGraph> {
+        if self.linkage.is_interposable() {
+            return None;
+        }
+        self.body.as_ref()
+    }
+
+    /// Signature of the function, i.e., everything except the body
+    pub fn signature(&self) -> String {
+        let Self {
+            name,
+            params,
+            variadic,
+            conv,
+            linkage,
+            ret,
+            ret_attrs,
+            is_weak,
+            attrs,
+            body,
+        } = self;
+
+        let mut items: Vec<_> = params.iter().map(|p| p.to_string()).collect();
+        if *variadic {
+            items.push("...".to_string());
+        }
+        let mut repr = format!(
+            "{} {}{} {}",
+            if body.is_some() { "define" } else { "declare" },
+            linkage,
+            if *is_weak { " weak" } else { "" },
+            conv
+        );
+        let ret_attrs = ret_attrs.to_string();
+        if !ret_attrs.is_empty() {
+            repr.push_str(&format!(" {}", ret_attrs));
+        }
+        match ret {
+            None => repr.push_str(" void"),
+            Some(ty) => repr.push_str(&format!(" {}", ty)),
+        }
+        repr.push_str(&format!(" @{}({})", name, items.join(", ")));
+        for attr in attrs {
+            repr.push_str(&format!(" {}", attr));
+        }
+        repr
+    }
+}
+
+impl Display for Function {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.signature())?;
+        match &self.body {
+            None => Ok(()),
+            Some(cfg) => write!(f, " {{\n{}}}", cfg),
+        }
+    }
+}
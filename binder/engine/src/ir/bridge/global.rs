@@ -1,11 +1,123 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
 use crate::error::{EngineError, EngineResult, Unsupported};
 use crate::ir::adapter;
-use crate::ir::bridge::constant::Constant;
+use crate::ir::bridge::constant::{Constant, UndefSemantics};
+use crate::ir::bridge::layout::DataLayout;
 use crate::ir::bridge::shared::{Identifier, SymbolRegistry};
 use crate::ir::bridge::typing::{Type, TypeRegistry};
 
+/// An adapted representation of an LLVM linkage type
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Clone, Copy, Debug)]
+pub enum Linkage {
+    External,
+    AvailableExternally,
+    LinkOnceAny,
+    LinkOnceODR,
+    WeakAny,
+    WeakODR,
+    Appending,
+    Internal,
+    Private,
+    ExternalWeak,
+    Common,
+}
+
+impl Linkage {
+    pub fn parse(name: &str) -> EngineResult<Self> {
+        let linkage = match name {
+            "external" => Self::External,
+            "available_externally" => Self::AvailableExternally,
+            "link_once_any" => Self::LinkOnceAny,
+            "link_once_odr" => Self::LinkOnceODR,
+            "weak_any" => Self::WeakAny,
+            "weak_odr" => Self::WeakODR,
+            "appending" => Self::Appending,
+            "internal" => Self::Internal,
+            "private" => Self::Private,
+            "external_weak" => Self::ExternalWeak,
+            "common" => Self::Common,
+            _ => {
+                return Err(EngineError::InvalidAssumption(format!(
+                    "unknown linkage type: {}",
+                    name
+                )));
+            }
+        };
+        Ok(linkage)
+    }
+
+    /// Whether the symbol is only visible within the module
+    pub fn is_local(&self) -> bool {
+        matches!(self, Self::Internal | Self::Private)
+    }
+
+    /// Whether a definition of the symbol may be replaced by another one at link time
+    pub fn is_interposable(&self) -> bool {
+        matches!(self, Self::WeakAny | Self::LinkOnceAny | Self::ExternalWeak)
+    }
+}
+
+impl Display for Linkage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let repr = match self {
+            Self::External => "external",
+            Self::AvailableExternally => "available_externally",
+            Self::LinkOnceAny => "link_once_any",
+            Self::LinkOnceODR => "link_once_odr",
+            Self::WeakAny => "weak_any",
+            Self::WeakODR => "weak_odr",
+            Self::Appending => "appending",
+            Self::Internal => "internal",
+            Self::Private => "private",
+            Self::ExternalWeak => "external_weak",
+            Self::Common => "common",
+        };
+        write!(f, "{}", repr)
+    }
+}
+
+/// An adapted representation of an LLVM visibility style
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Clone, Copy, Debug)]
+pub enum Visibility {
+    Default,
+    Hidden,
+    Protected,
+}
+
+impl Visibility {
+    pub fn parse(name: &str) -> EngineResult<Self> {
+        let visibility = match name {
+            "default" => Self::Default,
+            "hidden" => Self::Hidden,
+            "protected" => Self::Protected,
+            _ => {
+                return Err(EngineError::InvalidAssumption(format!(
+                    "unknown visibility type: {}",
+                    name
+                )));
+            }
+        };
+        Ok(visibility)
+    }
+}
+
+impl Display for Visibility {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let repr = match self {
+            Self::Default => "default",
+            Self::Hidden => "hidden",
+            Self::Protected => "protected",
+        };
+        write!(f, "{}", repr)
+    }
+}
+
 /// An adapted representation of an LLVM global variable
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Clone)]
 pub struct GlobalVariable {
     /// variable name
     pub name: Identifier,
@@ -15,6 +127,12 @@ pub struct GlobalVariable {
     pub is_weak: bool,
     /// mutability
     pub is_constant: bool,
+    /// linkage type
+    pub linkage: Linkage,
+    /// visibility
+    pub visibility: Visibility,
+    /// section, if explicitly specified
+    pub section: Option<String>,
     /// initializer
     pub initializer: Option<Constant>,
 }
@@ -24,6 +142,7 @@ impl GlobalVariable {
         gvar: &adapter::global::GlobalVariable,
         typing: &TypeRegistry,
         symbols: &SymbolRegistry,
+        semantics: UndefSemantics,
     ) -> EngineResult<Self> {
         let adapter::global::GlobalVariable {
             name,
@@ -33,6 +152,9 @@ impl GlobalVariable {
             is_exact,
             is_thread_local,
             address_space,
+            linkage,
+            visibility,
+            section,
             initializer,
         } = gvar;
 
@@ -77,7 +199,9 @@ impl GlobalVariable {
                         ident
                     )));
                 }
-                Some(Constant::convert(constant, &gvar_ty, typing, symbols)?)
+                Some(Constant::convert(
+                    constant, &gvar_ty, typing, symbols, semantics,
+                )?)
             }
         };
 
@@ -87,10 +211,35 @@ impl GlobalVariable {
             ty: gvar_ty,
             is_weak: !*is_exact,
             is_constant: *is_const,
+            linkage: Linkage::parse(linkage)?,
+            visibility: Visibility::parse(visibility)?,
+            section: section.clone(),
             initializer: gvar_init,
         })
     }
 
+    /// Whether the variable may be modified by code outside of the module
+    pub fn is_externally_mutable(&self) -> bool {
+        !self.is_constant && !self.linkage.is_local()
+    }
+
+    /// Flatten the initializer (if any) into scalars keyed by their byte offsets
+    pub fn flatten_initializer(
+        &self,
+        layout: &DataLayout,
+    ) -> EngineResult<Option<FlatInitializer>> {
+        let init = match &self.initializer {
+            None => return Ok(None),
+            Some(init) => init,
+        };
+        let mut cells = BTreeMap::new();
+        flatten(layout, &self.ty, init, 0, &mut cells)?;
+        Ok(Some(FlatInitializer {
+            size: layout.store_size(&self.ty)?,
+            cells,
+        }))
+    }
+
     /// Apply the one definition rule
     pub fn apply_odr(entries: Vec<Self>) -> EngineResult<Self> {
         // obtain the strongly defined symbol
@@ -131,3 +280,185 @@ impl GlobalVariable {
         Ok(val)
     }
 }
+
+/// A scalar in the initial contents of a global variable
+#[derive(Clone)]
+pub struct InitCell {
+    /// type of the scalar
+    pub ty: Type,
+    /// number of bytes the scalar occupies
+    pub size: usize,
+    /// value of the scalar
+    pub value: Constant,
+}
+
+/// The initializer of a global variable, with nested arrays, structs, and vectors resolved
+pub struct FlatInitializer {
+    /// number of bytes of the variable
+    size: usize,
+    /// scalars keyed by their byte offsets (padding bytes have no entries)
+    cells: BTreeMap<usize, InitCell>,
+}
+
+impl FlatInitializer {
+    /// Number of bytes of the variable
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// All scalars keyed by their byte offsets
+    pub fn cells(&self) -> &BTreeMap<usize, InitCell> {
+        &self.cells
+    }
+
+    /// The scalar starting exactly at the byte offset
+    pub fn get(&self, offset: usize) -> Option<&InitCell> {
+        self.cells.get(&offset)
+    }
+
+    /// The scalar covering the byte at the offset, along with the offset of the byte within it
+    pub fn covering(&self, offset: usize) -> Option<(usize, &InitCell)> {
+        let (start, cell) = self.cells.range(..=offset).next_back()?;
+        let delta = offset - start;
+        if delta < cell.size {
+            Some((delta, cell))
+        } else {
+            None
+        }
+    }
+
+    /// The scalar read by an access of the type at the byte offset, if it matches exactly
+    pub fn read(&self, offset: usize, ty: &Type) -> Option<&Constant> {
+        self.get(offset)
+            .filter(|cell| &cell.ty == ty)
+            .map(|cell| &cell.value)
+    }
+
+    /// Scalars of an array-typed variable keyed by element index, given the element size
+    pub fn elements(&self, stride: usize) -> BTreeMap<usize, Vec<(usize, &InitCell)>> {
+        let mut elements: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        if stride == 0 {
+            return elements;
+        }
+        for (offset, cell) in &self.cells {
+            elements
+                .entry(offset / stride)
+                .or_default()
+                .push((offset % stride, cell));
+        }
+        elements
+    }
+}
+
+/// Utility: flatten a constant of the type placed at the byte offset
+fn flatten(
+    layout: &DataLayout,
+    ty: &Type,
+    constant: &Constant,
+    offset: usize,
+    cells: &mut BTreeMap<usize, InitCell>,
+) -> EngineResult<()> {
+    match (ty, constant) {
+        (Type::Array { element, length }, Constant::Array { sub: _, elements }) => {
+            if elements.len() != *length {
+                return Err(EngineError::InvariantViolation(
+                    "array initializer length mismatch".into(),
+                ));
+            }
+            let stride = layout.alloc_size(element)?;
+            for (i, item) in elements.iter().enumerate() {
+                flatten(layout, element, item, offset + i * stride, cells)?;
+            }
+        }
+        (
+            Type::Struct {
+                name: _,
+                fields,
+                packed,
+            },
+            Constant::Struct {
+                name: _,
+                fields: values,
+            },
+        ) => {
+            if fields.len() != values.len() {
+                return Err(EngineError::InvariantViolation(
+                    "struct initializer field count mismatch".into(),
+                ));
+            }
+            let struct_layout = layout.struct_layout(fields, *packed)?;
+            for ((field, value), field_offset) in
+                fields.iter().zip(values).zip(&struct_layout.offsets)
+            {
+                flatten(layout, field, value, offset + field_offset, cells)?;
+            }
+        }
+        (
+            Type::Bitvec {
+                bits,
+                number,
+                length: Some(n),
+            },
+            Constant::NumVec {
+                bits: _,
+                number: _,
+                elements,
+            },
+        ) if bits.is_multiple_of(8) && elements.len() == *n => {
+            // byte-aligned lanes are addressable individually
+            let scalar = Type::Bitvec {
+                bits: *bits,
+                number: *number,
+                length: None,
+            };
+            let stride = bits / 8;
+            for (i, item) in elements.iter().enumerate() {
+                flatten(layout, &scalar, item, offset + i * stride, cells)?;
+            }
+        }
+        _ => {
+            cells.insert(
+                offset,
+                InitCell {
+                    ty: ty.clone(),
+                    size: layout.store_size(ty)?,
+                    value: constant.clone(),
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
+impl Display for GlobalVariable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            name,
+            ty,
+            is_weak,
+            is_constant,
+            linkage,
+            visibility,
+            section,
+            initializer,
+        } = self;
+
+        write!(
+            f,
+            "@{} = {} {}{} {} {}",
+            name,
+            linkage,
+            visibility,
+            if *is_weak { " weak" } else { "" },
+            if *is_constant { "constant" } else { "global" },
+            ty
+        )?;
+        if let Some(init) = initializer {
+            write!(f, " = {}", init)?;
+        }
+        if let Some(sec) = section {
+            write!(f, ", section \"{}\"", sec)?;
+        }
+        Ok(())
+    }
+}
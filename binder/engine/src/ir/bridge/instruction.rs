@@ -1,34 +1,42 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
 
 use rug::Integer;
+use serde::{Deserialize, Serialize};
 
 use crate::error::{EngineError, EngineResult, Unsupported};
 use crate::ir::adapter;
-use crate::ir::bridge::constant::{Constant, NumValue};
+use crate::ir::bridge::constant::{join_display, Constant, NumValue, UndefSemantics};
+use crate::ir::bridge::function::{CallingConvention, ValueAttributes};
 use crate::ir::bridge::intrinsics::filter_intrinsics;
 use crate::ir::bridge::shared::{Identifier, SymbolRegistry};
 use crate::ir::bridge::typing::{NumRepr, Type, TypeRegistry};
 use crate::ir::bridge::value::{BlockLabel, RegisterSlot, Value};
 
 /// An naive translation of an LLVM instruction
-#[derive(Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum Instruction {
     // memory access
     Alloca {
         base_type: Type,
         size: Option<Value>,
+        align: usize,
         result: RegisterSlot,
     },
     Load {
         pointee_type: Type,
         pointer: Value,
+        align: usize,
+        volatile: bool,
         result: RegisterSlot,
     },
     Store {
         pointee_type: Type,
         pointer: Value,
         value: Value,
+        align: usize,
+        volatile: bool,
     },
     // variadic argument
     VariadicArg {
@@ -37,14 +45,23 @@ pub enum Instruction {
     // call
     CallDirect {
         function: Identifier,
+        conv: CallingConvention,
         args: Vec<Value>,
+        arg_attrs: Vec<ValueAttributes>,
         result: Option<(Type, RegisterSlot)>,
     },
     CallIndirect {
         callee: Value,
+        conv: CallingConvention,
         args: Vec<Value>,
+        arg_attrs: Vec<ValueAttributes>,
         result: Option<(Type, RegisterSlot)>,
     },
+    // specification from source code
+    Contract {
+        kind: ContractKind,
+        cond: Value,
+    },
     // unary
     UnaryArith {
         bits: usize,
@@ -60,6 +77,10 @@ pub enum Instruction {
         number: NumRepr,
         length: Option<usize>,
         opcode: BinaryOpArith,
+        /// no signed wrap: a signed overflow yields poison
+        nsw: bool,
+        /// no unsigned wrap: an unsigned overflow yields poison
+        nuw: bool,
         lhs: Value,
         rhs: Value,
         result: RegisterSlot,
@@ -242,7 +263,41 @@ pub enum Instruction {
     },
 }
 
-#[derive(Eq, PartialEq, Clone)]
+/// A specification written in C source code via `libra_assume.h`
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Clone, Copy)]
+pub enum ContractKind {
+    /// the condition holds, paths where it does not are discarded
+    Assume,
+    /// the condition must hold, violations are to be reported
+    Assert,
+    /// the condition holds on every iteration of the enclosing loop
+    Invariant,
+}
+
+impl ContractKind {
+    /// Recognize the reserved function that a specification macro lowers to
+    pub fn from_callee(name: &str) -> Option<Self> {
+        let kind = match name {
+            "__libra_assume" => Self::Assume,
+            "__libra_assert" => Self::Assert,
+            "__libra_invariant" => Self::Invariant,
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
+
+impl Display for ContractKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Assume => write!(f, "assume"),
+            Self::Assert => write!(f, "assert"),
+            Self::Invariant => write!(f, "invariant"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Clone)]
 pub enum UnaryOpArith {
     Neg,
 }
@@ -266,7 +321,7 @@ impl UnaryOperator {
     }
 }
 
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Clone)]
 pub enum BinaryOpArith {
     Add,
     Sub,
@@ -275,14 +330,14 @@ pub enum BinaryOpArith {
     Mod,
 }
 
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Clone)]
 pub enum BinaryOpBitwise {
     And,
     Or,
     Xor,
 }
 
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Clone)]
 pub enum BinaryOpShift {
     Shl,
     Shr,
@@ -323,7 +378,7 @@ impl BinaryOperator {
     }
 }
 
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Clone)]
 pub enum ComparePredicate {
     EQ,
     NE,
@@ -333,6 +388,32 @@ pub enum ComparePredicate {
     LE,
 }
 
+impl ComparePredicate {
+    /// The predicate that holds when this one does not
+    pub fn negate(&self) -> Self {
+        match self {
+            Self::EQ => Self::NE,
+            Self::NE => Self::EQ,
+            Self::LT => Self::GE,
+            Self::GE => Self::LT,
+            Self::LE => Self::GT,
+            Self::GT => Self::LE,
+        }
+    }
+
+    /// The predicate with its operands swapped
+    pub fn swap(&self) -> Self {
+        match self {
+            Self::EQ => Self::EQ,
+            Self::NE => Self::NE,
+            Self::LT => Self::GT,
+            Self::GT => Self::LT,
+            Self::LE => Self::GE,
+            Self::GE => Self::LE,
+        }
+    }
+}
+
 pub enum CompareOperator {
     Pred(ComparePredicate, NumRepr),
     Ord(bool),
@@ -372,7 +453,7 @@ impl CompareOperator {
 }
 
 /// Represents an index into an aggregate in the GEP instruction
-#[derive(Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub enum GEPIndex {
     /// element index in array
     Array(Value),
@@ -383,7 +464,7 @@ pub enum GEPIndex {
 }
 
 /// Represents an exception clause
-#[derive(Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub enum ExceptionDirective {
     CatchAll,
     CatchOne(Identifier),
@@ -392,7 +473,7 @@ pub enum ExceptionDirective {
 }
 
 /// An naive translation of an LLVM terminator instruction
-#[derive(Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub enum Terminator {
     /// function return
     Return { val: Option<Value> },
@@ -407,6 +488,7 @@ pub enum Terminator {
     /// switch
     Switch {
         cond: Value,
+        #[serde(with = "crate::ir::bridge::shared::map_as_pairs")]
         cases: BTreeMap<Integer, BlockLabel>,
         default: Option<BlockLabel>,
     },
@@ -418,7 +500,9 @@ pub enum Terminator {
     /// direct invoke
     InvokeDirect {
         function: Identifier,
+        conv: CallingConvention,
         args: Vec<Value>,
+        arg_attrs: Vec<ValueAttributes>,
         result: Option<(Type, RegisterSlot)>,
         normal: BlockLabel,
         unwind: BlockLabel,
@@ -426,7 +510,9 @@ pub enum Terminator {
     /// indirect invoke
     InvokeIndirect {
         callee: Value,
+        conv: CallingConvention,
         args: Vec<Value>,
+        arg_attrs: Vec<ValueAttributes>,
         result: Option<(Type, RegisterSlot)>,
         normal: BlockLabel,
         unwind: BlockLabel,
@@ -437,10 +523,924 @@ pub enum Terminator {
     Unreachable,
 }
 
+impl Display for UnaryOpArith {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Neg => write!(f, "neg"),
+        }
+    }
+}
+
+impl Display for BinaryOpArith {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let repr = match self {
+            Self::Add => "add",
+            Self::Sub => "sub",
+            Self::Mul => "mul",
+            Self::Div => "div",
+            Self::Mod => "mod",
+        };
+        write!(f, "{}", repr)
+    }
+}
+
+impl Display for BinaryOpBitwise {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let repr = match self {
+            Self::And => "and",
+            Self::Or => "or",
+            Self::Xor => "xor",
+        };
+        write!(f, "{}", repr)
+    }
+}
+
+impl Display for BinaryOpShift {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let repr = match self {
+            Self::Shl => "shl",
+            Self::Shr => "shr",
+        };
+        write!(f, "{}", repr)
+    }
+}
+
+impl Display for ComparePredicate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let repr = match self {
+            Self::EQ => "eq",
+            Self::NE => "ne",
+            Self::GT => "gt",
+            Self::GE => "ge",
+            Self::LT => "lt",
+            Self::LE => "le",
+        };
+        write!(f, "{}", repr)
+    }
+}
+
+impl Display for GEPIndex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Array(v) => write!(f, "[{}]", v),
+            Self::Struct(v) => write!(f, ".{}", v),
+            Self::Vector(v) => write!(f, "<{}>", v),
+        }
+    }
+}
+
+impl Display for ExceptionDirective {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CatchAll => write!(f, "catch *"),
+            Self::CatchOne(name) => write!(f, "catch @{}", name),
+            Self::FilterAll => write!(f, "filter *"),
+            Self::FilterOne(names) => {
+                let repr: Vec<_> = names.iter().map(|n| format!("@{}", n)).collect();
+                write!(f, "filter [{}]", repr.join(", "))
+            }
+        }
+    }
+}
+
+/// Utility: type of a bitvec for display
+fn bitvec(bits: usize, number: NumRepr, length: Option<usize>) -> Type {
+    Type::Bitvec {
+        bits,
+        number,
+        length,
+    }
+}
+
+/// Utility: arguments of a call site for display
+fn call_args(args: &[Value], arg_attrs: &[ValueAttributes]) -> String {
+    args.iter()
+        .zip(arg_attrs)
+        .map(|(arg, attrs)| {
+            let attrs = attrs.to_string();
+            if attrs.is_empty() {
+                arg.to_string()
+            } else {
+                format!("{} {}", attrs, arg)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Utility: result of a call site for display
+fn call_result(result: &Option<(Type, RegisterSlot)>) -> String {
+    match result {
+        None => "void".to_string(),
+        Some((ty, slot)) => format!("{} = {}", slot, ty),
+    }
+}
+
+impl Instruction {
+    /// Register defined by this instruction, if any
+    pub fn result(&self) -> Option<RegisterSlot> {
+        match self {
+            Self::Alloca { result, .. }
+            | Self::Load { result, .. }
+            | Self::UnaryArith { result, .. }
+            | Self::BinaryArith { result, .. }
+            | Self::BinaryBitwise { result, .. }
+            | Self::BinaryShift { result, .. }
+            | Self::CompareBitvec { result, .. }
+            | Self::CompareOrder { result, .. }
+            | Self::ComparePtr { result, .. }
+            | Self::CastBitvecSize { result, .. }
+            | Self::CastBitvecRepr { result, .. }
+            | Self::CastBitvecFree { result, .. }
+            | Self::CastPtr { result, .. }
+            | Self::CastPtrToInt { result, .. }
+            | Self::CastIntToPtr { result, .. }
+            | Self::GEP { result, .. }
+            | Self::GEPNop { result, .. }
+            | Self::ITEOne { result, .. }
+            | Self::ITEVec { result, .. }
+            | Self::Phi { result, .. }
+            | Self::GetValue { result, .. }
+            | Self::SetValue { result, .. }
+            | Self::GetElement { result, .. }
+            | Self::SetElement { result, .. }
+            | Self::ShuffleVec { result, .. }
+            | Self::LandingPad { result, .. } => Some(*result),
+            Self::CallDirect { result, .. } | Self::CallIndirect { result, .. } => {
+                result.as_ref().map(|(_, slot)| *slot)
+            }
+            Self::Store { .. }
+            | Self::VariadicArg { .. }
+            | Self::Contract { .. }
+            | Self::FreezeBitvec { .. }
+            | Self::FreezePtr
+            | Self::FreezeNop { .. } => None,
+        }
+    }
+
+    /// Whether this instruction is a volatile memory access, i.e., the accessed location
+    /// may change or be observed outside of the program and must not be folded
+    pub fn is_volatile(&self) -> bool {
+        match self {
+            Self::Load { volatile, .. } | Self::Store { volatile, .. } => *volatile,
+            _ => false,
+        }
+    }
+
+    /// Values read by this instruction, in order of appearance
+    pub fn operands(&self) -> Vec<&Value> {
+        match self {
+            Self::Alloca {
+                base_type: _,
+                size,
+                align: _,
+                result: _,
+            } => size.iter().collect(),
+            Self::Load {
+                pointee_type: _,
+                pointer,
+                align: _,
+                volatile: _,
+                result: _,
+            } => vec![pointer],
+            Self::Store {
+                pointee_type: _,
+                pointer,
+                value,
+                align: _,
+                volatile: _,
+            } => vec![pointer, value],
+            Self::VariadicArg { pointer } => vec![pointer],
+            Self::CallDirect {
+                function: _,
+                conv: _,
+                args,
+                arg_attrs: _,
+                result: _,
+            } => args.iter().collect(),
+            Self::CallIndirect {
+                callee,
+                conv: _,
+                args,
+                arg_attrs: _,
+                result: _,
+            } => std::iter::once(callee).chain(args).collect(),
+            Self::Contract { kind: _, cond } => vec![cond],
+            Self::UnaryArith { operand, .. }
+            | Self::CastBitvecSize { operand, .. }
+            | Self::CastBitvecRepr { operand, .. }
+            | Self::CastBitvecFree { operand, .. }
+            | Self::CastPtr { operand, .. }
+            | Self::CastPtrToInt { operand, .. }
+            | Self::CastIntToPtr { operand, .. } => vec![operand],
+            Self::BinaryArith { lhs, rhs, .. }
+            | Self::BinaryBitwise { lhs, rhs, .. }
+            | Self::BinaryShift { lhs, rhs, .. }
+            | Self::CompareBitvec { lhs, rhs, .. }
+            | Self::CompareOrder { lhs, rhs, .. }
+            | Self::ComparePtr { lhs, rhs, .. }
+            | Self::ShuffleVec { lhs, rhs, .. } => vec![lhs, rhs],
+            Self::FreezeBitvec { .. } | Self::FreezePtr => vec![],
+            Self::FreezeNop { value } => vec![value],
+            Self::GEP {
+                src_pointee_type: _,
+                dst_pointee_type: _,
+                pointer,
+                offset,
+                indices,
+                result: _,
+            } => {
+                let mut values = vec![pointer, offset];
+                for index in indices {
+                    match index {
+                        GEPIndex::Array(value) | GEPIndex::Vector(value) => values.push(value),
+                        GEPIndex::Struct(_) => (),
+                    }
+                }
+                values
+            }
+            Self::GEPNop {
+                pointee_type: _,
+                pointer,
+                result: _,
+            } => vec![pointer],
+            Self::ITEOne {
+                cond,
+                then_value,
+                else_value,
+                result: _,
+            }
+            | Self::ITEVec {
+                cond,
+                then_value,
+                else_value,
+                ..
+            } => vec![cond, then_value, else_value],
+            Self::Phi { options, result: _ } => options.values().collect(),
+            Self::GetValue { aggregate, .. } => vec![aggregate],
+            Self::SetValue {
+                aggregate,
+                value,
+                indices: _,
+                result: _,
+            } => vec![aggregate, value],
+            Self::GetElement { vector, slot, .. } => vec![vector, slot],
+            Self::SetElement {
+                vector,
+                value,
+                slot,
+                ..
+            } => vec![vector, value, slot],
+            Self::LandingPad { .. } => vec![],
+        }
+    }
+
+    /// Mutable references to the values read by this instruction
+    pub fn operands_mut(&mut self) -> Vec<&mut Value> {
+        match self {
+            Self::Alloca {
+                base_type: _,
+                size,
+                align: _,
+                result: _,
+            } => size.iter_mut().collect(),
+            Self::Load {
+                pointee_type: _,
+                pointer,
+                align: _,
+                volatile: _,
+                result: _,
+            } => vec![pointer],
+            Self::Store {
+                pointee_type: _,
+                pointer,
+                value,
+                align: _,
+                volatile: _,
+            } => vec![pointer, value],
+            Self::VariadicArg { pointer } => vec![pointer],
+            Self::CallDirect {
+                function: _,
+                conv: _,
+                args,
+                arg_attrs: _,
+                result: _,
+            } => args.iter_mut().collect(),
+            Self::CallIndirect {
+                callee,
+                conv: _,
+                args,
+                arg_attrs: _,
+                result: _,
+            } => std::iter::once(callee).chain(args).collect(),
+            Self::Contract { kind: _, cond } => vec![cond],
+            Self::UnaryArith { operand, .. }
+            | Self::CastBitvecSize { operand, .. }
+            | Self::CastBitvecRepr { operand, .. }
+            | Self::CastBitvecFree { operand, .. }
+            | Self::CastPtr { operand, .. }
+            | Self::CastPtrToInt { operand, .. }
+            | Self::CastIntToPtr { operand, .. } => vec![operand],
+            Self::BinaryArith { lhs, rhs, .. }
+            | Self::BinaryBitwise { lhs, rhs, .. }
+            | Self::BinaryShift { lhs, rhs, .. }
+            | Self::CompareBitvec { lhs, rhs, .. }
+            | Self::CompareOrder { lhs, rhs, .. }
+            | Self::ComparePtr { lhs, rhs, .. }
+            | Self::ShuffleVec { lhs, rhs, .. } => vec![lhs, rhs],
+            Self::FreezeBitvec { .. } | Self::FreezePtr => vec![],
+            Self::FreezeNop { value } => vec![value],
+            Self::GEP {
+                src_pointee_type: _,
+                dst_pointee_type: _,
+                pointer,
+                offset,
+                indices,
+                result: _,
+            } => {
+                let mut values = vec![pointer, offset];
+                for index in indices {
+                    match index {
+                        GEPIndex::Array(value) | GEPIndex::Vector(value) => values.push(value),
+                        GEPIndex::Struct(_) => (),
+                    }
+                }
+                values
+            }
+            Self::GEPNop {
+                pointee_type: _,
+                pointer,
+                result: _,
+            } => vec![pointer],
+            Self::ITEOne {
+                cond,
+                then_value,
+                else_value,
+                result: _,
+            }
+            | Self::ITEVec {
+                cond,
+                then_value,
+                else_value,
+                ..
+            } => vec![cond, then_value, else_value],
+            Self::Phi { options, result: _ } => options.values_mut().collect(),
+            Self::GetValue { aggregate, .. } => vec![aggregate],
+            Self::SetValue {
+                aggregate,
+                value,
+                indices: _,
+                result: _,
+            } => vec![aggregate, value],
+            Self::GetElement { vector, slot, .. } => vec![vector, slot],
+            Self::SetElement {
+                vector,
+                value,
+                slot,
+                ..
+            } => vec![vector, value, slot],
+            Self::LandingPad { .. } => vec![],
+        }
+    }
+}
+
+impl Terminator {
+    /// Register defined by this terminator, if any
+    pub fn result(&self) -> Option<RegisterSlot> {
+        match self {
+            Self::InvokeDirect { result, .. } | Self::InvokeIndirect { result, .. } => {
+                result.as_ref().map(|(_, slot)| *slot)
+            }
+            Self::Return { .. }
+            | Self::Goto { .. }
+            | Self::Branch { .. }
+            | Self::Switch { .. }
+            | Self::Indirect { .. }
+            | Self::Resume { .. }
+            | Self::Unreachable => None,
+        }
+    }
+
+    /// Values read by this terminator, in order of appearance
+    pub fn operands(&self) -> Vec<&Value> {
+        match self {
+            Self::Return { val } => val.iter().collect(),
+            Self::Goto { target: _ } | Self::Unreachable => vec![],
+            Self::Branch { cond, .. } | Self::Switch { cond, .. } => vec![cond],
+            Self::Indirect {
+                address,
+                targets: _,
+            } => vec![address],
+            Self::InvokeDirect { args, .. } => args.iter().collect(),
+            Self::InvokeIndirect { callee, args, .. } => {
+                std::iter::once(callee).chain(args).collect()
+            }
+            Self::Resume { val } => vec![val],
+        }
+    }
+
+    /// Mutable references to the values read by this terminator
+    pub fn operands_mut(&mut self) -> Vec<&mut Value> {
+        match self {
+            Self::Return { val } => val.iter_mut().collect(),
+            Self::Goto { target: _ } | Self::Unreachable => vec![],
+            Self::Branch { cond, .. } | Self::Switch { cond, .. } => vec![cond],
+            Self::Indirect {
+                address,
+                targets: _,
+            } => vec![address],
+            Self::InvokeDirect { args, .. } => args.iter_mut().collect(),
+            Self::InvokeIndirect { callee, args, .. } => {
+                std::iter::once(callee).chain(args).collect()
+            }
+            Self::Resume { val } => vec![val],
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Alloca {
+                base_type,
+                size,
+                align,
+                result,
+            } => match size {
+                None => write!(f, "{} = alloca {}, align {}", result, base_type, align),
+                Some(size) => write!(
+                    f,
+                    "{} = alloca {}, {}, align {}",
+                    result, base_type, size, align
+                ),
+            },
+            Self::Load {
+                pointee_type,
+                pointer,
+                align,
+                volatile,
+                result,
+            } => write!(
+                f,
+                "{} = load{} {}, {}, align {}",
+                result,
+                if *volatile { " volatile" } else { "" },
+                pointee_type,
+                pointer,
+                align
+            ),
+            Self::Store {
+                pointee_type,
+                pointer,
+                value,
+                align,
+                volatile,
+            } => write!(
+                f,
+                "store{} {}, {}, {}, align {}",
+                if *volatile { " volatile" } else { "" },
+                pointee_type,
+                pointer,
+                value,
+                align
+            ),
+            Self::VariadicArg { pointer } => write!(f, "va_arg {}", pointer),
+            Self::CallDirect {
+                function,
+                conv,
+                args,
+                arg_attrs,
+                result,
+            } => write!(
+                f,
+                "{} call {} @{}({})",
+                call_result(result),
+                conv,
+                function,
+                call_args(args, arg_attrs)
+            ),
+            Self::CallIndirect {
+                callee,
+                conv,
+                args,
+                arg_attrs,
+                result,
+            } => write!(
+                f,
+                "{} call {} {}({})",
+                call_result(result),
+                conv,
+                callee,
+                call_args(args, arg_attrs)
+            ),
+            Self::Contract { kind, cond } => write!(f, "{} {}", kind, cond),
+            Self::UnaryArith {
+                bits,
+                number,
+                length,
+                opcode,
+                operand,
+                result,
+            } => write!(
+                f,
+                "{} = {} {} {}",
+                result,
+                opcode,
+                bitvec(*bits, *number, *length),
+                operand
+            ),
+            Self::BinaryArith {
+                bits,
+                number,
+                length,
+                opcode,
+                nsw,
+                nuw,
+                lhs,
+                rhs,
+                result,
+            } => write!(
+                f,
+                "{} = {}{}{} {} {}, {}",
+                result,
+                opcode,
+                if *nsw { " nsw" } else { "" },
+                if *nuw { " nuw" } else { "" },
+                bitvec(*bits, *number, *length),
+                lhs,
+                rhs
+            ),
+            Self::BinaryBitwise {
+                bits,
+                length,
+                opcode,
+                lhs,
+                rhs,
+                result,
+            } => write!(
+                f,
+                "{} = {} {} {}, {}",
+                result,
+                opcode,
+                bitvec(*bits, NumRepr::Int, *length),
+                lhs,
+                rhs
+            ),
+            Self::BinaryShift {
+                bits,
+                length,
+                opcode,
+                lhs,
+                rhs,
+                result,
+            } => write!(
+                f,
+                "{} = {} {} {}, {}",
+                result,
+                opcode,
+                bitvec(*bits, NumRepr::Int, *length),
+                lhs,
+                rhs
+            ),
+            Self::CompareBitvec {
+                bits,
+                number,
+                length,
+                predicate,
+                lhs,
+                rhs,
+                result,
+            } => write!(
+                f,
+                "{} = cmp {} {} {}, {}",
+                result,
+                predicate,
+                bitvec(*bits, *number, *length),
+                lhs,
+                rhs
+            ),
+            Self::CompareOrder {
+                bits,
+                length,
+                ordered,
+                lhs,
+                rhs,
+                result,
+            } => write!(
+                f,
+                "{} = cmp {} {} {}, {}",
+                result,
+                if *ordered { "ord" } else { "uno" },
+                bitvec(*bits, NumRepr::Float, *length),
+                lhs,
+                rhs
+            ),
+            Self::ComparePtr {
+                predicate,
+                lhs,
+                rhs,
+                result,
+            } => write!(f, "{} = cmp {} ptr {}, {}", result, predicate, lhs, rhs),
+            Self::CastBitvecSize {
+                bits_from,
+                bits_into,
+                number,
+                length,
+                operand,
+                result,
+            } => write!(
+                f,
+                "{} = resize {} {} to {}",
+                result,
+                bitvec(*bits_from, *number, *length),
+                operand,
+                bitvec(*bits_into, *number, *length)
+            ),
+            Self::CastBitvecRepr {
+                bits_from,
+                bits_into,
+                number_from,
+                number_into,
+                length,
+                operand,
+                result,
+            } => write!(
+                f,
+                "{} = convert {} {} to {}",
+                result,
+                bitvec(*bits_from, *number_from, *length),
+                operand,
+                bitvec(*bits_into, *number_into, *length)
+            ),
+            Self::CastBitvecFree {
+                bits_from,
+                bits_into,
+                number_from,
+                number_into,
+                length_from,
+                length_into,
+                operand,
+                result,
+            } => write!(
+                f,
+                "{} = bitcast {} {} to {}",
+                result,
+                bitvec(*bits_from, *number_from, *length_from),
+                operand,
+                bitvec(*bits_into, *number_into, *length_into)
+            ),
+            Self::CastPtr { operand, result } => {
+                write!(f, "{} = cast ptr {}", result, operand)
+            }
+            Self::CastPtrToInt {
+                bits_into,
+                operand,
+                result,
+            } => write!(
+                f,
+                "{} = ptr_to_int ptr {} to int{}",
+                result, operand, bits_into
+            ),
+            Self::CastIntToPtr {
+                bits_from,
+                operand,
+                result,
+            } => write!(
+                f,
+                "{} = int_to_ptr int{} {} to ptr",
+                result, bits_from, operand
+            ),
+            Self::FreezeBitvec { bits, number } => {
+                write!(f, "freeze {}", bitvec(*bits, *number, None))
+            }
+            Self::FreezePtr => write!(f, "freeze ptr"),
+            Self::FreezeNop { value } => write!(f, "freeze {}", value),
+            Self::GEP {
+                src_pointee_type,
+                dst_pointee_type,
+                pointer,
+                offset,
+                indices,
+                result,
+            } => write!(
+                f,
+                "{} = gep {}, {}, [{}]{} -> {}",
+                result,
+                src_pointee_type,
+                pointer,
+                offset,
+                join_display(indices, ""),
+                dst_pointee_type
+            ),
+            Self::GEPNop {
+                pointee_type,
+                pointer,
+                result,
+            } => write!(f, "{} = gep {}, {}", result, pointee_type, pointer),
+            Self::ITEOne {
+                cond,
+                then_value,
+                else_value,
+                result,
+            } => write!(
+                f,
+                "{} = ite {}, {}, {}",
+                result, cond, then_value, else_value
+            ),
+            Self::ITEVec {
+                bits,
+                number,
+                length,
+                cond,
+                then_value,
+                else_value,
+                result,
+            } => write!(
+                f,
+                "{} = ite {} {}, {}, {}",
+                result,
+                bitvec(*bits, *number, Some(*length)),
+                cond,
+                then_value,
+                else_value
+            ),
+            Self::Phi { options, result } => {
+                let repr: Vec<_> = options
+                    .iter()
+                    .map(|(label, value)| format!("{}: {}", label, value))
+                    .collect();
+                write!(f, "{} = phi [{}]", result, repr.join(", "))
+            }
+            Self::GetValue {
+                src_ty,
+                dst_ty,
+                aggregate,
+                indices,
+                result,
+            } => write!(
+                f,
+                "{} = get_value {} {}, [{}] -> {}",
+                result,
+                src_ty,
+                aggregate,
+                join_display(indices, ", "),
+                dst_ty
+            ),
+            Self::SetValue {
+                aggregate,
+                value,
+                indices,
+                result,
+            } => write!(
+                f,
+                "{} = set_value {}, {}, [{}]",
+                result,
+                aggregate,
+                value,
+                join_display(indices, ", ")
+            ),
+            Self::GetElement {
+                bits,
+                number,
+                length,
+                vector,
+                slot,
+                result,
+            } => write!(
+                f,
+                "{} = get_element {} {}, {}",
+                result,
+                bitvec(*bits, *number, Some(*length)),
+                vector,
+                slot
+            ),
+            Self::SetElement {
+                bits,
+                number,
+                length,
+                vector,
+                value,
+                slot,
+                result,
+            } => write!(
+                f,
+                "{} = set_element {} {}, {}, {}",
+                result,
+                bitvec(*bits, *number, Some(*length)),
+                vector,
+                value,
+                slot
+            ),
+            Self::ShuffleVec {
+                bits,
+                number,
+                length,
+                lhs,
+                rhs,
+                mask,
+                result,
+            } => write!(
+                f,
+                "{} = shuffle {} {}, {}, [{}]",
+                result,
+                bitvec(*bits, *number, Some(*length)),
+                lhs,
+                rhs,
+                join_display(mask, ", ")
+            ),
+            Self::LandingPad {
+                directives,
+                is_cleanup,
+                result,
+            } => write!(
+                f,
+                "{} = landingpad{} [{}]",
+                result,
+                if *is_cleanup { " cleanup" } else { "" },
+                join_display(directives, ", ")
+            ),
+        }
+    }
+}
+
+impl Display for Terminator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Return { val } => match val {
+                None => write!(f, "ret void"),
+                Some(v) => write!(f, "ret {}", v),
+            },
+            Self::Goto { target } => write!(f, "goto {}", target),
+            Self::Branch {
+                cond,
+                then_case,
+                else_case,
+            } => write!(f, "br {}, {}, {}", cond, then_case, else_case),
+            Self::Switch {
+                cond,
+                cases,
+                default,
+            } => {
+                let repr: Vec<_> = cases
+                    .iter()
+                    .map(|(case, label)| format!("{}: {}", case, label))
+                    .collect();
+                write!(f, "switch {} [{}]", cond, repr.join(", "))?;
+                match default {
+                    None => Ok(()),
+                    Some(label) => write!(f, " default {}", label),
+                }
+            }
+            Self::Indirect { address, targets } => {
+                write!(f, "indirect {} [{}]", address, join_display(targets, ", "))
+            }
+            Self::InvokeDirect {
+                function,
+                conv,
+                args,
+                arg_attrs,
+                result,
+                normal,
+                unwind,
+            } => write!(
+                f,
+                "{} invoke {} @{}({}) to {} unwind {}",
+                call_result(result),
+                conv,
+                function,
+                call_args(args, arg_attrs),
+                normal,
+                unwind
+            ),
+            Self::InvokeIndirect {
+                callee,
+                conv,
+                args,
+                arg_attrs,
+                result,
+                normal,
+                unwind,
+            } => write!(
+                f,
+                "{} invoke {} {}({}) to {} unwind {}",
+                call_result(result),
+                conv,
+                callee,
+                call_args(args, arg_attrs),
+                normal,
+                unwind
+            ),
+            Self::Resume { val } => write!(f, "resume {}", val),
+            Self::Unreachable => write!(f, "unreachable"),
+        }
+    }
+}
+
 /// A context manager for converting instructions
 pub struct Context<'a> {
     pub typing: &'a TypeRegistry,
     pub symbols: &'a SymbolRegistry,
+    pub semantics: UndefSemantics,
     pub blocks: BTreeSet<usize>,
     pub insts: BTreeMap<usize, Option<Type>>,
     pub args: BTreeMap<usize, Type>,
@@ -448,6 +1448,17 @@ pub struct Context<'a> {
 }
 
 impl<'a> Context<'a> {
+    /// check an alignment (in bytes) on a memory access
+    fn parse_align(align: usize) -> EngineResult<usize> {
+        if !align.is_power_of_two() {
+            return Err(EngineError::InvalidAssumption(format!(
+                "alignment must be a power of two, got {}",
+                align
+            )));
+        }
+        Ok(align)
+    }
+
     /// convert a value
     pub fn parse_value(
         &mut self,
@@ -462,6 +1473,7 @@ impl<'a> Context<'a> {
                 expected_type,
                 self.typing,
                 self.symbols,
+                self.semantics,
             )?),
             AdaptedValue::Argument { ty, index } => {
                 let actual_ty = self.typing.convert(ty)?;
@@ -586,6 +1598,7 @@ impl<'a> Context<'a> {
             ty,
             index,
             repr,
+            debug: _,
         } = inst;
 
         let item = match repr {
@@ -593,6 +1606,7 @@ impl<'a> Context<'a> {
             AdaptedInst::Alloca {
                 allocated_type,
                 size,
+                align,
                 address_space,
             } => {
                 let inst_ty = self.typing.convert(ty)?;
@@ -614,12 +1628,15 @@ impl<'a> Context<'a> {
                 Instruction::Alloca {
                     base_type,
                     size: size_new,
+                    align: Self::parse_align(*align)?,
                     result: index.into(),
                 }
             }
             AdaptedInst::Load {
                 pointee_type,
                 pointer,
+                align,
+                is_volatile,
                 ordering,
                 address_space,
             } => {
@@ -643,6 +1660,8 @@ impl<'a> Context<'a> {
                 Instruction::Load {
                     pointee_type: pointee_type_new,
                     pointer: pointer_new,
+                    align: Self::parse_align(*align)?,
+                    volatile: *is_volatile,
                     result: index.into(),
                 }
             }
@@ -650,6 +1669,8 @@ impl<'a> Context<'a> {
                 pointee_type,
                 pointer,
                 value,
+                align,
+                is_volatile,
                 ordering,
                 address_space,
             } => {
@@ -674,6 +1695,8 @@ impl<'a> Context<'a> {
                     pointee_type: pointee_type_new,
                     pointer: pointer_new,
                     value: value_new,
+                    align: Self::parse_align(*align)?,
+                    volatile: *is_volatile,
                 }
             }
             AdaptedInst::VAArg { pointer } => {
@@ -686,7 +1709,9 @@ impl<'a> Context<'a> {
             AdaptedInst::CallDirect {
                 callee,
                 target_type,
+                calling_conv,
                 args,
+                arg_attrs,
             } => {
                 // extract the name of the called function
                 let callee_new = self.parse_value(callee, &Type::Pointer)?;
@@ -719,12 +1744,23 @@ impl<'a> Context<'a> {
                             ));
                         }
 
+                        if arg_attrs.len() != args.len() {
+                            return Err(EngineError::InvalidAssumption(
+                                "CallDirect number of argument attributes mismatch".into(),
+                            ));
+                        }
+
                         // conversion
                         let args_new: Vec<_> = params
                             .iter()
                             .zip(args.iter())
                             .map(|(t, v)| self.parse_value(v, t))
                             .collect::<EngineResult<_>>()?;
+                        let arg_attrs_new: Vec<_> = params
+                            .iter()
+                            .zip(arg_attrs.iter())
+                            .map(|(t, a)| ValueAttributes::convert(a, t, self.typing))
+                            .collect::<EngineResult<_>>()?;
                         let ret_ty = match ret {
                             None => {
                                 if !matches!(ty, AdaptedType::Void) {
@@ -745,10 +1781,32 @@ impl<'a> Context<'a> {
                             }
                         };
 
+                        // specifications from source code
+                        if let Some(kind) = ContractKind::from_callee(callee_name.as_ref()) {
+                            let is_cond = matches!(
+                                params.as_slice(),
+                                [Type::Bitvec {
+                                    number: NumRepr::Int,
+                                    length: None,
+                                    ..
+                                }]
+                            );
+                            if !is_cond || *variadic || ret_ty.is_some() {
+                                return Err(EngineError::InvalidAssumption(format!(
+                                    "{} expects one integer condition and no result",
+                                    callee_name
+                                )));
+                            }
+                            let cond = args_new.into_iter().next().unwrap();
+                            return Ok(Instruction::Contract { kind, cond });
+                        }
+
                         // construction
                         Instruction::CallDirect {
                             function: callee_name,
+                            conv: CallingConvention::parse(*calling_conv),
                             args: args_new,
+                            arg_attrs: arg_attrs_new,
                             result: ret_ty.map(|t| (t, index.into())),
                         }
                     }
@@ -762,7 +1820,9 @@ impl<'a> Context<'a> {
             AdaptedInst::CallIndirect {
                 callee,
                 target_type,
+                calling_conv,
                 args,
+                arg_attrs,
             } => {
                 // extract the indirect callee
                 let callee_new = self.parse_value(callee, &Type::Pointer)?;
@@ -792,12 +1852,23 @@ impl<'a> Context<'a> {
                             ));
                         }
 
+                        if arg_attrs.len() != args.len() {
+                            return Err(EngineError::InvalidAssumption(
+                                "CallIndirect number of argument attributes mismatch".into(),
+                            ));
+                        }
+
                         // conversion
                         let args_new: Vec<_> = params
                             .iter()
                             .zip(args.iter())
                             .map(|(t, v)| self.parse_value(v, t))
                             .collect::<EngineResult<_>>()?;
+                        let arg_attrs_new: Vec<_> = params
+                            .iter()
+                            .zip(arg_attrs.iter())
+                            .map(|(t, a)| ValueAttributes::convert(a, t, self.typing))
+                            .collect::<EngineResult<_>>()?;
                         let ret_ty = match ret {
                             None => {
                                 if !matches!(ty, AdaptedType::Void) {
@@ -821,7 +1892,9 @@ impl<'a> Context<'a> {
                         // construction
                         Instruction::CallIndirect {
                             callee: callee_new,
+                            conv: CallingConvention::parse(*calling_conv),
                             args: args_new,
+                            arg_attrs: arg_attrs_new,
                             result: ret_ty.map(|t| (t, index.into())),
                         }
                     }
@@ -835,7 +1908,9 @@ impl<'a> Context<'a> {
             AdaptedInst::Intrinsic {
                 callee,
                 target_type,
+                calling_conv,
                 args,
+                arg_attrs,
             } => {
                 // early filtering of unsupported intrinsics
                 let callee_new = self.parse_value(callee, &Type::Pointer)?;
@@ -871,12 +1946,23 @@ impl<'a> Context<'a> {
                             ));
                         }
 
+                        if arg_attrs.len() != args.len() {
+                            return Err(EngineError::InvalidAssumption(
+                                "CallIntrinsic number of argument attributes mismatch".into(),
+                            ));
+                        }
+
                         // conversion
                         let args_new: Vec<_> = params
                             .iter()
                             .zip(args.iter())
                             .map(|(t, v)| self.parse_value(v, t))
                             .collect::<EngineResult<_>>()?;
+                        let arg_attrs_new: Vec<_> = params
+                            .iter()
+                            .zip(arg_attrs.iter())
+                            .map(|(t, a)| ValueAttributes::convert(a, t, self.typing))
+                            .collect::<EngineResult<_>>()?;
                         let ret_ty = match ret {
                             None => {
                                 if !matches!(ty, AdaptedType::Void) {
@@ -900,7 +1986,9 @@ impl<'a> Context<'a> {
                         // construction
                         Instruction::CallDirect {
                             function: callee_name,
+                            conv: CallingConvention::parse(*calling_conv),
                             args: args_new,
+                            arg_attrs: arg_attrs_new,
                             result: ret_ty.map(|t| (t, index.into())),
                         }
                     }
@@ -941,7 +2029,13 @@ impl<'a> Context<'a> {
                 }
             }
             // binary
-            AdaptedInst::Binary { opcode, lhs, rhs } => {
+            AdaptedInst::Binary {
+                opcode,
+                no_signed_wrap,
+                no_unsigned_wrap,
+                lhs,
+                rhs,
+            } => {
                 let inst_ty = self.typing.convert(ty)?;
                 let lhs_new = self.parse_value(lhs, &inst_ty)?;
                 let rhs_new = self.parse_value(rhs, &inst_ty)?;
@@ -956,6 +2050,8 @@ impl<'a> Context<'a> {
                             number,
                             length,
                             opcode: operator,
+                            nsw: *no_signed_wrap,
+                            nuw: *no_unsigned_wrap,
                             lhs: lhs_new,
                             rhs: rhs_new,
                             result: index.into(),
@@ -1417,7 +2513,11 @@ impl<'a> Context<'a> {
                 let mut indices_new = vec![];
                 for idx in indices.iter().skip(1) {
                     let next_cur_ty = match cur_ty {
-                        Type::Struct { name: _, fields } => {
+                        Type::Struct {
+                            name: _,
+                            fields,
+                            packed: _,
+                        } => {
                             let idx_new = self.parse_value_int_any(idx)?;
                             let field_offset = match idx_new {
                                 Value::Constant(Constant::NumOne {
@@ -1582,7 +2682,11 @@ impl<'a> Context<'a> {
                 let mut cur_ty = &src_ty;
                 for idx in indices {
                     let next_cur_ty = match cur_ty {
-                        Type::Struct { name: _, fields } => {
+                        Type::Struct {
+                            name: _,
+                            fields,
+                            packed: _,
+                        } => {
                             if *idx >= fields.len() {
                                 return Err(EngineError::InvalidAssumption(
                                     "field number out of range".into(),
@@ -1631,7 +2735,11 @@ impl<'a> Context<'a> {
                 let mut cur_ty = &src_ty;
                 for idx in indices {
                     let next_cur_ty = match cur_ty {
-                        Type::Struct { name: _, fields } => {
+                        Type::Struct {
+                            name: _,
+                            fields,
+                            packed: _,
+                        } => {
                             if *idx >= fields.len() {
                                 return Err(EngineError::InvalidAssumption(
                                     "field number out of range".into(),
@@ -1888,6 +2996,7 @@ impl<'a> Context<'a> {
             ty,
             index,
             repr,
+            debug: _,
         } = inst;
 
         let term = match repr {
@@ -2006,8 +3115,13 @@ impl<'a> Context<'a> {
                         ));
                     }
 
-                    let case_val =
-                        Constant::convert(&case.value, &cond_ty_new, self.typing, self.symbols)?;
+                    let case_val = Constant::convert(
+                        &case.value,
+                        &cond_ty_new,
+                        self.typing,
+                        self.symbols,
+                        self.semantics,
+                    )?;
                     let label_val = match case_val {
                         Constant::NumOne {
                             bits: _,
@@ -2068,7 +3182,9 @@ impl<'a> Context<'a> {
             AdaptedInst::InvokeDirect {
                 callee,
                 target_type,
+                calling_conv,
                 args,
+                arg_attrs,
                 normal,
                 unwind,
             } => {
@@ -2103,12 +3219,23 @@ impl<'a> Context<'a> {
                             ));
                         }
 
+                        if arg_attrs.len() != args.len() {
+                            return Err(EngineError::InvalidAssumption(
+                                "InvokeDirect number of argument attributes mismatch".into(),
+                            ));
+                        }
+
                         // conversion
                         let args_new: Vec<_> = params
                             .iter()
                             .zip(args.iter())
                             .map(|(t, v)| self.parse_value(v, t))
                             .collect::<EngineResult<_>>()?;
+                        let arg_attrs_new: Vec<_> = params
+                            .iter()
+                            .zip(arg_attrs.iter())
+                            .map(|(t, a)| ValueAttributes::convert(a, t, self.typing))
+                            .collect::<EngineResult<_>>()?;
                         let ret_ty = match ret {
                             None => {
                                 if !matches!(ty, AdaptedType::Void) {
@@ -2147,7 +3274,9 @@ impl<'a> Context<'a> {
                         // construction
                         Terminator::InvokeDirect {
                             function: callee_name,
+                            conv: CallingConvention::parse(*calling_conv),
                             args: args_new,
+                            arg_attrs: arg_attrs_new,
                             result: ret_ty.map(|t| (t, index.into())),
                             normal: normal_label,
                             unwind: unwind_label,
@@ -2163,7 +3292,9 @@ impl<'a> Context<'a> {
             AdaptedInst::InvokeIndirect {
                 callee,
                 target_type,
+                calling_conv,
                 args,
+                arg_attrs,
                 normal,
                 unwind,
             } => {
@@ -2195,12 +3326,23 @@ impl<'a> Context<'a> {
                             ));
                         }
 
+                        if arg_attrs.len() != args.len() {
+                            return Err(EngineError::InvalidAssumption(
+                                "InvokeIndirect number of argument attributes mismatch".into(),
+                            ));
+                        }
+
                         // conversion
                         let args_new: Vec<_> = params
                             .iter()
                             .zip(args.iter())
                             .map(|(t, v)| self.parse_value(v, t))
                             .collect::<EngineResult<_>>()?;
+                        let arg_attrs_new: Vec<_> = params
+                            .iter()
+                            .zip(arg_attrs.iter())
+                            .map(|(t, a)| ValueAttributes::convert(a, t, self.typing))
+                            .collect::<EngineResult<_>>()?;
                         let ret_ty = match ret {
                             None => {
                                 if !matches!(ty, AdaptedType::Void) {
@@ -2239,7 +3381,9 @@ impl<'a> Context<'a> {
                         // construction
                         Terminator::InvokeIndirect {
                             callee: callee_new,
+                            conv: CallingConvention::parse(*calling_conv),
                             args: args_new,
+                            arg_attrs: arg_attrs_new,
                             result: ret_ty.map(|t| (t, index.into())),
                             normal: normal_label,
                             unwind: unwind_label,
@@ -0,0 +1,509 @@
+use std::collections::BTreeMap;
+
+use rug::Integer;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngineError, EngineResult};
+use crate::ir::bridge::constant::{Constant, Expression, GEPConstIndex, NumValue};
+use crate::ir::bridge::instruction::{GEPIndex, Instruction};
+use crate::ir::bridge::typing::{NumRepr, Type};
+use crate::ir::bridge::value::Value;
+
+/// Size and alignment (in bits) of a primitive type
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Copy, Clone, Debug)]
+struct AlignSpec {
+    abi: usize,
+    pref: usize,
+}
+
+impl AlignSpec {
+    fn new(abi: usize) -> Self {
+        Self { abi, pref: abi }
+    }
+}
+
+/// A parsed LLVM data layout string
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Clone, Debug)]
+pub struct DataLayout {
+    /// whether the target is little endian
+    pub little_endian: bool,
+    /// pointer size (in bits) of the default address space
+    pointer_size: usize,
+    /// pointer alignment of the default address space
+    pointer_align: AlignSpec,
+    /// alignment of integer types, keyed by bit width
+    ints: BTreeMap<usize, AlignSpec>,
+    /// alignment of floating-point types, keyed by bit width
+    floats: BTreeMap<usize, AlignSpec>,
+    /// alignment of vector types, keyed by total bit width
+    vectors: BTreeMap<usize, AlignSpec>,
+    /// alignment of aggregate types
+    aggregate: AlignSpec,
+}
+
+impl Default for DataLayout {
+    fn default() -> Self {
+        // defaults as documented in the LLVM language reference
+        let ints = [(1, 8), (8, 8), (16, 16), (32, 32), (64, 32)]
+            .into_iter()
+            .map(|(k, v)| (k, AlignSpec::new(v)))
+            .collect();
+        let floats = [(16, 16), (32, 32), (64, 64), (128, 128)]
+            .into_iter()
+            .map(|(k, v)| (k, AlignSpec::new(v)))
+            .collect();
+        let vectors = [(64, 64), (128, 128)]
+            .into_iter()
+            .map(|(k, v)| (k, AlignSpec::new(v)))
+            .collect();
+        Self {
+            little_endian: true,
+            pointer_size: 64,
+            pointer_align: AlignSpec::new(64),
+            ints,
+            floats,
+            vectors,
+            aggregate: AlignSpec { abi: 0, pref: 64 },
+        }
+    }
+}
+
+/// Memory layout of a struct
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+pub struct StructLayout {
+    /// allocation size in bytes (including trailing padding)
+    pub size: usize,
+    /// alignment in bytes
+    pub align: usize,
+    /// byte offset of each field
+    pub offsets: Vec<usize>,
+    /// bytes of padding inserted before each field
+    pub paddings: Vec<usize>,
+    /// bytes of padding after the last field
+    pub tail_padding: usize,
+}
+
+/// An index operand of a GEP, from either an instruction or a constant expression
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub enum SymbolicIndex<'a> {
+    /// index in a GEP instruction
+    Value(&'a Value),
+    /// index in a constant GEP expression
+    Constant(&'a Constant),
+}
+
+/// A scaled index in a byte offset
+#[derive(Eq, PartialEq, Clone, Copy)]
+pub struct OffsetTerm<'a> {
+    /// the index, interpreted as a signed integer
+    pub index: SymbolicIndex<'a>,
+    /// number of bytes per unit of the index
+    pub scale: usize,
+}
+
+/// A byte offset in the form of `constant + sum(scale * index)`
+#[derive(Eq, PartialEq, Clone)]
+pub struct ByteOffset<'a> {
+    /// constant part of the offset, in bytes
+    pub constant: Integer,
+    /// symbolic parts of the offset
+    pub terms: Vec<OffsetTerm<'a>>,
+}
+
+impl<'a> ByteOffset<'a> {
+    fn zero() -> Self {
+        Self {
+            constant: Integer::ZERO,
+            terms: vec![],
+        }
+    }
+
+    /// The exact offset, if all indices are constant
+    pub fn as_exact(&self) -> Option<&Integer> {
+        if self.terms.is_empty() {
+            Some(&self.constant)
+        } else {
+            None
+        }
+    }
+
+    fn add_index(&mut self, index: SymbolicIndex<'a>, scale: usize) {
+        let constant = match index {
+            SymbolicIndex::Value(Value::Constant(c)) | SymbolicIndex::Constant(c) => c,
+            SymbolicIndex::Value(Value::Argument { .. } | Value::Register { .. }) => {
+                self.terms.push(OffsetTerm { index, scale });
+                return;
+            }
+        };
+        match constant {
+            Constant::NumOne {
+                bits,
+                value: NumValue::Int(v),
+            } => {
+                // GEP indices are signed
+                let mut signed = v.clone();
+                if v.get_bit((*bits - 1) as u32) {
+                    signed -= Integer::ONE.clone() << (*bits as u32);
+                }
+                self.constant += signed * scale;
+            }
+            _ => self.terms.push(OffsetTerm { index, scale }),
+        }
+    }
+}
+
+/// A step taken while walking down the source type of a GEP
+enum GEPStep<'a> {
+    Field(usize),
+    Element(SymbolicIndex<'a>),
+}
+
+fn parse_bits(item: &str, spec: &str) -> EngineResult<usize> {
+    item.parse::<usize>().map_err(|_| {
+        EngineError::InvalidAssumption(format!("invalid number in data layout spec: {}", spec))
+    })
+}
+
+fn parse_align(items: &[&str], spec: &str) -> EngineResult<AlignSpec> {
+    let abi = match items.first() {
+        None => {
+            return Err(EngineError::InvalidAssumption(format!(
+                "missing alignment in data layout spec: {}",
+                spec
+            )));
+        }
+        Some(item) => parse_bits(item, spec)?,
+    };
+    let pref = match items.get(1) {
+        None => abi,
+        Some(item) => parse_bits(item, spec)?,
+    };
+    Ok(AlignSpec { abi, pref })
+}
+
+fn align_to(offset: usize, align: usize) -> usize {
+    if align == 0 {
+        return offset;
+    }
+    offset.div_ceil(align) * align
+}
+
+impl DataLayout {
+    /// Parse the data layout string of a module
+    pub fn parse(repr: &str) -> EngineResult<Self> {
+        let mut layout = Self::default();
+        for spec in repr.split('-').filter(|s| !s.is_empty()) {
+            let (kind, rest) = spec.split_at(1);
+            match kind {
+                "e" => layout.little_endian = true,
+                "E" => layout.little_endian = false,
+                "p" => {
+                    let mut items: Vec<_> = rest.split(':').collect();
+                    let address_space = match items.remove(0) {
+                        "" => 0,
+                        n => parse_bits(n, spec)?,
+                    };
+                    if items.is_empty() {
+                        return Err(EngineError::InvalidAssumption(format!(
+                            "missing pointer size in data layout spec: {}",
+                            spec
+                        )));
+                    }
+                    let size = parse_bits(items[0], spec)?;
+                    let align = parse_align(&items[1..], spec)?;
+                    // only the default address space is modeled
+                    if address_space == 0 {
+                        layout.pointer_size = size;
+                        layout.pointer_align = align;
+                    }
+                }
+                "i" | "f" | "v" => {
+                    let items: Vec<_> = rest.split(':').collect();
+                    let size = parse_bits(items[0], spec)?;
+                    let align = parse_align(&items[1..], spec)?;
+                    let table = match kind {
+                        "i" => &mut layout.ints,
+                        "f" => &mut layout.floats,
+                        _ => &mut layout.vectors,
+                    };
+                    table.insert(size, align);
+                }
+                "a" => {
+                    let items: Vec<_> = rest.split(':').collect();
+                    // the size field, if present, is ignored by LLVM
+                    let items = if items.first().is_some_and(|s| s.is_empty()) {
+                        &items[1..]
+                    } else {
+                        &items[..]
+                    };
+                    layout.aggregate = parse_align(items, spec)?;
+                }
+                // stack alignment, address spaces, function pointer alignment,
+                // mangling, native integer widths and non-integral pointers
+                // do not affect the layout of types
+                "S" | "P" | "A" | "G" | "F" | "m" | "n" => (),
+                _ => {
+                    return Err(EngineError::InvalidAssumption(format!(
+                        "unknown data layout spec: {}",
+                        spec
+                    )));
+                }
+            }
+        }
+        Ok(layout)
+    }
+
+    /// Render the layout back into a data layout string, covering the parts that are modeled
+    pub fn to_spec(&self) -> String {
+        let mut items = vec![if self.little_endian { "e" } else { "E" }.to_string()];
+        items.push(format!(
+            "p:{}:{}:{}",
+            self.pointer_size, self.pointer_align.abi, self.pointer_align.pref
+        ));
+        for (kind, table) in [("i", &self.ints), ("f", &self.floats), ("v", &self.vectors)] {
+            for (bits, spec) in table {
+                items.push(format!("{}{}:{}:{}", kind, bits, spec.abi, spec.pref));
+            }
+        }
+        items.push(format!("a:{}:{}", self.aggregate.abi, self.aggregate.pref));
+        items.join("-")
+    }
+
+    /// ABI alignment (in bits) of an integer type, following LLVM's lookup rules
+    fn int_align(&self, bits: usize) -> usize {
+        // pick the smallest entry that is larger than the width, otherwise
+        // fall back to the largest entry
+        match self.ints.range(bits..).next() {
+            Some((_, spec)) => spec.abi,
+            None => self
+                .ints
+                .values()
+                .next_back()
+                .map_or(bits.next_power_of_two(), |spec| spec.abi),
+        }
+    }
+
+    fn float_align(&self, bits: usize) -> EngineResult<usize> {
+        self.floats.get(&bits).map(|spec| spec.abi).ok_or_else(|| {
+            EngineError::InvalidAssumption(format!("no alignment for float of {} bits", bits))
+        })
+    }
+
+    fn vector_align(&self, bits: usize) -> usize {
+        // vectors without an explicit entry are naturally aligned
+        match self.vectors.get(&bits) {
+            Some(spec) => spec.abi,
+            None => bits.next_power_of_two(),
+        }
+    }
+
+    /// Number of bytes written by a store of the type
+    pub fn store_size(&self, ty: &Type) -> EngineResult<usize> {
+        let size = match ty {
+            Type::Bitvec {
+                bits,
+                number: _,
+                length,
+            } => match length {
+                None => bits.div_ceil(8),
+                Some(n) => (bits * n).div_ceil(8),
+            },
+            Type::Array { element, length } => self.alloc_size(element)? * length,
+            Type::Struct {
+                name: _,
+                fields,
+                packed,
+            } => self.struct_layout(fields, *packed)?.size,
+            Type::Pointer => self.pointer_size.div_ceil(8),
+            Type::Function { .. } => {
+                return Err(EngineError::InvalidAssumption(
+                    "function type has no size".into(),
+                ));
+            }
+        };
+        Ok(size)
+    }
+
+    /// Number of bytes between two consecutive elements of the type in memory
+    pub fn alloc_size(&self, ty: &Type) -> EngineResult<usize> {
+        Ok(align_to(self.store_size(ty)?, self.align_of(ty)?))
+    }
+
+    /// ABI alignment (in bytes) of the type
+    pub fn align_of(&self, ty: &Type) -> EngineResult<usize> {
+        let bits = match ty {
+            Type::Bitvec {
+                bits,
+                number,
+                length,
+            } => match (number, length) {
+                (_, Some(n)) => self.vector_align(bits * n),
+                (NumRepr::Int, None) => self.int_align(*bits),
+                (NumRepr::Float, None) => self.float_align(*bits)?,
+            },
+            Type::Array { element, length: _ } => return self.align_of(element),
+            Type::Struct {
+                name: _,
+                fields,
+                packed,
+            } => return Ok(self.struct_layout(fields, *packed)?.align),
+            Type::Pointer => self.pointer_align.abi,
+            Type::Function { .. } => {
+                return Err(EngineError::InvalidAssumption(
+                    "function type has no alignment".into(),
+                ));
+            }
+        };
+        Ok(bits.div_ceil(8).max(1))
+    }
+
+    /// Compute field offsets and padding of a struct
+    pub fn struct_layout(&self, fields: &[Type], packed: bool) -> EngineResult<StructLayout> {
+        let mut offset = 0;
+        let mut align = 1;
+        let mut offsets = vec![];
+        let mut paddings = vec![];
+        for field in fields {
+            let field_align = if packed { 1 } else { self.align_of(field)? };
+            let aligned = align_to(offset, field_align);
+            paddings.push(aligned - offset);
+            offsets.push(aligned);
+            offset = aligned + self.alloc_size(field)?;
+            align = align.max(field_align);
+        }
+
+        // aggregate alignment only applies to non-packed structs
+        if !packed {
+            align = align.max(self.aggregate.abi.div_ceil(8));
+        }
+        let size = align_to(offset, align);
+        Ok(StructLayout {
+            size,
+            align,
+            offsets,
+            paddings,
+            tail_padding: size - offset,
+        })
+    }
+
+    /// Lower a GEP instruction into a byte offset relative to its pointer operand
+    pub fn gep_offset<'a>(&self, inst: &'a Instruction) -> EngineResult<ByteOffset<'a>> {
+        match inst {
+            Instruction::GEP {
+                src_pointee_type,
+                dst_pointee_type: _,
+                pointer: _,
+                offset,
+                indices,
+                result: _,
+            } => {
+                let steps = indices
+                    .iter()
+                    .map(|idx| match idx {
+                        GEPIndex::Struct(field) => GEPStep::Field(*field),
+                        GEPIndex::Array(v) | GEPIndex::Vector(v) => {
+                            GEPStep::Element(SymbolicIndex::Value(v))
+                        }
+                    })
+                    .collect();
+                self.lower_gep(src_pointee_type, SymbolicIndex::Value(offset), steps)
+            }
+            Instruction::GEPNop { .. } => Ok(ByteOffset::zero()),
+            _ => Err(EngineError::InvariantViolation(
+                "expect a GEP instruction for offset lowering".into(),
+            )),
+        }
+    }
+
+    /// Lower a constant GEP expression into a byte offset relative to its pointer operand
+    pub fn gep_const_offset<'a>(&self, expr: &'a Expression) -> EngineResult<ByteOffset<'a>> {
+        match expr {
+            Expression::GEP {
+                src_pointee_type,
+                dst_pointee_type: _,
+                pointer: _,
+                offset,
+                indices,
+            } => {
+                let steps = indices
+                    .iter()
+                    .map(|idx| match idx {
+                        GEPConstIndex::Struct(field) => GEPStep::Field(*field),
+                        GEPConstIndex::Array(v) | GEPConstIndex::Vector(v) => {
+                            GEPStep::Element(SymbolicIndex::Constant(v))
+                        }
+                    })
+                    .collect();
+                self.lower_gep(src_pointee_type, SymbolicIndex::Constant(offset), steps)
+            }
+            Expression::GEPNop { .. } => Ok(ByteOffset::zero()),
+            _ => Err(EngineError::InvariantViolation(
+                "expect a GEP expression for offset lowering".into(),
+            )),
+        }
+    }
+
+    fn lower_gep<'a>(
+        &self,
+        src_ty: &Type,
+        offset: SymbolicIndex<'a>,
+        steps: Vec<GEPStep<'a>>,
+    ) -> EngineResult<ByteOffset<'a>> {
+        let mut result = ByteOffset::zero();
+        result.add_index(offset, self.alloc_size(src_ty)?);
+
+        // walk-down the tree
+        let mut cur_ty = src_ty.clone();
+        for step in steps {
+            cur_ty = match (step, cur_ty) {
+                (
+                    GEPStep::Field(field),
+                    Type::Struct {
+                        name: _,
+                        fields,
+                        packed,
+                    },
+                ) => {
+                    let layout = self.struct_layout(&fields, packed)?;
+                    match (layout.offsets.get(field), fields.into_iter().nth(field)) {
+                        (Some(field_offset), Some(field_ty)) => {
+                            result.constant += *field_offset;
+                            field_ty
+                        }
+                        _ => {
+                            return Err(EngineError::InvalidAssumption(
+                                "field number out of range".into(),
+                            ));
+                        }
+                    }
+                }
+                (GEPStep::Element(index), Type::Array { element, length: _ }) => {
+                    result.add_index(index, self.alloc_size(&element)?);
+                    *element
+                }
+                (
+                    GEPStep::Element(index),
+                    Type::Bitvec {
+                        bits,
+                        number,
+                        length: Some(_),
+                    },
+                ) => {
+                    let element = Type::Bitvec {
+                        bits,
+                        number,
+                        length: None,
+                    };
+                    result.add_index(index, self.alloc_size(&element)?);
+                    element
+                }
+                _ => {
+                    return Err(EngineError::InvalidAssumption(
+                        "GEP index does not match the type being indexed".into(),
+                    ));
+                }
+            };
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,1468 @@
+//! Lowering of the bridge IR back into textual LLVM IR.
+//!
+//! The conversion into the bridge is not injective, hence lowering picks one LLVM construct for
+//! each bridge construct:
+//! - integer division, remainder, right shift, extension, and comparison use the signed forms,
+//! - floating-point comparisons use the ordered forms (except `une` for inequality),
+//! - pointer comparisons use the unsigned forms.
+//!
+//! Constructs whose results are not kept in the bridge (e.g., `freeze` and `va_arg`) or whose
+//! context is lost (e.g., the personality of a landing pad) cannot be lowered.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use rug::Integer;
+
+use crate::error::{EngineError, EngineResult, Unsupported};
+use crate::ir::bridge::cfg::Block;
+use crate::ir::bridge::constant::{Constant, Expression, GEPConstIndex, NumValue};
+use crate::ir::bridge::function::{
+    CallingConvention, Function, FunctionAttribute, ValueAttributes,
+};
+use crate::ir::bridge::global::{GlobalVariable, Linkage, Visibility};
+use crate::ir::bridge::instruction::{
+    BinaryOpArith, BinaryOpBitwise, BinaryOpShift, ComparePredicate, GEPIndex, Instruction,
+    Terminator, UnaryOpArith,
+};
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::typing::{NumRepr, Type};
+use crate::ir::bridge::value::{BlockLabel, RegisterSlot, Value};
+
+/// Lower a converted module into textual LLVM IR
+pub fn lower_module(module: &Module) -> EngineResult<String> {
+    let mut lowering = Lowering {
+        module,
+        struct_defs: BTreeMap::new(),
+        packed_structs: BTreeSet::new(),
+        extra_decls: BTreeMap::new(),
+    };
+
+    // lower the symbols first, collecting the types and declarations they need
+    let mut body = vec![];
+    for gvar in module.globals().values() {
+        body.push(lowering.global(gvar)?);
+    }
+    for func in module.functions().values() {
+        body.push(lowering.function(func)?);
+    }
+
+    let mut text = format!("target datalayout = \"{}\"\n\n", module.layout().to_spec());
+    for def in lowering.struct_defs.values() {
+        text.push_str(def);
+        text.push('\n');
+    }
+    if !lowering.struct_defs.is_empty() {
+        text.push('\n');
+    }
+    for item in body {
+        text.push_str(&item);
+        text.push('\n');
+    }
+    for decl in lowering.extra_decls.values() {
+        text.push_str(decl);
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+/// Utility: a symbol name with its sigil, quoted if needed
+fn symbol(sigil: char, name: &str) -> String {
+    let plain = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '$' | '.' | '_'));
+    if plain {
+        return format!("{}{}", sigil, name);
+    }
+    let mut quoted = String::new();
+    for c in name.bytes() {
+        if c == b'"' || c == b'\\' || !(0x20..0x7f).contains(&c) {
+            quoted.push_str(&format!("\\{:02X}", c));
+        } else {
+            quoted.push(c as char);
+        }
+    }
+    format!("{}\"{}\"", sigil, quoted)
+}
+
+fn global_name(name: &Identifier) -> String {
+    symbol('@', name.as_ref())
+}
+
+fn register(slot: &RegisterSlot) -> String {
+    format!("%r{}", slot.index())
+}
+
+fn label(block: &BlockLabel) -> String {
+    format!("%bb{}", block.index())
+}
+
+/// Utility: an integer of the bit width, in its signed interpretation
+fn signed(value: &Integer, bits: usize) -> Integer {
+    if bits > 0 && value.get_bit((bits - 1) as u32) {
+        value - (Integer::from(1) << (bits as u32))
+    } else {
+        value.clone()
+    }
+}
+
+fn lossy(what: &str) -> EngineError {
+    log::debug!("unable to lower {}", what);
+    EngineError::NotSupportedYet(Unsupported::LossyLowering)
+}
+
+fn linkage_keyword(linkage: &Linkage) -> &'static str {
+    match linkage {
+        Linkage::External => "",
+        Linkage::AvailableExternally => "available_externally ",
+        Linkage::LinkOnceAny => "linkonce ",
+        Linkage::LinkOnceODR => "linkonce_odr ",
+        Linkage::WeakAny => "weak ",
+        Linkage::WeakODR => "weak_odr ",
+        Linkage::Appending => "appending ",
+        Linkage::Internal => "internal ",
+        Linkage::Private => "private ",
+        Linkage::ExternalWeak => "extern_weak ",
+        Linkage::Common => "common ",
+    }
+}
+
+fn conv_keyword(conv: &CallingConvention) -> String {
+    match conv {
+        CallingConvention::Other(id) => format!("cc {}", id),
+        _ => conv.to_string(),
+    }
+}
+
+fn visibility_keyword(visibility: &Visibility) -> &'static str {
+    match visibility {
+        Visibility::Default => "",
+        Visibility::Hidden => "hidden ",
+        Visibility::Protected => "protected ",
+    }
+}
+
+fn unary_opcode(opcode: &UnaryOpArith) -> &'static str {
+    match opcode {
+        UnaryOpArith::Neg => "fneg",
+    }
+}
+
+fn arith_opcode(opcode: &BinaryOpArith, number: NumRepr) -> &'static str {
+    match (opcode, number) {
+        (BinaryOpArith::Add, NumRepr::Int) => "add",
+        (BinaryOpArith::Sub, NumRepr::Int) => "sub",
+        (BinaryOpArith::Mul, NumRepr::Int) => "mul",
+        (BinaryOpArith::Div, NumRepr::Int) => "sdiv",
+        (BinaryOpArith::Mod, NumRepr::Int) => "srem",
+        (BinaryOpArith::Add, NumRepr::Float) => "fadd",
+        (BinaryOpArith::Sub, NumRepr::Float) => "fsub",
+        (BinaryOpArith::Mul, NumRepr::Float) => "fmul",
+        (BinaryOpArith::Div, NumRepr::Float) => "fdiv",
+        (BinaryOpArith::Mod, NumRepr::Float) => "frem",
+    }
+}
+
+fn bitwise_opcode(opcode: &BinaryOpBitwise) -> &'static str {
+    match opcode {
+        BinaryOpBitwise::And => "and",
+        BinaryOpBitwise::Or => "or",
+        BinaryOpBitwise::Xor => "xor",
+    }
+}
+
+fn shift_opcode(opcode: &BinaryOpShift) -> &'static str {
+    match opcode {
+        BinaryOpShift::Shl => "shl",
+        BinaryOpShift::Shr => "ashr",
+    }
+}
+
+fn compare_opcode(predicate: &ComparePredicate, number: NumRepr) -> &'static str {
+    match (predicate, number) {
+        (ComparePredicate::EQ, NumRepr::Int) => "icmp eq",
+        (ComparePredicate::NE, NumRepr::Int) => "icmp ne",
+        (ComparePredicate::GT, NumRepr::Int) => "icmp sgt",
+        (ComparePredicate::GE, NumRepr::Int) => "icmp sge",
+        (ComparePredicate::LT, NumRepr::Int) => "icmp slt",
+        (ComparePredicate::LE, NumRepr::Int) => "icmp sle",
+        (ComparePredicate::EQ, NumRepr::Float) => "fcmp oeq",
+        (ComparePredicate::NE, NumRepr::Float) => "fcmp une",
+        (ComparePredicate::GT, NumRepr::Float) => "fcmp ogt",
+        (ComparePredicate::GE, NumRepr::Float) => "fcmp oge",
+        (ComparePredicate::LT, NumRepr::Float) => "fcmp olt",
+        (ComparePredicate::LE, NumRepr::Float) => "fcmp ole",
+    }
+}
+
+fn compare_ptr_opcode(predicate: &ComparePredicate) -> &'static str {
+    match predicate {
+        ComparePredicate::EQ => "icmp eq",
+        ComparePredicate::NE => "icmp ne",
+        ComparePredicate::GT => "icmp ugt",
+        ComparePredicate::GE => "icmp uge",
+        ComparePredicate::LT => "icmp ult",
+        ComparePredicate::LE => "icmp ule",
+    }
+}
+
+fn resize_opcode(bits_from: usize, bits_into: usize, number: NumRepr) -> &'static str {
+    match (bits_into < bits_from, number) {
+        (true, NumRepr::Int) => "trunc",
+        (false, NumRepr::Int) => "sext",
+        (true, NumRepr::Float) => "fptrunc",
+        (false, NumRepr::Float) => "fpext",
+    }
+}
+
+fn convert_opcode(number_from: NumRepr) -> &'static str {
+    match number_from {
+        NumRepr::Int => "sitofp",
+        NumRepr::Float => "fptosi",
+    }
+}
+
+fn bitvec(bits: usize, number: NumRepr, length: Option<usize>) -> Type {
+    Type::Bitvec {
+        bits,
+        number,
+        length,
+    }
+}
+
+/// Utility: the type of a constant
+fn constant_type(constant: &Constant) -> EngineResult<Type> {
+    let ty = match constant {
+        Constant::NumOne { bits, value } => match value {
+            NumValue::Int(_) | NumValue::IntUndef => bitvec(*bits, NumRepr::Int, None),
+            NumValue::Float(_) | NumValue::FloatUndef => bitvec(*bits, NumRepr::Float, None),
+        },
+        Constant::NumVec {
+            bits,
+            number,
+            elements,
+        } => bitvec(*bits, *number, Some(elements.len())),
+        Constant::Null
+        | Constant::UndefPointer
+        | Constant::Variable { .. }
+        | Constant::Function { .. }
+        | Constant::Block { .. } => Type::Pointer,
+        Constant::Array { sub, elements } => Type::Array {
+            element: Box::new(sub.clone()),
+            length: elements.len(),
+        },
+        Constant::Struct { name, fields } => Type::Struct {
+            name: name.clone(),
+            fields: fields
+                .iter()
+                .map(constant_type)
+                .collect::<EngineResult<_>>()?,
+            packed: false,
+        },
+        Constant::Expr(expr) => expression_type(expr)?,
+    };
+    Ok(ty)
+}
+
+/// Utility: the type of a constant expression
+fn expression_type(expr: &Expression) -> EngineResult<Type> {
+    let ty = match expr {
+        Expression::UnaryArith {
+            bits,
+            number,
+            length,
+            ..
+        }
+        | Expression::BinaryArith {
+            bits,
+            number,
+            length,
+            ..
+        } => bitvec(*bits, *number, *length),
+        Expression::BinaryBitwise { bits, length, .. }
+        | Expression::BinaryShift { bits, length, .. } => bitvec(*bits, NumRepr::Int, *length),
+        Expression::CompareBitvec { length, .. } | Expression::CompareOrder { length, .. } => {
+            bitvec(1, NumRepr::Int, *length)
+        }
+        Expression::ComparePtr { .. } => bitvec(1, NumRepr::Int, None),
+        Expression::CastBitvecSize {
+            bits_into,
+            number,
+            length,
+            ..
+        } => bitvec(*bits_into, *number, *length),
+        Expression::CastBitvecRepr {
+            bits_into,
+            number_into,
+            length,
+            ..
+        } => bitvec(*bits_into, *number_into, *length),
+        Expression::CastBitvecFree {
+            bits_into,
+            number_into,
+            length_into,
+            ..
+        } => bitvec(*bits_into, *number_into, *length_into),
+        Expression::CastPtrToInt { bits_into, .. } => bitvec(*bits_into, NumRepr::Int, None),
+        Expression::CastPtr { .. }
+        | Expression::CastIntToPtr { .. }
+        | Expression::GEP { .. }
+        | Expression::GEPNop { .. } => Type::Pointer,
+        Expression::ITEOne { then_value, .. } => constant_type(then_value)?,
+        Expression::ITEVec {
+            bits,
+            number,
+            length,
+            ..
+        } => bitvec(*bits, *number, Some(*length)),
+        Expression::GetValue { dst_ty, .. } => dst_ty.clone(),
+        Expression::SetValue { aggregate, .. } => constant_type(aggregate)?,
+        Expression::GetElement { bits, number, .. } => bitvec(*bits, *number, None),
+        Expression::SetElement {
+            bits,
+            number,
+            length,
+            ..
+        } => bitvec(*bits, *number, Some(*length)),
+        Expression::ShuffleVec {
+            bits, number, mask, ..
+        } => bitvec(*bits, *number, Some(mask.len())),
+    };
+    Ok(ty)
+}
+
+/// Utility: the type of a value
+fn value_type(value: &Value) -> EngineResult<Type> {
+    match value {
+        Value::Constant(constant) => constant_type(constant),
+        Value::Argument { index: _, ty } | Value::Register { index: _, ty } => Ok(ty.clone()),
+    }
+}
+
+/// State of the lowering of a module
+struct Lowering<'a> {
+    module: &'a Module,
+    /// definitions of the named struct types encountered
+    struct_defs: BTreeMap<Identifier, String>,
+    /// named struct types that are packed
+    packed_structs: BTreeSet<Identifier>,
+    /// declarations of callees not in the module (e.g., intrinsics)
+    extra_decls: BTreeMap<Identifier, String>,
+}
+
+impl<'a> Lowering<'a> {
+    fn ty(&mut self, ty: &Type) -> EngineResult<String> {
+        let repr = match ty {
+            Type::Bitvec {
+                bits,
+                number,
+                length,
+            } => {
+                let scalar = match number {
+                    NumRepr::Int => format!("i{}", bits),
+                    NumRepr::Float => match bits {
+                        16 => "half".to_string(),
+                        32 => "float".to_string(),
+                        64 => "double".to_string(),
+                        80 => "x86_fp80".to_string(),
+                        128 => "fp128".to_string(),
+                        _ => {
+                            return Err(EngineError::InvalidAssumption(format!(
+                                "no floating-point type of {} bits",
+                                bits
+                            )));
+                        }
+                    },
+                };
+                match length {
+                    None => scalar,
+                    Some(n) => format!("<{} x {}>", n, scalar),
+                }
+            }
+            Type::Array { element, length } => format!("[{} x {}]", length, self.ty(element)?),
+            Type::Struct {
+                name,
+                fields,
+                packed,
+            } => {
+                let body = self.struct_body(fields, *packed)?;
+                match name {
+                    None => body,
+                    Some(ident) => {
+                        let repr = symbol('%', ident.as_ref());
+                        if !self.struct_defs.contains_key(ident) {
+                            if *packed {
+                                self.packed_structs.insert(ident.clone());
+                            }
+                            self.struct_defs
+                                .insert(ident.clone(), format!("{} = type {}", repr, body));
+                        }
+                        repr
+                    }
+                }
+            }
+            Type::Function {
+                params,
+                variadic,
+                ret,
+            } => {
+                let mut items = params
+                    .iter()
+                    .map(|t| self.ty(t))
+                    .collect::<EngineResult<Vec<_>>>()?;
+                if *variadic {
+                    items.push("...".to_string());
+                }
+                format!("{} ({})", self.ret_ty(ret.as_deref())?, items.join(", "))
+            }
+            Type::Pointer => "ptr".to_string(),
+        };
+        Ok(repr)
+    }
+
+    fn struct_body(&mut self, fields: &[Type], packed: bool) -> EngineResult<String> {
+        let items = fields
+            .iter()
+            .map(|t| self.ty(t))
+            .collect::<EngineResult<Vec<_>>>()?;
+        let inner = if items.is_empty() {
+            "{}".to_string()
+        } else {
+            format!("{{ {} }}", items.join(", "))
+        };
+        Ok(if packed {
+            format!("<{}>", inner)
+        } else {
+            inner
+        })
+    }
+
+    fn ret_ty(&mut self, ty: Option<&Type>) -> EngineResult<String> {
+        match ty {
+            None => Ok("void".to_string()),
+            Some(t) => self.ty(t),
+        }
+    }
+
+    fn float(bits: usize, value: &NumValue) -> EngineResult<String> {
+        match value {
+            NumValue::FloatUndef => Ok("undef".to_string()),
+            NumValue::Float(None) => Err(lossy("a non-finite floating-point constant")),
+            NumValue::Float(Some(v)) => {
+                if bits > 64 {
+                    return Err(lossy("an extended-precision floating-point constant"));
+                }
+                // LLVM accepts the hex form of a double for narrower types if exact
+                Ok(format!("0x{:016X}", v.to_f64().to_bits()))
+            }
+            NumValue::Int(_) | NumValue::IntUndef => Err(EngineError::InvariantViolation(
+                "expect a floating-point constant".into(),
+            )),
+        }
+    }
+
+    fn constant(&mut self, constant: &Constant) -> EngineResult<String> {
+        let repr = match constant {
+            Constant::NumOne { bits, value } => match value {
+                NumValue::Int(v) => signed(v, *bits).to_string(),
+                NumValue::IntUndef => "undef".to_string(),
+                NumValue::Float(_) | NumValue::FloatUndef => Self::float(*bits, value)?,
+            },
+            Constant::NumVec { elements, .. } => format!("<{}>", self.typed_constants(elements)?),
+            Constant::Null => "null".to_string(),
+            Constant::UndefPointer => "undef".to_string(),
+            Constant::Array { sub: _, elements } => {
+                format!("[{}]", self.typed_constants(elements)?)
+            }
+            Constant::Struct { name, fields } => {
+                let inner = format!("{{ {} }}", self.typed_constants(fields)?);
+                match name {
+                    Some(ident) if self.packed_structs.contains(ident) => format!("<{}>", inner),
+                    _ => inner,
+                }
+            }
+            Constant::Variable { name } | Constant::Function { name } => global_name(name),
+            Constant::Block { func, block } => format!(
+                "blockaddress({}, {})",
+                global_name(func),
+                label(&BlockLabel::from(*block))
+            ),
+            Constant::Expr(expr) => self.expression(expr)?,
+        };
+        Ok(repr)
+    }
+
+    fn typed_constant(&mut self, constant: &Constant) -> EngineResult<String> {
+        let ty = self.ty(&constant_type(constant)?)?;
+        Ok(format!("{} {}", ty, self.constant(constant)?))
+    }
+
+    fn typed_constants(&mut self, constants: &[Constant]) -> EngineResult<String> {
+        let items = constants
+            .iter()
+            .map(|c| self.typed_constant(c))
+            .collect::<EngineResult<Vec<_>>>()?;
+        Ok(items.join(", "))
+    }
+
+    fn shuffle_mask(mask: &[i128]) -> String {
+        let items: Vec<_> = mask
+            .iter()
+            .map(|m| {
+                if *m < 0 {
+                    "i32 poison".to_string()
+                } else {
+                    format!("i32 {}", m)
+                }
+            })
+            .collect();
+        format!("<{} x i32> <{}>", mask.len(), items.join(", "))
+    }
+
+    fn expression(&mut self, expr: &Expression) -> EngineResult<String> {
+        let repr = match expr {
+            Expression::UnaryArith {
+                opcode, operand, ..
+            } => format!(
+                "{} ({})",
+                unary_opcode(opcode),
+                self.typed_constant(operand)?
+            ),
+            Expression::BinaryArith {
+                number,
+                opcode,
+                lhs,
+                rhs,
+                ..
+            } => format!(
+                "{} ({}, {})",
+                arith_opcode(opcode, *number),
+                self.typed_constant(lhs)?,
+                self.typed_constant(rhs)?
+            ),
+            Expression::BinaryBitwise {
+                opcode, lhs, rhs, ..
+            } => format!(
+                "{} ({}, {})",
+                bitwise_opcode(opcode),
+                self.typed_constant(lhs)?,
+                self.typed_constant(rhs)?
+            ),
+            Expression::BinaryShift {
+                opcode, lhs, rhs, ..
+            } => format!(
+                "{} ({}, {})",
+                shift_opcode(opcode),
+                self.typed_constant(lhs)?,
+                self.typed_constant(rhs)?
+            ),
+            Expression::CompareBitvec {
+                number,
+                predicate,
+                lhs,
+                rhs,
+                ..
+            } => format!(
+                "{} ({}, {})",
+                compare_opcode(predicate, *number),
+                self.typed_constant(lhs)?,
+                self.typed_constant(rhs)?
+            ),
+            Expression::CompareOrder {
+                ordered, lhs, rhs, ..
+            } => format!(
+                "fcmp {} ({}, {})",
+                if *ordered { "ord" } else { "uno" },
+                self.typed_constant(lhs)?,
+                self.typed_constant(rhs)?
+            ),
+            Expression::ComparePtr {
+                predicate,
+                lhs,
+                rhs,
+            } => format!(
+                "{} ({}, {})",
+                compare_ptr_opcode(predicate),
+                self.typed_constant(lhs)?,
+                self.typed_constant(rhs)?
+            ),
+            Expression::CastBitvecSize {
+                bits_from,
+                bits_into,
+                number,
+                operand,
+                ..
+            } => self.cast_expression(
+                resize_opcode(*bits_from, *bits_into, *number),
+                operand,
+                &expression_type(expr)?,
+            )?,
+            Expression::CastBitvecRepr {
+                number_from,
+                operand,
+                ..
+            } => self.cast_expression(
+                convert_opcode(*number_from),
+                operand,
+                &expression_type(expr)?,
+            )?,
+            Expression::CastBitvecFree { operand, .. } => {
+                self.cast_expression("bitcast", operand, &expression_type(expr)?)?
+            }
+            Expression::CastPtr { operand } => {
+                self.cast_expression("bitcast", operand, &Type::Pointer)?
+            }
+            Expression::CastPtrToInt { operand, .. } => {
+                self.cast_expression("ptrtoint", operand, &expression_type(expr)?)?
+            }
+            Expression::CastIntToPtr { operand, .. } => {
+                self.cast_expression("inttoptr", operand, &Type::Pointer)?
+            }
+            Expression::GEP {
+                src_pointee_type,
+                dst_pointee_type: _,
+                pointer,
+                offset,
+                indices,
+            } => {
+                let mut items = vec![
+                    self.ty(src_pointee_type)?,
+                    self.typed_constant(pointer)?,
+                    self.typed_constant(offset)?,
+                ];
+                for index in indices {
+                    items.push(match index {
+                        GEPConstIndex::Struct(field) => format!("i32 {}", field),
+                        GEPConstIndex::Array(c) | GEPConstIndex::Vector(c) => {
+                            self.typed_constant(c)?
+                        }
+                    });
+                }
+                format!("getelementptr ({})", items.join(", "))
+            }
+            Expression::GEPNop {
+                pointee_type,
+                pointer,
+            } => format!(
+                "getelementptr ({}, {}, i64 0)",
+                self.ty(pointee_type)?,
+                self.typed_constant(pointer)?
+            ),
+            Expression::ITEOne {
+                cond,
+                then_value,
+                else_value,
+            }
+            | Expression::ITEVec {
+                cond,
+                then_value,
+                else_value,
+                ..
+            } => format!(
+                "select ({}, {}, {})",
+                self.typed_constant(cond)?,
+                self.typed_constant(then_value)?,
+                self.typed_constant(else_value)?
+            ),
+            Expression::GetValue {
+                aggregate, indices, ..
+            } => format!(
+                "extractvalue ({}, {})",
+                self.typed_constant(aggregate)?,
+                Self::field_path(indices)
+            ),
+            Expression::SetValue {
+                aggregate,
+                value,
+                indices,
+            } => format!(
+                "insertvalue ({}, {}, {})",
+                self.typed_constant(aggregate)?,
+                self.typed_constant(value)?,
+                Self::field_path(indices)
+            ),
+            Expression::GetElement { vector, slot, .. } => format!(
+                "extractelement ({}, {})",
+                self.typed_constant(vector)?,
+                self.typed_constant(slot)?
+            ),
+            Expression::SetElement {
+                vector,
+                value,
+                slot,
+                ..
+            } => format!(
+                "insertelement ({}, {}, {})",
+                self.typed_constant(vector)?,
+                self.typed_constant(value)?,
+                self.typed_constant(slot)?
+            ),
+            Expression::ShuffleVec { lhs, rhs, mask, .. } => format!(
+                "shufflevector ({}, {}, {})",
+                self.typed_constant(lhs)?,
+                self.typed_constant(rhs)?,
+                Self::shuffle_mask(mask)
+            ),
+        };
+        Ok(repr)
+    }
+
+    fn cast_expression(
+        &mut self,
+        opcode: &str,
+        operand: &Constant,
+        into: &Type,
+    ) -> EngineResult<String> {
+        Ok(format!(
+            "{} ({} to {})",
+            opcode,
+            self.typed_constant(operand)?,
+            self.ty(into)?
+        ))
+    }
+
+    fn field_path(indices: &[usize]) -> String {
+        indices
+            .iter()
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn value(&mut self, value: &Value) -> EngineResult<String> {
+        match value {
+            Value::Constant(constant) => self.constant(constant),
+            Value::Argument { index, ty: _ } => Ok(format!("%a{}", index.index())),
+            Value::Register { index, ty: _ } => Ok(register(index)),
+        }
+    }
+
+    fn typed(&mut self, value: &Value) -> EngineResult<String> {
+        let ty = self.ty(&value_type(value)?)?;
+        Ok(format!("{} {}", ty, self.value(value)?))
+    }
+
+    fn attrs(&mut self, attrs: &ValueAttributes) -> EngineResult<String> {
+        let ValueAttributes {
+            no_alias,
+            non_null,
+            align,
+            by_val,
+            struct_ret,
+        } = attrs;
+
+        let mut items = vec![];
+        if *no_alias {
+            items.push("noalias".to_string());
+        }
+        if *non_null {
+            items.push("nonnull".to_string());
+        }
+        if let Some(n) = align {
+            items.push(format!("align {}", n));
+        }
+        if let Some(ty) = by_val {
+            items.push(format!("byval({})", self.ty(ty)?));
+        }
+        if let Some(ty) = struct_ret {
+            items.push(format!("sret({})", self.ty(ty)?));
+        }
+        Ok(items.iter().map(|item| format!("{} ", item)).collect())
+    }
+
+    fn global(&mut self, gvar: &GlobalVariable) -> EngineResult<String> {
+        let GlobalVariable {
+            name,
+            ty,
+            is_weak: _,
+            is_constant,
+            linkage,
+            visibility,
+            section,
+            initializer,
+        } = gvar;
+
+        let mut repr = format!("{} = ", global_name(name));
+        match initializer {
+            None => repr.push_str(match linkage {
+                Linkage::ExternalWeak => "extern_weak ",
+                _ => "external ",
+            }),
+            Some(_) => repr.push_str(linkage_keyword(linkage)),
+        }
+        repr.push_str(visibility_keyword(visibility));
+        repr.push_str(if *is_constant { "constant " } else { "global " });
+        repr.push_str(&self.ty(ty)?);
+        if let Some(init) = initializer {
+            repr.push(' ');
+            repr.push_str(&self.constant(init)?);
+        }
+        if let Some(sec) = section {
+            repr.push_str(&format!(", section \"{}\"", sec));
+        }
+        Ok(repr)
+    }
+
+    fn function(&mut self, func: &Function) -> EngineResult<String> {
+        let Function {
+            name,
+            params,
+            variadic,
+            conv,
+            linkage,
+            ret,
+            ret_attrs,
+            is_weak: _,
+            attrs,
+            body,
+        } = func;
+
+        let mut items = vec![];
+        for (i, param) in params.iter().enumerate() {
+            let mut item = format!("{} {}", self.ty(&param.ty)?, self.attrs(&param.attrs)?);
+            if body.is_some() {
+                item.push_str(&format!("%a{}", i));
+            }
+            items.push(item.trim_end().to_string());
+        }
+        if *variadic {
+            items.push("...".to_string());
+        }
+
+        let mut fn_attrs = vec![];
+        for attr in attrs {
+            fn_attrs.push(match attr {
+                FunctionAttribute::NoReturn => "noreturn",
+                FunctionAttribute::ReadNone => "memory(none)",
+                FunctionAttribute::ReadOnly => "memory(read)",
+                FunctionAttribute::NoUnwind => "nounwind",
+                FunctionAttribute::Ignored(_) => continue,
+            });
+        }
+
+        let mut header = vec![match body {
+            None => "declare".to_string(),
+            Some(_) => "define".to_string(),
+        }];
+        let linkage_repr = match (body, linkage) {
+            (None, Linkage::ExternalWeak) => "extern_weak",
+            (None, _) => "",
+            (Some(_), _) => linkage_keyword(linkage).trim_end(),
+        };
+        if !linkage_repr.is_empty() {
+            header.push(linkage_repr.to_string());
+        }
+        header.push(conv_keyword(conv));
+        let ret_attrs_repr = self.attrs(ret_attrs)?;
+        if !ret_attrs_repr.is_empty() {
+            header.push(ret_attrs_repr.trim_end().to_string());
+        }
+        header.push(self.ret_ty(ret.as_ref())?);
+        header.push(format!("{}({})", global_name(name), items.join(", ")));
+        header.extend(fn_attrs.into_iter().map(|s| s.to_string()));
+        let header = header.join(" ");
+
+        let cfg = match body {
+            None => return Ok(header),
+            Some(cfg) => cfg,
+        };
+
+        // the entry block goes first
+        let entry = cfg.entry();
+        let mut blocks: Vec<_> = cfg.blocks().filter(|(l, _)| **l == entry).collect();
+        blocks.extend(cfg.blocks().filter(|(l, _)| **l != entry));
+
+        let mut lines = vec![format!("{} {{", header)];
+        let mut needs_trap = false;
+        for (block_label, block) in blocks {
+            lines.push(format!("bb{}:", block_label.index()));
+            self.block(block, &mut lines, &mut needs_trap)?;
+        }
+        if needs_trap {
+            lines.push("bb.unreachable:".to_string());
+            lines.push("  unreachable".to_string());
+        }
+        lines.push("}".to_string());
+        Ok(lines.join("\n"))
+    }
+
+    fn block(
+        &mut self,
+        block: &Block,
+        lines: &mut Vec<String>,
+        needs_trap: &mut bool,
+    ) -> EngineResult<()> {
+        for inst in block.instructions() {
+            let repr = self.instruction(inst)?;
+            lines.push(format!("  {}", repr));
+        }
+        let repr = self.terminator(block.terminator(), needs_trap)?;
+        lines.push(format!("  {}", repr));
+        Ok(())
+    }
+
+    /// Callee and result parts of a call, declaring the callee if not in the module
+    fn call(
+        &mut self,
+        callee: Option<&Identifier>,
+        callee_value: Option<&Value>,
+        conv: &CallingConvention,
+        args: &[Value],
+        arg_attrs: &[ValueAttributes],
+        result: &Option<(Type, RegisterSlot)>,
+    ) -> EngineResult<(String, String)> {
+        let ret_ty = self.ret_ty(result.as_ref().map(|(ty, _)| ty))?;
+        let mut items = vec![];
+        for (arg, attrs) in args.iter().zip(arg_attrs) {
+            let ty = self.ty(&value_type(arg)?)?;
+            items.push(format!("{} {}{}", ty, self.attrs(attrs)?, self.value(arg)?));
+        }
+
+        let (callee_repr, fn_ty) = match (callee, callee_value) {
+            (Some(name), _) => {
+                let fn_ty = match self.module.get_function(name.as_ref()) {
+                    Some(func) if func.variadic => {
+                        let mut params = func
+                            .params
+                            .iter()
+                            .map(|p| self.ty(&p.ty))
+                            .collect::<EngineResult<Vec<_>>>()?;
+                        params.push("...".to_string());
+                        format!("{} ({})", ret_ty, params.join(", "))
+                    }
+                    Some(_) => ret_ty.clone(),
+                    None => {
+                        // e.g., intrinsics filtered out of the module
+                        let params = args
+                            .iter()
+                            .map(|a| self.ty(&value_type(a)?))
+                            .collect::<EngineResult<Vec<_>>>()?;
+                        self.extra_decls.entry(name.clone()).or_insert_with(|| {
+                            format!(
+                                "declare {} {}({})",
+                                ret_ty,
+                                global_name(name),
+                                params.join(", ")
+                            )
+                        });
+                        ret_ty.clone()
+                    }
+                };
+                (global_name(name), fn_ty)
+            }
+            (None, Some(value)) => (self.value(value)?, ret_ty.clone()),
+            (None, None) => {
+                return Err(EngineError::InvariantViolation(
+                    "expect a callee for a call site".into(),
+                ));
+            }
+        };
+
+        let assign = match result {
+            None => String::new(),
+            Some((_, slot)) => format!("{} = ", register(slot)),
+        };
+        Ok((
+            assign,
+            format!(
+                "{} {} {}({})",
+                conv_keyword(conv),
+                fn_ty,
+                callee_repr,
+                items.join(", ")
+            ),
+        ))
+    }
+
+    fn instruction(&mut self, inst: &Instruction) -> EngineResult<String> {
+        let repr = match inst {
+            Instruction::Alloca {
+                base_type,
+                size,
+                align,
+                result,
+            } => match size {
+                None => format!(
+                    "{} = alloca {}, align {}",
+                    register(result),
+                    self.ty(base_type)?,
+                    align
+                ),
+                Some(count) => format!(
+                    "{} = alloca {}, {}, align {}",
+                    register(result),
+                    self.ty(base_type)?,
+                    self.typed(count)?,
+                    align
+                ),
+            },
+            Instruction::Load {
+                pointee_type,
+                pointer,
+                align,
+                volatile,
+                result,
+            } => format!(
+                "{} = load {}{}, {}, align {}",
+                register(result),
+                if *volatile { "volatile " } else { "" },
+                self.ty(pointee_type)?,
+                self.typed(pointer)?,
+                align
+            ),
+            Instruction::Store {
+                pointee_type: _,
+                pointer,
+                value,
+                align,
+                volatile,
+            } => format!(
+                "store {}{}, {}, align {}",
+                if *volatile { "volatile " } else { "" },
+                self.typed(value)?,
+                self.typed(pointer)?,
+                align
+            ),
+            Instruction::VariadicArg { .. } => return Err(lossy("va_arg")),
+            Instruction::CallDirect {
+                function,
+                conv,
+                args,
+                arg_attrs,
+                result,
+            } => {
+                let (assign, call) =
+                    self.call(Some(function), None, conv, args, arg_attrs, result)?;
+                format!("{}call {}", assign, call)
+            }
+            Instruction::CallIndirect {
+                callee,
+                conv,
+                args,
+                arg_attrs,
+                result,
+            } => {
+                let (assign, call) =
+                    self.call(None, Some(callee), conv, args, arg_attrs, result)?;
+                format!("{}call {}", assign, call)
+            }
+            Instruction::Contract { kind, cond } => {
+                // back to the reserved function the specification macro lowers to
+                let name = Identifier::from(format!("__libra_{}", kind));
+                let cond_ty = self.ty(&value_type(cond)?)?;
+                if self.module.get_function(name.as_ref()).is_none() {
+                    self.extra_decls.entry(name.clone()).or_insert_with(|| {
+                        format!("declare void {}({})", global_name(&name), cond_ty)
+                    });
+                }
+                format!("call void {}({})", global_name(&name), self.typed(cond)?)
+            }
+            Instruction::UnaryArith {
+                opcode,
+                operand,
+                result,
+                ..
+            } => format!(
+                "{} = {} {}",
+                register(result),
+                unary_opcode(opcode),
+                self.typed(operand)?
+            ),
+            Instruction::BinaryArith {
+                number,
+                opcode,
+                lhs,
+                rhs,
+                result,
+                ..
+            } => self.binary(result, arith_opcode(opcode, *number), lhs, rhs)?,
+            Instruction::BinaryBitwise {
+                opcode,
+                lhs,
+                rhs,
+                result,
+                ..
+            } => self.binary(result, bitwise_opcode(opcode), lhs, rhs)?,
+            Instruction::BinaryShift {
+                opcode,
+                lhs,
+                rhs,
+                result,
+                ..
+            } => self.binary(result, shift_opcode(opcode), lhs, rhs)?,
+            Instruction::CompareBitvec {
+                number,
+                predicate,
+                lhs,
+                rhs,
+                result,
+                ..
+            } => self.binary(result, compare_opcode(predicate, *number), lhs, rhs)?,
+            Instruction::CompareOrder {
+                ordered,
+                lhs,
+                rhs,
+                result,
+                ..
+            } => self.binary(
+                result,
+                if *ordered { "fcmp ord" } else { "fcmp uno" },
+                lhs,
+                rhs,
+            )?,
+            Instruction::ComparePtr {
+                predicate,
+                lhs,
+                rhs,
+                result,
+            } => self.binary(result, compare_ptr_opcode(predicate), lhs, rhs)?,
+            Instruction::CastBitvecSize {
+                bits_from,
+                bits_into,
+                number,
+                length,
+                operand,
+                result,
+            } => self.cast(
+                result,
+                resize_opcode(*bits_from, *bits_into, *number),
+                operand,
+                &bitvec(*bits_into, *number, *length),
+            )?,
+            Instruction::CastBitvecRepr {
+                bits_into,
+                number_from,
+                number_into,
+                length,
+                operand,
+                result,
+                ..
+            } => self.cast(
+                result,
+                convert_opcode(*number_from),
+                operand,
+                &bitvec(*bits_into, *number_into, *length),
+            )?,
+            Instruction::CastBitvecFree {
+                bits_into,
+                number_into,
+                length_into,
+                operand,
+                result,
+                ..
+            } => self.cast(
+                result,
+                "bitcast",
+                operand,
+                &bitvec(*bits_into, *number_into, *length_into),
+            )?,
+            Instruction::CastPtr { operand, result } => {
+                self.cast(result, "bitcast", operand, &Type::Pointer)?
+            }
+            Instruction::CastPtrToInt {
+                bits_into,
+                operand,
+                result,
+            } => self.cast(
+                result,
+                "ptrtoint",
+                operand,
+                &bitvec(*bits_into, NumRepr::Int, None),
+            )?,
+            Instruction::CastIntToPtr {
+                bits_from: _,
+                operand,
+                result,
+            } => self.cast(result, "inttoptr", operand, &Type::Pointer)?,
+            Instruction::FreezeBitvec { .. }
+            | Instruction::FreezePtr
+            | Instruction::FreezeNop { .. } => return Err(lossy("freeze")),
+            Instruction::GEP {
+                src_pointee_type,
+                dst_pointee_type: _,
+                pointer,
+                offset,
+                indices,
+                result,
+            } => {
+                let mut items = vec![
+                    self.ty(src_pointee_type)?,
+                    self.typed(pointer)?,
+                    self.typed(offset)?,
+                ];
+                for index in indices {
+                    items.push(match index {
+                        GEPIndex::Struct(field) => format!("i32 {}", field),
+                        GEPIndex::Array(v) | GEPIndex::Vector(v) => self.typed(v)?,
+                    });
+                }
+                format!("{} = getelementptr {}", register(result), items.join(", "))
+            }
+            Instruction::GEPNop {
+                pointee_type,
+                pointer,
+                result,
+            } => format!(
+                "{} = getelementptr {}, {}, i64 0",
+                register(result),
+                self.ty(pointee_type)?,
+                self.typed(pointer)?
+            ),
+            Instruction::ITEOne {
+                cond,
+                then_value,
+                else_value,
+                result,
+            }
+            | Instruction::ITEVec {
+                cond,
+                then_value,
+                else_value,
+                result,
+                ..
+            } => format!(
+                "{} = select {}, {}, {}",
+                register(result),
+                self.typed(cond)?,
+                self.typed(then_value)?,
+                self.typed(else_value)?
+            ),
+            Instruction::Phi { options, result } => {
+                let ty = match options.values().next() {
+                    None => {
+                        return Err(EngineError::InvariantViolation(
+                            "expect at least one option in a phi node".into(),
+                        ));
+                    }
+                    Some(v) => value_type(v)?,
+                };
+                let mut items = vec![];
+                for (block_label, value) in options {
+                    items.push(format!(
+                        "[ {}, {} ]",
+                        self.value(value)?,
+                        label(block_label)
+                    ));
+                }
+                format!(
+                    "{} = phi {} {}",
+                    register(result),
+                    self.ty(&ty)?,
+                    items.join(", ")
+                )
+            }
+            Instruction::GetValue {
+                aggregate,
+                indices,
+                result,
+                ..
+            } => format!(
+                "{} = extractvalue {}, {}",
+                register(result),
+                self.typed(aggregate)?,
+                Self::field_path(indices)
+            ),
+            Instruction::SetValue {
+                aggregate,
+                value,
+                indices,
+                result,
+            } => format!(
+                "{} = insertvalue {}, {}, {}",
+                register(result),
+                self.typed(aggregate)?,
+                self.typed(value)?,
+                Self::field_path(indices)
+            ),
+            Instruction::GetElement {
+                vector,
+                slot,
+                result,
+                ..
+            } => format!(
+                "{} = extractelement {}, {}",
+                register(result),
+                self.typed(vector)?,
+                self.typed(slot)?
+            ),
+            Instruction::SetElement {
+                vector,
+                value,
+                slot,
+                result,
+                ..
+            } => format!(
+                "{} = insertelement {}, {}, {}",
+                register(result),
+                self.typed(vector)?,
+                self.typed(value)?,
+                self.typed(slot)?
+            ),
+            Instruction::ShuffleVec {
+                lhs,
+                rhs,
+                mask,
+                result,
+                ..
+            } => format!(
+                "{} = shufflevector {}, {}, {}",
+                register(result),
+                self.typed(lhs)?,
+                self.typed(rhs)?,
+                Self::shuffle_mask(mask)
+            ),
+            Instruction::LandingPad {
+                directives,
+                is_cleanup,
+                result,
+            } => {
+                // the personality function of the enclosing function is not kept
+                let _ = (directives, is_cleanup, result);
+                return Err(lossy("landingpad"));
+            }
+        };
+        Ok(repr)
+    }
+
+    fn binary(
+        &mut self,
+        result: &RegisterSlot,
+        opcode: &str,
+        lhs: &Value,
+        rhs: &Value,
+    ) -> EngineResult<String> {
+        Ok(format!(
+            "{} = {} {}, {}",
+            register(result),
+            opcode,
+            self.typed(lhs)?,
+            self.value(rhs)?
+        ))
+    }
+
+    fn cast(
+        &mut self,
+        result: &RegisterSlot,
+        opcode: &str,
+        operand: &Value,
+        into: &Type,
+    ) -> EngineResult<String> {
+        Ok(format!(
+            "{} = {} {} to {}",
+            register(result),
+            opcode,
+            self.typed(operand)?,
+            self.ty(into)?
+        ))
+    }
+
+    fn terminator(&mut self, term: &Terminator, needs_trap: &mut bool) -> EngineResult<String> {
+        let repr = match term {
+            Terminator::Return { val } => match val {
+                None => "ret void".to_string(),
+                Some(v) => format!("ret {}", self.typed(v)?),
+            },
+            Terminator::Goto { target } => format!("br label {}", label(target)),
+            Terminator::Branch {
+                cond,
+                then_case,
+                else_case,
+            } => format!(
+                "br {}, label {}, label {}",
+                self.typed(cond)?,
+                label(then_case),
+                label(else_case)
+            ),
+            Terminator::Switch {
+                cond,
+                cases,
+                default,
+            } => {
+                let cond_ty = value_type(cond)?;
+                let bits = match &cond_ty {
+                    Type::Bitvec { bits, .. } => *bits,
+                    _ => {
+                        return Err(EngineError::InvariantViolation(
+                            "expect an integer condition in a switch".into(),
+                        ));
+                    }
+                };
+                let ty = self.ty(&cond_ty)?;
+                let default = match default {
+                    None => {
+                        // a switch without a default is total on the cases
+                        *needs_trap = true;
+                        "%bb.unreachable".to_string()
+                    }
+                    Some(target) => label(target),
+                };
+                let items: Vec<_> = cases
+                    .iter()
+                    .map(|(value, target)| {
+                        format!("{} {}, label {}", ty, signed(value, bits), label(target))
+                    })
+                    .collect();
+                format!(
+                    "switch {}, label {} [ {} ]",
+                    self.typed(cond)?,
+                    default,
+                    items.join(" ")
+                )
+            }
+            Terminator::Indirect { address, targets } => {
+                let items: Vec<_> = targets
+                    .iter()
+                    .map(|t| format!("label {}", label(t)))
+                    .collect();
+                format!(
+                    "indirectbr {}, [ {} ]",
+                    self.typed(address)?,
+                    items.join(", ")
+                )
+            }
+            Terminator::InvokeDirect {
+                function,
+                conv,
+                args,
+                arg_attrs,
+                result,
+                normal,
+                unwind,
+            } => {
+                let (assign, call) =
+                    self.call(Some(function), None, conv, args, arg_attrs, result)?;
+                format!(
+                    "{}invoke {} to label {} unwind label {}",
+                    assign,
+                    call,
+                    label(normal),
+                    label(unwind)
+                )
+            }
+            Terminator::InvokeIndirect {
+                callee,
+                conv,
+                args,
+                arg_attrs,
+                result,
+                normal,
+                unwind,
+            } => {
+                let (assign, call) =
+                    self.call(None, Some(callee), conv, args, arg_attrs, result)?;
+                format!(
+                    "{}invoke {} to label {} unwind label {}",
+                    assign,
+                    call,
+                    label(normal),
+                    label(unwind)
+                )
+            }
+            Terminator::Resume { val } => format!("resume {}", self.typed(val)?),
+            Terminator::Unreachable => "unreachable".to_string(),
+        };
+        Ok(repr)
+    }
+}
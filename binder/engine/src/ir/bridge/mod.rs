@@ -1,10 +1,15 @@
 pub mod cfg;
 pub mod constant;
+pub mod dominance;
 pub mod function;
 pub mod global;
 pub mod instruction;
 pub mod intrinsics;
+pub mod layout;
+pub mod lower;
 pub mod module;
+pub mod program;
 pub mod shared;
 pub mod typing;
 pub mod value;
+pub mod visit;
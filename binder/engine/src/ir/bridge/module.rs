@@ -1,17 +1,28 @@
+use std::collections::hash_map::DefaultHasher;
 use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
 
 use log::debug;
+use serde::{Deserialize, Serialize};
 
 use crate::error::{EngineError, EngineResult, Unsupported};
 use crate::ir::adapter;
+use crate::ir::bridge::constant::{Constant, NumValue, UndefSemantics};
 use crate::ir::bridge::function::Function;
-use crate::ir::bridge::global::GlobalVariable;
+use crate::ir::bridge::global::{FlatInitializer, GlobalVariable};
+use crate::ir::bridge::layout::DataLayout;
 use crate::ir::bridge::shared::{Identifier, SymbolRegistry};
 use crate::ir::bridge::typing::TypeRegistry;
+use crate::ir::callgraph::{CallGraph, CallKind};
+use crate::ir::census::ModuleStats;
+use crate::ir::effects::{infer_effects, Effect};
 
 /// An adapted representation of an LLVM module
-#[derive(Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Eq, PartialEq)]
 pub struct Module {
+    /// data layout
+    layout: DataLayout,
     /// type registry
     typing: TypeRegistry,
     /// symbol registry
@@ -20,13 +31,19 @@ pub struct Module {
     globals: BTreeMap<Identifier, GlobalVariable>,
     /// functions
     functions: BTreeMap<Identifier, Function>,
+    /// side effects of the functions, inferred once the functions are converted
+    effects: BTreeMap<Identifier, Effect>,
 }
 
 impl Module {
-    pub fn convert(module_adapted: &adapter::module::Module) -> EngineResult<Self> {
+    pub fn convert(
+        module_adapted: &adapter::module::Module,
+        semantics: UndefSemantics,
+    ) -> EngineResult<Self> {
         let adapter::module::Module {
             name,
             asm,
+            data_layout,
             structs,
             global_variables,
             functions,
@@ -42,6 +59,9 @@ impl Module {
             ));
         }
 
+        // parse data layout
+        let layout = DataLayout::parse(data_layout)?;
+
         // build type registry
         let typing = TypeRegistry::populate(structs)?;
 
@@ -54,12 +74,17 @@ impl Module {
             .iter()
             .filter_map(|func| func.name.as_ref().map(|e| e.into()))
             .collect();
-        let symbols = SymbolRegistry::new(allowed_globals, allowed_functions);
+        let no_return_functions: BTreeSet<Identifier> = functions
+            .iter()
+            .filter(|func| func.attrs.iter().any(|a| a == "noreturn"))
+            .filter_map(|func| func.name.as_ref().map(|e| e.into()))
+            .collect();
+        let symbols = SymbolRegistry::new(allowed_globals, allowed_functions, no_return_functions);
 
         // collect global variables
         let mut gvar_table = BTreeMap::new();
         for gvar in global_variables.iter() {
-            let converted = GlobalVariable::convert(gvar, &typing, &symbols)?;
+            let converted = GlobalVariable::convert(gvar, &typing, &symbols, semantics)?;
             gvar_table
                 .entry(converted.name.clone())
                 .or_insert_with(Vec::new)
@@ -69,7 +94,7 @@ impl Module {
         // collect functions
         let mut func_table = BTreeMap::new();
         for func in functions.iter() {
-            let converted = Function::convert(func, &typing, &symbols)?;
+            let converted = Function::convert(func, &typing, &symbols, semantics)?;
             func_table
                 .entry(converted.name.clone())
                 .or_insert_with(Vec::new)
@@ -89,12 +114,182 @@ impl Module {
             functions.insert(key, val);
         }
 
-        // done
-        Ok(Self {
+        // infer the side effects over the call graph
+        let mut module = Self {
+            layout,
             typing,
             symbols,
             globals,
             functions,
-        })
+            effects: BTreeMap::new(),
+        };
+        module.effects = infer_effects(&module);
+
+        // done
+        Ok(module)
+    }
+
+    /// Data layout of the target
+    pub fn layout(&self) -> &DataLayout {
+        &self.layout
+    }
+
+    /// Symbol registry of the module
+    pub fn symbols(&self) -> &SymbolRegistry {
+        &self.symbols
+    }
+
+    /// Global variables in the module
+    pub fn globals(&self) -> &BTreeMap<Identifier, GlobalVariable> {
+        &self.globals
+    }
+
+    /// Functions in the module
+    pub fn functions(&self) -> &BTreeMap<Identifier, Function> {
+        &self.functions
+    }
+
+    /// Look up a function by name
+    pub fn get_function(&self, name: &str) -> Option<&Function> {
+        self.functions.get(name)
+    }
+
+    /// Look up a global variable by name
+    pub fn get_global(&self, name: &str) -> Option<&GlobalVariable> {
+        self.globals.get(name)
+    }
+
+    /// Side effect of a function, including those of its callees, where a function unknown to
+    /// the module may perform I/O
+    pub fn effect(&self, name: &str) -> Effect {
+        self.effects
+            .get(name)
+            .copied()
+            .unwrap_or(Effect::PerformsIO)
+    }
+
+    /// Side effects of the functions in the module
+    pub fn effects(&self) -> &BTreeMap<Identifier, Effect> {
+        &self.effects
+    }
+
+    /// Functions with a body
+    pub fn defined_functions(&self) -> impl Iterator<Item = &Function> {
+        self.functions.values().filter(|func| func.body.is_some())
+    }
+
+    /// Functions declared only
+    pub fn declared_functions(&self) -> impl Iterator<Item = &Function> {
+        self.functions.values().filter(|func| func.body.is_none())
+    }
+
+    /// Static constructors (e.g., of C++ globals) in the order they run, as listed in
+    /// `llvm.global_ctors` by priority
+    pub fn static_constructors(&self) -> Vec<&Function> {
+        let entries = match self.get_global("llvm.global_ctors") {
+            Some(GlobalVariable {
+                initializer: Some(Constant::Array { sub: _, elements }),
+                ..
+            }) => elements,
+            _ => return vec![],
+        };
+        let mut ctors: Vec<_> = entries
+            .iter()
+            .filter_map(|entry| match entry {
+                Constant::Struct { name: _, fields } => match fields.as_slice() {
+                    [Constant::NumOne {
+                        bits: _,
+                        value: NumValue::Int(priority),
+                    }, Constant::Function { name }, ..] => Some((priority, name)),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .filter_map(|(priority, name)| {
+                self.get_function(name.as_ref())
+                    .filter(|func| func.body.is_some())
+                    .map(|func| (priority, func))
+            })
+            .collect();
+        // entries of the same priority run in the order listed
+        ctors.sort_by_key(|(priority, _)| *priority);
+        ctors.into_iter().map(|(_, func)| func).collect()
+    }
+
+    /// Functions where an execution may start: the static constructors, followed by `main` if
+    /// defined, otherwise every externally visible function with a body that is not directly
+    /// called within the module
+    pub fn entry_points(&self) -> Vec<&Function> {
+        let mut entries = self.static_constructors();
+        if let Some(func) = self.get_function("main") {
+            if func.body.is_some() {
+                entries.push(func);
+                return entries;
+            }
+        }
+
+        let graph = CallGraph::build(self);
+        let others: Vec<_> = self
+            .defined_functions()
+            .filter(|func| !func.linkage.is_local())
+            .filter(|func| {
+                graph
+                    .callers(&func.name)
+                    .into_iter()
+                    .all(|(caller, kind)| caller == &func.name || kind != CallKind::Direct)
+            })
+            .filter(|func| entries.iter().all(|ctor| ctor.name != func.name))
+            .collect();
+        entries.extend(others);
+        entries
+    }
+
+    /// Initializers of the global variables flattened by byte offsets, computed once so that
+    /// analyses can look up initial memory contents directly
+    pub fn flat_initializers(&self) -> EngineResult<BTreeMap<Identifier, FlatInitializer>> {
+        let mut result = BTreeMap::new();
+        for (name, gvar) in &self.globals {
+            if let Some(flat) = gvar.flatten_initializer(&self.layout)? {
+                result.insert(name.clone(), flat);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Statistics of the module
+    pub fn stats(&self) -> ModuleStats {
+        ModuleStats::collect(self)
+    }
+
+    /// A structural hash that is deterministic across runs and agrees with equality, i.e.,
+    /// equal modules always share the hash (the registries and effects are derived and hence skipped)
+    pub fn canonical_hash(&self) -> u64 {
+        let Self {
+            layout,
+            typing: _,
+            symbols: _,
+            globals,
+            functions,
+            effects: _,
+        } = self;
+
+        let mut hasher = DefaultHasher::new();
+        layout.hash(&mut hasher);
+        globals.hash(&mut hasher);
+        functions.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl Display for Module {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for gvar in self.globals.values() {
+            writeln!(f, "{}", gvar)?;
+        }
+        for func in self.functions.values() {
+            writeln!(f)?;
+            writeln!(f, "{}", func)?;
+        }
+        Ok(())
     }
 }
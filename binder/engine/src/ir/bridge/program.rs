@@ -0,0 +1,195 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use crate::error::{EngineError, EngineResult, Unsupported};
+use crate::ir::bridge::function::Function;
+use crate::ir::bridge::global::GlobalVariable;
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::{Identifier, SymbolRegistry};
+
+/// A program made of separately converted modules (one per translation unit),
+/// linked logically by resolving symbols across modules instead of by llvm-link
+pub struct Program {
+    /// converted modules, keyed by the name of their translation units
+    modules: BTreeMap<String, Module>,
+    /// symbol registry shared by all modules
+    symbols: SymbolRegistry,
+    /// module holding the definition of each externally visible global variable
+    global_defs: BTreeMap<Identifier, String>,
+    /// module holding the definition of each externally visible function
+    function_defs: BTreeMap<Identifier, String>,
+}
+
+/// Utility: pick the definition of an externally visible symbol following the one definition rule
+fn resolve_odr<'a, T, F>(
+    name: &Identifier,
+    candidates: Vec<(&'a String, &'a T)>,
+    is_weak: F,
+    unsupported: Unsupported,
+) -> EngineResult<&'a String>
+where
+    T: PartialEq,
+    F: Fn(&T) -> bool,
+{
+    // obtain the strongly defined symbol
+    let mut def = None;
+    let mut weak_defs = vec![];
+    for (unit, entry) in candidates {
+        if is_weak(entry) {
+            weak_defs.push((unit, entry));
+            continue;
+        }
+        if let Some((prior, _)) = def {
+            return Err(EngineError::InvalidAssumption(format!(
+                "symbol {} defined in both {} and {}",
+                name, prior, unit
+            )));
+        }
+        def = Some((unit, entry));
+    }
+    if let Some((unit, _)) = def {
+        return Ok(unit);
+    }
+
+    // no strongly defined symbol found, try to unify weak symbols
+    let mut iter = weak_defs.into_iter();
+    let (unit, val) = match iter.next() {
+        None => {
+            return Err(EngineError::InvariantViolation("no entries for ODR".into()));
+        }
+        Some(v) => v,
+    };
+    for (_, entry) in iter {
+        if entry != val {
+            return Err(EngineError::NotSupportedYet(unsupported));
+        }
+    }
+    Ok(unit)
+}
+
+impl Program {
+    /// Link the modules logically by resolving their externally visible symbols
+    pub fn link(modules: BTreeMap<String, Module>) -> EngineResult<Self> {
+        // collect definitions of externally visible symbols
+        let mut gvar_table: BTreeMap<&Identifier, Vec<(&String, &GlobalVariable)>> =
+            BTreeMap::new();
+        let mut func_table: BTreeMap<&Identifier, Vec<(&String, &Function)>> = BTreeMap::new();
+        for (unit, module) in &modules {
+            for (name, gvar) in module.globals() {
+                if gvar.initializer.is_some() && !gvar.linkage.is_local() {
+                    gvar_table.entry(name).or_default().push((unit, gvar));
+                }
+            }
+            for (name, func) in module.functions() {
+                if func.body.is_some() && !func.linkage.is_local() {
+                    func_table.entry(name).or_default().push((unit, func));
+                }
+            }
+        }
+
+        // resolve strong and weak symbols
+        let mut global_defs = BTreeMap::new();
+        for (name, candidates) in gvar_table {
+            let unit = resolve_odr(
+                name,
+                candidates,
+                |gvar| gvar.is_weak,
+                Unsupported::WeakGlobalVariable,
+            )?;
+            global_defs.insert(name.clone(), unit.clone());
+        }
+        let mut function_defs = BTreeMap::new();
+        for (name, candidates) in func_table {
+            let unit = resolve_odr(
+                name,
+                candidates,
+                |func| func.is_weak,
+                Unsupported::WeakFunction,
+            )?;
+            function_defs.insert(name.clone(), unit.clone());
+        }
+
+        // merge the symbol registries
+        let mut symbols = SymbolRegistry::default();
+        for module in modules.values() {
+            symbols.merge(module.symbols());
+        }
+
+        Ok(Self {
+            modules,
+            symbols,
+            global_defs,
+            function_defs,
+        })
+    }
+
+    /// Modules in the program, keyed by the name of their translation units
+    pub fn modules(&self) -> &BTreeMap<String, Module> {
+        &self.modules
+    }
+
+    /// Symbol registry shared by all modules
+    pub fn symbols(&self) -> &SymbolRegistry {
+        &self.symbols
+    }
+
+    /// Resolve a function referred to in a module into its definition, if any
+    pub fn resolve_function(&self, unit: &str, name: &Identifier) -> Option<(&str, &Function)> {
+        // a local definition shadows externally visible ones
+        let (key, module) = self.modules.get_key_value(unit)?;
+        if let Some(func) = module.functions().get(name) {
+            if func.body.is_some() && func.linkage.is_local() {
+                return Some((key.as_str(), func));
+            }
+        }
+        let owner = self.function_defs.get(name)?;
+        let func = self.modules.get(owner)?.functions().get(name)?;
+        Some((owner.as_str(), func))
+    }
+
+    /// Resolve a global variable referred to in a module into its definition, if any
+    pub fn resolve_global(&self, unit: &str, name: &Identifier) -> Option<(&str, &GlobalVariable)> {
+        // a local definition shadows externally visible ones
+        let (key, module) = self.modules.get_key_value(unit)?;
+        if let Some(gvar) = module.globals().get(name) {
+            if gvar.initializer.is_some() && gvar.linkage.is_local() {
+                return Some((key.as_str(), gvar));
+            }
+        }
+        let owner = self.global_defs.get(name)?;
+        let gvar = self.modules.get(owner)?.globals().get(name)?;
+        Some((owner.as_str(), gvar))
+    }
+
+    /// Functions declared but not defined in any module (i.e., left for the environment)
+    pub fn unresolved_functions(&self) -> BTreeSet<&Identifier> {
+        self.modules
+            .values()
+            .flat_map(|module| module.functions())
+            .filter(|(name, func)| func.body.is_none() && !self.function_defs.contains_key(*name))
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// Global variables declared but not defined in any module (i.e., left for the environment)
+    pub fn unresolved_globals(&self) -> BTreeSet<&Identifier> {
+        self.modules
+            .values()
+            .flat_map(|module| module.globals())
+            .filter(|(name, gvar)| {
+                gvar.initializer.is_none() && !self.global_defs.contains_key(*name)
+            })
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
+
+impl Display for Program {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (unit, module) in &self.modules {
+            writeln!(f, "; module {}", unit)?;
+            writeln!(f, "{}", module)?;
+        }
+        Ok(())
+    }
+}
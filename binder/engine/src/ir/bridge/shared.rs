@@ -1,8 +1,11 @@
+use std::borrow::Borrow;
 use std::collections::BTreeSet;
 use std::fmt::{Display, Formatter};
 
+use serde::{Deserialize, Serialize};
+
 /// Represents an identifier in the LLVM system
-#[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Debug)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Ord, PartialOrd, Clone, Debug)]
 pub struct Identifier(String);
 
 impl Display for Identifier {
@@ -33,16 +36,31 @@ impl AsRef<str> for Identifier {
     }
 }
 
+impl Borrow<str> for Identifier {
+    fn borrow(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
 /// Symbol registry
-#[derive(Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Default)]
 pub struct SymbolRegistry {
     globals: BTreeSet<Identifier>,
     functions: BTreeSet<Identifier>,
+    no_return: BTreeSet<Identifier>,
 }
 
 impl SymbolRegistry {
-    pub fn new(globals: BTreeSet<Identifier>, functions: BTreeSet<Identifier>) -> Self {
-        Self { globals, functions }
+    pub fn new(
+        globals: BTreeSet<Identifier>,
+        functions: BTreeSet<Identifier>,
+        no_return: BTreeSet<Identifier>,
+    ) -> Self {
+        Self {
+            globals,
+            functions,
+            no_return,
+        }
     }
 
     pub fn has_global(&self, ident: &Identifier) -> bool {
@@ -52,4 +70,56 @@ impl SymbolRegistry {
     pub fn has_function(&self, ident: &Identifier) -> bool {
         self.functions.contains(ident)
     }
+
+    pub fn is_no_return(&self, ident: &Identifier) -> bool {
+        self.no_return.contains(ident)
+    }
+
+    /// Merge the symbols of another registry into this one
+    pub fn merge(&mut self, other: &Self) {
+        self.globals.extend(other.globals.iter().cloned());
+        self.functions.extend(other.functions.iter().cloned());
+        self.no_return.extend(other.no_return.iter().cloned());
+    }
+}
+
+/// A position in the source code the module is compiled from
+#[derive(Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Clone)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// Serde helper that encodes a map as a sequence of key-value pairs,
+/// for keys (e.g., big integers) that are not valid JSON object keys
+pub mod map_as_pairs {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<K, V, S>(map: &BTreeMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize,
+        V: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(map.iter())
+    }
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<BTreeMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + Ord,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let pairs = Vec::<(K, V)>::deserialize(deserializer)?;
+        Ok(pairs.into_iter().collect())
+    }
 }
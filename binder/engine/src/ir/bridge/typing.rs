@@ -1,13 +1,15 @@
 use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 
+use serde::{Deserialize, Serialize};
+
 use crate::error::{EngineError, EngineResult, Unsupported};
 use crate::ir::adapter;
 use crate::ir::adapter::typing::UserDefinedStruct;
 use crate::ir::bridge::shared::Identifier;
 
 /// The underlying representation of the bitvec
-#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Copy, Clone, Debug)]
 pub enum NumRepr {
     Int,
     Float,
@@ -39,6 +41,7 @@ enum TypeToken {
     Struct {
         name: Option<Identifier>,
         fields: Vec<TypeToken>,
+        packed: bool,
     },
     Function {
         params: Vec<TypeToken>,
@@ -51,7 +54,7 @@ enum TypeToken {
 impl TypeToken {
     fn parse(
         ty: &adapter::typing::Type,
-        user_defined_structs: &BTreeMap<Identifier, Vec<adapter::typing::Type>>,
+        user_defined_structs: &BTreeMap<Identifier, (Vec<adapter::typing::Type>, bool)>,
     ) -> EngineResult<Self> {
         use adapter::typing::Type as AdaptedType;
 
@@ -107,7 +110,11 @@ impl TypeToken {
                     length: *length,
                 }
             }
-            AdaptedType::Struct { name, fields } => {
+            AdaptedType::Struct {
+                name,
+                fields,
+                packed,
+            } => {
                 let field_tys = match fields {
                     None => {
                         return Err(EngineError::InvalidAssumption(
@@ -128,8 +135,8 @@ impl TypeToken {
                                 ident
                             )));
                         }
-                        Some(defined_tys) => {
-                            if defined_tys != field_tys {
+                        Some((defined_tys, defined_packed)) => {
+                            if defined_tys != field_tys || defined_packed != packed {
                                 return Err(EngineError::InvalidAssumption(format!(
                                     "conflicting definition of named struct: {}",
                                     ident
@@ -147,6 +154,7 @@ impl TypeToken {
                 Self::Struct {
                     name: name_new,
                     fields: fields_new,
+                    packed: *packed,
                 }
             }
             AdaptedType::Function {
@@ -221,13 +229,18 @@ impl Display for TypeToken {
             Self::Array { element, length } => {
                 write!(f, "{}[{}]", element, length)
             }
-            Self::Struct { name, fields } => {
+            Self::Struct {
+                name,
+                fields,
+                packed,
+            } => {
                 let repr: Vec<_> = fields.iter().map(|e| e.to_string()).collect();
                 write!(
                     f,
-                    "{}{{{}}}",
+                    "{}{}{{{}}}",
                     name.as_ref()
                         .map_or_else(|| "<anonymous>".to_string(), |n| n.to_string()),
+                    if *packed { "<packed>" } else { "" },
                     repr.join(",")
                 )
             }
@@ -251,7 +264,7 @@ impl Display for TypeToken {
 }
 
 /// An adapted representation of LLVM typing system
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Clone, Debug)]
 pub enum Type {
     /// Bitvec
     Bitvec {
@@ -266,6 +279,7 @@ pub enum Type {
     Struct {
         name: Option<Identifier>,
         fields: Vec<Type>,
+        packed: bool,
     },
     /// A function type
     Function {
@@ -301,7 +315,11 @@ impl Type {
                     length: *length,
                 }
             }
-            TypeToken::Struct { name, fields } => {
+            TypeToken::Struct {
+                name,
+                fields,
+                packed,
+            } => {
                 let converted = fields
                     .iter()
                     .map(Self::convert_token)
@@ -309,6 +327,7 @@ impl Type {
                 Self::Struct {
                     name: name.as_ref().cloned(),
                     fields: converted,
+                    packed: *packed,
                 }
             }
             TypeToken::Function {
@@ -361,13 +380,18 @@ impl Display for Type {
             Self::Array { element, length } => {
                 write!(f, "{}[{}]", element, length)
             }
-            Self::Struct { name, fields } => {
+            Self::Struct {
+                name,
+                fields,
+                packed,
+            } => {
                 let repr: Vec<_> = fields.iter().map(|e| e.to_string()).collect();
                 write!(
                     f,
-                    "{}{{{}}}",
+                    "{}{}{{{}}}",
                     name.as_ref()
                         .map_or_else(|| "<anonymous>".to_string(), |n| n.to_string()),
+                    if *packed { "<packed>" } else { "" },
                     repr.join(",")
                 )
             }
@@ -392,9 +416,9 @@ impl Display for Type {
 }
 
 /// A type registry that holds all the user-defined struct types
-#[derive(Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Eq, PartialEq)]
 pub struct TypeRegistry {
-    user_defined_structs: BTreeMap<Identifier, Vec<adapter::typing::Type>>,
+    user_defined_structs: BTreeMap<Identifier, (Vec<adapter::typing::Type>, bool)>,
 }
 
 impl TypeRegistry {
@@ -408,7 +432,11 @@ impl TypeRegistry {
         let mut type_ident_to_fields = BTreeMap::new();
 
         for def in user_defined_structs {
-            let UserDefinedStruct { name, fields } = def;
+            let UserDefinedStruct {
+                name,
+                fields,
+                packed,
+            } = def;
             let ident: Identifier = name
                 .as_ref()
                 .ok_or_else(|| {
@@ -422,7 +450,7 @@ impl TypeRegistry {
                 .ok_or(EngineError::NotSupportedYet(Unsupported::OpaqueType))?
                 .clone();
 
-            match type_ident_to_fields.insert(ident, items) {
+            match type_ident_to_fields.insert(ident, (items, *packed)) {
                 None => (),
                 Some(_) => {
                     return Err(EngineError::InvalidAssumption(format!(
@@ -435,7 +463,7 @@ impl TypeRegistry {
 
         // analyze their definitions
         let mut type_defs = BTreeMap::new();
-        for (src_ident, items) in type_ident_to_fields.iter() {
+        for (src_ident, (items, _)) in type_ident_to_fields.iter() {
             // convert fields
             let fields: Vec<_> = items
                 .iter()
@@ -1,10 +1,27 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
 use crate::error::{EngineError, EngineResult};
 use crate::ir::bridge::constant::Constant;
 use crate::ir::bridge::typing::Type;
 
-#[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Ord, PartialOrd, Copy, Clone)]
 pub struct BlockLabel(usize);
 
+impl Display for BlockLabel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+impl BlockLabel {
+    /// Position of the item in its numbering
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
 impl From<usize> for BlockLabel {
     fn from(v: usize) -> Self {
         Self(v)
@@ -16,9 +33,22 @@ impl From<&usize> for BlockLabel {
     }
 }
 
-#[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Ord, PartialOrd, Copy, Clone)]
 pub struct RegisterSlot(usize);
 
+impl Display for RegisterSlot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "%{}", self.0)
+    }
+}
+
+impl RegisterSlot {
+    /// Position of the item in its numbering
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
 impl From<usize> for RegisterSlot {
     fn from(v: usize) -> Self {
         Self(v)
@@ -30,9 +60,22 @@ impl From<&usize> for RegisterSlot {
     }
 }
 
-#[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Ord, PartialOrd, Copy, Clone)]
 pub struct ArgumentSlot(usize);
 
+impl Display for ArgumentSlot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "${}", self.0)
+    }
+}
+
+impl ArgumentSlot {
+    /// Position of the item in its numbering
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
 impl From<usize> for ArgumentSlot {
     fn from(v: usize) -> Self {
         Self(v)
@@ -45,7 +88,7 @@ impl From<&usize> for ArgumentSlot {
 }
 
 /// An naive translation of an LLVM value
-#[derive(Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub enum Value {
     /// a constant value
     Constant(Constant),
@@ -65,3 +108,13 @@ impl Value {
         }
     }
 }
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Constant(constant) => constant.fmt(f),
+            Self::Argument { index, ty: _ } => index.fmt(f),
+            Self::Register { index, ty: _ } => index.fmt(f),
+        }
+    }
+}
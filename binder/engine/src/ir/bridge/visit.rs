@@ -0,0 +1,136 @@
+use std::mem;
+
+use crate::ir::bridge::cfg::{Block, ControlFlowGraph};
+use crate::ir::bridge::constant::{Constant, Expression};
+use crate::ir::bridge::function::Function;
+use crate::ir::bridge::instruction::{Instruction, Terminator};
+use crate::ir::bridge::value::Value;
+
+/// A read-only traversal over the IR, where every method defaults to visiting the children
+pub trait Visitor<'a> {
+    fn visit_function(&mut self, func: &'a Function) {
+        walk_function(self, func)
+    }
+
+    fn visit_block(&mut self, block: &'a Block) {
+        walk_block(self, block)
+    }
+
+    fn visit_instruction(&mut self, inst: &'a Instruction) {
+        walk_instruction(self, inst)
+    }
+
+    fn visit_terminator(&mut self, term: &'a Terminator) {
+        walk_terminator(self, term)
+    }
+
+    fn visit_value(&mut self, value: &'a Value) {
+        walk_value(self, value)
+    }
+
+    fn visit_constant(&mut self, constant: &'a Constant) {
+        walk_constant(self, constant)
+    }
+
+    fn visit_expression(&mut self, expr: &'a Expression) {
+        walk_expression(self, expr)
+    }
+}
+
+pub fn walk_function<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, func: &'a Function) {
+    if let Some(cfg) = &func.body {
+        walk_cfg(visitor, cfg);
+    }
+}
+
+pub fn walk_cfg<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, cfg: &'a ControlFlowGraph) {
+    for (_, block) in cfg.blocks() {
+        visitor.visit_block(block);
+    }
+}
+
+pub fn walk_block<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, block: &'a Block) {
+    for inst in block.instructions() {
+        visitor.visit_instruction(inst);
+    }
+    visitor.visit_terminator(block.terminator());
+}
+
+pub fn walk_instruction<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, inst: &'a Instruction) {
+    for value in inst.operands() {
+        visitor.visit_value(value);
+    }
+}
+
+pub fn walk_terminator<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, term: &'a Terminator) {
+    for value in term.operands() {
+        visitor.visit_value(value);
+    }
+}
+
+pub fn walk_value<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, value: &'a Value) {
+    match value {
+        Value::Constant(constant) => visitor.visit_constant(constant),
+        Value::Argument { .. } | Value::Register { .. } => (),
+    }
+}
+
+pub fn walk_constant<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, constant: &'a Constant) {
+    match constant {
+        Constant::Expr(expr) => visitor.visit_expression(expr),
+        _ => {
+            for child in constant.children() {
+                visitor.visit_constant(child);
+            }
+        }
+    }
+}
+
+pub fn walk_expression<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expr: &'a Expression) {
+    for constant in expr.operands() {
+        visitor.visit_constant(constant);
+    }
+}
+
+/// A rewriting traversal over the IR, where values and constants are folded bottom-up
+/// and every method defaults to folding the children only
+pub trait Fold {
+    fn fold_instruction(&mut self, inst: &mut Instruction) {
+        for value in inst.operands_mut() {
+            let folded = self.fold_value(mem::replace(value, Value::Constant(Constant::Null)));
+            *value = folded;
+        }
+    }
+
+    fn fold_terminator(&mut self, term: &mut Terminator) {
+        for value in term.operands_mut() {
+            let folded = self.fold_value(mem::replace(value, Value::Constant(Constant::Null)));
+            *value = folded;
+        }
+    }
+
+    fn fold_value(&mut self, value: Value) -> Value {
+        match value {
+            Value::Constant(constant) => Value::Constant(self.fold_constant(constant)),
+            Value::Argument { .. } | Value::Register { .. } => value,
+        }
+    }
+
+    fn fold_constant(&mut self, mut constant: Constant) -> Constant {
+        match &mut constant {
+            Constant::Expr(expr) => {
+                for operand in expr.operands_mut() {
+                    let folded = self.fold_constant(mem::replace(operand, Constant::Null));
+                    *operand = folded;
+                }
+            }
+            _ => {
+                for child in constant.children_mut() {
+                    let folded = self.fold_constant(mem::replace(child, Constant::Null));
+                    *child = folded;
+                }
+            }
+        }
+        constant
+    }
+}
@@ -0,0 +1,384 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use petgraph::algo::{tarjan_scc, toposort};
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::{Dfs, EdgeRef};
+
+use crate::ir::bridge::constant::{Constant, NumValue};
+use crate::ir::bridge::function::{CallingConvention, Function};
+use crate::ir::bridge::instruction::{Instruction, Terminator};
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::typing::{NumRepr, Type};
+use crate::ir::bridge::value::Value;
+use crate::ir::bridge::visit::Visitor;
+
+/// How a caller reaches a callee
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Debug)]
+pub enum CallKind {
+    /// through a call or invoke on a named function
+    Direct,
+    /// through a call or invoke on a function pointer, resolved by signature
+    Indirect,
+}
+
+/// Signature of an indirect call site
+struct CallSite<'a> {
+    conv: CallingConvention,
+    args: &'a [Value],
+    ret: Option<&'a Type>,
+}
+
+impl CallSite<'_> {
+    /// Whether the function may be the target of this call site
+    fn may_target(&self, func: &Function) -> bool {
+        if func.conv != self.conv || func.params.len() != self.args.len() {
+            return false;
+        }
+        if func.ret.as_ref() != self.ret {
+            return false;
+        }
+        // arguments without a known type (e.g., aggregate constants) match anything
+        func.params
+            .iter()
+            .zip(self.args)
+            .all(|(param, arg)| value_type(arg).is_none_or(|ty| ty == param.ty))
+    }
+}
+
+/// Utility: the type of a value, if it can be derived from the value itself
+fn value_type(value: &Value) -> Option<Type> {
+    let ty = match value {
+        Value::Argument { index: _, ty } | Value::Register { index: _, ty } => ty.clone(),
+        Value::Constant(constant) => match constant {
+            Constant::NumOne { bits, value } => Type::Bitvec {
+                bits: *bits,
+                number: match value {
+                    NumValue::Int(_) | NumValue::IntUndef => NumRepr::Int,
+                    NumValue::Float(_) | NumValue::FloatUndef => NumRepr::Float,
+                },
+                length: None,
+            },
+            Constant::NumVec {
+                bits,
+                number,
+                elements,
+            } => Type::Bitvec {
+                bits: *bits,
+                number: *number,
+                length: Some(elements.len()),
+            },
+            Constant::Null
+            | Constant::UndefPointer
+            | Constant::Variable { .. }
+            | Constant::Function { .. }
+            | Constant::Block { .. } => Type::Pointer,
+            Constant::Array { .. } | Constant::Struct { .. } | Constant::Expr(_) => {
+                return None;
+            }
+        },
+    };
+    Some(ty)
+}
+
+/// Collector of call sites in a function
+#[derive(Default)]
+struct CallCollector<'a> {
+    direct: Vec<&'a Identifier>,
+    indirect: Vec<CallSite<'a>>,
+}
+
+impl<'a> Visitor<'a> for CallCollector<'a> {
+    fn visit_instruction(&mut self, inst: &'a Instruction) {
+        match inst {
+            Instruction::CallDirect { function, .. } => self.direct.push(function),
+            Instruction::CallIndirect {
+                callee: _,
+                conv,
+                args,
+                arg_attrs: _,
+                result,
+            } => self.indirect.push(CallSite {
+                conv: *conv,
+                args,
+                ret: result.as_ref().map(|(ty, _)| ty),
+            }),
+            _ => (),
+        }
+    }
+
+    fn visit_terminator(&mut self, term: &'a Terminator) {
+        match term {
+            Terminator::InvokeDirect { function, .. } => self.direct.push(function),
+            Terminator::InvokeIndirect {
+                callee: _,
+                conv,
+                args,
+                arg_attrs: _,
+                result,
+                normal: _,
+                unwind: _,
+            } => self.indirect.push(CallSite {
+                conv: *conv,
+                args,
+                ret: result.as_ref().map(|(ty, _)| ty),
+            }),
+            _ => (),
+        }
+    }
+}
+
+/// A strongly connected component of the call graph
+pub struct Component<'a> {
+    /// functions of the component, where callees tend to come before their callers
+    pub functions: Vec<&'a Identifier>,
+    /// whether the functions call each other, or the function calls itself
+    pub recursive: bool,
+}
+
+/// A call graph over the functions in a module
+pub struct CallGraph {
+    /// the call graph
+    graph: DiGraph<Identifier, CallKind>,
+    /// function name to index in the graph
+    function_to_index: BTreeMap<Identifier, NodeIndex>,
+}
+
+impl CallGraph {
+    /// Build the call graph, over-approximating indirect calls by signature match
+    pub fn build(module: &Module) -> Self {
+        let functions = module.functions();
+
+        // collect the call edges, where direct calls take precedence
+        let mut edges: BTreeMap<(&Identifier, &Identifier), CallKind> = BTreeMap::new();
+        for (caller, func) in functions {
+            let mut collector = CallCollector::default();
+            collector.visit_function(func);
+            let CallCollector { direct, indirect } = collector;
+
+            for site in indirect {
+                for (callee, target) in functions {
+                    if site.may_target(target) {
+                        edges.entry((caller, callee)).or_insert(CallKind::Indirect);
+                    }
+                }
+            }
+            for callee in direct {
+                edges.insert((caller, callee), CallKind::Direct);
+            }
+        }
+
+        // construct the graph
+        let mut graph = DiGraph::new();
+        let mut function_to_index = BTreeMap::new();
+        for name in functions.keys() {
+            function_to_index.insert(name.clone(), graph.add_node(name.clone()));
+        }
+        for ((caller, callee), kind) in edges {
+            let src = *function_to_index
+                .entry(caller.clone())
+                .or_insert_with(|| graph.add_node(caller.clone()));
+            let dst = *function_to_index
+                .entry(callee.clone())
+                .or_insert_with(|| graph.add_node(callee.clone()));
+            graph.add_edge(src, dst, kind);
+        }
+
+        Self {
+            graph,
+            function_to_index,
+        }
+    }
+
+    /// Functions called by the function
+    pub fn callees(&self, name: &Identifier) -> BTreeMap<&Identifier, CallKind> {
+        match self.function_to_index.get(name) {
+            None => BTreeMap::new(),
+            Some(index) => self
+                .graph
+                .edges(*index)
+                .map(|edge| (&self.graph[edge.target()], *edge.weight()))
+                .collect(),
+        }
+    }
+
+    /// Functions calling the function
+    pub fn callers(&self, name: &Identifier) -> BTreeMap<&Identifier, CallKind> {
+        match self.function_to_index.get(name) {
+            None => BTreeMap::new(),
+            Some(index) => self
+                .graph
+                .edges_directed(*index, petgraph::Direction::Incoming)
+                .map(|edge| (&self.graph[edge.source()], *edge.weight()))
+                .collect(),
+        }
+    }
+
+    /// Strongly connected components, with callees ordered before their callers
+    pub fn sccs(&self) -> Vec<BTreeSet<&Identifier>> {
+        tarjan_scc(&self.graph)
+            .into_iter()
+            .map(|scc| scc.into_iter().map(|index| &self.graph[index]).collect())
+            .collect()
+    }
+
+    /// Utility: append the members of an SCC reachable from the node in DFS postorder
+    fn postorder(
+        &self,
+        node: NodeIndex,
+        members: &BTreeSet<NodeIndex>,
+        visited: &mut BTreeSet<NodeIndex>,
+        order: &mut Vec<NodeIndex>,
+    ) {
+        if !visited.insert(node) {
+            return;
+        }
+        for next in self.graph.neighbors(node) {
+            if members.contains(&next) {
+                self.postorder(next, members, visited, order);
+            }
+        }
+        order.push(node);
+    }
+
+    /// Strongly connected components, with callees ordered before their callers, both across
+    /// the components and (as far as the cycles permit) within one, so that a fixedpoint over a
+    /// recursion converges in fewer rounds
+    pub fn components(&self) -> Vec<Component<'_>> {
+        tarjan_scc(&self.graph)
+            .into_iter()
+            .map(|scc| {
+                let recursive = scc.len() > 1 || self.graph.contains_edge(scc[0], scc[0]);
+                let members: BTreeSet<_> = scc.iter().copied().collect();
+                let mut visited = BTreeSet::new();
+                let mut order = vec![];
+                for node in &scc {
+                    self.postorder(*node, &members, &mut visited, &mut order);
+                }
+                Component {
+                    functions: order.into_iter().map(|index| &self.graph[index]).collect(),
+                    recursive,
+                }
+            })
+            .collect()
+    }
+
+    /// Maximum number of frames on the stack while a function runs, i.e., the longest call chain
+    /// from the function, or `None` if the function may reach a recursion
+    pub fn call_depths(&self) -> BTreeMap<&Identifier, Option<usize>> {
+        let mut depths: BTreeMap<&Identifier, Option<usize>> = BTreeMap::new();
+        for component in self.components() {
+            for name in &component.functions {
+                let depth = if component.recursive {
+                    None
+                } else {
+                    self.callees(name)
+                        .into_keys()
+                        .try_fold(0, |acc, callee| depths[callee].map(|d| acc.max(d)))
+                        .map(|d| d + 1)
+                };
+                depths.insert(name, depth);
+            }
+        }
+        depths
+    }
+
+    /// Whether the function is part of a recursion (including self-recursion)
+    pub fn is_recursive(&self, name: &Identifier) -> bool {
+        let index = match self.function_to_index.get(name) {
+            None => return false,
+            Some(index) => *index,
+        };
+        if self.graph.contains_edge(index, index) {
+            return true;
+        }
+        tarjan_scc(&self.graph)
+            .into_iter()
+            .any(|scc| scc.len() > 1 && scc.contains(&index))
+    }
+
+    /// Functions in topological order (callers before callees), or `None` if there is recursion
+    pub fn topological_order(&self) -> Option<Vec<&Identifier>> {
+        let order = toposort(&self.graph, None).ok()?;
+        Some(order.into_iter().map(|index| &self.graph[index]).collect())
+    }
+
+    /// Functions reachable from the roots (including the roots themselves)
+    pub fn reachable_from<'a, I>(&self, roots: I) -> BTreeSet<&Identifier>
+    where
+        I: IntoIterator<Item = &'a Identifier>,
+    {
+        let mut reachable = BTreeSet::new();
+        for root in roots {
+            let index = match self.function_to_index.get(root) {
+                None => continue,
+                Some(index) => *index,
+            };
+            let mut dfs = Dfs::new(&self.graph, index);
+            while let Some(next) = dfs.next(&self.graph) {
+                reachable.insert(&self.graph[next]);
+            }
+        }
+        reachable
+    }
+}
+
+/// Recursion in a module and the depths of the calls elsewhere
+pub struct RecursionReport {
+    /// functions calling themselves or each other, one group per call-graph SCC
+    pub recursions: Vec<Vec<Identifier>>,
+    /// longest call chain from each function, `None` if it may reach a recursion
+    pub depths: BTreeMap<Identifier, Option<usize>>,
+}
+
+impl RecursionReport {
+    pub fn collect(module: &Module) -> Self {
+        let graph = CallGraph::build(module);
+        let recursions = graph
+            .components()
+            .into_iter()
+            .filter(|component| component.recursive)
+            .map(|component| component.functions.into_iter().cloned().collect())
+            .collect();
+        let depths = graph
+            .call_depths()
+            .into_iter()
+            .filter(|(name, _)| {
+                module
+                    .get_function(name.as_ref())
+                    .is_some_and(|func| func.body.is_some())
+            })
+            .map(|(name, depth)| (name.clone(), depth))
+            .collect();
+        Self { recursions, depths }
+    }
+
+    /// Deepest call chain among the functions not reaching a recursion
+    pub fn max_depth(&self) -> Option<usize> {
+        self.depths.values().flatten().copied().max()
+    }
+}
+
+impl Display for RecursionReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for group in &self.recursions {
+            let names: Vec<_> = group.iter().map(|name| format!("@{}", name)).collect();
+            if group.len() == 1 {
+                writeln!(f, "direct recursion: {}", names.join(", "))?;
+            } else {
+                writeln!(f, "mutual recursion: {}", names.join(", "))?;
+            }
+        }
+        for (name, depth) in &self.depths {
+            match depth {
+                None => writeln!(f, "@{}: unbounded depth", name)?,
+                Some(depth) => writeln!(f, "@{}: depth {}", name, depth)?,
+            }
+        }
+        match self.max_depth() {
+            None => writeln!(f, "max depth: none"),
+            Some(depth) => writeln!(f, "max depth: {}", depth),
+        }
+    }
+}
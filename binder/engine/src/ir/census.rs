@@ -0,0 +1,582 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngineError, Unsupported};
+use crate::ir::adapter::constant::{Const, Constant};
+use crate::ir::adapter::function::Function;
+use crate::ir::adapter::global::GlobalVariable;
+use crate::ir::adapter::instruction::{Inst, Instruction};
+use crate::ir::adapter::module::Module;
+use crate::ir::adapter::typing::Type;
+use crate::ir::adapter::value::Value;
+use crate::ir::bridge;
+use crate::ir::bridge::instruction::Terminator;
+use crate::ir::bridge::intrinsics::filter_intrinsics;
+use crate::ir::bridge::visit::{walk_function, Visitor};
+
+/// A quick census of what is inside a module, collected before conversion
+#[derive(Default)]
+pub struct Census {
+    /// number of functions with a body
+    pub num_defined_functions: usize,
+    /// number of functions declared only
+    pub num_declared_functions: usize,
+    /// number of global variables
+    pub num_global_variables: usize,
+    /// number of basic blocks
+    pub num_blocks: usize,
+    /// count of instructions per kind
+    pub instructions: BTreeMap<&'static str, usize>,
+    /// count of types per shape
+    pub types: BTreeMap<&'static str, usize>,
+    /// count of vector types per width
+    pub vector_widths: BTreeMap<usize, usize>,
+    /// count of calls per intrinsic
+    pub intrinsics: BTreeMap<String, usize>,
+    /// count of features that the bridge will reject
+    pub near_misses: BTreeMap<Unsupported, usize>,
+}
+
+impl Census {
+    /// Collect the census for an adapted module
+    pub fn collect(module: &Module) -> Self {
+        let Module {
+            name: _,
+            asm,
+            data_layout: _,
+            structs,
+            global_variables,
+            functions,
+        } = module;
+
+        let mut census = Self::default();
+        if !asm.is_empty() {
+            census.near_miss(Unsupported::ModuleLevelAssembly);
+        }
+        for item in structs {
+            match &item.fields {
+                None => census.near_miss(Unsupported::OpaqueType),
+                Some(fields) => {
+                    for field in fields {
+                        census.visit_type(field);
+                    }
+                }
+            }
+        }
+        for gvar in global_variables {
+            census.visit_global(gvar);
+        }
+        for func in functions {
+            census.visit_function(func);
+        }
+        census
+    }
+
+    fn bump<K: Ord>(map: &mut BTreeMap<K, usize>, key: K) {
+        *map.entry(key).or_insert(0) += 1;
+    }
+
+    fn near_miss(&mut self, item: Unsupported) {
+        Self::bump(&mut self.near_misses, item);
+    }
+
+    fn visit_global(&mut self, gvar: &GlobalVariable) {
+        let GlobalVariable {
+            name,
+            ty,
+            is_defined: _,
+            is_exact,
+            is_const: _,
+            is_thread_local,
+            address_space,
+            linkage: _,
+            visibility: _,
+            section: _,
+            initializer,
+        } = gvar;
+
+        self.num_global_variables += 1;
+        if name.is_none() {
+            self.near_miss(Unsupported::AnonymousGlobalVariable);
+        }
+        if !*is_exact {
+            self.near_miss(Unsupported::WeakGlobalVariable);
+        }
+        if *is_thread_local {
+            self.near_miss(Unsupported::ThreadLocalStorage);
+        }
+        self.visit_address_space(*address_space);
+        self.visit_type(ty);
+        if let Some(init) = initializer {
+            self.visit_constant(init);
+        }
+    }
+
+    fn visit_function(&mut self, func: &Function) {
+        let Function {
+            name,
+            ty,
+            is_defined,
+            is_exact,
+            is_intrinsic: _,
+            calling_conv: _,
+            linkage: _,
+            attrs: _,
+            ret_attrs: _,
+            params: _,
+            blocks,
+        } = func;
+
+        if *is_defined {
+            self.num_defined_functions += 1;
+            if !*is_exact {
+                self.near_miss(Unsupported::WeakFunction);
+            }
+        } else {
+            self.num_declared_functions += 1;
+        }
+        match name {
+            None => self.near_miss(Unsupported::AnonymousFunction),
+            Some(ident) => self.visit_intrinsic_name(ident),
+        }
+        self.visit_type(ty);
+
+        for block in blocks {
+            self.num_blocks += 1;
+            for inst in block.body.iter().chain(std::iter::once(&block.terminator)) {
+                self.visit_instruction(inst);
+            }
+        }
+    }
+
+    fn visit_intrinsic_name(&mut self, name: &str) {
+        if let Err(EngineError::NotSupportedYet(item)) = filter_intrinsics(name) {
+            self.near_miss(item);
+        }
+    }
+
+    fn visit_address_space(&mut self, address_space: usize) {
+        if address_space != 0 {
+            self.near_miss(Unsupported::PointerAddressSpace);
+        }
+    }
+
+    fn visit_instruction(&mut self, inst: &Instruction) {
+        let Instruction {
+            name: _,
+            ty,
+            index: _,
+            repr,
+            debug: _,
+        } = inst;
+        self.visit_type(ty);
+        self.visit_inst(repr);
+    }
+
+    fn visit_inst(&mut self, inst: &Inst) {
+        let kind = match inst {
+            Inst::Alloca { address_space, .. } => {
+                self.visit_address_space(*address_space);
+                "alloca"
+            }
+            Inst::Load { address_space, .. } => {
+                self.visit_address_space(*address_space);
+                "load"
+            }
+            Inst::Store { address_space, .. } => {
+                self.visit_address_space(*address_space);
+                "store"
+            }
+            Inst::VAArg { .. } => "va_arg",
+            Inst::Intrinsic { callee, .. } => {
+                let name = match callee {
+                    Value::Constant(Constant {
+                        ty: _,
+                        repr: Const::Function { name: Some(name) },
+                    }) => name.clone(),
+                    _ => "<unknown>".to_string(),
+                };
+                self.visit_intrinsic_name(&name);
+                Self::bump(&mut self.intrinsics, name);
+                "intrinsic"
+            }
+            Inst::CallDirect { .. } => "call_direct",
+            Inst::CallIndirect { .. } => "call_indirect",
+            Inst::CallAsm { .. } => {
+                self.near_miss(Unsupported::InlineAssembly);
+                "call_asm"
+            }
+            Inst::Unary { .. } => "unary",
+            Inst::Binary { .. } => "binary",
+            Inst::Compare { .. } => "compare",
+            Inst::Cast { .. } => "cast",
+            Inst::Freeze { .. } => "freeze",
+            Inst::GEP { address_space, .. } => {
+                self.visit_address_space(*address_space);
+                "gep"
+            }
+            Inst::ITE { .. } => "ite",
+            Inst::Phi { .. } => "phi",
+            Inst::GetValue { .. } => "get_value",
+            Inst::SetValue { .. } => "set_value",
+            Inst::GetElement { .. } => "get_element",
+            Inst::SetElement { .. } => "set_element",
+            Inst::ShuffleVector { .. } => "shuffle_vector",
+            Inst::Fence { .. } => {
+                self.near_miss(Unsupported::AtomicInstruction);
+                "fence"
+            }
+            Inst::AtomicCmpXchg { .. } => {
+                self.near_miss(Unsupported::AtomicInstruction);
+                "atomic_cmpxchg"
+            }
+            Inst::AtomicRMW { .. } => {
+                self.near_miss(Unsupported::AtomicInstruction);
+                "atomic_rmw"
+            }
+            Inst::LandingPad { .. } => "landing_pad",
+            Inst::CatchPad => {
+                self.near_miss(Unsupported::WindowsEH);
+                "catch_pad"
+            }
+            Inst::CleanupPad => {
+                self.near_miss(Unsupported::WindowsEH);
+                "cleanup_pad"
+            }
+            Inst::Return { .. } => "return",
+            Inst::Branch { .. } => "branch",
+            Inst::Switch { .. } => "switch",
+            Inst::IndirectJump { .. } => "indirect_jump",
+            Inst::InvokeDirect { .. } => "invoke_direct",
+            Inst::InvokeIndirect { .. } => "invoke_indirect",
+            Inst::InvokeAsm { .. } => {
+                self.near_miss(Unsupported::InlineAssembly);
+                "invoke_asm"
+            }
+            Inst::Resume { .. } => "resume",
+            Inst::CatchSwitch => {
+                self.near_miss(Unsupported::WindowsEH);
+                "catch_switch"
+            }
+            Inst::CatchReturn => {
+                self.near_miss(Unsupported::WindowsEH);
+                "catch_return"
+            }
+            Inst::CleanupReturn => {
+                self.near_miss(Unsupported::WindowsEH);
+                "cleanup_return"
+            }
+            Inst::CallBranch => {
+                self.near_miss(Unsupported::CallBranch);
+                "call_branch"
+            }
+            Inst::Unreachable => "unreachable",
+        };
+        Self::bump(&mut self.instructions, kind);
+    }
+
+    fn visit_constant(&mut self, constant: &Constant) {
+        let Constant { ty: _, repr } = constant;
+        match repr {
+            Const::Int { .. }
+            | Const::Float { .. }
+            | Const::Null
+            | Const::None
+            | Const::Undef
+            | Const::Default
+            | Const::Variable { .. }
+            | Const::Function { .. }
+            | Const::Label { .. } => (),
+            Const::Extension => self.near_miss(Unsupported::ArchSpecificExtension),
+            Const::Alias { .. } => self.near_miss(Unsupported::GlobalAlias),
+            Const::Interface { .. } => self.near_miss(Unsupported::InterfaceResolver),
+            Const::Marker { .. } => self.near_miss(Unsupported::GlobalMarker),
+            Const::Vector { elements } | Const::Array { elements } | Const::Struct { elements } => {
+                for element in elements {
+                    self.visit_constant(element);
+                }
+            }
+            Const::Expr { inst } => self.visit_inst(inst),
+        }
+    }
+
+    fn visit_type(&mut self, ty: &Type) {
+        let shape = match ty {
+            Type::Void => "void",
+            Type::Int { .. } => "int",
+            Type::Float { .. } => "float",
+            Type::Array { element, .. } => {
+                self.visit_type(element);
+                "array"
+            }
+            Type::Struct { fields, .. } => {
+                if fields.is_none() {
+                    self.near_miss(Unsupported::OpaqueType);
+                }
+                "struct"
+            }
+            Type::Function { params, ret, .. } => {
+                for param in params {
+                    self.visit_type(param);
+                }
+                self.visit_type(ret);
+                "function"
+            }
+            Type::Pointer { address_space } => {
+                self.visit_address_space(*address_space);
+                "pointer"
+            }
+            Type::Vector {
+                element,
+                fixed,
+                length,
+            } => {
+                if !*fixed {
+                    self.near_miss(Unsupported::ScalableVector);
+                }
+                if matches!(element.as_ref(), Type::Pointer { .. }) {
+                    self.near_miss(Unsupported::VectorOfPointers);
+                }
+                Self::bump(&mut self.vector_widths, *length);
+                self.visit_type(element);
+                "vector"
+            }
+            Type::Extension { .. } => {
+                self.near_miss(Unsupported::ArchSpecificExtension);
+                "extension"
+            }
+            Type::TypedPointer { .. } => {
+                self.near_miss(Unsupported::TypedPointer);
+                "typed_pointer"
+            }
+            Type::Label => "label",
+            Type::Token => "token",
+            Type::Metadata => "metadata",
+        };
+        Self::bump(&mut self.types, shape);
+    }
+}
+
+impl Display for Census {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            num_defined_functions,
+            num_declared_functions,
+            num_global_variables,
+            num_blocks,
+            instructions,
+            types,
+            vector_widths,
+            intrinsics,
+            near_misses,
+        } = self;
+
+        writeln!(f, "functions (defined): {}", num_defined_functions)?;
+        writeln!(f, "functions (declared): {}", num_declared_functions)?;
+        writeln!(f, "global variables: {}", num_global_variables)?;
+        writeln!(f, "basic blocks: {}", num_blocks)?;
+
+        writeln!(f, "instructions:")?;
+        for (kind, count) in instructions {
+            writeln!(f, "  {}: {}", kind, count)?;
+        }
+        writeln!(f, "types:")?;
+        for (shape, count) in types {
+            writeln!(f, "  {}: {}", shape, count)?;
+        }
+        writeln!(f, "vector widths:")?;
+        for (width, count) in vector_widths {
+            writeln!(f, "  {}: {}", width, count)?;
+        }
+        writeln!(f, "intrinsics:")?;
+        for (name, count) in intrinsics {
+            writeln!(f, "  {}: {}", name, count)?;
+        }
+        writeln!(f, "unsupported features:")?;
+        for (item, count) in near_misses {
+            writeln!(f, "  {}: {}", item, count)?;
+        }
+        Ok(())
+    }
+}
+
+/// Statistics of a converted module
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct ModuleStats {
+    /// number of functions with a body
+    pub num_defined_functions: usize,
+    /// number of functions declared only
+    pub num_declared_functions: usize,
+    /// number of global variables
+    pub num_global_variables: usize,
+    /// number of basic blocks
+    pub num_blocks: usize,
+    /// count of instructions (including terminators) per opcode group
+    pub instructions: BTreeMap<String, usize>,
+    /// number of volatile loads and stores, which analyses cannot fold
+    pub num_volatile_accesses: usize,
+    /// count of features accepted by the bridge only in a restricted form
+    pub near_misses: BTreeMap<Unsupported, usize>,
+}
+
+/// Utility: opcode group of an instruction
+fn instruction_group(inst: &bridge::instruction::Instruction) -> &'static str {
+    use bridge::instruction::Instruction;
+
+    match inst {
+        Instruction::Alloca { .. } | Instruction::Load { .. } | Instruction::Store { .. } => {
+            "memory"
+        }
+        Instruction::VariadicArg { .. } => "variadic",
+        Instruction::CallDirect { .. } | Instruction::CallIndirect { .. } => "call",
+        Instruction::Contract { .. } => "contract",
+        Instruction::UnaryArith { .. } | Instruction::BinaryArith { .. } => "arithmetic",
+        Instruction::BinaryBitwise { .. } => "bitwise",
+        Instruction::BinaryShift { .. } => "shift",
+        Instruction::CompareBitvec { .. }
+        | Instruction::CompareOrder { .. }
+        | Instruction::ComparePtr { .. } => "compare",
+        Instruction::CastBitvecSize { .. }
+        | Instruction::CastBitvecRepr { .. }
+        | Instruction::CastBitvecFree { .. }
+        | Instruction::CastPtr { .. }
+        | Instruction::CastPtrToInt { .. }
+        | Instruction::CastIntToPtr { .. } => "cast",
+        Instruction::FreezeBitvec { .. }
+        | Instruction::FreezePtr
+        | Instruction::FreezeNop { .. } => "freeze",
+        Instruction::GEP { .. } | Instruction::GEPNop { .. } => "gep",
+        Instruction::ITEOne { .. } | Instruction::ITEVec { .. } => "selection",
+        Instruction::Phi { .. } => "phi",
+        Instruction::GetValue { .. }
+        | Instruction::SetValue { .. }
+        | Instruction::GetElement { .. }
+        | Instruction::SetElement { .. }
+        | Instruction::ShuffleVec { .. } => "aggregate",
+        Instruction::LandingPad { .. } => "exception",
+    }
+}
+
+/// Utility: opcode group of a terminator
+fn terminator_group(term: &Terminator) -> &'static str {
+    match term {
+        Terminator::Return { .. } => "return",
+        Terminator::Goto { .. } | Terminator::Branch { .. } | Terminator::Switch { .. } => "branch",
+        Terminator::Indirect { .. } => "indirect-branch",
+        Terminator::InvokeDirect { .. } | Terminator::InvokeIndirect { .. } => "invoke",
+        Terminator::Resume { .. } => "exception",
+        Terminator::Unreachable => "unreachable",
+    }
+}
+
+impl ModuleStats {
+    /// Collect the statistics of a converted module
+    pub fn collect(module: &bridge::module::Module) -> Self {
+        let mut stats = Self::default();
+        for gvar in module.globals().values() {
+            stats.num_global_variables += 1;
+            if gvar.is_weak {
+                stats.near_miss(Unsupported::WeakGlobalVariable);
+            }
+        }
+        for func in module.functions().values() {
+            stats.visit_function(func);
+        }
+        stats
+    }
+
+    fn bump<K: Ord>(map: &mut BTreeMap<K, usize>, key: K) {
+        *map.entry(key).or_insert(0) += 1;
+    }
+
+    fn near_miss(&mut self, item: Unsupported) {
+        Self::bump(&mut self.near_misses, item);
+    }
+
+    /// Accumulate the statistics of another module, e.g., across a test suite
+    pub fn merge(&mut self, other: &Self) {
+        let Self {
+            num_defined_functions,
+            num_declared_functions,
+            num_global_variables,
+            num_blocks,
+            instructions,
+            num_volatile_accesses,
+            near_misses,
+        } = other;
+
+        self.num_defined_functions += num_defined_functions;
+        self.num_declared_functions += num_declared_functions;
+        self.num_global_variables += num_global_variables;
+        self.num_blocks += num_blocks;
+        self.num_volatile_accesses += num_volatile_accesses;
+        for (key, count) in instructions {
+            *self.instructions.entry(key.clone()).or_insert(0) += count;
+        }
+        for (key, count) in near_misses {
+            *self.near_misses.entry(key.clone()).or_insert(0) += count;
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for ModuleStats {
+    fn visit_function(&mut self, func: &'a bridge::function::Function) {
+        match &func.body {
+            None => self.num_declared_functions += 1,
+            Some(cfg) => {
+                self.num_defined_functions += 1;
+                self.num_blocks += cfg.blocks().count();
+            }
+        }
+        if func.is_weak {
+            self.near_miss(Unsupported::WeakFunction);
+        }
+        if func.variadic {
+            self.near_miss(Unsupported::VariadicArguments);
+        }
+        walk_function(self, func);
+    }
+
+    fn visit_instruction(&mut self, inst: &'a bridge::instruction::Instruction) {
+        Self::bump(&mut self.instructions, instruction_group(inst).to_string());
+        if inst.is_volatile() {
+            self.num_volatile_accesses += 1;
+        }
+    }
+
+    fn visit_terminator(&mut self, term: &'a Terminator) {
+        Self::bump(&mut self.instructions, terminator_group(term).to_string());
+    }
+}
+
+impl Display for ModuleStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            num_defined_functions,
+            num_declared_functions,
+            num_global_variables,
+            num_blocks,
+            instructions,
+            num_volatile_accesses,
+            near_misses,
+        } = self;
+
+        writeln!(f, "functions (defined): {}", num_defined_functions)?;
+        writeln!(f, "functions (declared): {}", num_declared_functions)?;
+        writeln!(f, "global variables: {}", num_global_variables)?;
+        writeln!(f, "basic blocks: {}", num_blocks)?;
+
+        writeln!(f, "instructions:")?;
+        for (group, count) in instructions {
+            writeln!(f, "  {}: {}", group, count)?;
+        }
+        writeln!(f, "volatile accesses: {}", num_volatile_accesses)?;
+        writeln!(f, "near misses:")?;
+        for (item, count) in near_misses {
+            writeln!(f, "  {}: {}", item, count)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngineError, EngineResult};
+use crate::ir::bridge::cfg::ControlFlowGraph;
+use crate::ir::bridge::function::Function;
+use crate::ir::bridge::instruction::{Instruction, Terminator};
+use crate::ir::bridge::value::{BlockLabel, RegisterSlot, Value};
+
+/// Location of an instruction or a terminator in a CFG
+#[derive(Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Copy, Clone)]
+pub enum Site {
+    /// the n-th instruction in the block
+    Instruction { block: BlockLabel, index: usize },
+    /// the terminator of the block
+    Terminator { block: BlockLabel },
+}
+
+impl Site {
+    /// Block holding the site
+    pub fn block(&self) -> BlockLabel {
+        match self {
+            Self::Instruction { block, index: _ } | Self::Terminator { block } => *block,
+        }
+    }
+}
+
+impl Display for Site {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Instruction { block, index } => write!(f, "{}[{}]", block, index),
+            Self::Terminator { block } => write!(f, "{}[term]", block),
+        }
+    }
+}
+
+/// A statement referred to by a site
+pub enum Statement<'a> {
+    Instruction(&'a Instruction),
+    Terminator(&'a Terminator),
+}
+
+/// Def-use chains of the registers in a function
+pub struct DefUse {
+    /// site defining each register
+    defs: BTreeMap<RegisterSlot, Site>,
+    /// sites using each register
+    uses: BTreeMap<RegisterSlot, BTreeSet<Site>>,
+}
+
+impl DefUse {
+    /// Build the def-use chains, an empty index for function declarations
+    pub fn build(func: &Function) -> EngineResult<Self> {
+        let mut defs = BTreeMap::new();
+        let mut uses: BTreeMap<RegisterSlot, BTreeSet<Site>> = BTreeMap::new();
+
+        let cfg = match &func.body {
+            None => return Ok(Self { defs, uses }),
+            Some(cfg) => cfg,
+        };
+
+        for (label, block) in cfg.blocks() {
+            let sites = block
+                .instructions()
+                .iter()
+                .enumerate()
+                .map(|(index, inst)| {
+                    (
+                        Site::Instruction {
+                            block: *label,
+                            index,
+                        },
+                        inst.result(),
+                        inst.operands(),
+                    )
+                })
+                .chain(std::iter::once((
+                    Site::Terminator { block: *label },
+                    block.terminator().result(),
+                    block.terminator().operands(),
+                )));
+
+            for (site, result, operands) in sites {
+                if let Some(reg) = result {
+                    if let Some(prior) = defs.insert(reg, site) {
+                        return Err(EngineError::InvariantViolation(format!(
+                            "register {} defined at both {} and {} in function {}",
+                            reg, prior, site, func.name
+                        )));
+                    }
+                }
+                for value in operands {
+                    if let Value::Register { index, ty: _ } = value {
+                        uses.entry(*index).or_default().insert(site);
+                    }
+                }
+            }
+        }
+
+        // every register used must be defined
+        for (reg, sites) in &uses {
+            if !defs.contains_key(reg) {
+                return Err(EngineError::InvariantViolation(format!(
+                    "register {} used at {} without a definition in function {}",
+                    reg,
+                    sites.iter().next().expect("at least one use site"),
+                    func.name
+                )));
+            }
+        }
+        Ok(Self { defs, uses })
+    }
+
+    /// Registers defined in the function
+    pub fn registers(&self) -> impl Iterator<Item = &RegisterSlot> {
+        self.defs.keys()
+    }
+
+    /// Site defining the register
+    pub fn definition(&self, reg: &RegisterSlot) -> Option<Site> {
+        self.defs.get(reg).copied()
+    }
+
+    /// Sites using the register
+    pub fn uses(&self, reg: &RegisterSlot) -> impl Iterator<Item = &Site> {
+        self.uses.get(reg).into_iter().flatten()
+    }
+
+    /// Whether the register is defined but never used
+    pub fn is_dead(&self, reg: &RegisterSlot) -> bool {
+        self.defs.contains_key(reg) && !self.uses.contains_key(reg)
+    }
+
+    /// Resolve a site into the statement in the CFG
+    pub fn resolve<'a>(cfg: &'a ControlFlowGraph, site: &Site) -> Option<Statement<'a>> {
+        let block = cfg.get_block_by_label(&site.block())?;
+        let stmt = match site {
+            Site::Instruction { block: _, index } => {
+                Statement::Instruction(block.instructions().get(*index)?)
+            }
+            Site::Terminator { block: _ } => Statement::Terminator(block.terminator()),
+        };
+        Some(stmt)
+    }
+}
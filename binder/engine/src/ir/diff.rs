@@ -0,0 +1,303 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use crate::ir::bridge::cfg::{Block, ControlFlowGraph};
+use crate::ir::bridge::function::Function;
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::value::BlockLabel;
+
+/// An edit on a sequence of statements
+#[derive(Eq, PartialEq)]
+pub enum Edit {
+    /// statement only in the new sequence
+    Added(String),
+    /// statement only in the old sequence
+    Removed(String),
+}
+
+impl Display for Edit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Added(line) => write!(f, "+ {}", line),
+            Self::Removed(line) => write!(f, "- {}", line),
+        }
+    }
+}
+
+/// Utility: minimal edits turning the old lines into the new lines (via the longest common subsequence)
+fn diff_lines(old: &[String], new: &[String]) -> Vec<Edit> {
+    // lengths of the longest common subsequences of the suffixes
+    let mut table = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            table[i][j] = if old[i] == new[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+
+    // walk the table to recover the edits
+    let mut edits = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if table[i + 1][j] >= table[i][j + 1] {
+            edits.push(Edit::Removed(old[i].clone()));
+            i += 1;
+        } else {
+            edits.push(Edit::Added(new[j].clone()));
+            j += 1;
+        }
+    }
+    edits.extend(old[i..].iter().map(|line| Edit::Removed(line.clone())));
+    edits.extend(new[j..].iter().map(|line| Edit::Added(line.clone())));
+    edits
+}
+
+/// Utility: statements of a block, one per line
+fn block_lines(block: &Block) -> Vec<String> {
+    block
+        .instructions()
+        .iter()
+        .map(|inst| inst.to_string())
+        .chain(std::iter::once(block.terminator().to_string()))
+        .collect()
+}
+
+/// Difference on a block
+pub enum BlockDiff {
+    Added(BlockLabel),
+    Removed(BlockLabel),
+    Changed(BlockLabel, Vec<Edit>),
+}
+
+/// Difference on a function
+pub enum FunctionDiff {
+    Added(Identifier),
+    Removed(Identifier),
+    Changed {
+        name: Identifier,
+        /// old and new signatures, if changed
+        signature: Option<(String, String)>,
+        /// differences on blocks, matched by label
+        blocks: Vec<BlockDiff>,
+    },
+}
+
+/// Difference on a global variable
+pub enum GlobalDiff {
+    Added(String),
+    Removed(String),
+    Changed(String, String),
+}
+
+/// Structural difference between two modules
+pub struct ModuleDiff {
+    pub globals: Vec<GlobalDiff>,
+    pub functions: Vec<FunctionDiff>,
+}
+
+/// Utility: pair up the entries of two maps by key
+fn pair_up<'a, K: Ord, V>(
+    old: &'a BTreeMap<K, V>,
+    new: &'a BTreeMap<K, V>,
+) -> Vec<(&'a K, Option<&'a V>, Option<&'a V>)> {
+    let keys: BTreeSet<&K> = old.keys().chain(new.keys()).collect();
+    keys.into_iter()
+        .map(|key| (key, old.get(key), new.get(key)))
+        .collect()
+}
+
+fn diff_cfg(old: &ControlFlowGraph, new: &ControlFlowGraph) -> Vec<BlockDiff> {
+    let old_blocks: BTreeMap<_, _> = old.blocks().collect();
+    let new_blocks: BTreeMap<_, _> = new.blocks().collect();
+
+    let mut result = vec![];
+    for (label, lhs, rhs) in pair_up(&old_blocks, &new_blocks) {
+        match (lhs, rhs) {
+            (None, None) => unreachable!(),
+            (None, Some(_)) => result.push(BlockDiff::Added(**label)),
+            (Some(_), None) => result.push(BlockDiff::Removed(**label)),
+            (Some(lhs), Some(rhs)) => {
+                if lhs == rhs {
+                    continue;
+                }
+                let edits = diff_lines(&block_lines(lhs), &block_lines(rhs));
+                if !edits.is_empty() {
+                    result.push(BlockDiff::Changed(**label, edits));
+                }
+            }
+        }
+    }
+    result
+}
+
+fn diff_function(old: &Function, new: &Function) -> Option<FunctionDiff> {
+    if old == new {
+        return None;
+    }
+
+    let sig_old = old.signature();
+    let sig_new = new.signature();
+    let signature = if sig_old == sig_new {
+        None
+    } else {
+        Some((sig_old, sig_new))
+    };
+    let blocks = match (&old.body, &new.body) {
+        (Some(lhs), Some(rhs)) => diff_cfg(lhs, rhs),
+        (None, None) => vec![],
+        (Some(lhs), None) => lhs
+            .blocks()
+            .map(|(label, _)| BlockDiff::Removed(*label))
+            .collect(),
+        (None, Some(rhs)) => rhs
+            .blocks()
+            .map(|(label, _)| BlockDiff::Added(*label))
+            .collect(),
+    };
+    if signature.is_none() && blocks.is_empty() {
+        // equal up to block renaming in the CFG
+        return None;
+    }
+    Some(FunctionDiff::Changed {
+        name: new.name.clone(),
+        signature,
+        blocks,
+    })
+}
+
+impl ModuleDiff {
+    /// Compare two modules function-by-function and block-by-block
+    pub fn compute(old: &Module, new: &Module) -> Self {
+        let mut globals = vec![];
+        for (_, lhs, rhs) in pair_up(old.globals(), new.globals()) {
+            match (lhs, rhs) {
+                (None, None) => unreachable!(),
+                (None, Some(gvar)) => globals.push(GlobalDiff::Added(gvar.to_string())),
+                (Some(gvar), None) => globals.push(GlobalDiff::Removed(gvar.to_string())),
+                (Some(lhs), Some(rhs)) => {
+                    if lhs != rhs {
+                        globals.push(GlobalDiff::Changed(lhs.to_string(), rhs.to_string()));
+                    }
+                }
+            }
+        }
+
+        let mut functions = vec![];
+        for (name, lhs, rhs) in pair_up(old.functions(), new.functions()) {
+            match (lhs, rhs) {
+                (None, None) => unreachable!(),
+                (None, Some(_)) => functions.push(FunctionDiff::Added(name.clone())),
+                (Some(_), None) => functions.push(FunctionDiff::Removed(name.clone())),
+                (Some(lhs), Some(rhs)) => {
+                    if let Some(delta) = diff_function(lhs, rhs) {
+                        functions.push(delta);
+                    }
+                }
+            }
+        }
+
+        Self { globals, functions }
+    }
+
+    /// Whether the two modules are structurally the same
+    pub fn is_empty(&self) -> bool {
+        self.globals.is_empty() && self.functions.is_empty()
+    }
+
+    /// Names of the functions added, removed, or changed
+    pub fn function_names(&self) -> Vec<&Identifier> {
+        self.functions
+            .iter()
+            .map(|delta| match delta {
+                FunctionDiff::Added(name)
+                | FunctionDiff::Removed(name)
+                | FunctionDiff::Changed { name, .. } => name,
+            })
+            .collect()
+    }
+
+    /// Numbers of the instructions added and removed in the changed functions
+    pub fn instruction_delta(&self) -> (usize, usize) {
+        let mut added = 0;
+        let mut removed = 0;
+        for delta in &self.functions {
+            if let FunctionDiff::Changed { blocks, .. } = delta {
+                for block in blocks {
+                    if let BlockDiff::Changed(_, edits) = block {
+                        for edit in edits {
+                            match edit {
+                                Edit::Added(_) => added += 1,
+                                Edit::Removed(_) => removed += 1,
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        (added, removed)
+    }
+
+    /// A one-line summary of the differences
+    pub fn summary(&self) -> String {
+        let (added, removed) = self.instruction_delta();
+        format!(
+            "{} globals changed, {} functions changed, {} instructions added, {} removed",
+            self.globals.len(),
+            self.functions.len(),
+            added,
+            removed
+        )
+    }
+}
+
+impl Display for ModuleDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for delta in &self.globals {
+            match delta {
+                GlobalDiff::Added(repr) => writeln!(f, "+ {}", repr)?,
+                GlobalDiff::Removed(repr) => writeln!(f, "- {}", repr)?,
+                GlobalDiff::Changed(old, new) => {
+                    writeln!(f, "- {}", old)?;
+                    writeln!(f, "+ {}", new)?;
+                }
+            }
+        }
+        for delta in &self.functions {
+            match delta {
+                FunctionDiff::Added(name) => writeln!(f, "+ function @{}", name)?,
+                FunctionDiff::Removed(name) => writeln!(f, "- function @{}", name)?,
+                FunctionDiff::Changed {
+                    name,
+                    signature,
+                    blocks,
+                } => {
+                    writeln!(f, "@@ function @{}", name)?;
+                    if let Some((old, new)) = signature {
+                        writeln!(f, "- {}", old)?;
+                        writeln!(f, "+ {}", new)?;
+                    }
+                    for block in blocks {
+                        match block {
+                            BlockDiff::Added(label) => writeln!(f, "+ block {}", label)?,
+                            BlockDiff::Removed(label) => writeln!(f, "- block {}", label)?,
+                            BlockDiff::Changed(label, edits) => {
+                                writeln!(f, "  {}:", label)?;
+                                for edit in edits {
+                                    writeln!(f, "  {}", edit)?;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
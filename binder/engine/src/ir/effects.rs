@@ -0,0 +1,247 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::libc::find_model;
+use crate::ir::bridge::function::{Function, FunctionAttribute};
+use crate::ir::bridge::instruction::Instruction;
+use crate::ir::bridge::module::Module;
+use crate::ir::bridge::shared::Identifier;
+use crate::ir::bridge::value::{RegisterSlot, Value};
+use crate::ir::callgraph::CallGraph;
+
+/// Side effects of a function, ordered from the weakest to the strongest
+///
+/// Accesses to the stack objects of the function itself are not effects, as no caller observes
+/// them.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Debug)]
+pub enum Effect {
+    /// no access to memory beyond the own stack
+    Pure,
+    /// may read memory it does not own
+    ReadsMemory,
+    /// may read and write memory it does not own
+    WritesMemory,
+    /// may interact with the environment, e.g., files, sockets, or volatile memory
+    PerformsIO,
+}
+
+impl Effect {
+    pub fn reads_memory(&self) -> bool {
+        *self >= Self::ReadsMemory
+    }
+
+    pub fn writes_memory(&self) -> bool {
+        *self >= Self::WritesMemory
+    }
+}
+
+impl Display for Effect {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pure => write!(f, "pure"),
+            Self::ReadsMemory => write!(f, "reads-memory"),
+            Self::WritesMemory => write!(f, "writes-memory"),
+            Self::PerformsIO => write!(f, "performs-io"),
+        }
+    }
+}
+
+/// Library functions interacting with the environment
+static IO_FUNCTIONS: [&str; 34] = [
+    "printf", "fprintf", "vprintf", "vfprintf", "puts", "fputs", "putchar", "fputc", "putc",
+    "fwrite", "fflush", "scanf", "fscanf", "getchar", "fgetc", "getc", "fgets", "fread", "fopen",
+    "fclose", "fseek", "ftell", "open", "close", "read", "write", "socket", "bind", "listen",
+    "accept", "send", "recv", "exit", "abort",
+];
+
+/// Library functions that only compute on their arguments
+static PURE_FUNCTIONS: [&str; 14] = [
+    "abs", "labs", "llabs", "fabs", "sqrt", "pow", "exp", "log", "sin", "cos", "floor", "ceil",
+    "isdigit", "isspace",
+];
+
+/// Library functions managing the heap
+static HEAP_FUNCTIONS: [&str; 13] = [
+    "malloc",
+    "calloc",
+    "realloc",
+    "aligned_alloc",
+    "strdup",
+    "strndup",
+    "free",
+    "_Znwm",
+    "_Znam",
+    "_ZdlPv",
+    "_ZdaPv",
+    "_ZdlPvm",
+    "_ZdaPvm",
+];
+
+/// C++ runtime functions managing exceptions and the guards of static locals
+static CXX_RUNTIME_FUNCTIONS: [&str; 9] = [
+    "__cxa_allocate_exception",
+    "__cxa_free_exception",
+    "__cxa_throw",
+    "__cxa_rethrow",
+    "__cxa_begin_catch",
+    "__cxa_end_catch",
+    "__cxa_guard_acquire",
+    "__cxa_guard_release",
+    "__cxa_guard_abort",
+];
+
+/// Effect of a function without a body, from its attributes and the known library functions,
+/// where anything unknown may perform I/O
+pub fn declared_effect(func: &Function) -> Effect {
+    if func.attrs.contains(&FunctionAttribute::ReadNone) {
+        return Effect::Pure;
+    }
+    if func.attrs.contains(&FunctionAttribute::ReadOnly) {
+        return Effect::ReadsMemory;
+    }
+    library_effect(func.name.as_ref())
+}
+
+/// Effect of a library function by name
+fn library_effect(name: &str) -> Effect {
+    if IO_FUNCTIONS.contains(&name) {
+        return Effect::PerformsIO;
+    }
+    if PURE_FUNCTIONS.contains(&name) {
+        return Effect::Pure;
+    }
+    if HEAP_FUNCTIONS.contains(&name) || CXX_RUNTIME_FUNCTIONS.contains(&name) {
+        return Effect::WritesMemory;
+    }
+    if let Some(intrinsic) = name.strip_prefix("llvm.") {
+        // besides the memory and variadic-argument intrinsics, intrinsics compute on values or
+        // annotate the code
+        let writes = [
+            "memcpy", "memmove", "memset", "va_start", "va_end", "va_copy",
+        ];
+        return if writes.iter().any(|prefix| intrinsic.starts_with(prefix)) {
+            Effect::WritesMemory
+        } else {
+            Effect::Pure
+        };
+    }
+    match find_model(name) {
+        Some(model) if !model.writes.is_empty() => Effect::WritesMemory,
+        Some(model) if !model.reads.is_empty() => Effect::ReadsMemory,
+        Some(_) => Effect::Pure,
+        None => Effect::PerformsIO,
+    }
+}
+
+/// Utility: the register a pointer operand is held in
+fn register_of(value: &Value) -> Option<RegisterSlot> {
+    match value {
+        Value::Register { index, ty: _ } => Some(*index),
+        _ => None,
+    }
+}
+
+/// Effect of the instructions of a function, leaving out the calls
+fn local_effect(func: &Function) -> Effect {
+    let cfg = match func.exact_body() {
+        None => return declared_effect(func),
+        Some(cfg) => cfg,
+    };
+
+    // registers pointing into the stack objects of the function
+    let mut locals = BTreeSet::new();
+    loop {
+        let count = locals.len();
+        for (_, block) in cfg.blocks() {
+            for inst in block.instructions() {
+                match inst {
+                    Instruction::Alloca { result, .. } => {
+                        locals.insert(*result);
+                    }
+                    Instruction::CastPtr {
+                        operand: pointer,
+                        result,
+                    }
+                    | Instruction::GEP {
+                        pointer, result, ..
+                    }
+                    | Instruction::GEPNop {
+                        pointer, result, ..
+                    } if register_of(pointer).is_some_and(|slot| locals.contains(&slot)) => {
+                        locals.insert(*result);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        if locals.len() == count {
+            break;
+        }
+    }
+    let is_local =
+        |pointer: &Value| register_of(pointer).is_some_and(|slot| locals.contains(&slot));
+
+    let mut effect = Effect::Pure;
+    for (_, block) in cfg.blocks() {
+        for inst in block.instructions() {
+            let item = match inst {
+                Instruction::Load { volatile: true, .. }
+                | Instruction::Store { volatile: true, .. } => Effect::PerformsIO,
+                Instruction::Load { pointer, .. } if !is_local(pointer) => Effect::ReadsMemory,
+                Instruction::Store { pointer, .. } | Instruction::VariadicArg { pointer }
+                    if !is_local(pointer) =>
+                {
+                    Effect::WritesMemory
+                }
+                _ => Effect::Pure,
+            };
+            effect = effect.max(item);
+        }
+    }
+    effect
+}
+
+/// Infer the effects of the functions in the module, including those of their callees, where
+/// indirect calls resolve to the functions of a matching signature
+pub fn infer_effects(module: &Module) -> BTreeMap<Identifier, Effect> {
+    let graph = CallGraph::build(module);
+    let mut effects = BTreeMap::new();
+
+    // callees are settled before their callers, except within a recursion
+    for component in graph.components() {
+        let scc = component.functions;
+        for name in &scc {
+            let effect = module
+                .get_function(name.as_ref())
+                .map_or_else(|| library_effect(name.as_ref()), local_effect);
+            effects.insert((*name).clone(), effect);
+        }
+        loop {
+            let mut changed = false;
+            for name in &scc {
+                // the callees of a body the linker may replace are not known either
+                if module
+                    .get_function(name.as_ref())
+                    .is_some_and(|func| func.exact_body().is_none())
+                {
+                    continue;
+                }
+                let effect = graph
+                    .callees(name)
+                    .into_keys()
+                    .filter_map(|callee| effects.get(callee).copied())
+                    .fold(effects[*name], Effect::max);
+                if effect != effects[*name] {
+                    effects.insert((*name).clone(), effect);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+    effects
+}
@@ -1,3 +1,9 @@
 pub mod adapter;
+pub mod alignment;
 pub mod bridge;
+pub mod callgraph;
+pub mod census;
+pub mod defuse;
+pub mod diff;
+pub mod effects;
 pub mod flatten;
@@ -1,3 +1,20 @@
+pub mod analysis;
 pub mod error;
 pub mod flow;
 pub mod ir;
+
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::flow::fixedpoint::FlowFixedpoint;
+use crate::flow::shared::Context;
+use crate::ir::bridge::module::Module;
+
+/// Resume the fixedpoint optimization in a workspace kept by a prior run (i.e., with `--keep`),
+/// reusing the steps and serialized modules already there
+pub fn resume(path: &Path, depth: Option<usize>) -> Result<Vec<Module>> {
+    let ctxt = Context::new()?;
+    let trace = FlowFixedpoint::resume(&ctxt, path, depth)?;
+    Ok(trace)
+}
@@ -1,15 +1,37 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
 
 use anyhow::{bail, Result};
-use libra_engine::flow::build_simple::FlowBuildSimple;
-use libra_engine::flow::fixedpoint::FlowFixedpoint;
-use log::info;
+use libra_engine::analysis::bounds::check_out_of_bounds;
+use libra_engine::analysis::config::AnalysisConfig;
+use libra_engine::analysis::cstring::check_string_misuses;
+use libra_engine::analysis::deadcode::{analyze_live_code, check_dead_code};
+use libra_engine::analysis::escape::check_stack_leaks;
+use libra_engine::analysis::invariant::{analyze_intervals, synthesize_loop_invariants};
+use libra_engine::analysis::lifetime::check_heap_lifetimes;
+use libra_engine::analysis::overflow::check_overflows;
+use libra_engine::analysis::pointer::{check_null_dereferences, validate_null_checks};
+use libra_engine::analysis::pointsto::{analyze_aliases, AliasMode};
+use libra_engine::analysis::taint::{analyze_taint, TaintSpec};
+use libra_engine::analysis::uninit::check_uninitialized_reads;
+use libra_engine::analysis::verify::verify_assertions_with;
+use libra_engine::flow::build_simple::{FlowBuildSimple, Profile};
+use libra_engine::flow::cache::FixedpointCache;
+use libra_engine::flow::fixedpoint::{FlowFixedpoint, OptLevel, Schedule};
+use libra_engine::flow::multi::{FlowMultiTarget, Target};
+use log::{error, info};
 use structopt::StructOpt;
 use tempfile::tempdir;
 
-use libra_engine::flow::shared::Context;
+use libra_engine::flow::shared::{Context, Loader, SerialFormat, ToolLimits};
+use libra_engine::ir::alignment::check_misaligned_accesses;
+use libra_engine::ir::bridge::constant::UndefSemantics;
+use libra_engine::ir::bridge::lower::lower_module;
+use libra_engine::ir::bridge::module::Module;
+use libra_engine::ir::callgraph::RecursionReport;
+use libra_engine::ir::diff::ModuleDiff;
 use libra_shared::config::{initialize, PATH_STUDIO};
 
 #[derive(StructOpt)]
@@ -23,21 +45,101 @@ struct Args {
     #[structopt(short, long)]
     keep: bool,
 
+    /// Produce the artifacts for debugging (e.g., disassembled bitcode), implied by --keep
+    #[structopt(long)]
+    debug_artifacts: bool,
+
     /// Actions
     #[structopt(short, long)]
     actions: Vec<Action>,
 
-    /// Source code files
-    #[structopt(required = true)]
+    /// Source files, or bitcode and IR files to link along
+    #[structopt(required_unless = "target")]
     inputs: Vec<PathBuf>,
 
+    /// Independent targets as name=input[,input...], each built and optimized into its own module
+    /// in parallel, in place of the inputs
+    #[structopt(long, parse(try_from_str = Target::parse))]
+    target: Vec<Target>,
+
     /// Extra flags to be passed to clang
     #[structopt(short, long)]
     flags: Vec<String>,
 
+    /// Write a compilation database of the sources built into bitcode to this path
+    #[structopt(long)]
+    compile_db: Option<PathBuf>,
+
+    /// Preset of clang flags: default, hardened, size, c11, or gnu89
+    #[structopt(long, default_value = "default", parse(try_from_str = Profile::parse))]
+    profile: Profile,
+
     /// Limit the depth of fixedpoint optimization
     #[structopt(short, long)]
     depth: Option<usize>,
+
+    /// Opt pass pipeline of the fixedpoint rounds (default<O3> if none), where multiple ones are
+    /// applied round-robin
+    #[structopt(long)]
+    pipeline: Vec<String>,
+
+    /// Optimization levels of the first fixedpoint rounds in order (e.g., O1,O3), staying at the
+    /// last one afterwards, in place of the pipelines
+    #[structopt(long, use_delimiter = true, parse(try_from_str = OptLevel::parse))]
+    levels: Vec<OptLevel>,
+
+    /// Reuse the fixedpoint steps cached in the studio for unchanged inputs, caching the others
+    #[structopt(long)]
+    cache: bool,
+
+    /// Format of the modules serialized by the libra pass: json or cbor
+    #[structopt(long, default_value = "json", parse(try_from_str = SerialFormat::parse))]
+    serial_format: SerialFormat,
+
+    /// Producer of the serialized modules: pass, or native for loading without the libra pass at a
+    /// reduced fidelity (needs the engine built with the native feature)
+    #[structopt(long, default_value = "pass", parse(try_from_str = Loader::parse))]
+    loader: Loader,
+
+    /// Wall-clock seconds before an external tool (e.g., clang or opt) is killed
+    #[structopt(long)]
+    tool_timeout: Option<u64>,
+
+    /// Megabytes of memory an external tool may use
+    #[structopt(long)]
+    tool_memory: Option<u64>,
+
+    /// Interpretation of undef values: poison, any, or zero
+    #[structopt(short, long, default_value = "poison", parse(try_from_str = UndefSemantics::parse))]
+    undef: UndefSemantics,
+
+    /// Print the converted IR module
+    #[structopt(short, long)]
+    print: bool,
+
+    /// Emit artifacts of the converted IR module
+    #[structopt(short, long)]
+    emit: Vec<Emit>,
+
+    /// Pointer analysis behind the points-to artifacts: inclusion or unification
+    #[structopt(long, default_value = "inclusion", parse(try_from_str = AliasMode::parse))]
+    alias: AliasMode,
+
+    /// Sources, sinks, and sanitizers (in JSON) for the taint artifacts, overriding the config
+    #[structopt(long)]
+    taint_spec: Option<PathBuf>,
+
+    /// Analysis configuration (in TOML) selecting the checkers and their parameters
+    #[structopt(long)]
+    config: Option<PathBuf>,
+
+    /// Symbols to keep externally visible when linking (e.g., the exported API of a library)
+    #[structopt(long, use_delimiter = true)]
+    public_api: Vec<String>,
+
+    /// Convert each translation unit separately and link them logically instead of via llvm-link
+    #[structopt(short, long)]
+    separate: bool,
 }
 
 #[derive(StructOpt)]
@@ -46,6 +148,91 @@ enum Action {
     Build,
     /// Run fixedpoint optimization
     Fixedpoint,
+    /// Report a census of the module
+    Stats,
+    /// Compare two bitcode files structurally
+    Diff,
+}
+
+#[derive(StructOpt)]
+enum Emit {
+    /// Control-flow graphs of defined functions in DOT format
+    CfgDot,
+    /// Control-flow graphs of defined functions without the dead blocks and edges
+    CfgDotPruned,
+    /// Loads and stores claiming more alignment than their pointers have
+    Misaligned,
+    /// Textual LLVM IR lowered from the converted module, verified by opt
+    Llvm,
+    /// Verdicts on user assertions treated as proof obligations
+    Verify,
+    /// Candidate loop invariants in JSON format
+    Invariants,
+    /// Candidate loop invariants as ACSL annotations
+    InvariantsAcsl,
+    /// Points-to sets of the pointers in the module
+    PointsTo,
+    /// Null checks decided by the flow-sensitive pointer states
+    NullChecks,
+    /// Loads, stores, and GEPs through pointers that may be null
+    NullDerefs,
+    /// Per-function summaries of the nsw/nuw arithmetic that may overflow
+    Overflows,
+    /// GEPs, loads, and stores that may index past their allocations
+    OutOfBounds,
+    /// Uses and frees of heap objects that may be freed already
+    HeapMisuses,
+    /// Loads from stack slots that may not be initialized
+    UninitReads,
+    /// Tainted flows into sinks, as configured by the taint spec
+    Taint,
+    /// Blocks that never execute and branches that always go one way
+    DeadCode,
+    /// Side effects inferred for the functions
+    Effects,
+    /// Stack addresses that may outlive their functions
+    StackLeaks,
+    /// String functions that may overflow their buffers or read unterminated strings
+    StringMisuses,
+    /// Recursive functions and the call depths of the others
+    Recursion,
+    /// Findings of all checkers with severities, the taint checker only with a taint spec
+    Diagnostics,
+    /// Findings of all checkers as a SARIF log
+    Sarif,
+}
+
+impl FromStr for Emit {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let emit = match s {
+            "cfg-dot" => Self::CfgDot,
+            "cfg-dot-pruned" => Self::CfgDotPruned,
+            "misaligned" => Self::Misaligned,
+            "llvm" => Self::Llvm,
+            "verify" => Self::Verify,
+            "invariants" => Self::Invariants,
+            "invariants-acsl" => Self::InvariantsAcsl,
+            "points-to" => Self::PointsTo,
+            "null-checks" => Self::NullChecks,
+            "null-derefs" => Self::NullDerefs,
+            "overflows" => Self::Overflows,
+            "out-of-bounds" => Self::OutOfBounds,
+            "heap-misuses" => Self::HeapMisuses,
+            "uninit-reads" => Self::UninitReads,
+            "taint" => Self::Taint,
+            "dead-code" => Self::DeadCode,
+            "effects" => Self::Effects,
+            "stack-leaks" => Self::StackLeaks,
+            "string-misuses" => Self::StringMisuses,
+            "recursion" => Self::Recursion,
+            "diagnostics" => Self::Diagnostics,
+            "sarif" => Self::Sarif,
+            _ => return Err("invalid emit"),
+        };
+        Ok(emit)
+    }
 }
 
 impl FromStr for Action {
@@ -55,24 +242,199 @@ impl FromStr for Action {
         let action = match s {
             "build" => Self::Build,
             "fixedpoint" => Self::Fixedpoint,
+            "stats" => Self::Stats,
+            "diff" => Self::Diff,
             _ => return Err("invalid action"),
         };
         Ok(action)
     }
 }
 
+/// Emit the requested artifacts of a converted module
+fn emit_artifacts(
+    ctxt: &Context,
+    ir: &Module,
+    emit: &[Emit],
+    alias: AliasMode,
+    config: &AnalysisConfig,
+    output: &Path,
+) -> Result<()> {
+    let solver = config.solver(ir)?;
+    let widening = &solver.widening;
+    for item in emit {
+        match item {
+            Emit::CfgDot => {
+                for (name, func) in ir.functions() {
+                    if let Some(cfg) = &func.body {
+                        println!("// @{}", name);
+                        print!("{}", cfg.to_dot());
+                    }
+                }
+            }
+            Emit::CfgDotPruned => {
+                let intervals = analyze_intervals(ir, &solver);
+                for (name, func) in ir.functions() {
+                    if let (Some(cfg), Some(states)) = (&func.body, intervals.get(name)) {
+                        println!("// @{}", name);
+                        print!("{}", analyze_live_code(cfg, states).to_dot(cfg));
+                    }
+                }
+            }
+            Emit::Misaligned => {
+                for finding in check_misaligned_accesses(ir)? {
+                    println!("{}", finding);
+                }
+            }
+            Emit::Llvm => {
+                let text = lower_module(ir)?;
+                let path_text = output.join("lowered.ll");
+                let path_bitcode = output.join("lowered.bc");
+                fs::write(&path_text, &text)?;
+                ctxt.assemble(&path_text, &path_bitcode)?;
+                ctxt.opt_verify(&path_bitcode)?;
+                print!("{}", text);
+            }
+            Emit::Verify => {
+                for report in verify_assertions_with(ir, &config.domains, &solver)? {
+                    println!("{}", report);
+                }
+            }
+            Emit::Invariants => {
+                let items: Vec<_> = synthesize_loop_invariants(ir, widening)?
+                    .iter()
+                    .map(|inv| inv.to_json())
+                    .collect();
+                let text = serde_json::to_string_pretty(&items)?;
+                fs::write(output.join("invariants.json"), &text)?;
+                println!("{}", text);
+            }
+            Emit::InvariantsAcsl => {
+                for inv in synthesize_loop_invariants(ir, widening)? {
+                    if let Some(text) = inv.to_acsl() {
+                        println!("{}", text);
+                    }
+                }
+            }
+            Emit::PointsTo => {
+                print!("{}", analyze_aliases(ir, alias)?);
+            }
+            Emit::NullChecks => {
+                for check in validate_null_checks(ir, widening)? {
+                    println!("{}", check);
+                }
+            }
+            Emit::NullDerefs => {
+                for finding in check_null_dereferences(ir, widening)? {
+                    println!("{}", finding);
+                }
+            }
+            Emit::Overflows => {
+                for summary in check_overflows(ir, &solver)? {
+                    print!("{}", summary);
+                }
+            }
+            Emit::OutOfBounds => {
+                for finding in check_out_of_bounds(ir, &solver)? {
+                    println!("{}", finding);
+                }
+            }
+            Emit::HeapMisuses => {
+                for finding in check_heap_lifetimes(ir, &solver)? {
+                    println!("{}", finding);
+                }
+            }
+            Emit::UninitReads => {
+                for finding in check_uninitialized_reads(ir, &solver)? {
+                    println!("{}", finding);
+                }
+            }
+            Emit::Taint => {
+                let path = match &config.taint_spec {
+                    None => bail!("emitting taint flows requires a taint spec"),
+                    Some(path) => path,
+                };
+                let spec = TaintSpec::load(path)?;
+                for flow in analyze_taint(ir, &spec, widening)? {
+                    println!("{}", flow);
+                }
+            }
+            Emit::DeadCode => {
+                for finding in check_dead_code(ir, &solver)? {
+                    println!("{}", finding);
+                }
+            }
+            Emit::Effects => {
+                for (name, effect) in ir.effects() {
+                    println!("@{}: {}", name, effect);
+                }
+            }
+            Emit::StackLeaks => {
+                for finding in check_stack_leaks(ir)? {
+                    println!("{}", finding);
+                }
+            }
+            Emit::StringMisuses => {
+                for finding in check_string_misuses(ir, &solver)? {
+                    println!("{}", finding);
+                }
+            }
+            Emit::Recursion => {
+                print!("{}", RecursionReport::collect(ir));
+            }
+            Emit::Diagnostics => {
+                print!("{}", config.run(ir)?);
+            }
+            Emit::Sarif => {
+                let diagnostics = config.run(ir)?;
+                let text = serde_json::to_string_pretty(&diagnostics.to_sarif())?;
+                fs::write(output.join("diagnostics.sarif"), &text)?;
+                println!("{}", text);
+            }
+        }
+    }
+    Ok(())
+}
+
 fn main() -> Result<()> {
     // setup
     let args = Args::from_args();
     let Args {
         keep,
+        debug_artifacts,
         mut actions,
         inputs,
+        target,
         flags,
+        profile,
+        compile_db,
         depth,
+        pipeline,
+        levels,
+        cache,
+        serial_format,
+        loader,
+        tool_timeout,
+        tool_memory,
+        undef,
+        print,
+        emit,
+        alias,
+        taint_spec,
+        config,
+        public_api,
+        separate,
     } = args;
     initialize();
 
+    // settle the analysis configuration
+    let mut config = match config {
+        None => AnalysisConfig::default(),
+        Some(path) => AnalysisConfig::load(&path)?,
+    };
+    if taint_spec.is_some() {
+        config.taint_spec = taint_spec;
+    }
+
     // decide on the workspace
     let (temp, output) = if keep {
         let path = PATH_STUDIO.join("libra");
@@ -88,7 +450,133 @@ fn main() -> Result<()> {
     };
 
     // run the workflow
-    let ctxt = Context::new()?;
+    let mut ctxt = Context::new()?;
+    ctxt.set_undef_semantics(undef);
+    ctxt.set_serial_format(serial_format);
+    ctxt.set_loader(loader);
+    ctxt.set_debug_artifacts(keep || debug_artifacts);
+    ctxt.set_tool_limits(ToolLimits {
+        timeout: tool_timeout.map(Duration::from_secs),
+        memory: tool_memory.map(|megabytes| megabytes << 20),
+    });
+
+    // alternative workflow: compare two bitcode files
+    if actions.iter().any(|a| matches!(a, Action::Diff)) {
+        if actions.len() != 1 || inputs.len() != 2 {
+            bail!("the diff action expects two bitcode files and no other actions");
+        }
+        let old = ctxt.load(&inputs[0])?;
+        let new = ctxt.load(&inputs[1])?;
+        let delta = ModuleDiff::compute(&old, &new);
+        print!("{}", delta);
+        info!("{}", delta.summary());
+
+        // drop temp dir explicitly
+        if let Some(dir) = temp {
+            dir.close()?;
+        }
+        return Ok(());
+    }
+
+    // alternative workflow: keep translation units as separate modules
+    if separate {
+        if actions.iter().any(|a| !matches!(a, Action::Build)) {
+            bail!("only the build action is allowed when converting translation units separately");
+        }
+        let mut flow = FlowBuildSimple::new(&ctxt, inputs, output.clone(), flags);
+        flow.set_profile(profile);
+        if let Some(path) = compile_db {
+            flow.set_compile_db(path);
+        }
+        let units = flow.execute_separate()?;
+        let program = ctxt.load_program(&units)?;
+        info!(
+            "Translation units linked logically: {}",
+            program.modules().len()
+        );
+        if print {
+            println!("{}", program);
+        }
+        for module in program.modules().values() {
+            emit_artifacts(&ctxt, module, &emit, alias, &config, &output)?;
+        }
+
+        // drop temp dir explicitly
+        if let Some(dir) = temp {
+            dir.close()?;
+        }
+        return Ok(());
+    }
+
+    // alternative workflow: analyze independent targets
+    if !target.is_empty() {
+        if !inputs.is_empty() {
+            bail!("inputs are not expected along with targets");
+        }
+        if compile_db.is_some() {
+            bail!("a compilation database is not expected along with targets");
+        }
+        if actions
+            .iter()
+            .any(|a| !matches!(a, Action::Build | Action::Fixedpoint))
+        {
+            bail!("only the build and fixedpoint actions are allowed on targets");
+        }
+        let fixedpoint = actions.iter().any(|a| matches!(a, Action::Fixedpoint));
+        let mut flow = FlowMultiTarget::new(&ctxt, target, output.clone(), flags, depth);
+        flow.set_profile(profile);
+        if !public_api.is_empty() {
+            flow.set_public_api(public_api);
+        }
+        flow.set_fixedpoint(fixedpoint);
+        if !levels.is_empty() {
+            if !pipeline.is_empty() {
+                bail!("optimization levels and pipelines are exclusive");
+            }
+            flow.set_schedule(Schedule::levels(&levels));
+        } else if !pipeline.is_empty() {
+            flow.set_schedule(Schedule::round_robin(pipeline));
+        }
+        if cache {
+            flow.set_cache(FixedpointCache::open(&ctxt)?);
+        }
+        let results = flow.execute()?;
+        let mut failed = vec![];
+        for (name, result) in results {
+            let trace = match result {
+                Ok(trace) => trace,
+                Err(e) => {
+                    error!("target {} failed: {}", name, e);
+                    failed.push(name);
+                    continue;
+                }
+            };
+            if fixedpoint {
+                info!(
+                    "Number of fixedpoint optimization rounds for {}: {}",
+                    name,
+                    trace.len()
+                );
+            }
+            let ir = match trace.last() {
+                None => bail!("fixedpoint optimization leaves no modules in trace"),
+                Some(ir) => ir,
+            };
+            if print {
+                println!("{}", ir);
+            }
+            emit_artifacts(&ctxt, ir, &emit, alias, &config, &output.join(&name))?;
+        }
+
+        // drop temp dir explicitly
+        if let Some(dir) = temp {
+            dir.close()?;
+        }
+        if !failed.is_empty() {
+            bail!("failed targets: {}", failed.join(", "));
+        }
+        return Ok(());
+    }
 
     // phase 1: see if anything to build
     let path_base_bitcode = match actions.iter().position(|a| matches!(a, Action::Build)) {
@@ -101,7 +589,15 @@ fn main() -> Result<()> {
         Some(index) => {
             let path_output = match actions.remove(index) {
                 Action::Build => {
-                    FlowBuildSimple::new(&ctxt, inputs, output.clone(), flags).execute()?
+                    let mut flow = FlowBuildSimple::new(&ctxt, inputs, output.clone(), flags);
+                    flow.set_profile(profile);
+                    if let Some(path) = compile_db {
+                        flow.set_compile_db(path);
+                    }
+                    if !public_api.is_empty() {
+                        flow.set_public_api(public_api);
+                    }
+                    flow.execute()?
                 }
                 _ => unreachable!(),
             };
@@ -114,13 +610,32 @@ fn main() -> Result<()> {
         }
     };
 
-    // phase 2: any optimizations to run
-    let _ir = match actions.iter().position(|a| matches!(a, Action::Fixedpoint)) {
+    // phase 2: any census to report
+    let stats = actions.iter().any(|a| matches!(a, Action::Stats));
+    if stats {
+        let census = ctxt.census(&path_base_bitcode)?;
+        println!("{}", census);
+        actions.retain(|a| !matches!(a, Action::Stats));
+    }
+
+    // phase 3: any optimizations to run
+    let ir = match actions.iter().position(|a| matches!(a, Action::Fixedpoint)) {
         None => ctxt.load(&path_base_bitcode)?,
         Some(index) => match actions.remove(index) {
             Action::Fixedpoint => {
-                let trace =
-                    FlowFixedpoint::new(&ctxt, path_base_bitcode, output, depth).execute()?;
+                let mut flow = FlowFixedpoint::new(&ctxt, path_base_bitcode, output.clone(), depth);
+                if !levels.is_empty() {
+                    if !pipeline.is_empty() {
+                        bail!("optimization levels and pipelines are exclusive");
+                    }
+                    flow.set_schedule(Schedule::levels(&levels));
+                } else if !pipeline.is_empty() {
+                    flow.set_schedule(Schedule::round_robin(pipeline));
+                }
+                if cache {
+                    flow.set_cache(FixedpointCache::open(&ctxt)?);
+                }
+                let trace = flow.execute()?;
                 if trace.is_empty() {
                     bail!("fixedpoint optimization leaves no modules in trace");
                 }
@@ -131,6 +646,12 @@ fn main() -> Result<()> {
         },
     };
 
+    // phase 4: dump the converted module if requested
+    if print {
+        println!("{}", ir);
+    }
+    emit_artifacts(&ctxt, &ir, &emit, alias, &config, &output)?;
+
     // drop temp dir explicitly
     match temp {
         None => (),
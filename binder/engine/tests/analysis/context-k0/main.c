@@ -0,0 +1,14 @@
+#include <libra_assume.h>
+
+// not inlined, so both calls are kept and only the context tells their arguments apart; the
+// result is not the argument itself, which the compiler would forward to the callers
+__attribute__((noinline)) int inc(int v) {
+  return v + 1;
+}
+
+int main(void) {
+  int a = inc(1);
+  int b = inc(100);
+  LIBRA_ASSERT(a <= 2);
+  return a + b;
+}
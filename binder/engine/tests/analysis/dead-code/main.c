@@ -0,0 +1,20 @@
+void side(void);
+
+int input(void);
+
+// not inlined, and the loop keeps the compiler from bounding the value returned, which the
+// summary still bounds from below
+__attribute__((noinline)) int get(void) {
+  int i = 1;
+  while (i < 100 && input()) {
+    i += input() ? 1 : 3;
+  }
+  return i;
+}
+
+void foo(void) {
+  // the summary of the callee decides the branch, which the compiler cannot fold
+  if (get() > 0) {
+    side();
+  }
+}
@@ -0,0 +1,11 @@
+void free(void *ptr);
+
+// not inlined, so the free is only known to the caller from the summary of the callee
+__attribute__((noinline)) void release(int *p) {
+  free(p);
+}
+
+void foo(int *p) {
+  release(p);
+  free(p);
+}
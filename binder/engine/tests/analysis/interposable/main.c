@@ -0,0 +1,17 @@
+#include <libra_assume.h>
+
+int input(void);
+
+// the linker may pick another definition, so the body says nothing about the value returned
+__attribute__((weak)) int status(int v) {
+  if (v > 0) {
+    return 5;
+  }
+  return 12;
+}
+
+int main(void) {
+  int r = status(input());
+  LIBRA_ASSERT(r >= 5);
+  return r;
+}
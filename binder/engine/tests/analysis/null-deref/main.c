@@ -0,0 +1,15 @@
+char *strchr(const char *s, int c);
+
+int foo(const char *s) {
+  // the model of the library function returns null when the character is absent
+  char *p = strchr(s, 'a');
+  return *p;
+}
+
+int bar(const char *s) {
+  // arguments are assumed non-null, which makes the check redundant
+  if (!s) {
+    return -1;
+  }
+  return *s;
+}
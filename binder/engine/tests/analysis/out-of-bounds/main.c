@@ -0,0 +1,13 @@
+int table[4] = {1, 2, 3, 4};
+
+int input(void);
+
+// not inlined, so the call is kept and the value returned is only known from the summary
+__attribute__((noinline)) int pick(int v) {
+  return v > 0 ? 6 : 9;
+}
+
+int foo(void) {
+  // every index from the summary of the callee is past the end of the array
+  return table[pick(input())];
+}
@@ -0,0 +1,11 @@
+int input(void);
+
+// not inlined, so the call is kept and the value returned is only known from the summary
+__attribute__((noinline)) int big(int v) {
+  return v > 0 ? 2147483647 : 2147483640;
+}
+
+int foo(void) {
+  // the signed addition on the largest ints wraps on some executions
+  return big(input()) + 4;
+}
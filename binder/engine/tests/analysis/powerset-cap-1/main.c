@@ -0,0 +1,11 @@
+#include <libra_assume.h>
+
+// not inlined, so both calls are kept and each context passes a single argument
+__attribute__((noinline)) int check(int v) {
+  LIBRA_ASSERT(v != 50);
+  return v;
+}
+
+int main(void) {
+  return check(1) + check(100);
+}
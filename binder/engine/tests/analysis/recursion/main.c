@@ -0,0 +1,27 @@
+#include <libra_assume.h>
+
+int input(void);
+
+__attribute__((noinline)) int odd(int n);
+
+__attribute__((noinline)) int even(int n) {
+  if (n == 0) {
+    return 1;
+  }
+  return odd(n - 1);
+}
+
+__attribute__((noinline)) int odd(int n) {
+  if (n == 0) {
+    return 0;
+  }
+  return even(n - 1);
+}
+
+int main(void) {
+  // the summaries of the recursion are iterated to a fixedpoint, where both 0 and 1 return
+  int r = even(input());
+  LIBRA_ASSERT(r != 2);
+  LIBRA_ASSERT(r == 1);
+  return r;
+}
@@ -0,0 +1,20 @@
+#include <libra_assume.h>
+
+int input(void);
+
+// not inlined, so the call is kept and the value returned is only known from the summary
+__attribute__((noinline)) int level(int v) {
+  if (v) {
+    return 20;
+  }
+  return 0;
+}
+
+int main(void) {
+  int r = level(input());
+  if (r > 5) {
+    // the summary bounds r from above and the branch from below
+    LIBRA_ASSERT((unsigned)(r - 6) <= 14);
+  }
+  return r;
+}
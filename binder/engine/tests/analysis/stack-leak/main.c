@@ -0,0 +1,7 @@
+int *saved;
+
+void keep(void) {
+  int x = 0;
+  // the address of the local outlives the call through the global
+  saved = &x;
+}
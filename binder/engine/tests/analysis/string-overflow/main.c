@@ -0,0 +1,10 @@
+typedef struct FILE FILE;
+
+char *fgets(char *s, int n, FILE *stream);
+
+char buf[4];
+
+void foo(FILE *stream) {
+  // the bound passed is larger than the buffer
+  fgets(buf, 16, stream);
+}
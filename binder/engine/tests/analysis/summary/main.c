@@ -0,0 +1,17 @@
+#include <libra_assume.h>
+
+int input(void);
+
+// not inlined, so the call is kept and the value returned is only known from the summary
+__attribute__((noinline)) int status(int v) {
+  if (v > 0) {
+    return 5;
+  }
+  return 12;
+}
+
+int main(void) {
+  int r = status(input());
+  LIBRA_ASSERT(r >= 5);
+  return r;
+}
@@ -0,0 +1,13 @@
+int read_input(void);
+int sanitize(int v);
+void exec_cmd(int v);
+
+void foo(void) {
+  int v = read_input();
+  exec_cmd(v + 1);
+}
+
+void bar(void) {
+  // the sanitizer cleans the value before it reaches the sink
+  exec_cmd(sanitize(read_input()));
+}
@@ -0,0 +1,5 @@
+int foo(void) {
+  // volatile, so the slot and the read of it survive the optimizations
+  volatile int x;
+  return x;
+}
@@ -0,0 +1,15 @@
+int input(void);
+
+__attribute__((noinline)) int big(int v) {
+  return v > 0 ? 2147483647 : 2147483640;
+}
+
+int foo(void) {
+  // the call is unconstrained to the executor, which finds the value that wraps
+  return big(input()) + 4;
+}
+
+int bar(int x) {
+  // the intervals lose the relation between x and its half, the executor keeps it
+  return x - (x >> 1);
+}
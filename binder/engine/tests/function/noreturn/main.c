@@ -0,0 +1,20 @@
+#include <libra_assume.h>
+
+int input(void);
+
+__attribute__((noreturn)) void fail(void);
+
+__attribute__((weak)) int foo(int a) {
+  int r = 1;
+  if (a < 0) {
+    fail();
+    // the block ends at the call, so this value never reaches the merge
+    r = 2;
+  }
+  LIBRA_ASSERT(r == 1);
+  return r;
+}
+
+int main(void) {
+  return foo(input());
+}
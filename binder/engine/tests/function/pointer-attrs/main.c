@@ -0,0 +1,22 @@
+struct S {
+  int a[16];
+};
+
+struct S make(int *restrict p) {
+  struct S s = {0};
+  s.a[0] = *p;
+  return s;
+}
+
+int take(struct S s) {
+  return s.a[0];
+}
+
+int foo(int v) {
+  return take(make(&v));
+}
+
+long long peek(long long *p __attribute__((align_value(16)))) {
+  // four bytes past a 16-aligned argument, the load cannot be 8-aligned
+  return *(long long *)((char *)p + 4);
+}
@@ -0,0 +1,15 @@
+#include <libra_assume.h>
+
+int foo(int *buf, int len) {
+  // the assumption reaches the analyses through the extension of its condition
+  LIBRA_ASSUME(len == 4);
+
+  int sum = 0;
+  for (int i = 0; i < len; i++) {
+    LIBRA_INVARIANT(i >= 0);
+    sum += buf[i];
+  }
+
+  LIBRA_ASSERT(len != 0);
+  return sum;
+}
@@ -0,0 +1,21 @@
+struct __attribute__((packed)) S {
+  char c;
+  int i;
+};
+
+int foo(int v) {
+  _Alignas(16) int a = v;
+  struct S s;
+  s.c = 0;
+  s.i = a;
+  return s.i;
+}
+
+void fill(char *buf);
+
+int bar(void) {
+  _Alignas(16) char buf[8];
+  fill(buf);
+  // one byte past a 16-aligned slot, the int cannot be 4-aligned
+  return *(int *)(buf + 1);
+}
@@ -0,0 +1,22 @@
+#include <libra_assume.h>
+
+volatile long g;
+long h;
+
+long foo(long v) {
+  volatile long a = v;
+  g = a;
+
+  long *ptr_g = (long *)&g;
+  *ptr_g = a;
+
+  return g;
+}
+
+void bar(void) {
+  h = 5;
+  LIBRA_ASSERT(h == 5);
+  // a volatile load is not forwarded from the store before it
+  g = 5;
+  LIBRA_ASSERT(g == 5);
+}
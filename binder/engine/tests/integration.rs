@@ -7,10 +7,11 @@ use datatest_stable::{harness, Result};
 use fs_extra::dir::CopyOptions;
 use tempfile::tempdir;
 
+use libra_engine::analysis::config::AnalysisConfig;
 use libra_engine::error::EngineResult;
 use libra_engine::flow::build_simple::FlowBuildSimple;
 use libra_engine::flow::fixedpoint::FlowFixedpoint;
-use libra_engine::flow::shared::Context;
+use libra_engine::flow::shared::{Context, Loader};
 use libra_engine::ir::bridge;
 
 #[derive(Copy, Clone)]
@@ -44,9 +45,36 @@ fn workflow(
     flow_fixedpoint.execute()
 }
 
+/// Run the checkers configured for the test case on the optimized module, rendering each
+/// diagnostic without its site, which depends on the block numbering of the compiler
+fn diagnose(path_config: &Path, trace: &[bridge::module::Module]) -> EngineResult<String> {
+    let config = AnalysisConfig::load(path_config)?;
+    let module = trace
+        .last()
+        .expect("the trace has at least the baseline module");
+    let diagnostics = config.run(module)?;
+    let mut text = String::new();
+    for item in diagnostics.iter() {
+        text.push_str(&format!(
+            "{}[{}] @{}: {}\n",
+            item.severity, item.checker, item.function, item.message
+        ));
+    }
+    Ok(text)
+}
+
+/// Whether the engine is built with an optional feature, by its name in the manifest
+fn feature_enabled(name: &str) -> bool {
+    match name {
+        "smt" => cfg!(feature = "smt"),
+        "native" => cfg!(feature = "native"),
+        _ => panic!("unknown feature {}", name),
+    }
+}
+
 fn run_test(path_output: &Path) -> Result<()> {
     // ready context
-    let ctxt = Context::new()?;
+    let mut ctxt = Context::new()?;
 
     // config based on environment variable
     let keep = env::var("KEEP").map_or(false, |v| v == "1");
@@ -67,6 +95,25 @@ fn run_test(path_output: &Path) -> Result<()> {
         .parent()
         .expect("unable to locate the test case directory");
 
+    // a test case listing the optional features it needs is skipped without them
+    let path_features = path_dir.join("features");
+    let features = if path_features.exists() {
+        fs::read_to_string(&path_features)
+            .expect("unable to load content from the features file")
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect()
+    } else {
+        vec![]
+    };
+    if !features.iter().all(|name| feature_enabled(name)) {
+        return Ok(());
+    }
+    if features.iter().any(|name| name == "native") {
+        ctxt.set_loader(Loader::Native);
+    }
+
     let path_artifact = path_dir.join("testing");
     if path_artifact.exists() {
         fs::remove_dir_all(&path_artifact)?;
@@ -84,7 +131,23 @@ fn run_test(path_output: &Path) -> Result<()> {
 
     // create output dir
     let temp = tempdir().expect("unable to create a temporary directory");
+    let path_config = path_dir.join("libra.toml");
     let success = match workflow(&ctxt, inputs, temp.path().to_path_buf()) {
+        // a test case with a config expects the diagnostics of the checkers
+        Ok(trace) if path_config.exists() => match diagnose(&path_config, &trace) {
+            Ok(obtained) if obtained == expected => true,
+            Ok(obtained) => {
+                println!(
+                    "Diagnostics mismatch:\n{}\n<- expected vs obtained ->\n{}",
+                    expected, obtained
+                );
+                false
+            }
+            Err(err) => {
+                println!("Checkers failed while diagnostics are expected:\n{}", err);
+                false
+            }
+        },
         Ok(trace) => {
             if expected.is_empty() {
                 if matches!(verbosity, Verbosity::Verbose | Verbosity::Extensive) {
@@ -0,0 +1,64 @@
+use rug::Integer;
+
+use libra_engine::analysis::interval::Interval;
+use libra_engine::analysis::lattice::Lattice;
+use libra_engine::analysis::powerset::{JoinAll, JoinOverflow, Powerset};
+
+fn range(lo: i64, hi: i64) -> Interval {
+    Interval::finite(Integer::from(lo), Integer::from(hi))
+}
+
+#[test]
+fn join_retains_disjuncts() {
+    // a pointer offset that is either null or within the object
+    let null = Powerset::<Interval, JoinAll<2>>::singleton(range(0, 0));
+    let valid = Powerset::singleton(range(16, 32));
+    let either = null.join(&valid);
+    assert_eq!(either.disjuncts().len(), 2);
+    assert!(!either
+        .disjuncts()
+        .iter()
+        .any(|d| d.contains(&Integer::from(8))));
+
+    let checked = either.meet(&Powerset::singleton(range(1, 100)));
+    assert!(checked == valid);
+}
+
+#[test]
+fn subsumed_disjuncts_are_dropped() {
+    let set = Powerset::<Interval, JoinAll<4>>::new(vec![
+        range(0, 4),
+        Interval::bottom(),
+        range(1, 2),
+        range(10, 12),
+    ]);
+    assert_eq!(set.disjuncts().len(), 2);
+    assert!(set == Powerset::new(vec![range(10, 12), range(0, 4)]));
+}
+
+#[test]
+fn overflow_collapses_all() {
+    let set = Powerset::<Interval, JoinAll<2>>::new(vec![range(0, 0), range(4, 4), range(8, 8)]);
+    assert!(set.disjuncts() == [range(0, 8)]);
+}
+
+#[test]
+fn overflow_collapses_the_tail() {
+    let set =
+        Powerset::<Interval, JoinOverflow<2>>::new(vec![range(0, 0), range(4, 4), range(8, 8)]);
+    assert!(set.disjuncts() == [range(0, 0), range(4, 8)]);
+}
+
+#[test]
+fn widening_stabilizes() {
+    let mut state = Powerset::<Interval, JoinAll<8>>::singleton(range(0, 0));
+    for i in 1..100 {
+        let next = state.join(&Powerset::singleton(range(i, i)));
+        let widened = state.widen(&next);
+        if widened == state {
+            break;
+        }
+        state = widened;
+    }
+    assert!(state.disjuncts().len() == 1 && state.merge().contains(&Integer::from(1000)));
+}
@@ -0,0 +1,62 @@
+use rug::Integer;
+
+use libra_engine::analysis::congruence::{Congruence, IntervalCongruenceReduction};
+use libra_engine::analysis::interval::Interval;
+use libra_engine::analysis::lattice::Lattice;
+use libra_engine::analysis::product::{NoReduction, ReducedProduct};
+
+type Reduced = ReducedProduct<Interval, Congruence, IntervalCongruenceReduction>;
+type Plain = ReducedProduct<Interval, Congruence, NoReduction>;
+
+fn range(lo: i64, hi: i64) -> Interval {
+    Interval::finite(Integer::from(lo), Integer::from(hi))
+}
+
+fn class(modulus: i64, residue: i64) -> Congruence {
+    Congruence::new(Integer::from(modulus), Integer::from(residue))
+}
+
+#[test]
+fn reduction_tightens_bounds() {
+    let plain = Plain::new(range(1, 10), class(4, 0));
+    assert!(plain.first == range(1, 10));
+
+    let reduced = Reduced::new(range(1, 10), class(4, 0));
+    assert!(reduced.first == range(4, 8));
+    assert!(reduced.second == class(4, 0));
+}
+
+#[test]
+fn reduction_detects_emptiness() {
+    let plain =
+        Plain::new(range(0, 8), class(4, 0)).meet(&Plain::new(range(1, 3), Congruence::top()));
+    assert!(!plain.first.is_bottom());
+
+    let reduced =
+        Reduced::new(range(0, 8), class(4, 0)).meet(&Reduced::new(range(1, 3), Congruence::top()));
+    assert!(reduced.is_bottom());
+    assert!(reduced.first.is_bottom() && reduced.second.is_bottom());
+}
+
+#[test]
+fn reduction_collapses_singletons() {
+    let plain = Plain::new(range(5, 7), class(4, 1));
+    assert!(plain.second == class(4, 1));
+
+    let reduced = Reduced::new(range(5, 7), class(4, 1));
+    assert!(reduced.first == range(5, 5));
+    assert!(reduced.second == Congruence::singleton(Integer::from(5)));
+}
+
+#[test]
+fn join_keeps_reduced_precision() {
+    let lhs = Reduced::new(range(0, 0), Congruence::top());
+    let rhs = Reduced::new(range(6, 6), Congruence::top());
+    let joined = lhs.join(&rhs);
+    assert!(joined.first == range(0, 6));
+    assert!(joined.second == class(6, 0));
+
+    let narrowed = joined.meet(&Reduced::new(range(1, 100), Congruence::top()));
+    assert!(narrowed.first == range(6, 6));
+    assert!(Reduced::bottom().join(&lhs) == lhs);
+}
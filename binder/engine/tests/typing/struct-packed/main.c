@@ -0,0 +1,22 @@
+struct S {
+  char c;
+  int i;
+} __attribute__((packed));
+
+struct T {
+  char c;
+  struct S s;
+  long l;
+};
+
+struct T t;
+
+void fill(struct T *t);
+
+int bar(void) {
+  struct T u;
+  fill(&u);
+  // the packed member sits at offset 2 of an 8-aligned slot
+  int *p = &u.s.i;
+  return *p;
+}
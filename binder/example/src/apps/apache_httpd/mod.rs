@@ -1,54 +1,57 @@
-use std::path::Path;
 use std::process::Command;
 
 use anyhow::{anyhow, bail, Result};
 use serde::{Deserialize, Serialize};
 
-use crate::common::AppConfig;
+use crate::common::{WorkflowConfig, Workspace};
 use crate::snippet;
-use crate::workflow::retrieve_workflow;
 
 /// Workflow configuration
 #[derive(Serialize, Deserialize)]
 pub struct Config {}
 
-impl AppConfig for Config {
+impl WorkflowConfig for Config {
     fn app() -> &'static str {
         "apache_httpd"
     }
 
-    fn build(&self, path_src: &Path, path_bin: &Path) -> Result<()> {
+    fn build(&self, workspace: &Workspace) -> Result<()> {
+        let Workspace { src, bin, deps, .. } = workspace;
+
         // grab dependencies
-        let (dep_pcre2, _) = retrieve_workflow::<crate::apps::pcre2::Config>("default")?;
+        let dep_pcre2 = deps
+            .get(crate::apps::pcre2::Config::app())
+            .ok_or_else(|| anyhow!("missing dependency on pcre2"))?;
 
-        snippet::git_clone(path_src, "https://github.com/apache/httpd.git")?;
+        snippet::git_clone(src, "https://github.com/apache/httpd.git")?;
         snippet::svn_clone(
-            &path_src.join("srclib").join("apr"),
+            &src.join("srclib").join("apr"),
             "https://svn.apache.org/repos/asf/apr/apr/trunk/",
         )?;
         snippet::svn_clone(
-            &path_src.join("srclib").join("apr-util"),
+            &src.join("srclib").join("apr-util"),
             "https://svn.apache.org/repos/asf/apr/apr-util/trunk/",
         )?;
 
         // special configuration step for apache httpd
         let mut cmd = Command::new("./buildconf");
-        cmd.current_dir(path_src);
+        cmd.current_dir(src);
         if !cmd.status()?.success() {
             bail!("unable to buildconf");
         }
 
         // resume normal autoconf procedure
         snippet::build_via_autoconf(
-            path_src,
-            path_bin,
+            src,
+            bin,
             None,
             &[
                 "--with-included-apr",
                 &format!(
                     "--with-pcre={}",
                     dep_pcre2
-                        .join("bin")
+                        .bin
+                        .clone()
                         .into_os_string()
                         .into_string()
                         .map_err(|_| anyhow!("non-ascii path"))?
@@ -1,4 +1,20 @@
+use anyhow::{bail, Result};
+
+use crate::common::WorkflowConfig;
+use crate::workflow::execute_one;
+
 pub mod apache_httpd;
 pub mod libxml2;
 pub mod pcre2;
 pub mod zlib;
+
+/// Run a workflow of an app identified by name
+pub fn dispatch(app: &str, name: &str, force: bool) -> Result<()> {
+    match app {
+        _ if app == apache_httpd::Config::app() => execute_one::<apache_httpd::Config>(name, force),
+        _ if app == libxml2::Config::app() => execute_one::<libxml2::Config>(name, force),
+        _ if app == pcre2::Config::app() => execute_one::<pcre2::Config>(name, force),
+        _ if app == zlib::Config::app() => execute_one::<zlib::Config>(name, force),
+        _ => bail!("unknown app: {}", app),
+    }
+}
@@ -1,22 +1,22 @@
-use std::path::Path;
-
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
-use crate::common::AppConfig;
+use crate::common::{WorkflowConfig, Workspace};
 use crate::snippet;
 
 /// Workflow configuration
 #[derive(Serialize, Deserialize)]
 pub struct Config {}
 
-impl AppConfig for Config {
+impl WorkflowConfig for Config {
     fn app() -> &'static str {
         "pcre2"
     }
 
-    fn build(&self, path_src: &Path, path_bin: &Path) -> Result<()> {
-        snippet::git_clone(path_src, "https://github.com/PCRE2Project/pcre2.git")?;
-        snippet::build_via_autoconf(path_src, path_bin, Some(&[]), &[])
+    fn build(&self, workspace: &Workspace) -> Result<()> {
+        let Workspace { src, bin, .. } = workspace;
+
+        snippet::git_clone(src, "https://github.com/PCRE2Project/pcre2.git")?;
+        snippet::build_via_autoconf(src, bin, Some(&[]), &[])
     }
 }
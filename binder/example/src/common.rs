@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use anyhow::Result;
@@ -27,13 +28,39 @@ lazy_static! {
 /// Extension for LLVM IR (bitcode) file
 pub static BITCODE_EXTENSION: &str = "bc";
 
+/// Directory layout of a workflow
+pub struct Workspace {
+    /// root of the workflow, hosting the stage marks
+    pub root: PathBuf,
+    /// source code (also the build tree)
+    pub src: PathBuf,
+    /// installation prefix
+    pub bin: PathBuf,
+    /// workspace for analysis
+    pub wks: PathBuf,
+    /// workspaces of the dependencies, keyed by app name
+    pub deps: BTreeMap<String, Workspace>,
+}
+
+impl Workspace {
+    pub fn new(workdir: &Path) -> Self {
+        Self {
+            root: workdir.to_path_buf(),
+            src: workdir.join("src"),
+            bin: workdir.join("bin"),
+            wks: workdir.join("wks"),
+            deps: BTreeMap::new(),
+        }
+    }
+}
+
 /// Common trait for workflow config
-pub trait AppConfig: Serialize + DeserializeOwned {
+pub trait WorkflowConfig: Serialize + DeserializeOwned {
     /// Obtain the application name
     fn app() -> &'static str;
 
-    /// Build process
-    fn build(&self, path_src: &Path, path_bin: &Path) -> Result<()>;
+    /// Build process, which fetches the source code into `src` and installs into `bin`
+    fn build(&self, workspace: &Workspace) -> Result<()>;
 }
 
 /// Utility: Derive the filename for bitcode based on an existing file
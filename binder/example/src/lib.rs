@@ -3,7 +3,7 @@ use structopt::StructOpt;
 
 use libra_shared::config::initialize;
 
-use crate::workflow::execute;
+use crate::workflow::{execute, execute_with_deps};
 
 pub mod proxy;
 
@@ -31,20 +31,29 @@ struct Args {
     /// Example
     #[structopt(subcommand)]
     example: Example,
+
+    /// Build outputs (by file name) to analyze when the config defines no entrypoint
+    #[structopt(short, long)]
+    select: Vec<String>,
+}
+
+/// Run a workflow of an example app end-to-end, with its dependencies built first
+pub fn run_workflow(app: &str, name: &str, force: bool) -> Result<()> {
+    execute_with_deps(app, name, force)
 }
 
 /// Main entrypoint
 pub fn entrypoint() -> Result<()> {
     // setup
     let args = Args::from_args();
-    let Args { example } = args;
+    let Args { example, select } = args;
     initialize();
 
     // run the subcommand
     match example {
-        Example::ApacheHttpd => execute::<apps::apache_httpd::Config>(),
-        Example::LibXML2 => execute::<apps::libxml2::Config>(),
-        Example::PCRE2 => execute::<apps::pcre2::Config>(),
-        Example::ZLib => execute::<apps::zlib::Config>(),
+        Example::ApacheHttpd => execute::<apps::apache_httpd::Config>(&select),
+        Example::LibXML2 => execute::<apps::libxml2::Config>(&select),
+        Example::PCRE2 => execute::<apps::pcre2::Config>(&select),
+        Example::ZLib => execute::<apps::zlib::Config>(&select),
     }
 }
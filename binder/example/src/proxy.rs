@@ -1,7 +1,8 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::{env, fmt, fs, process};
 
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
 use libra_engine::flow::shared::Context;
@@ -360,6 +361,35 @@ impl fmt::Display for ClangInvocation {
     }
 }
 
+/// Manifest recorded in a library mark
+#[derive(Serialize, Deserialize)]
+pub struct LibraryMark {
+    /// the real library artifact
+    pub artifact: PathBuf,
+    /// bitcode files of the members linked into the library
+    pub members: Vec<PathBuf>,
+    /// flags used to build the library
+    pub flags: Vec<String>,
+    /// shared object name, if any
+    pub soname: Option<String>,
+}
+
+impl LibraryMark {
+    /// Load the manifest from a mark file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let mark = serde_json::from_str(&content)?;
+        Ok(mark)
+    }
+
+    /// Save the manifest into a mark file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+}
+
 /// Wrap a clang tool
 pub fn proxy_clang(cxx: bool) {
     // get paths
@@ -1,13 +1,12 @@
 use std::fs;
-use std::os::unix::fs::symlink;
 use std::path::Path;
 use std::process::Command;
 
 use anyhow::{anyhow, bail, Result};
 use walkdir::WalkDir;
 
-use crate::common::{CLANG_CPP_WRAP, CLANG_WRAP};
-use crate::proxy::{COMMAND_EXTENSION, LIBMARK_EXTENSION};
+use crate::common::{derive_bitcode_path, CLANG_CPP_WRAP, CLANG_WRAP};
+use crate::proxy::{ClangArg, ClangInvocation, LibraryMark, COMMAND_EXTENSION, LIBMARK_EXTENSION};
 
 /// Git clone
 pub fn git_clone(path_src: &Path, repo: &str) -> Result<()> {
@@ -140,20 +139,46 @@ pub fn mark_artifact_lib<P: AsRef<Path>, Q: AsRef<Path>>(
         if target.is_some() {
             bail!("more than one library target for {}", name);
         }
-        let mut path = entry.path();
+        let command = entry.path();
+        let mut path = command.clone();
         path.pop();
-        target = Some(path.join(original));
+        target = Some((path.join(original), command));
     }
-    let src = match target {
+    let (src, command) = match target {
         None => bail!("no target to mark for library {}", name),
-        Some(path) => path,
+        Some(item) => item,
     };
 
-    // create the symbolic link
+    // collect details from the build command
+    let content = fs::read_to_string(command)?;
+    let invocation: ClangInvocation = serde_json::from_str(&content)?;
+    let mut members = vec![];
+    let mut flags = vec![];
+    let mut soname = None;
+    for arg in &invocation.args {
+        match arg {
+            ClangArg::Input(item) => {
+                members.push(derive_bitcode_path(invocation.cwd.join(item)));
+            }
+            ClangArg::LinkSoname(item) => {
+                soname = Some(item.to_string());
+            }
+            ClangArg::Output(..) => (),
+            _ => flags.extend(arg.as_args()),
+        }
+    }
+
+    // write the manifest
+    let mark = LibraryMark {
+        artifact: src.canonicalize()?,
+        members,
+        flags,
+        soname,
+    };
     let dst = path_install
         .as_ref()
         .join(format!("lib{}{}", name, LIBMARK_EXTENSION));
-    symlink(src, dst)?;
+    mark.save(&dst)?;
 
     // done
     Ok(())
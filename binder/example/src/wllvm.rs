@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
 use std::path::{Component, Path, PathBuf};
 use std::process::Command;
 use std::{fs, io};
@@ -7,14 +8,89 @@ use anyhow::{bail, Result};
 use libra_engine::flow::shared::Context;
 use log::debug;
 use petgraph::algo::toposort;
-use petgraph::dot::{Config, Dot};
+use petgraph::dot::Dot;
 use petgraph::graph::DiGraph;
+use petgraph::visit::EdgeRef;
+use serde::Serialize;
 use walkdir::WalkDir;
 
 use crate::common::derive_bitcode_path;
-use crate::proxy::{ClangArg, ClangInvocation, COMMAND_EXTENSION, LIBMARK_EXTENSION};
+use crate::proxy::{ClangArg, ClangInvocation, LibraryMark, COMMAND_EXTENSION, LIBMARK_EXTENSION};
 
 static COMPILE_GRAPH_DOT: &str = ".compile_graph.dot";
+static COMPILE_GRAPH_JSON: &str = ".compile_graph.json";
+static BUILD_OUTPUTS_JSON: &str = ".build_outputs.json";
+
+/// Format to export the compilation graph in
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Copy)]
+pub enum GraphFormat {
+    Dot,
+    Json,
+}
+
+impl GraphFormat {
+    pub fn parse(name: &str) -> Result<Self> {
+        let format = match name {
+            "dot" => Self::Dot,
+            "json" => Self::Json,
+            _ => bail!("unknown graph format: {}", name),
+        };
+        Ok(format)
+    }
+}
+
+/// Kind of a build action
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum ActionKind {
+    Compile,
+    Link,
+    CompileAndLink,
+}
+
+/// A node in the compilation graph
+#[derive(Serialize)]
+struct GraphNode {
+    kind: ActionKind,
+    output: PathBuf,
+    inputs: Vec<PathBuf>,
+    libs: Vec<PathBuf>,
+}
+
+impl Display for GraphNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let tag = match self.kind {
+            ActionKind::Compile => "compile",
+            ActionKind::Link => "link",
+            ActionKind::CompileAndLink => "compile-and-link",
+        };
+        write!(f, "[{}] {}", tag, self.output.to_string_lossy())
+    }
+}
+
+/// An edge in the compilation graph
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum GraphEdge {
+    Input,
+    Library,
+}
+
+impl Display for GraphEdge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Input => write!(f, "input"),
+            Self::Library => write!(f, "library"),
+        }
+    }
+}
+
+/// A serializable dump of the compilation graph
+#[derive(Serialize)]
+struct GraphDump<'a> {
+    nodes: Vec<&'a GraphNode>,
+    edges: Vec<(usize, usize, GraphEdge)>,
+}
 
 enum SysLib {
     C,
@@ -48,11 +124,40 @@ impl CommonExtensions {
     }
 }
 
+/// How libraries built by the dependencies of a workflow are linked
+pub struct DependencyLinking {
+    /// root directories of the dependency workflows
+    pub roots: Vec<PathBuf>,
+    /// whether to link the merged bitcode of dependency libraries
+    pub link: bool,
+}
+
 #[derive(Default)]
 struct Libraries {
     #[allow(dead_code)]
     sys: Vec<SysLib>,
-    usr: Vec<PathBuf>,
+    usr: Vec<LibraryMark>,
+    /// libraries built by the dependencies
+    deps: Vec<LibraryMark>,
+}
+
+impl Libraries {
+    fn artifacts(&self) -> Vec<PathBuf> {
+        self.usr
+            .iter()
+            .chain(self.deps.iter())
+            .map(|e| e.artifact.clone())
+            .collect()
+    }
+
+    /// Move the libraries built under any of the dependency roots into `deps`
+    fn separate_deps(&mut self, roots: &[PathBuf]) {
+        let (deps, usr) = std::mem::take(&mut self.usr)
+            .into_iter()
+            .partition(|e| roots.iter().any(|r| e.artifact.starts_with(r)));
+        self.usr = usr;
+        self.deps = deps;
+    }
 }
 
 enum Action {
@@ -244,10 +349,7 @@ impl Action {
                                 bail!("more than one candidate found for library {}", name);
                             }
                             found = true;
-                            libs_usr.push(
-                                // canonicalize dereferences the mark which is a symbolic link
-                                entry.path().canonicalize()?,
-                            );
+                            libs_usr.push(LibraryMark::load(&entry.path())?);
                         }
                     }
                 }
@@ -258,6 +360,7 @@ impl Action {
             Some(Libraries {
                 sys: libs_sys,
                 usr: libs_usr,
+                deps: vec![],
             })
         } else {
             None
@@ -362,8 +465,37 @@ impl Action {
         }
     }
 
+    /// Summarize this action as a node in the compilation graph
+    fn as_graph_node(&self) -> GraphNode {
+        let (kind, inputs, libs) = match self {
+            Self::Compile { input, .. } => (ActionKind::Compile, vec![input.clone()], vec![]),
+            Self::Link { inputs, libs, .. } => (ActionKind::Link, inputs.clone(), libs.artifacts()),
+            Self::CompileAndLink { input, libs, .. } => (
+                ActionKind::CompileAndLink,
+                vec![input.clone()],
+                libs.artifacts(),
+            ),
+        };
+        GraphNode {
+            kind,
+            output: self.output().to_path_buf(),
+            inputs,
+            libs,
+        }
+    }
+
+    /// Mark the libraries that are built by the dependencies
+    fn separate_deps(&mut self, roots: &[PathBuf]) {
+        match self {
+            Self::Compile { .. } => (),
+            Self::Link { libs, .. } | Self::CompileAndLink { libs, .. } => {
+                libs.separate_deps(roots)
+            }
+        }
+    }
+
     /// Invoke the build action for whole-program LLVM
-    pub fn invoke_for_wllvm(&self) -> Result<()> {
+    pub fn invoke_for_wllvm(&self, link_deps: bool) -> Result<()> {
         // unpack
         let output = self.output();
         debug!("[wllvm] processing: {}", output.to_string_lossy());
@@ -390,7 +522,7 @@ impl Action {
                 output: _,
                 invocation: _,
             } => {
-                Self::invoke_linker(cwd, inputs, libs, &bitcode_output)?;
+                Self::invoke_linker(cwd, inputs, libs, link_deps, &bitcode_output)?;
             }
             Self::CompileAndLink {
                 input,
@@ -400,7 +532,7 @@ impl Action {
             } => {
                 let bitcode_temp = derive_bitcode_path(&bitcode_output);
                 Self::invoke_compiler(cwd, *cxx, args, input, &bitcode_temp)?;
-                Self::invoke_linker(cwd, &[&bitcode_output], libs, &bitcode_output)?;
+                Self::invoke_linker(cwd, &[&bitcode_output], libs, link_deps, &bitcode_output)?;
             }
         }
         Ok(())
@@ -481,6 +613,7 @@ impl Action {
         cwd: &Path,
         inputs: &[P],
         libs: &Libraries,
+        link_deps: bool,
         output: &Path,
     ) -> Result<()> {
         let ctxt = Context::new().expect("LLVM context");
@@ -501,7 +634,26 @@ impl Action {
             cmd.arg(derive_bitcode_path(input));
         }
         for lib in &libs.usr {
-            cmd.arg(derive_bitcode_path(lib));
+            // prefer the bitcode of the library itself, otherwise assemble it from members
+            let bitcode = derive_bitcode_path(&lib.artifact);
+            if bitcode.exists() {
+                cmd.arg(bitcode);
+            } else {
+                cmd.args(&lib.members);
+            }
+        }
+        if link_deps {
+            // see through the library boundary with the bitcode merged by the dependency
+            for lib in &libs.deps {
+                let bitcode = derive_bitcode_path(&lib.artifact);
+                if !bitcode.exists() {
+                    bail!(
+                        "merged bitcode of dependency library does not exist: {}",
+                        bitcode.to_string_lossy()
+                    );
+                }
+                cmd.arg(bitcode);
+            }
         }
 
         // invoke the command
@@ -510,7 +662,12 @@ impl Action {
 }
 
 /// Scan over the directory, collect build commands, and simulate the build to get bitcode files
-pub fn merge(path_src: &Path, path_bin: &Path) -> Result<()> {
+pub fn merge(
+    path_src: &Path,
+    path_bin: &Path,
+    deps: &DependencyLinking,
+    exports: &[GraphFormat],
+) -> Result<()> {
     // collect commands
     let mut actions = BTreeMap::new();
     for entry in WalkDir::new(path_src) {
@@ -525,7 +682,8 @@ pub fn merge(path_src: &Path, path_bin: &Path) -> Result<()> {
             let invocation: ClangInvocation = serde_json::from_str(&content)?;
             debug!("[wllvm] parsing invocation {}", invocation);
 
-            let action = Action::parse(invocation)?;
+            let mut action = Action::parse(invocation)?;
+            action.separate_deps(&deps.roots);
             let exists = actions.insert(action.output().to_path_buf(), action);
             match exists {
                 None => (),
@@ -544,8 +702,8 @@ pub fn merge(path_src: &Path, path_bin: &Path) -> Result<()> {
     let mut nodes = BTreeMap::new();
 
     // add nodes
-    for key in actions.keys() {
-        let nid = graph.add_node(key.to_path_buf());
+    for (key, val) in &actions {
+        let nid = graph.add_node(val.as_graph_node());
         nodes.insert(key.to_path_buf(), nid);
     }
 
@@ -562,7 +720,7 @@ pub fn merge(path_src: &Path, path_bin: &Path) -> Result<()> {
                         None => bail!("linker input does not exist: {}", item.to_string_lossy()),
                         Some(idx) => *idx,
                     };
-                    graph.add_edge(src, dst, ());
+                    graph.add_edge(src, dst, GraphEdge::Input);
                 }
             }
         }
@@ -572,11 +730,11 @@ pub fn merge(path_src: &Path, path_bin: &Path) -> Result<()> {
             Action::Compile { .. } => (),
             Action::Link { libs, .. } | Action::CompileAndLink { libs, .. } => {
                 for item in &libs.usr {
-                    let src = match nodes.get(item) {
+                    let src = match nodes.get(&item.artifact) {
                         None => continue,
                         Some(idx) => *idx,
                     };
-                    graph.add_edge(src, dst, ());
+                    graph.add_edge(src, dst, GraphEdge::Library);
                 }
             }
         }
@@ -590,19 +748,56 @@ pub fn merge(path_src: &Path, path_bin: &Path) -> Result<()> {
 
     // build and merge according to topological order
     for nid in ordered {
-        let key = graph.node_weight(nid).unwrap();
+        let key = &graph.node_weight(nid).unwrap().output;
         let action = actions.get(key).unwrap();
-        action.invoke_for_wllvm()?;
+        action.invoke_for_wllvm(deps.link)?;
     }
 
-    // plot the graph into a dot file
-    let dot = format!("{:?}", Dot::with_config(&graph, &[Config::EdgeNoLabel]));
-    fs::write(path_bin.join(COMPILE_GRAPH_DOT), dot)?;
+    // final outputs are linked artifacts not consumed by any other action
+    let outputs: Vec<_> = graph
+        .node_indices()
+        .filter(|nid| graph.edges(*nid).next().is_none())
+        .map(|nid| graph.node_weight(nid).unwrap())
+        .filter(|node| !matches!(node.kind, ActionKind::Compile))
+        .map(|node| node.output.clone())
+        .collect();
+    fs::write(
+        path_bin.join(BUILD_OUTPUTS_JSON),
+        serde_json::to_string_pretty(&outputs)?,
+    )?;
+
+    // export the graph in requested formats
+    for format in exports {
+        match format {
+            GraphFormat::Dot => {
+                let dot = format!("{}", Dot::new(&graph));
+                fs::write(path_bin.join(COMPILE_GRAPH_DOT), dot)?;
+            }
+            GraphFormat::Json => {
+                let dump = GraphDump {
+                    nodes: graph.node_weights().collect(),
+                    edges: graph
+                        .edge_references()
+                        .map(|e| (e.source().index(), e.target().index(), *e.weight()))
+                        .collect(),
+                };
+                let content = serde_json::to_string_pretty(&dump)?;
+                fs::write(path_bin.join(COMPILE_GRAPH_JSON), content)?;
+            }
+        }
+    }
 
     // done
     Ok(())
 }
 
+/// Retrieve the final build outputs discovered during merging
+pub fn load_outputs(path_bin: &Path) -> Result<Vec<PathBuf>> {
+    let content = fs::read_to_string(path_bin.join(BUILD_OUTPUTS_JSON))?;
+    let outputs = serde_json::from_str(&content)?;
+    Ok(outputs)
+}
+
 /// Utility: Like `fs::canonicalize`, but without resolving and symbolic links
 fn normalize_path<P: AsRef<Path>, Q: AsRef<Path>>(cwd: P, path: Q) -> PathBuf {
     let path = path.as_ref();
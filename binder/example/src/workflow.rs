@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::OpenOptions;
 use std::path::{Path, PathBuf};
 use std::{env, fs};
@@ -12,12 +12,22 @@ use libra_engine::flow::fixedpoint::FlowFixedpoint;
 use libra_engine::flow::shared::Context;
 use libra_shared::config::PATH_STUDIO;
 
-use crate::common::{derive_bitcode_path, AppConfig};
-use crate::proxy::LIBMARK_EXTENSION;
+use crate::common::{derive_bitcode_path, WorkflowConfig, Workspace};
+use crate::proxy::{LibraryMark, LIBMARK_EXTENSION};
+use crate::wllvm::{DependencyLinking, GraphFormat};
 use crate::{snippet, wllvm};
 
 lazy_static! {
     static ref FORCE: bool = matches!(env::var("FORCE"), Ok(val) if val == "1");
+    static ref GRAPH: Vec<GraphFormat> = match env::var("GRAPH") {
+        Err(_) => vec![GraphFormat::Dot],
+        Ok(val) => val
+            .split(',')
+            .filter(|e| !e.is_empty())
+            .map(GraphFormat::parse)
+            .collect::<Result<_>>()
+            .expect("valid graph formats in GRAPH"),
+    };
 }
 
 /// Details for a library artifact
@@ -62,70 +72,89 @@ impl Stage {
     }
 
     /// set the stage mark
-    pub fn set_mark(self, workdir: &Path) -> Result<()> {
+    pub fn set_mark(self, workspace: &Workspace) -> Result<()> {
         OpenOptions::new()
             .create_new(true)
             .write(true)
-            .open(workdir.join(self.mark()))?;
+            .open(workspace.root.join(self.mark()))?;
         Ok(())
     }
 
     /// get the stage mark
-    pub fn get_mark(self, workdir: &Path) -> bool {
-        workdir.join(self.mark()).exists()
+    pub fn get_mark(self, workspace: &Workspace) -> bool {
+        workspace.root.join(self.mark()).exists()
     }
 }
 
+/// Dependencies declared in a workflow config, readable without knowing the app
+#[derive(Deserialize)]
+struct DependencyDecl {
+    #[serde(default)]
+    deps: BTreeMap<String, String>,
+}
+
 /// Workflow for an app
 #[derive(Serialize, Deserialize)]
-#[serde(bound = "T: AppConfig")]
-pub struct Workflow<T: AppConfig> {
+#[serde(bound = "T: WorkflowConfig")]
+pub struct Workflow<T: WorkflowConfig> {
     // config
     config: T,
     // build
     libs: BTreeMap<String, Artifact>,
     bins: BTreeMap<String, Artifact>,
-    entry: Entrypoint,
+    entry: Option<Entrypoint>,
+    // dependencies: app name -> workflow name
+    #[serde(default)]
+    deps: BTreeMap<String, String>,
+    // whether to link the bitcode of the dependencies into the build outputs
+    #[serde(default)]
+    link_deps: bool,
     // analysis
     fixedpoint: Option<usize>,
 }
 
-impl<T: AppConfig> Workflow<T> {
-    /// Check build artifact
-    fn check(&self, path_src: &Path, path_bin: &Path) -> Result<()> {
+impl<T: WorkflowConfig> Workflow<T> {
+    /// Check build artifact and mark the libraries
+    fn check(&self, workspace: &Workspace) -> Result<()> {
+        let Workspace { src, bin, .. } = workspace;
         for (name, details) in &self.libs {
             snippet::mark_artifact_lib(
                 name,
-                path_bin.join(&details.item_in_bin),
-                path_src.join(&details.item_in_src),
+                bin.join(&details.item_in_bin),
+                src.join(&details.item_in_src),
             )?;
         }
         for (name, details) in &self.bins {
             snippet::check_artifact_bin(
                 name,
-                path_bin.join(&details.item_in_bin),
-                path_src.join(&details.item_in_src),
+                bin.join(&details.item_in_bin),
+                src.join(&details.item_in_src),
             )?;
         }
         Ok(())
     }
 
     /// Analyze the artifact based on entry point
-    fn analyze(&self, path_src: &Path, path_bin: &Path, path_wks: &Path) -> Result<()> {
+    fn analyze(&self, entry: &Entrypoint, workspace: &Workspace) -> Result<()> {
+        let Workspace { src, bin, wks, .. } = workspace;
+
         // derive the bitcode file
-        let artifact = match &self.entry.kind {
-            ArtifactKind::Lib => match self.libs.get(&self.entry.name) {
-                None => bail!("unable to find entry target in libs: {}", self.entry.name),
-                Some(artifact) => path_bin
-                    .join(&artifact.item_in_bin)
-                    .join(format!("lib{}{}", self.entry.name, LIBMARK_EXTENSION))
-                    .canonicalize()?,
+        let artifact = match &entry.kind {
+            ArtifactKind::Lib => match self.libs.get(&entry.name) {
+                None => bail!("unable to find entry target in libs: {}", entry.name),
+                Some(artifact) => {
+                    LibraryMark::load(
+                        &bin.join(&artifact.item_in_bin)
+                            .join(format!("lib{}{}", entry.name, LIBMARK_EXTENSION)),
+                    )?
+                    .artifact
+                }
             },
-            ArtifactKind::Bin => match self.bins.get(&self.entry.name) {
-                None => bail!("unable to find entry target in bins: {}", self.entry.name),
-                Some(artifact) => path_src
+            ArtifactKind::Bin => match self.bins.get(&entry.name) {
+                None => bail!("unable to find entry target in bins: {}", entry.name),
+                Some(artifact) => src
                     .join(&artifact.item_in_src)
-                    .join(&self.entry.name)
+                    .join(&entry.name)
                     .canonicalize()?,
             },
         };
@@ -136,7 +165,11 @@ impl<T: AppConfig> Workflow<T> {
             );
         }
 
-        let path_base_bitcode = derive_bitcode_path(artifact);
+        self.analyze_bitcode(derive_bitcode_path(artifact), wks)
+    }
+
+    /// Analyze the whole-program bitcode of a build output
+    fn analyze_bitcode(&self, path_base_bitcode: PathBuf, wks: &Path) -> Result<()> {
         if !path_base_bitcode.exists() {
             bail!(
                 "artifact bitcode does not exist: {}",
@@ -146,16 +179,12 @@ impl<T: AppConfig> Workflow<T> {
 
         // prepare for analysis
         let ctxt = Context::new()?;
-        fs::create_dir_all(path_wks)?;
+        fs::create_dir_all(wks)?;
 
         // fixedpoint optimization (if applicable)
-        let trace = FlowFixedpoint::new(
-            &ctxt,
-            path_base_bitcode,
-            path_wks.to_path_buf(),
-            self.fixedpoint,
-        )
-        .execute()?;
+        let trace =
+            FlowFixedpoint::new(&ctxt, path_base_bitcode, wks.to_path_buf(), self.fixedpoint)
+                .execute()?;
 
         if trace.is_empty() {
             bail!("fixedpoint optimization leaves no modules in trace");
@@ -167,43 +196,90 @@ impl<T: AppConfig> Workflow<T> {
         Ok(())
     }
 
-    /// Execute the profile
-    pub fn run(&self, workdir: &Path) -> Result<()> {
-        let path_src = workdir.join("src");
-        let path_bin = workdir.join("bin");
-        let path_wks = workdir.join("wks");
+    /// Analyze the build outputs discovered during merging, as selected by file name
+    fn analyze_selected(&self, selection: &[String], workspace: &Workspace) -> Result<()> {
+        let outputs = wllvm::load_outputs(&workspace.bin)?;
+        for output in &outputs {
+            info!("Discovered build output: {}", output.to_string_lossy());
+        }
+        if selection.is_empty() {
+            info!("No entrypoint defined nor outputs selected, skipping the analysis");
+            return Ok(());
+        }
 
+        for name in selection {
+            let mut candidates = outputs
+                .iter()
+                .filter(|e| e.file_name().is_some_and(|n| n == name.as_str()));
+            let output = match (candidates.next(), candidates.next()) {
+                (None, _) => bail!("no build output named {}", name),
+                (Some(_), Some(_)) => bail!("more than one build output named {}", name),
+                (Some(item), None) => item,
+            };
+            info!("Analyzing build output: {}", output.to_string_lossy());
+            self.analyze_bitcode(derive_bitcode_path(output), &workspace.wks.join(name))?;
+        }
+        Ok(())
+    }
+
+    /// Execute the profile
+    pub fn run(&self, workspace: &Workspace, selection: &[String]) -> Result<()> {
         // obtain the bitcode
-        if !Stage::Build.get_mark(workdir) {
-            T::build(&self.config, &path_src, &path_bin)?;
-            Stage::Build.set_mark(workdir)?;
+        if !Stage::Build.get_mark(workspace) {
+            T::build(&self.config, workspace)?;
+            Stage::Build.set_mark(workspace)?;
         }
-        if !Stage::Check.get_mark(workdir) {
-            self.check(&path_src, &path_bin)?;
-            Stage::Check.set_mark(workdir)?;
+        if !Stage::Check.get_mark(workspace) {
+            self.check(workspace)?;
+            Stage::Check.set_mark(workspace)?;
         }
-        if !Stage::Merge.get_mark(workdir) {
-            wllvm::merge(&path_src, &path_bin)?;
-            Stage::Merge.set_mark(workdir)?;
+        if !Stage::Merge.get_mark(workspace) {
+            let deps = DependencyLinking {
+                roots: workspace.deps.values().map(|e| e.root.clone()).collect(),
+                link: self.link_deps,
+            };
+            wllvm::merge(&workspace.src, &workspace.bin, &deps, &GRAPH)?;
+            Stage::Merge.set_mark(workspace)?;
         }
 
         // run the analysis
-        if !Stage::Analyze.get_mark(workdir) {
-            self.analyze(&path_src, &path_bin, &path_wks)?;
+        if !Stage::Analyze.get_mark(workspace) {
+            match &self.entry {
+                None => self.analyze_selected(selection, workspace)?,
+                Some(entry) => self.analyze(entry, workspace)?,
+            }
         }
 
         Ok(())
     }
 }
 
+/// Path to the config directory of an app
+fn path_configs(app: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("src")
+        .join("apps")
+        .join(app)
+        .join("configs")
+}
+
+/// Derive the workspace of a workflow, including the workspaces of its direct dependencies
+fn derive_workspace(app: &str, name: &str, deps: &BTreeMap<String, String>) -> Workspace {
+    let mut workspace = Workspace::new(&PATH_STUDIO.join("example").join(app).join(name));
+    for (dep_app, dep_name) in deps {
+        let dep_workspace =
+            Workspace::new(&PATH_STUDIO.join("example").join(dep_app).join(dep_name));
+        workspace.deps.insert(dep_app.to_string(), dep_workspace);
+    }
+    workspace
+}
+
 /// Probe for workflows available
-fn probe_workflows<T: AppConfig>() -> Result<Vec<(String, PathBuf, Workflow<T>)>> {
+fn probe_workflows<T: WorkflowConfig>() -> Result<Vec<(String, Workspace, Workflow<T>)>> {
     let app = T::app();
     let mut workflows = vec![];
 
-    let base = Path::new(env!("CARGO_MANIFEST_DIR"));
-    let config_dir = base.join("src").join("apps").join(app).join("configs");
-    for item in config_dir.read_dir()? {
+    for item in path_configs(app).read_dir()? {
         let item = item?;
         let path = item.path();
         if path.extension().map_or(false, |e| e == "json") {
@@ -218,44 +294,123 @@ fn probe_workflows<T: AppConfig>() -> Result<Vec<(String, PathBuf, Workflow<T>)>
                 .strip_suffix(".json")
                 .expect("strip the .json suffix")
                 .to_string();
-            let workdir = PATH_STUDIO.join("example").join(app).join(&name);
+            let workspace = derive_workspace(app, &name, &workflow.deps);
 
-            workflows.push((name, workdir, workflow));
+            workflows.push((name, workspace, workflow));
         }
     }
 
     Ok(workflows)
 }
 
+/// Visit the dependency graph in post-order, detecting cycles along the way
+fn visit_dependencies(
+    node: (String, String),
+    stack: &mut Vec<(String, String)>,
+    order: &mut Vec<(String, String)>,
+) -> Result<()> {
+    if order.contains(&node) {
+        return Ok(());
+    }
+    if stack.contains(&node) {
+        let trace: Vec<_> = stack
+            .iter()
+            .chain(std::iter::once(&node))
+            .map(|(app, name)| format!("{}:{}", app, name))
+            .collect();
+        bail!(
+            "cyclic dependencies among workflows: {}",
+            trace.join(" -> ")
+        );
+    }
+
+    let (app, name) = &node;
+    let path = path_configs(app).join(format!("{}.json", name));
+    if !path.exists() {
+        bail!("no such workflow '{}' for app '{}'", name, app);
+    }
+    let content = fs::read_to_string(&path)?;
+    let decl: DependencyDecl = serde_json::from_str(&content)?;
+
+    stack.push(node.clone());
+    for dep in decl.deps {
+        visit_dependencies(dep, stack, order)?;
+    }
+    stack.pop();
+
+    order.push(node);
+    Ok(())
+}
+
+/// Resolve the dependencies of a workflow, in the order they should be built
+fn resolve_dependencies(app: &str, name: &str) -> Result<Vec<(String, String)>> {
+    let root = (app.to_string(), name.to_string());
+    let mut order = vec![];
+    visit_dependencies(root.clone(), &mut vec![], &mut order)?;
+
+    // the root itself is always the last one in post-order
+    let last = order.pop();
+    assert_eq!(last.as_ref(), Some(&root));
+    Ok(order)
+}
+
+/// Prepare the work directory
+fn prepare_workdir(workspace: &Workspace, force: bool) -> Result<()> {
+    let workdir = &workspace.root;
+    if workdir.exists() && force {
+        fs::remove_dir_all(workdir)?;
+    }
+    if !workdir.exists() {
+        fs::create_dir_all(workdir)?;
+    }
+    Ok(())
+}
+
+/// Run a particular workflow without resolving its dependencies
+pub fn execute_one<T: WorkflowConfig>(target: &str, force: bool) -> Result<()> {
+    let (workspace, workflow) = retrieve_workflow::<T>(target)?;
+    info!("Processing '{}' under config '{}'", T::app(), target);
+    prepare_workdir(&workspace, force)?;
+    workflow.run(&workspace, &[])
+}
+
+/// Run a particular workflow of an app, building its dependencies first
+pub fn execute_with_deps(app: &str, target: &str, force: bool) -> Result<()> {
+    for (dep_app, dep_name) in resolve_dependencies(app, target)? {
+        crate::apps::dispatch(&dep_app, &dep_name, false)?;
+    }
+    crate::apps::dispatch(app, target, force)
+}
+
 /// Run the workflows based on defined config files
-pub fn execute<T: AppConfig>() -> Result<()> {
+pub fn execute<T: WorkflowConfig>(selection: &[String]) -> Result<()> {
     let app = T::app();
     let workflows = probe_workflows::<T>()?;
 
     // execute the workflows one by one
-    for (name, workdir, workflow) in workflows {
-        info!("Processing '{}' under config '{}'", app, name);
-
-        // prepare the work directory
-        if workdir.exists() && *FORCE {
-            fs::remove_dir_all(&workdir)?;
-        }
-        if !workdir.exists() {
-            fs::create_dir_all(&workdir)?;
+    let mut finished = BTreeSet::new();
+    for (name, workspace, workflow) in workflows {
+        // build the dependencies first, their stage marks serve as the cache
+        for (dep_app, dep_name) in resolve_dependencies(app, &name)? {
+            if finished.insert((dep_app.clone(), dep_name.clone())) {
+                crate::apps::dispatch(&dep_app, &dep_name, false)?;
+            }
         }
 
-        // execute it
-        workflow.run(&workdir)?;
+        info!("Processing '{}' under config '{}'", app, name);
+        prepare_workdir(&workspace, *FORCE)?;
+        workflow.run(&workspace, selection)?;
+        finished.insert((app.to_string(), name));
     }
     Ok(())
 }
 
 /// Retrieve a particular workflow
-pub fn retrieve_workflow<T: AppConfig>(target: &str) -> Result<(PathBuf, Workflow<T>)> {
+pub fn retrieve_workflow<T: WorkflowConfig>(target: &str) -> Result<(Workspace, Workflow<T>)> {
     let configs = probe_workflows::<T>()?;
-    for (name, workdir, config) in configs {
+    for (name, workspace, config) in configs {
         if target == name.as_str() {
-            return Ok((workdir, config));
+            return Ok((workspace, config));
         }
     }
     bail!("no such workflow '{}' for app '{}'", target, T::app());
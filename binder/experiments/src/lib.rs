@@ -0,0 +1,82 @@
+mod matrix;
+mod report;
+mod spec;
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use structopt::StructOpt;
+
+use libra_shared::config::initialize;
+
+use crate::matrix::workdir_of;
+use crate::report::Report;
+use crate::spec::ExperimentSpec;
+
+#[derive(StructOpt)]
+enum Command {
+    /// Check an experiment spec without running it
+    Check {
+        /// Path to the spec file
+        #[structopt(parse(from_os_str))]
+        spec: PathBuf,
+    },
+
+    /// Run the full matrix of an experiment and aggregate the results
+    Run {
+        /// Path to the spec file
+        #[structopt(parse(from_os_str))]
+        spec: PathBuf,
+
+        /// Force the execution to start from scratch
+        #[structopt(short, long)]
+        force: bool,
+    },
+
+    /// Show the report of a prior run of an experiment
+    Report {
+        /// Name of the experiment
+        name: String,
+    },
+}
+
+#[derive(StructOpt)]
+#[structopt(
+    name = "libra-experiments",
+    about = "A driver for reproducible LIBRA experiments",
+    rename_all = "kebab-case"
+)]
+struct Args {
+    /// Command to run
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+/// Main entrypoint
+pub fn entrypoint() -> Result<()> {
+    let args = Args::from_args();
+    let Args { command } = args;
+    // setup
+    initialize();
+
+    // run the subcommand
+    match command {
+        Command::Check { spec } => {
+            let spec = ExperimentSpec::load(&spec)?;
+            println!(
+                "experiment {}: {} program(s), {} suite(s), {} app(s), {} depth(s)",
+                spec.name,
+                spec.programs.len(),
+                spec.suites.len(),
+                spec.apps.len(),
+                spec.depth_points().len()
+            );
+        }
+        Command::Run { spec, force } => {
+            let spec = ExperimentSpec::load(&spec)?;
+            matrix::run(&spec, force)?.show();
+        }
+        Command::Report { name } => Report::load(&workdir_of(&name))?.show(),
+    }
+    Ok(())
+}
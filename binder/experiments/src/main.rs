@@ -0,0 +1,7 @@
+use anyhow::Result;
+
+use libra_experiments::entrypoint;
+
+fn main() -> Result<()> {
+    entrypoint()
+}
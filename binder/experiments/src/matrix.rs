@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use log::{info, warn};
+
+use libra_engine::analysis::dataflow::Widening;
+use libra_engine::analysis::invariant::synthesize_loop_invariants;
+use libra_engine::analysis::verify::{verify_assertions, Verdict};
+use libra_engine::error::{EngineError, EngineResult};
+use libra_engine::flow::build_simple::FlowBuildSimple;
+use libra_engine::flow::fixedpoint::FlowFixedpoint;
+use libra_engine::flow::shared::Context;
+use libra_engine::ir::alignment::check_misaligned_accesses;
+use libra_engine::ir::bridge::module::Module;
+use libra_shared::config::PATH_STUDIO;
+use libra_testsuite::{run_example_app, sweep_suite, DepthOutcome};
+
+use crate::report::{Cell, Report, TargetKind};
+use crate::spec::{Analysis, ExperimentSpec, ProgramSpec};
+
+static PATH_WORKSPACE: &str = "experiments";
+
+/// Outcome recorded for a toolchain that is requested but not active
+static OUTCOME_UNAVAILABLE: &str = "unavailable";
+
+/// Location of the workspace of an experiment
+pub fn workdir_of(name: &str) -> PathBuf {
+    PATH_STUDIO.join(PATH_WORKSPACE).join(name)
+}
+
+/// Utility: directory name of a depth
+fn depth_dir(depth: Option<usize>) -> String {
+    depth.map_or_else(|| "depth-none".to_string(), |d| format!("depth-{}", d))
+}
+
+/// Run the analyses on a converted module and collect their metrics
+fn analyze(
+    module: &Module,
+    analyses: &[Analysis],
+) -> EngineResult<BTreeMap<String, BTreeMap<String, usize>>> {
+    let mut collected = BTreeMap::new();
+    for analysis in analyses {
+        let mut metrics = BTreeMap::new();
+        match analysis {
+            Analysis::Verify => {
+                for key in ["proved", "violated", "unknown"] {
+                    metrics.insert(key.to_string(), 0);
+                }
+                for report in verify_assertions(module)? {
+                    let key = match report.verdict {
+                        Verdict::Proved => "proved",
+                        Verdict::Violated(_) => "violated",
+                        Verdict::Unknown => "unknown",
+                    };
+                    *metrics.entry(key.to_string()).or_insert(0) += 1;
+                }
+            }
+            Analysis::Invariants => {
+                let invariants = synthesize_loop_invariants(module, &Widening::default())?;
+                metrics.insert("loops".to_string(), invariants.len());
+                metrics.insert(
+                    "facts".to_string(),
+                    invariants.iter().map(|inv| inv.facts.len()).sum(),
+                );
+            }
+            Analysis::Misaligned => {
+                metrics.insert(
+                    "findings".to_string(),
+                    check_misaligned_accesses(module)?.len(),
+                );
+            }
+        }
+        collected.insert(analysis.to_string(), metrics);
+    }
+    Ok(collected)
+}
+
+/// Build a program once and run it through the fixedpoint flow at each depth
+fn run_program(
+    ctxt: &Context,
+    llvm: &str,
+    program: &ProgramSpec,
+    depths: &[Option<usize>],
+    analyses: &[Analysis],
+    workdir: &Path,
+) -> Result<Vec<Cell>> {
+    let ProgramSpec {
+        name,
+        sources,
+        flags,
+    } = program;
+    let base = workdir.join(name);
+    let path_build = base.join("build");
+    fs::create_dir_all(&path_build)?;
+
+    info!("Building program {} with LLVM {}", name, llvm);
+    let built = FlowBuildSimple::new(ctxt, sources.clone(), path_build, flags.clone()).execute();
+
+    let mut cells = vec![];
+    for depth in depths {
+        let result = built.clone().and_then(|bitcode| {
+            let output = base.join(depth_dir(*depth));
+            fs::create_dir_all(&output)
+                .map_err(|e| EngineError::CompilationError(e.to_string()))?;
+            let trace = FlowFixedpoint::new(ctxt, bitcode, output, *depth).execute()?;
+            let metrics = match trace.last() {
+                None => BTreeMap::new(),
+                Some(module) => analyze(module, analyses)?,
+            };
+            Ok((trace.len(), metrics))
+        });
+        let (outcome, metrics) = match result {
+            Ok((rounds, metrics)) => (DepthOutcome::Rounds(rounds), metrics),
+            Err(err) => (
+                DepthOutcome::from_result(&Err::<usize, _>(err)),
+                BTreeMap::new(),
+            ),
+        };
+        cells.push(Cell {
+            llvm: llvm.to_string(),
+            kind: TargetKind::Program,
+            target: name.clone(),
+            depth: *depth,
+            outcome: outcome.to_string(),
+            metrics,
+        });
+    }
+    Ok(cells)
+}
+
+/// Cells of the matrix that cannot be run on the active toolchain
+fn unavailable(spec: &ExperimentSpec, llvm: &str, depths: &[Option<usize>]) -> Vec<Cell> {
+    let mut targets = vec![];
+    for program in &spec.programs {
+        for depth in depths {
+            targets.push((TargetKind::Program, program.name.clone(), *depth));
+        }
+    }
+    for suite in &spec.suites {
+        let kind = match suite.suite.as_str() {
+            "external" => TargetKind::External,
+            _ => TargetKind::Internal,
+        };
+        for depth in depths {
+            targets.push((kind, suite.selection.join(","), *depth));
+        }
+    }
+    for app in &spec.apps {
+        targets.push((TargetKind::App, app.clone(), None));
+    }
+    targets
+        .into_iter()
+        .map(|(kind, target, depth)| Cell {
+            llvm: llvm.to_string(),
+            kind,
+            target,
+            depth,
+            outcome: OUTCOME_UNAVAILABLE.to_string(),
+            metrics: BTreeMap::new(),
+        })
+        .collect()
+}
+
+/// Run the full matrix of an experiment and aggregate the results into a report
+pub fn run(spec: &ExperimentSpec, force: bool) -> Result<Report> {
+    // prepare the environment
+    let workdir = workdir_of(&spec.name);
+    if workdir.exists() {
+        if !force {
+            info!("Prior experiment result exists");
+            return Report::load(&workdir);
+        }
+        fs::remove_dir_all(&workdir)?;
+    }
+    fs::create_dir_all(&workdir)?;
+    fs::write(
+        workdir.join("spec.json"),
+        serde_json::to_string_pretty(spec)?,
+    )?;
+
+    // the toolchain is decided by the build, so other versions can only be recorded
+    let ctxt = Context::new()?;
+    let active = ctxt.llvm_version()?;
+    let versions = if spec.llvm.is_empty() {
+        vec![active.clone()]
+    } else {
+        spec.llvm.clone()
+    };
+    let depths = spec.depth_points();
+
+    let mut cells = vec![];
+    for llvm in versions {
+        if !active.starts_with(llvm.as_str()) {
+            warn!("LLVM {} is requested but {} is active", llvm, active);
+            cells.extend(unavailable(spec, &llvm, &depths));
+            continue;
+        }
+        let wks = workdir.join(format!("llvm-{}", llvm));
+
+        // programs
+        for program in &spec.programs {
+            cells.extend(run_program(
+                &ctxt,
+                &llvm,
+                program,
+                &depths,
+                &spec.analyses,
+                &wks.join("programs"),
+            )?);
+        }
+
+        // test suites, swept over the span of the depths
+        let span = match (depths.first(), depths.last()) {
+            (Some(Some(lo)), Some(Some(hi))) => Some(*lo..=*hi),
+            _ => None,
+        };
+        for suite in &spec.suites {
+            let span = match &span {
+                None => continue,
+                Some(span) => span.clone(),
+            };
+            let kind = match suite.suite.as_str() {
+                "external" => TargetKind::External,
+                _ => TargetKind::Internal,
+            };
+            info!("Sweeping test suite {} with LLVM {}", suite.suite, llvm);
+            let records = sweep_suite(&suite.suite, force, suite.selection.clone(), span)?;
+            for (test, record) in records {
+                for depth in depths.iter().flatten() {
+                    if let Some(outcome) = record.outcome(*depth) {
+                        cells.push(Cell {
+                            llvm: llvm.clone(),
+                            kind,
+                            target: test.clone(),
+                            depth: Some(*depth),
+                            outcome: outcome.to_string(),
+                            metrics: BTreeMap::new(),
+                        });
+                    }
+                }
+            }
+        }
+
+        // example apps
+        for app in &spec.apps {
+            let outcome = run_example_app(app, force);
+            cells.push(Cell {
+                llvm: llvm.clone(),
+                kind: TargetKind::App,
+                target: app.clone(),
+                depth: None,
+                outcome: outcome.to_string(),
+                metrics: BTreeMap::new(),
+            });
+        }
+    }
+
+    // aggregate
+    let report = Report {
+        name: spec.name.clone(),
+        llvm_active: active,
+        cells,
+    };
+    report.save(&workdir)?;
+    Ok(report)
+}
@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use log::info;
+use serde::{Deserialize, Serialize};
+
+/// Kind of a target in the experiment matrix
+#[derive(Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetKind {
+    Program,
+    External,
+    Internal,
+    App,
+}
+
+impl Display for TargetKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Program => write!(f, "program"),
+            Self::External => write!(f, "external"),
+            Self::Internal => write!(f, "internal"),
+            Self::App => write!(f, "app"),
+        }
+    }
+}
+
+/// Result of one point in the experiment matrix
+#[derive(Serialize, Deserialize)]
+pub struct Cell {
+    /// version of the LLVM toolchain
+    pub llvm: String,
+    /// kind of the target
+    pub kind: TargetKind,
+    /// name of the target
+    pub target: String,
+    /// depth of fixedpoint optimization, if applicable
+    pub depth: Option<usize>,
+    /// outcome of running the target
+    pub outcome: String,
+    /// metrics collected by the analyses, keyed by analysis and then metric
+    #[serde(default)]
+    pub metrics: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+/// Aggregated results of an experiment
+#[derive(Serialize, Deserialize)]
+pub struct Report {
+    /// name of the experiment
+    pub name: String,
+    /// version of the LLVM toolchain that is active
+    pub llvm_active: String,
+    /// results of the matrix
+    pub cells: Vec<Cell>,
+}
+
+impl Report {
+    /// Print the number of cells per outcome for each toolchain, target kind, and depth
+    pub fn show(&self) {
+        let mut groups: BTreeMap<_, BTreeMap<&str, usize>> = BTreeMap::new();
+        for cell in &self.cells {
+            *groups
+                .entry((&cell.llvm, cell.kind, cell.depth))
+                .or_default()
+                .entry(&cell.outcome)
+                .or_insert(0) += 1;
+        }
+
+        println!(
+            "experiment: {} (active llvm: {})",
+            self.name, self.llvm_active
+        );
+        for ((llvm, kind, depth), outcomes) in groups {
+            println!(
+                "  llvm={} kind={} depth={}",
+                llvm,
+                kind,
+                depth.map_or_else(|| "none".to_string(), |d| d.to_string())
+            );
+            for (outcome, num) in outcomes {
+                println!("    - {}: {}", outcome, num);
+            }
+        }
+
+        // metrics summed over all cells
+        let mut totals: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+        for cell in &self.cells {
+            for (analysis, metrics) in &cell.metrics {
+                for (metric, value) in metrics {
+                    *totals.entry((analysis, metric)).or_insert(0) += value;
+                }
+            }
+        }
+        for ((analysis, metric), value) in totals {
+            println!("  {}.{}: {}", analysis, metric, value);
+        }
+    }
+
+    /// Save the report in JSON and CSV formats
+    pub fn save(&self, workdir: &Path) -> Result<()> {
+        let path_json = workdir.join("report.json");
+        fs::write(&path_json, serde_json::to_string_pretty(self)?)?;
+
+        let mut content = String::from("llvm,kind,target,depth,outcome,metrics\n");
+        for cell in &self.cells {
+            let metrics: Vec<_> = cell
+                .metrics
+                .iter()
+                .flat_map(|(analysis, metrics)| {
+                    metrics
+                        .iter()
+                        .map(move |(metric, value)| format!("{}.{}={}", analysis, metric, value))
+                })
+                .collect();
+            content.push_str(&format!(
+                "{},{},\"{}\",{},\"{}\",\"{}\"\n",
+                cell.llvm,
+                cell.kind,
+                cell.target.replace('"', "\"\""),
+                cell.depth.map_or_else(String::new, |d| d.to_string()),
+                cell.outcome.replace('"', "\"\""),
+                metrics.join(";")
+            ));
+        }
+        let path_csv = workdir.join("report.csv");
+        fs::write(&path_csv, content)?;
+
+        info!(
+            "Experiment report saved at: {} and {}",
+            path_json.to_string_lossy(),
+            path_csv.to_string_lossy()
+        );
+        Ok(())
+    }
+
+    pub fn load(workdir: &Path) -> Result<Self> {
+        let content = fs::read_to_string(workdir.join("report.json"))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
@@ -0,0 +1,153 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// An analysis applied to the converted module of each program input
+#[derive(Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Analysis {
+    /// user assertions treated as proof obligations
+    Verify,
+    /// candidate loop invariants
+    Invariants,
+    /// loads and stores claiming more alignment than their pointers have
+    Misaligned,
+}
+
+impl Display for Analysis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Verify => write!(f, "verify"),
+            Self::Invariants => write!(f, "invariants"),
+            Self::Misaligned => write!(f, "misaligned"),
+        }
+    }
+}
+
+/// A program built from source files
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProgramSpec {
+    /// name of the program, unique in the experiment
+    pub name: String,
+    /// source files, relative to the spec file unless absolute
+    pub sources: Vec<PathBuf>,
+    /// extra flags passed to the compiler
+    #[serde(default)]
+    pub flags: Vec<String>,
+}
+
+/// A selection of test cases from a test suite
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SuiteSpec {
+    /// name of the suite, i.e., `external` or `internal`
+    pub suite: String,
+    /// test cases to run, all if empty
+    #[serde(default)]
+    pub selection: Vec<String>,
+}
+
+/// Specification of an experiment: the matrix to run and what to collect
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExperimentSpec {
+    /// name of the experiment, also the name of its workspace
+    pub name: String,
+    /// versions of the LLVM toolchain to cover, the active one if empty
+    #[serde(default)]
+    pub llvm: Vec<String>,
+    /// depths of fixedpoint optimization, unbounded if empty
+    #[serde(default)]
+    pub depths: Vec<usize>,
+    /// analyses applied to the program inputs
+    #[serde(default)]
+    pub analyses: Vec<Analysis>,
+    /// programs built from source files
+    #[serde(default)]
+    pub programs: Vec<ProgramSpec>,
+    /// test suites, swept over the depths
+    #[serde(default)]
+    pub suites: Vec<SuiteSpec>,
+    /// example apps in the form of `<app>[:<workflow>]`, which have no notion of depth
+    #[serde(default)]
+    pub apps: Vec<String>,
+}
+
+impl ExperimentSpec {
+    /// Load the spec from a JSON file, resolving the paths against its location
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let mut spec: Self = serde_json::from_str(&content)?;
+
+        let base = path.parent().unwrap_or(Path::new("."));
+        for program in spec.programs.iter_mut() {
+            for source in program.sources.iter_mut() {
+                if source.is_relative() {
+                    *source = base.join(&*source);
+                }
+            }
+        }
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let Self {
+            name,
+            llvm: _,
+            depths,
+            analyses,
+            programs,
+            suites,
+            apps,
+        } = self;
+
+        if name.is_empty() || name.contains(['/', '\\']) {
+            bail!("invalid experiment name: {}", name);
+        }
+        if depths.contains(&0) {
+            bail!("fixedpoint depth must be positive");
+        }
+        if !analyses.is_empty() && programs.is_empty() {
+            bail!("analyses are only applied to programs but none is specified");
+        }
+        if !suites.is_empty() && depths.is_empty() {
+            bail!("test suites are swept over the depths but none is specified");
+        }
+        if programs.is_empty() && suites.is_empty() && apps.is_empty() {
+            bail!("the experiment has nothing to run");
+        }
+
+        let mut names: Vec<_> = programs.iter().map(|p| p.name.as_str()).collect();
+        names.sort();
+        if let Some(w) = names.windows(2).find(|w| w[0] == w[1]) {
+            bail!("duplicated program name: {}", w[0]);
+        }
+        for program in programs {
+            if program.sources.is_empty() {
+                bail!("program {} has no source files", program.name);
+            }
+        }
+        for suite in suites {
+            if !matches!(suite.suite.as_str(), "external" | "internal") {
+                bail!("unknown test suite: {}", suite.suite);
+            }
+        }
+        Ok(())
+    }
+
+    /// Depths to run at, where `None` stands for unbounded
+    pub fn depth_points(&self) -> Vec<Option<usize>> {
+        if self.depths.is_empty() {
+            return vec![None];
+        }
+        let mut depths = self.depths.clone();
+        depths.sort();
+        depths.dedup();
+        depths.into_iter().map(Some).collect()
+    }
+}
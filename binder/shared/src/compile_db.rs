@@ -26,6 +26,21 @@ impl CompileDB {
         let entries: Vec<CompileEntry> = serde_json::from_str(&content)?;
         Ok(Self { entries })
     }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(&self.entries)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+/// Quote a token of a command line if needed, as a shell would split the command line
+pub fn quote_token(token: &str) -> String {
+    if token.is_empty() || token.contains(|c: char| c.is_whitespace() || c == '"' || c == '\'') {
+        format!("\"{}\"", token.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        token.to_string()
+    }
 }
 
 pub struct TokenStream<'a> {
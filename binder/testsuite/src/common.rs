@@ -1,42 +1,90 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::env;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use anyhow::{bail, Result};
 use lazy_static::lazy_static;
 use log::{error, info};
 use rayon::iter::IntoParallelIterator;
 use rayon::iter::ParallelIterator;
+use rayon::ThreadPoolBuilder;
 use serde::{Deserialize, Serialize};
 
 use libra_engine::error::{EngineError, EngineResult};
-use libra_engine::flow::shared::Context;
+use libra_engine::flow::shared::{Context, ToolLimits};
 use libra_shared::config::PATH_STUDIO;
 use libra_shared::dep::Resolver;
 use libra_shared::git::GitRepo;
 
+use crate::history::{self, RunRecord};
+use crate::rounds::{Distribution, Rounds};
+use crate::sweep::{self, DepthOutcome, DepthRange, SweepRecord};
+
 // Environment configurations
 lazy_static! {
     static ref PARALLEL: bool = matches!(env::var("LIBRA_PARALLEL"), Ok(val) if val == "1");
     static ref CONTINUE: bool = matches!(env::var("LIBRA_CONTINUE"), Ok(val) if val == "1");
+    static ref COMPILE_JOBS: usize = env::var("LIBRA_COMPILE_JOBS")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .filter(|jobs| *jobs != 0)
+        .unwrap_or_else(|| (rayon::current_num_threads() / 2).max(1));
+    static ref TOOL_TIMEOUT: Option<Duration> = env::var("LIBRA_TOOL_TIMEOUT")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .map(Duration::from_secs);
 }
 
 /// Controls whether we need to halt the parallel execution
 static HALT_PARALLEL_EXECUTION: AtomicBool = AtomicBool::new(false);
 
+/// Location of the depth sweep workspaces from the studio
+static PATH_SWEEP: [&str; 2] = ["testsuite", "sweep"];
+
+/// Utility: a context with the limits on the tools taken from the environment
+fn new_context() -> Result<Context> {
+    let mut ctxt = Context::new()?;
+    ctxt.set_tool_limits(ToolLimits {
+        timeout: *TOOL_TIMEOUT,
+        memory: None,
+    });
+    Ok(ctxt)
+}
+
 /// A trait that marks a test case
 pub trait TestCase: Send {
     /// Get the name of the test case
     fn name(&self) -> &str;
 
-    /// Run the test case through libra workflow
+    /// Compile the test case into bitcode, yielding `None` if the test case is not applicable
+    fn compile(&self, ctxt: &Context, workdir: &Path) -> Result<Option<EngineResult<PathBuf>>>;
+
+    /// Run libra workflow on the compiled bitcode, yielding the number of fixedpoint rounds
+    fn analyze(
+        &self,
+        ctxt: &Context,
+        bitcode: &Path,
+        workdir: &Path,
+        depth: Option<usize>,
+    ) -> EngineResult<usize>;
+
+    /// Run the test case through libra workflow, yielding the number of fixedpoint rounds
     fn run_libra(
         &self,
         ctxt: &Context,
         workdir: &Path,
-    ) -> Result<(String, Option<EngineResult<()>>)>;
+        depth: Option<usize>,
+    ) -> Result<(String, Option<EngineResult<usize>>)> {
+        let result = match self.compile(ctxt, workdir)? {
+            None => None,
+            Some(Err(err)) => Some(Err(err)),
+            Some(Ok(bitcode)) => Some(self.analyze(ctxt, &bitcode, workdir, depth)),
+        };
+        Ok((self.name().to_string(), result))
+    }
 }
 
 /// A trait that marks a test suite
@@ -44,11 +92,20 @@ pub trait TestSuite<C: TestCase, R: Resolver> {
     /// Location of the workspace from the studio
     fn wks_path_from_studio() -> &'static [&'static str];
 
+    /// Depth of fixedpoint optimization applied to each test case
+    fn fixedpoint_depth() -> Option<usize>;
+
     /// Test case discovery
     fn discover_test_cases(repo: &GitRepo, resolver: &R) -> Result<Vec<C>>;
 
     /// Run the test suite
-    fn run(repo: GitRepo, resolver: R, force: bool, filter: Vec<String>) -> Result<()> {
+    fn run(
+        repo: GitRepo,
+        resolver: R,
+        force: bool,
+        filter: Vec<String>,
+        bless: bool,
+    ) -> Result<()> {
         // prepare the environment
         let mut workdir = PATH_STUDIO.to_path_buf();
         workdir.extend(Self::wks_path_from_studio());
@@ -66,31 +123,50 @@ pub trait TestSuite<C: TestCase, R: Resolver> {
         info!("Number of test cases discovered: {}", test_cases.len());
 
         // run the tests
-        let ctxt = Context::new()?;
+        let ctxt = new_context()?;
+        let selective = !filter.is_empty();
         let consolidated: Vec<_> = if *PARALLEL && filter.is_empty() {
-            test_cases
+            // phase 1: compilation is memory-hungry, hence bounded parallelism
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(*COMPILE_JOBS)
+                .build()?;
+            info!("Compiling test cases with {} jobs", *COMPILE_JOBS);
+            let compiled: Vec<_> = pool.install(|| {
+                test_cases
+                    .into_par_iter()
+                    .map(|test| {
+                        if HALT_PARALLEL_EXECUTION.load(Ordering::SeqCst) {
+                            // not executing this one
+                            return Ok((test, None));
+                        }
+                        let output = test.compile(&ctxt, &workdir)?;
+                        if !keep_parallel_result(&output) {
+                            return Ok((test, None));
+                        }
+                        Ok((test, output))
+                    })
+                    .collect::<Result<_>>()
+            })?;
+
+            // phase 2: analysis with full parallelism
+            compiled
                 .into_par_iter()
-                .map(|test| {
+                .map(|(test, compiled)| {
+                    let bitcode = match compiled {
+                        None => return Ok((test.name().to_string(), None)),
+                        Some(Err(err)) => return Ok((test.name().to_string(), Some(Err(err)))),
+                        Some(Ok(bitcode)) => bitcode,
+                    };
                     if HALT_PARALLEL_EXECUTION.load(Ordering::SeqCst) {
                         // not executing this one
                         return Ok((test.name().to_string(), None));
                     }
-                    let (name, output) = test.run_libra(&ctxt, &workdir)?;
-                    match shall_halt(&output) {
-                        None => (),
-                        Some(message) => {
-                            if !*CONTINUE {
-                                if HALT_PARALLEL_EXECUTION.swap(true, Ordering::SeqCst) {
-                                    // not reporting this one
-                                    return Ok((test.name().to_string(), None));
-                                } else {
-                                    // report this one and we have marked the execution to halt
-                                    error!("potential bug: {}", message);
-                                }
-                            }
-                        }
+                    let output =
+                        Some(test.analyze(&ctxt, &bitcode, &workdir, Self::fixedpoint_depth()));
+                    if !keep_parallel_result(&output) {
+                        return Ok((test.name().to_string(), None));
                     }
-                    Ok((name, output))
+                    Ok((test.name().to_string(), output))
                 })
                 .collect::<Result<_>>()?
         } else {
@@ -102,7 +178,7 @@ pub trait TestSuite<C: TestCase, R: Resolver> {
                 }
 
                 // actual execution
-                let (name, output) = test.run_libra(&ctxt, &workdir)?;
+                let (name, output) = test.run_libra(&ctxt, &workdir, Self::fixedpoint_depth())?;
 
                 // check errors
                 match shall_halt(&output) {
@@ -128,16 +204,137 @@ pub trait TestSuite<C: TestCase, R: Resolver> {
         summary.save(&path_summary)?;
         info!("Summary saved at: {}", path_summary.to_string_lossy());
 
+        // analyze the distribution of fixedpoint rounds
+        let suite = Self::wks_path_from_studio()
+            .last()
+            .copied()
+            .unwrap_or("unknown");
+        let distribution = Distribution::new(&summary.rounds);
+        distribution.show();
+        distribution.save(&workdir)?;
+        distribution.compare(suite, selective)?;
+        if bless {
+            distribution.bless(suite)?;
+        }
+
+        // record the run in the history
+        let record = RunRecord::new(
+            &ctxt,
+            suite,
+            &repo,
+            Self::fixedpoint_depth(),
+            selective,
+            &summary,
+        )?;
+        history::append(&record)?;
+
         // done
         Ok(())
     }
+
+    /// Run each test case at a range of fixedpoint depths and record where it stabilizes
+    fn sweep(
+        repo: GitRepo,
+        resolver: R,
+        force: bool,
+        filter: Vec<String>,
+        depths: DepthRange,
+    ) -> Result<BTreeMap<String, SweepRecord>> {
+        // prepare the environment
+        let suite = Self::wks_path_from_studio()
+            .last()
+            .copied()
+            .unwrap_or("unknown");
+        let mut workdir = PATH_STUDIO.to_path_buf();
+        workdir.extend(PATH_SWEEP);
+        workdir.push(suite);
+        if workdir.exists() {
+            if !force {
+                info!("Prior sweep result exists");
+                let content = fs::read_to_string(workdir.join("sweep.json"))?;
+                return Ok(serde_json::from_str(&content)?);
+            }
+            fs::remove_dir_all(&workdir)?;
+        }
+        fs::create_dir_all(&workdir)?;
+
+        // information collection
+        let test_cases: Vec<_> = Self::discover_test_cases(&repo, &resolver)?
+            .into_iter()
+            .filter(|test| filter.is_empty() || filter.iter().any(|v| v == test.name()))
+            .collect();
+        info!(
+            "Sweeping {} test cases over depths {}..{}",
+            test_cases.len(),
+            depths.start(),
+            depths.end()
+        );
+
+        // compile once and analyze at every depth
+        let ctxt = new_context()?;
+        let sweep_one = |test: C| -> Result<Option<(String, SweepRecord)>> {
+            let name = test.name().to_string();
+            let bitcode = match test.compile(&ctxt, &workdir)? {
+                None | Some(Err(_)) => return Ok(None),
+                Some(Ok(bitcode)) => bitcode,
+            };
+            let mut outcomes = BTreeMap::new();
+            for depth in depths.clone() {
+                let output = workdir.join(format!("depth-{}", depth));
+                fs::create_dir_all(output.join(&name))?;
+                let result = test.analyze(&ctxt, &bitcode, &output, Some(depth));
+                outcomes.insert(depth, DepthOutcome::from_result(&result));
+            }
+            Ok(Some((name, SweepRecord::new(outcomes))))
+        };
+        let records: Vec<_> = if *PARALLEL {
+            test_cases
+                .into_par_iter()
+                .map(&sweep_one)
+                .collect::<Result<_>>()?
+        } else {
+            test_cases
+                .into_iter()
+                .map(&sweep_one)
+                .collect::<Result<_>>()?
+        };
+        let records: BTreeMap<_, _> = records.into_iter().flatten().collect();
+
+        // report the result
+        sweep::report(&records, &depths, &workdir)?;
+        Ok(records)
+    }
+}
+
+/// A utility to decide whether a result from parallel execution should be reported,
+/// marking the execution to halt on the first potential bug
+fn keep_parallel_result<T>(output: &Option<EngineResult<T>>) -> bool {
+    match shall_halt(output) {
+        None => true,
+        Some(message) => {
+            if *CONTINUE {
+                return true;
+            }
+            if HALT_PARALLEL_EXECUTION.swap(true, Ordering::SeqCst) {
+                // not reporting this one
+                false
+            } else {
+                // report this one and we have marked the execution to halt
+                error!("potential bug: {}", message);
+                true
+            }
+        }
+    }
 }
 
 /// A utility to check whether this error means a potential bug
 fn shall_halt<T>(output: &Option<EngineResult<T>>) -> Option<&str> {
     match output.as_ref()?.as_ref().err()? {
-        EngineError::NotSupportedYet(_) | EngineError::CompilationError(_) => None,
+        EngineError::NotSupportedYet(_)
+        | EngineError::CompilationError(_)
+        | EngineError::ToolTimeout(_) => None,
         EngineError::LLVMLoadingError(reason)
+        | EngineError::OracleCrash(reason)
         | EngineError::InvalidAssumption(reason)
         | EngineError::InvariantViolation(reason) => Some(reason),
     }
@@ -150,13 +347,19 @@ pub struct Summary {
     skipped: Vec<String>,
     failed_compile: Vec<String>,
     failed_loading: Vec<String>,
+    #[serde(default)]
+    failed_crash: Vec<String>,
     failed_invariant: Vec<String>,
     failed_assumption: Vec<String>,
+    #[serde(default)]
+    failed_timeout: Vec<String>,
     failed_unsupported: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    rounds: Rounds,
 }
 
 impl Summary {
-    pub fn new(consolidated: Vec<(String, Option<EngineResult<()>>)>) -> Self {
+    pub fn new(consolidated: Vec<(String, Option<EngineResult<usize>>)>) -> Self {
         let size = consolidated.len();
 
         // split the results
@@ -164,16 +367,22 @@ impl Summary {
         let mut skipped = vec![];
         let mut failed_compile = vec![];
         let mut failed_loading = vec![];
+        let mut failed_crash = vec![];
         let mut failed_invariant = vec![];
         let mut failed_assumption = vec![];
+        let mut failed_timeout = vec![];
         let mut failed_unsupported = BTreeMap::new();
+        let mut rounds = Rounds::new();
 
         let mut name_set = BTreeSet::new();
         for (name, result) in consolidated {
             name_set.insert(name.clone());
             match result {
                 None => skipped.push(name),
-                Some(Ok(_)) => passed.push(name),
+                Some(Ok(count)) => {
+                    rounds.insert(name.clone(), count);
+                    passed.push(name);
+                }
                 Some(Err(err)) => match err {
                     // potential setup issue
                     EngineError::CompilationError(_) => {
@@ -190,6 +399,9 @@ impl Summary {
                     EngineError::LLVMLoadingError(_) => {
                         failed_loading.push(name);
                     }
+                    EngineError::OracleCrash(_) => {
+                        failed_crash.push(name);
+                    }
                     // potential bugs with the backend
                     EngineError::InvariantViolation(_) => {
                         failed_invariant.push(name);
@@ -197,6 +409,10 @@ impl Summary {
                     EngineError::InvalidAssumption(_) => {
                         failed_assumption.push(name);
                     }
+                    // pathological inputs to the tools
+                    EngineError::ToolTimeout(_) => {
+                        failed_timeout.push(name);
+                    }
                 },
             }
         }
@@ -214,15 +430,39 @@ impl Summary {
             skipped,
             failed_compile,
             failed_loading,
+            failed_crash,
             failed_invariant,
             failed_assumption,
+            failed_timeout,
             failed_unsupported: failed_unsupported
                 .into_iter()
                 .map(|(k, v)| (k.to_string(), v))
                 .collect(),
+            rounds,
         }
     }
 
+    /// Number of test cases per outcome
+    pub fn counts(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        counts.insert("passed".to_string(), self.passed.len());
+        counts.insert("skipped".to_string(), self.skipped.len());
+        counts.insert("failed_compile".to_string(), self.failed_compile.len());
+        counts.insert("failed_loading".to_string(), self.failed_loading.len());
+        counts.insert("failed_crash".to_string(), self.failed_crash.len());
+        counts.insert("failed_invariant".to_string(), self.failed_invariant.len());
+        counts.insert(
+            "failed_assumption".to_string(),
+            self.failed_assumption.len(),
+        );
+        counts.insert("failed_timeout".to_string(), self.failed_timeout.len());
+        counts.insert(
+            "unsupported".to_string(),
+            self.failed_unsupported.values().map(|v| v.len()).sum(),
+        );
+        counts
+    }
+
     pub fn save(&self, path: &Path) -> Result<()> {
         let content = serde_json::to_string_pretty(self)?;
         fs::write(path, content)?;
@@ -240,12 +480,18 @@ impl Summary {
         if !self.failed_loading.is_empty() {
             println!("failed [loading]: {}", self.failed_loading.len());
         }
+        if !self.failed_crash.is_empty() {
+            println!("failed [crash]: {}", self.failed_crash.len());
+        }
         if !self.failed_invariant.is_empty() {
             println!("failed [invariant]: {}", self.failed_invariant.len());
         }
         if !self.failed_assumption.is_empty() {
             println!("failed [assumption]: {}", self.failed_assumption.len());
         }
+        if !self.failed_timeout.is_empty() {
+            println!("failed [timeout]: {}", self.failed_timeout.len());
+        }
         println!(
             "unsupported: {}",
             self.failed_unsupported
@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+
+use libra_engine::error::EngineError;
+use libra_example::run_workflow;
+use libra_shared::config::PATH_STUDIO;
+
+static PATH_WORKSPACE: [&str; 2] = ["testsuite", "apps"];
+
+/// Name of the baseline file, located at the root of this crate
+static BASELINE: &str = "apps.json";
+
+/// Outcome of running an example workflow end-to-end
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Passed,
+    FailedCompile,
+    FailedLoading,
+    FailedCrash,
+    FailedInvariant,
+    FailedAssumption,
+    FailedTimeout,
+    Unsupported,
+    /// failure in the workflow itself (e.g., build or merge)
+    FailedWorkflow,
+}
+
+impl Outcome {
+    fn from_result(result: &Result<()>) -> Self {
+        let err = match result {
+            Ok(_) => return Self::Passed,
+            Err(err) => err,
+        };
+        match err.downcast_ref::<EngineError>() {
+            None => Self::FailedWorkflow,
+            Some(EngineError::CompilationError(_)) => Self::FailedCompile,
+            Some(EngineError::LLVMLoadingError(_)) => Self::FailedLoading,
+            Some(EngineError::OracleCrash(_)) => Self::FailedCrash,
+            Some(EngineError::InvariantViolation(_)) => Self::FailedInvariant,
+            Some(EngineError::InvalidAssumption(_)) => Self::FailedAssumption,
+            Some(EngineError::ToolTimeout(_)) => Self::FailedTimeout,
+            Some(EngineError::NotSupportedYet(_)) => Self::Unsupported,
+        }
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let repr = match self {
+            Self::Passed => "passed",
+            Self::FailedCompile => "failed [compile]",
+            Self::FailedLoading => "failed [loading]",
+            Self::FailedCrash => "failed [crash]",
+            Self::FailedInvariant => "failed [invariant]",
+            Self::FailedAssumption => "failed [assumption]",
+            Self::FailedTimeout => "failed [timeout]",
+            Self::Unsupported => "unsupported",
+            Self::FailedWorkflow => "failed [workflow]",
+        };
+        write!(f, "{}", repr)
+    }
+}
+
+/// Expected outcomes, keyed by `<app>:<workflow>`
+type Baseline = BTreeMap<String, Outcome>;
+
+fn path_baseline() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(BASELINE)
+}
+
+fn load_baseline() -> Result<Baseline> {
+    let path = path_baseline();
+    if !path.exists() {
+        return Ok(Baseline::new());
+    }
+    let content = fs::read_to_string(path)?;
+    let baseline = serde_json::from_str(&content)?;
+    Ok(baseline)
+}
+
+/// Normalize a selection item into the `<app>:<workflow>` form
+fn normalize_target(item: &str) -> String {
+    if item.contains(':') {
+        item.to_string()
+    } else {
+        format!("{}:default", item)
+    }
+}
+
+/// Run one example workflow, in the form of `<app>[:<workflow>]`, end-to-end
+pub fn run_target(target: &str, force: bool) -> Outcome {
+    let target = normalize_target(target);
+    let (app, name) = target.split_once(':').expect("normalized target");
+    info!("Running example workflow: {}", target);
+    let result = run_workflow(app, name, force);
+    if let Err(err) = &result {
+        info!("Example workflow {} failed: {}", target, err);
+    }
+    Outcome::from_result(&result)
+}
+
+/// Run the example workflows end-to-end and compare the outcomes against the baseline
+pub fn run(force: bool, selection: Vec<String>, bless: bool) -> Result<()> {
+    let mut baseline = load_baseline()?;
+
+    // by default, run every workflow recorded in the baseline
+    let targets: Vec<_> = if selection.is_empty() {
+        baseline.keys().cloned().collect()
+    } else {
+        selection.iter().map(|e| normalize_target(e)).collect()
+    };
+    info!("Number of example workflows selected: {}", targets.len());
+
+    // run the workflows
+    let mut results = BTreeMap::new();
+    for target in targets {
+        let outcome = run_target(&target, force);
+        results.insert(target, outcome);
+    }
+
+    // compare against the baseline
+    let mut deviations = 0;
+    for (target, outcome) in &results {
+        match baseline.get(target) {
+            None => println!("{}: {} (no baseline)", target, outcome),
+            Some(expected) if expected == outcome => println!("{}: {}", target, outcome),
+            Some(expected) => {
+                deviations += 1;
+                error!("{}: {} (expected: {})", target, outcome, expected);
+            }
+        }
+    }
+
+    // save the result
+    let mut workdir = PATH_STUDIO.to_path_buf();
+    workdir.extend(PATH_WORKSPACE);
+    fs::create_dir_all(&workdir)?;
+    let path_summary = workdir.join("summary.json");
+    fs::write(&path_summary, serde_json::to_string_pretty(&results)?)?;
+    info!("Summary saved at: {}", path_summary.to_string_lossy());
+
+    // update the baseline if requested
+    if bless {
+        baseline.extend(results);
+        fs::write(path_baseline(), serde_json::to_string_pretty(&baseline)?)?;
+        info!("Baseline updated");
+        return Ok(());
+    }
+    if deviations != 0 {
+        bail!(
+            "{} example workflow(s) deviate from the baseline",
+            deviations
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use libra_engine::flow::shared::Context;
+use libra_shared::config::PATH_STUDIO;
+use libra_shared::git::GitRepo;
+
+use crate::common::Summary;
+
+static PATH_HISTORY: [&str; 2] = ["testsuite", "history.jsonl"];
+
+/// Metadata and results of one testsuite run
+#[derive(Serialize, Deserialize)]
+pub struct RunRecord {
+    /// seconds since the UNIX epoch
+    timestamp: u64,
+    /// name of the test suite
+    suite: String,
+    /// version of the LLVM toolchain
+    llvm_version: String,
+    /// commit of the libra repository
+    engine_commit: String,
+    /// commit of the test suite repository
+    suite_commit: String,
+    /// depth of fixedpoint optimization
+    depth: Option<usize>,
+    /// whether only selective test cases were run
+    selective: bool,
+    /// number of test cases per outcome
+    counts: BTreeMap<String, usize>,
+}
+
+impl RunRecord {
+    pub fn new(
+        ctxt: &Context,
+        suite: &str,
+        repo: &GitRepo,
+        depth: Option<usize>,
+        selective: bool,
+        summary: &Summary,
+    ) -> Result<Self> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let engine_commit = GitRepo::new(PathBuf::from(env!("CARGO_MANIFEST_DIR")), None)
+            .map_or_else(|_| "unknown".to_string(), |r| r.commit().to_string());
+        Ok(Self {
+            timestamp,
+            suite: suite.to_string(),
+            llvm_version: ctxt
+                .llvm_version()
+                .unwrap_or_else(|_| "unknown".to_string()),
+            engine_commit,
+            suite_commit: repo.commit().to_string(),
+            depth,
+            selective,
+            counts: summary.counts(),
+        })
+    }
+
+    /// Ratio of passed test cases among the ones executed
+    fn pass_rate(&self) -> Option<f64> {
+        let passed = self.counts.get("passed").copied().unwrap_or(0);
+        let skipped = self.counts.get("skipped").copied().unwrap_or(0);
+        let executed = self.counts.values().sum::<usize>() - skipped;
+        if executed == 0 {
+            None
+        } else {
+            Some(passed as f64 / executed as f64)
+        }
+    }
+}
+
+fn path_history() -> PathBuf {
+    let mut path = PATH_STUDIO.to_path_buf();
+    path.extend(PATH_HISTORY);
+    path
+}
+
+/// Append a record to the history database
+pub fn append(record: &RunRecord) -> Result<()> {
+    let path = path_history();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+fn load(path: &Path) -> Result<Vec<RunRecord>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let content = fs::read_to_string(path)?;
+    let mut records = vec![];
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        records.push(serde_json::from_str(line)?);
+    }
+    Ok(records)
+}
+
+/// Report the history of test suite runs, optionally filtered by suite
+pub fn report(suite: Option<&str>, last: Option<usize>) -> Result<()> {
+    let mut records = load(&path_history())?;
+    if let Some(name) = suite {
+        records.retain(|r| r.suite == name);
+    }
+    if let Some(n) = last {
+        let skip = records.len().saturating_sub(n);
+        records.drain(..skip);
+    }
+
+    // track the trend per suite on full runs only
+    let mut previous: BTreeMap<&str, f64> = BTreeMap::new();
+    for record in &records {
+        let rate = record.pass_rate();
+        let trend = match (rate, previous.get(record.suite.as_str())) {
+            (Some(cur), Some(prev)) if !record.selective => {
+                format!("{:+.2}%", (cur - prev) * 100.0)
+            }
+            _ => "-".to_string(),
+        };
+        if let (Some(cur), false) = (rate, record.selective) {
+            previous.insert(&record.suite, cur);
+        }
+
+        println!(
+            "{} {} llvm={} engine={} depth={} passed={}/{} rate={} trend={}{}",
+            record.timestamp,
+            record.suite,
+            record.llvm_version,
+            record
+                .engine_commit
+                .get(..8)
+                .unwrap_or(&record.engine_commit),
+            record
+                .depth
+                .map_or_else(|| "none".to_string(), |d| d.to_string()),
+            record.counts.get("passed").copied().unwrap_or(0),
+            record.counts.values().sum::<usize>(),
+            rate.map_or_else(|| "-".to_string(), |r| format!("{:.2}%", r * 100.0)),
+            trend,
+            if record.selective { " (selective)" } else { "" },
+        );
+    }
+    Ok(())
+}
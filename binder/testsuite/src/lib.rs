@@ -1,8 +1,14 @@
 mod common;
+mod example_apps;
+mod history;
 mod llvm_external;
 mod llvm_internal;
+mod rounds;
+mod sweep;
 
-use anyhow::Result;
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
 use structopt::StructOpt;
 
 use libra_shared::config::initialize;
@@ -11,6 +17,10 @@ use libra_shared::dep::{DepState, Dependency, Resolver};
 use crate::common::{TestCase, TestSuite};
 use crate::llvm_external::{DepLLVMExternal, ResolverLLVMExternal, TestCaseExternal};
 use crate::llvm_internal::{DepLLVMInternal, ResolverLLVMInternal, TestCaseInternal};
+use crate::sweep::parse_depth_range;
+
+pub use crate::example_apps::{run_target as run_example_app, Outcome as AppOutcome};
+pub use crate::sweep::{DepthOutcome, DepthRange, SweepRecord};
 
 #[derive(StructOpt)]
 enum Command {
@@ -33,6 +43,14 @@ enum Command {
         /// Run selective test cases only
         #[structopt(short, long)]
         selection: Vec<String>,
+
+        /// Record the fixedpoint round counts as the new baseline
+        #[structopt(long)]
+        bless: bool,
+
+        /// Run each test case over a range of fixedpoint depths, e.g., 1..8
+        #[structopt(long, parse(try_from_str = parse_depth_range))]
+        depth_sweep: Option<DepthRange>,
     },
 }
 
@@ -46,19 +64,58 @@ impl Command {
             Self::Build { force } => {
                 state.build(force)?;
             }
-            Self::Run { force, selection } => {
+            Self::Run {
+                force,
+                selection,
+                bless,
+                depth_sweep,
+            } => {
                 let (repo, resolver) = state.into_source_and_artifact()?;
-                T::run(repo, resolver, force, selection)?;
+                match depth_sweep {
+                    None => T::run(repo, resolver, force, selection, bless)?,
+                    Some(depths) => {
+                        T::sweep(repo, resolver, force, selection, depths)?;
+                    }
+                }
             }
         }
         Ok(())
     }
 }
 
+#[derive(StructOpt)]
+enum AppsCommand {
+    /// Run the example workflows and compare the outcomes against the baseline
+    Run {
+        /// Force the workflows to start from scratch
+        #[structopt(short, long)]
+        force: bool,
+
+        /// Run selective workflows only, in the form of <app>[:<workflow>]
+        #[structopt(short, long)]
+        selection: Vec<String>,
+
+        /// Record the outcomes as the new baseline
+        #[structopt(long)]
+        bless: bool,
+    },
+}
+
 #[derive(StructOpt)]
 enum Suite {
     External(Command),
     Internal(Command),
+    Apps(AppsCommand),
+    /// Report the history of test suite runs
+    History {
+        /// Only report runs of this suite
+        #[structopt(short, long)]
+        suite: Option<String>,
+
+        /// Only report the last N runs
+        #[structopt(short, long)]
+        last: Option<usize>,
+    },
 }
 
 #[derive(StructOpt)]
@@ -73,6 +130,34 @@ struct Args {
     suite: Suite,
 }
 
+/// Sweep a test suite over a range of fixedpoint depths, yielding the record per test case
+pub fn sweep_suite(
+    suite: &str,
+    force: bool,
+    selection: Vec<String>,
+    depths: DepthRange,
+) -> Result<BTreeMap<String, SweepRecord>> {
+    match suite {
+        "external" => sweep_with::<TestCaseExternal, ResolverLLVMExternal, DepLLVMExternal>(
+            force, selection, depths,
+        ),
+        "internal" => sweep_with::<TestCaseInternal, ResolverLLVMInternal, DepLLVMInternal>(
+            force, selection, depths,
+        ),
+        _ => bail!("unknown test suite: {}", suite),
+    }
+}
+
+fn sweep_with<C: TestCase, R: Resolver, T: Dependency<R> + TestSuite<C, R>>(
+    force: bool,
+    selection: Vec<String>,
+    depths: DepthRange,
+) -> Result<BTreeMap<String, SweepRecord>> {
+    let state: DepState<R, T> = DepState::new()?;
+    let (repo, resolver) = state.into_source_and_artifact()?;
+    T::sweep(repo, resolver, force, selection, depths)
+}
+
 /// Main entrypoint
 pub fn entrypoint() -> Result<()> {
     let args = Args::from_args();
@@ -88,5 +173,11 @@ pub fn entrypoint() -> Result<()> {
         Suite::Internal(command) => {
             command.run_internal::<TestCaseInternal, ResolverLLVMInternal, DepLLVMInternal>()
         }
+        Suite::Apps(AppsCommand::Run {
+            force,
+            selection,
+            bless,
+        }) => example_apps::run(force, selection, bless),
+        Suite::History { suite, last } => history::report(suite.as_deref(), last),
     }
 }
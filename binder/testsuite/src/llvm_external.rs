@@ -123,6 +123,10 @@ impl TestSuite<TestCaseExternal, ResolverLLVMExternal> for DepLLVMExternal {
         PATH_WORKSPACE.as_ref()
     }
 
+    fn fixedpoint_depth() -> Option<usize> {
+        Some(MAX_ROUNDS_OF_FIXEDPOINT_OPTIMIZATION)
+    }
+
     fn discover_test_cases(
         _repo: &GitRepo,
         resolver: &ResolverLLVMExternal,
@@ -309,31 +313,19 @@ pub struct TestCaseExternal {
 }
 
 impl TestCaseExternal {
-    /// Run libra engine
-    fn libra_workflow(
+    /// Compile the input into bitcode
+    fn compile_bitcode(
         ctxt: &Context,
         command: &ClangCommand,
         input: &Path,
         output: &Path,
-    ) -> EngineResult<()> {
-        // compile
+    ) -> EngineResult<PathBuf> {
         let bc_init = output.join("init.bc");
         ctxt.compile_to_bitcode(input, &bc_init, command.gen_args_for_libra())
             .map_err(|e| EngineError::CompilationError(format!("Error during clang: {}", e)))?;
         ctxt.disassemble_in_place(&bc_init)
             .map_err(|e| EngineError::CompilationError(format!("Error during disas: {}", e)))?;
-
-        // fixedpoint
-        let flow_fp = FlowFixedpoint::new(
-            ctxt,
-            bc_init,
-            output.to_path_buf(),
-            Some(MAX_ROUNDS_OF_FIXEDPOINT_OPTIMIZATION),
-        );
-        flow_fp.execute()?;
-
-        // done with everything
-        Ok(())
+        Ok(bc_init)
     }
 }
 
@@ -342,11 +334,7 @@ impl TestCase for TestCaseExternal {
         &self.name
     }
 
-    fn run_libra(
-        &self,
-        ctxt: &Context,
-        workdir: &Path,
-    ) -> Result<(String, Option<EngineResult<()>>)> {
+    fn compile(&self, ctxt: &Context, workdir: &Path) -> Result<Option<EngineResult<PathBuf>>> {
         let Self {
             name,
             _path: _,
@@ -355,7 +343,7 @@ impl TestCase for TestCaseExternal {
 
         // filter ignored cases
         if IGNORED_TEST_CASES.contains(&name.as_str()) {
-            return Ok((name.to_string(), None));
+            return Ok(None);
         }
 
         // TODO: support other languages like ObjC
@@ -365,7 +353,7 @@ impl TestCase for TestCaseExternal {
                 ClangSupportedLanguage::C
                 | ClangSupportedLanguage::CPP
                 | ClangSupportedLanguage::Bitcode => (),
-                _ => return Ok((name.to_string(), None)),
+                _ => return Ok(None),
             },
         }
 
@@ -378,7 +366,7 @@ impl TestCase for TestCaseExternal {
         let input = inputs.into_iter().next().unwrap();
 
         // report progress
-        debug!("running test case: {}", name);
+        debug!("compiling test case: {}", name);
 
         // prepare output directory
         let output_dir = workdir.join(name);
@@ -388,11 +376,28 @@ impl TestCase for TestCaseExternal {
         let cursor = env::current_dir()?;
         env::set_current_dir(&command.workdir)?;
 
-        // workflow
-        let result = Self::libra_workflow(ctxt, command, Path::new(input), &output_dir);
+        // compile
+        let result = Self::compile_bitcode(ctxt, command, Path::new(input), &output_dir);
 
         // clean-up
         env::set_current_dir(cursor)?;
-        Ok((name.to_string(), Some(result)))
+        Ok(Some(result))
+    }
+
+    fn analyze(
+        &self,
+        ctxt: &Context,
+        bitcode: &Path,
+        workdir: &Path,
+        depth: Option<usize>,
+    ) -> EngineResult<usize> {
+        // report progress
+        debug!("analyzing test case: {}", self.name);
+
+        // fixedpoint
+        let flow_fp =
+            FlowFixedpoint::new(ctxt, bitcode.to_path_buf(), workdir.join(&self.name), depth);
+        let trace = flow_fp.execute()?;
+        Ok(trace.len())
     }
 }
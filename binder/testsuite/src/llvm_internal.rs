@@ -70,6 +70,10 @@ impl TestSuite<TestCaseInternal, ResolverLLVMInternal> for DepLLVMInternal {
         PATH_WORKSPACE.as_ref()
     }
 
+    fn fixedpoint_depth() -> Option<usize> {
+        Some(MAX_ROUNDS_OF_FIXEDPOINT_OPTIMIZATION)
+    }
+
     fn discover_test_cases(
         repo: &GitRepo,
         resolver: &ResolverLLVMInternal,
@@ -248,34 +252,16 @@ pub struct TestCaseInternal {
     path: PathBuf,
 }
 
-impl TestCaseInternal {
-    fn libra_workflow(ctxt: &Context, input: &Path, output: &Path) -> EngineResult<()> {
-        // fixedpoint
-        let flow_fp = FlowFixedpoint::new(
-            ctxt,
-            input.to_path_buf(),
-            output.to_path_buf(),
-            Some(MAX_ROUNDS_OF_FIXEDPOINT_OPTIMIZATION),
-        );
-        flow_fp.execute()?;
-        Ok(())
-    }
-}
-
 impl TestCase for TestCaseInternal {
     fn name(&self) -> &str {
         &self.name
     }
 
-    fn run_libra(
-        &self,
-        ctxt: &Context,
-        workdir: &Path,
-    ) -> Result<(String, Option<EngineResult<()>>)> {
+    fn compile(&self, ctxt: &Context, workdir: &Path) -> Result<Option<EngineResult<PathBuf>>> {
         let Self { name, path } = self;
 
         // report progress
-        debug!("running test case: {}", name);
+        debug!("compiling test case: {}", name);
 
         // prepare output directory
         let output_dir = workdir.join(name);
@@ -290,12 +276,26 @@ impl TestCase for TestCaseInternal {
             Ok(_) => (),
             Err(e) => {
                 warn!("unable to validate bitcode {}: {}", name, e);
-                return Ok((name.to_string(), None));
+                return Ok(None);
             }
         }
+        Ok(Some(Ok(path_bc_init)))
+    }
+
+    fn analyze(
+        &self,
+        ctxt: &Context,
+        bitcode: &Path,
+        workdir: &Path,
+        depth: Option<usize>,
+    ) -> EngineResult<usize> {
+        // report progress
+        debug!("analyzing test case: {}", self.name);
 
-        // workflow
-        let result = Self::libra_workflow(ctxt, &path_bc_init, &output_dir);
-        Ok((name.to_string(), Some(result)))
+        // fixedpoint
+        let flow_fp =
+            FlowFixedpoint::new(ctxt, bitcode.to_path_buf(), workdir.join(&self.name), depth);
+        let trace = flow_fp.execute()?;
+        Ok(trace.len())
     }
 }
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use log::{info, warn};
+
+/// Width of the longest bar in the text histogram
+static HISTOGRAM_WIDTH: usize = 60;
+
+/// Number of fixedpoint rounds per passed test case
+pub type Rounds = BTreeMap<String, usize>;
+
+/// Location of the checked-in baseline for a suite, at the root of this crate
+fn path_baseline(suite: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("rounds-{}.json", suite))
+}
+
+fn load_baseline(suite: &str) -> Result<Rounds> {
+    let path = path_baseline(suite);
+    if !path.exists() {
+        return Ok(Rounds::new());
+    }
+    let content = fs::read_to_string(path)?;
+    let baseline = serde_json::from_str(&content)?;
+    Ok(baseline)
+}
+
+/// Distribution of fixedpoint round counts across test cases
+pub struct Distribution<'a> {
+    rounds: &'a Rounds,
+    histogram: BTreeMap<usize, usize>,
+}
+
+impl<'a> Distribution<'a> {
+    pub fn new(rounds: &'a Rounds) -> Self {
+        let mut histogram = BTreeMap::new();
+        for count in rounds.values() {
+            *histogram.entry(*count).or_insert(0) += 1;
+        }
+        Self { rounds, histogram }
+    }
+
+    /// Print the distribution as a text histogram
+    pub fn show(&self) {
+        if self.histogram.is_empty() {
+            return;
+        }
+        let peak = self.histogram.values().copied().max().unwrap_or(0);
+        println!("fixedpoint rounds:");
+        for (count, num) in &self.histogram {
+            let width = (num * HISTOGRAM_WIDTH).div_ceil(peak);
+            println!(
+                "  {:>3} | {:<w$} {}",
+                count,
+                "#".repeat(width),
+                num,
+                w = HISTOGRAM_WIDTH
+            );
+        }
+    }
+
+    /// Save the per-test round counts and the histogram as CSV files
+    pub fn save(&self, workdir: &Path) -> Result<()> {
+        let mut content = String::from("test,rounds\n");
+        for (name, count) in self.rounds {
+            content.push_str(&format!("\"{}\",{}\n", name.replace('"', "\"\""), count));
+        }
+        let path_rounds = workdir.join("rounds.csv");
+        fs::write(&path_rounds, content)?;
+
+        let mut content = String::from("rounds,tests\n");
+        for (count, num) in &self.histogram {
+            content.push_str(&format!("{},{}\n", count, num));
+        }
+        let path_histogram = workdir.join("rounds-histogram.csv");
+        fs::write(&path_histogram, content)?;
+
+        info!(
+            "Round counts saved at: {} and {}",
+            path_rounds.to_string_lossy(),
+            path_histogram.to_string_lossy()
+        );
+        Ok(())
+    }
+
+    /// Flag test cases whose round count differs from the baseline of the suite
+    pub fn compare(&self, suite: &str, selective: bool) -> Result<()> {
+        let baseline = load_baseline(suite)?;
+        if baseline.is_empty() {
+            info!("No round count baseline for suite {}", suite);
+            return Ok(());
+        }
+
+        let mut changed = 0;
+        for (name, count) in self.rounds {
+            match baseline.get(name) {
+                None => (),
+                Some(expected) if expected == count => (),
+                Some(expected) => {
+                    changed += 1;
+                    warn!("{}: {} rounds (baseline: {})", name, count, expected);
+                }
+            }
+        }
+        let missing = if selective {
+            0
+        } else {
+            baseline
+                .keys()
+                .filter(|name| !self.rounds.contains_key(*name))
+                .count()
+        };
+        println!(
+            "rounds changed versus baseline: {}, no longer passing: {}",
+            changed, missing
+        );
+        Ok(())
+    }
+
+    /// Record the round counts as the new baseline of the suite
+    pub fn bless(&self, suite: &str) -> Result<()> {
+        let mut baseline = load_baseline(suite)?;
+        baseline.extend(self.rounds.iter().map(|(k, v)| (k.clone(), *v)));
+        fs::write(
+            path_baseline(suite),
+            serde_json::to_string_pretty(&baseline)?,
+        )?;
+        info!("Round count baseline updated for suite {}", suite);
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Result};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use libra_engine::error::{EngineError, EngineResult};
+
+/// An inclusive range of fixedpoint depths
+pub type DepthRange = RangeInclusive<usize>;
+
+/// Parse a depth range in the form of `<from>..<to>` (inclusive on both ends)
+pub fn parse_depth_range(repr: &str) -> Result<DepthRange> {
+    let (from, to) = repr
+        .split_once("..")
+        .ok_or_else(|| anyhow!("expect a depth range in the form of <from>..<to>"))?;
+    let from: usize = from.trim().parse()?;
+    let to: usize = to.trim_start_matches('=').trim().parse()?;
+    if from == 0 || from > to {
+        bail!("invalid depth range: {}", repr);
+    }
+    Ok(from..=to)
+}
+
+/// Outcome of running a test case at a specific depth
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum DepthOutcome {
+    /// number of modules in the optimization trace
+    Rounds(usize),
+    /// category of the failure
+    Failed(String),
+}
+
+impl DepthOutcome {
+    pub fn from_result(result: &EngineResult<usize>) -> Self {
+        match result {
+            Ok(rounds) => Self::Rounds(*rounds),
+            Err(err) => {
+                let category = match err {
+                    EngineError::CompilationError(_) => "compile".to_string(),
+                    EngineError::LLVMLoadingError(_) => "loading".to_string(),
+                    EngineError::OracleCrash(_) => "crash".to_string(),
+                    EngineError::InvariantViolation(_) => "invariant".to_string(),
+                    EngineError::InvalidAssumption(_) => "assumption".to_string(),
+                    EngineError::ToolTimeout(_) => "timeout".to_string(),
+                    EngineError::NotSupportedYet(reason) => format!("unsupported: {}", reason),
+                };
+                Self::Failed(category)
+            }
+        }
+    }
+}
+
+impl Display for DepthOutcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rounds(n) => write!(f, "{} rounds", n),
+            Self::Failed(category) => write!(f, "failed [{}]", category),
+        }
+    }
+}
+
+/// Outcomes of a test case across the depth range
+#[derive(Serialize, Deserialize)]
+pub struct SweepRecord {
+    /// outcome per depth
+    outcomes: BTreeMap<usize, DepthOutcome>,
+    /// smallest depth at which a fixedpoint is reached
+    stabilized_at: Option<usize>,
+}
+
+impl SweepRecord {
+    pub fn new(outcomes: BTreeMap<usize, DepthOutcome>) -> Self {
+        // at depth N, the trace holds N + 1 modules if the limit is hit before a fixedpoint
+        let stabilized_at = outcomes
+            .iter()
+            .find(|(depth, outcome)| matches!(outcome, DepthOutcome::Rounds(n) if n <= *depth))
+            .map(|(depth, _)| *depth);
+        Self {
+            outcomes,
+            stabilized_at,
+        }
+    }
+
+    /// Outcome at the depth, if the test case was run at it
+    pub fn outcome(&self, depth: usize) -> Option<&DepthOutcome> {
+        self.outcomes.get(&depth)
+    }
+
+    /// Smallest depth at which a fixedpoint is reached
+    pub fn stabilized_at(&self) -> Option<usize> {
+        self.stabilized_at
+    }
+}
+
+/// Report where test cases stabilize and save the raw data in the workdir
+pub fn report(
+    records: &BTreeMap<String, SweepRecord>,
+    depths: &DepthRange,
+    workdir: &Path,
+) -> Result<()> {
+    // distribution of stabilization depths
+    let mut histogram = BTreeMap::new();
+    let mut unstable = 0;
+    for record in records.values() {
+        match record.stabilized_at {
+            None => unstable += 1,
+            Some(depth) => *histogram.entry(depth).or_insert(0_usize) += 1,
+        }
+    }
+
+    let total = records.len();
+    let mut cumulative = 0;
+    println!("test cases swept: {}", total);
+    for depth in depths.clone() {
+        let num = histogram.get(&depth).copied().unwrap_or(0);
+        cumulative += num;
+        println!(
+            "  depth {:>3}: {} stabilized, {} cumulative ({:.2}%)",
+            depth,
+            num,
+            cumulative,
+            if total == 0 {
+                0.0
+            } else {
+                cumulative as f64 * 100.0 / total as f64
+            }
+        );
+    }
+    println!("  not stabilized: {}", unstable);
+
+    // save the raw data
+    let path_json = workdir.join("sweep.json");
+    fs::write(&path_json, serde_json::to_string_pretty(records)?)?;
+
+    let mut content = String::from("test,stabilized_at");
+    for depth in depths.clone() {
+        content.push_str(&format!(",depth_{}", depth));
+    }
+    content.push('\n');
+    for (name, record) in records {
+        content.push_str(&format!(
+            "\"{}\",{}",
+            name.replace('"', "\"\""),
+            record
+                .stabilized_at
+                .map_or_else(String::new, |d| d.to_string())
+        ));
+        for depth in depths.clone() {
+            let cell = match record.outcomes.get(&depth) {
+                None => String::new(),
+                Some(DepthOutcome::Rounds(n)) => n.to_string(),
+                Some(DepthOutcome::Failed(category)) => format!("\"{}\"", category),
+            };
+            content.push(',');
+            content.push_str(&cell);
+        }
+        content.push('\n');
+    }
+    let path_csv = workdir.join("sweep.csv");
+    fs::write(&path_csv, content)?;
+
+    info!(
+        "Depth sweep saved at: {} and {}",
+        path_json.to_string_lossy(),
+        path_csv.to_string_lossy()
+    );
+    Ok(())
+}
@@ -10,6 +10,14 @@ namespace {
 cl::opt<std::string> OptOutput("libra-output",
                                cl::desc("The output file name"));
 
+/// Format of the output
+enum class OutputFormat { JSON, CBOR };
+cl::opt<OutputFormat> OptOutputFormat(
+    "libra-output-format", cl::desc("The output format"),
+    cl::values(clEnumValN(OutputFormat::JSON, "json", "JSON text"),
+               clEnumValN(OutputFormat::CBOR, "cbor", "CBOR binary")),
+    cl::init(OutputFormat::JSON));
+
 constexpr const char *PASS_NAME = "Libra";
 
 struct LibraPass : PassInfoMixin<LibraPass> {
@@ -41,7 +49,15 @@ struct LibraPass : PassInfoMixin<LibraPass> {
     if (ec) {
       LOG->fatal("unable to create output file: {0}", OptOutput);
     }
-    stm << formatv("{0:2}", json::Value(std::move(data)));
+    json::Value value(std::move(data));
+    switch (OptOutputFormat) {
+    case OutputFormat::JSON:
+      stm << formatv("{0:2}", value);
+      break;
+    case OutputFormat::CBOR:
+      write_cbor(stm, value);
+      break;
+    }
     stm.close();
 
     // end of execution
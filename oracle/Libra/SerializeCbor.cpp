@@ -0,0 +1,91 @@
+#include "Serializer.h"
+
+#include <cstring>
+
+namespace libra {
+
+namespace {
+
+/// Write the unsigned integer in big-endian with the number of bytes
+void write_big_endian(raw_ostream &stm, uint64_t val, unsigned bytes) {
+  for (unsigned i = bytes; i > 0; i--) {
+    stm << static_cast<char>((val >> ((i - 1) * 8)) & 0xff);
+  }
+}
+
+/// Write the head of a data item, i.e., its major type and argument
+void write_head(raw_ostream &stm, uint8_t major, uint64_t arg) {
+  uint8_t initial = major << 5;
+  if (arg < 24) {
+    stm << static_cast<char>(initial | arg);
+  } else if (arg <= UINT8_MAX) {
+    stm << static_cast<char>(initial | 24);
+    write_big_endian(stm, arg, 1);
+  } else if (arg <= UINT16_MAX) {
+    stm << static_cast<char>(initial | 25);
+    write_big_endian(stm, arg, 2);
+  } else if (arg <= UINT32_MAX) {
+    stm << static_cast<char>(initial | 26);
+    write_big_endian(stm, arg, 4);
+  } else {
+    stm << static_cast<char>(initial | 27);
+    write_big_endian(stm, arg, 8);
+  }
+}
+
+void write_string(raw_ostream &stm, StringRef text) {
+  write_head(stm, 3, text.size());
+  stm << text;
+}
+
+} // namespace
+
+void write_cbor(raw_ostream &stm, const json::Value &value) {
+  switch (value.kind()) {
+  case json::Value::Null:
+    stm << static_cast<char>(0xf6);
+    break;
+  case json::Value::Boolean:
+    stm << static_cast<char>(*value.getAsBoolean() ? 0xf5 : 0xf4);
+    break;
+  case json::Value::Number:
+    if (auto signed_val = value.getAsInteger()) {
+      if (*signed_val >= 0) {
+        write_head(stm, 0, static_cast<uint64_t>(*signed_val));
+      } else {
+        write_head(stm, 1, static_cast<uint64_t>(-1 - *signed_val));
+      }
+    } else if (auto unsigned_val = value.getAsUINT64()) {
+      write_head(stm, 0, *unsigned_val);
+    } else {
+      double real = *value.getAsNumber();
+      uint64_t bits;
+      std::memcpy(&bits, &real, sizeof(bits));
+      stm << static_cast<char>(0xfb);
+      write_big_endian(stm, bits, 8);
+    }
+    break;
+  case json::Value::String:
+    write_string(stm, *value.getAsString());
+    break;
+  case json::Value::Array: {
+    const auto *items = value.getAsArray();
+    write_head(stm, 4, items->size());
+    for (const auto &item : *items) {
+      write_cbor(stm, item);
+    }
+    break;
+  }
+  case json::Value::Object: {
+    const auto *fields = value.getAsObject();
+    write_head(stm, 5, fields->size());
+    for (const auto &[key, item] : *fields) {
+      write_string(stm, key);
+      write_cbor(stm, item);
+    }
+    break;
+  }
+  }
+}
+
+} // namespace libra
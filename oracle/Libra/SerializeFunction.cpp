@@ -24,6 +24,25 @@ json::Object serialize_function(const Function &func) {
   result["is_defined"] = !func.isDeclaration();
   result["is_exact"] = func.isDefinitionExact();
   result["is_intrinsic"] = is_intrinsic_function(func);
+  result["calling_conv"] = func.getCallingConv();
+  result["linkage"] = serialize_linkage(func.getLinkage());
+
+  // function attributes
+  json::Array attrs;
+  if (func.doesNotReturn()) {
+    attrs.push_back("noreturn");
+  }
+  if (func.doesNotAccessMemory()) {
+    attrs.push_back("readnone");
+  } else if (func.onlyReadsMemory()) {
+    attrs.push_back("readonly");
+  }
+  if (func.doesNotThrow()) {
+    attrs.push_back("nounwind");
+  }
+  result["attrs"] = std::move(attrs);
+  result["ret_attrs"] =
+      serialize_value_attrs(func.getAttributes().getRetAttrs());
   // TODO: additional attributes or metadata?
 
   // parameters
@@ -76,6 +95,28 @@ json::Object serialize_parameter(const Argument &param) {
         *param.getAttribute(Attribute::AttrKind::ElementType).getValueAsType());
   }
 
+  // pointer-related attributes
+  result["attrs"] = serialize_value_attrs(
+      param.getParent()->getAttributes().getParamAttrs(param.getArgNo()));
+
+  return result;
+}
+
+json::Object serialize_value_attrs(const AttributeSet &attrs) {
+  json::Object result;
+
+  result["no_alias"] = attrs.hasAttribute(Attribute::AttrKind::NoAlias);
+  result["non_null"] = attrs.hasAttribute(Attribute::AttrKind::NonNull);
+  if (const auto align = attrs.getAlignment()) {
+    result["align"] = align->value();
+  }
+  if (const auto *ty = attrs.getByValType()) {
+    result["by_val"] = serialize_type(*ty);
+  }
+  if (const auto *ty = attrs.getStructRetType()) {
+    result["struct_ret"] = serialize_type(*ty);
+  }
+
   return result;
 }
 
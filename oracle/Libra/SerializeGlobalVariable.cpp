@@ -2,6 +2,47 @@
 
 namespace libra {
 
+const char *serialize_linkage(GlobalValue::LinkageTypes linkage) {
+  switch (linkage) {
+  case GlobalValue::ExternalLinkage:
+    return "external";
+  case GlobalValue::AvailableExternallyLinkage:
+    return "available_externally";
+  case GlobalValue::LinkOnceAnyLinkage:
+    return "link_once_any";
+  case GlobalValue::LinkOnceODRLinkage:
+    return "link_once_odr";
+  case GlobalValue::WeakAnyLinkage:
+    return "weak_any";
+  case GlobalValue::WeakODRLinkage:
+    return "weak_odr";
+  case GlobalValue::AppendingLinkage:
+    return "appending";
+  case GlobalValue::InternalLinkage:
+    return "internal";
+  case GlobalValue::PrivateLinkage:
+    return "private";
+  case GlobalValue::ExternalWeakLinkage:
+    return "external_weak";
+  case GlobalValue::CommonLinkage:
+    return "common";
+  }
+  LOG->fatal("unknown linkage type");
+}
+
+static const char *
+serialize_visibility(GlobalValue::VisibilityTypes visibility) {
+  switch (visibility) {
+  case GlobalValue::DefaultVisibility:
+    return "default";
+  case GlobalValue::HiddenVisibility:
+    return "hidden";
+  case GlobalValue::ProtectedVisibility:
+    return "protected";
+  }
+  LOG->fatal("unknown visibility type");
+}
+
 json::Object serialize_global_variable(const GlobalVariable &gvar) {
   json::Object result;
 
@@ -19,6 +60,11 @@ json::Object serialize_global_variable(const GlobalVariable &gvar) {
   result["is_const"] = gvar.isConstant();
   result["is_thread_local"] = gvar.isThreadLocal();
   result["address_space"] = gvar.getAddressSpace();
+  result["linkage"] = serialize_linkage(gvar.getLinkage());
+  result["visibility"] = serialize_visibility(gvar.getVisibility());
+  if (gvar.hasSection()) {
+    result["section"] = gvar.getSection();
+  }
   // TODO: additional attributes or metadata?
 
   // initializer
@@ -28,6 +28,13 @@ json::Object FunctionSerializationContext::serialize_instruction(
     result["name"] = inst.getName();
   }
   result["repr"] = serialize_inst(inst);
+  if (const DILocation *loc = inst.getDebugLoc().get()) {
+    json::Object debug;
+    debug["file"] = loc->getFilename();
+    debug["line"] = loc->getLine();
+    debug["column"] = loc->getColumn();
+    result["debug"] = std::move(debug);
+  }
   return result;
 }
 
@@ -185,6 +192,7 @@ json::Object FunctionSerializationContext::serialize_inst_alloca(
   if (inst.isArrayAllocation()) {
     result["size"] = serialize_value(*inst.getArraySize());
   }
+  result["align"] = inst.getAlign().value();
   result["address_space"] = inst.getAddressSpace();
   return result;
 }
@@ -194,6 +202,8 @@ FunctionSerializationContext::serialize_inst_load(const LoadInst &inst) const {
   json::Object result;
   result["pointee_type"] = serialize_type(*inst.getType());
   result["pointer"] = serialize_value(*inst.getPointerOperand());
+  result["align"] = inst.getAlign().value();
+  result["is_volatile"] = inst.isVolatile();
   result["ordering"] = toIRString(inst.getOrdering());
   result["address_space"] = inst.getPointerAddressSpace();
   return result;
@@ -205,6 +215,8 @@ json::Object FunctionSerializationContext::serialize_inst_store(
   result["pointee_type"] = serialize_type(*inst.getValueOperand()->getType());
   result["pointer"] = serialize_value(*inst.getPointerOperand());
   result["value"] = serialize_value(*inst.getValueOperand());
+  result["align"] = inst.getAlign().value();
+  result["is_volatile"] = inst.isVolatile();
   result["ordering"] = toIRString(inst.getOrdering());
   result["address_space"] = inst.getPointerAddressSpace();
   return result;
@@ -238,12 +250,17 @@ FunctionSerializationContext::serialize_inst_call_direct(
   json::Object result;
   result["callee"] = serialize_value(*inst.getCalledOperand());
   result["target_type"] = serialize_type(*inst.getFunctionType());
+  result["calling_conv"] = inst.getCallingConv();
 
   json::Array args;
+  json::Array arg_attrs;
   for (const auto &arg : inst.args()) {
     args.push_back(serialize_value(*arg.get()));
+    arg_attrs.push_back(serialize_value_attrs(
+        inst.getAttributes().getParamAttrs(inst.getArgOperandNo(&arg))));
   }
   result["args"] = std::move(args);
+  result["arg_attrs"] = std::move(arg_attrs);
   return result;
 }
 
@@ -253,12 +270,17 @@ FunctionSerializationContext::serialize_inst_call_indirect(
   json::Object result;
   result["callee"] = serialize_value(*inst.getCalledOperand());
   result["target_type"] = serialize_type(*inst.getFunctionType());
+  result["calling_conv"] = inst.getCallingConv();
 
   json::Array args;
+  json::Array arg_attrs;
   for (const auto &arg : inst.args()) {
     args.push_back(serialize_value(*arg.get()));
+    arg_attrs.push_back(serialize_value_attrs(
+        inst.getAttributes().getParamAttrs(inst.getArgOperandNo(&arg))));
   }
   result["args"] = std::move(args);
+  result["arg_attrs"] = std::move(arg_attrs);
   return result;
 }
 
@@ -268,12 +290,17 @@ FunctionSerializationContext::serialize_inst_call_intrinsic(
   json::Object result;
   result["callee"] = serialize_value(*inst.getCalledOperand());
   result["target_type"] = serialize_type(*inst.getFunctionType());
+  result["calling_conv"] = inst.getCallingConv();
 
   json::Array args;
+  json::Array arg_attrs;
   for (const auto &arg : inst.args()) {
     args.push_back(serialize_value(*arg.get()));
+    arg_attrs.push_back(serialize_value_attrs(
+        inst.getAttributes().getParamAttrs(inst.getArgOperandNo(&arg))));
   }
   result["args"] = std::move(args);
+  result["arg_attrs"] = std::move(arg_attrs);
   return result;
 }
 
@@ -378,7 +405,14 @@ FunctionSerializationContext::serialize_inst_binary_operator(
     LOG->fatal("unexpected end of binary ops");
   }
   }
-  // TODO: flags (NSW, NUW, Exact)? maybe not needed?
+  // wrapping flags are only defined on add, sub, mul, and shl
+  if (isa<OverflowingBinaryOperator>(inst)) {
+    result["no_signed_wrap"] = inst.hasNoSignedWrap();
+    result["no_unsigned_wrap"] = inst.hasNoUnsignedWrap();
+  } else {
+    result["no_signed_wrap"] = false;
+    result["no_unsigned_wrap"] = false;
+  }
   result["lhs"] = serialize_value(*inst.getOperand(0));
   result["rhs"] = serialize_value(*inst.getOperand(1));
   return result;
@@ -950,12 +984,17 @@ json::Object FunctionSerializationContext::serialize_inst_invoke_direct(
   json::Object result;
   result["callee"] = serialize_value(*inst.getCalledOperand());
   result["target_type"] = serialize_type(*inst.getFunctionType());
+  result["calling_conv"] = inst.getCallingConv();
 
   json::Array args;
+  json::Array arg_attrs;
   for (const auto &arg : inst.args()) {
     args.push_back(serialize_value(*arg.get()));
+    arg_attrs.push_back(serialize_value_attrs(
+        inst.getAttributes().getParamAttrs(inst.getArgOperandNo(&arg))));
   }
   result["args"] = std::move(args);
+  result["arg_attrs"] = std::move(arg_attrs);
 
   result["normal"] = get_block(*inst.getNormalDest());
   result["unwind"] = get_block(*inst.getUnwindDest());
@@ -967,12 +1006,17 @@ json::Object FunctionSerializationContext::serialize_inst_invoke_indirect(
   json::Object result;
   result["callee"] = serialize_value(*inst.getCalledOperand());
   result["target_type"] = serialize_type(*inst.getFunctionType());
+  result["calling_conv"] = inst.getCallingConv();
 
   json::Array args;
+  json::Array arg_attrs;
   for (const auto &arg : inst.args()) {
     args.push_back(serialize_value(*arg.get()));
+    arg_attrs.push_back(serialize_value_attrs(
+        inst.getAttributes().getParamAttrs(inst.getArgOperandNo(&arg))));
   }
   result["args"] = std::move(args);
+  result["arg_attrs"] = std::move(arg_attrs);
 
   result["normal"] = get_block(*inst.getNormalDest());
   result["unwind"] = get_block(*inst.getUnwindDest());
@@ -8,6 +8,7 @@ json::Object serialize_module(const Module &module) {
   // module level info
   result["name"] = module.getModuleIdentifier();
   result["asm"] = module.getModuleInlineAsm();
+  result["data_layout"] = module.getDataLayoutStr();
 
   // user-defined struct types
   json::Array structs;
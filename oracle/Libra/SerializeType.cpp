@@ -107,6 +107,7 @@ json::Object serialize_type_struct(const StructType &type) {
   if (type.hasName()) {
     result["name"] = type.getName();
   }
+  result["packed"] = type.isPacked();
 
   // collect fields only when non-opaque
   if (!type.isOpaque()) {
@@ -15,6 +15,9 @@ void prepare_for_serialization(Module &module);
 
 [[nodiscard]] json::Object serialize_module(const Module &module);
 
+// compact binary encoding (CBOR) of the serialized data
+void write_cbor(raw_ostream &stm, const json::Value &value);
+
 [[nodiscard]] json::Object serialize_type(const Type &type);
 [[nodiscard]] json::Object serialize_type_int(const IntegerType &type);
 [[nodiscard]] json::Object serialize_type_array(const ArrayType &type);
@@ -50,11 +53,14 @@ serialize_const_ref_interface(const GlobalIFunc &val);
 [[nodiscard]] json::Object serialize_block_address(const BlockAddress &addr);
 [[nodiscard]] json::Object serialize_const_expr(const ConstantExpr &expr);
 
+[[nodiscard]] const char *
+serialize_linkage(GlobalValue::LinkageTypes linkage);
 [[nodiscard]] json::Object
 serialize_global_variable(const GlobalVariable &gvar);
 
 [[nodiscard]] json::Object serialize_function(const Function &func);
 [[nodiscard]] json::Object serialize_parameter(const Argument &param);
+[[nodiscard]] json::Object serialize_value_attrs(const AttributeSet &attrs);
 
 [[nodiscard]] json::Object serialize_inline_asm(const InlineAsm &assembly);
 
@@ -0,0 +1,43 @@
+#ifndef LIBRA_ASSUME_H
+#define LIBRA_ASSUME_H
+
+/*
+ * Specifications for LIBRA, written in C source code.
+ *
+ * When compiled by LIBRA (which defines __LIBRA__), each macro becomes a call
+ * to a reserved function that the engine turns into a fact for its analyses:
+ * - LIBRA_ASSUME(c):    c holds here, paths where it does not are discarded
+ * - LIBRA_ASSERT(c):    c must hold here, violations are reported
+ * - LIBRA_INVARIANT(c): c holds at this point on every iteration of a loop
+ *
+ * In any other compilation, the macros expand to nothing and the conditions
+ * are not evaluated.
+ */
+
+#ifdef __LIBRA__
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+void __libra_assume(int cond);
+void __libra_assert(int cond);
+void __libra_invariant(int cond);
+
+#ifdef __cplusplus
+}
+#endif
+
+#define LIBRA_ASSUME(c) __libra_assume(!!(c))
+#define LIBRA_ASSERT(c) __libra_assert(!!(c))
+#define LIBRA_INVARIANT(c) __libra_invariant(!!(c))
+
+#else
+
+#define LIBRA_ASSUME(c) ((void)0)
+#define LIBRA_ASSERT(c) ((void)0)
+#define LIBRA_INVARIANT(c) ((void)0)
+
+#endif
+
+#endif /* LIBRA_ASSUME_H */